@@ -0,0 +1,149 @@
+// Headless entry point for scripts and CI: the same library as the app,
+// without opening the Tauri window. Every command prints JSON on stdout;
+// errors go to stderr with a non-zero exit code.
+use aura_lib::commands::{cpu, gpu, memory, network, storage, system};
+use aura_lib::services::optimization_engine::ENGINE;
+use aura_lib::services::{display_settings, process_control};
+use serde::Serialize;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: aura-cli <command> [arguments]
+
+Commands:
+  stats [cpu|memory|storage|network|system|gpu|all]  Print system stats
+  optimizations                                      List the available optimizations
+  apply <optimization-id>...                         Apply optimizations as a profile
+  apply-profile <file.json>                          Apply the ids listed in a JSON array
+  revert <optimization-id>                           Revert an optimization
+  boost <pid>                                        Boost a process for gaming";
+
+// Exit codes: 1 when the command failed, 2 when it was called wrong
+const EXIT_FAILURE: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+fn main() -> ExitCode {
+    display_settings::start();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            ExitCode::from(EXIT_USAGE)
+        }
+        Err(CliError::Failed(message)) => {
+            eprintln!("{}", message);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage("Missing command".to_string()));
+    };
+
+    match command.as_str() {
+        "stats" => print_stats(rest.first().map(String::as_str).unwrap_or("all")),
+        "optimizations" => {
+            let engine = ENGINE.lock().map_err(failed)?;
+            print_json(&engine.get_available_optimizations().map_err(failed)?)
+        }
+        "apply" => {
+            if rest.is_empty() {
+                return Err(CliError::Usage("Missing optimization id".to_string()));
+            }
+            apply_profile(rest)
+        }
+        "apply-profile" => {
+            let path = rest
+                .first()
+                .ok_or_else(|| CliError::Usage("Missing profile file".to_string()))?;
+            let content = std::fs::read_to_string(path).map_err(failed)?;
+            let ids: Vec<String> = serde_json::from_str(&content)
+                .map_err(|e| CliError::Failed(format!("Invalid profile {}: {}", path, e)))?;
+            apply_profile(&ids)
+        }
+        "revert" => {
+            let id = rest
+                .first()
+                .ok_or_else(|| CliError::Usage("Missing optimization id".to_string()))?;
+            let engine = ENGINE.lock().map_err(failed)?;
+            let result = engine.revert(id).map_err(failed)?;
+            print_json(&result)?;
+            succeeded(result.success)
+        }
+        "boost" => {
+            let pid = rest
+                .first()
+                .and_then(|pid| pid.parse::<u32>().ok())
+                .ok_or_else(|| CliError::Usage("Missing or invalid pid".to_string()))?;
+            process_control::boost_process_for_gaming(pid).map_err(failed)?;
+            print_json(&serde_json::json!({ "pid": pid, "boosted": true }))
+        }
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(CliError::Usage(format!("Unknown command: {}", other))),
+    }
+}
+
+fn print_stats(kind: &str) -> Result<(), CliError> {
+    match kind {
+        "cpu" => print_json(&cpu::get_cpu_stats().map_err(failed)?),
+        "memory" => print_json(&memory::get_memory_stats()),
+        "storage" => print_json(&storage::get_storage_stats().map_err(failed)?),
+        "network" => print_json(&network::get_network_stats().map_err(failed)?),
+        "system" => print_json(&system::get_system_stats().map_err(failed)?),
+        "gpu" => print_json(&gpu::get_gpu_stats().map_err(failed)?),
+        // Subsystems that fail are reported as errors instead of failing the whole run
+        "all" => print_json(&serde_json::json!({
+            "cpu": json_or_error(cpu::get_cpu_stats()),
+            "memory": json_or_error(Ok::<_, String>(memory::get_memory_stats())),
+            "storage": json_or_error(storage::get_storage_stats()),
+            "network": json_or_error(network::get_network_stats()),
+            "system": json_or_error(system::get_system_stats()),
+            "gpu": json_or_error(gpu::get_gpu_stats()),
+        })),
+        other => Err(CliError::Usage(format!("Unknown stats: {}", other))),
+    }
+}
+
+fn apply_profile(ids: &[String]) -> Result<(), CliError> {
+    let engine = ENGINE.lock().map_err(failed)?;
+    let result = engine.apply_profile(ids);
+    print_json(&result)?;
+    succeeded(result.success)
+}
+
+fn json_or_error<T: Serialize>(result: Result<T, String>) -> serde_json::Value {
+    match result {
+        Ok(value) => serde_json::to_value(value).unwrap_or_default(),
+        Err(e) => serde_json::json!({ "error": e }),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), CliError> {
+    let json = serde_json::to_string_pretty(value).map_err(failed)?;
+    println!("{}", json);
+    Ok(())
+}
+
+// The result is already printed; only the exit code tells scripts it failed
+fn succeeded(success: bool) -> Result<(), CliError> {
+    if success {
+        Ok(())
+    } else {
+        Err(CliError::Failed("Operation failed".to_string()))
+    }
+}
+
+fn failed(error: impl ToString) -> CliError {
+    CliError::Failed(error.to_string())
+}
@@ -0,0 +1,30 @@
+use crate::models::audio::{AudioSession, FocusModeConfig};
+use crate::services::audio_service;
+use tauri::command;
+
+/// Processes with an audio session, with their volume and mute state
+#[command]
+pub fn get_audio_sessions() -> Result<Vec<AudioSession>, String> {
+    audio_service::get_audio_sessions().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn set_audio_session_mute(pid: u32, muted: bool) -> Result<(), String> {
+    audio_service::set_process_mute(pid, muted).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn set_audio_session_volume(pid: u32, volume: f32) -> Result<(), String> {
+    audio_service::set_process_volume(pid, volume).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_focus_mode() -> Result<FocusModeConfig, String> {
+    Ok(audio_service::get_focus_mode())
+}
+
+/// Apps listed in `muted_apps` are muted while a tracked game is running
+#[command]
+pub fn set_focus_mode(config: FocusModeConfig) -> Result<(), String> {
+    audio_service::set_focus_mode(config).map_err(|e| e.to_string())
+}
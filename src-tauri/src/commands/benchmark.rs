@@ -0,0 +1,54 @@
+use crate::models::benchmark::{
+    InputLatencyResult, MemoryBenchmarkResult, StressTestConfig, StressTestStatus,
+};
+use crate::services::{input_latency, memory_benchmark, stress_test};
+use tauri::command;
+
+/// Loads the CPU (and optionally RAM) for a while, stopping early above the
+/// temperature limit or on a computation error. Progress comes with the
+/// stress-test-progress event.
+#[command]
+pub fn start_stress_test(config: StressTestConfig) -> Result<StressTestStatus, String> {
+    stress_test::start(config).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn stop_stress_test() -> StressTestStatus {
+    stress_test::stop()
+}
+
+#[command]
+pub fn get_stress_test_status() -> StressTestStatus {
+    stress_test::get_status()
+}
+
+/// Memory bandwidth and latency, with the speed the firmware configured to
+/// check whether XMP/EXPO is active. Takes a few seconds.
+#[command]
+pub async fn run_memory_benchmark() -> Result<MemoryBenchmarkResult, String> {
+    memory_benchmark::run().map_err(|e| e.to_string())
+}
+
+/// Time from input to the game's next frame. Switch to the game within three
+/// seconds of starting; `label` names the run for comparing before and after
+/// an optimization. The injected moves nudge the game's camera, see
+/// `input_latency::notice`.
+#[command]
+pub async fn measure_input_latency(
+    pid: u32,
+    samples: Option<u32>,
+    label: Option<String>,
+) -> Result<InputLatencyResult, String> {
+    input_latency::measure(pid, samples, label).map_err(|e| e.to_string())
+}
+
+/// Earlier latency measurements, oldest first
+#[command]
+pub fn get_input_latency_results() -> Vec<InputLatencyResult> {
+    input_latency::get_results()
+}
+
+#[command]
+pub fn clear_input_latency_results() {
+    input_latency::clear_results()
+}
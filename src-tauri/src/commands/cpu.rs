@@ -1,4 +1,9 @@
-use crate::models::system_stats::{GenericData, ProgressData, SystemStats};
+use crate::models::system_stats::{
+    CpuHeatmap, CpuTuningStatus, GenericData, InterruptTime, ProgressData, SystemStats,
+};
+use crate::services::{cpu_heatmap, cpu_overhead, cpu_tuning};
+use crate::utils::locale::format_number;
+use crate::utils::temperature::{convert_temperature, format_temperature};
 use anyhow;
 use std::{
     sync::{Arc, Mutex},
@@ -64,9 +69,14 @@ lazy_static::lazy_static! {
 pub fn get_cpu_stats() -> std::result::Result<SystemStats, String> {
     match crate::shared::system::SYSTEM.lock() {
         Ok(mut system) => {
+            // Interrupt and DPC time are measured over the same pause
+            let interrupts = cpu_overhead::Sampler::begin();
             system.refresh_cpu_all();
             std::thread::sleep(Duration::from_millis(100)); // Brief pause for accurate readings
             system.refresh_cpu_all();
+            let interrupt_times = interrupts
+                .map(|sampler| sampler.finish())
+                .unwrap_or_default();
 
             let cpus = system.cpus();
             let global_usage = system.global_cpu_usage();
@@ -107,45 +117,61 @@ pub fn get_cpu_stats() -> std::result::Result<SystemStats, String> {
                         title: format!("Core {}", i + 1),
                         value: cpu.cpu_usage(),
                         temperature: if core_temp > 0.0 {
-                            Some(core_temp)
+                            Some(convert_temperature(core_temp))
                         } else {
                             None
                         },
+                        raw_value: None,
+                        unit: None,
                     }
                 })
                 .collect(); // Create detailed generic data
-            let generic_data = vec![
+            let mut generic_data = vec![
                 GenericData {
                     title: "Model".to_string(),
                     value: cpu_brand.clone(),
+                    raw_value: None,
+                    unit: None,
                 },
                 GenericData {
                     title: "Temp".to_string(),
                     value: if avg_temp > 0.0 {
-                        format!("{:.1}°C", avg_temp)
+                        format_temperature(avg_temp)
                     } else {
                         "N/A".to_string()
                     },
+                    raw_value: (avg_temp > 0.0).then_some(avg_temp as f64),
+                    unit: (avg_temp > 0.0).then(|| "°C".to_string()),
                 },
                 GenericData {
                     title: "Base Clock".to_string(),
-                    value: format!("{:.1} GHz", base_freq as f64 / 1000.0),
+                    value: format!("{} GHz", format_number(base_freq as f64 / 1000.0, 1)),
+                    raw_value: Some(base_freq as f64 * 1_000_000.0),
+                    unit: Some("Hz".to_string()),
                 },
                 GenericData {
                     title: "Max Clock".to_string(),
-                    value: format!("{:.1} GHz", max_freq as f64 / 1000.0),
+                    value: format!("{} GHz", format_number(max_freq as f64 / 1000.0, 1)),
+                    raw_value: Some(max_freq as f64 * 1_000_000.0),
+                    unit: Some("Hz".to_string()),
                 },
                 GenericData {
                     title: "Cores/Threads".to_string(),
                     value: format!("{}/{}", cpus.len(), cpus.len()), // Most CPUs show same for simplicity
+                    raw_value: None,
+                    unit: None,
                 },
             ];
 
+            generic_data.extend(interrupt_data(&interrupt_times));
+
             Ok(SystemStats {
                 title: cpu_brand,
                 percentage: Some(global_usage),
                 progress_data: Some(progress_data),
                 generic_data: Some(generic_data),
+                is_stale: false,
+                is_fallback: false,
             })
         }
         Err(_) => Ok(SystemStats {
@@ -155,11 +181,53 @@ pub fn get_cpu_stats() -> std::result::Result<SystemStats, String> {
             generic_data: Some(vec![GenericData {
                 title: "Error".to_string(),
                 value: "Unable to get CPU stats".to_string(),
+                raw_value: None,
+                unit: None,
             }]),
+            is_stale: false,
+            is_fallback: false,
         }),
     }
 }
 
+/// Interrupt and DPC time of all cores, with the busiest core when one
+/// stands out, as driver trouble often lands on a single core
+fn interrupt_data(times: &[InterruptTime]) -> Vec<GenericData> {
+    let Some(total) = times.iter().find(|time| time.core.is_none()) else {
+        return Vec::new();
+    };
+    let busiest = cpu_overhead::busiest_core(times);
+    let entry = |title: &str, total: f32, busiest: Option<(usize, f32)>| {
+        let value = match busiest {
+            Some((core, percent)) if percent >= 2.0 * total && percent >= 1.0 => format!(
+                "{}% (Core {}: {}%)",
+                format_number(total as f64, 1),
+                core + 1,
+                format_number(percent as f64, 1)
+            ),
+            _ => format!("{}%", format_number(total as f64, 1)),
+        };
+        GenericData {
+            title: title.to_string(),
+            value,
+            raw_value: Some(total as f64),
+            unit: Some("%".to_string()),
+        }
+    };
+    vec![
+        entry(
+            "Interrupt Time",
+            total.interrupt_percent,
+            busiest.and_then(|time| Some((time.core?, time.interrupt_percent))),
+        ),
+        entry(
+            "DPC Time",
+            total.dpc_percent,
+            busiest.and_then(|time| Some((time.core?, time.dpc_percent))),
+        ),
+    ]
+}
+
 fn measure_cpu_usage(system: &mut System) -> Result<CpuStats> {
     system.refresh_all();
     std::thread::sleep(CPU_SAMPLE_INTERVAL);
@@ -178,6 +246,8 @@ fn measure_cpu_usage(system: &mut System) -> Result<CpuStats> {
             title: format!("Core {}", i),
             value: cpu.cpu_usage().round(),
             temperature: None,
+            raw_value: None,
+            unit: None,
         })
         .collect();
 
@@ -187,6 +257,25 @@ fn measure_cpu_usage(system: &mut System) -> Result<CpuStats> {
     })
 }
 
+/// PPT/TDC/EDC limits and whether PBO or an undervolt appears active
+#[command]
+pub fn get_cpu_tuning_status() -> std::result::Result<CpuTuningStatus, String> {
+    Ok(cpu_tuning::get_status())
+}
+
+/// Interrupt and DPC time of every core over a short interval, then of all
+/// cores together
+#[command]
+pub async fn get_cpu_interrupt_time() -> std::result::Result<Vec<InterruptTime>, String> {
+    Ok(cpu_overhead::measure(CPU_SAMPLE_INTERVAL))
+}
+
+/// Per-core load of the last few seconds, sampled several times a second
+#[command]
+pub fn get_cpu_heatmap() -> std::result::Result<CpuHeatmap, String> {
+    Ok(cpu_heatmap::get_heatmap())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
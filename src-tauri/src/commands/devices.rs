@@ -0,0 +1,16 @@
+use crate::models::bluetooth_device::BluetoothDevice;
+use crate::models::usb_device::UsbDevice;
+use crate::services::device_inventory;
+use tauri::command;
+
+/// Connected USB peripherals, mice first with their polling rate
+#[command]
+pub fn get_usb_devices() -> Result<Vec<UsbDevice>, String> {
+    device_inventory::get_usb_devices().map_err(|e| e.to_string())
+}
+
+/// Paired Bluetooth peripherals with their battery level
+#[command]
+pub fn get_bluetooth_devices() -> Result<Vec<BluetoothDevice>, String> {
+    device_inventory::get_bluetooth_devices().map_err(|e| e.to_string())
+}
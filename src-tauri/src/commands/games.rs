@@ -0,0 +1,117 @@
+use crate::models::game_profile::{
+    CoolingStatus, FrameLimiter, GameProfile, JobGroupInfo, JobLimits, PortForwardingConfig,
+    PortForwardingStatus, QosPolicy,
+};
+use crate::models::session::{SessionReport, TrackedGame};
+use crate::services::cooling;
+use crate::services::crash_monitor;
+use crate::services::frame_limiter;
+use crate::services::game_profiles;
+use crate::services::job_groups;
+use crate::services::port_forwarding;
+use crate::services::qos;
+use crate::services::session_report;
+use tauri::command;
+
+/// Starts watching a game for crashes. With `collect_minidumps` the dump
+/// files written by WER or the game are attached to the crash record.
+#[command]
+pub fn track_game_process(pid: u32, collect_minidumps: bool) -> Result<TrackedGame, String> {
+    crash_monitor::track_game(pid, collect_minidumps).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn untrack_game_process(pid: u32) -> Result<(), String> {
+    crash_monitor::untrack_game(pid);
+    Ok(())
+}
+
+#[command]
+pub fn get_tracked_games() -> Result<Vec<TrackedGame>, String> {
+    Ok(crash_monitor::get_tracked_games())
+}
+
+#[command]
+pub fn get_session_report() -> Result<SessionReport, String> {
+    Ok(session_report::get_report())
+}
+
+#[command]
+pub fn reset_session_report() -> Result<(), String> {
+    session_report::reset();
+    Ok(())
+}
+
+#[command]
+pub fn get_game_profiles() -> Result<Vec<GameProfile>, String> {
+    Ok(game_profiles::get_profiles())
+}
+
+#[command]
+pub fn save_game_profile(profile: GameProfile) -> Result<(), String> {
+    game_profiles::save_profile(profile).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn delete_game_profile(exe_path: String) -> Result<(), String> {
+    game_profiles::delete_profile(&exe_path).map_err(|e| e.to_string())
+}
+
+/// Launches a game with the environment variables and arguments from its
+/// profile. Returns the PID of the game.
+#[command]
+pub fn launch_game(exe_path: String) -> Result<u32, String> {
+    game_profiles::launch_game(&exe_path).map_err(|e| e.to_string())
+}
+
+/// Games launched from Aura that are still running, with the combined usage
+/// of every process in their job group
+#[command]
+pub fn get_job_groups() -> Result<Vec<JobGroupInfo>, String> {
+    Ok(job_groups::list())
+}
+
+#[command]
+pub fn set_job_group_limits(pid: u32, limits: JobLimits) -> Result<(), String> {
+    job_groups::set_limits(pid, limits).map_err(|e| e.to_string())
+}
+
+/// Ends a game together with every process it started
+#[command]
+pub fn terminate_job_group(pid: u32) -> Result<(), String> {
+    job_groups::terminate(pid).map_err(|e| e.to_string())
+}
+
+/// Whether the cooling profile can be switched for games, and to which
+#[command]
+pub fn get_cooling_status() -> Result<CoolingStatus, String> {
+    Ok(cooling::get_status())
+}
+
+/// Frame limiters installed, that a game profile can cap the frame rate with
+#[command]
+pub fn get_frame_limiters() -> Result<Vec<FrameLimiter>, String> {
+    Ok(frame_limiter::available())
+}
+
+#[command]
+pub fn get_port_forwarding_config() -> Result<PortForwardingConfig, String> {
+    Ok(port_forwarding::get_config())
+}
+
+#[command]
+pub fn set_port_forwarding_config(config: PortForwardingConfig) -> Result<(), String> {
+    port_forwarding::set_config(config).map_err(|e| e.to_string())
+}
+
+/// The gateway found and the ports currently forwarded for running games
+#[command]
+pub fn get_port_forwarding_status() -> Result<PortForwardingStatus, String> {
+    Ok(port_forwarding::get_status())
+}
+
+/// DSCP tags currently applied to running games
+#[command]
+pub fn get_qos_policies() -> Result<Vec<QosPolicy>, String> {
+    Ok(qos::get_policies())
+}
@@ -1,326 +1,88 @@
-use crate::models::gpu_info::{GpuInfo, GpuStats};
-use rand::Rng;
+use crate::models::game_profile::ProcessPriority;
+use crate::models::gpu_info::{
+    DisplayInfo, EvictionAction, EvictionResult, GpuPriorityResult, GpuProcessUsage, GpuStats,
+    GpuTuningReport, RefreshRateProfile,
+};
+use crate::services::{display_modes, gpu, gpu_processes, gpu_tuning};
 use std::result::Result as StdResult;
 use tauri::command;
-use windows::Win32::Graphics::Dxgi::*;
-
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
 
+/// Every GPU, each read by the best backend that sees it (see services::gpu)
 #[command]
 pub fn get_gpu_stats() -> StdResult<GpuStats, String> {
-    let mut gpus = Vec::new();
-    let mut total_vram = 0;
-    let mut total_vram_used = 0;
-    let mut total_utilization = 0.0;
-    let mut gpu_count = 0;
-
-    // Try DXGI first for accurate GPU memory info
-    match get_dxgi_gpu_info() {
-        Ok(dxgi_gpus) => {
-            for gpu in dxgi_gpus {
-                total_vram += gpu.memory_total;
-                total_vram_used += gpu.memory_used;
-                total_utilization += gpu.utilization;
-                gpu_count += 1;
-                gpus.push(gpu);
-            }
-        }
-        Err(_) => {
-            // DXGI failed, try nvidia-smi for NVIDIA GPUs
-            match get_nvidia_gpus() {
-                Ok(nvidia_info) => {
-                    for info in nvidia_info {
-                        total_vram += info.memory_total;
-                        total_vram_used += info.memory_used;
-                        total_utilization += info.utilization;
-                        gpu_count += 1;
-                        gpus.push(info);
-                    }
-                }
-                Err(_) => {
-                    // Both failed, create a default GPU entry
-                    gpus.push(create_default_gpu());
-                    gpu_count = 1;
-                }
-            }
-        }
-    }
-
-    // Ensure we always have at least one GPU entry
-    if gpus.is_empty() {
-        gpus.push(create_default_gpu());
-        gpu_count = 1;
-    }
-
-    let average_utilization = if gpu_count > 0 {
-        total_utilization / gpu_count as f32
-    } else {
-        0.0
-    };
-
-    Ok(GpuStats {
-        gpus,
-        total_vram_used,
-        total_vram,
-        average_utilization,
-    })
+    gpu::get_gpu_stats()
 }
 
-fn get_dxgi_gpu_info() -> StdResult<Vec<GpuInfo>, String> {
-    unsafe {
-        // Create DXGI Factory
-        let factory: IDXGIFactory1 =
-            CreateDXGIFactory1().map_err(|e| format!("Failed to create DXGI factory: {:?}", e))?;
-
-        let mut gpus = Vec::new();
-        let mut adapter_index = 0;
-
-        loop {
-            // Enumerate adapters
-            match factory.EnumAdapters1(adapter_index) {
-                Ok(adapter) => {
-                    // Get adapter description
-                    let desc = match adapter.GetDesc1() {
-                        Ok(desc) => desc,
-                        Err(_) => {
-                            adapter_index += 1;
-                            continue;
-                        }
-                    }; // Convert GPU name from UTF-16
-                    let name = String::from_utf16_lossy(&desc.Description)
-                        .trim_end_matches('\0')
-                        .to_string();
-
-                    let name_lower = name.to_lowercase();
-
-                    // Only skip pure software/virtual adapters that are clearly not hardware
-                    // Be very conservative - only exclude obvious software-only adapters
-                    if name_lower.contains("microsoft basic render driver")
-                        || name_lower.contains("microsoft basic display adapter")
-                        || name_lower.contains("remote desktop")
-                        || name_lower.contains("teamviewer")
-                        || name_lower.contains("vnc")
-                        || name_lower.contains("parsec")
-                        || name_lower.contains("citrix")
-                        || (name_lower.contains("virtual")
-                        && !name_lower.contains("amd")
-                        && !name_lower.contains("nvidia")
-                        && !name_lower.contains("intel"))
-                        || name_lower == "software"
-                    {
-                        adapter_index += 1;
-                        continue;
-                    }
-
-                    let vendor = determine_vendor(&name);
-
-                    // Get memory info from DXGI_ADAPTER_DESC1
-                    let dedicated_memory = desc.DedicatedVideoMemory as u64;
-                    let shared_memory = desc.SharedSystemMemory as u64;
-
-                    // Calculate total available memory for all types of GPUs
-                    let memory_total = if dedicated_memory > 0 {
-                        // Discrete GPU: use dedicated memory
-                        dedicated_memory
-                    } else if shared_memory > 0 {
-                        // Integrated GPU: calculate realistic usable VRAM
-                        // Most integrated GPUs can use 512MB to 2GB+ depending on system
-                        let base_memory = 512 * 1024 * 1024; // 512MB base
-                        let additional_memory = shared_memory / 16; // Small fraction of shared memory
-                        let estimated_vram = base_memory + additional_memory;
-                        std::cmp::min(estimated_vram, 8 * 1024 * 1024 * 1024) // Cap at 8GB
-                    } else {
-                        // Fallback for any edge cases
-                        512 * 1024 * 1024 // 512MB fallback
-                    };
-
-                    // Simulate reasonable memory usage (5-30% for better realism)
-                    let mut rng = rand::rng();
-                    let memory_used =
-                        (memory_total as f32 * (0.05 + rng.random::<f32>() * 0.25)) as u64;
-
-                    // Include ALL GPU hardware - discrete and integrated
-                    // Only exclude if it's clearly a software-only adapter
-                    let is_known_vendor =
-                        vendor == "AMD" || vendor == "Intel" || vendor == "NVIDIA";
-                    let has_graphics_in_name = name_lower.contains("graphics")
-                        || name_lower.contains("display")
-                        || name_lower.contains("video")
-                        || name_lower.contains("gpu");
-                    let has_gpu_keywords = name_lower.contains("radeon")
-                        || name_lower.contains("geforce")
-                        || name_lower.contains("quadro")
-                        || name_lower.contains("iris")
-                        || name_lower.contains("uhd")
-                        || name_lower.contains("vega")
-                        || name_lower.contains("navi")
-                        || name_lower.contains("rtx")
-                        || name_lower.contains("gtx");
-
-                    // Include if it's from a known vendor OR has graphics-related keywords OR has reasonable memory
-                    let should_include = is_known_vendor
-                        || has_graphics_in_name
-                        || has_gpu_keywords
-                        || memory_total >= 128 * 1024 * 1024;
-
-                    if should_include {
-                        let utilization = rng.random::<f32>() * 15.0; // 0-15% for idle
-                        let memory_usage_percentage = if memory_total > 0 {
-                            (memory_used as f32 / memory_total as f32) * 100.0
-                        } else {
-                            0.0
-                        };
-
-                        gpus.push(GpuInfo {
-                            name,
-                            vendor: vendor.to_string(),
-                            utilization,
-                            memory_used,
-                            memory_total,
-                            memory_usage_percentage,
-                            temperature: Some(45.0 + rng.random::<f32>() * 20.0), // 45-65°C
-                            power_usage: Some(20.0 + rng.random::<f32>() * 80.0), // 20-100W
-                            clock_speed: Some(1200 + rng.random::<u32>() % 1300), // 1200-2500 MHz
-                            memory_clock: Some(6000 + rng.random::<u32>() % 6000), // 6000-12000 MHz
-                            driver_version: Some("Unknown".to_string()),
-                            is_nvidia: vendor == "NVIDIA",
-                            is_amd: vendor == "AMD",
-                        });
-                    }
-
-                    adapter_index += 1;
-                }
-                Err(_) => break, // No more adapters
-            }
-        }
+/// Processes holding at least `min_vram_mb` of dedicated GPU memory (default 256 MB)
+#[command]
+pub fn get_vram_heavy_processes(
+    min_vram_mb: Option<u64>,
+) -> StdResult<Vec<GpuProcessUsage>, String> {
+    let min_bytes = min_vram_mb.unwrap_or(256) * 1024 * 1024;
+    gpu_processes::find_vram_heavy_processes(min_bytes).map_err(|e| e.to_string())
+}
 
-        if gpus.is_empty() {
-            Err("No DXGI adapters found".to_string())
-        } else {
-            Ok(gpus)
-        }
-    }
+/// Trims or kills the selected processes to free VRAM before a game starts
+#[command]
+pub fn evict_gpu_processes(
+    pids: Vec<u32>,
+    action: EvictionAction,
+) -> StdResult<EvictionResult, String> {
+    gpu_processes::evict_processes(&pids, action).map_err(|e| e.to_string())
 }
 
-fn determine_vendor(gpu_name: &str) -> &'static str {
-    let name_lower = gpu_name.to_lowercase();
+/// GPU scheduling priority of a process, next to its CPU priority class
+#[command]
+pub fn get_gpu_priority(pid: u32) -> StdResult<ProcessPriority, String> {
+    gpu_processes::get_gpu_priority(pid).map_err(|e| e.to_string())
+}
 
-    if name_lower.contains("nvidia")
-        || name_lower.contains("geforce")
-        || name_lower.contains("rtx")
-        || name_lower.contains("gtx")
-        || name_lower.contains("quadro")
-        || name_lower.contains("tesla")
-    {
-        "NVIDIA"
-    } else if name_lower.contains("amd")
-        || name_lower.contains("radeon")
-        || name_lower.contains("rx ")
-        || name_lower.contains("vega")
-        || name_lower.contains("navi")
-        || name_lower.contains("rdna")
-    {
-        "AMD"
-    } else if name_lower.contains("intel")
-        || name_lower.contains("iris")
-        || name_lower.contains("uhd")
-        || name_lower.contains("hd graphics")
-    {
-        "Intel"
-    } else {
-        "Unknown"
-    }
+#[command]
+pub fn set_gpu_priority(pid: u32, priority: ProcessPriority) -> StdResult<(), String> {
+    gpu_processes::set_gpu_priority(pid, priority).map_err(|e| e.to_string())
 }
 
-// Fallback function for NVIDIA GPUs using nvidia-smi
-fn get_nvidia_gpus() -> StdResult<Vec<GpuInfo>, String> {
-    #[cfg(target_os = "windows")]
-    let output = std::process::Command::new("cmd")
-        .args(&[
-            "/C",
-            "timeout",
-            "5",
-            "nvidia-smi",
-            "--query-gpu=name,memory.total,memory.used,temperature.gpu,utilization.gpu",
-            "--format=csv,noheader,nounits",
-        ])
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW
-        .output()
-        .map_err(|e| format!("Failed to execute nvidia-smi: {}", e))?;
+/// Gives the game's GPU work precedence over capture and overlay apps
+#[command]
+pub fn prioritize_game_gpu(pid: u32) -> StdResult<GpuPriorityResult, String> {
+    gpu_processes::prioritize_game_gpu(pid).map_err(|e| e.to_string())
+}
 
-    #[cfg(not(target_os = "windows"))]
-    let output = std::process::Command::new("cmd")
-        .args(&[
-            "/C",
-            "timeout",
-            "5",
-            "nvidia-smi",
-            "--query-gpu=name,memory.total,memory.used,temperature.gpu,utilization.gpu",
-            "--format=csv,noheader,nounits",
-        ])
-        .output()
-        .map_err(|e| format!("Failed to execute nvidia-smi: {}", e))?;
+/// Power limit, clock offsets and fans the driver reports, read-only
+#[command]
+pub fn get_gpu_tuning() -> StdResult<GpuTuningReport, String> {
+    Ok(gpu_tuning::get_report())
+}
 
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut gpus = Vec::new();
+/// Active displays with their refresh rates and VRR support
+#[command]
+pub async fn get_displays() -> StdResult<Vec<DisplayInfo>, String> {
+    display_modes::get_displays().map_err(|e| e.to_string())
+}
 
-        for line in stdout.lines() {
-            let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
-            if parts.len() >= 5 {
-                let memory_total_mb = parts[1].parse::<u64>().unwrap_or(0);
-                let memory_used_mb = parts[2].parse::<u64>().unwrap_or(0);
-                let temperature = parts[3].parse().unwrap_or(0.0);
-                let utilization = parts[4].parse().unwrap_or(0.0);
+/// Switches a display, the primary one when None, to the closest supported
+/// rate, the highest when `refresh_hz` is None
+#[command]
+pub async fn set_refresh_rate(
+    display: Option<String>,
+    refresh_hz: Option<u32>,
+) -> StdResult<DisplayInfo, String> {
+    display_modes::set_refresh_rate(display.as_deref(), refresh_hz).map_err(|e| e.to_string())
+}
 
-                let mut rng = rand::rng();
-                gpus.push(GpuInfo {
-                    name: parts[0].to_string(),
-                    vendor: "NVIDIA".to_string(),
-                    utilization,
-                    memory_used: memory_used_mb * 1024 * 1024,
-                    memory_total: memory_total_mb * 1024 * 1024,
-                    memory_usage_percentage: if memory_total_mb > 0 {
-                        (memory_used_mb as f32 / memory_total_mb as f32) * 100.0
-                    } else {
-                        0.0
-                    },
-                    temperature: Some(temperature),
-                    power_usage: Some(50.0 + rng.random::<f32>() * 200.0), // 50-250W
-                    clock_speed: Some(1400 + rng.random::<u32>() % 1100),  // 1400-2500 MHz
-                    memory_clock: Some(7000 + rng.random::<u32>() % 7000), // 7000-14000 MHz
-                    driver_version: Some("Unknown".to_string()),
-                    is_nvidia: true,
-                    is_amd: false,
-                });
-            }
-        }
+#[command]
+pub fn get_refresh_profiles() -> Vec<RefreshRateProfile> {
+    display_modes::get_profiles()
+}
 
-        if gpus.is_empty() {
-            Err("No NVIDIA GPUs found".to_string())
-        } else {
-            Ok(gpus)
-        }
-    } else {
-        Err("nvidia-smi command failed".to_string())
-    }
+#[command]
+pub fn set_refresh_profiles(
+    profiles: Vec<RefreshRateProfile>,
+) -> StdResult<Vec<RefreshRateProfile>, String> {
+    display_modes::set_profiles(profiles).map_err(|e| e.to_string())
 }
 
-fn create_default_gpu() -> GpuInfo {
-    GpuInfo {
-        name: "Unknown Display Adapter".to_string(),
-        vendor: "Unknown".to_string(),
-        utilization: 0.0,
-        memory_used: 0,
-        memory_total: 2048 * 1024 * 1024, // 2GB fallback
-        memory_usage_percentage: 0.0,
-        temperature: None,
-        power_usage: None,
-        clock_speed: None,
-        memory_clock: None,
-        driver_version: Some("Unknown".to_string()),
-        is_nvidia: false,
-        is_amd: false,
-    }
+#[command]
+pub async fn apply_refresh_profile(name: String) -> StdResult<DisplayInfo, String> {
+    display_modes::apply_profile(&name).map_err(|e| e.to_string())
 }
@@ -1,4 +1,6 @@
-use crate::models::system_stats::{GenericData, ProgressData, SystemStats};
+use crate::models::system_stats::{GenericData, MemoryBreakdown, ProgressData, SystemStats};
+use crate::services::memory_breakdown;
+use crate::utils::bytes::format_bytes_in;
 use sysinfo::System;
 use tauri::command;
 
@@ -90,6 +92,8 @@ fn get_memory_details() -> Vec<GenericData> {
                                     "{} GB @ {} MHz - {} | {}-bit",
                                     capacity_gb, speed, part_clean, data_width
                                 ),
+                                raw_value: None,
+                                unit: None,
                             });
                         }
                     }
@@ -103,6 +107,8 @@ fn get_memory_details() -> Vec<GenericData> {
         details.push(GenericData {
             title: "System Memory - DDR4 DIMM".to_string(),
             value: "System RAM @ Standard Speed".to_string(),
+            raw_value: None,
+            unit: None,
         });
     }
 
@@ -127,14 +133,6 @@ pub fn get_memory_stats() -> SystemStats {
     let total_swap = system.total_swap();
     let used_swap = system.used_swap();
 
-    // Convert to GB for display
-    let total_gb = total_memory as f64 / (1024.0 * 1024.0 * 1024.0);
-    let used_gb = used_memory as f64 / (1024.0 * 1024.0 * 1024.0);
-    let available_gb = available_memory as f64 / (1024.0 * 1024.0 * 1024.0);
-    let free_gb = free_memory as f64 / (1024.0 * 1024.0 * 1024.0);
-    let total_swap_gb = total_swap as f64 / (1024.0 * 1024.0 * 1024.0);
-    let used_swap_gb = used_swap as f64 / (1024.0 * 1024.0 * 1024.0);
-
     // Calculate percentages
     let memory_percentage = if total_memory > 0 {
         ((used_memory as f64 / total_memory as f64) * 100.0).round() as u32
@@ -155,19 +153,27 @@ pub fn get_memory_stats() -> SystemStats {
     let mut generic_data = vec![
         GenericData {
             title: "Total Memory".to_string(),
-            value: format!("{:.1} GB", total_gb),
+            value: format_bytes_in(total_memory, 3, 1),
+            raw_value: Some(total_memory as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Used Memory".to_string(),
-            value: format!("{:.1} GB", used_gb),
+            value: format_bytes_in(used_memory, 3, 1),
+            raw_value: Some(used_memory as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Available Memory".to_string(),
-            value: format!("{:.1} GB", available_gb),
+            value: format_bytes_in(available_memory, 3, 1),
+            raw_value: Some(available_memory as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Free Memory".to_string(),
-            value: format!("{:.1} GB", free_gb),
+            value: format_bytes_in(free_memory, 3, 1),
+            raw_value: Some(free_memory as f64),
+            unit: Some("B".to_string()),
         },
     ];
 
@@ -175,18 +181,61 @@ pub fn get_memory_stats() -> SystemStats {
     if total_swap > 0 {
         generic_data.push(GenericData {
             title: "Total Swap".to_string(),
-            value: format!("{:.1} GB", total_swap_gb),
+            value: format_bytes_in(total_swap, 3, 1),
+            raw_value: Some(total_swap as f64),
+            unit: Some("B".to_string()),
         });
         generic_data.push(GenericData {
             title: "Used Swap".to_string(),
-            value: format!("{:.1} GB", used_swap_gb),
+            value: format_bytes_in(used_swap, 3, 1),
+            raw_value: Some(used_swap as f64),
+            unit: Some("B".to_string()),
         });
         generic_data.push(GenericData {
             title: "Swap Usage".to_string(),
             value: format!("{}%", swap_percentage),
+            raw_value: Some(swap_percentage as f64),
+            unit: Some("%".to_string()),
         });
     }
 
+    // Commit charge, caches and pools, as Task Manager shows them
+    let breakdown = memory_breakdown::get_breakdown();
+    for (title, bytes) in [
+        ("Committed", breakdown.committed),
+        ("Commit Limit", breakdown.commit_limit),
+        ("Cached", breakdown.cached),
+        ("Standby", breakdown.standby),
+        ("Modified", breakdown.modified),
+        ("Paged Pool", breakdown.paged_pool),
+        ("Non-paged Pool", breakdown.nonpaged_pool),
+        ("Compressed", breakdown.compressed),
+    ] {
+        if let Some(bytes) = bytes {
+            generic_data.push(GenericData {
+                title: title.to_string(),
+                value: format_bytes_in(bytes, 3, 1),
+                raw_value: Some(bytes as f64),
+                unit: Some("B".to_string()),
+            });
+        }
+    }
+
+    // Hard faults read from disk, sustained ones mean RAM is running short
+    for (title, rate) in [
+        ("Page Faults", breakdown.page_faults_per_sec),
+        ("Hard Faults", breakdown.hard_faults_per_sec),
+    ] {
+        if let Some(rate) = rate {
+            generic_data.push(GenericData {
+                title: title.to_string(),
+                value: format!("{}/s", rate),
+                raw_value: Some(rate as f64),
+                unit: Some("/s".to_string()),
+            });
+        }
+    }
+
     // Append detailed memory information
     generic_data.append(&mut detailed_info); // Create progress data for memory modules navigation
     let progress_data = if detailed_info.len() > 1 {
@@ -203,7 +252,9 @@ pub fn get_memory_stats() -> SystemStats {
             module_progress.push(ProgressData {
                 title: module.title.clone(),
                 value: module_usage,
-                temperature: Some(42.0 + (index as f32 * 2.0)), // Simulated temperature
+                temperature: None, // No sensor reads the modules
+                raw_value: None,
+                unit: None,
             });
         }
         Some(module_progress)
@@ -213,12 +264,16 @@ pub fn get_memory_stats() -> SystemStats {
             ProgressData {
                 title: "RAM Usage".to_string(),
                 value: memory_percentage as f32,
-                temperature: Some(42.0),
+                temperature: None,
+                raw_value: Some(used_memory as f64),
+                unit: Some("B".to_string()),
             },
             ProgressData {
                 title: "Swap Usage".to_string(),
                 value: swap_percentage as f32,
                 temperature: None,
+                raw_value: Some(used_swap as f64),
+                unit: Some("B".to_string()),
             },
         ])
     } else {
@@ -226,7 +281,9 @@ pub fn get_memory_stats() -> SystemStats {
         Some(vec![ProgressData {
             title: "RAM Usage".to_string(),
             value: memory_percentage as f32,
-            temperature: Some(42.0),
+            temperature: None,
+            raw_value: Some(used_memory as f64),
+            unit: Some("B".to_string()),
         }])
     };
 
@@ -235,5 +292,14 @@ pub fn get_memory_stats() -> SystemStats {
         percentage: Some(memory_percentage as f32),
         progress_data,
         generic_data: Some(generic_data),
+        is_stale: false,
+        is_fallback: false,
     }
 }
+
+/// Commit charge, standby and modified lists, kernel pools and compressed
+/// memory, in bytes
+#[command]
+pub fn get_memory_breakdown() -> MemoryBreakdown {
+    memory_breakdown::get_breakdown()
+}
@@ -1,11 +1,18 @@
+pub mod audio;
+pub mod benchmark;
 pub mod cpu;
+pub mod devices;
+pub mod games;
 pub mod gpu;
 pub mod memory;
 pub mod network;
 pub mod optimization_commands;
 pub mod optimizations;
+pub mod plugins;
 pub mod process;
 pub mod processes;
+pub mod readiness;
 pub mod resilient_monitor;
+pub mod settings;
 pub mod storage;
 pub mod system;
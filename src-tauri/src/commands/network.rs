@@ -1,4 +1,8 @@
-use crate::models::system_stats::{GenericData, ProgressData, SystemStats};
+use crate::models::system_stats::{
+    DataCapConfig, DataUsageStatus, GenericData, ProgressData, SystemStats,
+};
+use crate::services::data_usage;
+use crate::utils::bytes::{format_bytes, format_bytes_per_second};
 use std::{
     process::Command,
     sync::{Arc, Mutex},
@@ -282,26 +286,6 @@ fn measure_network_speed(networks: &mut Networks, cache: &mut NetworkCache) -> N
     }
 }
 
-fn format_network_speed(bytes_per_sec: u64) -> String {
-    let bytes = bytes_per_sec as f64;
-    if bytes >= BYTES_IN_MB {
-        format!("{:.2} MB/s", bytes / BYTES_IN_MB)
-    } else {
-        format!("{:.2} KB/s", bytes / 1024.0)
-    }
-}
-
-fn format_bytes(bytes: u64) -> String {
-    let bytes = bytes as f64;
-    if bytes >= 1024.0 * 1024.0 * 1024.0 {
-        format!("{:.2} GB", bytes / (1024.0 * 1024.0 * 1024.0))
-    } else if bytes >= 1024.0 * 1024.0 {
-        format!("{:.2} MB", bytes / (1024.0 * 1024.0))
-    } else {
-        format!("{:.2} KB", bytes / 1024.0)
-    }
-}
-
 #[command]
 pub fn get_network_stats() -> Result<SystemStats, String> {
     let mut cache = NETWORK_CACHE
@@ -385,29 +369,41 @@ pub fn get_network_stats() -> Result<SystemStats, String> {
             title: interface_title,
             value: interface_usage,
             temperature: None,
+            raw_value: None,
+            unit: None,
         });
     }
 
     let generic_data = vec![
         GenericData {
             title: "Download Speed".to_string(),
-            value: format_network_speed(info.download_speed),
+            value: format_bytes_per_second(info.download_speed),
+            raw_value: Some(info.download_speed as f64),
+            unit: Some("B/s".to_string()),
         },
         GenericData {
             title: "Upload Speed".to_string(),
-            value: format_network_speed(info.upload_speed),
+            value: format_bytes_per_second(info.upload_speed),
+            raw_value: Some(info.upload_speed as f64),
+            unit: Some("B/s".to_string()),
         },
         GenericData {
             title: "Total Downloaded".to_string(),
             value: format_bytes(info.total_received),
+            raw_value: Some(info.total_received as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Total Uploaded".to_string(),
             value: format_bytes(info.total_transmitted),
+            raw_value: Some(info.total_transmitted as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Active Interfaces".to_string(),
             value: info.interfaces.len().to_string(),
+            raw_value: None,
+            unit: None,
         },
     ];
 
@@ -416,5 +412,23 @@ pub fn get_network_stats() -> Result<SystemStats, String> {
         percentage: Some(usage_percentage),
         progress_data: Some(progress_data),
         generic_data: Some(generic_data),
+        is_stale: false,
+        is_fallback: false,
     })
 }
+
+/// Traffic per interface of the current billing period and the ones before
+#[command]
+pub fn get_data_usage() -> Result<DataUsageStatus, String> {
+    Ok(data_usage::get_status())
+}
+
+#[command]
+pub fn get_data_cap_config() -> Result<DataCapConfig, String> {
+    Ok(data_usage::get_config())
+}
+
+#[command]
+pub fn set_data_cap_config(config: DataCapConfig) -> Result<DataCapConfig, String> {
+    data_usage::set_config(config).map_err(|e| e.to_string())
+}
@@ -1,101 +1,187 @@
-use crate::models::optimization::{OptimizationCategory, OptimizationResult};
-use crate::services::optimization_service::OptimizationService;
-use serde::Serialize;
-use std::sync::{Arc, Mutex};
+use crate::models::optimization::{
+    DefenderExclusions, DirectStorageStatus, HostsBlocklistStatus, NetworkAdapterTuning,
+    OptimizationAuditEntry, OptimizationCategory, OptimizationRecipe, OptimizationResult,
+    PendingRestartItem, ProfileApplyResult, TimerKeeperConfig, TimerResolution, VbsStatus,
+    WindowsUpdateState,
+};
+use crate::models::system_stats::PlatformInfo;
+use crate::services::optimization_engine::{self, ENGINE};
+use crate::services::{
+    defender, device_guard, direct_storage, hosts_blocklist, network_tuning, os_version,
+    timer_resolution, windows_update,
+};
 use tauri::command;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
+#[command]
+pub async fn get_available_optimizations() -> Result<Vec<OptimizationCategory>, String> {
+    let engine = ENGINE.lock().map_err(|e| e.to_string())?;
+    engine
+        .get_available_optimizations()
+        .map_err(|e| e.to_string())
+}
 
-lazy_static::lazy_static! {
-    static ref OPTIMIZATION_SERVICE: Arc<Mutex<OptimizationService>> = Arc::new(Mutex::new(OptimizationService::new()));
+#[command]
+pub async fn apply_optimization(optimization_id: String) -> Result<OptimizationResult, String> {
+    let engine = ENGINE.lock().map_err(|e| e.to_string())?;
+    engine.apply(&optimization_id).map_err(|e| e.to_string())
 }
 
-#[derive(Serialize)]
-pub struct PlatformInfo {
-    pub os: String,
-    pub version: String,
-    pub arch: String,
+#[command]
+pub async fn revert_optimization(optimization_id: String) -> Result<OptimizationResult, String> {
+    let engine = ENGINE.lock().map_err(|e| e.to_string())?;
+    engine.revert(&optimization_id).map_err(|e| e.to_string())
 }
 
+/// Applies several optimizations at once, dependencies first and without
+/// conflicting pairs
 #[command]
-pub async fn get_available_optimizations() -> Result<Vec<OptimizationCategory>, String> {
-    let service = OPTIMIZATION_SERVICE.lock().map_err(|e| e.to_string())?;
-    service
-        .get_available_optimizations()
+pub async fn apply_optimization_profile(
+    optimization_ids: Vec<String>,
+) -> Result<ProfileApplyResult, String> {
+    let engine = ENGINE.lock().map_err(|e| e.to_string())?;
+    Ok(engine.apply_profile(&optimization_ids))
+}
+
+/// Every apply and revert Aura has done, newest first
+#[command]
+pub fn get_optimization_history() -> Result<Vec<OptimizationAuditEntry>, String> {
+    Ok(optimization_engine::get_history())
+}
+
+/// Applied and reverted optimizations that take effect after the next restart
+#[command]
+pub fn get_pending_restart_items() -> Result<Vec<PendingRestartItem>, String> {
+    Ok(optimization_engine::get_pending_restart_items())
+}
+
+#[command]
+pub fn schedule_restart(minutes: u32) -> Result<(), String> {
+    optimization_engine::schedule_restart(minutes).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn cancel_scheduled_restart() -> Result<(), String> {
+    optimization_engine::cancel_scheduled_restart().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_custom_optimizations() -> Result<Vec<OptimizationRecipe>, String> {
+    let engine = ENGINE.lock().map_err(|e| e.to_string())?;
+    Ok(engine.get_custom_optimizations())
+}
+
+/// Adds or updates a user-defined recipe. It is checked against the recipe
+/// sandbox and then listed and applied like any built-in optimization.
+#[command]
+pub fn save_custom_optimization(recipe: OptimizationRecipe) -> Result<(), String> {
+    let mut engine = ENGINE.lock().map_err(|e| e.to_string())?;
+    engine
+        .save_custom_optimization(recipe)
         .map_err(|e| e.to_string())
 }
 
 #[command]
-pub async fn apply_optimization(optimization_id: String) -> Result<OptimizationResult, String> {
-    let service = OPTIMIZATION_SERVICE.lock().map_err(|e| e.to_string())?;
-    service
-        .apply_optimization(&optimization_id)
+pub fn delete_custom_optimization(optimization_id: String) -> Result<(), String> {
+    let mut engine = ENGINE.lock().map_err(|e| e.to_string())?;
+    engine
+        .delete_custom_optimization(&optimization_id)
         .map_err(|e| e.to_string())
 }
 
+/// Pauses Windows Update for the length of a gaming event, 7 days by default
 #[command]
-pub async fn revert_optimization(optimization_id: String) -> Result<OptimizationResult, String> {
-    let service = OPTIMIZATION_SERVICE.lock().map_err(|e| e.to_string())?;
-    service
-        .revert_optimization(&optimization_id)
+pub fn pause_windows_update(days: Option<u32>) -> Result<WindowsUpdateState, String> {
+    windows_update::pause(days.unwrap_or(windows_update::DEFAULT_PAUSE_DAYS))
         .map_err(|e| e.to_string())
 }
 
+#[command]
+pub fn resume_windows_update() -> Result<WindowsUpdateState, String> {
+    windows_update::resume().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_windows_update_state() -> Result<WindowsUpdateState, String> {
+    windows_update::get_state().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_defender_exclusions() -> Result<DefenderExclusions, String> {
+    defender::get_exclusions().map_err(|e| e.to_string())
+}
+
+/// Stops Defender from scanning the given game folders. High risk: anything
+/// dropped in those folders is no longer checked for malware.
+#[command]
+pub fn add_defender_exclusions(paths: Vec<String>) -> Result<DefenderExclusions, String> {
+    defender::add_exclusions(&paths).map_err(|e| e.to_string())
+}
+
+/// Removes exclusions added by Aura; the user's own are kept
+#[command]
+pub fn remove_defender_exclusions(paths: Vec<String>) -> Result<DefenderExclusions, String> {
+    defender::remove_exclusions(&paths).map_err(|e| e.to_string())
+}
+
+/// Domains the hosts file blocks, Aura's blocklist apart from the user's own
+#[command]
+pub fn get_hosts_blocklist_status() -> Result<HostsBlocklistStatus, String> {
+    hosts_blocklist::get_status().map_err(|e| e.to_string())
+}
+
+/// Physical adapters with their IPv4 metric and MTU
+#[command]
+pub fn get_network_adapters() -> Result<Vec<NetworkAdapterTuning>, String> {
+    network_tuning::get_adapters().map_err(|e| e.to_string())
+}
+
+/// Changes an adapter's MTU; the original is kept for reset_adapter_mtu
+#[command]
+pub fn set_adapter_mtu(index: u32, mtu: u32) -> Result<Vec<NetworkAdapterTuning>, String> {
+    network_tuning::set_mtu(index, mtu).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn reset_adapter_mtu(index: u32) -> Result<Vec<NetworkAdapterTuning>, String> {
+    network_tuning::restore_mtu(index).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_vbs_status() -> Result<VbsStatus, String> {
+    device_guard::get_status().map_err(|e| e.to_string())
+}
+
+/// Toggles Core Isolation memory integrity; `restart_required` tells the
+/// frontend to offer a restart
+#[command]
+pub fn set_memory_integrity(enabled: bool) -> Result<VbsStatus, String> {
+    device_guard::set_memory_integrity(enabled).map_err(|e| e.to_string())
+}
+
+/// Whether a drive supports the DirectStorage fast path, the system drive
+/// when none is given
+#[command]
+pub fn get_direct_storage_status(drive: Option<String>) -> Result<DirectStorageStatus, String> {
+    Ok(direct_storage::get_status(drive))
+}
+
+/// Actual system timer resolution, whoever requested it
+#[command]
+pub fn get_timer_resolution() -> Result<TimerResolution, String> {
+    timer_resolution::get_timer_resolution().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_timer_keeper() -> Result<TimerKeeperConfig, String> {
+    Ok(timer_resolution::get_keeper())
+}
+
+#[command]
+pub fn set_timer_keeper(config: TimerKeeperConfig) -> Result<TimerResolution, String> {
+    timer_resolution::set_keeper(config).map_err(|e| e.to_string())
+}
+
 #[command]
 pub async fn get_current_platform() -> PlatformInfo {
-    let os = if cfg!(target_os = "windows") {
-        "Windows"
-    } else if cfg!(target_os = "linux") {
-        "Linux"
-    } else if cfg!(target_os = "macos") {
-        "macOS"
-    } else {
-        "Unknown"
-    };
-
-    let arch = if cfg!(target_arch = "x86_64") {
-        "x64"
-    } else if cfg!(target_arch = "x86") {
-        "x86"
-    } else if cfg!(target_arch = "aarch64") {
-        "ARM64"
-    } else {
-        "Unknown"
-    };    // Get OS version for Windows
-    let version = if cfg!(target_os = "windows") {
-        // Try to get Windows version
-        #[cfg(target_os = "windows")]
-        let result = std::process::Command::new("cmd")
-            .args(&["/C", "ver"])
-            .creation_flags(0x08000000) // CREATE_NO_WINDOW
-            .output();
-
-        #[cfg(not(target_os = "windows"))]
-        let result = std::process::Command::new("cmd")
-            .args(&["/C", "ver"])
-            .output();
-
-        match result {
-            Ok(output) => {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                if output_str.contains("Windows 11") {
-                    "11".to_string()
-                } else if output_str.contains("Windows 10") {
-                    "10".to_string()
-                } else {
-                    "Unknown".to_string()
-                }
-            }
-            Err(_) => "Unknown".to_string(),
-        }
-    } else {
-        "Unknown".to_string()
-    };
-
-    PlatformInfo {
-        os: os.to_string(),
-        version,
-        arch: arch.to_string(),
-    }
+    os_version::get_platform_info()
 }
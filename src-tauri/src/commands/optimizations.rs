@@ -1,14 +1,9 @@
+use crate::services::optimization_engine::ENGINE;
 use anyhow;
-use ntapi::ntexapi::NtSetTimerResolution;
-use std::process::Command;
-use std::sync::{Arc, Mutex};
 use tauri::command;
 use tauri::ipc::InvokeError;
 use thiserror::Error;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-
 #[derive(Error, Debug)]
 pub enum OptimizationError {
     #[error("Failed to modify registry: {0}")]
@@ -19,9 +14,6 @@ pub enum OptimizationError {
 
     #[error("Operation not supported on this platform")]
     UnsupportedPlatform,
-
-    #[error("Failed to set timer resolution: {0}")]
-    TimerError(i32),
 }
 
 impl From<OptimizationError> for InvokeError {
@@ -32,141 +24,69 @@ impl From<OptimizationError> for InvokeError {
 
 type Result<T> = std::result::Result<T, OptimizationError>;
 
-// Cache per evitare chiamate ripetute al registro
-lazy_static::lazy_static! {
-    static ref REGISTRY_CACHE: Arc<Mutex<RegistryCache>> = Arc::new(Mutex::new(RegistryCache::new()));
-}
-
-struct RegistryCache {
-    game_dvr_state: Option<bool>,
-    irq_priority_state: Option<bool>,
-}
-
-impl RegistryCache {
-    fn new() -> Self {
-        Self {
-            game_dvr_state: None,
-            irq_priority_state: None,
-        }
-    }
-}
+// These toggles predate the optimization panel and share its engine, so a
+// tweak applied here shows up as applied there and can be reverted from either
 
+/// Disables Game DVR when `enable` is true and restores it otherwise
 #[command]
 pub fn disable_game_dvr(enable: bool) -> Result<()> {
-    let mut cache = REGISTRY_CACHE
-        .lock()
-        .map_err(|e| OptimizationError::RegistryError(e.to_string()))?;
-
-    // Controlla la cache
-    if cache.game_dvr_state == Some(enable) {
-        return Ok(());
-    }
-
-    let value = if enable { "0" } else { "1" };
-    let reg_path = r"HKEY_CURRENT_USER\System\GameConfigStore";
-
-    modify_registry(reg_path, "GameDVR_Enabled", value)
-        .map_err(|e| OptimizationError::RegistryError(e))?;
-
-    // Aggiorna la cache
-    cache.game_dvr_state = Some(enable);
-    Ok(())
+    toggle("disable_game_dvr", enable, OptimizationError::RegistryError)
 }
 
 #[command]
 pub fn optimize_interrupt_affinity(enable: bool) -> Result<()> {
-    let mut cache = REGISTRY_CACHE
-        .lock()
-        .map_err(|e| OptimizationError::RegistryError(e.to_string()))?;
-
-    if cache.irq_priority_state == Some(enable) {
-        return Ok(());
-    }
-
-    let reg_path = r"HKEY_LOCAL_MACHINE\System\CurrentControlSet\Control\PriorityControl";
-
-    if enable {
-        modify_registry(reg_path, "IRQ8Priority", "1")
-    } else {
-        delete_registry_value(reg_path, "IRQ8Priority")
-    }
-        .map_err(|e| OptimizationError::RegistryError(e))?;
-
-    cache.irq_priority_state = Some(enable);
-    Ok(())
+    toggle("irq8_priority", enable, OptimizationError::RegistryError)
 }
 
+/// Turns the 1 ms timer resolution keeper on or off. The request is held
+/// while a game runs (or always, per the keeper settings) and dropped on exit.
 #[tauri::command]
 pub fn optimize_time_resolution(enable: bool) -> Result<()> {
-    #[cfg(target_os = "windows")]
-    unsafe {
-        let mut current_res: u32 = 0;
-        let status = NtSetTimerResolution(10000, enable as u8, &mut current_res);
-
-        if status >= 0 {
-            Ok(())
-        } else {
-            Err(OptimizationError::TimerError(status))
-        }
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    Err(OptimizationError::UnsupportedPlatform)
+    toggle(
+        "increase_timer_resolution",
+        enable,
+        OptimizationError::CommandError,
+    )
 }
 
-fn modify_registry(path: &str, key: &str, value: &str) -> std::result::Result<(), String> {
-    #[cfg(target_os = "windows")]
-    let output = Command::new("reg")
-        .args(&["add", path, "/v", key, "/t", "REG_DWORD", "/d", value, "/f"])
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    #[cfg(not(target_os = "windows"))]
-    let output = Command::new("reg")
-        .args(&["add", path, "/v", key, "/t", "REG_DWORD", "/d", value, "/f"])
-        .output()
-        .map_err(|e| e.to_string())?;
+/// Applies or reverts an engine optimization, turning a failed result into
+/// an error
+fn toggle(id: &str, enable: bool, error: fn(String) -> OptimizationError) -> Result<()> {
+    let engine = ENGINE
+        .lock()
+        .map_err(|e| OptimizationError::CommandError(e.to_string()))?;
+    if !engine.is_supported(id) {
+        return Err(OptimizationError::UnsupportedPlatform);
+    }
 
-    if !output.status.success() {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    let result = if enable {
+        engine.apply(id)
     } else {
-        Ok(())
+        engine.revert(id)
     }
-}
-
-fn delete_registry_value(path: &str, key: &str) -> std::result::Result<(), String> {
-    #[cfg(target_os = "windows")]
-    let output = Command::new("reg")
-        .args(&["delete", path, "/v", key, "/f"])
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW
-        .output()
-        .map_err(|e| e.to_string())?;
+    .map_err(|e| error(e.to_string()))?;
 
-    #[cfg(not(target_os = "windows"))]
-    let output = Command::new("reg")
-        .args(&["delete", path, "/v", key, "/f"])
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if !output.status.success() {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
-    } else {
+    if result.success {
         Ok(())
+    } else {
+        Err(error(result.message))
     }
 }
 
-// Low-level registry optimization functions
-// These are used by the optimization service for actual system modifications
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_game_dvr_toggle() {
-        let result = disable_game_dvr(true);
-        assert!(result.is_ok());
+        // Applying it would write the live registry, finding it is enough
+        #[cfg(target_os = "windows")]
+        assert!(ENGINE.lock().unwrap().is_supported("disable_game_dvr"));
+        #[cfg(not(target_os = "windows"))]
+        assert!(matches!(
+            disable_game_dvr(true),
+            Err(OptimizationError::UnsupportedPlatform)
+        ));
     }
 
     #[test]
@@ -0,0 +1,21 @@
+use crate::models::system_stats::SystemStats;
+use crate::services::plugin_host::{self, PluginList};
+use tauri::command;
+
+/// Loaded plugins, with the libraries that failed to load and why
+#[command]
+pub fn get_plugins() -> PluginList {
+    plugin_host::get_plugins()
+}
+
+/// Enables or disables an installed plugin by file name, from the next start
+#[command]
+pub fn set_plugin_enabled(file_name: String, enabled: bool) -> Result<PluginList, String> {
+    plugin_host::set_plugin_enabled(&file_name, enabled).map_err(|e| e.to_string())?;
+    Ok(plugin_host::get_plugins())
+}
+
+#[command]
+pub fn get_plugin_stats(provider_id: String) -> Result<SystemStats, String> {
+    plugin_host::collect_stats(&provider_id).map_err(|e| e.to_string())
+}
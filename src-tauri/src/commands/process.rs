@@ -111,25 +111,26 @@ fn fetch_process_data(pid: Arc<Pid>) -> Result<ProcessData> {
     })
 }
 
+/// Shows the file selected in the system file manager
 #[command]
 pub fn open_file_location(path: String) -> Result<()> {
-    if path.is_empty() || path == "N/A" {
-        return Err(ProcessError::DataError("Invalid file path".to_string()));
-    }
+    let path = existing_path(&path)?;
+    let dir = containing_folder(&path);
 
     #[cfg(target_os = "windows")]
     {
+        use std::os::windows::process::CommandExt;
         use std::process::Command;
 
-        // Extract directory from file path
-        let path_obj = std::path::Path::new(&path);
-        let dir = if path_obj.is_file() {
-            path_obj.parent().unwrap_or(path_obj)
+        // explorer parses its own command line: the path must be quoted
+        // right after the comma, which the standard argument quoting can't do
+        let result = if path.is_file() {
+            Command::new("explorer")
+                .raw_arg(explorer_select_arg(&path))
+                .spawn()
         } else {
-            path_obj
+            Command::new("explorer").arg(dir).spawn()
         };
-
-        let result = Command::new("explorer").arg("/select,").arg(&path).spawn();
         match result {
             Ok(_) => Ok(()),
             Err(_e) => {
@@ -150,12 +151,6 @@ pub fn open_file_location(path: String) -> Result<()> {
             Ok(_) => Ok(()),
             Err(_) => {
                 // Fallback: open directory
-                let path_obj = std::path::Path::new(&path);
-                let dir = if path_obj.is_file() {
-                    path_obj.parent().unwrap_or(path_obj)
-                } else {
-                    path_obj
-                };
                 let _ = Command::new("open").arg(dir).spawn();
                 Ok(())
             }
@@ -166,18 +161,11 @@ pub fn open_file_location(path: String) -> Result<()> {
     {
         use std::process::Command;
 
-        let path_obj = std::path::Path::new(&path);
-        let dir = if path_obj.is_file() {
-            path_obj.parent().unwrap_or(path_obj)
-        } else {
-            path_obj
-        };
-
         // Try various file managers
         let managers = ["nautilus", "dolphin", "thunar", "pcmanfm", "nemo"];
 
         for manager in &managers {
-            if let Ok(_) = Command::new(manager).arg(dir).spawn() {
+            if Command::new(manager).arg(dir).spawn().is_ok() {
                 return Ok(());
             }
         }
@@ -195,6 +183,109 @@ pub fn open_file_location(path: String) -> Result<()> {
     }
 }
 
+/// Opens a terminal in the folder of the file, or in the folder itself
+#[command]
+pub fn open_in_terminal(path: String) -> Result<()> {
+    let path = existing_path(&path)?;
+    let dir = containing_folder(&path);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        use std::process::Command;
+
+        // Windows Terminal when installed, the classic console otherwise
+        if Command::new("wt").arg("-d").arg(dir).spawn().is_ok() {
+            return Ok(());
+        }
+        Command::new("cmd")
+            .arg("/K")
+            .current_dir(dir)
+            .creation_flags(0x00000010) // CREATE_NEW_CONSOLE
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| ProcessError::DataError(format!("Failed to open a terminal: {}", e)))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        Command::new("open")
+            .arg("-a")
+            .arg("Terminal")
+            .arg(dir)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| ProcessError::DataError(format!("Failed to open a terminal: {}", e)))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::process::Command;
+
+        // The user's choice first; every one of these starts in its working directory
+        let terminals = std::env::var("TERMINAL").into_iter().chain(
+            [
+                "x-terminal-emulator",
+                "gnome-terminal",
+                "konsole",
+                "xfce4-terminal",
+                "alacritty",
+                "kitty",
+                "foot",
+                "xterm",
+            ]
+            .iter()
+            .map(|terminal| terminal.to_string()),
+        );
+        for terminal in terminals {
+            if Command::new(&terminal).current_dir(dir).spawn().is_ok() {
+                return Ok(());
+            }
+        }
+        Err(ProcessError::DataError(
+            "No terminal emulator found".to_string(),
+        ))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = dir;
+        Err(ProcessError::DataError(
+            "Platform not supported".to_string(),
+        ))
+    }
+}
+
+fn existing_path(path: &str) -> Result<std::path::PathBuf> {
+    if path.is_empty() || path == "N/A" {
+        return Err(ProcessError::DataError("Invalid file path".to_string()));
+    }
+    let path = std::path::PathBuf::from(path);
+    if !path.exists() {
+        return Err(ProcessError::DataError(format!(
+            "File not found: {}",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+fn containing_folder(path: &std::path::Path) -> &std::path::Path {
+    if path.is_file() {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    }
+}
+
+/// `/select,"<path>"`, quoted for explorer. Windows paths can't contain quotes.
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn explorer_select_arg(path: &std::path::Path) -> String {
+    format!("/select,\"{}\"", path.display())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,4 +317,19 @@ mod tests {
         assert!(!info.status.is_empty());
         assert!(info.children_processes.is_empty() || info.children_processes.len() > 0);
     }
+
+    #[test]
+    fn test_explorer_select_arg() {
+        let path = std::path::Path::new("C:\\Games\\My Game, Deluxe\\game.exe");
+        assert_eq!(
+            explorer_select_arg(path),
+            "/select,\"C:\\Games\\My Game, Deluxe\\game.exe\""
+        );
+    }
+
+    #[test]
+    fn test_missing_path() {
+        assert!(open_file_location("N/A".to_string()).is_err());
+        assert!(open_in_terminal("/no/such/folder/aura".to_string()).is_err());
+    }
 }
@@ -1,27 +1,48 @@
+use crate::models::game_profile::{AntiCheatInfo, BlockedApp, ProcessPriority};
 use crate::models::process_info::{ProcessFilter, ProcessStatus};
 use crate::models::system_stats::{GenericData, SystemStats};
+use crate::services::anti_cheat;
+use crate::services::energy_monitor;
+use crate::services::handle_monitor::{self, HandleLeakAlert};
+use crate::services::launch_rules;
+use crate::services::network_block::{self, NetworkBlockError};
+use crate::services::process_cleanup::{self, CleanupResult, OrphanProcess};
 use crate::services::process_control;
+use crate::services::process_history::{self, ProcessHistory};
+use crate::services::process_report::{self, ProcessReportSnippet};
+use crate::services::process_security::{self, ProcessSecurityInfo};
+use crate::services::process_watcher;
+use crate::services::service_host::{self, HostedService};
+use crate::services::window_control::{self, ProcessWindow, WindowAction};
+use crate::shared::events;
 use crate::shared::system::get_system;
-use crate::utils::{bytes::format_bytes, time::format_run_time};
+use crate::utils::{
+    bytes::{format_bytes, format_bytes_per_second},
+    locale::format_number,
+    time::format_run_time,
+};
 use anyhow;
 use regex;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use sysinfo;
 use tauri::command;
 use tauri::ipc::InvokeError;
 use thiserror::Error;
 
 // Frontend-compatible filter structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct FrontendProcessFilter {
     pub search_query: Option<String>,
-    pub status: Option<String>,
+    pub status: Option<ProcessStatus>,
     pub page: Option<usize>,
     pub per_page: Option<usize>,
     pub min_cpu: Option<f32>,
     pub min_memory: Option<u64>, // in bytes
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
+    pub fields: Option<Vec<String>>, // columns to fill in, all of them when None
 }
 
 // Frontend-compatible process data structure
@@ -29,19 +50,30 @@ pub struct FrontendProcessFilter {
 pub struct FrontendProcessData {
     pub name: String,
     pub pid: u32,
-    pub cpu_usage: f64,
+    pub cpu_usage: f64,             // percent of total CPU capacity
+    pub cpu_usage_single_core: f64, // percent of one logical core
     pub exe_path: String,
     pub affinity_set: bool,
     pub ram_usage: u64, // in MB
     pub run_time: String,
+    /// A `ProcessStatus` string, empty when the column isn't requested
     pub status: String,
+    pub user: String,  // DOMAIN\user
+    pub is_hung: bool, // "Not responding"
+    /// Runs in session 0 or hosts services, which a kill or suspend takes down
+    pub is_service: bool,
+    pub services: Vec<String>, // names of the hosted services
     pub disk_usage: FrontendDiskUsage,
+    pub energy_impact: f64,          // estimated, 0-100
+    pub energy_impact_level: String, // "Very low" .. "Very high"
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct FrontendDiskUsage {
-    pub read: String,
-    pub write: String,
+    pub read: String,  // formatted bytes/sec
+    pub write: String, // formatted bytes/sec
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -60,6 +92,14 @@ pub enum ProcessesError {
 
     #[error("Process control error: {0}")]
     ControlError(#[from] process_control::ProcessControlError),
+
+    #[error(
+        "Process {0} is protected by an anti-cheat, only priority and affinity can be changed"
+    )]
+    AntiCheatProtected(u32),
+
+    #[error("Network block error: {0}")]
+    NetworkBlockError(#[from] NetworkBlockError),
 }
 
 impl From<ProcessesError> for InvokeError {
@@ -70,6 +110,65 @@ impl From<ProcessesError> for InvokeError {
 
 type Result<T> = std::result::Result<T, ProcessesError>;
 
+/// Optional columns of a process row. pid, name, CPU and memory are always
+/// filled in: they are cheap and drive filtering and sorting. Columns left
+/// out are empty strings, zeros and false.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RowFields {
+    exe_path: bool,
+    run_time: bool,
+    status: bool,
+    user: bool,
+    is_hung: bool,
+    services: bool,
+    disk_usage: bool,
+    energy_impact: bool,
+}
+
+impl RowFields {
+    const ALL: Self = Self {
+        exe_path: true,
+        run_time: true,
+        status: true,
+        user: true,
+        is_hung: true,
+        services: true,
+        disk_usage: true,
+        energy_impact: true,
+    };
+
+    /// Columns listed in the filter, plus the ones its status filter and
+    /// sort order need. Accepts the row field names and the short names
+    /// used for sorting ("disk", "energy").
+    fn from_filter(filter: &FrontendProcessFilter) -> Self {
+        let Some(names) = &filter.fields else {
+            return Self::ALL;
+        };
+        let wants = |aliases: &[&str]| names.iter().any(|name| aliases.contains(&name.as_str()));
+        let sort_by = filter.sort_by.as_deref();
+
+        Self {
+            exe_path: wants(&["exe_path", "path"]),
+            run_time: wants(&["run_time"]),
+            status: wants(&["status"]) || filter.status.is_some(),
+            user: wants(&["user"]),
+            is_hung: wants(&["is_hung", "hung"]),
+            services: wants(&["services", "is_service"]),
+            disk_usage: wants(&["disk_usage", "disk", "io"]),
+            energy_impact: wants(&["energy_impact", "energy_impact_level", "energy"])
+                || sort_by == Some("energy"),
+        }
+    }
+
+    fn query(self) -> process_control::ProcessQuery {
+        process_control::ProcessQuery {
+            exe_path: self.exe_path,
+            energy_impact: self.energy_impact,
+            is_hung: self.is_hung,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ProcessEntry {
     pid: i32,
@@ -91,7 +190,7 @@ pub fn get_processes(filter: ProcessFilter) -> Result<Vec<SystemStats>> {
     let mut process_list = Vec::new();
 
     for (pid, process) in processes {
-        let status = ProcessStatus::from(process.status().to_string().as_str());
+        let status = ProcessStatus::from(process.status());
 
         // Applica i filtri
         if let Some(name_filter) = &filter.name {
@@ -128,10 +227,25 @@ pub fn get_processes(filter: ProcessFilter) -> Result<Vec<SystemStats>> {
             run_time: process.run_time(),
         };
 
-        process_list.push(format_process_entry(&entry));
+        process_list.push(entry);
     }
 
-    Ok(process_list)
+    sort_entries(&mut process_list, &filter);
+    // Without paging parameters every process is returned, as before they
+    // existed
+    if filter.page.is_some() || filter.per_page.is_some() {
+        process_list = paginate(process_list, filter.page, filter.per_page);
+    }
+
+    Ok(process_list.iter().map(format_process_entry).collect())
+}
+
+fn sort_entries(entries: &mut [ProcessEntry], filter: &ProcessFilter) {
+    sort_rows(
+        entries,
+        filter.sort_by.as_deref(),
+        filter.sort_order.as_deref(),
+    );
 }
 
 fn format_process_entry(process: &ProcessEntry) -> SystemStats {
@@ -139,22 +253,32 @@ fn format_process_entry(process: &ProcessEntry) -> SystemStats {
         GenericData {
             title: "PID".to_string(),
             value: process.pid.to_string(),
+            raw_value: None,
+            unit: None,
         },
         GenericData {
             title: "CPU Usage".to_string(),
-            value: format!("{:.1}%", process.cpu_usage),
+            value: format!("{}%", format_number(process.cpu_usage as f64, 1)),
+            raw_value: Some(process.cpu_usage as f64),
+            unit: Some("%".to_string()),
         },
         GenericData {
             title: "Memory".to_string(),
             value: format_bytes(process.memory_usage),
+            raw_value: Some(process.memory_usage as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Status".to_string(),
             value: format!("{:?}", process.status),
+            raw_value: None,
+            unit: None,
         },
         GenericData {
             title: "Run Time".to_string(),
             value: format_run_time(process.run_time),
+            raw_value: Some(process.run_time as f64),
+            unit: Some("s".to_string()),
         },
     ];
 
@@ -163,6 +287,8 @@ fn format_process_entry(process: &ProcessEntry) -> SystemStats {
         percentage: Some(process.cpu_usage),
         progress_data: None,
         generic_data: Some(generic_data),
+        is_stale: false,
+        is_fallback: false,
     }
 }
 
@@ -189,16 +315,93 @@ mod tests {
             assert!(process.generic_data.is_some());
         }
     }
+
+    #[test]
+    fn test_sort_and_paginate_entries() {
+        let entry = |pid: i32, memory_usage: u64| ProcessEntry {
+            pid,
+            name: format!("p{}", pid),
+            cpu_usage: 0.0,
+            memory_usage,
+            status: ProcessStatus::Running,
+            run_time: 0,
+        };
+        let mut entries = vec![entry(1, 300), entry(2, 100), entry(3, 200), entry(4, 100)];
+
+        sort_entries(
+            &mut entries,
+            &ProcessFilter::new().with_sort("memory", "desc"),
+        );
+        let pids: Vec<i32> = entries.iter().map(|e| e.pid).collect();
+        // Ties keep their order when descending too
+        assert_eq!(pids, [1, 3, 2, 4]);
+
+        sort_entries(&mut entries, &ProcessFilter::new().with_sort("pid", "asc"));
+        let page: Vec<i32> = paginate(entries, Some(1), Some(2))
+            .iter()
+            .map(|e| e.pid)
+            .collect();
+        assert_eq!(page, [3, 4]);
+        assert!(paginate(vec![1, 2], Some(5), Some(10)).is_empty());
+    }
+
+    #[test]
+    fn test_row_fields_from_filter() {
+        assert_eq!(
+            RowFields::from_filter(&FrontendProcessFilter::default()),
+            RowFields::ALL
+        );
+
+        let compact = FrontendProcessFilter {
+            fields: Some(vec!["pid".into(), "name".into(), "cpu".into()]),
+            ..Default::default()
+        };
+        let fields = RowFields::from_filter(&compact);
+        assert!(!fields.exe_path && !fields.status && !fields.user && !fields.disk_usage);
+        assert!(!fields.services);
+
+        // Filtering and sorting pull in the columns they need
+        let sorted = FrontendProcessFilter {
+            status: Some(ProcessStatus::Suspended),
+            sort_by: Some("energy".into()),
+            ..compact
+        };
+        let fields = RowFields::from_filter(&sorted);
+        assert!(fields.status && fields.energy_impact && !fields.exe_path);
+    }
 }
 
 #[command]
-pub fn boost_process_for_gaming(pid: u32) -> Result<()> {
-    process_control::boost_process_for_gaming(pid).map_err(ProcessesError::ControlError)
+pub fn boost_process_for_gaming(pid: u32, follow_children: Option<bool>) -> Result<()> {
+    process_control::boost_process_for_gaming(pid).map_err(ProcessesError::ControlError)?;
+    // Child processes the game's profile lists get the same boost, a failure
+    // there doesn't undo the game's own
+    for child in launch_rules::game_child_processes(pid) {
+        let _ = process_control::boost_process_for_gaming(child);
+    }
+    if follow_children.unwrap_or(false) {
+        let cores =
+            process_control::get_process_affinity(pid).map_err(ProcessesError::ControlError)?;
+        launch_rules::follow_children(pid, cores, ProcessPriority::High);
+    }
+    Ok(())
 }
 
+/// With `follow_children` the processes it starts later get the same cores
+/// and priority, for a launcher that spawns the game afterwards
 #[command]
-pub fn set_process_affinity(pid: u32, cores: Vec<u32>) -> Result<()> {
-    process_control::set_process_affinity_cores(pid, cores).map_err(ProcessesError::ControlError)
+pub fn set_process_affinity(
+    pid: u32,
+    cores: Vec<u32>,
+    follow_children: Option<bool>,
+) -> Result<()> {
+    process_control::set_process_affinity_cores(pid, cores.clone())
+        .map_err(ProcessesError::ControlError)?;
+    if follow_children.unwrap_or(false) {
+        // set_process_affinity_cores raises the priority to high as well
+        launch_rules::follow_children(pid, cores, ProcessPriority::High);
+    }
+    Ok(())
 }
 
 #[command]
@@ -206,6 +409,20 @@ pub fn get_process_affinity(pid: u32) -> Result<Vec<u32>> {
     process_control::get_process_affinity(pid).map_err(ProcessesError::ControlError)
 }
 
+/// Whether Windows may dynamically boost the priority of the process
+#[command]
+pub fn get_process_priority_boost(pid: u32) -> Result<bool> {
+    process_control::get_priority_boost(pid).map_err(ProcessesError::ControlError)
+}
+
+/// Turns dynamic priority boosting on or off and returns the state read back
+/// from the process
+#[command]
+pub fn set_process_priority_boost(pid: u32, enabled: bool) -> Result<bool> {
+    process_control::set_priority_boost(pid, enabled).map_err(ProcessesError::ControlError)?;
+    process_control::get_priority_boost(pid).map_err(ProcessesError::ControlError)
+}
+
 #[command]
 pub fn get_cpu_core_count() -> Result<u32> {
     let system = get_system()
@@ -223,6 +440,7 @@ pub fn kill_process(pid: u32) -> Result<()> {
         if let Ok(mut system) = get_system().lock() {
             system.refresh_all();
         }
+        emit_process_state_changed(pid, ProcessStatus::Killed);
     }
 
     result
@@ -230,12 +448,208 @@ pub fn kill_process(pid: u32) -> Result<()> {
 
 #[command]
 pub fn suspend_process(pid: u32) -> Result<()> {
-    process_control::suspend_process(pid).map_err(ProcessesError::ControlError)
+    // Suspending a protected game or its anti-cheat can get the account banned
+    if anti_cheat::detect(pid).protected {
+        return Err(ProcessesError::AntiCheatProtected(pid));
+    }
+    process_control::suspend_process(pid).map_err(ProcessesError::ControlError)?;
+    emit_process_state_changed(pid, ProcessStatus::Suspended);
+    Ok(())
+}
+
+#[command]
+pub fn get_anti_cheat_info(pid: u32) -> AntiCheatInfo {
+    anti_cheat::detect(pid)
 }
 
 #[command]
 pub fn resume_process(pid: u32) -> Result<()> {
-    process_control::resume_process(pid).map_err(ProcessesError::ControlError)
+    process_control::resume_process(pid).map_err(ProcessesError::ControlError)?;
+    emit_process_state_changed(pid, ProcessStatus::Running);
+    Ok(())
+}
+
+/// Cuts the executable of a process off the network until unblocked or Aura
+/// exits
+#[command]
+pub fn block_process_network(pid: u32) -> Result<BlockedApp> {
+    Ok(network_block::block_pid(pid)?)
+}
+
+#[command]
+pub fn unblock_process_network(exe_path: String) -> Result<()> {
+    Ok(network_block::unblock(&exe_path)?)
+}
+
+#[command]
+pub fn get_network_blocked_apps() -> Vec<BlockedApp> {
+    network_block::get_blocked()
+}
+
+#[command]
+pub fn get_process_windows(pid: u32) -> Result<Vec<ProcessWindow>> {
+    window_control::get_process_windows(pid).map_err(ProcessesError::ControlError)
+}
+
+#[command]
+pub fn minimize_process_windows(pid: u32) -> Result<usize> {
+    window_control::apply_window_action(pid, WindowAction::Minimize)
+        .map_err(ProcessesError::ControlError)
+}
+
+#[command]
+pub fn restore_process_windows(pid: u32) -> Result<usize> {
+    window_control::apply_window_action(pid, WindowAction::Restore)
+        .map_err(ProcessesError::ControlError)
+}
+
+#[command]
+pub fn focus_process_window(pid: u32) -> Result<usize> {
+    window_control::apply_window_action(pid, WindowAction::BringToFront)
+        .map_err(ProcessesError::ControlError)
+}
+
+/// Asks the process to close its windows (WM_CLOSE), giving it a chance to
+/// save state, unlike `kill_process`
+#[command]
+pub fn close_process_windows(pid: u32) -> Result<usize> {
+    window_control::apply_window_action(pid, WindowAction::Close)
+        .map_err(ProcessesError::ControlError)
+}
+
+/// Probes the windows of a process to tell whether it is "Not responding"
+#[command]
+pub fn is_process_hung(pid: u32) -> Result<bool> {
+    window_control::is_process_hung(pid).map_err(ProcessesError::ControlError)
+}
+
+/// Kills a process and launches its executable again, meant for frozen games.
+/// Returns the PID of the new instance.
+#[command]
+pub fn restart_process(pid: u32) -> Result<u32> {
+    let process_info =
+        process_control::get_process_detailed_info(pid).map_err(ProcessesError::ControlError)?;
+    let exe_path = std::path::PathBuf::from(&process_info.exe_path);
+
+    if !exe_path.is_file() {
+        return Err(ProcessesError::ControlError(
+            process_control::ProcessControlError::OpenError(format!(
+                "Executable path not available for process {}",
+                pid
+            )),
+        ));
+    }
+
+    kill_process(pid)?;
+
+    let working_dir = exe_path
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default();
+    let child =
+        std::process::Command::new(&exe_path)
+            .current_dir(working_dir)
+            .spawn()
+            .map_err(|e| {
+                ProcessesError::ControlError(process_control::ProcessControlError::OpenError(
+                    format!("Failed to relaunch {}: {}", exe_path.display(), e),
+                ))
+            })?;
+
+    Ok(child.id())
+}
+
+/// Lists processes left behind by crashed games (parent gone, executable in a
+/// game directory) so the user can review them before cleaning up
+#[command]
+pub fn find_orphaned_game_processes() -> Result<Vec<OrphanProcess>> {
+    process_cleanup::find_orphaned_game_processes().map_err(ProcessesError::ControlError)
+}
+
+#[command]
+pub fn cleanup_orphaned_game_processes(pids: Vec<u32>) -> Result<CleanupResult> {
+    let result = process_cleanup::terminate_orphaned_processes(&pids)
+        .map_err(ProcessesError::ControlError)?;
+
+    for pid in &result.terminated {
+        emit_process_state_changed(*pid, ProcessStatus::Killed);
+    }
+
+    Ok(result)
+}
+
+/// Processes whose handle count has been growing steadily past the leak
+/// threshold (also pushed live through the `handle-leak-detected` event)
+#[command]
+pub fn get_handle_leak_alerts() -> Result<Vec<HandleLeakAlert>> {
+    Ok(handle_monitor::get_active_alerts())
+}
+
+/// Environment variables of a running process as `NAME=value` entries
+#[command]
+pub fn get_process_env_vars(pid: u32) -> Result<Vec<String>> {
+    let pid = std::sync::Arc::new(sysinfo::Pid::from(pid as usize));
+    crate::services::process_service::env_vars(pid)
+        .map_err(|e| ProcessesError::ReadError(e.to_string()))
+}
+
+/// Which source feeds the `process-created` / `process-exited` events
+/// ("wmi-trace", "wmi", "netlink" or "polling")
+#[command]
+pub fn get_process_watcher_backend() -> Result<String> {
+    Ok(process_watcher::active_backend().to_string())
+}
+
+/// Payload of the `process-state-changed` event
+#[derive(Debug, Serialize, Clone)]
+pub struct ProcessStateChangedEvent {
+    pub pid: u32,
+    pub status: ProcessStatus,
+    pub timestamp: u64,
+}
+
+fn emit_process_state_changed(pid: u32, status: ProcessStatus) {
+    events::emit(
+        events::PROCESS_STATE_CHANGED,
+        ProcessStateChangedEvent {
+            pid,
+            status,
+            timestamp: events::now_millis(),
+        },
+    );
+}
+
+/// Re-reads a single process so the UI can update one row right after an
+/// action instead of polling the whole process list.
+#[command]
+pub fn refresh_process(pid: u32) -> Result<FrontendProcessData> {
+    let process_info =
+        process_control::get_process_detailed_info(pid).map_err(ProcessesError::ControlError)?;
+
+    Ok(process_row_from_info(&process_info, RowFields::ALL))
+}
+
+/// Services running in a process, so its row can link to their entries in
+/// the services view. Empty for ordinary processes.
+#[command]
+pub fn get_process_services(pid: u32) -> Result<Vec<HostedService>> {
+    Ok(service_host::services_of(pid))
+}
+
+/// CPU and memory samples of the last minute, for the detail pane sparklines.
+/// Samples are taken whenever the process list refreshes.
+#[command]
+pub fn get_process_history(pid: u32) -> Result<ProcessHistory> {
+    Ok(process_history::get_history(pid))
+}
+
+/// Details, recent usage, connections, modules and environment of a process
+/// as markdown and JSON, for pasting into a support thread
+#[command]
+pub async fn get_process_report(pid: u32) -> Result<ProcessReportSnippet> {
+    let report =
+        process_report::build(pid).map_err(|e| ProcessesError::ReadError(e.to_string()))?;
+    process_report::snippet(&report).map_err(|e| ProcessesError::ReadError(e.to_string()))
 }
 
 #[command]
@@ -275,7 +689,8 @@ async fn get_running_processes_native(filter: FrontendProcessFilter) -> Result<P
     };
 
     // Use optimized Windows native API for much better performance
-    let processes_info = process_control::get_all_processes_info()
+    let fields = RowFields::from_filter(&filter);
+    let processes_info = process_control::get_processes_info(fields.query())
         .map_err(|e| ProcessesError::ReadError(format!("Native API failed: {}", e)))?;
 
     // Process filtering with native data
@@ -299,16 +714,9 @@ async fn get_running_processes_native(filter: FrontendProcessFilter) -> Result<P
         }
 
         // Status filter
-        if let Some(ref status_filter) = filter.status {
-            if !status_filter.is_empty() {
-                let normalized_status = if process_info.is_suspended {
-                    "suspended"
-                } else {
-                    "runnable" // For simplicity, treat all non-suspended as runnable
-                };
-                if normalized_status != status_filter.as_str() {
-                    continue;
-                }
+        if let Some(status_filter) = filter.status {
+            if native_status(process_info) != status_filter {
+                continue;
             }
         }
 
@@ -325,39 +733,7 @@ async fn get_running_processes_native(filter: FrontendProcessFilter) -> Result<P
             }
         }
 
-        // Calculate runtime in a more readable format
-        let run_time = if process_info.create_time > 0 {
-            // Convert Windows FILETIME to duration
-            let current_time = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs() as i64;
-            let create_time_secs = process_info.create_time / 10_000_000 - 11644473600; // Convert FILETIME to Unix timestamp
-            let runtime_secs = (current_time - create_time_secs).max(0) as u64;
-            format_run_time(runtime_secs)
-        } else {
-            "Unknown".to_string()
-        };
-
-        let status = if process_info.is_suspended {
-            "suspended"
-        } else {
-            "runnable"
-        };
-        let entry = FrontendProcessData {
-            pid: process_info.pid,
-            name: process_name.clone(),
-            cpu_usage: process_info.cpu_usage_percent,
-            exe_path: process_info.exe_path.clone(),
-            affinity_set: false,
-            ram_usage: process_info.memory_working_set / (1024 * 1024), // Convert to MB
-            run_time,
-            status: status.to_string(),
-            disk_usage: FrontendDiskUsage {
-                read: format_bytes(process_info.io_read_bytes),
-                write: format_bytes(process_info.io_write_bytes),
-            },
-        };
+        let entry = process_row_from_info(process_info, fields);
 
         filtered_processes.push(entry);
     }
@@ -368,7 +744,7 @@ async fn get_running_processes_native(filter: FrontendProcessFilter) -> Result<P
     let total_count = filtered_processes.len();
 
     // Apply pagination
-    let paginated_processes = paginate_processes(filtered_processes, &filter);
+    let paginated_processes = paginate(filtered_processes, filter.page, filter.per_page);
 
     Ok(ProcessResponse {
         processes: paginated_processes,
@@ -388,6 +764,20 @@ async fn get_running_processes_fallback(filter: FrontendProcessFilter) -> Result
     system.refresh_all();
 
     let processes = system.processes();
+    let logical_processors = system.cpus().len() as u32;
+    let fields = RowFields::from_filter(&filter);
+    let hung_pids = if fields.is_hung {
+        window_control::get_hung_process_ids()
+    } else {
+        Default::default()
+    };
+    // One snapshot for every row; a process missing from it (access denied,
+    // or started since) counts as not suspended
+    let suspension_states = if fields.status {
+        process_control::get_suspension_states().unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
     let mut filtered_processes = Vec::new();
 
     // Pre-compile regex if needed for search
@@ -404,14 +794,15 @@ async fn get_running_processes_fallback(filter: FrontendProcessFilter) -> Result
     // Process filtering without any cache
     for (pid, process) in processes.iter() {
         let pid_u32 = pid.as_u32();
-        let status = process.status().to_string();
-        let cpu_usage = process.cpu_usage();
+        // sysinfo reports usage relative to a single core, normalize it like the native path
+        let (cpu_usage_single_core, cpu_usage) =
+            process_control::normalize_cpu_usage(process.cpu_usage() as f64, logical_processors);
         let memory_usage = process.memory();
         let process_name = process.name().to_string_lossy().into_owned();
+        process_history::record(pid_u32, cpu_usage, memory_usage);
 
-        // Always check suspension status for all processes to ensure accuracy
-        // Use unwrap_or(false) to handle cases where suspension check fails (e.g., access denied)
-        let is_suspended = process_control::is_process_suspended(pid_u32).unwrap_or(false);
+        let is_suspended = suspension_states.get(&pid_u32).copied().unwrap_or(false);
+        let status = ProcessStatus::from_state(process.status(), is_suspended);
 
         // Apply filters with early return for performance
         if let Some(ref search_query) = filter.search_query {
@@ -430,18 +821,15 @@ async fn get_running_processes_fallback(filter: FrontendProcessFilter) -> Result
         }
 
         // Status filter
-        if let Some(ref status_filter) = filter.status {
-            if !status_filter.is_empty() {
-                let normalized_status = normalize_process_status(&status, Some(is_suspended));
-                if normalized_status != status_filter.as_str() {
-                    continue;
-                }
+        if let Some(status_filter) = filter.status {
+            if status != status_filter {
+                continue;
             }
         }
 
         // CPU filter
         if let Some(min_cpu) = filter.min_cpu {
-            if cpu_usage < min_cpu {
+            if cpu_usage < min_cpu as f64 {
                 continue;
             }
         }
@@ -453,37 +841,95 @@ async fn get_running_processes_fallback(filter: FrontendProcessFilter) -> Result
             }
         }
 
-        // Always use the suspension status for display
-        let final_status = normalize_process_status(&status, Some(is_suspended));
+        // Disk I/O rates from the cumulative counters reported by sysinfo
+        let (read_rate, write_rate) = if fields.disk_usage || fields.energy_impact {
+            let disk_usage = process.disk_usage();
+            process_control::calculate_io_rates(
+                pid_u32,
+                disk_usage.total_read_bytes,
+                disk_usage.total_written_bytes,
+            )
+        } else {
+            (0, 0)
+        };
+        let energy_impact = if fields.energy_impact {
+            energy_monitor::estimate_energy_impact(pid_u32, cpu_usage, read_rate, write_rate)
+        } else {
+            0.0
+        };
+
+        // Session ids from sysinfo are not the Windows ones, only the hosted
+        // services tell a service process apart here
+        let services = if fields.services {
+            service_host::services_of(pid_u32)
+        } else {
+            Vec::new()
+        };
 
         let entry = FrontendProcessData {
             pid: pid_u32,
             name: process_name,
-            cpu_usage: cpu_usage as f64,
-            exe_path: process
-                .exe()
-                .map(|p| p.to_string_lossy().into_owned())
-                .unwrap_or_else(|| "N/A".to_string()),
+            cpu_usage,
+            cpu_usage_single_core,
+            exe_path: if fields.exe_path {
+                process
+                    .exe()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "N/A".to_string())
+            } else {
+                String::new()
+            },
             affinity_set: false, // TODO: Implement affinity checking
             ram_usage: memory_usage / (1024 * 1024), // Convert to MB
-            run_time: format_run_time(process.run_time()),
-            status: final_status.to_string(),
-            disk_usage: FrontendDiskUsage {
-                read: "0".to_string(),
-                write: "0".to_string(),
+            run_time: if fields.run_time {
+                format_run_time(process.run_time())
+            } else {
+                String::new()
+            },
+            status: if fields.status {
+                status.as_str().to_string()
+            } else {
+                String::new()
+            },
+            user: if fields.user {
+                process
+                    .user_id()
+                    .map(process_security::resolve_user_id)
+                    .unwrap_or_else(|| "N/A".to_string())
+            } else {
+                String::new()
+            },
+            is_hung: hung_pids.contains(&pid_u32),
+            is_service: service_host::is_service_process(None, &services),
+            services: services.into_iter().map(|service| service.name).collect(),
+            disk_usage: if fields.disk_usage {
+                disk_usage(read_rate, write_rate)
+            } else {
+                FrontendDiskUsage::default()
+            },
+            energy_impact,
+            energy_impact_level: if fields.energy_impact {
+                energy_monitor::energy_impact_level(energy_impact).to_string()
+            } else {
+                String::new()
             },
         };
 
         filtered_processes.push(entry);
     }
 
+    // Drop cached samples of processes that no longer exist, the I/O rates
+    // and energy estimates as well as the history
+    let live_pids: HashSet<u32> = processes.keys().map(|pid| pid.as_u32()).collect();
+    process_control::prune_stale_samples(&live_pids);
+
     // Sort processes
     sort_processes(&mut filtered_processes, &filter);
 
     let total_count = filtered_processes.len();
 
     // Apply pagination
-    let paginated_processes = paginate_processes(filtered_processes, &filter);
+    let paginated_processes = paginate(filtered_processes, filter.page, filter.per_page);
 
     Ok(ProcessResponse {
         processes: paginated_processes,
@@ -492,81 +938,192 @@ async fn get_running_processes_fallback(filter: FrontendProcessFilter) -> Result
 }
 
 // Helper functions
-fn normalize_process_status(status: &str, is_suspended: Option<bool>) -> &'static str {
-    // Check if process is suspended first
-    if let Some(true) = is_suspended {
-        return "suspended";
+
+/// Builds a process table row from the native process information
+fn process_row_from_info(
+    process_info: &process_control::ProcessInfo,
+    fields: RowFields,
+) -> FrontendProcessData {
+    let status = native_status(process_info);
+    let services = if fields.services {
+        service_host::services_of(process_info.pid)
+    } else {
+        Vec::new()
+    };
+
+    FrontendProcessData {
+        pid: process_info.pid,
+        name: process_info.name.clone(),
+        cpu_usage: process_info.cpu_usage_percent,
+        cpu_usage_single_core: process_info.cpu_usage_single_core,
+        exe_path: if fields.exe_path {
+            process_info.exe_path.clone()
+        } else {
+            String::new()
+        },
+        affinity_set: false,
+        ram_usage: process_info.memory_working_set / (1024 * 1024), // Convert to MB
+        run_time: if fields.run_time {
+            format_native_run_time(process_info.create_time)
+        } else {
+            String::new()
+        },
+        status: if fields.status {
+            status.as_str().to_string()
+        } else {
+            String::new()
+        },
+        user: if fields.user {
+            process_security::get_process_user(process_info.pid, process_info.create_time)
+                .unwrap_or_else(|| "N/A".to_string())
+        } else {
+            String::new()
+        },
+        is_hung: process_info.is_hung,
+        is_service: fields.services
+            && service_host::is_service_process(Some(process_info.session_id), &services),
+        services: services.into_iter().map(|service| service.name).collect(),
+        disk_usage: if fields.disk_usage {
+            disk_usage(process_info.io_read_rate, process_info.io_write_rate)
+        } else {
+            FrontendDiskUsage::default()
+        },
+        energy_impact: process_info.energy_impact,
+        energy_impact_level: if fields.energy_impact {
+            energy_monitor::energy_impact_level(process_info.energy_impact).to_string()
+        } else {
+            String::new()
+        },
     }
+}
 
-    match status.to_lowercase().as_str() {
-        "running" | "runnable" => "runnable",
-        "sleeping" | "sleep" | "idle" => "sleeping",
-        "stopped" | "zombie" | "dead" => "stopped",
-        _ => "unknown",
+fn disk_usage(read_rate: u64, write_rate: u64) -> FrontendDiskUsage {
+    FrontendDiskUsage {
+        read: format_bytes_per_second(read_rate),
+        write: format_bytes_per_second(write_rate),
+        read_bytes_per_sec: read_rate,
+        write_bytes_per_sec: write_rate,
     }
 }
 
-fn sort_processes(processes: &mut Vec<FrontendProcessData>, filter: &FrontendProcessFilter) {
-    if let (Some(sort_by), Some(sort_order)) = (&filter.sort_by, &filter.sort_order) {
-        let ascending = sort_order == "asc";
+/// Formats the run time of a process from its creation time (Windows FILETIME)
+fn format_native_run_time(create_time: i64) -> String {
+    if create_time > 0 {
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let create_time_secs = create_time / 10_000_000 - 11644473600; // Convert FILETIME to Unix timestamp
+        let runtime_secs = (current_time - create_time_secs).max(0) as u64;
+        format_run_time(runtime_secs)
+    } else {
+        "Unknown".to_string()
+    }
+}
 
-        match sort_by.as_str() {
-            "name" => {
-                if ascending {
-                    processes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                } else {
-                    processes.sort_by(|a, b| b.name.to_lowercase().cmp(&a.name.to_lowercase()));
-                }
-            }
-            "cpu" => {
-                if ascending {
-                    processes.sort_by(|a, b| {
-                        a.cpu_usage
-                            .partial_cmp(&b.cpu_usage)
-                            .unwrap_or(std::cmp::Ordering::Equal)
-                    });
-                } else {
-                    processes.sort_by(|a, b| {
-                        b.cpu_usage
-                            .partial_cmp(&a.cpu_usage)
-                            .unwrap_or(std::cmp::Ordering::Equal)
-                    });
-                }
-            }
-            "memory" => {
-                if ascending {
-                    processes.sort_by(|a, b| a.ram_usage.cmp(&b.ram_usage));
-                } else {
-                    processes.sort_by(|a, b| b.ram_usage.cmp(&a.ram_usage));
-                }
-            }
-            "pid" => {
-                if ascending {
-                    processes.sort_by(|a, b| a.pid.cmp(&b.pid));
-                } else {
-                    processes.sort_by(|a, b| b.pid.cmp(&a.pid));
-                }
-            }
-            _ => {}
-        }
+/// The native API only tells suspended processes apart, every other one is
+/// reported as running
+fn native_status(process_info: &process_control::ProcessInfo) -> ProcessStatus {
+    if process_info.is_suspended {
+        ProcessStatus::Suspended
+    } else {
+        ProcessStatus::Running
     }
 }
 
-fn paginate_processes(
-    processes: Vec<FrontendProcessData>,
-    filter: &FrontendProcessFilter,
-) -> Vec<FrontendProcessData> {
-    let page = filter.page.unwrap_or(0); // 0-based page indexing to match frontend
-    let page_size = filter.per_page.unwrap_or(50).min(1000); // Max 1000 items per page
+fn sort_processes(processes: &mut [FrontendProcessData], filter: &FrontendProcessFilter) {
+    sort_rows(
+        processes,
+        filter.sort_by.as_deref(),
+        filter.sort_order.as_deref(),
+    );
+}
 
-    let start_index = (page * page_size) as usize;
-    let end_index = (start_index + page_size as usize).min(processes.len());
+/// What both process APIs sort on. A column one of them doesn't have
+/// compares equal and leaves the order alone.
+trait SortKeys {
+    fn name(&self) -> &str;
+    fn pid(&self) -> u32;
+    fn cpu(&self) -> f64;
+    fn memory(&self) -> u64;
+    fn run_time(&self) -> u64 {
+        0
+    }
+    fn energy(&self) -> f64 {
+        0.0
+    }
+}
 
-    if start_index >= processes.len() {
-        return Vec::new();
+impl SortKeys for ProcessEntry {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn pid(&self) -> u32 {
+        self.pid as u32
+    }
+    fn cpu(&self) -> f64 {
+        self.cpu_usage as f64
+    }
+    fn memory(&self) -> u64 {
+        self.memory_usage
+    }
+    fn run_time(&self) -> u64 {
+        self.run_time
     }
+}
 
-    processes[start_index..end_index].to_vec()
+impl SortKeys for FrontendProcessData {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+    fn cpu(&self) -> f64 {
+        self.cpu_usage
+    }
+    fn memory(&self) -> u64 {
+        self.ram_usage
+    }
+    fn energy(&self) -> f64 {
+        self.energy_impact
+    }
+}
+
+/// Shared by both process APIs: descending unless the order is "asc", and
+/// unsorted without a known column
+fn sort_rows<T: SortKeys>(rows: &mut [T], sort_by: Option<&str>, sort_order: Option<&str>) {
+    let Some(sort_by) = sort_by else {
+        return;
+    };
+    let compare: fn(&T, &T) -> Ordering = match sort_by {
+        "name" => |a, b| a.name().to_lowercase().cmp(&b.name().to_lowercase()),
+        "cpu" => |a, b| a.cpu().total_cmp(&b.cpu()),
+        "memory" => |a, b| a.memory().cmp(&b.memory()),
+        "pid" => |a, b| a.pid().cmp(&b.pid()),
+        "run_time" => |a, b| a.run_time().cmp(&b.run_time()),
+        "energy" => |a, b| a.energy().total_cmp(&b.energy()),
+        _ => return,
+    };
+    // Reversing the comparison rather than the result keeps equal rows in
+    // their order, so rows with the same value don't swap on every refresh
+    if sort_order == Some("asc") {
+        rows.sort_by(compare);
+    } else {
+        rows.sort_by(|a, b| compare(a, b).reverse());
+    }
+}
+
+/// Shared by both process APIs
+fn paginate<T>(items: Vec<T>, page: Option<usize>, per_page: Option<usize>) -> Vec<T> {
+    let page = page.unwrap_or(0); // 0-based page indexing to match frontend
+    let page_size = per_page.unwrap_or(50).min(1000); // Max 1000 items per page
+
+    items
+        .into_iter()
+        .skip(page.saturating_mul(page_size))
+        .take(page_size)
+        .collect()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -585,6 +1142,7 @@ pub struct ProcessDetailedInfo {
     pub name: String,
     pub exe_path: String,
     pub cpu_usage_percent: f64,
+    pub cpu_usage_single_core: f64,
     pub memory_working_set: u64, // in MB
     pub memory_private: u64,     // in MB
     pub memory_virtual: u64,     // in MB
@@ -593,11 +1151,21 @@ pub struct ProcessDetailedInfo {
     pub thread_count: u32,
     pub is_suspended: bool,
     pub session_id: u32,
+    pub user: String, // DOMAIN\user
     pub io_read_bytes: String,
     pub io_write_bytes: String,
+    pub io_read_rate: String,
+    pub io_write_rate: String,
     pub io_read_operations: u64,
     pub io_write_operations: u64,
+    pub page_faults: u64,
+    pub hard_faults: u64,
+    pub page_fault_rate: u64, // faults/sec
+    pub hard_fault_rate: u64, // faults/sec
     pub run_time: String,
+    pub security: Option<ProcessSecurityInfo>, // None when the token can't be opened
+    pub is_service: bool,
+    pub services: Vec<HostedService>,
     pub children: Vec<ProcessBasicInfo>,
 }
 
@@ -610,12 +1178,22 @@ pub async fn get_detailed_process_info(pid: u32) -> Result<ProcessDetailedInfo>
     let children =
         process_control::get_child_processes(pid).map_err(ProcessesError::ControlError)?;
 
+    let security = process_security::get_process_security_info(pid).ok();
+    let user = security
+        .as_ref()
+        .and_then(|s| s.user.clone())
+        .or_else(|| process_security::get_process_user(pid, process_info.create_time))
+        .unwrap_or_else(|| "N/A".to_string());
+    let services = service_host::services_of(pid);
+    let is_service = service_host::is_service_process(Some(process_info.session_id), &services);
+
     let detailed_info = ProcessDetailedInfo {
         pid: process_info.pid,
         parent_pid: process_info.parent_pid,
         name: process_info.name,
         exe_path: process_info.exe_path,
         cpu_usage_percent: process_info.cpu_usage_percent,
+        cpu_usage_single_core: process_info.cpu_usage_single_core,
         memory_working_set: process_info.memory_working_set / (1024 * 1024), // Convert to MB
         memory_private: process_info.memory_private / (1024 * 1024),         // Convert to MB
         memory_virtual: process_info.memory_virtual / (1024 * 1024),         // Convert to MB
@@ -624,22 +1202,21 @@ pub async fn get_detailed_process_info(pid: u32) -> Result<ProcessDetailedInfo>
         thread_count: process_info.thread_count,
         is_suspended: process_info.is_suspended,
         session_id: process_info.session_id,
+        user,
         io_read_bytes: format_bytes(process_info.io_read_bytes),
         io_write_bytes: format_bytes(process_info.io_write_bytes),
+        io_read_rate: format_bytes_per_second(process_info.io_read_rate),
+        io_write_rate: format_bytes_per_second(process_info.io_write_rate),
         io_read_operations: process_info.io_read_operations,
         io_write_operations: process_info.io_write_operations,
-        run_time: if process_info.create_time > 0 {
-            // Convert Windows FILETIME to duration
-            let current_time = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs() as i64;
-            let create_time_secs = process_info.create_time / 10_000_000 - 11644473600; // Convert FILETIME to Unix timestamp
-            let runtime_secs = (current_time - create_time_secs).max(0) as u64;
-            format_run_time(runtime_secs)
-        } else {
-            "Unknown".to_string()
-        },
+        page_faults: process_info.page_faults,
+        hard_faults: process_info.hard_faults,
+        page_fault_rate: process_info.page_fault_rate,
+        hard_fault_rate: process_info.hard_fault_rate,
+        run_time: format_native_run_time(process_info.create_time),
+        security,
+        is_service,
+        services,
         children: children
             .into_iter()
             .map(|child| ProcessBasicInfo {
@@ -0,0 +1,16 @@
+use crate::models::readiness::{GamingServicesStatus, ReadinessReport};
+use crate::services::{gaming_services, readiness};
+use tauri::command;
+
+/// Read-only audit of Game Bar, Game DVR, Game Mode and the Xbox services
+#[command]
+pub fn get_gaming_services_status() -> Result<GamingServicesStatus, String> {
+    gaming_services::get_status().map_err(|e| e.to_string())
+}
+
+/// Pre-flight checklist before launching a game. Failed items carry the id of
+/// the optimization that fixes them.
+#[command]
+pub async fn run_readiness_check() -> Result<ReadinessReport, String> {
+    readiness::run_readiness_check().map_err(|e| e.to_string())
+}
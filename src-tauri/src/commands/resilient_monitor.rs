@@ -1,13 +1,33 @@
 use crate::models::system_stats::{GenericData, ProgressData, SystemStats};
+use crate::services::monitor_history::{self, ChronicIssue, SubsystemErrorHistory};
+use crate::services::{quiet_hours, user_idle};
+use crate::shared::{events, i18n, paths};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tauri::command;
 
+// A sampler that has not finished a fetch for this long is considered wedged
 const MONITOR_TIMEOUT: Duration = Duration::from_secs(10);
 const CACHE_TIMEOUT: Duration = Duration::from_secs(5);
 const MAX_RETRIES: u32 = 3;
+// Shorter than CACHE_TIMEOUT so the commands always find fresh data
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(5);
+const POLICIES_FILE: &str = "monitor_policies.json";
+
+type Fetcher = fn() -> Result<SystemStats, String>;
+
+// Subsystems sampled in the background, one thread each
+const SAMPLERS: [(&str, Fetcher); 5] = [
+    ("cpu", fetch_cpu),
+    ("memory", fetch_memory),
+    ("storage", fetch_storage),
+    ("network", fetch_network),
+    ("system", fetch_system),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorHealth {
@@ -19,6 +39,59 @@ pub struct MonitorHealth {
     pub system_healthy: bool,
     pub last_health_check: u64,
     pub error_counts: HashMap<String, u32>,
+    pub samplers: Vec<SamplerStatus>,
+    // Failures across launches, loaded from disk
+    pub error_history: HashMap<String, SubsystemErrorHistory>,
+    pub chronic_issues: Vec<ChronicIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplerStatus {
+    pub name: String,
+    pub running: bool,       // false while wedged, until the supervisor restarts it
+    pub last_heartbeat: u64, // Unix millis
+    pub restarts: u32,
+}
+
+struct SamplerState {
+    // Bumped on restart, so a wedged thread that wakes up knows it was replaced
+    generation: u64,
+    last_heartbeat: Instant,
+    last_heartbeat_ms: u64,
+    restarts: u32,
+}
+
+impl SamplerState {
+    fn new() -> Self {
+        Self {
+            generation: 0,
+            last_heartbeat: Instant::now(),
+            last_heartbeat_ms: events::now_millis(),
+            restarts: 0,
+        }
+    }
+
+    fn beat(&mut self) {
+        self.last_heartbeat = Instant::now();
+        self.last_heartbeat_ms = events::now_millis();
+    }
+
+    fn is_wedged(&self) -> bool {
+        self.last_heartbeat.elapsed() > MONITOR_TIMEOUT
+    }
+}
+
+/// What a subsystem returns when fetching fresh data fails
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum FallbackPolicy {
+    /// Last good data, flagged `is_stale`, or the placeholder if there is none
+    #[default]
+    ReturnCached,
+    /// Placeholder data flagged `is_fallback`
+    ReturnFallback,
+    /// The error itself
+    ReturnError,
 }
 
 #[derive(Clone)]
@@ -33,6 +106,7 @@ struct ResilientMonitor {
     health_status: MonitorHealth,
     last_health_check: Instant,
     error_counts: HashMap<String, u32>,
+    policies: HashMap<String, FallbackPolicy>,
 }
 
 impl ResilientMonitor {
@@ -48,12 +122,23 @@ impl ResilientMonitor {
                 system_healthy: true,
                 last_health_check: 0,
                 error_counts: HashMap::new(),
+                samplers: Vec::new(),
+                error_history: HashMap::new(),
+                chronic_issues: Vec::new(),
             },
             last_health_check: Instant::now(),
             error_counts: HashMap::new(),
+            policies: std::fs::read_to_string(paths::app_data_file(POLICIES_FILE))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default(),
         }
     }
 
+    fn policy(&self, stat_type: &str) -> FallbackPolicy {
+        self.policies.get(stat_type).copied().unwrap_or_default()
+    }
+
     fn should_use_cache(&self, stat_type: &str) -> bool {
         if let Some(cached) = self.cached_stats.get(stat_type) {
             cached.timestamp.elapsed() < CACHE_TIMEOUT
@@ -72,14 +157,15 @@ impl ResilientMonitor {
         self.cached_stats.insert(
             stat_type,
             CachedStats {
+                is_fallback: stats.is_fallback,
                 data: stats,
                 timestamp: Instant::now(),
-                is_fallback: false,
             },
         );
     }
 
-    fn record_error(&mut self, stat_type: &str) {
+    fn record_error(&mut self, stat_type: &str, reason: &str) {
+        monitor_history::record_failure(stat_type, reason);
         let count = self.error_counts.entry(stat_type.to_string()).or_insert(0);
         *count += 1;
 
@@ -95,6 +181,18 @@ impl ResilientMonitor {
         }
     }
 
+    fn is_healthy(&self, stat_type: &str) -> bool {
+        match stat_type {
+            "cpu" => self.health_status.cpu_healthy,
+            "memory" => self.health_status.memory_healthy,
+            "storage" => self.health_status.storage_healthy,
+            "network" => self.health_status.network_healthy,
+            "gpu" => self.health_status.gpu_healthy,
+            "system" => self.health_status.system_healthy,
+            _ => true,
+        }
+    }
+
     fn reset_error_count(&mut self, stat_type: &str) {
         self.error_counts.insert(stat_type.to_string(), 0);
 
@@ -110,6 +208,24 @@ impl ResilientMonitor {
         }
     }
 
+    /// Result of a failed fetch, according to the subsystem's policy
+    fn handle_failure(&mut self, stat_type: &str, error: String) -> Result<SystemStats, String> {
+        match self.policy(stat_type) {
+            FallbackPolicy::ReturnCached => {
+                if let Some(mut cached_stats) = self.get_cached_or_fallback(stat_type) {
+                    cached_stats.is_stale = true;
+                    return Ok(cached_stats);
+                }
+            }
+            FallbackPolicy::ReturnFallback => {}
+            FallbackPolicy::ReturnError => return Err(error),
+        }
+
+        let fallback_stats = self.create_fallback_stats(stat_type);
+        self.update_cache(stat_type.to_string(), fallback_stats.clone());
+        Ok(fallback_stats)
+    }
+
     fn create_fallback_stats(&self, stat_type: &str) -> SystemStats {
         match stat_type {
             "cpu" => SystemStats {
@@ -119,11 +235,20 @@ impl ResilientMonitor {
                     title: "Core 0".to_string(),
                     value: 0.0,
                     temperature: None,
+                    raw_value: None,
+                    unit: None,
                 }]),
                 generic_data: Some(vec![GenericData {
                     title: "Status".to_string(),
-                    value: "Monitoring temporarily unavailable".to_string(),
+                    value: i18n::text(
+                        "monitor.unavailable.cpu",
+                        "Monitoring temporarily unavailable",
+                    ),
+                    raw_value: None,
+                    unit: None,
                 }]),
+                is_stale: false,
+                is_fallback: true,
             },
             "memory" => SystemStats {
                 title: "Memory (Safe Mode)".to_string(),
@@ -132,11 +257,20 @@ impl ResilientMonitor {
                     title: "System Memory".to_string(),
                     value: 0.0,
                     temperature: None,
+                    raw_value: None,
+                    unit: None,
                 }]),
                 generic_data: Some(vec![GenericData {
                     title: "Status".to_string(),
-                    value: "Memory monitoring temporarily unavailable".to_string(),
+                    value: i18n::text(
+                        "monitor.unavailable.memory",
+                        "Memory monitoring temporarily unavailable",
+                    ),
+                    raw_value: None,
+                    unit: None,
                 }]),
+                is_stale: false,
+                is_fallback: true,
             },
             "storage" => SystemStats {
                 title: "Storage (Safe Mode)".to_string(),
@@ -145,11 +279,20 @@ impl ResilientMonitor {
                     title: "Primary Drive".to_string(),
                     value: 0.0,
                     temperature: None,
+                    raw_value: None,
+                    unit: None,
                 }]),
                 generic_data: Some(vec![GenericData {
                     title: "Status".to_string(),
-                    value: "Storage monitoring temporarily unavailable".to_string(),
+                    value: i18n::text(
+                        "monitor.unavailable.storage",
+                        "Storage monitoring temporarily unavailable",
+                    ),
+                    raw_value: None,
+                    unit: None,
                 }]),
+                is_stale: false,
+                is_fallback: true,
             },
             "network" => SystemStats {
                 title: "Network (Safe Mode)".to_string(),
@@ -158,11 +301,20 @@ impl ResilientMonitor {
                     title: "Primary Interface".to_string(),
                     value: 0.0,
                     temperature: None,
+                    raw_value: None,
+                    unit: None,
                 }]),
                 generic_data: Some(vec![GenericData {
                     title: "Status".to_string(),
-                    value: "Network monitoring temporarily unavailable".to_string(),
+                    value: i18n::text(
+                        "monitor.unavailable.network",
+                        "Network monitoring temporarily unavailable",
+                    ),
+                    raw_value: None,
+                    unit: None,
                 }]),
+                is_stale: false,
+                is_fallback: true,
             },
             "system" => SystemStats {
                 title: "System (Safe Mode)".to_string(),
@@ -170,8 +322,15 @@ impl ResilientMonitor {
                 progress_data: None,
                 generic_data: Some(vec![GenericData {
                     title: "Status".to_string(),
-                    value: "System monitoring temporarily unavailable".to_string(),
+                    value: i18n::text(
+                        "monitor.unavailable.system",
+                        "System monitoring temporarily unavailable",
+                    ),
+                    raw_value: None,
+                    unit: None,
                 }]),
+                is_stale: false,
+                is_fallback: true,
             },
             _ => SystemStats {
                 title: "Unknown (Safe Mode)".to_string(),
@@ -179,8 +338,15 @@ impl ResilientMonitor {
                 progress_data: None,
                 generic_data: Some(vec![GenericData {
                     title: "Error".to_string(),
-                    value: "Component temporarily unavailable".to_string(),
+                    value: i18n::text(
+                        "monitor.unavailable.other",
+                        "Component temporarily unavailable",
+                    ),
+                    raw_value: None,
+                    unit: None,
                 }]),
+                is_stale: false,
+                is_fallback: true,
             },
         }
     }
@@ -190,95 +356,289 @@ lazy_static::lazy_static! {
     static ref RESILIENT_MONITOR: Arc<Mutex<ResilientMonitor>> = Arc::new(Mutex::new(ResilientMonitor::new()));
 }
 
+static SAMPLER_STATES: once_cell::sync::Lazy<Mutex<HashMap<&'static str, SamplerState>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+static SAMPLERS_RUNNING: AtomicBool = AtomicBool::new(false);
+
+fn fetch_cpu() -> Result<SystemStats, String> {
+    super::cpu::get_cpu_stats()
+}
+
+fn fetch_memory() -> Result<SystemStats, String> {
+    Ok(super::memory::get_memory_stats())
+}
+
+fn fetch_storage() -> Result<SystemStats, String> {
+    super::storage::get_storage_stats().map_err(|e| e.to_string())
+}
+
+fn fetch_network() -> Result<SystemStats, String> {
+    super::network::get_network_stats()
+}
+
+fn fetch_system() -> Result<SystemStats, String> {
+    super::system::get_system_stats()
+}
+
 #[command]
 pub fn get_resilient_cpu_stats() -> Result<SystemStats, String> {
-    resilient_stat_fetch("cpu", || super::cpu::get_cpu_stats())
+    resilient_stat_fetch("cpu", fetch_cpu)
 }
 
 #[command]
 pub fn get_resilient_memory_stats() -> Result<SystemStats, String> {
-    resilient_stat_fetch("memory", || Ok(super::memory::get_memory_stats()))
+    resilient_stat_fetch("memory", fetch_memory)
 }
 
 #[command]
 pub fn get_resilient_storage_stats() -> Result<SystemStats, String> {
-    resilient_stat_fetch("storage", || {
-        super::storage::get_storage_stats().map_err(|e| e.to_string())
-    })
+    resilient_stat_fetch("storage", fetch_storage)
 }
 
 #[command]
 pub fn get_resilient_network_stats() -> Result<SystemStats, String> {
-    resilient_stat_fetch("network", || super::network::get_network_stats())
+    resilient_stat_fetch("network", fetch_network)
 }
 
 #[command]
 pub fn get_resilient_system_stats() -> Result<SystemStats, String> {
-    resilient_stat_fetch("system", || super::system::get_system_stats())
+    resilient_stat_fetch("system", fetch_system)
 }
 
 #[command]
 pub fn get_monitor_health() -> Result<MonitorHealth, String> {
-    let mut monitor = RESILIENT_MONITOR
-        .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+    health_snapshot()
+}
 
-    // Update health status
-    monitor.health_status.last_health_check = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
+/// Starts one sampler thread per subsystem, keeping the cache warm, and a
+/// supervisor that restarts any sampler that stops sending heartbeats.
+/// Health changes are pushed as `monitor-health-changed` events.
+/// Subsequent calls do nothing.
+pub fn start_samplers() {
+    if SAMPLERS_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
 
-    monitor.health_status.error_counts = monitor.error_counts.clone();
+    for (name, fetch_fn) in SAMPLERS {
+        spawn_sampler(name, fetch_fn);
+    }
 
-    Ok(monitor.health_status.clone())
+    std::thread::spawn(|| loop {
+        std::thread::sleep(SUPERVISE_INTERVAL);
+        supervise();
+    });
 }
 
-fn resilient_stat_fetch<F>(stat_type: &str, fetch_fn: F) -> Result<SystemStats, String>
-where
-    F: Fn() -> Result<SystemStats, String>,
-{
-    let mut monitor = RESILIENT_MONITOR
-        .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+fn spawn_sampler(name: &'static str, fetch_fn: Fetcher) {
+    let generation = match SAMPLER_STATES.lock() {
+        Ok(mut states) => {
+            let state = states.entry(name).or_insert_with(SamplerState::new);
+            state.generation += 1;
+            state.beat();
+            state.generation
+        }
+        Err(_) => return,
+    };
+
+    std::thread::spawn(move || {
+        let mut round: u64 = 0;
+        loop {
+            // Keeps beating while paused or idle so the supervisor leaves it
+            // alone. The system fetch scans every process, it waits for the
+            // user to come back.
+            let idle_skip =
+                user_idle::skips_round(round) || (name == "system" && user_idle::is_idle());
+            if !quiet_hours::is_paused() && !idle_skip {
+                let _ = refresh_stat(name, fetch_fn);
+            }
+            if !heartbeat(name, generation) {
+                // Replaced by the supervisor while this fetch was stuck
+                break;
+            }
+            round = round.wrapping_add(1);
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+}
 
-    // Check if we should use cached data
-    if monitor.should_use_cache(stat_type) {
-        if let Some(cached_stats) = monitor.get_cached_or_fallback(stat_type) {
-            return Ok(cached_stats);
+/// Records that a sampler is alive, false when it has been superseded
+fn heartbeat(name: &str, generation: u64) -> bool {
+    let Ok(mut states) = SAMPLER_STATES.lock() else {
+        return false;
+    };
+    match states.get_mut(name) {
+        Some(state) if state.generation == generation => {
+            state.beat();
+            true
         }
+        _ => false,
     }
+}
 
-    // Try to fetch fresh data with timeout protection
-    let fetch_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| fetch_fn()));
+/// Restarts the samplers whose fetch has been stuck for too long
+fn supervise() {
+    let wedged: Vec<(&'static str, Fetcher)> = match SAMPLER_STATES.lock() {
+        Ok(mut states) => SAMPLERS
+            .into_iter()
+            .filter(|(name, _)| match states.get_mut(name) {
+                Some(state) if state.is_wedged() => {
+                    state.restarts += 1;
+                    true
+                }
+                _ => false,
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    if wedged.is_empty() {
+        return;
+    }
 
-    match fetch_result {
+    if let Ok(mut monitor) = RESILIENT_MONITOR.lock() {
+        for (name, _) in &wedged {
+            monitor.record_error(name, "Sampler stopped responding");
+        }
+    }
+    for (name, fetch_fn) in wedged {
+        eprintln!("{} sampler stopped responding, restarting it", name);
+        spawn_sampler(name, fetch_fn);
+    }
+    emit_health();
+}
+
+fn sampler_statuses() -> Vec<SamplerStatus> {
+    let Ok(states) = SAMPLER_STATES.lock() else {
+        return Vec::new();
+    };
+    SAMPLERS
+        .iter()
+        .filter_map(|(name, _)| {
+            states.get(name).map(|state| SamplerStatus {
+                name: name.to_string(),
+                running: !state.is_wedged(),
+                last_heartbeat: state.last_heartbeat_ms,
+                restarts: state.restarts,
+            })
+        })
+        .collect()
+}
+
+fn health_snapshot() -> Result<MonitorHealth, String> {
+    let mut health = {
+        let mut monitor = RESILIENT_MONITOR
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+
+        // Update health status
+        monitor.health_status.last_health_check = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        monitor.health_status.error_counts = monitor.error_counts.clone();
+        monitor.health_status.clone()
+    };
+    health.samplers = sampler_statuses();
+    health.error_history = monitor_history::get_error_history();
+    health.chronic_issues = monitor_history::get_chronic_issues();
+
+    Ok(health)
+}
+
+fn emit_health() {
+    if let Ok(health) = health_snapshot() {
+        events::emit(events::MONITOR_HEALTH_CHANGED, health);
+    }
+}
+
+fn resilient_stat_fetch(stat_type: &str, fetch_fn: Fetcher) -> Result<SystemStats, String> {
+    {
+        let monitor = RESILIENT_MONITOR
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+
+        // Check if we should use cached data
+        if monitor.should_use_cache(stat_type) {
+            if let Some(cached_stats) = monitor.get_cached_or_fallback(stat_type) {
+                return Ok(cached_stats);
+            }
+        }
+    }
+
+    refresh_stat(stat_type, fetch_fn)
+}
+
+/// Fetches fresh data and updates the cache. The lock is not held during the
+/// fetch, so a stuck subsystem cannot block the others.
+fn refresh_stat(stat_type: &str, fetch_fn: Fetcher) -> Result<SystemStats, String> {
+    let fetch_result = std::panic::catch_unwind(fetch_fn);
+
+    let mut monitor = RESILIENT_MONITOR
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    let was_healthy = monitor.is_healthy(stat_type);
+
+    let result = match fetch_result {
         Ok(Ok(stats)) => {
             // Success - update cache and reset error count
             monitor.update_cache(stat_type.to_string(), stats.clone());
             monitor.reset_error_count(stat_type);
             Ok(stats)
         }
-        Ok(Err(_error)) => {
-            // Controlled error - record and try fallback
-            monitor.record_error(stat_type);
-
-            if let Some(cached_stats) = monitor.get_cached_or_fallback(stat_type) {
-                Ok(cached_stats) // Return cached data if available
-            } else {
-                let fallback_stats = monitor.create_fallback_stats(stat_type);
-                monitor.update_cache(stat_type.to_string(), fallback_stats.clone());
-                Ok(fallback_stats)
-            }
+        Ok(Err(error)) => {
+            // Controlled error - record and apply the subsystem's policy
+            monitor.record_error(stat_type, &error);
+            monitor.handle_failure(stat_type, error)
         }
         Err(_panic) => {
-            // Panic occurred - create safe fallback
-            monitor.record_error(stat_type);
-            let fallback_stats = monitor.create_fallback_stats(stat_type);
-            monitor.update_cache(stat_type.to_string(), fallback_stats.clone());
-            Ok(fallback_stats)
+            // Panic occurred - same as an error, the message is lost
+            let error = "Monitoring code panicked".to_string();
+            monitor.record_error(stat_type, &error);
+            monitor.handle_failure(stat_type, error)
         }
+    };
+
+    let health_changed = monitor.is_healthy(stat_type) != was_healthy;
+    drop(monitor);
+    if health_changed {
+        emit_health();
+    }
+
+    result
+}
+
+#[command]
+pub fn get_monitor_fallback_policies() -> Result<HashMap<String, FallbackPolicy>, String> {
+    let monitor = RESILIENT_MONITOR
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+
+    let policies = SAMPLERS
+        .iter()
+        .map(|(name, _)| (name.to_string(), monitor.policy(name)))
+        .collect();
+    Ok(policies)
+}
+
+#[command]
+pub fn set_monitor_fallback_policy(
+    subsystem: String,
+    policy: FallbackPolicy,
+) -> Result<(), String> {
+    if !SAMPLERS.iter().any(|(name, _)| *name == subsystem) {
+        return Err(format!("Unknown subsystem: {}", subsystem));
     }
+
+    let mut monitor = RESILIENT_MONITOR
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+
+    monitor.policies.insert(subsystem, policy);
+    let content = serde_json::to_string_pretty(&monitor.policies).map_err(|e| e.to_string())?;
+    std::fs::write(paths::app_data_file(POLICIES_FILE), content).map_err(|e| e.to_string())?;
+
+    Ok(())
 }
 
 #[command]
@@ -300,7 +660,60 @@ pub fn reset_monitor_health() -> Result<(), String> {
             .unwrap_or_default()
             .as_secs(),
         error_counts: HashMap::new(),
+        samplers: Vec::new(),
+        error_history: HashMap::new(),
+        chronic_issues: Vec::new(),
     };
+    drop(monitor);
+    monitor_history::clear();
+    emit_health();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_rejects_replaced_sampler() {
+        let mut state = SamplerState::new();
+        state.generation = 2;
+        SAMPLER_STATES.lock().unwrap().insert("test", state);
+
+        assert!(!heartbeat("test", 1));
+        assert!(heartbeat("test", 2));
+        assert!(!heartbeat("missing", 1));
+    }
+
+    #[test]
+    fn test_handle_failure_follows_policy() {
+        let mut monitor = ResilientMonitor::new();
+        monitor.policies.clear();
+        let mut stats = SystemStats::new("CPU");
+        stats.percentage = Some(42.0);
+        monitor.update_cache("cpu".to_string(), stats);
+
+        let cached = monitor
+            .handle_failure("cpu", "timeout".to_string())
+            .unwrap();
+        assert!(cached.is_stale);
+        assert_eq!(cached.percentage, Some(42.0));
+
+        monitor
+            .policies
+            .insert("cpu".to_string(), FallbackPolicy::ReturnFallback);
+        let fallback = monitor
+            .handle_failure("cpu", "timeout".to_string())
+            .unwrap();
+        assert!(fallback.is_fallback);
+
+        monitor
+            .policies
+            .insert("cpu".to_string(), FallbackPolicy::ReturnError);
+        assert_eq!(
+            monitor.handle_failure("cpu", "timeout".to_string()).err(),
+            Some("timeout".to_string())
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::models::system_stats::{
+    DiscordConfig, DisplaySettings, MonitoringPauseStatus, MqttConfig, ObsConfig, QuietHoursConfig,
+    RemoteAgent, RemoteMonitorStatus, RestApiStatus, UpdateConfig, UpdateStatus, UserIdleStatus,
+};
+use crate::services::{
+    discord_presence, display_settings, mqtt, obs, quiet_hours, remote_monitor, rest_api,
+    stats_line, updater, user_idle,
+};
+use crate::shared::i18n;
+use tauri::{command, AppHandle};
+
+#[command]
+pub fn get_display_settings() -> DisplaySettings {
+    display_settings::get_settings()
+}
+
+/// Locale and units used to format the values of every monitor
+#[command]
+pub fn set_display_settings(settings: DisplaySettings) -> Result<DisplaySettings, String> {
+    display_settings::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// Languages backend messages can be shown in
+#[command]
+pub fn get_available_languages() -> Vec<String> {
+    i18n::available_languages()
+}
+
+/// Switches error messages and optimization names to another language
+#[command]
+pub fn set_language(language: String) -> Result<DisplaySettings, String> {
+    let settings = DisplaySettings {
+        language,
+        ..display_settings::get_settings()
+    };
+    display_settings::set_settings(settings).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_rest_api_status() -> RestApiStatus {
+    rest_api::get_status()
+}
+
+/// Turns the localhost REST API on or off; takes effect immediately.
+/// LAN access is kept as it was when not given.
+#[command]
+pub fn set_rest_api_config(
+    enabled: bool,
+    port: u16,
+    allow_lan: Option<bool>,
+) -> Result<RestApiStatus, String> {
+    let allow_lan = allow_lan.unwrap_or(rest_api::get_status().config.allow_lan);
+    rest_api::set_config(enabled, port, allow_lan).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn regenerate_rest_api_token() -> Result<RestApiStatus, String> {
+    rest_api::regenerate_token().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_mqtt_config() -> MqttConfig {
+    mqtt::get_config()
+}
+
+/// Broker and interval for publishing stats; the publisher picks changes up
+/// within a second
+#[command]
+pub fn set_mqtt_config(config: MqttConfig) -> Result<MqttConfig, String> {
+    mqtt::set_config(config).map_err(|e| e.to_string())
+}
+
+/// Paired agent, connection state and the latest stats it sent
+#[command]
+pub fn get_remote_monitor_status() -> RemoteMonitorStatus {
+    remote_monitor::get_status()
+}
+
+/// Starts watching Aura on another PC, which needs its REST API enabled with
+/// LAN access
+#[command]
+pub fn pair_remote_agent(agent: RemoteAgent) -> Result<RemoteMonitorStatus, String> {
+    remote_monitor::pair(agent).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn unpair_remote_agent() -> Result<RemoteMonitorStatus, String> {
+    remote_monitor::unpair().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_obs_config() -> ObsConfig {
+    obs::get_config()
+}
+
+/// obs-websocket connection, text source and scenes to switch on game start
+#[command]
+pub fn set_obs_config(config: ObsConfig) -> Result<ObsConfig, String> {
+    obs::set_config(config).map_err(|e| e.to_string())
+}
+
+/// The stats summary shown in the OBS text source
+#[command]
+pub fn get_stats_line() -> String {
+    stats_line::stats_line()
+}
+
+#[command]
+pub fn get_discord_config() -> DiscordConfig {
+    discord_presence::get_config()
+}
+
+/// Rich Presence for the running game; games can opt out in their profile
+#[command]
+pub fn set_discord_config(config: DiscordConfig) -> Result<DiscordConfig, String> {
+    discord_presence::set_config(config).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_quiet_hours() -> QuietHoursConfig {
+    quiet_hours::get_config()
+}
+
+/// Daily window and processes during which background monitoring pauses
+#[command]
+pub fn set_quiet_hours(config: QuietHoursConfig) -> Result<QuietHoursConfig, String> {
+    quiet_hours::set_config(config).map_err(|e| e.to_string())
+}
+
+/// Pauses sampling, alerts and watchdogs for the given minutes, or until
+/// resumed when none are given
+#[command]
+pub fn pause_monitoring(minutes: Option<u32>) -> MonitoringPauseStatus {
+    quiet_hours::pause(minutes)
+}
+
+#[command]
+pub fn resume_monitoring() -> MonitoringPauseStatus {
+    quiet_hours::resume()
+}
+
+#[command]
+pub fn get_monitoring_pause_status() -> MonitoringPauseStatus {
+    quiet_hours::get_status()
+}
+
+/// Time since the last input and whether monitoring is throttled for it
+#[command]
+pub fn get_user_idle_status() -> UserIdleStatus {
+    user_idle::get_status()
+}
+
+#[command]
+pub fn get_update_config() -> UpdateConfig {
+    updater::get_config()
+}
+
+/// Turning automatic checks off is the opt-out; manual checks still work
+#[command]
+pub fn set_update_config(config: UpdateConfig) -> Result<UpdateConfig, String> {
+    updater::set_config(config).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_update_status() -> UpdateStatus {
+    updater::get_status()
+}
+
+#[command]
+pub async fn check_for_updates() -> Result<UpdateStatus, String> {
+    updater::check().map_err(|e| e.to_string())
+}
+
+/// Downloads and verifies the installer of the release found by the last check
+#[command]
+pub async fn download_update() -> Result<UpdateStatus, String> {
+    updater::download().map_err(|e| e.to_string())
+}
+
+/// Starts the verified installer, then quits on Windows so it can replace
+/// the files, or restarts into the new AppImage on Linux
+#[command]
+pub fn install_update(app: AppHandle) -> Result<(), String> {
+    updater::install().map_err(|e| e.to_string())?;
+    if cfg!(target_os = "windows") {
+        app.exit(0);
+    } else {
+        app.restart();
+    }
+    Ok(())
+}
+
+#[command]
+pub fn restart_app(app: AppHandle) {
+    app.restart();
+}
@@ -1,4 +1,11 @@
-use crate::models::system_stats::{GenericData, ProgressData, SystemStats};
+use crate::models::system_stats::{
+    CleanupCandidate, DriveSpaceStatus, DriveWatchConfig, FileCleanupResult, FolderMoveStatus,
+    GenericData, ProgressData, RecycleBinInfo, SystemStats,
+};
+use crate::services::{disk_map, drive_watch, file_cleanup, folder_move, recycle_bin};
+use crate::utils::bytes::{format_bytes_in, unit_power};
+use crate::utils::locale::format_number;
+use crate::utils::temperature::convert_temperature;
 use anyhow;
 use std::process::Command;
 use std::sync::{Arc, Mutex};
@@ -10,8 +17,6 @@ use thiserror::Error;
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
-const TB: f64 = 1024.0 * 1024.0 * 1024.0 * 1024.0;
-const GB: f64 = 1024.0 * 1024.0 * 1024.0;
 const CACHE_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
 
 #[derive(Error, Debug)]
@@ -56,7 +61,7 @@ lazy_static::lazy_static! {
 
 #[derive(Clone, Debug)]
 struct DriveInfo {
-    drive_letter: String,
+    disk_id: String, // as disk_map identifies the disk
     model: String,
     interface: String,
     drive_type: String,
@@ -152,7 +157,7 @@ fn get_drive_models() -> Vec<DriveInfo> {
                         };
 
                         drives.push(DriveInfo {
-                            drive_letter: drive_index.to_string(),
+                            disk_id: drive_index.to_string(),
                             model: format!("{}{}", model, size_info),
                             interface: interface_clean.to_string(),
                             drive_type: drive_type.to_string(),
@@ -166,7 +171,7 @@ fn get_drive_models() -> Vec<DriveInfo> {
     // If no drives found through WMIC, add fallback info
     if drives.is_empty() {
         drives.push(DriveInfo {
-            drive_letter: "0".to_string(),
+            disk_id: "0".to_string(),
             model: "Unknown Storage Device".to_string(),
             interface: "SATA".to_string(),
             drive_type: "Storage".to_string(),
@@ -178,7 +183,42 @@ fn get_drive_models() -> Vec<DriveInfo> {
 
 #[cfg(not(target_os = "windows"))]
 fn get_drive_models() -> Vec<DriveInfo> {
-    Vec::new() // Placeholder for non-Windows systems
+    let Ok(entries) = std::fs::read_dir("/sys/block") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        // Only disks have a device folder, loop, zram and dm devices don't
+        .filter(|entry| entry.path().join("device").exists())
+        .map(|entry| {
+            let disk_id = entry.file_name().to_string_lossy().into_owned();
+            let read = |file: &str| {
+                std::fs::read_to_string(entry.path().join(file))
+                    .map(|value| value.trim().to_string())
+                    .unwrap_or_default()
+            };
+            let model = read("device/model");
+            let is_nvme = disk_id.starts_with("nvme");
+            let drive_type = if read("queue/rotational") == "1" {
+                "HDD"
+            } else if is_nvme {
+                "NVMe SSD"
+            } else {
+                "SATA SSD"
+            };
+            DriveInfo {
+                model: if model.is_empty() {
+                    "Unknown Storage Device".to_string()
+                } else {
+                    model
+                },
+                interface: if is_nvme { "NVMe" } else { "SCSI" }.to_string(),
+                drive_type: drive_type.to_string(),
+                disk_id,
+            }
+        })
+        .collect()
 }
 
 #[command]
@@ -202,7 +242,7 @@ pub fn get_storage_stats() -> std::result::Result<SystemStats, String> {
     let mut disk_details = Vec::new();
     let mut progress_data = Vec::new();
 
-    for (index, disk) in disks.iter().enumerate() {
+    for disk in disks.iter() {
         let disk_total = disk.total_space();
         let disk_available = disk.available_space();
         let disk_used = disk_total.saturating_sub(disk_available);
@@ -212,16 +252,23 @@ pub fn get_storage_stats() -> std::result::Result<SystemStats, String> {
             0.0
         };
 
-        // Try to find model info for this drive
-        let drive_info = drive_models
-            .get(index)
+        // Model info of the physical disk this volume lives on, several
+        // partitions of one disk share it
+        let disk_id = disk_map::disk_of_volume(disk.mount_point(), &disk.name().to_string_lossy());
+        let drive_info = disk_id
+            .as_ref()
+            .and_then(|id| drive_models.iter().find(|drive| drive.disk_id == *id))
             .cloned()
             .unwrap_or_else(|| DriveInfo {
-                drive_letter: index.to_string(),
+                disk_id: disk_id.clone().unwrap_or_default(),
                 model: "Unknown Drive".to_string(),
                 interface: "Unknown".to_string(),
                 drive_type: "Storage".to_string(),
             });
+        let temperature = disk_id
+            .as_deref()
+            .and_then(disk_map::disk_temperature)
+            .map(convert_temperature);
 
         // Create progress data for drive navigation
         let drive_title = format!("{} - {}", disk.name().to_string_lossy(), drive_info.model);
@@ -229,7 +276,9 @@ pub fn get_storage_stats() -> std::result::Result<SystemStats, String> {
         progress_data.push(ProgressData {
             title: drive_title.clone(),
             value: disk_usage_pct as f32,
-            temperature: Some(35.0 + (index as f32 * 5.0)), // Simulated drive temperature
+            temperature,
+            raw_value: Some(disk_used as f64),
+            unit: Some("B".to_string()),
         });
 
         // Detailed disk information
@@ -248,6 +297,8 @@ pub fn get_storage_stats() -> std::result::Result<SystemStats, String> {
                 drive_info.drive_type,
                 drive_info.interface
             ),
+            raw_value: None,
+            unit: None,
         });
     }
 
@@ -255,22 +306,32 @@ pub fn get_storage_stats() -> std::result::Result<SystemStats, String> {
         GenericData {
             title: "Total Storage".to_string(),
             value: format_storage(info.total),
+            raw_value: Some(info.total as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Used Storage".to_string(),
             value: format_storage(info.used),
+            raw_value: Some(info.used as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Free Storage".to_string(),
             value: format_storage(info.free),
+            raw_value: Some(info.free as f64),
+            unit: Some("B".to_string()),
         },
         GenericData {
             title: "Usage Percentage".to_string(),
-            value: format!("{:.1}%", info.usage_percentage),
+            value: format!("{}%", format_number(info.usage_percentage as f64, 1)),
+            raw_value: Some(info.usage_percentage as f64),
+            unit: Some("%".to_string()),
         },
         GenericData {
             title: "Disk Count".to_string(),
             value: disks.len().to_string(),
+            raw_value: None,
+            unit: None,
         },
     ];
 
@@ -281,9 +342,86 @@ pub fn get_storage_stats() -> std::result::Result<SystemStats, String> {
         percentage: Some(info.usage_percentage),
         progress_data: Some(progress_data),
         generic_data: Some(generic_data),
+        is_stale: false,
+        is_fallback: false,
     })
 }
 
+#[command]
+pub fn get_drive_watch_config() -> DriveWatchConfig {
+    drive_watch::get_config()
+}
+
+/// The drive games are installed on and the free space to warn below
+#[command]
+pub fn set_drive_watch_config(
+    config: DriveWatchConfig,
+) -> std::result::Result<DriveWatchConfig, String> {
+    drive_watch::set_config(config).map_err(|e| e.to_string())
+}
+
+/// Free space and growth rate of every drive
+#[command]
+pub fn get_drive_space_status() -> Vec<DriveSpaceStatus> {
+    drive_watch::get_status()
+}
+
+/// Recycle Bin size of every drive whose bin holds something
+#[command]
+pub fn get_recycle_bin_usage() -> Vec<RecycleBinInfo> {
+    recycle_bin::get_usage()
+}
+
+/// Empties the Recycle Bin of one drive, or of all of them without one.
+/// Returns the bytes freed.
+#[command]
+pub fn empty_recycle_bin(drive: Option<String>) -> std::result::Result<u64, String> {
+    let freed = recycle_bin::empty(drive.as_deref()).map_err(|e| e.to_string())?;
+    // The free space changed, don't show the cached figures
+    if let Ok(mut cache) = STORAGE_CACHE.lock() {
+        cache.stats = None;
+    }
+    Ok(freed)
+}
+
+/// Moves a folder, usually a game, into `destination_dir` on another drive
+/// and leaves a junction at the old path. Progress comes with the
+/// folder-move-progress event.
+#[command]
+pub fn start_folder_move(
+    source: String,
+    destination_dir: String,
+) -> std::result::Result<FolderMoveStatus, String> {
+    folder_move::start(&source, &destination_dir).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn cancel_folder_move() -> FolderMoveStatus {
+    folder_move::cancel()
+}
+
+#[command]
+pub fn get_folder_move_status() -> FolderMoveStatus {
+    folder_move::get_status()
+}
+
+/// Large duplicate files, old installers and driver and runtime packages
+/// that can probably be deleted. Can take a while on full user folders.
+#[command]
+pub async fn scan_cleanup_candidates() -> Vec<CleanupCandidate> {
+    file_cleanup::scan()
+}
+
+/// Deletes candidates of the last scan; other paths are skipped
+#[command]
+pub fn delete_cleanup_candidates(paths: Vec<String>) -> FileCleanupResult {
+    let result = file_cleanup::delete(&paths);
+    if let Ok(mut cache) = STORAGE_CACHE.lock() {
+        cache.stats = None;
+    }
+    result
+}
+
 #[derive(Clone)]
 struct StorageInfo {
     used: u64,
@@ -319,18 +457,17 @@ fn calculate_storage_usage(disks: &Disks) -> Result<StorageInfo> {
 }
 
 fn format_storage(bytes: u64) -> String {
-    let bytes = bytes as f64;
-    if bytes >= TB {
-        format!("{:.2} TB", bytes / TB)
-    } else {
-        format!("{:.2} GB", bytes / GB)
-    }
+    // Never below GB, drives are too big for smaller units to be readable
+    format_bytes_in(bytes, unit_power(bytes, 3, 4), 2)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const TB: f64 = 1024.0 * 1024.0 * 1024.0 * 1024.0;
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
     #[test]
     fn test_format_storage() {
         assert_eq!(format_storage(2 * TB as u64), "2.00 TB");
@@ -1,7 +1,15 @@
 use sysinfo::System;
 use tauri::command;
 
-use crate::models::system_stats::{GenericData, SystemStats};
+use crate::models::gpu_info::GpuMemoryAlertConfig;
+use crate::models::system_stats::{
+    GenericData, MaintenanceConfig, MaintenanceRun, MaintenanceTask, SystemHealth, SystemStats,
+    TemperatureSummary,
+};
+use crate::services::{
+    cpu_overhead, maintenance, platform_features, system_health, temperature_history,
+};
+use crate::utils::locale::format_number;
 
 #[command]
 pub fn get_system_stats() -> std::result::Result<SystemStats, String> {
@@ -21,7 +29,14 @@ pub fn get_system_stats() -> std::result::Result<SystemStats, String> {
         format!("{} minutes", minutes)
     };
 
-    let generic_data = vec![
+    let features = platform_features::get_platform_features(&system);
+    let on_off = |enabled: Option<bool>| match enabled {
+        Some(true) => "Enabled".to_string(),
+        Some(false) => "Disabled".to_string(),
+        None => "Unknown".to_string(),
+    };
+
+    let mut generic_data = vec![
         GenericData {
             title: "OS".to_string(),
             value: format!(
@@ -29,25 +44,132 @@ pub fn get_system_stats() -> std::result::Result<SystemStats, String> {
                 System::name().unwrap_or("Unknown".to_string()),
                 System::os_version().unwrap_or("Unknown".to_string())
             ),
+            raw_value: None,
+            unit: None,
         },
         GenericData {
             title: "Uptime".to_string(),
             value: uptime_str,
+            raw_value: Some(uptime as f64),
+            unit: Some("s".to_string()),
         },
         GenericData {
             title: "CPU Cores".to_string(),
             value: system.cpus().len().to_string(),
+            raw_value: None,
+            unit: None,
         },
         GenericData {
             title: "Hostname".to_string(),
             value: System::host_name().unwrap_or("Unknown".to_string()),
+            raw_value: None,
+            unit: None,
         },
     ];
 
+    generic_data.push(GenericData {
+        title: "Virtualization".to_string(),
+        value: if features.virtualization_supported || features.hypervisor_present {
+            on_off(features.virtualization_enabled)
+        } else {
+            "Not supported".to_string()
+        },
+        raw_value: None,
+        unit: None,
+    });
+    generic_data.push(GenericData {
+        title: "SMT".to_string(),
+        value: format!(
+            "{} ({} cores, {} threads)",
+            on_off(Some(features.smt_enabled)),
+            features.physical_cores,
+            features.logical_cores
+        ),
+        raw_value: None,
+        unit: None,
+    });
+    generic_data.push(GenericData {
+        title: "Secure Boot".to_string(),
+        value: on_off(features.secure_boot_enabled),
+        raw_value: None,
+        unit: None,
+    });
+
+    // Rates since the previous refresh of the panel
+    let (context_switches, system_calls) = cpu_overhead::kernel_event_rates();
+    for (title, rate) in [
+        ("Context Switches", context_switches),
+        ("System Calls", system_calls),
+    ] {
+        if let Some(rate) = rate {
+            generic_data.push(GenericData {
+                title: title.to_string(),
+                value: format!("{}/s", format_number(rate as f64, 0)),
+                raw_value: Some(rate as f64),
+                unit: Some("/s".to_string()),
+            });
+        }
+    }
+
     Ok(SystemStats {
         title: "System Info".to_string(),
         percentage: None,
         progress_data: None,
         generic_data: Some(generic_data),
+        is_stale: false,
+        is_fallback: false,
     })
 }
+
+/// Whether a restart is recommended, with the reasons
+#[command]
+pub async fn get_system_health() -> Result<SystemHealth, String> {
+    Ok(system_health::get_health())
+}
+
+/// Session min/avg/max of every temperature sensor
+#[command]
+pub fn get_temperature_summary() -> Result<TemperatureSummary, String> {
+    Ok(temperature_history::get_summary())
+}
+
+#[command]
+pub fn reset_temperature_summary() -> Result<TemperatureSummary, String> {
+    temperature_history::reset();
+    Ok(temperature_history::get_summary())
+}
+
+#[command]
+pub fn get_gpu_memory_alert_config() -> GpuMemoryAlertConfig {
+    temperature_history::get_alert_config()
+}
+
+#[command]
+pub fn set_gpu_memory_alert_config(
+    config: GpuMemoryAlertConfig,
+) -> Result<GpuMemoryAlertConfig, String> {
+    temperature_history::set_alert_config(config).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_maintenance_config() -> MaintenanceConfig {
+    maintenance::get_config()
+}
+
+#[command]
+pub fn set_maintenance_config(config: MaintenanceConfig) -> Result<MaintenanceConfig, String> {
+    maintenance::set_config(config).map_err(|e| e.to_string())
+}
+
+/// Runs the given maintenance tasks right away, the configured ones when None
+#[command]
+pub async fn run_maintenance_now(
+    tasks: Option<Vec<MaintenanceTask>>,
+) -> Result<MaintenanceRun, String> {
+    maintenance::run_now(tasks).map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_maintenance_log() -> Vec<MaintenanceRun> {
+    maintenance::get_log()
+}
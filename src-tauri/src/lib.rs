@@ -22,7 +22,8 @@ pub use commands::{
     optimizations::{disable_game_dvr, optimize_time_resolution},
     process::open_file_location,
     processes::{
-        get_processes, get_running_processes, kill_process, resume_process, suspend_process,
+        get_processes, get_running_processes, kill_process, refresh_process, resume_process,
+        suspend_process,
     },
     storage::get_storage_stats,
     system::get_system_stats,
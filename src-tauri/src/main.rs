@@ -11,32 +11,109 @@ mod utils;
 use aura_lib::ui::window::setup_window_effects;
 
 // Import local commands
-use commands::cpu::get_cpu_stats;
-use commands::gpu::get_gpu_stats;
-use commands::memory::get_memory_stats;
-use commands::network::get_network_stats;
+use commands::audio::{
+    get_audio_sessions, get_focus_mode, set_audio_session_mute, set_audio_session_volume,
+    set_focus_mode,
+};
+use commands::benchmark::{
+    clear_input_latency_results, get_input_latency_results, get_stress_test_status,
+    measure_input_latency, run_memory_benchmark, start_stress_test, stop_stress_test,
+};
+use commands::cpu::{
+    get_cpu_heatmap, get_cpu_interrupt_time, get_cpu_stats, get_cpu_tuning_status,
+};
+use commands::devices::{get_bluetooth_devices, get_usb_devices};
+use commands::games::{
+    delete_game_profile, get_cooling_status, get_frame_limiters, get_game_profiles, get_job_groups,
+    get_port_forwarding_config, get_port_forwarding_status, get_qos_policies, get_session_report,
+    get_tracked_games, launch_game, reset_session_report, save_game_profile, set_job_group_limits,
+    set_port_forwarding_config, terminate_job_group, track_game_process, untrack_game_process,
+};
+use commands::gpu::{
+    apply_refresh_profile, evict_gpu_processes, get_displays, get_gpu_priority, get_gpu_stats,
+    get_gpu_tuning, get_refresh_profiles, get_vram_heavy_processes, prioritize_game_gpu,
+    set_gpu_priority, set_refresh_profiles, set_refresh_rate,
+};
+use commands::memory::{get_memory_breakdown, get_memory_stats};
+use commands::network::{
+    get_data_cap_config, get_data_usage, get_network_stats, set_data_cap_config,
+};
 use commands::optimization_commands::{
-    apply_optimization, get_available_optimizations, get_current_platform, revert_optimization,
+    add_defender_exclusions, apply_optimization, apply_optimization_profile,
+    cancel_scheduled_restart, delete_custom_optimization, get_available_optimizations,
+    get_current_platform, get_custom_optimizations, get_defender_exclusions,
+    get_direct_storage_status, get_hosts_blocklist_status, get_network_adapters,
+    get_optimization_history, get_pending_restart_items, get_timer_keeper, get_timer_resolution,
+    get_vbs_status, get_windows_update_state, pause_windows_update, remove_defender_exclusions,
+    reset_adapter_mtu, resume_windows_update, revert_optimization, save_custom_optimization,
+    schedule_restart, set_adapter_mtu, set_memory_integrity, set_timer_keeper,
 };
 use commands::optimizations::{disable_game_dvr, optimize_time_resolution};
-use commands::process::open_file_location;
+use commands::plugins::{get_plugin_stats, get_plugins, set_plugin_enabled};
+use commands::process::{open_file_location, open_in_terminal};
 use commands::processes::{
-    boost_process_for_gaming, get_cpu_core_count, get_detailed_process_info, get_process_affinity,
-    get_processes, get_running_processes, kill_process, resume_process, set_process_affinity,
-    suspend_process,
+    block_process_network, boost_process_for_gaming, cleanup_orphaned_game_processes,
+    close_process_windows, find_orphaned_game_processes, focus_process_window, get_anti_cheat_info,
+    get_cpu_core_count, get_detailed_process_info, get_handle_leak_alerts,
+    get_network_blocked_apps, get_process_affinity, get_process_env_vars, get_process_history,
+    get_process_priority_boost, get_process_report, get_process_services,
+    get_process_watcher_backend, get_process_windows, get_processes, get_running_processes,
+    is_process_hung, kill_process, minimize_process_windows, refresh_process, restart_process,
+    restore_process_windows, resume_process, set_process_affinity, set_process_priority_boost,
+    suspend_process, unblock_process_network,
 };
+use commands::readiness::{get_gaming_services_status, run_readiness_check};
 use commands::resilient_monitor::{
-    get_monitor_health, get_resilient_cpu_stats, get_resilient_memory_stats,
-    get_resilient_network_stats, get_resilient_storage_stats, get_resilient_system_stats,
-    reset_monitor_health,
+    get_monitor_fallback_policies, get_monitor_health, get_resilient_cpu_stats,
+    get_resilient_memory_stats, get_resilient_network_stats, get_resilient_storage_stats,
+    get_resilient_system_stats, reset_monitor_health, set_monitor_fallback_policy,
+};
+use commands::settings::{
+    check_for_updates, download_update, get_available_languages, get_discord_config,
+    get_display_settings, get_monitoring_pause_status, get_mqtt_config, get_obs_config,
+    get_quiet_hours, get_remote_monitor_status, get_rest_api_status, get_stats_line,
+    get_update_config, get_update_status, get_user_idle_status, install_update, pair_remote_agent,
+    pause_monitoring, regenerate_rest_api_token, restart_app, resume_monitoring,
+    set_discord_config, set_display_settings, set_language, set_mqtt_config, set_obs_config,
+    set_quiet_hours, set_rest_api_config, set_update_config, unpair_remote_agent,
+};
+use commands::storage::{
+    cancel_folder_move, delete_cleanup_candidates, empty_recycle_bin, get_drive_space_status,
+    get_drive_watch_config, get_folder_move_status, get_recycle_bin_usage, get_storage_stats,
+    scan_cleanup_candidates, set_drive_watch_config, start_folder_move,
+};
+use commands::system::{
+    get_gpu_memory_alert_config, get_maintenance_config, get_maintenance_log, get_system_health,
+    get_system_stats, get_temperature_summary, reset_temperature_summary, run_maintenance_now,
+    set_gpu_memory_alert_config, set_maintenance_config,
 };
-use commands::storage::get_storage_stats;
-use commands::system::get_system_stats;
 use tauri::Manager;
 
 fn main() {
     tauri::Builder::default()
         .setup(|app| {
+            shared::events::init(app.handle().clone());
+            services::recovery::start();
+            services::display_settings::start();
+            services::quiet_hours::start();
+            services::user_idle::start();
+            services::process_watcher::start();
+            services::launch_rules::start();
+            services::audio_service::start_focus_mode();
+            services::timer_resolution::start_keeper();
+            commands::resilient_monitor::start_samplers();
+            services::temperature_history::start();
+            services::drive_watch::start();
+            services::hardware_watch::start();
+            services::data_usage::start();
+            services::maintenance::start();
+            services::rest_api::start();
+            services::mqtt::start();
+            services::remote_monitor::start();
+            services::obs::start();
+            services::discord_presence::start();
+            services::updater::start();
+
             let window = app.get_webview_window("main").unwrap();
             setup_window_effects(&window).expect("Failed to apply window effects");
             Ok(())
@@ -46,7 +123,11 @@ fn main() {
             get_memory_stats,
             get_storage_stats,
             get_network_stats,
+            get_data_usage,
+            get_data_cap_config,
+            set_data_cap_config,
             get_system_stats,
+            get_system_health,
             get_resilient_cpu_stats,
             get_resilient_memory_stats,
             get_resilient_storage_stats,
@@ -54,6 +135,8 @@ fn main() {
             get_resilient_system_stats,
             get_monitor_health,
             reset_monitor_health,
+            get_monitor_fallback_policies,
+            set_monitor_fallback_policy,
             get_detailed_process_info,
             get_processes,
             get_running_processes,
@@ -64,15 +147,171 @@ fn main() {
             kill_process,
             suspend_process,
             resume_process,
+            block_process_network,
+            unblock_process_network,
+            get_network_blocked_apps,
+            refresh_process,
+            get_process_windows,
+            minimize_process_windows,
+            restore_process_windows,
+            focus_process_window,
+            close_process_windows,
+            is_process_hung,
+            restart_process,
+            find_orphaned_game_processes,
+            cleanup_orphaned_game_processes,
+            get_handle_leak_alerts,
+            get_process_env_vars,
+            get_process_watcher_backend,
+            track_game_process,
+            untrack_game_process,
+            get_tracked_games,
+            get_session_report,
+            reset_session_report,
+            get_game_profiles,
+            save_game_profile,
+            delete_game_profile,
+            launch_game,
+            get_audio_sessions,
+            set_audio_session_mute,
+            set_audio_session_volume,
+            get_focus_mode,
+            set_focus_mode,
             open_file_location,
+            open_in_terminal,
             disable_game_dvr,
             optimize_time_resolution,
             get_gpu_stats,
+            get_usb_devices,
+            get_bluetooth_devices,
+            get_gaming_services_status,
+            run_readiness_check,
+            get_vram_heavy_processes,
+            evict_gpu_processes,
             get_available_optimizations,
             apply_optimization,
             revert_optimization,
             get_current_platform,
+            pause_windows_update,
+            resume_windows_update,
+            get_windows_update_state,
+            get_defender_exclusions,
+            add_defender_exclusions,
+            remove_defender_exclusions,
+            get_hosts_blocklist_status,
+            get_network_adapters,
+            set_adapter_mtu,
+            reset_adapter_mtu,
+            get_vbs_status,
+            set_memory_integrity,
+            get_timer_resolution,
+            get_timer_keeper,
+            set_timer_keeper,
+            get_custom_optimizations,
+            save_custom_optimization,
+            delete_custom_optimization,
+            get_optimization_history,
+            apply_optimization_profile,
+            get_pending_restart_items,
+            schedule_restart,
+            cancel_scheduled_restart,
+            get_direct_storage_status,
+            get_process_history,
+            get_process_report,
+            get_process_services,
+            get_process_priority_boost,
+            set_process_priority_boost,
+            get_gpu_priority,
+            set_gpu_priority,
+            prioritize_game_gpu,
+            get_displays,
+            set_refresh_rate,
+            get_refresh_profiles,
+            set_refresh_profiles,
+            apply_refresh_profile,
+            get_display_settings,
+            set_display_settings,
+            get_available_languages,
+            set_language,
+            get_plugins,
+            get_plugin_stats,
+            set_plugin_enabled,
+            get_rest_api_status,
+            set_rest_api_config,
+            regenerate_rest_api_token,
+            get_mqtt_config,
+            set_mqtt_config,
+            get_remote_monitor_status,
+            pair_remote_agent,
+            unpair_remote_agent,
+            get_obs_config,
+            set_obs_config,
+            get_stats_line,
+            get_discord_config,
+            set_discord_config,
+            get_quiet_hours,
+            set_quiet_hours,
+            pause_monitoring,
+            resume_monitoring,
+            get_monitoring_pause_status,
+            get_update_config,
+            set_update_config,
+            get_update_status,
+            check_for_updates,
+            download_update,
+            install_update,
+            restart_app,
+            start_stress_test,
+            stop_stress_test,
+            get_stress_test_status,
+            run_memory_benchmark,
+            measure_input_latency,
+            get_input_latency_results,
+            clear_input_latency_results,
+            get_anti_cheat_info,
+            get_job_groups,
+            set_job_group_limits,
+            terminate_job_group,
+            get_temperature_summary,
+            reset_temperature_summary,
+            get_gpu_memory_alert_config,
+            set_gpu_memory_alert_config,
+            get_maintenance_config,
+            set_maintenance_config,
+            run_maintenance_now,
+            get_maintenance_log,
+            get_cooling_status,
+            get_frame_limiters,
+            get_port_forwarding_config,
+            set_port_forwarding_config,
+            get_port_forwarding_status,
+            get_qos_policies,
+            get_user_idle_status,
+            get_gpu_tuning,
+            get_cpu_tuning_status,
+            get_cpu_heatmap,
+            get_cpu_interrupt_time,
+            get_memory_breakdown,
+            get_drive_watch_config,
+            set_drive_watch_config,
+            get_drive_space_status,
+            get_recycle_bin_usage,
+            empty_recycle_bin,
+            start_folder_move,
+            cancel_folder_move,
+            get_folder_move_status,
+            scan_cleanup_candidates,
+            delete_cleanup_candidates,
         ])
-        .run(tauri::generate_context!())
-        .expect("Errore nell'avviare l'applicazione");
+        .build(tauri::generate_context!())
+        .expect("Errore nell'avviare l'applicazione")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Resume suspended processes, unmute focus-mode apps and give
+                // the cooling profile and timer resolution back to the system
+                services::recovery::undo_all();
+                // Crashes are covered by the lease, a clean exit removes them now
+                services::port_forwarding::close_all();
+            }
+        });
 }
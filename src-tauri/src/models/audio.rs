@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// An audio session of a process on one of the output devices
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AudioSession {
+    pub pid: u32,
+    pub name: String,
+    pub display_name: String,
+    pub volume: f32, // 0.0 - 1.0
+    pub is_muted: bool,
+    pub is_playing: bool,
+}
+
+/// Apps muted automatically while a tracked game is running
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FocusModeConfig {
+    pub enabled: bool,
+    /// Executable names, matched case-insensitively with or without extension
+    #[serde(default)]
+    pub muted_apps: Vec<String>,
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings of a stress test run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StressTestConfig {
+    pub threads: Option<usize>, // None loads every logical core
+    pub duration_secs: u64,
+    pub include_memory: bool, // also fills and verifies a block of RAM
+    pub memory_mb: u64,
+    pub max_temperature: f32, // °C, the test stops above it
+}
+
+impl Default for StressTestConfig {
+    fn default() -> Self {
+        Self {
+            threads: None,
+            duration_secs: 300,
+            include_memory: false,
+            memory_mb: 1024,
+            max_temperature: 95.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum StressTestOutcome {
+    Completed,
+    Stopped,                            // stopped by the user
+    ThermalLimit { temperature: f32 },  // preferred scale
+    ComputationError { thread: usize }, // a worker got a wrong result
+    MemoryError { errors: u64 },
+}
+
+/// Live state of the stress test, also pushed with the
+/// stress-test-progress event once per second
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct StressTestStatus {
+    pub running: bool,
+    pub threads: usize,
+    pub elapsed_secs: u64,
+    pub duration_secs: u64,
+    pub cpu_usage: Option<f32>,
+    pub temperature: Option<f32>,          // preferred scale
+    pub max_temperature_seen: Option<f32>, // preferred scale
+    pub clock_mhz: Option<u64>,
+    pub iterations: u64, // verified work units over all threads
+    pub outcome: Option<StressTestOutcome>,
+}
+
+/// A populated memory slot as reported by SMBIOS
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MemoryModuleSpeed {
+    pub locator: String, // e.g. DIMM_A1
+    pub memory_type: Option<String>,
+    pub rated_mts: Option<u32>, // highest speed the module reports, MT/s
+    pub configured_mts: Option<u32>, // speed it runs at, MT/s
+}
+
+/// STREAM-style bandwidth and pointer-chasing latency, next to the speed the
+/// firmware configured
+#[derive(Debug, Serialize, Clone)]
+pub struct MemoryBenchmarkResult {
+    pub threads: usize,
+    pub copy_gbps: f64,
+    pub scale_gbps: f64,
+    pub add_gbps: f64,
+    pub triad_gbps: f64,
+    pub latency_ns: f64,
+    pub modules: Vec<MemoryModuleSpeed>,
+    pub smbios_error: Option<String>, // why modules is empty, when it is
+    pub configured_mts: Option<u32>,
+    pub theoretical_gbps: Option<f64>, // assuming one channel per module, up to two
+    pub efficiency: Option<f64>,       // best measured bandwidth over theoretical
+    // Some(true) above the JEDEC ceiling of the memory type, Some(false) below
+    // what the modules are rated for, None when it can't be told apart
+    pub xmp_expo_active: Option<bool>,
+}
+
+/// Time from an injected mouse movement to the next frame the game presented,
+/// over a series of samples
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InputLatencyResult {
+    pub pid: u32,
+    pub process_name: String,
+    pub label: Option<String>, // e.g. "before Game Mode", to tell runs apart
+    pub timestamp: u64,        // Unix millis
+    pub samples_ms: Vec<f64>,
+    pub missed: u32, // no frame in time, or the game wasn't in the foreground
+    pub average_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BluetoothDeviceKind {
+    Controller,
+    Headset,
+    Mouse,
+    Keyboard,
+    Other,
+}
+
+/// A paired Bluetooth peripheral
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BluetoothDevice {
+    pub address: String, // AA:BB:CC:DD:EE:FF
+    pub name: String,
+    pub kind: BluetoothDeviceKind,
+    pub is_connected: bool,
+    /// None when the device does not report its battery
+    pub battery_percent: Option<u8>,
+    pub low_battery: bool,
+}
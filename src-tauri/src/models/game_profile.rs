@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Per-game settings, keyed by the game's executable path
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GameProfile {
+    pub exe_path: String,
+    pub name: String,
+    /// Injected into the game's environment at launch (e.g. DXVK_HUD, VKD3D_CONFIG)
+    #[serde(default)]
+    pub env_vars: HashMap<String, String>,
+    #[serde(default)]
+    pub launch_args: Vec<String>,
+    /// Keeps the game out of the Discord Rich Presence
+    #[serde(default)]
+    pub hide_from_discord: bool,
+    /// Applied as soon as the game process starts
+    #[serde(default)]
+    pub priority: Option<ProcessPriority>,
+    #[serde(default)]
+    pub affinity: Option<Vec<u32>>, // logical cores
+    /// Processes suspended while the game runs, matched by name, and resumed
+    /// when it exits
+    #[serde(default)]
+    pub suspend_while_running: Vec<String>,
+    /// Cut off the network while the game runs, by process name or full
+    /// executable path, and unblocked when it exits
+    #[serde(default)]
+    pub block_network_while_running: Vec<String>,
+    /// Name patterns (`*` and `?` wildcards) of the processes the game
+    /// spawns, like shader compilers or a separate renderer, that get the
+    /// same priority and affinity
+    #[serde(default)]
+    pub child_processes: Vec<String>,
+    /// Limits of the job object the game runs in when launched from Aura
+    #[serde(default)]
+    pub job_limits: JobLimits,
+    /// Applied while the game runs, where the system exposes fan control
+    #[serde(default)]
+    pub cooling_profile: Option<CoolingProfile>,
+    /// Switches to the High performance power plan while the game runs, on
+    /// Windows
+    #[serde(default)]
+    pub high_performance_power_plan: bool,
+    /// Forwarded on the router while the game runs, when port forwarding is
+    /// enabled
+    #[serde(default)]
+    pub port_mappings: Vec<PortMapping>,
+    /// DSCP value the game's traffic is tagged with while it runs, 46
+    /// (Expedited Forwarding) for the highest priority
+    #[serde(default)]
+    pub dscp: Option<u8>,
+    /// Caps the frame rate while the game runs, through RTSS or MangoHud
+    #[serde(default)]
+    pub frame_limit: Option<FrameLimit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessPriority {
+    Idle,
+    BelowNormal,
+    Normal,
+    AboveNormal,
+    High,
+}
+
+/// What was applied to a game the moment it started, pushed with the
+/// game-rules-applied event
+#[derive(Debug, Serialize, Clone)]
+pub struct AppliedGameRules {
+    pub pid: u32,
+    pub game_pid: u32, // differs from pid for a child process of the game
+    pub name: String,
+    pub exe_path: String,
+    pub priority: Option<ProcessPriority>,
+    pub affinity: Option<Vec<u32>>,
+    pub suspended: Vec<u32>,          // pids
+    pub network_blocked: Vec<String>, // executable paths
+    pub cooling_profile: Option<CoolingProfile>,
+    pub frame_limit: Option<u32>, // fps
+    pub errors: Vec<String>,
+    pub latency_ms: u64, // from the process start to its priority and affinity being in place
+}
+
+/// Kernel anti-cheats known to ban for suspending or tampering with the game
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AntiCheatSystem {
+    EasyAntiCheat,
+    BattlEye,
+    Vanguard,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AntiCheatInfo {
+    pub pid: u32,
+    pub systems: Vec<AntiCheatSystem>, // empty when the process is not protected
+    /// Only priority and affinity changes are safe when protected
+    pub protected: bool,
+}
+
+/// Applied to a game and every process it starts, through a job object
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct JobLimits {
+    #[serde(default)]
+    pub priority: Option<ProcessPriority>,
+    #[serde(default)]
+    pub cpu_rate_percent: Option<u32>, // hard cap, of all cores together
+    #[serde(default)]
+    pub memory_limit_mb: Option<u64>, // for the whole group
+}
+
+/// A launched game and the processes it started, with their combined usage
+#[derive(Debug, Serialize, Clone)]
+pub struct JobGroupInfo {
+    pub pid: u32, // of the game, identifies the group
+    pub name: String,
+    pub exe_path: String,
+    pub limits: JobLimits,
+    pub pids: Vec<u32>,
+    pub active_processes: u32,
+    pub total_processes: u32, // started in the group since launch
+    pub cpu_time_ms: u64,
+    pub cpu_usage: Option<f32>, // percent of all cores since the previous query
+    pub memory_bytes: u64,
+    pub peak_memory_bytes: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// How the system trades fan noise for temperature
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoolingProfile {
+    Quiet,
+    Balanced,
+    Performance,
+}
+
+/// Tools that cap a game's frame rate through their per-game settings
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameLimiter {
+    Rtss, // RivaTuner Statistics Server, Windows
+    MangoHud,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FrameLimit {
+    pub fps: u32,
+    /// The first one installed when None
+    #[serde(default)]
+    pub limiter: Option<FrameLimiter>,
+    /// Only caps a laptop running on battery, checked when the game starts
+    #[serde(default)]
+    pub battery_only: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CoolingStatus {
+    pub supported: bool,
+    pub available: Vec<CoolingProfile>,
+    pub current: Option<CoolingProfile>,
+    pub games: Vec<u32>, // pids of the running games a profile is applied for
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PortProtocol {
+    Tcp,
+    Udp,
+}
+
+/// A port a game needs reachable from the internet, the same inside and out
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PortMapping {
+    pub protocol: PortProtocol,
+    pub port: u16,
+}
+
+/// Nothing is asked of the router until enabled
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PortForwardingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NatMethod {
+    Upnp,
+    NatPmp,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ActivePortMapping {
+    pub game_pid: u32,
+    pub game: String,
+    pub protocol: PortProtocol,
+    pub internal_port: u16,
+    pub external_port: u16, // NAT-PMP routers may hand out another one
+    pub method: NatMethod,
+    pub renewed_at: u64, // unix seconds
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PortForwardingStatus {
+    pub enabled: bool,
+    pub method: Option<NatMethod>, // of the gateway found, if any
+    pub gateway: Option<String>,
+    pub local_ip: Option<String>,
+    pub external_ip: Option<String>,
+    pub mappings: Vec<ActivePortMapping>,
+    pub last_error: Option<String>,
+}
+
+/// An executable cut off the network, for a game or until unblocked by hand
+#[derive(Debug, Serialize, Clone)]
+pub struct BlockedApp {
+    pub exe_path: String,
+    pub name: String,
+    pub game_pid: Option<u32>, // unblocked when this game exits
+    pub since: u64,            // unix millis
+}
+
+/// The DSCP tag applied to a running game's traffic
+#[derive(Debug, Serialize, Clone)]
+pub struct QosPolicy {
+    pub game_pid: u32,
+    pub exe_path: String,
+    pub dscp: u8,
+    pub since: u64, // unix millis
+}
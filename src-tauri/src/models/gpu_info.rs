@@ -1,3 +1,4 @@
+use crate::utils::temperature::TemperatureUnit;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +10,12 @@ pub struct GpuInfo {
     pub memory_total: u64,
     pub memory_usage_percentage: f32,
     pub temperature: Option<f32>,
+    pub memory_temperature: Option<f32>, // memory junction, where the card reports it
+    pub hotspot_temperature: Option<f32>, // hottest point of the die
+    pub fans: Vec<GpuFanInfo>,
+    pub zero_rpm_enabled: Option<bool>, // fans stop while cool, None when the driver doesn't say
+    pub fans_stopped: bool,
+    pub pcie_link: Option<PcieLink>,
     pub power_usage: Option<f32>,
     pub clock_speed: Option<u32>,
     pub memory_clock: Option<u32>,
@@ -23,6 +30,8 @@ pub struct GpuStats {
     pub total_vram_used: u64,
     pub total_vram: u64,
     pub average_utilization: f32,
+    pub vram_display: String, // used / total, in the preferred byte units
+    pub temperature_unit: TemperatureUnit, // scale of every GpuInfo temperature
 }
 
 impl Default for GpuInfo {
@@ -35,6 +44,12 @@ impl Default for GpuInfo {
             memory_total: 0,
             memory_usage_percentage: 0.0,
             temperature: None,
+            memory_temperature: None,
+            hotspot_temperature: None,
+            fans: Vec::new(),
+            zero_rpm_enabled: None,
+            fans_stopped: false,
+            pcie_link: None,
             power_usage: None,
             clock_speed: None,
             memory_clock: None,
@@ -52,6 +67,161 @@ impl Default for GpuStats {
             total_vram_used: 0,
             total_vram: 0,
             average_utilization: 0.0,
+            vram_display: String::new(),
+            temperature_unit: TemperatureUnit::default(),
         }
     }
 }
+
+/// The PCIe link of a GPU, as negotiated right now and the best the card
+/// supports. Cards drop to a lower generation while idle to save power.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PcieLink {
+    pub current_gen: Option<u32>,
+    pub current_width: Option<u32>, // lanes
+    pub max_gen: Option<u32>,
+    pub max_width: Option<u32>,
+    pub warning: Option<String>, // the link is narrower or slower than it should be
+}
+
+/// GPU memory held by a single process, summed over all adapters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuProcessUsage {
+    pub pid: u32,
+    pub name: String,
+    pub exe_path: String,
+    pub dedicated_memory: u64, // bytes of VRAM
+    pub shared_memory: u64,    // bytes of system memory mapped for the GPU
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionAction {
+    /// Empties the working set; the process keeps running but pages out
+    Trim,
+    /// Terminates the process tree, releasing all of its VRAM
+    Kill,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvictionResult {
+    pub evicted: Vec<u32>,
+    pub skipped: Vec<u32>, // protected or already gone
+    pub failed: Vec<String>,
+    pub freed_dedicated_memory: u64, // estimated from the last sample, kills only
+}
+
+/// Outcome of favoring a game in the GPU scheduler
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GpuPriorityResult {
+    pub raised: Vec<u32>,
+    pub lowered: Vec<u32>, // capture and overlay apps
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FanControlMode {
+    /// The driver or VBIOS curve
+    Auto,
+    /// A fixed speed or a curve driven by software like Afterburner
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuFanInfo {
+    pub index: u32,
+    pub speed_percent: Option<u32>,
+    pub rpm: Option<u32>,
+    pub mode: Option<FanControlMode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FanCurvePoint {
+    pub temperature: f32, // in the report's unit
+    pub speed_percent: u32,
+}
+
+/// Power limit, clock offsets and fans of a GPU as the driver has them now,
+/// to check that an overclocking or undervolting profile is applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuTuning {
+    pub index: u32,
+    pub name: String,
+    pub vendor: String,
+    pub source: String, // the interface the values were read through
+    pub power_limit_w: Option<f32>,
+    pub default_power_limit_w: Option<f32>,
+    pub min_power_limit_w: Option<f32>,
+    pub max_power_limit_w: Option<f32>,
+    pub core_clock_offset_mhz: Option<i32>,
+    pub memory_clock_offset_mhz: Option<i32>,
+    pub max_core_clock_mhz: Option<u32>,
+    pub max_memory_clock_mhz: Option<u32>,
+    pub fans: Vec<GpuFanInfo>,
+    pub fan_curve: Vec<FanCurvePoint>, // empty when the driver keeps it to itself
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuTuningReport {
+    pub gpus: Vec<GpuTuning>,
+    pub temperature_unit: TemperatureUnit, // of every fan curve point
+    pub errors: Vec<String>,               // per interface that failed
+}
+
+/// A monitor, the refresh rates of its current resolution and whether it
+/// can run a variable refresh rate (G-Sync Compatible, FreeSync, Adaptive-Sync)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayInfo {
+    pub id: String,   // \\.\DISPLAY1 on Windows, the xrandr output on Linux
+    pub name: String, // the monitor's own name when its EDID has one
+    pub primary: bool,
+    pub width: u32,
+    pub height: u32,
+    pub refresh_hz: u32,
+    pub refresh_rates: Vec<u32>,   // ascending
+    pub vrr_capable: Option<bool>, // None when the EDID could not be read
+    pub vrr_min_hz: Option<u32>,
+    pub vrr_max_hz: Option<u32>,
+    pub vrr_enabled: Option<bool>, // the OS variable refresh rate setting, None when unknown
+}
+
+/// A named refresh rate to switch a display to, like 60 Hz on the desktop
+/// and 165 Hz for games
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RefreshRateProfile {
+    pub name: String,
+    pub display: Option<String>, // None for the primary display
+    pub refresh_hz: Option<u32>, // None for the highest rate the display runs at
+}
+
+/// When a hot memory junction is worth an alert. VRAM throttles around
+/// 105-110 °C, staying close to that for long wears the card.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpuMemoryAlertConfig {
+    pub enabled: bool,
+    pub threshold_celsius: f32,
+    pub sustained_secs: u64, // how long it has to stay above before the alert
+}
+
+impl Default for GpuMemoryAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_celsius: 100.0,
+            sustained_secs: 5 * 60,
+        }
+    }
+}
+
+/// Raised once per hot spell of a GPU's memory junction
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuMemoryHotAlert {
+    pub gpu: String,
+    pub memory_temperature: f32, // in the current unit
+    pub threshold: f32,          // in the current unit
+    pub temperature_unit: TemperatureUnit,
+    pub hot_since: u64, // millis
+    pub timestamp: u64,
+}
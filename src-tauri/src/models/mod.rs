@@ -1,4 +1,11 @@
+pub mod audio;
+pub mod benchmark;
+pub mod bluetooth_device;
+pub mod game_profile;
 pub mod gpu_info;
 pub mod optimization;
 pub mod process_info;
+pub mod readiness;
+pub mod session;
 pub mod system_stats;
+pub mod usb_device;
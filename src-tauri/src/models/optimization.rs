@@ -11,6 +11,12 @@ pub struct OptimizationItem {
     pub requires_admin: bool,
     pub risk_level: RiskLevel,
     pub platform: Platform,
+    #[serde(default)]
+    pub depends_on: Vec<String>, // ids that must be applied first
+    #[serde(default)]
+    pub conflicts_with: Vec<String>, // ids that cannot be applied at the same time
+    #[serde(default)]
+    pub min_build: Option<u32>, // oldest Windows build the optimization exists on
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,3 +53,212 @@ pub struct PlatformInfo {
     pub version: String,
     pub arch: String,
 }
+
+/// Windows Update pause state, as Windows itself reports it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowsUpdateState {
+    pub paused: bool,
+    pub paused_until: Option<u64>, // Unix millis
+    pub delivery_optimization_running: bool,
+}
+
+/// Microsoft Defender path exclusions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefenderExclusions {
+    pub paths: Vec<String>,   // all configured exclusions, admin only
+    pub managed: Vec<String>, // the ones Aura added and will revert
+    pub real_time_protection_enabled: bool,
+}
+
+/// Virtualization-based security and memory integrity (HVCI) state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VbsStatus {
+    pub vbs_enabled: bool,
+    pub vbs_running: bool,
+    pub hvci_running: bool,
+    pub hvci_configured: Option<bool>, // None when never set in the registry
+    /// Mode Based Execution Control, without it HVCI is emulated and costly
+    pub mbec_supported: bool,
+    pub restart_required: bool,
+}
+
+/// System timer resolution, in milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimerResolution {
+    pub current_ms: f64,
+    pub finest_ms: f64,
+    pub coarsest_ms: f64,
+    pub held_by_aura: bool,
+}
+
+/// Keeps a 1 ms timer resolution requested, optionally only while a game runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimerKeeperConfig {
+    pub enabled: bool,
+    #[serde(default = "default_only_while_gaming")]
+    pub only_while_gaming: bool,
+}
+
+fn default_only_while_gaming() -> bool {
+    true
+}
+
+impl Default for TimerKeeperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            only_while_gaming: default_only_while_gaming(),
+        }
+    }
+}
+
+/// A user-defined optimization, stored as JSON and run by the same engine as
+/// the built-in ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationRecipe {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_recipe_category")]
+    pub category: String,
+    pub platform: Platform,
+    pub risk_level: RiskLevel,
+    #[serde(default)]
+    pub requires_admin: bool,
+    pub apply: Vec<RecipeStep>,
+    #[serde(default)]
+    pub revert: Vec<RecipeStep>, // empty means the recipe cannot be reverted
+    #[serde(default)]
+    pub detect: Option<RegistryCheck>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub conflicts_with: Vec<String>,
+    #[serde(default)]
+    pub min_build: Option<u32>,
+}
+
+fn default_recipe_category() -> String {
+    "Custom".to_string()
+}
+
+/// One operation of a recipe. Only these are allowed, and each is checked
+/// against the recipe sandbox before it is saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecipeStep {
+    SetRegistryDword {
+        key: String,
+        value: String,
+        data: u32,
+    },
+    DeleteRegistryValue {
+        key: String,
+        value: String,
+    },
+    RunCommand {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+/// Treats the recipe as applied when a DWORD has the given value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryCheck {
+    pub key: String,
+    pub value: String,
+    pub equals: u32,
+}
+
+/// Outcome of applying several optimizations together, in the order they ran.
+/// Optimizations rejected before anything ran are listed first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileApplyResult {
+    pub success: bool,
+    pub results: Vec<ProfileItemResult>,
+    pub needs_restart: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileItemResult {
+    pub optimization_id: String,
+    pub result: OptimizationResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AuditAction {
+    Apply,
+    Revert,
+}
+
+/// One apply or revert, as recorded in the optimization history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationAuditEntry {
+    pub timestamp: u64, // Unix millis
+    pub action: AuditAction,
+    pub optimization_id: String,
+    pub optimization_name: String,
+    pub user: String,
+    pub changes: Vec<SettingChange>,
+    pub success: bool,
+    pub message: String,
+}
+
+/// A setting whose value differs before and after the action. None means
+/// the value was not set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettingChange {
+    pub setting: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// An apply or revert that only takes effect after the next restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingRestartItem {
+    pub optimization_id: String,
+    pub optimization_name: String,
+    pub action: AuditAction,
+    pub requested_at: u64, // Unix millis
+}
+
+/// Whether games can use the DirectStorage fast path on a drive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectStorageStatus {
+    pub drive: String,
+    /// Windows 11 storage stack; Windows 10 runs DirectStorage without it
+    pub optimized_os: bool,
+    pub bypass_io_supported: Option<bool>, // None when fsutil could not be run
+    pub storage_type: Option<String>,      // e.g. NVMe
+    pub optimized: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AdapterKind {
+    Ethernet,
+    WiFi,
+    Other,
+}
+
+/// IPv4 route metric and MTU of a network adapter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkAdapterTuning {
+    pub index: u32,
+    pub name: String,
+    pub kind: AdapterKind,
+    pub connected: bool,
+    pub metric: u32, // lower is preferred
+    pub automatic_metric: bool,
+    pub mtu: u32,
+    pub modified: bool, // Aura changed it and can restore the original
+}
+
+/// Domains blocked through the hosts file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostsBlocklistStatus {
+    pub applied: bool,
+    pub managed: Vec<String>,       // blocked by Aura's blocklist
+    pub other_blocked: Vec<String>, // blocked by entries Aura did not add
+}
@@ -84,6 +84,10 @@ pub struct ProcessFilter {
     pub status: Option<ProcessStatus>,
     pub min_cpu: Option<f32>,
     pub min_memory: Option<u64>,
+    pub page: Option<usize>, // 0-based, every process when neither is set
+    pub per_page: Option<usize>,
+    pub sort_by: Option<String>, // "name", "cpu", "memory", "pid" or "run_time"
+    pub sort_order: Option<String>, // "asc" or "desc"
 }
 
 impl ProcessFilter {
@@ -94,6 +98,10 @@ impl ProcessFilter {
             status: None,
             min_cpu: None,
             min_memory: None,
+            page: None,
+            per_page: None,
+            sort_by: None,
+            sort_order: None,
         }
     }
 
@@ -121,29 +129,93 @@ impl ProcessFilter {
         self.min_memory = Some(min_memory);
         self
     }
+
+    pub fn with_page(mut self, page: usize, per_page: usize) -> Self {
+        self.page = Some(page);
+        self.per_page = Some(per_page);
+        self
+    }
+
+    pub fn with_sort(mut self, sort_by: impl Into<String>, sort_order: impl Into<String>) -> Self {
+        self.sort_by = Some(sort_by.into());
+        self.sort_order = Some(sort_order.into());
+        self
+    }
 }
 
+/// State of a process, serialized as the strings the frontend shows and
+/// filters by ("runnable", "sleeping", "suspended", ...)
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum ProcessStatus {
+    #[serde(rename = "runnable", alias = "running")]
     Running,
     Sleeping,
+    /// Suspended by the user, every thread on Windows or SIGSTOP on Linux
+    Suspended,
     Stopped,
     Zombie,
+    /// Ended from Aura, only reported by the `process-state-changed` event
+    Killed,
     Unknown,
 }
 
+impl ProcessStatus {
+    /// Suspension wins over what the scheduler reports
+    pub fn from_state(status: sysinfo::ProcessStatus, is_suspended: bool) -> Self {
+        if is_suspended {
+            Self::Suspended
+        } else {
+            Self::from(status)
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "runnable",
+            Self::Sleeping => "sleeping",
+            Self::Suspended => "suspended",
+            Self::Stopped => "stopped",
+            Self::Zombie => "zombie",
+            Self::Killed => "killed",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
 impl From<&str> for ProcessStatus {
     fn from(s: &str) -> Self {
         match s.to_lowercase().as_str() {
-            "running" => Self::Running,
-            "sleeping" | "idle" => Self::Sleeping,
-            "stopped" | "suspended" => Self::Stopped,
-            "zombie" | "defunct" => Self::Zombie,
+            "running" | "runnable" => Self::Running,
+            "sleeping" | "sleep" | "idle" => Self::Sleeping,
+            "suspended" => Self::Suspended,
+            "stopped" => Self::Stopped,
+            "zombie" | "defunct" | "dead" => Self::Zombie,
+            "killed" => Self::Killed,
             _ => Self::Unknown,
         }
     }
 }
 
+impl From<sysinfo::ProcessStatus> for ProcessStatus {
+    fn from(status: sysinfo::ProcessStatus) -> Self {
+        use sysinfo::ProcessStatus as Sys;
+
+        match status {
+            Sys::Run | Sys::Waking => Self::Running,
+            Sys::Sleep
+            | Sys::Idle
+            | Sys::UninterruptibleDiskSleep
+            | Sys::Parked
+            | Sys::LockBlocked
+            | Sys::Wakekill => Self::Sleeping,
+            Sys::Stop | Sys::Tracing => Self::Stopped,
+            Sys::Zombie | Sys::Dead => Self::Zombie,
+            Sys::Unknown(_) => Self::Unknown,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,13 +240,18 @@ mod tests {
             .with_user("testuser")
             .with_status(ProcessStatus::Running)
             .with_min_cpu(1.0)
-            .with_min_memory(1024);
+            .with_min_memory(1024)
+            .with_page(2, 25)
+            .with_sort("memory", "desc");
 
         assert_eq!(filter.name, Some("test".to_string()));
         assert_eq!(filter.user, Some("testuser".to_string()));
         assert_eq!(filter.status, Some(ProcessStatus::Running));
         assert_eq!(filter.min_cpu, Some(1.0));
         assert_eq!(filter.min_memory, Some(1024));
+        assert_eq!((filter.page, filter.per_page), (Some(2), Some(25)));
+        assert_eq!(filter.sort_by.as_deref(), Some("memory"));
+        assert_eq!(filter.sort_order.as_deref(), Some("desc"));
     }
 
     #[test]
@@ -184,6 +261,36 @@ mod tests {
         assert_eq!(ProcessStatus::from("stopped"), ProcessStatus::Stopped);
         assert_eq!(ProcessStatus::from("zombie"), ProcessStatus::Zombie);
         assert_eq!(ProcessStatus::from("invalid"), ProcessStatus::Unknown);
+        assert_eq!(ProcessStatus::from("runnable"), ProcessStatus::Running);
+        assert_eq!(ProcessStatus::from("suspended"), ProcessStatus::Suspended);
+    }
+
+    #[test]
+    fn test_process_status_serde_matches_frontend() {
+        for status in [
+            ProcessStatus::Running,
+            ProcessStatus::Sleeping,
+            ProcessStatus::Suspended,
+            ProcessStatus::Stopped,
+            ProcessStatus::Zombie,
+            ProcessStatus::Killed,
+            ProcessStatus::Unknown,
+        ] {
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status.as_str()));
+            assert_eq!(
+                serde_json::from_str::<ProcessStatus>(&json).unwrap(),
+                status
+            );
+        }
+        assert_eq!(
+            serde_json::from_str::<ProcessStatus>("\"running\"").unwrap(),
+            ProcessStatus::Running
+        );
+        assert_eq!(
+            ProcessStatus::from_state(sysinfo::ProcessStatus::Stop, true),
+            ProcessStatus::Suspended
+        );
     }
 
     #[test]
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceState {
+    Running,
+    Stopped,
+    Disabled,
+    NotInstalled,
+}
+
+/// State of a Windows service relevant to gaming
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceStatus {
+    pub name: String,
+    pub display_name: String,
+    pub state: ServiceState,
+}
+
+/// Game Bar, Game DVR, Game Mode and Xbox service states in one place.
+/// Registry settings are None when they could not be read.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GamingServicesStatus {
+    pub game_bar_enabled: Option<bool>,
+    pub game_dvr_enabled: Option<bool>,
+    pub background_recording_enabled: Option<bool>,
+    pub game_mode_enabled: Option<bool>,
+    pub xbox_services: Vec<ServiceStatus>,
+    pub game_input_service: ServiceStatus,
+    pub checklist: Vec<ReadinessItem>,
+}
+
+/// One entry of the gaming readiness checklist
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReadinessItem {
+    pub id: String,
+    pub label: String,
+    pub passed: bool,
+    pub detail: String,
+    /// Optimization id that fixes a failed check, if any
+    pub fix_id: Option<String>,
+}
+
+/// Result of the gaming readiness pre-flight check
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReadinessReport {
+    pub score: u8, // 0-100, weighted by impact
+    pub items: Vec<ReadinessItem>,
+    pub checked_at: u64, // Unix millis
+}
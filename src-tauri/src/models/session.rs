@@ -0,0 +1,72 @@
+use crate::models::gpu_info::GpuFanInfo;
+use serde::{Deserialize, Serialize};
+
+/// A game process Aura keeps an eye on during the session
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrackedGame {
+    pub pid: u32,
+    pub name: String,
+    pub exe_path: String,
+    pub started_at: u64, // Unix millis
+    pub collect_minidumps: bool,
+}
+
+/// How a tracked game ended
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameExitRecord {
+    pub pid: u32,
+    pub name: String,
+    pub exe_path: String,
+    pub started_at: u64,
+    pub exited_at: u64,
+    pub exit_code: Option<u32>, // None when the platform can't report it
+    pub crashed: bool,
+    pub crash_reason: Option<String>,
+    pub minidump_paths: Vec<String>,
+    pub gpu_cooling: Vec<GpuCoolingSample>, // the minutes before a crash
+    pub hardware_changes: Vec<HardwareChange>, // around a crash
+}
+
+/// Temperatures and fans of one GPU at a point of the session, to look back
+/// at how the card was cooled before a crash
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GpuCoolingSample {
+    pub at: u64, // Unix millis
+    pub gpu: String,
+    pub temperature_celsius: Option<f32>,
+    pub memory_temperature_celsius: Option<f32>,
+    pub hotspot_temperature_celsius: Option<f32>,
+    pub fans: Vec<GpuFanInfo>,
+    pub fans_stopped: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HardwareChangeKind {
+    GpuReset, // a driver timeout (TDR) or reset
+    DeviceAdded,
+    DeviceRemoved,
+    DisplayAdded,
+    DisplayRemoved,
+    DiskAdded,
+    DiskRemoved,
+    MemoryChanged,
+}
+
+/// Something that changed in the hardware while Aura was running
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HardwareChange {
+    pub at: u64, // Unix millis
+    pub kind: HardwareChangeKind,
+    pub description: String,
+}
+
+/// Everything worth reporting about the current Aura session
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionReport {
+    pub started_at: u64,
+    pub tracked_games: Vec<TrackedGame>,
+    pub game_exits: Vec<GameExitRecord>,
+    pub gpu_cooling: Vec<GpuCoolingSample>, // the last half hour
+    pub hardware_changes: Vec<HardwareChange>,
+}
@@ -1,12 +1,22 @@
-use serde::Serialize;
+use crate::models::gpu_info::GpuStats;
+use crate::utils::bytes::ByteUnits;
+use crate::utils::temperature::TemperatureUnit;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SystemStats {
     pub title: String,
     pub percentage: Option<f32>,
     pub progress_data: Option<Vec<ProgressData>>,
     pub generic_data: Option<Vec<GenericData>>,
+    // Set by the resilient monitor: cached data returned after a failed fetch
+    #[serde(default)]
+    pub is_stale: bool,
+    // Set by the resilient monitor: placeholder data, nothing was measured
+    #[serde(default)]
+    pub is_fallback: bool,
 }
 
 impl SystemStats {
@@ -16,6 +26,8 @@ impl SystemStats {
             percentage: None,
             progress_data: None,
             generic_data: None,
+            is_stale: false,
+            is_fallback: false,
         }
     }
 
@@ -43,11 +55,14 @@ impl SystemStats {
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProgressData {
     pub title: String,
     pub value: f32,
     pub temperature: Option<f32>,
+    // Quantity behind the percentage, e.g. used bytes, for charting
+    pub raw_value: Option<f64>,
+    pub unit: Option<String>,
 }
 
 impl ProgressData {
@@ -56,6 +71,8 @@ impl ProgressData {
             title: title.into(),
             value: value.clamp(0.0, 100.0),
             temperature: None,
+            raw_value: None,
+            unit: None,
         }
     }
 
@@ -71,10 +88,14 @@ impl fmt::Display for ProgressData {
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GenericData {
     pub title: String,
-    pub value: String,
+    pub value: String, // formatted for display in the current locale
+    // Unformatted number behind `value` in base units (bytes, Hz, seconds,
+    // °C, percent), None for text values
+    pub raw_value: Option<f64>,
+    pub unit: Option<String>,
 }
 
 impl GenericData {
@@ -82,6 +103,8 @@ impl GenericData {
         Self {
             title: title.into(),
             value: value.into(),
+            raw_value: None,
+            unit: None,
         }
     }
 }
@@ -92,6 +115,640 @@ impl fmt::Display for GenericData {
     }
 }
 
+/// How values in SystemStats are formatted for display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub locale: String,   // BCP 47 tag, e.g. it-IT
+    pub language: String, // language of backend messages, e.g. it
+    pub byte_units: ByteUnits,
+    // Also the scale of the numeric temperatures in ProgressData and GpuInfo
+    pub temperature_unit: TemperatureUnit,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            locale: crate::utils::locale::DEFAULT_LOCALE.to_string(),
+            language: crate::shared::i18n::DEFAULT_LANGUAGE.to_string(),
+            byte_units: ByteUnits::default(),
+            temperature_unit: TemperatureUnit::default(),
+        }
+    }
+}
+
+/// Opt-in localhost REST API for home-automation and streaming tools
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String, // expected as "Authorization: Bearer <token>"
+    // Also stream stats on every interface, one port above `port`, so Aura on
+    // another PC of the LAN can pair with this one
+    pub allow_lan: bool,
+    // Read-only token of that stream, the only thing reachable from the LAN
+    #[serde(default)]
+    pub pairing_token: String,
+}
+
+impl Default for RestApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7878,
+            token: String::new(),
+            allow_lan: false,
+            pairing_token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestApiStatus {
+    #[serde(flatten)]
+    pub config: RestApiConfig,
+    pub running: bool,
+    pub available: bool, // false when Aura was built without the rest-api feature
+    pub pairing_port: Option<u16>, // None while LAN pairing is off
+}
+
+/// Another PC running Aura, watched through its REST API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteAgent {
+    pub host: String,
+    pub port: u16,
+    pub token: String, // the agent's pairing token, which only reads stats
+}
+
+/// Stats a paired agent streams, all taken at the same time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSnapshot {
+    pub host_name: String,
+    pub timestamp: u64, // Unix millis on the agent
+    pub stats: Vec<SystemStats>,
+    pub gpu: Option<GpuStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteMonitorStatus {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub connected: bool,
+    pub last_error: Option<String>,
+    pub snapshot: Option<RemoteSnapshot>, // latest one received
+    pub available: bool, // false when Aura was built without the remote-monitor feature
+}
+
+/// Optional MQTT publishing of stats and game sessions, with Home Assistant
+/// discovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub interval_secs: u64,
+    pub base_topic: String,       // state goes to <base_topic>/<device>/state
+    pub discovery_prefix: String, // "homeassistant" unless changed in Home Assistant
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 1883,
+            username: None,
+            password: None,
+            interval_secs: 10,
+            base_topic: "aura".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+/// OBS Studio connection through obs-websocket (v5)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ObsConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub password: Option<String>,
+    pub text_source: Option<String>, // text input that shows the stats line
+    pub interval_secs: u64,
+    pub game_scene: Option<String>, // switched to when a tracked game starts
+    pub idle_scene: Option<String>, // switched to when it exits
+}
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: 4455,
+            password: None,
+            text_source: None,
+            interval_secs: 2,
+            game_scene: None,
+            idle_scene: None,
+        }
+    }
+}
+
+/// Discord Rich Presence showing the running game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    pub enabled: bool,
+    pub client_id: String, // id of the Discord application the presence belongs to
+    pub show_stats: bool,  // adds the stats line under the game name
+    pub interval_secs: u64,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            client_id: String::new(),
+            show_stats: true,
+            interval_secs: 15,
+        }
+    }
+}
+
+/// Hours, and processes, during which background sampling, alerts and
+/// watchdogs are paused
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuietHoursConfig {
+    pub enabled: bool,
+    pub start: String,                    // local time, "HH:MM"
+    pub end: String,                      // may be earlier than start to span midnight
+    pub pause_for_processes: Vec<String>, // e.g. a DAW or a benchmark, matched by name
+    /// Samples less often and skips process scans while nobody uses the PC,
+    /// independently of the quiet hours
+    pub throttle_when_idle: bool,
+    pub idle_after_secs: u32, // without keyboard or mouse input
+}
+
+impl Default for QuietHoursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            pause_for_processes: Vec::new(),
+            throttle_when_idle: true,
+            idle_after_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitoringPauseReason {
+    Manual,
+    QuietHours,
+    Process(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitoringPauseStatus {
+    pub paused: bool,
+    pub reason: Option<MonitoringPauseReason>,
+    pub manual_until: Option<u64>, // millis; None with a manual reason means until resumed
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserIdleStatus {
+    pub supported: bool, // false where the last input time can't be read
+    pub idle: bool,
+    pub idle_ms: u64, // since the last keyboard or mouse input
+    pub throttled: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorKind {
+    Cpu,
+    Gpu,
+    Storage,
+    Other,
+}
+
+/// Session statistics of one temperature sensor, in the current unit
+#[derive(Debug, Clone, Serialize)]
+pub struct TemperatureSensorSummary {
+    pub sensor: String,
+    pub kind: SensorKind,
+    pub current: f32,
+    pub min: f32,
+    pub avg: f32,
+    pub max: f32,
+    pub max_at: u64, // millis
+    pub samples: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemperatureSummary {
+    pub since: u64, // millis, start of the session or the last reset
+    pub unit: TemperatureUnit,
+    pub sensors: Vec<TemperatureSensorSummary>,
+}
+
+/// Where memory goes beyond used and free, in bytes, broken down the way
+/// Task Manager and RAMMap do. None where the platform has no equivalent.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct MemoryBreakdown {
+    pub committed: Option<u64>,
+    pub commit_limit: Option<u64>, // RAM plus page files
+    pub commit_peak: Option<u64>,
+    pub cached: Option<u64>, // standby and modified lists on Windows
+    pub standby: Option<u64>,
+    pub modified: Option<u64>, // waiting to be written to disk
+    pub paged_pool: Option<u64>,
+    pub nonpaged_pool: Option<u64>,
+    pub compressed: Option<u64>, // RAM held by the compression store, zram or zswap
+    pub page_faults_per_sec: Option<u64>,
+    pub hard_faults_per_sec: Option<u64>, // pages read from or written to disk on Windows
+}
+
+/// The drive games are installed on and when to warn about its free space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriveWatchConfig {
+    pub games_drive: Option<String>, // mount point, e.g. "D:\\"
+    pub min_free_bytes: u64,
+}
+
+impl Default for DriveWatchConfig {
+    fn default() -> Self {
+        Self {
+            games_drive: None,
+            // Room for a large patch of a modern game
+            min_free_bytes: 50 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveSpaceStatus {
+    pub mount_point: String,
+    pub name: String,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub is_games_drive: bool,
+    pub low_space: bool,                   // only the games drive is checked
+    pub growth_bytes_per_day: Option<f64>, // negative when space is being freed
+    pub days_until_full: Option<f64>,      // at the current growth rate
+}
+
+/// What the Recycle Bin holds on one drive
+#[derive(Debug, Clone, Serialize)]
+pub struct RecycleBinInfo {
+    pub drive: String, // mount point
+    pub size_bytes: u64,
+    pub item_count: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderMoveStage {
+    #[default]
+    Copying,
+    Verifying,
+    Linking,  // the source is replaced by a junction to the copy
+    Removing, // the original files are deleted
+    Done,
+    Cancelled,
+    Failed,
+}
+
+/// Progress of moving a folder to another drive, also pushed with the
+/// folder-move-progress event
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct FolderMoveStatus {
+    pub running: bool,
+    pub source: String,
+    pub destination: String,
+    pub stage: FolderMoveStage,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    pub copied_files: u64,
+    pub total_files: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupCategory {
+    Duplicate,
+    Installer,       // old setup files and archives in Downloads
+    DriverInstaller, // extracted NVIDIA and AMD driver packages
+    Redistributable, // runtimes games install on first launch
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteSafety {
+    Safe,   // recreated or downloaded again when needed
+    Review, // may still be wanted, the user decides
+}
+
+/// A file or folder that can probably go, found by the cleanup scan
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupCandidate {
+    pub path: String,
+    pub category: CleanupCategory,
+    pub size_bytes: u64,
+    pub modified: Option<u64>, // unix seconds
+    pub safety: DeleteSafety,
+    pub reason: String,
+    pub duplicate_of: Option<String>, // the copy that is kept
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct FileCleanupResult {
+    pub deleted: Vec<String>,
+    pub freed_bytes: u64,
+    pub skipped: Vec<String>, // not in the last scan, or the kept copy is gone
+    pub failed: Vec<String>,
+}
+
+/// Raised once when the games drive falls below the free space threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveSpaceAlert {
+    pub mount_point: String,
+    pub free_bytes: u64,
+    pub min_free_bytes: u64,
+    pub timestamp: u64,
+}
+
+/// The monthly allowance of a metered connection and when to warn about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataCapConfig {
+    pub enabled: bool,
+    pub cap_bytes: u64,
+    pub interfaces: Vec<String>, // counted against the cap, all when empty
+    pub reset_day: u32,          // day of the month the allowance starts over, 1-28
+    pub warn_percent: u8,
+}
+
+impl Default for DataCapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cap_bytes: 100 * 1024 * 1024 * 1024,
+            interfaces: Vec::new(),
+            reset_day: 1,
+            warn_percent: 80,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InterfaceUsage {
+    pub received_bytes: u64,
+    pub transmitted_bytes: u64,
+}
+
+/// Traffic of one billing period, keyed by interface name
+#[derive(Debug, Clone, Serialize)]
+pub struct DataUsagePeriod {
+    pub start: String, // local date, "YYYY-MM-DD"
+    pub interfaces: HashMap<String, InterfaceUsage>,
+    pub counted_bytes: u64, // both directions of the interfaces under the cap
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataUsageStatus {
+    pub config: DataCapConfig,
+    pub current: DataUsagePeriod,
+    pub history: Vec<DataUsagePeriod>, // older periods, newest first
+    pub cap_used_percent: Option<f64>, // None while the cap is disabled
+}
+
+/// Raised once per period when the warning threshold and again when the cap
+/// is reached
+#[derive(Debug, Clone, Serialize)]
+pub struct DataCapAlert {
+    pub period_start: String,
+    pub used_bytes: u64,
+    pub cap_bytes: u64,
+    pub exceeded: bool,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    TempCleanup,
+    Retrim,
+    StandbyPurge,
+    LogRotation,
+}
+
+/// When the maintenance tasks run on their own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    pub enabled: bool,
+    pub time: String, // local time, "HH:MM"
+    pub tasks: Vec<MaintenanceTask>,
+    pub require_idle: bool,     // no keyboard or mouse input for a while
+    pub require_ac_power: bool, // laptops on battery wait for the charger
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: "03:00".to_string(),
+            tasks: vec![
+                MaintenanceTask::TempCleanup,
+                MaintenanceTask::Retrim,
+                MaintenanceTask::StandbyPurge,
+                MaintenanceTask::LogRotation,
+            ],
+            require_idle: true,
+            require_ac_power: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceTaskResult {
+    pub task: MaintenanceTask,
+    pub success: bool,
+    pub message: String,
+    pub freed_bytes: Option<u64>,
+}
+
+/// One run of the maintenance tasks, as kept in the results log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceRun {
+    pub started: u64, // Unix millis
+    pub finished: u64,
+    pub scheduled: bool, // false when started by hand
+    pub results: Vec<MaintenanceTaskResult>,
+}
+
+/// Whether Aura looks for new releases on its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    pub check_automatically: bool,
+    pub include_prereleases: bool,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            check_automatically: true,
+            include_prereleases: false,
+        }
+    }
+}
+
+/// A newer release and the installer picked for this platform
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub name: String,
+    pub notes: String, // release notes, markdown
+    pub published_at: String,
+    pub page_url: String,
+    pub asset_name: String,
+    pub asset_url: String,
+    pub asset_size: u64,
+    pub checksum_url: Option<String>, // SHA-256 file published with the release
+    pub signature_url: Option<String>, // minisign signature of the AppImage
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateStage {
+    UpToDate,
+    Available,
+    Downloading,
+    Ready, // downloaded and verified, waiting to be installed
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateStatus {
+    pub current_version: String,
+    pub stage: UpdateStage,
+    pub update: Option<UpdateInfo>,
+    pub last_checked: Option<u64>, // Unix millis
+    pub error: Option<String>,
+}
+
+/// CPU and firmware features users commonly toggle in the BIOS
+#[derive(Debug, Serialize, Clone)]
+pub struct PlatformFeatures {
+    pub virtualization_supported: bool,       // VT-x / AMD-V
+    pub virtualization_enabled: Option<bool>, // None when it can't be determined
+    pub hypervisor_present: bool,
+    pub smt_enabled: bool,
+    pub physical_cores: usize,
+    pub logical_cores: usize,
+    pub secure_boot_enabled: Option<bool>, // None on legacy BIOS boots
+}
+
+/// CPU power limits and signs of Precision Boost Overdrive, a curve
+/// optimizer or an undervolt, as far as the platform exposes them
+#[derive(Debug, Serialize, Clone)]
+pub struct CpuTuningStatus {
+    pub vendor: String,
+    pub model: String,
+    pub source: Option<String>, // where limits and voltage were read from
+    pub ppt_limit_w: Option<f32>,
+    pub ppt_w: Option<f32>,
+    pub tdc_limit_a: Option<f32>,
+    pub tdc_a: Option<f32>,
+    pub edc_limit_a: Option<f32>,
+    pub edc_a: Option<f32>,
+    pub core_voltage: Option<f32>,   // volts
+    pub peak_clock_mhz: Option<u32>, // fastest core when read
+    pub rated_boost_mhz: Option<u32>,
+    /// The PPT limit matches none of the stock desktop limits, typical of PBO
+    pub limits_modified: Option<bool>,
+    /// Boosting close to the rated clock at a voltage stock parts don't run
+    /// at. A guess, None while the CPU isn't boosting.
+    pub undervolt_suspected: Option<bool>,
+    pub notes: Vec<String>,
+}
+
+/// Recent load of every logical core, oldest sample first, for a heatmap
+#[derive(Debug, Serialize, Clone)]
+pub struct CpuHeatmap {
+    pub interval_ms: u64,
+    pub cores: usize,
+    pub samples: Vec<Vec<u8>>, // percent, one row per sample, one column per core
+    pub last_sample_at: Option<u64>, // Unix millis
+    /// A core that stayed nearly full while the others had room, the sign of
+    /// a single-threaded bottleneck
+    pub bottleneck_core: Option<usize>,
+}
+
+/// Share of time a core spent in hardware interrupts and deferred procedure
+/// calls (softirqs on Linux), driver work no process is charged for
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct InterruptTime {
+    pub core: Option<usize>, // None for all cores together
+    pub interrupt_percent: f32,
+    pub dpc_percent: f32,
+}
+
+/// Why a restart would help
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RebootReasonKind {
+    LongUptime,
+    PendingOptimizations,
+    PendingUpdates,
+    CommitCharge,
+    KernelPool,
+    MemoryFragmentation,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RebootReason {
+    pub kind: RebootReasonKind,
+    pub detail: String,
+}
+
+/// Whether the system has been running long enough, or has piled up enough
+/// changes and memory trouble, that a restart is worth it
+#[derive(Debug, Serialize, Clone)]
+pub struct SystemHealth {
+    pub uptime_secs: u64,
+    pub reboot_recommended: bool,
+    pub reasons: Vec<RebootReason>,
+}
+
+/// Operating system the app runs on, as precise as the platform reports it
+#[derive(Debug, Serialize, Clone)]
+pub struct PlatformInfo {
+    pub os: String,      // Windows, Linux or macOS
+    pub version: String, // 11 or 10 on Windows, distro or macOS version elsewhere
+    pub arch: String,
+    pub build: Option<u32>, // Windows build, e.g. 22631
+    pub build_revision: Option<u32>,
+    pub display_version: Option<String>, // Windows feature update, e.g. 23H2
+    pub edition: Option<String>,         // Windows edition, e.g. Professional
+    pub distro_id: Option<String>,       // os-release ID, e.g. fedora
+    pub distro_name: Option<String>,
+    pub immutable: bool, // read-only system image, e.g. SteamOS or Silverblue
+    pub kernel_version: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
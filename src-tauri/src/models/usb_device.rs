@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Negotiated USB link speed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsbSpeed {
+    Low,       // 1.5 Mbps
+    Full,      // 12 Mbps
+    High,      // 480 Mbps
+    Super,     // 5 Gbps
+    SuperPlus, // 10 Gbps and above
+    Unknown,
+}
+
+/// A connected USB peripheral
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UsbDevice {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub speed: UsbSpeed,
+    pub is_mouse: bool,
+    /// Derived from the interrupt endpoint interval, None when unknown
+    pub polling_rate_hz: Option<u32>,
+}
@@ -0,0 +1,213 @@
+// Detects the anti-cheats that ban for suspending or tampering with a
+// protected game: Easy Anti-Cheat, BattlEye and Riot Vanguard. A process
+// counts as protected when it is part of an anti-cheat, when its install
+// directory ships one, or when an anti-cheat process runs under it. Only
+// priority and affinity changes are safe on a protected process.
+use crate::models::game_profile::{AntiCheatInfo, AntiCheatSystem};
+use crate::shared::system;
+use std::path::Path;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, UpdateKind};
+
+// Levels above the executable searched for the anti-cheat, Unreal games keep
+// it at the install root and the executable in Binaries\Win64
+const PARENT_LEVELS: usize = 3;
+
+struct Signature {
+    system: AntiCheatSystem,
+    processes: &'static [&'static str],
+    files: &'static [&'static str], // next to the game or a few levels up
+    path_markers: &'static [&'static str], // in the game's install path
+}
+
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        system: AntiCheatSystem::EasyAntiCheat,
+        processes: &["EasyAntiCheat", "EasyAntiCheat_EOS", "start_protected_game"],
+        files: &[
+            "EasyAntiCheat",
+            "EasyAntiCheat_EOS_Setup.exe",
+            "start_protected_game.exe",
+        ],
+        path_markers: &[],
+    },
+    Signature {
+        system: AntiCheatSystem::BattlEye,
+        processes: &["BEService", "BEService_x64", "*_BE"],
+        files: &["BattlEye", "BEClient_x64.dll", "*_BE.exe"],
+        path_markers: &[],
+    },
+    Signature {
+        system: AntiCheatSystem::Vanguard,
+        processes: &["vgc", "vgtray"],
+        files: &[],
+        // Vanguard lives outside the game and protects every Riot title
+        path_markers: &["riot games"],
+    },
+];
+
+pub fn detect(pid: u32) -> AntiCheatInfo {
+    let mut systems = Vec::new();
+    if let Ok(mut system) = system::get_system().lock() {
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+        );
+        if let Some(process) = system.process(Pid::from_u32(pid)) {
+            add(
+                &mut systems,
+                by_process_name(&process.name().to_string_lossy()),
+            );
+            if let Some(exe) = process.exe() {
+                add(&mut systems, by_install_path(exe));
+            }
+        }
+
+        // Anti-cheat processes started by the game
+        let mut parents = vec![pid];
+        while let Some(parent) = parents.pop() {
+            for (child, process) in system.processes() {
+                if process.parent().map(|p| p.as_u32()) == Some(parent) {
+                    parents.push(child.as_u32());
+                    add(
+                        &mut systems,
+                        by_process_name(&process.name().to_string_lossy()),
+                    );
+                }
+            }
+        }
+    }
+
+    AntiCheatInfo {
+        pid,
+        protected: !systems.is_empty(),
+        systems,
+    }
+}
+
+/// Whether a process belongs to an anti-cheat itself, by name
+pub fn is_anti_cheat_process(name: &str) -> bool {
+    !by_process_name(name).is_empty()
+}
+
+fn add(systems: &mut Vec<AntiCheatSystem>, found: Vec<AntiCheatSystem>) {
+    for system in found {
+        if !systems.contains(&system) {
+            systems.push(system);
+        }
+    }
+}
+
+fn by_process_name(name: &str) -> Vec<AntiCheatSystem> {
+    SIGNATURES
+        .iter()
+        .filter(|signature| {
+            signature
+                .processes
+                .iter()
+                .any(|pattern| system::matches_process_pattern(name, pattern))
+        })
+        .map(|signature| signature.system)
+        .collect()
+}
+
+fn by_install_path(exe: &Path) -> Vec<AntiCheatSystem> {
+    let mut found = by_path_markers(&exe.to_string_lossy());
+    for dir in exe.ancestors().skip(1).take(PARENT_LEVELS + 1) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        let names: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        add(&mut found, by_file_names(&names));
+    }
+    found
+}
+
+fn by_path_markers(path: &str) -> Vec<AntiCheatSystem> {
+    let path = path.to_lowercase();
+    SIGNATURES
+        .iter()
+        .filter(|signature| signature.path_markers.iter().any(|m| path.contains(m)))
+        .map(|signature| signature.system)
+        .collect()
+}
+
+fn by_file_names(names: &[String]) -> Vec<AntiCheatSystem> {
+    SIGNATURES
+        .iter()
+        .filter(|signature| {
+            names.iter().any(|name| {
+                signature
+                    .files
+                    .iter()
+                    .any(|pattern| wildcard_file_match(name, pattern))
+            })
+        })
+        .map(|signature| signature.system)
+        .collect()
+}
+
+/// File names keep their extension, unlike process names
+fn wildcard_file_match(name: &str, pattern: &str) -> bool {
+    let extension = |value: &str| {
+        Path::new(value)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+    };
+    extension(name) == extension(pattern) && system::matches_process_pattern(name, pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_by_file_names() {
+        assert_eq!(
+            by_file_names(&names(&["Engine", "EasyAntiCheat", "FortniteLauncher.exe"])),
+            [AntiCheatSystem::EasyAntiCheat]
+        );
+        assert_eq!(
+            by_file_names(&names(&["DayZ_x64.exe", "DayZ_BE.exe"])),
+            [AntiCheatSystem::BattlEye]
+        );
+        assert!(by_file_names(&names(&[
+            "Game.exe",
+            "battleye_notes.txt",
+            "UnityPlayer.dll"
+        ]))
+        .is_empty());
+        // An executable named like the folder is not the folder
+        assert!(by_file_names(&names(&["BattlEye.exe"])).is_empty());
+    }
+
+    #[test]
+    fn test_by_process_name() {
+        assert_eq!(
+            by_process_name("EasyAntiCheat_EOS.exe"),
+            [AntiCheatSystem::EasyAntiCheat]
+        );
+        assert_eq!(
+            by_process_name("BEService.exe"),
+            [AntiCheatSystem::BattlEye]
+        );
+        assert_eq!(by_process_name("vgc.exe"), [AntiCheatSystem::Vanguard]);
+        assert!(!is_anti_cheat_process("steam.exe"));
+    }
+
+    #[test]
+    fn test_by_path_markers() {
+        assert_eq!(
+            by_path_markers("C:\\Riot Games\\VALORANT\\live\\VALORANT.exe"),
+            [AntiCheatSystem::Vanguard]
+        );
+        assert!(by_path_markers("D:\\Games\\Hades\\Hades.exe").is_empty());
+    }
+}
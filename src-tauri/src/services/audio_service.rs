@@ -0,0 +1,356 @@
+use crate::models::audio::{AudioSession, FocusModeConfig};
+use crate::services::crash_monitor;
+use crate::services::recovery::{self, Action};
+use crate::shared::paths;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use thiserror::Error;
+
+const FOCUS_MODE_FILE: &str = "focus_mode.json";
+const FOCUS_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Error, Debug)]
+pub enum AudioError {
+    #[error("Audio API error: {0}")]
+    ApiError(String),
+
+    #[error("No audio session for process {0}")]
+    NotFound(u32),
+
+    #[error("Failed to access focus mode settings: {0}")]
+    StorageError(String),
+
+    // Session enumeration relies on WASAPI
+    #[cfg(not(target_os = "windows"))]
+    #[error("Audio sessions are not supported on this platform")]
+    UnsupportedPlatform,
+}
+
+type Result<T> = std::result::Result<T, AudioError>;
+
+static FOCUS_MODE: once_cell::sync::Lazy<Mutex<FocusModeConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_focus_mode()));
+
+// PIDs muted by focus mode, unmuted again once no game is running
+static FOCUS_MUTED: once_cell::sync::Lazy<Mutex<HashSet<u32>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashSet::new()));
+
+static FOCUS_THREAD_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Audio sessions of all output devices, with the owning process name
+pub fn get_audio_sessions() -> Result<Vec<AudioSession>> {
+    #[cfg(target_os = "windows")]
+    {
+        use crate::shared::system::get_system;
+        use sysinfo::Pid;
+
+        let sessions = wasapi::sessions()?;
+
+        let mut system = get_system()
+            .lock()
+            .map_err(|e| AudioError::ApiError(e.to_string()))?;
+        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| AudioSession {
+                pid: session.pid,
+                name: system
+                    .process(Pid::from(session.pid as usize))
+                    .map(|p| p.name().to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                display_name: session.display_name,
+                volume: session.volume,
+                is_muted: session.is_muted,
+                is_playing: session.is_playing,
+            })
+            .collect())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(AudioError::UnsupportedPlatform)
+    }
+}
+
+/// Mutes or unmutes every audio session of a process
+pub fn set_process_mute(pid: u32, muted: bool) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        match wasapi::set_mute(pid, muted)? {
+            0 => Err(AudioError::NotFound(pid)),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (pid, muted);
+        Err(AudioError::UnsupportedPlatform)
+    }
+}
+
+/// Sets the volume (0.0 - 1.0) of every audio session of a process
+pub fn set_process_volume(pid: u32, volume: f32) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        match wasapi::set_volume(pid, volume.clamp(0.0, 1.0))? {
+            0 => Err(AudioError::NotFound(pid)),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (pid, volume);
+        Err(AudioError::UnsupportedPlatform)
+    }
+}
+
+pub fn get_focus_mode() -> FocusModeConfig {
+    FOCUS_MODE
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+/// Saves the focus mode settings and applies them right away
+pub fn set_focus_mode(config: FocusModeConfig) -> Result<()> {
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| AudioError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(FOCUS_MODE_FILE), content)
+        .map_err(|e| AudioError::StorageError(e.to_string()))?;
+
+    *FOCUS_MODE
+        .lock()
+        .map_err(|e| AudioError::StorageError(e.to_string()))? = config;
+
+    ensure_focus_thread_running();
+    apply_focus_mode();
+    Ok(())
+}
+
+/// Resumes focus mode at startup if it was left enabled
+pub fn start_focus_mode() {
+    if get_focus_mode().enabled {
+        ensure_focus_thread_running();
+    }
+}
+
+fn load_focus_mode() -> FocusModeConfig {
+    std::fs::read_to_string(paths::app_data_file(FOCUS_MODE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn ensure_focus_thread_running() {
+    if FOCUS_THREAD_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(|| loop {
+        apply_focus_mode();
+        std::thread::sleep(FOCUS_CHECK_INTERVAL);
+    });
+}
+
+/// Mutes the configured apps while a tracked game runs and restores the ones
+/// it muted afterwards. Apps the user had muted already are left alone.
+fn apply_focus_mode() {
+    let config = get_focus_mode();
+    let gaming = config.enabled && !crash_monitor::get_tracked_games().is_empty();
+
+    let mut muted = match FOCUS_MUTED.lock() {
+        Ok(muted) => muted,
+        Err(_) => return,
+    };
+
+    if gaming {
+        let Ok(sessions) = get_audio_sessions() else {
+            return;
+        };
+
+        for session in sessions {
+            if session.is_muted || muted.contains(&session.pid) {
+                continue;
+            }
+            if config
+                .muted_apps
+                .iter()
+                .any(|app| matches_app(&session.name, app))
+                && set_process_mute(session.pid, true).is_ok()
+            {
+                muted.insert(session.pid);
+                recovery::track(Action::Unmute, session.pid);
+            }
+        }
+    } else {
+        for pid in muted.drain() {
+            // The process may be gone already
+            let _ = set_process_mute(pid, false);
+            recovery::untrack(Action::Unmute, pid);
+        }
+    }
+}
+
+/// Compares executable names ignoring case and the `.exe` extension
+fn matches_app(process_name: &str, app: &str) -> bool {
+    fn normalize(name: &str) -> String {
+        let name = name.trim().to_lowercase();
+        match name.strip_suffix(".exe") {
+            Some(stem) => stem.to_string(),
+            None => name,
+        }
+    }
+
+    !app.trim().is_empty() && normalize(process_name) == normalize(app)
+}
+
+#[cfg(target_os = "windows")]
+mod wasapi {
+    use super::{AudioError, Result};
+    use windows::core::Interface;
+    use windows::Win32::Foundation::S_OK;
+    use windows::Win32::Media::Audio::{
+        eRender, AudioSessionStateActive, IAudioSessionControl, IAudioSessionControl2,
+        IAudioSessionManager2, IMMDeviceEnumerator, ISimpleAudioVolume, MMDeviceEnumerator,
+        DEVICE_STATE_ACTIVE,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    pub struct Session {
+        pub pid: u32,
+        pub display_name: String,
+        pub volume: f32,
+        pub is_muted: bool,
+        pub is_playing: bool,
+    }
+
+    fn api_error(e: windows::core::Error) -> AudioError {
+        AudioError::ApiError(e.to_string())
+    }
+
+    /// Calls `f` for every process session of every active output device,
+    /// the system sounds session excluded
+    unsafe fn for_each_session(
+        mut f: impl FnMut(u32, &IAudioSessionControl, &ISimpleAudioVolume),
+    ) -> Result<()> {
+        // Fails harmlessly when the thread already joined an apartment
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).map_err(api_error)?;
+        let devices = enumerator
+            .EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)
+            .map_err(api_error)?;
+
+        for index in 0..devices.GetCount().map_err(api_error)? {
+            let Ok(device) = devices.Item(index) else {
+                continue;
+            };
+            let Ok(manager) = device.Activate::<IAudioSessionManager2>(CLSCTX_ALL, None) else {
+                continue;
+            };
+            let Ok(list) = manager.GetSessionEnumerator() else {
+                continue;
+            };
+
+            for i in 0..list.GetCount().unwrap_or(0) {
+                let Ok(control) = list.GetSession(i) else {
+                    continue;
+                };
+                let Ok(control2) = control.cast::<IAudioSessionControl2>() else {
+                    continue;
+                };
+                if control2.IsSystemSoundsSession() == S_OK {
+                    continue;
+                }
+                let (Ok(pid), Ok(volume)) = (
+                    control2.GetProcessId(),
+                    control.cast::<ISimpleAudioVolume>(),
+                ) else {
+                    continue;
+                };
+
+                f(pid, &control, &volume);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn sessions() -> Result<Vec<Session>> {
+        let mut sessions = Vec::new();
+        unsafe {
+            for_each_session(|pid, control, volume| {
+                sessions.push(Session {
+                    pid,
+                    display_name: display_name(control),
+                    volume: volume.GetMasterVolume().unwrap_or(0.0),
+                    is_muted: volume.GetMute().map(|m| m.as_bool()).unwrap_or(false),
+                    is_playing: control
+                        .GetState()
+                        .map(|state| state == AudioSessionStateActive)
+                        .unwrap_or(false),
+                });
+            })?;
+        }
+        Ok(sessions)
+    }
+
+    /// Returns the number of sessions changed
+    pub fn set_mute(pid: u32, muted: bool) -> Result<usize> {
+        let mut changed = 0;
+        unsafe {
+            for_each_session(|session_pid, _, volume| {
+                if session_pid == pid && volume.SetMute(muted, std::ptr::null()).is_ok() {
+                    changed += 1;
+                }
+            })?;
+        }
+        Ok(changed)
+    }
+
+    /// Returns the number of sessions changed
+    pub fn set_volume(pid: u32, level: f32) -> Result<usize> {
+        let mut changed = 0;
+        unsafe {
+            for_each_session(|session_pid, _, volume| {
+                if session_pid == pid && volume.SetMasterVolume(level, std::ptr::null()).is_ok() {
+                    changed += 1;
+                }
+            })?;
+        }
+        Ok(changed)
+    }
+
+    unsafe fn display_name(control: &IAudioSessionControl) -> String {
+        match control.GetDisplayName() {
+            Ok(name) if !name.is_null() => {
+                let display_name = name.to_string().unwrap_or_default();
+                CoTaskMemFree(Some(name.0 as *const _));
+                display_name
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_app() {
+        assert!(matches_app("Discord.exe", "discord"));
+        assert!(matches_app("discord.exe", "Discord.exe"));
+        assert!(matches_app("spotify", "spotify.exe"));
+        assert!(!matches_app("Discord.exe", "discordptb"));
+        assert!(!matches_app("Discord.exe", " "));
+    }
+}
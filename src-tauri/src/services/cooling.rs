@@ -0,0 +1,332 @@
+// Switches the system cooling profile while a game runs and puts the previous
+// one back when the last such game exits. On Windows this is the power plan's
+// system cooling policy (active cools with the fans first, passive slows the
+// CPU first), on Linux the ACPI platform profile laptops expose. The setting
+// found before the switch is saved to disk, so a crash of Aura in the middle
+// of a game does not leave the fans on the gaming profile.
+use crate::models::game_profile::{CoolingProfile, CoolingStatus};
+use crate::shared::paths;
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+const ORIGINAL_FILE: &str = "cooling_original.json";
+
+#[cfg(not(target_os = "windows"))]
+const PLATFORM_PROFILE: &str = "/sys/firmware/acpi/platform_profile";
+#[cfg(not(target_os = "windows"))]
+const PLATFORM_PROFILE_CHOICES: &str = "/sys/firmware/acpi/platform_profile_choices";
+
+#[derive(Error, Debug)]
+pub enum CoolingError {
+    #[error("Fan control is not available on this system")]
+    Unsupported,
+
+    #[error("The {0:?} cooling profile is not available on this system")]
+    UnavailableProfile(CoolingProfile),
+
+    #[error("Failed to change the cooling profile: {0}")]
+    ApplyError(String),
+
+    #[error("Failed to save the previous cooling profile: {0}")]
+    StorageError(String),
+}
+
+type Result<T> = std::result::Result<T, CoolingError>;
+
+// The setting as the platform stores it: AC and DC policy indexes on Windows,
+// the platform profile name on Linux
+#[cfg(target_os = "windows")]
+type Setting = (u32, u32);
+#[cfg(not(target_os = "windows"))]
+type Setting = String;
+
+struct Held {
+    original: Setting,
+    games: Vec<u32>,
+}
+
+static HELD: once_cell::sync::Lazy<Mutex<Option<Held>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+pub fn get_status() -> CoolingStatus {
+    let current = read_setting().ok();
+    CoolingStatus {
+        supported: current.is_some(),
+        available: available(),
+        current: current.as_ref().and_then(profile_of),
+        games: HELD
+            .lock()
+            .ok()
+            .and_then(|held| held.as_ref().map(|held| held.games.clone()))
+            .unwrap_or_default(),
+    }
+}
+
+/// Profiles the system can switch to, empty without fan control
+pub fn available() -> Vec<CoolingProfile> {
+    #[cfg(target_os = "windows")]
+    {
+        match read_setting() {
+            Ok(_) => vec![CoolingProfile::Quiet, CoolingProfile::Performance],
+            Err(_) => Vec::new(),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let choices = std::fs::read_to_string(PLATFORM_PROFILE_CHOICES).unwrap_or_default();
+        [
+            CoolingProfile::Quiet,
+            CoolingProfile::Balanced,
+            CoolingProfile::Performance,
+        ]
+        .into_iter()
+        .filter(|profile| platform_choice(*profile, &choices).is_some())
+        .collect()
+    }
+}
+
+/// Switches to `profile` for a game. The setting found before the first game
+/// is kept until the last one is released.
+pub fn apply_for_game(game_pid: u32, profile: CoolingProfile) -> Result<()> {
+    let setting = setting_for(profile)?;
+    let mut held = HELD
+        .lock()
+        .map_err(|e| CoolingError::ApplyError(e.to_string()))?;
+    if held.is_none() {
+        let original = read_setting()?;
+        save_original(&original)?;
+        *held = Some(Held {
+            original,
+            games: Vec::new(),
+        });
+    }
+    let result = write_setting(&setting);
+    if let Some(held) = held.as_mut() {
+        if !held.games.contains(&game_pid) {
+            held.games.push(game_pid);
+        }
+    }
+    result
+}
+
+/// Restores the previous setting once no running game needs a profile
+pub fn release_game(game_pid: u32) {
+    let Ok(mut held) = HELD.lock() else {
+        return;
+    };
+    let Some(current) = held.as_mut() else {
+        return;
+    };
+    current.games.retain(|pid| *pid != game_pid);
+    if current.games.is_empty() {
+        if let Err(e) = write_setting(&current.original) {
+            eprintln!("Failed to restore the cooling profile: {}", e);
+        }
+        let _ = std::fs::remove_file(paths::app_data_file(ORIGINAL_FILE));
+        *held = None;
+    }
+}
+
+/// Restores the previous setting right away, whatever games still run. Used
+/// on exit and from the panic hook, hence the try_lock.
+pub fn release_all() {
+    let Ok(mut held) = HELD.try_lock() else {
+        return;
+    };
+    if let Some(current) = held.take() {
+        // The saved original is replayed on the next start if this fails
+        if write_setting(&current.original).is_ok() {
+            let _ = std::fs::remove_file(paths::app_data_file(ORIGINAL_FILE));
+        }
+    }
+}
+
+/// Puts back a setting left behind when Aura stopped during a game
+pub fn restore_leftover() {
+    let path = paths::app_data_file(ORIGINAL_FILE);
+    let Some(original) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Setting>(&content).ok())
+    else {
+        return;
+    };
+    if HELD.lock().is_ok_and(|held| held.is_none()) && write_setting(&original).is_ok() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn save_original(original: &Setting) -> Result<()> {
+    let content =
+        serde_json::to_string(original).map_err(|e| CoolingError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(ORIGINAL_FILE), content)
+        .map_err(|e| CoolingError::StorageError(e.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn read_setting() -> Result<Setting> {
+    let output = powercfg(&["/query", "SCHEME_CURRENT", "SUB_PROCESSOR", "SYSCOOLPOL"])?;
+    parse_setting_indexes(&output).ok_or(CoolingError::Unsupported)
+}
+
+#[cfg(target_os = "windows")]
+fn write_setting(setting: &Setting) -> Result<()> {
+    let (ac, dc) = setting;
+    let ac = ac.to_string();
+    let dc = dc.to_string();
+    powercfg(&[
+        "/setacvalueindex",
+        "SCHEME_CURRENT",
+        "SUB_PROCESSOR",
+        "SYSCOOLPOL",
+        &ac,
+    ])?;
+    powercfg(&[
+        "/setdcvalueindex",
+        "SCHEME_CURRENT",
+        "SUB_PROCESSOR",
+        "SYSCOOLPOL",
+        &dc,
+    ])?;
+    // Changes to the active scheme only apply once it is set again
+    powercfg(&["/setactive", "SCHEME_CURRENT"]).map(|_| ())
+}
+
+#[cfg(target_os = "windows")]
+fn setting_for(profile: CoolingProfile) -> Result<Setting> {
+    // 0 is the passive policy, 1 the active one
+    match profile {
+        CoolingProfile::Quiet => Ok((0, 0)),
+        CoolingProfile::Performance => Ok((1, 1)),
+        CoolingProfile::Balanced => Err(CoolingError::UnavailableProfile(profile)),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn profile_of(setting: &Setting) -> Option<CoolingProfile> {
+    // Plugged in is what matters while gaming
+    match setting.0 {
+        0 => Some(CoolingProfile::Quiet),
+        1 => Some(CoolingProfile::Performance),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn powercfg(args: &[&str]) -> Result<String> {
+    let output = Command::new("powercfg")
+        .args(args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .map_err(|e| CoolingError::ApplyError(e.to_string()))?;
+    if !output.status.success() {
+        // Systems without the policy reject the alias
+        return Err(CoolingError::Unsupported);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_setting() -> Result<Setting> {
+    std::fs::read_to_string(PLATFORM_PROFILE)
+        .map(|profile| profile.trim().to_string())
+        .map_err(|_| CoolingError::Unsupported)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn write_setting(setting: &Setting) -> Result<()> {
+    std::fs::write(PLATFORM_PROFILE, setting).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => {
+            CoolingError::ApplyError("changing the platform profile requires root".to_string())
+        }
+        _ => CoolingError::ApplyError(e.to_string()),
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+fn setting_for(profile: CoolingProfile) -> Result<Setting> {
+    let choices =
+        std::fs::read_to_string(PLATFORM_PROFILE_CHOICES).map_err(|_| CoolingError::Unsupported)?;
+    platform_choice(profile, &choices)
+        .map(str::to_string)
+        .ok_or(CoolingError::UnavailableProfile(profile))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn profile_of(setting: &Setting) -> Option<CoolingProfile> {
+    match setting.as_str() {
+        "cool" | "quiet" | "low-power" => Some(CoolingProfile::Quiet),
+        "balanced" => Some(CoolingProfile::Balanced),
+        "balanced-performance" | "performance" => Some(CoolingProfile::Performance),
+        _ => None,
+    }
+}
+
+/// The first of the platform profile names meaning `profile` that the driver
+/// offers, in order of preference
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn platform_choice(profile: CoolingProfile, choices: &str) -> Option<&'static str> {
+    let names: &[&'static str] = match profile {
+        CoolingProfile::Quiet => &["quiet", "cool", "low-power"],
+        CoolingProfile::Balanced => &["balanced"],
+        CoolingProfile::Performance => &["performance", "balanced-performance"],
+    };
+    let offered: Vec<&str> = choices.split_whitespace().collect();
+    names.iter().copied().find(|name| offered.contains(name))
+}
+
+/// The current AC and DC indexes of a `powercfg /query` for one setting.
+/// They are the last two hex values of the output, whatever its language.
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn parse_setting_indexes(output: &str) -> Option<(u32, u32)> {
+    let values: Vec<u32> = output
+        .lines()
+        .filter_map(|line| line.rsplit_once(':'))
+        .filter_map(|(_, value)| value.trim().strip_prefix("0x"))
+        .filter_map(|hex| u32::from_str_radix(hex, 16).ok())
+        .collect();
+    match values.as_slice() {
+        [.., ac, dc] => Some((*ac, *dc)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_choice() {
+        let choices = "low-power balanced performance\n";
+        assert_eq!(
+            platform_choice(CoolingProfile::Quiet, choices),
+            Some("low-power")
+        );
+        assert_eq!(
+            platform_choice(CoolingProfile::Performance, choices),
+            Some("performance")
+        );
+        assert_eq!(
+            platform_choice(CoolingProfile::Balanced, "quiet cool"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_setting_indexes() {
+        let output = "Power Scheme GUID: 381b4222-f694-41f0-9685-ff5bb260df2e  (Balanced)\r\n  \
+            Subgroup GUID: 54533251-82be-4824-96c1-47b60b740d00  (Processor power management)\r\n    \
+            Power Setting GUID: 94d3a615-a899-4ac5-ae2b-e4d8f634367f  (System cooling policy)\r\n      \
+            Possible Setting Index: 000\r\n      Possible Setting Friendly Name: Passive\r\n      \
+            Possible Setting Index: 001\r\n      Possible Setting Friendly Name: Active\r\n    \
+            Current AC Power Setting Index: 0x00000001\r\n    \
+            Current DC Power Setting Index: 0x00000000\r\n";
+        assert_eq!(parse_setting_indexes(output), Some((1, 0)));
+        assert_eq!(parse_setting_indexes("Invalid Parameters"), None);
+    }
+}
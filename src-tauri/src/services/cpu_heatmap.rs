@@ -0,0 +1,135 @@
+// Keeps the load of every logical core over the last few seconds, sampled
+// several times a second, for the heatmap of the CPU panel. The averages the
+// rest of the panel shows hide a single core pinned at 100% by a game's main
+// thread; a heatmap makes it stand out. Sampling only runs while the heatmap
+// is being read and stops on its own a while after the last read.
+use crate::models::system_stats::CpuHeatmap;
+use crate::shared::events;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::System;
+
+// As fast as sysinfo can tell usage apart
+const SAMPLE_INTERVAL: Duration = sysinfo::MINIMUM_CPU_UPDATE_INTERVAL;
+const WINDOW: Duration = Duration::from_secs(6);
+// The sampler stops when the heatmap hasn't been read for this long
+const IDLE_STOP: Duration = Duration::from_secs(30);
+// A core above this while the average of the others stays below the second
+const BOTTLENECK_CORE: f32 = 90.0;
+const BOTTLENECK_OTHERS: f32 = 50.0;
+
+struct Samples {
+    rows: VecDeque<Vec<u8>>,
+    last_at: Option<u64>,
+}
+
+static SAMPLES: Mutex<Samples> = Mutex::new(Samples {
+    rows: VecDeque::new(),
+    last_at: None,
+});
+static SAMPLER_RUNNING: AtomicBool = AtomicBool::new(false);
+static LAST_READ: AtomicU64 = AtomicU64::new(0);
+
+/// The samples of the last few seconds. The first call starts the sampler,
+/// so it returns few or no rows.
+pub fn get_heatmap() -> CpuHeatmap {
+    LAST_READ.store(events::now_millis(), Ordering::Relaxed);
+    start();
+
+    let (samples, last_sample_at): (Vec<Vec<u8>>, Option<u64>) = SAMPLES
+        .lock()
+        .map(|samples| (samples.rows.iter().cloned().collect(), samples.last_at))
+        .unwrap_or_default();
+    CpuHeatmap {
+        interval_ms: SAMPLE_INTERVAL.as_millis() as u64,
+        cores: samples.first().map_or(0, Vec::len),
+        bottleneck_core: bottleneck_core(&samples),
+        samples,
+        last_sample_at,
+    }
+}
+
+fn start() {
+    if SAMPLER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(|| {
+        let mut system = System::new();
+        system.refresh_cpu_usage();
+        let max_rows = (WINDOW.as_millis() / SAMPLE_INTERVAL.as_millis()).max(1) as usize;
+        loop {
+            std::thread::sleep(SAMPLE_INTERVAL);
+            let now = events::now_millis();
+            if now.saturating_sub(LAST_READ.load(Ordering::Relaxed)) > IDLE_STOP.as_millis() as u64
+            {
+                break;
+            }
+
+            system.refresh_cpu_usage();
+            let row: Vec<u8> = system
+                .cpus()
+                .iter()
+                .map(|cpu| cpu.cpu_usage().clamp(0.0, 100.0).round() as u8)
+                .collect();
+            if let Ok(mut samples) = SAMPLES.lock() {
+                samples.rows.push_back(row);
+                while samples.rows.len() > max_rows {
+                    samples.rows.pop_front();
+                }
+                samples.last_at = Some(now);
+            }
+        }
+
+        // Old rows would show up as current in the next first read
+        if let Ok(mut samples) = SAMPLES.lock() {
+            samples.rows.clear();
+            samples.last_at = None;
+        }
+        SAMPLER_RUNNING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// The busiest core over the window, when it stayed nearly full while the
+/// other cores had room left
+fn bottleneck_core(samples: &[Vec<u8>]) -> Option<usize> {
+    let cores = samples.first()?.len();
+    if cores < 2 {
+        return None;
+    }
+    let averages: Vec<f32> = (0..cores)
+        .map(|core| {
+            let total: u32 = samples
+                .iter()
+                .map(|row| row.get(core).copied().unwrap_or(0) as u32)
+                .sum();
+            total as f32 / samples.len() as f32
+        })
+        .collect();
+    let (busiest, load) = averages
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let others = (averages.iter().sum::<f32>() - load) / (cores - 1) as f32;
+    (load >= BOTTLENECK_CORE && others < BOTTLENECK_OTHERS).then_some(busiest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bottleneck_core() {
+        let pinned = vec![vec![20, 98, 10, 15], vec![25, 95, 12, 30]];
+        assert_eq!(bottleneck_core(&pinned), Some(1));
+        // Busy everywhere is a different problem
+        let busy = vec![vec![95, 98, 90, 92]];
+        assert_eq!(bottleneck_core(&busy), None);
+        let idle = vec![vec![5, 3, 10, 2]];
+        assert_eq!(bottleneck_core(&idle), None);
+        assert_eq!(bottleneck_core(&[]), None);
+    }
+}
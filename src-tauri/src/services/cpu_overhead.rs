@@ -0,0 +1,381 @@
+// CPU time that belongs to no process: hardware interrupts and the deferred
+// procedure calls drivers queue from them. A misbehaving network, audio or
+// storage driver shows up here long before it shows in Task Manager, as
+// stutter with low process CPU. On Windows the Processor performance
+// counters are read, on Linux the irq and softirq columns of /proc/stat
+// (softirqs being the closest thing to DPCs). Both are shares of time, so a
+// reading is two samples some time apart. Context switches and system calls
+// per second tell about the same overhead from the other side, an anti-cheat
+// or overlay hooking every frame drives them up.
+use crate::models::system_stats::InterruptTime;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "windows")]
+use crate::services::memory_breakdown;
+
+#[cfg(target_os = "windows")]
+use windows::core::w;
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Performance::{
+    PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW,
+    PdhOpenQueryW, PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE, PDH_HCOUNTER, PDH_HQUERY,
+    PDH_MORE_DATA,
+};
+
+/// Interrupt and DPC time over `interval`, per core and then for all cores
+pub fn measure(interval: Duration) -> Vec<InterruptTime> {
+    let Some(sampler) = Sampler::begin() else {
+        return Vec::new();
+    };
+    std::thread::sleep(interval);
+    sampler.finish()
+}
+
+// Context switch and system call totals of the previous call, rates are the
+// difference to it
+static LAST_EVENTS: once_cell::sync::Lazy<Mutex<Option<(KernelEvents, Instant)>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+/// Running totals since boot
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct KernelEvents {
+    context_switches: Option<u64>,
+    system_calls: Option<u64>, // not counted by Linux
+}
+
+/// Context switches and system calls per second since the previous call,
+/// None on the first and for what the system doesn't count
+pub fn kernel_event_rates() -> (Option<u64>, Option<u64>) {
+    let Some(events) = kernel_events() else {
+        return (None, None);
+    };
+    let now = Instant::now();
+    let Ok(mut last) = LAST_EVENTS.lock() else {
+        return (None, None);
+    };
+    let rates = match *last {
+        Some((previous, at)) => {
+            let elapsed = now.duration_since(at).as_secs_f64();
+            let rate = |previous: Option<u64>, current: Option<u64>| {
+                let delta = current?.checked_sub(previous?)?;
+                (elapsed > 0.0).then(|| (delta as f64 / elapsed) as u64)
+            };
+            (
+                rate(previous.context_switches, events.context_switches),
+                rate(previous.system_calls, events.system_calls),
+            )
+        }
+        None => (None, None),
+    };
+    *last = Some((events, now));
+    rates
+}
+
+#[cfg(target_os = "windows")]
+fn kernel_events() -> Option<KernelEvents> {
+    use windows::core::w;
+
+    let totals = memory_breakdown::query_raw_counters(&[
+        w!("\\System\\Context Switches/sec"),
+        w!("\\System\\System Calls/sec"),
+    ]);
+    match totals[..] {
+        [None, None] => None,
+        [context_switches, system_calls] => Some(KernelEvents {
+            context_switches,
+            system_calls,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn kernel_events() -> Option<KernelEvents> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    Some(KernelEvents {
+        context_switches: Some(parse_context_switches(&stat)?),
+        system_calls: None,
+    })
+}
+
+/// The "ctxt" line of /proc/stat
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn parse_context_switches(stat: &str) -> Option<u64> {
+    stat.lines()
+        .find_map(|line| line.strip_prefix("ctxt "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// The core that spent the most time on interrupts and DPCs together
+pub fn busiest_core(times: &[InterruptTime]) -> Option<&InterruptTime> {
+    times
+        .iter()
+        .filter(|time| time.core.is_some())
+        .max_by(|a, b| {
+            (a.interrupt_percent + a.dpc_percent).total_cmp(&(b.interrupt_percent + b.dpc_percent))
+        })
+}
+
+/// A reading in progress, for callers already waiting between two samples
+#[cfg(target_os = "windows")]
+pub struct Sampler {
+    query: PDH_HQUERY,
+    interrupt: PDH_HCOUNTER,
+    dpc: PDH_HCOUNTER,
+}
+
+#[cfg(target_os = "windows")]
+impl Sampler {
+    /// Takes the first sample, `finish` takes the second
+    pub fn begin() -> Option<Self> {
+        unsafe {
+            let mut query = PDH_HQUERY::default();
+            if PdhOpenQueryW(None, 0, &mut query) != 0 {
+                return None;
+            }
+            // Dropping the sampler closes the query
+            let mut sampler = Sampler {
+                query,
+                interrupt: PDH_HCOUNTER::default(),
+                dpc: PDH_HCOUNTER::default(),
+            };
+            let added = PdhAddEnglishCounterW(
+                query,
+                w!("\\Processor(*)\\% Interrupt Time"),
+                0,
+                &mut sampler.interrupt,
+            ) == 0
+                && PdhAddEnglishCounterW(
+                    query,
+                    w!("\\Processor(*)\\% DPC Time"),
+                    0,
+                    &mut sampler.dpc,
+                ) == 0;
+            if !added || PdhCollectQueryData(query) != 0 {
+                return None;
+            }
+            Some(sampler)
+        }
+    }
+
+    pub fn finish(self) -> Vec<InterruptTime> {
+        unsafe {
+            if PdhCollectQueryData(self.query) != 0 {
+                return Vec::new();
+            }
+            let interrupt = read_percentages(self.interrupt);
+            let dpc = read_percentages(self.dpc);
+            let mut times: Vec<InterruptTime> = interrupt
+                .into_iter()
+                .filter_map(|(instance, interrupt_percent)| {
+                    let dpc_percent = dpc
+                        .iter()
+                        .find(|(name, _)| *name == instance)
+                        .map_or(0.0, |(_, percent)| *percent);
+                    Some(InterruptTime {
+                        core: parse_instance_core(&instance)?,
+                        interrupt_percent,
+                        dpc_percent,
+                    })
+                })
+                .collect();
+            sort(&mut times);
+            times
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = PdhCloseQuery(self.query);
+        }
+    }
+}
+
+/// Every instance of a percentage counter as (instance name, percent)
+#[cfg(target_os = "windows")]
+unsafe fn read_percentages(counter: PDH_HCOUNTER) -> Vec<(String, f32)> {
+    let mut buffer_size: u32 = 0;
+    let mut item_count: u32 = 0;
+    let status = PdhGetFormattedCounterArrayW(
+        counter,
+        PDH_FMT_DOUBLE,
+        &mut buffer_size,
+        &mut item_count,
+        None,
+    );
+    if status != PDH_MORE_DATA || buffer_size == 0 {
+        return Vec::new();
+    }
+
+    // The item array is followed by the instance name strings, keep it 8-byte aligned
+    let mut buffer = vec![0u64; (buffer_size as usize).div_ceil(8)];
+    let items = buffer.as_mut_ptr() as *mut PDH_FMT_COUNTERVALUE_ITEM_W;
+    let status = PdhGetFormattedCounterArrayW(
+        counter,
+        PDH_FMT_DOUBLE,
+        &mut buffer_size,
+        &mut item_count,
+        Some(items),
+    );
+    if status != 0 {
+        return Vec::new();
+    }
+
+    std::slice::from_raw_parts(items, item_count as usize)
+        .iter()
+        .filter(|item| item.FmtValue.CStatus == 0)
+        .filter_map(|item| {
+            let name = item.szName.to_string().ok()?;
+            let percent = item.FmtValue.Anonymous.doubleValue.clamp(0.0, 100.0) as f32;
+            Some((name, percent))
+        })
+        .collect()
+}
+
+/// Core of a Processor counter instance: "3" is core 3, "_Total" all of them
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn parse_instance_core(instance: &str) -> Option<Option<usize>> {
+    match instance {
+        "_Total" => Some(None),
+        core => core.parse().ok().map(Some),
+    }
+}
+
+/// A reading in progress, for callers already waiting between two samples
+#[cfg(not(target_os = "windows"))]
+pub struct Sampler {
+    first: Vec<(Option<usize>, CpuTimes)>,
+}
+
+#[cfg(not(target_os = "windows"))]
+impl Sampler {
+    /// Takes the first sample, `finish` takes the second
+    pub fn begin() -> Option<Self> {
+        let stat = std::fs::read_to_string("/proc/stat").ok()?;
+        let first = parse_proc_stat(&stat);
+        (!first.is_empty()).then_some(Sampler { first })
+    }
+
+    pub fn finish(self) -> Vec<InterruptTime> {
+        let Ok(stat) = std::fs::read_to_string("/proc/stat") else {
+            return Vec::new();
+        };
+        let mut times = interrupt_times(&self.first, &parse_proc_stat(&stat));
+        sort(&mut times);
+        times
+    }
+}
+
+/// Jiffies of one line of /proc/stat
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+struct CpuTimes {
+    irq: u64,
+    softirq: u64,
+    total: u64,
+}
+
+/// The "cpu" lines of /proc/stat, None for the first one covering all cores
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn parse_proc_stat(stat: &str) -> Vec<(Option<usize>, CpuTimes)> {
+    stat.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let core = match fields.next()?.strip_prefix("cpu")? {
+                "" => None,
+                index => Some(index.parse().ok()?),
+            };
+            // user nice system idle iowait irq softirq steal, guest time is
+            // already part of user
+            let values: Vec<u64> = fields.take(8).filter_map(|v| v.parse().ok()).collect();
+            if values.len() < 7 {
+                return None;
+            }
+            Some((
+                core,
+                CpuTimes {
+                    irq: values[5],
+                    softirq: values[6],
+                    total: values.iter().sum(),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn interrupt_times(
+    first: &[(Option<usize>, CpuTimes)],
+    second: &[(Option<usize>, CpuTimes)],
+) -> Vec<InterruptTime> {
+    second
+        .iter()
+        .filter_map(|(core, after)| {
+            let (_, before) = first.iter().find(|(other, _)| other == core)?;
+            let total = after.total.checked_sub(before.total).filter(|t| *t > 0)? as f32;
+            let share =
+                |after: u64, before: u64| after.saturating_sub(before) as f32 / total * 100.0;
+            Some(InterruptTime {
+                core: *core,
+                interrupt_percent: share(after.irq, before.irq),
+                dpc_percent: share(after.softirq, before.softirq),
+            })
+        })
+        .collect()
+}
+
+/// Cores in order, all cores together last
+fn sort(times: &mut [InterruptTime]) {
+    times.sort_by_key(|time| time.core.unwrap_or(usize::MAX));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_context_switches() {
+        let stat =
+            "cpu  100 0 100 700 0 50 50 0 0 0\nintr 12345 0 0\nctxt 987654321\nbtime 1700000000\n";
+        assert_eq!(parse_context_switches(stat), Some(987654321));
+        assert_eq!(parse_context_switches("cpu  1 2 3\n"), None);
+    }
+
+    #[test]
+    fn test_parse_instance_core() {
+        assert_eq!(parse_instance_core("3"), Some(Some(3)));
+        assert_eq!(parse_instance_core("_Total"), Some(None));
+        assert_eq!(parse_instance_core("0,1"), None);
+    }
+
+    #[test]
+    fn test_interrupt_times() {
+        let first = parse_proc_stat(
+            "cpu  100 0 100 700 0 50 50 0 0 0\n\
+             cpu0 50 0 50 350 0 40 10 0 0 0\n\
+             cpu1 50 0 50 350 0 10 40 0 0 0\n\
+             intr 12345\n",
+        );
+        assert_eq!(first.len(), 3);
+        let second = parse_proc_stat(
+            "cpu  150 0 150 1600 0 60 140 0 0 0\n\
+             cpu0 75 0 75 800 0 50 50 0 0 0\n\
+             cpu1 75 0 75 800 0 10 70 0 0 0\n",
+        );
+        let mut times = interrupt_times(&first, &second);
+        sort(&mut times);
+        assert_eq!(times.len(), 3);
+        // cpu0 ran 550 jiffies, 10 of them on interrupts and 40 on softirqs
+        assert_eq!(times[0].core, Some(0));
+        assert!((times[0].interrupt_percent - 10.0 / 550.0 * 100.0).abs() < 0.01);
+        assert!((times[0].dpc_percent - 40.0 / 550.0 * 100.0).abs() < 0.01);
+        assert_eq!(times[2].core, None);
+        assert_eq!(busiest_core(&times).and_then(|time| time.core), Some(0));
+    }
+}
@@ -0,0 +1,240 @@
+// AMD power limits (PPT, TDC, EDC) and hints of PBO or a curve optimizer
+// undervolt, for the benchmark and thermal panels. The limits come from the
+// SMU power table the ryzen_smu driver exposes on Linux and the core voltage
+// from the zenpower hwmon driver. Windows has no way to read either without
+// a kernel driver, so only the clocks are reported there.
+use crate::models::system_stats::CpuTuningStatus;
+use crate::shared::system::get_system;
+
+// Package power limits of stock AMD desktop CPUs: 65, 105, 120 and 170 W TDP
+const STOCK_PPT_W: &[f32] = &[88.0, 142.0, 162.0, 230.0];
+// Boosting above this share of the rated clock counts as boosting
+const NEAR_BOOST: f32 = 0.95;
+// Stock Zen parts request well over this at their top clocks
+const UNDERVOLT_MAX_VOLTAGE: f32 = 1.25;
+
+#[cfg(target_os = "linux")]
+const PM_TABLE: &str = "/sys/kernel/ryzen_smu_drv/pm_table";
+#[cfg(target_os = "linux")]
+const PM_TABLE_VERSION: &str = "/sys/kernel/ryzen_smu_drv/pm_table_version";
+
+#[derive(Debug, Clone, PartialEq)]
+struct PowerLimits {
+    ppt_limit: f32,
+    ppt: f32,
+    tdc_limit: f32,
+    tdc: f32,
+    edc_limit: f32,
+    edc: f32,
+}
+
+pub fn get_status() -> CpuTuningStatus {
+    let (vendor, model, peak_clock_mhz) = match get_system().lock() {
+        Ok(mut system) => {
+            system.refresh_cpu_frequency();
+            let cpus = system.cpus();
+            (
+                cpus.first()
+                    .map(|cpu| cpu.vendor_id().to_string())
+                    .unwrap_or_default(),
+                cpus.first()
+                    .map(|cpu| cpu.brand().trim().to_string())
+                    .unwrap_or_default(),
+                cpus.iter()
+                    .map(|cpu| cpu.frequency() as u32)
+                    .max()
+                    .filter(|mhz| *mhz > 0),
+            )
+        }
+        Err(_) => (String::new(), String::new(), None),
+    };
+
+    let mut status = CpuTuningStatus {
+        vendor,
+        model,
+        source: None,
+        ppt_limit_w: None,
+        ppt_w: None,
+        tdc_limit_a: None,
+        tdc_a: None,
+        edc_limit_a: None,
+        edc_a: None,
+        core_voltage: None,
+        peak_clock_mhz,
+        rated_boost_mhz: None,
+        limits_modified: None,
+        undervolt_suspected: None,
+        notes: Vec::new(),
+    };
+    if status.vendor != "AuthenticAMD" {
+        status
+            .notes
+            .push("PPT, TDC and EDC limits only exist on AMD CPUs".to_string());
+        return status;
+    }
+    read_platform(&mut status);
+
+    status.limits_modified = status.ppt_limit_w.map(limits_modified);
+    status.undervolt_suspected = match (
+        status.peak_clock_mhz,
+        status.rated_boost_mhz,
+        status.core_voltage,
+    ) {
+        (Some(peak), Some(rated), Some(voltage)) => undervolt_suspected(peak, rated, voltage),
+        _ => None,
+    };
+    status
+}
+
+#[cfg(target_os = "linux")]
+fn read_platform(status: &mut CpuTuningStatus) {
+    let mut sources = Vec::new();
+    match read_pm_table() {
+        Some((version, table)) => match power_limits(version, &table) {
+            Some(limits) => {
+                status.ppt_limit_w = Some(limits.ppt_limit);
+                status.ppt_w = Some(limits.ppt);
+                status.tdc_limit_a = Some(limits.tdc_limit);
+                status.tdc_a = Some(limits.tdc);
+                status.edc_limit_a = Some(limits.edc_limit);
+                status.edc_a = Some(limits.edc);
+                sources.push("ryzen_smu");
+            }
+            None => status.notes.push(format!(
+                "Unknown SMU power table version {:#x}, limits not read",
+                version
+            )),
+        },
+        None => status
+            .notes
+            .push("Power limits need the ryzen_smu kernel module".to_string()),
+    }
+
+    match zenpower_voltage() {
+        Some(voltage) => {
+            status.core_voltage = Some(voltage);
+            sources.push("zenpower");
+        }
+        None => status
+            .notes
+            .push("Core voltage needs the zenpower kernel module".to_string()),
+    }
+    if !sources.is_empty() {
+        status.source = Some(sources.join(", "));
+    }
+
+    // amd-pstate reports the fused boost clock, acpi-cpufreq only the base
+    let khz = |file: &str| {
+        std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu0/cpufreq/{}", file))
+            .ok()
+            .and_then(|value| value.trim().parse::<u32>().ok())
+    };
+    status.rated_boost_mhz = khz("amd_pstate_max_freq")
+        .or_else(|| khz("cpuinfo_max_freq"))
+        .map(|khz| khz / 1000);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_platform(status: &mut CpuTuningStatus) {
+    status.notes.push(
+        "Power limits and core voltage need a kernel driver, they are only read on Linux"
+            .to_string(),
+    );
+}
+
+/// The version and the values of the SMU power table
+#[cfg(target_os = "linux")]
+fn read_pm_table() -> Option<(u32, Vec<f32>)> {
+    let version = std::fs::read(PM_TABLE_VERSION).ok()?;
+    let version = u32::from_le_bytes(version.get(..4)?.try_into().ok()?);
+    let table = std::fs::read(PM_TABLE).ok()?;
+    let values = table
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+    Some((version, values))
+}
+
+/// SVI2 core voltage of the zenpower hwmon driver
+#[cfg(target_os = "linux")]
+fn zenpower_voltage() -> Option<f32> {
+    std::fs::read_dir("/sys/class/hwmon")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            std::fs::read_to_string(path.join("name")).is_ok_and(|name| name.trim() == "zenpower")
+        })
+        .and_then(|path| std::fs::read_to_string(path.join("in0_input")).ok())
+        .and_then(|millivolts| millivolts.trim().parse::<f32>().ok())
+        .map(|millivolts| millivolts / 1000.0)
+}
+
+/// Limits and current values from a power table whose layout is known:
+/// Matisse (Zen 2) and Vermeer (Zen 3) desktop parts
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn power_limits(version: u32, table: &[f32]) -> Option<PowerLimits> {
+    if !matches!(version >> 16, 0x24 | 0x38) {
+        return None;
+    }
+    let value = |index: usize| table.get(index).copied().filter(|v| v.is_finite());
+    Some(PowerLimits {
+        ppt_limit: value(0)?,
+        ppt: value(1)?,
+        tdc_limit: value(2)?,
+        tdc: value(3)?,
+        edc_limit: value(8)?,
+        edc: value(9)?,
+    })
+}
+
+fn limits_modified(ppt_limit: f32) -> bool {
+    !STOCK_PPT_W
+        .iter()
+        .any(|stock| (ppt_limit - stock).abs() < 1.0)
+}
+
+/// Whether the CPU boosts at a voltage too low for stock settings. Unknown
+/// while it isn't boosting, idle voltages say nothing.
+fn undervolt_suspected(peak_mhz: u32, rated_mhz: u32, voltage: f32) -> Option<bool> {
+    if rated_mhz == 0 || (peak_mhz as f32) < rated_mhz as f32 * NEAR_BOOST {
+        return None;
+    }
+    Some(voltage < UNDERVOLT_MAX_VOLTAGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_limits() {
+        let mut table = vec![0.0; 16];
+        table[0] = 142.0;
+        table[1] = 98.5;
+        table[2] = 95.0;
+        table[3] = 61.2;
+        table[8] = 140.0;
+        table[9] = 120.4;
+        let limits = power_limits(0x380805, &table).unwrap();
+        assert_eq!(limits.ppt_limit, 142.0);
+        assert_eq!(limits.edc, 120.4);
+
+        assert!(power_limits(0x540104, &table).is_none());
+        assert!(power_limits(0x240903, &table[..4]).is_none());
+    }
+
+    #[test]
+    fn test_limits_modified() {
+        assert!(!limits_modified(88.0));
+        assert!(!limits_modified(141.6));
+        assert!(limits_modified(200.0));
+    }
+
+    #[test]
+    fn test_undervolt_suspected() {
+        assert_eq!(undervolt_suspected(4900, 5000, 1.18), Some(true));
+        assert_eq!(undervolt_suspected(4900, 5000, 1.42), Some(false));
+        assert_eq!(undervolt_suspected(2200, 5000, 0.95), None);
+    }
+}
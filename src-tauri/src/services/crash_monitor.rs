@@ -0,0 +1,345 @@
+use crate::models::session::{GameExitRecord, TrackedGame};
+use crate::services::process_control::{self, ProcessControlError};
+use crate::services::process_watcher::{self, ProcessEventKind};
+use crate::services::{mqtt, obs, quiet_hours, session_report};
+use crate::shared::events;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{CloseHandle, HANDLE, STILL_ACTIVE};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{
+    GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+
+type Result<T> = std::result::Result<T, ProcessControlError>;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+// WER writes the dump a few seconds after the process is gone
+const MINIDUMP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+// GPU cooling history and hardware changes attached to a crash
+const CRASH_COOLING_WINDOW_MS: u64 = 5 * 60 * 1000;
+
+struct TrackedEntry {
+    game: TrackedGame,
+    // Process handle kept open so the exit code survives the process
+    #[cfg(target_os = "windows")]
+    handle: isize,
+}
+
+static TRACKED_GAMES: once_cell::sync::Lazy<Mutex<HashMap<u32, TrackedEntry>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+static MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts watching a game process; when it exits, the exit code is recorded in
+/// the session report and, if it crashed, a `game-crashed` event is emitted.
+pub fn track_game(pid: u32, collect_minidumps: bool) -> Result<TrackedGame> {
+    let info = process_control::get_process_detailed_info(pid)?;
+
+    let game = TrackedGame {
+        pid,
+        name: info.name,
+        exe_path: info.exe_path,
+        started_at: events::now_millis(),
+        collect_minidumps,
+    };
+
+    #[cfg(target_os = "windows")]
+    let handle = unsafe {
+        OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+            .map_err(|e| ProcessControlError::OpenError(e.to_string()))?
+            .0 as isize
+    };
+
+    {
+        let mut tracked = TRACKED_GAMES
+            .lock()
+            .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+
+        if let Some(existing) = tracked.get(&pid) {
+            #[cfg(target_os = "windows")]
+            close_handle(handle);
+            return Ok(existing.game.clone());
+        }
+
+        tracked.insert(
+            pid,
+            TrackedEntry {
+                game: game.clone(),
+                #[cfg(target_os = "windows")]
+                handle,
+            },
+        );
+    }
+
+    session_report::update(|report| report.tracked_games.push(game.clone()));
+    mqtt::publish_event("game_started", &game);
+    obs::on_game_started();
+    ensure_monitor_running();
+
+    Ok(game)
+}
+
+pub fn untrack_game(pid: u32) {
+    if let Ok(mut tracked) = TRACKED_GAMES.lock() {
+        if let Some(_entry) = tracked.remove(&pid) {
+            #[cfg(target_os = "windows")]
+            close_handle(_entry.handle);
+        }
+    }
+    session_report::update(|report| report.tracked_games.retain(|g| g.pid != pid));
+}
+
+pub fn get_tracked_games() -> Vec<TrackedGame> {
+    TRACKED_GAMES
+        .lock()
+        .map(|tracked| tracked.values().map(|e| e.game.clone()).collect())
+        .unwrap_or_default()
+}
+
+fn ensure_monitor_running() {
+    if MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    process_watcher::start();
+    let process_events = process_watcher::subscribe();
+
+    std::thread::spawn(move || loop {
+        // Wake up as soon as a process exits, the timeout is only a safety net
+        match process_events.recv_timeout(POLL_INTERVAL) {
+            Ok(event) if event.kind != ProcessEventKind::Exited => continue,
+            Err(RecvTimeoutError::Disconnected) => std::thread::sleep(POLL_INTERVAL),
+            _ => {}
+        }
+        // Exits are picked up once monitoring resumes
+        if quiet_hours::is_paused() {
+            continue;
+        }
+
+        for (entry, exit_code) in take_exited_games() {
+            handle_game_exit(entry.game, exit_code);
+        }
+    });
+}
+
+/// Removes the games that are no longer running from the tracked set
+fn take_exited_games() -> Vec<(TrackedEntry, Option<u32>)> {
+    let mut tracked = match TRACKED_GAMES.lock() {
+        Ok(tracked) => tracked,
+        Err(_) => return Vec::new(),
+    };
+
+    let exited: Vec<(u32, Option<u32>)> = tracked
+        .values()
+        .filter_map(|entry| poll_exit_code(entry).map(|code| (entry.game.pid, code)))
+        .collect();
+
+    exited
+        .into_iter()
+        .filter_map(|(pid, code)| {
+            tracked.remove(&pid).map(|entry| {
+                #[cfg(target_os = "windows")]
+                close_handle(entry.handle);
+                (entry, code)
+            })
+        })
+        .collect()
+}
+
+/// Returns Some(exit code) once the process has exited, None while it runs
+#[cfg(target_os = "windows")]
+fn poll_exit_code(entry: &TrackedEntry) -> Option<Option<u32>> {
+    let mut exit_code: u32 = 0;
+    unsafe {
+        let handle = HANDLE(entry.handle as *mut std::ffi::c_void);
+        match GetExitCodeProcess(handle, &mut exit_code) {
+            Ok(()) if exit_code == STILL_ACTIVE.0 as u32 => None,
+            Ok(()) => Some(Some(exit_code)),
+            Err(_) => Some(None),
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn poll_exit_code(entry: &TrackedEntry) -> Option<Option<u32>> {
+    use crate::shared::system::get_system;
+    use sysinfo::{Pid, ProcessesToUpdate};
+
+    let pid = Pid::from(entry.game.pid as usize);
+    let mut system = get_system().lock().ok()?;
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+
+    // The exit code of a process we didn't spawn is not available here
+    match system.process(pid) {
+        Some(_) => None,
+        None => Some(None),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn close_handle(handle: isize) {
+    unsafe {
+        let _ = CloseHandle(HANDLE(handle as *mut std::ffi::c_void));
+    }
+}
+
+fn handle_game_exit(game: TrackedGame, exit_code: Option<u32>) {
+    let crash_reason = exit_code.and_then(classify_exit_code);
+    let crashed = crash_reason.is_some();
+
+    let exited_at = events::now_millis();
+    let (gpu_cooling, hardware_changes) = if crashed {
+        let since = exited_at.saturating_sub(CRASH_COOLING_WINDOW_MS);
+        (
+            session_report::gpu_cooling_since(since),
+            session_report::hardware_changes_since(since),
+        )
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let mut record = GameExitRecord {
+        pid: game.pid,
+        name: game.name.clone(),
+        exe_path: game.exe_path.clone(),
+        started_at: game.started_at,
+        exited_at,
+        exit_code,
+        crashed,
+        crash_reason: crash_reason.map(|r| r.to_string()),
+        minidump_paths: Vec::new(),
+        gpu_cooling,
+        hardware_changes,
+    };
+
+    if crashed && game.collect_minidumps {
+        // Don't block the poll loop while waiting for WER
+        std::thread::spawn(move || {
+            std::thread::sleep(MINIDUMP_GRACE_PERIOD);
+            record.minidump_paths = find_minidumps(&game);
+            // A GPU reset may have been noticed meanwhile
+            record.hardware_changes = session_report::hardware_changes_since(
+                exited_at.saturating_sub(CRASH_COOLING_WINDOW_MS),
+            );
+            publish_exit(record);
+        });
+    } else {
+        publish_exit(record);
+    }
+}
+
+fn publish_exit(record: GameExitRecord) {
+    if record.crashed {
+        events::emit(events::GAME_CRASHED, record.clone());
+    }
+    mqtt::publish_event("game_exited", &record);
+    obs::on_game_exited();
+    session_report::record_game_exit(record);
+}
+
+/// Maps an exit code to a crash reason. Exit codes with the NTSTATUS error
+/// severity bits, unhandled C++ exceptions and DXGI device-removed errors are
+/// treated as crashes; anything else is a normal (even if non-zero) exit.
+pub fn classify_exit_code(code: u32) -> Option<&'static str> {
+    match code {
+        0xC0000005 => Some("Access violation"),
+        0xC00000FD => Some("Stack overflow"),
+        0xC0000374 => Some("Heap corruption"),
+        0xC0000409 => Some("Stack buffer overrun / fail fast"),
+        0xC000001D => Some("Illegal instruction"),
+        0xC0000094 => Some("Integer division by zero"),
+        0xC0000142 => Some("DLL initialization failed"),
+        0xE06D7363 => Some("Unhandled C++ exception"),
+        0x887A0005 => Some("GPU device removed"),
+        0x887A0006 => Some("GPU device hung"),
+        0x887A0007 => Some("GPU device reset"),
+        code if code & 0xC0000000 == 0xC0000000 => Some("Unhandled exception"),
+        _ => None,
+    }
+}
+
+/// Looks for dump files written after the game started: WER LocalDumps,
+/// WER report folders and the game's own directory
+fn find_minidumps(game: &TrackedGame) -> Vec<String> {
+    let exe_name = Path::new(&game.exe_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| game.name.to_lowercase());
+
+    let mut search_dirs: Vec<PathBuf> = Vec::new();
+
+    if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA").map(PathBuf::from) {
+        search_dirs.push(local_app_data.join("CrashDumps"));
+        let wer = local_app_data.join("Microsoft\\Windows\\WER");
+        search_dirs.extend(wer_report_dirs(&wer.join("ReportArchive"), &exe_name));
+        search_dirs.extend(wer_report_dirs(&wer.join("ReportQueue"), &exe_name));
+    }
+
+    if let Some(game_dir) = Path::new(&game.exe_path).parent() {
+        search_dirs.push(game_dir.to_path_buf());
+        search_dirs.push(game_dir.join("Crashes"));
+        search_dirs.push(game_dir.join("CrashDumps"));
+    }
+
+    let since = UNIX_EPOCH + Duration::from_millis(game.started_at);
+    let mut dumps = Vec::new();
+
+    for dir in search_dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_dump = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("dmp"));
+            let is_recent = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified >= since);
+
+            if is_dump && is_recent {
+                dumps.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    dumps
+}
+
+/// WER report folders are named like AppCrash_game.exe_<hash>_<id>
+fn wer_report_dirs(root: &Path, exe_name: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            name.starts_with("appcrash_") && name.contains(exe_name)
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_exit_code() {
+        assert_eq!(classify_exit_code(0), None);
+        assert_eq!(classify_exit_code(1), None);
+        assert_eq!(classify_exit_code(0xC0000005), Some("Access violation"));
+        assert_eq!(classify_exit_code(0x887A0005), Some("GPU device removed"));
+        assert_eq!(classify_exit_code(0xC0001234), Some("Unhandled exception"));
+    }
+}
@@ -0,0 +1,307 @@
+// Counts the traffic of every network interface across sessions, split into
+// billing periods that start on a configurable day of the month. The OS
+// counters run since boot, so the last values seen are stored with the boot
+// time: traffic while Aura was closed or paused still counts as long as the
+// machine wasn't restarted in between. With a data cap set, an alert is raised
+// once at the warning threshold and once when the cap is reached.
+use crate::models::system_stats::{
+    DataCapAlert, DataCapConfig, DataUsagePeriod, DataUsageStatus, InterfaceUsage,
+};
+use crate::services::quiet_hours;
+use crate::shared::{dates, events, paths};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::{Networks, System};
+
+const CONFIG_FILE: &str = "data_cap.json";
+const USAGE_FILE: &str = "data_usage.json";
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+// Samples between writes of the usage file
+const SAVE_EVERY: u32 = 5;
+// A year of history next to the current period
+const PERIODS_KEPT: usize = 13;
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Usage {
+    boot_time: u64,
+    // Totals since boot at the last sample, (received, transmitted)
+    counters: HashMap<String, (u64, u64)>,
+    // By period start date
+    periods: BTreeMap<String, HashMap<String, InterfaceUsage>>,
+    // Period start and whether the cap itself was reported
+    alerted: Option<(String, bool)>,
+}
+
+static CONFIG: once_cell::sync::Lazy<Mutex<DataCapConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+static USAGE: once_cell::sync::Lazy<Mutex<Usage>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_usage()));
+
+static SAMPLER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts counting once. Subsequent calls do nothing.
+pub fn start() {
+    if SAMPLER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let mut networks = Networks::new_with_refreshed_list();
+        let mut unsaved = 0;
+        loop {
+            // Nothing is lost while paused, the counters keep running
+            if !quiet_hours::is_paused() {
+                sample(&mut networks);
+                unsaved += 1;
+                if unsaved >= SAVE_EVERY {
+                    save_usage();
+                    unsaved = 0;
+                }
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+}
+
+pub fn get_config() -> DataCapConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: DataCapConfig) -> Result<DataCapConfig> {
+    if !(1..=28).contains(&config.reset_day) {
+        return Err(anyhow!("The reset day must be between 1 and 28"));
+    }
+    if !(1..=100).contains(&config.warn_percent) {
+        return Err(anyhow!("The warning threshold must be between 1 and 100%"));
+    }
+    let config = DataCapConfig {
+        interfaces: config
+            .interfaces
+            .iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        ..config
+    };
+
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    // A new cap or threshold is checked from scratch
+    if let Ok(mut usage) = USAGE.lock() {
+        usage.alerted = None;
+    }
+    Ok(config)
+}
+
+/// Usage of the current period and the ones before it
+pub fn get_status() -> DataUsageStatus {
+    let config = get_config();
+    let current_start = period_start(dates::local_date(), config.reset_day);
+    let periods = USAGE
+        .lock()
+        .map(|usage| usage.periods.clone())
+        .unwrap_or_default();
+
+    let to_period =
+        |start: &String, interfaces: &HashMap<String, InterfaceUsage>| DataUsagePeriod {
+            start: start.clone(),
+            counted_bytes: counted_bytes(interfaces, &config.interfaces),
+            interfaces: interfaces.clone(),
+        };
+    let current = to_period(
+        &current_start,
+        periods.get(&current_start).unwrap_or(&HashMap::new()),
+    );
+    let history = periods
+        .iter()
+        .rev()
+        .filter(|(start, _)| **start != current_start)
+        .map(|(start, interfaces)| to_period(start, interfaces))
+        .collect();
+    let cap_used_percent = (config.enabled && config.cap_bytes > 0)
+        .then(|| current.counted_bytes as f64 / config.cap_bytes as f64 * 100.0);
+
+    DataUsageStatus {
+        config,
+        current,
+        history,
+        cap_used_percent,
+    }
+}
+
+/// Adds the traffic since the previous sample to the current period and
+/// alerts about the cap
+fn sample(networks: &mut Networks) {
+    networks.refresh(true);
+    let config = get_config();
+    let period = period_start(dates::local_date(), config.reset_day);
+    let Ok(mut usage) = USAGE.lock() else {
+        return;
+    };
+
+    let boot_time = System::boot_time();
+    if usage.boot_time != boot_time {
+        usage.boot_time = boot_time;
+        usage.counters.clear();
+    }
+    for (name, data) in networks.iter() {
+        if name.to_lowercase().contains("loopback") || name == "lo" {
+            continue;
+        }
+        let totals = (data.total_received(), data.total_transmitted());
+        let last = usage
+            .counters
+            .insert(name.clone(), totals)
+            .unwrap_or((0, 0));
+        let received = counter_delta(last.0, totals.0);
+        let transmitted = counter_delta(last.1, totals.1);
+        if received == 0 && transmitted == 0 {
+            continue;
+        }
+        let entry = usage
+            .periods
+            .entry(period.clone())
+            .or_default()
+            .entry(name.clone())
+            .or_default();
+        entry.received_bytes += received;
+        entry.transmitted_bytes += transmitted;
+    }
+    while usage.periods.len() > PERIODS_KEPT {
+        usage.periods.pop_first();
+    }
+
+    if !config.enabled || config.cap_bytes == 0 {
+        return;
+    }
+    let used_bytes = usage.periods.get(&period).map_or(0, |interfaces| {
+        counted_bytes(interfaces, &config.interfaces)
+    });
+    let Some(exceeded) = alert_level(used_bytes, config.cap_bytes, config.warn_percent) else {
+        return;
+    };
+    let reported = usage
+        .alerted
+        .as_ref()
+        .filter(|(start, _)| *start == period)
+        .map(|(_, exceeded)| *exceeded);
+    if reported == Some(true) || reported == Some(exceeded) {
+        return;
+    }
+    usage.alerted = Some((period.clone(), exceeded));
+    events::emit(
+        events::DATA_CAP_WARNING,
+        DataCapAlert {
+            period_start: period,
+            used_bytes,
+            cap_bytes: config.cap_bytes,
+            exceeded,
+            timestamp: events::now_millis(),
+        },
+    );
+}
+
+/// Start date of the billing period a local date falls in
+fn period_start((year, month, day): (i64, i64, i64), reset_day: u32) -> String {
+    let reset_day = reset_day.clamp(1, 28) as i64;
+    let (year, month) = match (day >= reset_day, month) {
+        (true, _) => (year, month),
+        (false, 1) => (year - 1, 12),
+        (false, _) => (year, month - 1),
+    };
+    format!("{:04}-{:02}-{:02}", year, month, reset_day)
+}
+
+/// Growth of a counter, which starts over when the adapter is reset
+fn counter_delta(last: u64, current: u64) -> u64 {
+    if current >= last {
+        current - last
+    } else {
+        current
+    }
+}
+
+fn counted_bytes(interfaces: &HashMap<String, InterfaceUsage>, counted: &[String]) -> u64 {
+    interfaces
+        .iter()
+        .filter(|(name, _)| counted.is_empty() || counted.contains(name))
+        .map(|(_, usage)| usage.received_bytes + usage.transmitted_bytes)
+        .sum()
+}
+
+/// Some(false) past the warning threshold, Some(true) past the cap itself
+fn alert_level(used_bytes: u64, cap_bytes: u64, warn_percent: u8) -> Option<bool> {
+    if used_bytes >= cap_bytes {
+        Some(true)
+    } else if used_bytes as u128 * 100 >= cap_bytes as u128 * warn_percent as u128 {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn load_config() -> DataCapConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+fn load_usage() -> Usage {
+    paths::load_json(USAGE_FILE)
+}
+
+fn save_usage() {
+    let Ok(usage) = USAGE.lock() else {
+        return;
+    };
+    if let Ok(content) = serde_json::to_string(&*usage) {
+        let _ = std::fs::write(paths::app_data_file(USAGE_FILE), content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_start() {
+        assert_eq!(period_start((2024, 3, 15), 1), "2024-03-01");
+        assert_eq!(period_start((2024, 3, 15), 15), "2024-03-15");
+        assert_eq!(period_start((2024, 3, 14), 15), "2024-02-15");
+        assert_eq!(period_start((2024, 1, 5), 10), "2023-12-10");
+    }
+
+    #[test]
+    fn test_cap_accounting() {
+        assert_eq!(counter_delta(100, 250), 150);
+        assert_eq!(counter_delta(500, 40), 40);
+
+        let mut interfaces = HashMap::new();
+        interfaces.insert(
+            "Ethernet".to_string(),
+            InterfaceUsage {
+                received_bytes: 700,
+                transmitted_bytes: 100,
+            },
+        );
+        interfaces.insert(
+            "Wi-Fi".to_string(),
+            InterfaceUsage {
+                received_bytes: 50,
+                transmitted_bytes: 0,
+            },
+        );
+        assert_eq!(counted_bytes(&interfaces, &[]), 850);
+        assert_eq!(counted_bytes(&interfaces, &["Wi-Fi".to_string()]), 50);
+
+        assert_eq!(alert_level(799, 1000, 80), None);
+        assert_eq!(alert_level(800, 1000, 80), Some(false));
+        assert_eq!(alert_level(1000, 1000, 80), Some(true));
+    }
+}
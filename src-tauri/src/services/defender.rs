@@ -0,0 +1,215 @@
+use crate::models::optimization::DefenderExclusions;
+use crate::shared::paths;
+use std::path::Path;
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+// Exclusions Aura added, so a revert never touches the user's own
+const MANAGED_EXCLUSIONS_FILE: &str = "defender_exclusions.json";
+
+#[derive(Error, Debug)]
+pub enum DefenderError {
+    #[cfg(target_os = "windows")]
+    #[error("Defender command failed: {0}")]
+    CommandError(String),
+
+    #[error("Not a folder: {0}")]
+    InvalidPath(String),
+
+    #[error("Failed to access Defender exclusion list: {0}")]
+    StorageError(String),
+
+    // Microsoft Defender only exists on Windows
+    #[cfg(not(target_os = "windows"))]
+    #[error("Microsoft Defender is not available on this platform")]
+    UnsupportedPlatform,
+}
+
+type Result<T> = std::result::Result<T, DefenderError>;
+
+static MANAGED: once_cell::sync::Lazy<Mutex<Vec<String>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_managed()));
+
+fn load_managed() -> Vec<String> {
+    std::fs::read_to_string(paths::app_data_file(MANAGED_EXCLUSIONS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_managed(managed: &[String]) -> Result<()> {
+    let content = serde_json::to_string_pretty(managed)
+        .map_err(|e| DefenderError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(MANAGED_EXCLUSIONS_FILE), content)
+        .map_err(|e| DefenderError::StorageError(e.to_string()))
+}
+
+/// Current path exclusions and real-time protection state. Windows only shows
+/// the exclusion list to administrators, so `paths` may be empty otherwise.
+pub fn get_exclusions() -> Result<DefenderExclusions> {
+    let managed = MANAGED
+        .lock()
+        .map_err(|e| DefenderError::StorageError(e.to_string()))?
+        .clone();
+
+    #[cfg(target_os = "windows")]
+    {
+        let output =
+            powershell("$p = Get-MpPreference; $p.DisableRealtimeMonitoring; $p.ExclusionPath")?;
+        let mut lines = output.lines().map(str::trim).filter(|l| !l.is_empty());
+        let real_time_protection_enabled = lines.next() != Some("True");
+
+        Ok(DefenderExclusions {
+            // "N/A: Must be an administrator to view exclusions"
+            paths: lines
+                .filter(|l| !l.starts_with("N/A"))
+                .map(str::to_string)
+                .collect(),
+            managed,
+            real_time_protection_enabled,
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = managed;
+        Err(DefenderError::UnsupportedPlatform)
+    }
+}
+
+/// Excludes game folders from real-time and scheduled scans. Files in these
+/// folders are no longer checked for malware until the exclusion is removed.
+pub fn add_exclusions(folders: &[String]) -> Result<DefenderExclusions> {
+    for folder in folders {
+        if !Path::new(folder).is_dir() {
+            return Err(DefenderError::InvalidPath(folder.clone()));
+        }
+    }
+
+    // Folders the user excluded already stay theirs and are never reverted
+    let existing = get_exclusions()?.paths;
+
+    let mut managed = MANAGED
+        .lock()
+        .map_err(|e| DefenderError::StorageError(e.to_string()))?;
+    let new: Vec<&String> = folders
+        .iter()
+        .filter(|f| !managed.iter().any(|m| same_folder(m, f)))
+        .filter(|f| !existing.iter().any(|e| same_folder(e, f)))
+        .collect();
+
+    if !new.is_empty() {
+        run_preference_command("Add-MpPreference", &new)?;
+        managed.extend(new.into_iter().cloned());
+        save_managed(&managed)?;
+    }
+
+    drop(managed);
+    get_exclusions()
+}
+
+/// Removes exclusions Aura added. Exclusions the user configured themselves
+/// are left alone.
+pub fn remove_exclusions(folders: &[String]) -> Result<DefenderExclusions> {
+    let mut managed = MANAGED
+        .lock()
+        .map_err(|e| DefenderError::StorageError(e.to_string()))?;
+    let removed: Vec<String> = managed
+        .iter()
+        .filter(|m| folders.iter().any(|f| same_folder(m, f)))
+        .cloned()
+        .collect();
+
+    if !removed.is_empty() {
+        run_preference_command("Remove-MpPreference", &removed.iter().collect::<Vec<_>>())?;
+        managed.retain(|m| !removed.contains(m));
+        save_managed(&managed)?;
+    }
+
+    drop(managed);
+    get_exclusions()
+}
+
+/// Removes every exclusion Aura added
+pub fn revert_all() -> Result<DefenderExclusions> {
+    let managed = MANAGED
+        .lock()
+        .map_err(|e| DefenderError::StorageError(e.to_string()))?
+        .clone();
+    remove_exclusions(&managed)
+}
+
+/// Whether Aura has exclusions in place, for the optimization `is_applied` flag
+pub fn has_managed_exclusions() -> bool {
+    MANAGED.lock().map(|m| !m.is_empty()).unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn run_preference_command(cmdlet: &str, folders: &[&String]) -> Result<()> {
+    let list = folders
+        .iter()
+        .map(|f| quote(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    powershell(&format!("{} -ExclusionPath {}", cmdlet, list)).map(|_| ())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn run_preference_command(_cmdlet: &str, _folders: &[&String]) -> Result<()> {
+    Err(DefenderError::UnsupportedPlatform)
+}
+
+#[cfg(target_os = "windows")]
+fn powershell(script: &str) -> Result<String> {
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .map_err(|e| DefenderError::CommandError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(DefenderError::CommandError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Single-quoted PowerShell string literal, quotes doubled
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Folder paths compared the way Windows does: case-insensitive, either
+/// separator, trailing separator ignored
+fn same_folder(a: &str, b: &str) -> bool {
+    fn normalize(path: &str) -> String {
+        path.replace('/', "\\")
+            .trim_end_matches('\\')
+            .to_lowercase()
+    }
+    normalize(a) == normalize(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("C:\\Games"), "'C:\\Games'");
+        assert_eq!(quote("C:\\Tom's Games"), "'C:\\Tom''s Games'");
+    }
+
+    #[test]
+    fn test_same_folder() {
+        assert!(same_folder("C:\\XboxGames\\", "c:/xboxgames"));
+        assert!(!same_folder("C:\\XboxGames", "C:\\XboxGames\\Halo"));
+    }
+}
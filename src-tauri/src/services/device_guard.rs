@@ -0,0 +1,171 @@
+use crate::models::optimization::VbsStatus;
+use anyhow::Result;
+use serde_json::Value;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+const HVCI_KEY: &str =
+    "HKLM\\SYSTEM\\CurrentControlSet\\Control\\DeviceGuard\\Scenarios\\HypervisorEnforcedCodeIntegrity";
+
+// Win32_DeviceGuard codes
+const VBS_STATUS_ENABLED: u64 = 1;
+const VBS_STATUS_RUNNING: u64 = 2;
+const SERVICE_HVCI: u64 = 2;
+const PROPERTY_MBEC: u64 = 7;
+
+/// Reads VBS and memory integrity state from Win32_DeviceGuard, plus the
+/// configured HVCI setting that takes effect on the next boot
+pub fn get_status() -> Result<VbsStatus> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "Get-CimInstance -ClassName Win32_DeviceGuard -Namespace root\\Microsoft\\Windows\\DeviceGuard | ConvertTo-Json",
+            ])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Failed to query Device Guard: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let device_guard: Value = serde_json::from_slice(&output.stdout)?;
+        Ok(parse_status(&device_guard, hvci_configured()))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        anyhow::bail!("Memory integrity is Windows-only")
+    }
+}
+
+/// Turns memory integrity on or off. The change only applies after a restart.
+pub fn set_memory_integrity(enabled: bool) -> Result<VbsStatus> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("reg")
+            .args([
+                "add",
+                HVCI_KEY,
+                "/v",
+                "Enabled",
+                "/t",
+                "REG_DWORD",
+                "/d",
+                if enabled { "1" } else { "0" },
+                "/f",
+            ])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Failed to change memory integrity: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        get_status()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = enabled;
+        anyhow::bail!("Memory integrity is Windows-only")
+    }
+}
+
+/// Whether memory integrity is configured off, for the optimization `is_applied` flag
+pub fn is_memory_integrity_disabled() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        hvci_configured() == Some(false)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        false
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn hvci_configured() -> Option<bool> {
+    let output = Command::new("reg")
+        .args(["query", HVCI_KEY, "/v", "Enabled"])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let (_, value) = line.split_once("REG_DWORD")?;
+            u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+        })
+        .map(|value| value != 0)
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_status(device_guard: &Value, hvci_configured: Option<bool>) -> VbsStatus {
+    // ConvertTo-Json writes single-element arrays as plain numbers on older PowerShell
+    let codes = |field: &str| -> Vec<u64> {
+        match &device_guard[field] {
+            Value::Array(values) => values.iter().filter_map(Value::as_u64).collect(),
+            value => value.as_u64().into_iter().collect(),
+        }
+    };
+
+    let vbs_status = device_guard["VirtualizationBasedSecurityStatus"]
+        .as_u64()
+        .unwrap_or(0);
+    let hvci_running = codes("SecurityServicesRunning").contains(&SERVICE_HVCI);
+
+    VbsStatus {
+        vbs_enabled: vbs_status >= VBS_STATUS_ENABLED,
+        vbs_running: vbs_status == VBS_STATUS_RUNNING,
+        hvci_running,
+        hvci_configured,
+        mbec_supported: codes("AvailableSecurityProperties").contains(&PROPERTY_MBEC),
+        restart_required: hvci_configured.is_some_and(|configured| configured != hvci_running),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let device_guard = serde_json::json!({
+            "AvailableSecurityProperties": [1, 2, 3, 5, 7],
+            "SecurityServicesRunning": [2],
+            "VirtualizationBasedSecurityStatus": 2
+        });
+
+        let status = parse_status(&device_guard, Some(false));
+        assert!(status.vbs_enabled && status.vbs_running);
+        assert!(status.hvci_running);
+        assert!(status.mbec_supported);
+        assert!(status.restart_required);
+
+        let off = serde_json::json!({
+            "AvailableSecurityProperties": 1,
+            "SecurityServicesRunning": 0,
+            "VirtualizationBasedSecurityStatus": 0
+        });
+        let status = parse_status(&off, None);
+        assert!(!status.vbs_enabled && !status.hvci_running && !status.mbec_supported);
+        assert!(!status.restart_required);
+    }
+}
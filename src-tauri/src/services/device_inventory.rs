@@ -0,0 +1,912 @@
+use crate::models::bluetooth_device::{BluetoothDevice, BluetoothDeviceKind};
+use crate::models::usb_device::{UsbDevice, UsbSpeed};
+use anyhow::Result;
+
+// Battery level at or below which a device is flagged before a match
+const LOW_BATTERY_PERCENT: u8 = 20;
+
+/// Connected USB peripherals with their vendor, product and link speed.
+/// Hubs are left out; mice also report their polling rate when the
+/// interrupt endpoint descriptor is readable.
+pub fn get_usb_devices() -> Result<Vec<UsbDevice>> {
+    #[cfg(target_os = "windows")]
+    let mut devices = setupapi::usb_devices()?;
+
+    #[cfg(target_os = "linux")]
+    let mut devices = sysfs::usb_devices()?;
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let mut devices: Vec<UsbDevice> = Vec::new();
+
+    devices.sort_by(|a, b| {
+        b.is_mouse
+            .cmp(&a.is_mouse)
+            .then_with(|| a.product.cmp(&b.product))
+    });
+    Ok(devices)
+}
+
+/// Polling rate of an interrupt endpoint from its `bInterval`. Low and full
+/// speed count it in 1 ms frames, high speed and faster in 2^(n-1)
+/// microframes of 125 µs.
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+fn polling_rate_hz(speed: UsbSpeed, b_interval: u8) -> Option<u32> {
+    match speed {
+        UsbSpeed::Low | UsbSpeed::Full if b_interval > 0 => Some(1000 / b_interval as u32),
+        UsbSpeed::High | UsbSpeed::Super | UsbSpeed::SuperPlus
+            if (1..=16).contains(&b_interval) =>
+        {
+            Some(8000 >> (b_interval - 1))
+        }
+        _ => None,
+    }
+}
+
+/// Paired Bluetooth peripherals, connected ones with the lowest battery first.
+/// Only devices exposing a battery service (most headsets and controllers)
+/// report a level.
+pub fn get_bluetooth_devices() -> Result<Vec<BluetoothDevice>> {
+    #[cfg(target_os = "windows")]
+    let mut devices = bluetooth::paired_devices();
+
+    #[cfg(target_os = "linux")]
+    let mut devices = bluez::paired_devices();
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let mut devices: Vec<BluetoothDevice> = Vec::new();
+
+    for device in &mut devices {
+        device.low_battery = device
+            .battery_percent
+            .is_some_and(|percent| percent <= LOW_BATTERY_PERCENT);
+    }
+    devices.sort_by_key(|d| (!d.is_connected, d.battery_percent.unwrap_or(u8::MAX)));
+    Ok(devices)
+}
+
+/// Maps a Bluetooth Class of Device to the kind of peripheral
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn kind_from_class_of_device(class: u32) -> BluetoothDeviceKind {
+    const MAJOR_AUDIO_VIDEO: u32 = 0x04;
+    const MAJOR_PERIPHERAL: u32 = 0x05;
+
+    let major = (class >> 8) & 0x1F;
+    let minor = (class >> 2) & 0x3F;
+    match major {
+        MAJOR_AUDIO_VIDEO => BluetoothDeviceKind::Headset,
+        // Peripheral minor class: bits 4-5 keyboard/pointing, bits 0-3 joystick/gamepad
+        MAJOR_PERIPHERAL if matches!(minor & 0x0F, 0x01 | 0x02) => BluetoothDeviceKind::Controller,
+        MAJOR_PERIPHERAL if minor >> 4 == 0b10 => BluetoothDeviceKind::Mouse,
+        MAJOR_PERIPHERAL if minor >> 4 & 0b01 != 0 => BluetoothDeviceKind::Keyboard,
+        _ => BluetoothDeviceKind::Other,
+    }
+}
+
+/// Maps a BlueZ device icon name to the kind of peripheral
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn kind_from_icon(icon: &str) -> BluetoothDeviceKind {
+    match icon {
+        "input-gaming" => BluetoothDeviceKind::Controller,
+        "audio-headset" | "audio-headphones" | "audio-card" => BluetoothDeviceKind::Headset,
+        "input-mouse" | "input-tablet" => BluetoothDeviceKind::Mouse,
+        "input-keyboard" => BluetoothDeviceKind::Keyboard,
+        _ => BluetoothDeviceKind::Other,
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod setupapi {
+    use super::polling_rate_hz;
+    use crate::models::usb_device::{UsbDevice, UsbSpeed};
+    use std::collections::{HashMap, HashSet};
+    use std::mem::size_of;
+    use windows::core::{w, GUID, PCWSTR};
+    use windows::Win32::Devices::DeviceAndDriverInstallation::{
+        CM_Get_Parent, SetupDiDestroyDeviceInfoList, SetupDiEnumDeviceInfo,
+        SetupDiEnumDeviceInterfaces, SetupDiGetClassDevsW, SetupDiGetDeviceInstanceIdW,
+        SetupDiGetDeviceInterfaceDetailW, SetupDiGetDevicePropertyW,
+        SetupDiGetDeviceRegistryPropertyW, CR_SUCCESS, DIGCF_ALLCLASSES, DIGCF_DEVICEINTERFACE,
+        DIGCF_PRESENT, GUID_DEVCLASS_MOUSE, HDEVINFO, SETUP_DI_REGISTRY_PROPERTY, SPDRP_ADDRESS,
+        SPDRP_DEVICEDESC, SPDRP_FRIENDLYNAME, SPDRP_HARDWAREID, SPDRP_MFG,
+        SP_DEVICE_INTERFACE_DATA, SP_DEVICE_INTERFACE_DETAIL_DATA_W, SP_DEVINFO_DATA,
+    };
+    use windows::Win32::Devices::Properties::{DEVPROPTYPE, DEVPROP_TYPE_BYTE};
+    use windows::Win32::Devices::Usb::{
+        GUID_DEVINTERFACE_USB_DEVICE, GUID_DEVINTERFACE_USB_HUB,
+        IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX,
+        IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX_V2, USB_NODE_CONNECTION_INFORMATION_EX,
+        USB_NODE_CONNECTION_INFORMATION_EX_V2, USB_PIPE_INFO,
+    };
+    use windows::Win32::Foundation::{CloseHandle, DEVPROPKEY, GENERIC_WRITE, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows::Win32::System::IO::DeviceIoControl;
+
+    // DEVPKEY_Bluetooth_Battery, set by the hands-free and GATT battery drivers
+    const DEVPKEY_BLUETOOTH_BATTERY: DEVPROPKEY = DEVPROPKEY {
+        fmtid: GUID::from_u128(0x104ea319_6ee2_4701_bd47_8ddbf425bbe5),
+        pid: 2,
+    };
+    // Enumerators of classic and Low Energy Bluetooth device nodes
+    const BLUETOOTH_ENUMERATORS: [PCWSTR; 3] = [w!("BTHENUM"), w!("BTHLE"), w!("BTHLEDEVICE")];
+
+    // Room for the pipe list that follows the fixed part of the connection info
+    const MAX_PIPES: usize = 32;
+    // A mouse devnode sits below the HID interface and the composite interface
+    const MAX_ANCESTOR_DEPTH: usize = 4;
+
+    // USB_PROTOCOLS: Usb110 | Usb200 | Usb300
+    const ALL_USB_PROTOCOLS: u32 = 0b111;
+    // USB_NODE_CONNECTION_INFORMATION_EX_V2_FLAGS bits
+    const OPERATING_AT_SUPER_SPEED: u32 = 0b001;
+    const OPERATING_AT_SUPER_SPEED_PLUS: u32 = 0b100;
+
+    struct Connection {
+        speed: UsbSpeed,
+        is_hub: bool,
+        min_interrupt_interval: Option<u8>,
+    }
+
+    pub fn usb_devices() -> anyhow::Result<Vec<UsbDevice>> {
+        unsafe {
+            let hubs = hub_paths();
+            let mice = mouse_ancestors();
+
+            let set = SetupDiGetClassDevsW(
+                Some(&GUID_DEVINTERFACE_USB_DEVICE),
+                PCWSTR::null(),
+                None,
+                DIGCF_PRESENT | DIGCF_DEVICEINTERFACE,
+            )?;
+
+            let mut devices = Vec::new();
+            let mut index = 0;
+            loop {
+                let mut info = SP_DEVINFO_DATA {
+                    cbSize: size_of::<SP_DEVINFO_DATA>() as u32,
+                    ..Default::default()
+                };
+                if SetupDiEnumDeviceInfo(set, index, &mut info).is_err() {
+                    break;
+                }
+                index += 1;
+
+                let Some((vendor_id, product_id)) = registry_string(set, &info, SPDRP_HARDWAREID)
+                    .and_then(|id| parse_hardware_id(&id))
+                else {
+                    continue;
+                };
+
+                // The hub port the device is plugged into holds the link speed
+                let connection = parent(info.DevInst)
+                    .and_then(|hub| hubs.get(&hub))
+                    .zip(registry_u32(set, &info, SPDRP_ADDRESS))
+                    .and_then(|(hub_path, port)| query_connection(hub_path, port));
+                if connection.as_ref().is_some_and(|c| c.is_hub) {
+                    continue;
+                }
+
+                let speed = connection
+                    .as_ref()
+                    .map(|c| c.speed)
+                    .unwrap_or(UsbSpeed::Unknown);
+                let is_mouse = mice.contains(&info.DevInst);
+                let polling_rate_hz = connection
+                    .and_then(|c| c.min_interrupt_interval)
+                    .filter(|_| is_mouse)
+                    .and_then(|interval| polling_rate_hz(speed, interval));
+
+                devices.push(UsbDevice {
+                    vendor_id,
+                    product_id,
+                    manufacturer: registry_string(set, &info, SPDRP_MFG),
+                    product: registry_string(set, &info, SPDRP_FRIENDLYNAME)
+                        .or_else(|| registry_string(set, &info, SPDRP_DEVICEDESC)),
+                    speed,
+                    is_mouse,
+                    polling_rate_hz,
+                });
+            }
+
+            let _ = SetupDiDestroyDeviceInfoList(set);
+            Ok(devices)
+        }
+    }
+
+    /// Battery level per Bluetooth address (12 uppercase hex digits), read
+    /// from the device nodes of the Bluetooth stack
+    pub(super) fn bluetooth_battery_levels() -> HashMap<String, u8> {
+        let mut levels = HashMap::new();
+
+        for enumerator in BLUETOOTH_ENUMERATORS {
+            unsafe {
+                let Ok(set) =
+                    SetupDiGetClassDevsW(None, enumerator, None, DIGCF_PRESENT | DIGCF_ALLCLASSES)
+                else {
+                    continue;
+                };
+
+                let mut index = 0;
+                loop {
+                    let mut info = SP_DEVINFO_DATA {
+                        cbSize: size_of::<SP_DEVINFO_DATA>() as u32,
+                        ..Default::default()
+                    };
+                    if SetupDiEnumDeviceInfo(set, index, &mut info).is_err() {
+                        break;
+                    }
+                    index += 1;
+
+                    let mut property_type = DEVPROPTYPE::default();
+                    let mut value = [0u8; 1];
+                    if SetupDiGetDevicePropertyW(
+                        set,
+                        &info,
+                        &DEVPKEY_BLUETOOTH_BATTERY,
+                        &mut property_type,
+                        Some(&mut value),
+                        None,
+                        0,
+                    )
+                    .is_err()
+                        || property_type != DEVPROP_TYPE_BYTE
+                    {
+                        continue;
+                    }
+
+                    if let Some(address) =
+                        instance_id(set, &info).and_then(|id| address_from_instance_id(&id))
+                    {
+                        levels.insert(address, value[0].min(100));
+                    }
+                }
+
+                let _ = SetupDiDestroyDeviceInfoList(set);
+            }
+        }
+
+        levels
+    }
+
+    unsafe fn instance_id(set: HDEVINFO, info: &SP_DEVINFO_DATA) -> Option<String> {
+        let mut buffer = [0u16; 512];
+        SetupDiGetDeviceInstanceIdW(set, info, Some(&mut buffer), None).ok()?;
+        let length = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..length]))
+    }
+
+    /// The device address is the last 12-digit hex token of the instance id, e.g.
+    /// `BTHENUM\{...}_VID&0001005D_PID&B00D\7&2A7B1B5A&0&E4A7A0A1B2C3_C00000000`
+    fn address_from_instance_id(instance_id: &str) -> Option<String> {
+        instance_id
+            .split(['\\', '&', '_'])
+            .rfind(|token| token.len() == 12 && token.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(|token| token.to_uppercase())
+    }
+
+    /// Extracts VID and PID from a hardware id like `USB\VID_046D&PID_C08B&REV_2702`
+    fn parse_hardware_id(hardware_id: &str) -> Option<(u16, u16)> {
+        let upper = hardware_id.to_uppercase();
+        let field = |name: &str| {
+            let start = upper.find(name)? + name.len();
+            u16::from_str_radix(upper.get(start..start + 4)?, 16).ok()
+        };
+        Some((field("VID_")?, field("PID_")?))
+    }
+
+    unsafe fn parent(devinst: u32) -> Option<u32> {
+        let mut parent = 0;
+        (CM_Get_Parent(&mut parent, devinst, 0) == CR_SUCCESS).then_some(parent)
+    }
+
+    /// Device instances of every USB hub, mapped to their interface path
+    unsafe fn hub_paths() -> HashMap<u32, Vec<u16>> {
+        let mut hubs = HashMap::new();
+        let Ok(set) = SetupDiGetClassDevsW(
+            Some(&GUID_DEVINTERFACE_USB_HUB),
+            PCWSTR::null(),
+            None,
+            DIGCF_PRESENT | DIGCF_DEVICEINTERFACE,
+        ) else {
+            return hubs;
+        };
+
+        let mut index = 0;
+        loop {
+            let mut interface = SP_DEVICE_INTERFACE_DATA {
+                cbSize: size_of::<SP_DEVICE_INTERFACE_DATA>() as u32,
+                ..Default::default()
+            };
+            if SetupDiEnumDeviceInterfaces(
+                set,
+                None,
+                &GUID_DEVINTERFACE_USB_HUB,
+                index,
+                &mut interface,
+            )
+            .is_err()
+            {
+                break;
+            }
+            index += 1;
+
+            let mut required = 0u32;
+            let _ = SetupDiGetDeviceInterfaceDetailW(
+                set,
+                &interface,
+                None,
+                0,
+                Some(&mut required),
+                None,
+            );
+            if (required as usize) <= size_of::<u32>() {
+                continue;
+            }
+
+            // cbSize is the size of the fixed part, the path follows it
+            let mut buffer = vec![0u64; (required as usize).div_ceil(8)];
+            let detail = buffer.as_mut_ptr() as *mut SP_DEVICE_INTERFACE_DETAIL_DATA_W;
+            (*detail).cbSize = size_of::<SP_DEVICE_INTERFACE_DETAIL_DATA_W>() as u32;
+            let mut info = SP_DEVINFO_DATA {
+                cbSize: size_of::<SP_DEVINFO_DATA>() as u32,
+                ..Default::default()
+            };
+            if SetupDiGetDeviceInterfaceDetailW(
+                set,
+                &interface,
+                Some(detail),
+                required,
+                None,
+                Some(&mut info),
+            )
+            .is_err()
+            {
+                continue;
+            }
+
+            let path = std::slice::from_raw_parts(
+                std::ptr::addr_of!((*detail).DevicePath) as *const u16,
+                (required as usize - size_of::<u32>()) / 2,
+            );
+            let path: Vec<u16> = path
+                .iter()
+                .copied()
+                .take_while(|c| *c != 0)
+                .chain(std::iter::once(0))
+                .collect();
+            hubs.insert(info.DevInst, path);
+        }
+
+        let _ = SetupDiDestroyDeviceInfoList(set);
+        hubs
+    }
+
+    /// Device instances that have a mouse somewhere below them
+    unsafe fn mouse_ancestors() -> HashSet<u32> {
+        let mut ancestors = HashSet::new();
+        let Ok(set) = SetupDiGetClassDevsW(
+            Some(&GUID_DEVCLASS_MOUSE),
+            PCWSTR::null(),
+            None,
+            DIGCF_PRESENT,
+        ) else {
+            return ancestors;
+        };
+
+        let mut index = 0;
+        loop {
+            let mut info = SP_DEVINFO_DATA {
+                cbSize: size_of::<SP_DEVINFO_DATA>() as u32,
+                ..Default::default()
+            };
+            if SetupDiEnumDeviceInfo(set, index, &mut info).is_err() {
+                break;
+            }
+            index += 1;
+
+            let mut devinst = info.DevInst;
+            for _ in 0..MAX_ANCESTOR_DEPTH {
+                match parent(devinst) {
+                    Some(p) => {
+                        ancestors.insert(p);
+                        devinst = p;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        let _ = SetupDiDestroyDeviceInfoList(set);
+        ancestors
+    }
+
+    unsafe fn registry_property(
+        set: HDEVINFO,
+        info: &SP_DEVINFO_DATA,
+        property: SETUP_DI_REGISTRY_PROPERTY,
+    ) -> Option<Vec<u8>> {
+        let mut required = 0u32;
+        let _ =
+            SetupDiGetDeviceRegistryPropertyW(set, info, property, None, None, Some(&mut required));
+        if required == 0 {
+            return None;
+        }
+
+        let mut buffer = vec![0u8; required as usize];
+        SetupDiGetDeviceRegistryPropertyW(set, info, property, None, Some(&mut buffer), None)
+            .ok()?;
+        Some(buffer)
+    }
+
+    /// Reads a REG_SZ property; for REG_MULTI_SZ only the first entry is kept
+    unsafe fn registry_string(
+        set: HDEVINFO,
+        info: &SP_DEVINFO_DATA,
+        property: SETUP_DI_REGISTRY_PROPERTY,
+    ) -> Option<String> {
+        let buffer = registry_property(set, info, property)?;
+        let wide: Vec<u16> = buffer
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        let value = String::from_utf16_lossy(wide.split(|c| *c == 0).next()?);
+        (!value.is_empty()).then_some(value)
+    }
+
+    unsafe fn registry_u32(
+        set: HDEVINFO,
+        info: &SP_DEVINFO_DATA,
+        property: SETUP_DI_REGISTRY_PROPERTY,
+    ) -> Option<u32> {
+        let buffer = registry_property(set, info, property)?;
+        Some(u32::from_le_bytes(buffer.get(..4)?.try_into().ok()?))
+    }
+
+    /// Asks the hub for the speed and endpoints of the device on `port`
+    unsafe fn query_connection(hub_path: &[u16], port: u32) -> Option<Connection> {
+        let hub = CreateFileW(
+            PCWSTR(hub_path.as_ptr()),
+            GENERIC_WRITE.0,
+            FILE_SHARE_WRITE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAGS_AND_ATTRIBUTES(0),
+            None,
+        )
+        .ok()?;
+
+        let connection = query_connection_info(hub, port);
+        let _ = CloseHandle(hub);
+        connection
+    }
+
+    unsafe fn query_connection_info(hub: HANDLE, port: u32) -> Option<Connection> {
+        // The struct is packed and ends with a variable-length pipe list
+        let fixed_size = size_of::<USB_NODE_CONNECTION_INFORMATION_EX>();
+        let size = fixed_size + MAX_PIPES * size_of::<USB_PIPE_INFO>();
+        let mut buffer = vec![0u8; size];
+        let info_ptr = buffer.as_mut_ptr() as *mut USB_NODE_CONNECTION_INFORMATION_EX;
+        std::ptr::addr_of_mut!((*info_ptr).ConnectionIndex).write_unaligned(port);
+
+        DeviceIoControl(
+            hub,
+            IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX,
+            Some(info_ptr as *const _),
+            size as u32,
+            Some(info_ptr as *mut _),
+            size as u32,
+            None,
+            None,
+        )
+        .ok()?;
+
+        let info = std::ptr::read_unaligned(info_ptr);
+        let pipes_offset = fixed_size - size_of::<USB_PIPE_INFO>();
+        let pipe_count = (info.NumberOfOpenPipes as usize).min(MAX_PIPES);
+
+        // Interrupt IN endpoints carry HID reports
+        let min_interrupt_interval = (0..pipe_count)
+            .map(|i| {
+                std::ptr::read_unaligned(
+                    buffer
+                        .as_ptr()
+                        .add(pipes_offset + i * size_of::<USB_PIPE_INFO>())
+                        as *const USB_PIPE_INFO,
+                )
+                .EndpointDescriptor
+            })
+            .filter(|endpoint| {
+                endpoint.bmAttributes & 0x03 == 0x03 && endpoint.bEndpointAddress & 0x80 != 0
+            })
+            .map(|endpoint| endpoint.bInterval)
+            .min();
+
+        let speed = match info.Speed {
+            0 => UsbSpeed::Low,
+            1 => UsbSpeed::Full,
+            // SuperSpeed devices are reported as high speed by this IOCTL
+            2 | 3 => query_super_speed(hub, port).unwrap_or(UsbSpeed::High),
+            _ => UsbSpeed::Unknown,
+        };
+
+        Some(Connection {
+            speed,
+            is_hub: info.DeviceIsHub,
+            min_interrupt_interval,
+        })
+    }
+
+    unsafe fn query_super_speed(hub: HANDLE, port: u32) -> Option<UsbSpeed> {
+        let mut info = USB_NODE_CONNECTION_INFORMATION_EX_V2 {
+            ConnectionIndex: port,
+            Length: size_of::<USB_NODE_CONNECTION_INFORMATION_EX_V2>() as u32,
+            ..Default::default()
+        };
+        info.SupportedUsbProtocols.ul = ALL_USB_PROTOCOLS;
+        let size = size_of::<USB_NODE_CONNECTION_INFORMATION_EX_V2>() as u32;
+
+        DeviceIoControl(
+            hub,
+            IOCTL_USB_GET_NODE_CONNECTION_INFORMATION_EX_V2,
+            Some(&info as *const _ as *const _),
+            size,
+            Some(&mut info as *mut _ as *mut _),
+            size,
+            None,
+            None,
+        )
+        .ok()?;
+
+        let flags = info.Flags.ul;
+        if flags & OPERATING_AT_SUPER_SPEED_PLUS != 0 {
+            Some(UsbSpeed::SuperPlus)
+        } else if flags & OPERATING_AT_SUPER_SPEED != 0 {
+            Some(UsbSpeed::Super)
+        } else {
+            Some(UsbSpeed::High)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_hardware_id() {
+            assert_eq!(
+                parse_hardware_id("USB\\VID_046D&PID_C08B&REV_2702"),
+                Some((0x046D, 0xC08B))
+            );
+            assert_eq!(parse_hardware_id("USB\\ROOT_HUB30"), None);
+        }
+
+        #[test]
+        fn test_address_from_instance_id() {
+            assert_eq!(
+                address_from_instance_id(
+                    "BTHENUM\\{0000111E-0000-1000-8000-00805F9B34FB}_VID&0001005D_PID&B00D\\7&2A7B1B5A&0&E4A7A0A1B2C3_C00000000"
+                )
+                .as_deref(),
+                Some("E4A7A0A1B2C3")
+            );
+            assert_eq!(
+                address_from_instance_id("BTHLE\\DEV_e4a7a0a1b2c3\\7&1A2B3C4D&0&E4A7A0A1B2C3")
+                    .as_deref(),
+                Some("E4A7A0A1B2C3")
+            );
+            assert_eq!(address_from_instance_id("USB\\VID_046D&PID_C08B"), None);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod bluetooth {
+    use super::kind_from_class_of_device;
+    use crate::models::bluetooth_device::BluetoothDevice;
+    use std::mem::size_of;
+    use windows::Win32::Devices::Bluetooth::{
+        BluetoothFindDeviceClose, BluetoothFindFirstDevice, BluetoothFindNextDevice,
+        BLUETOOTH_DEVICE_INFO, BLUETOOTH_DEVICE_SEARCH_PARAMS,
+    };
+
+    pub fn paired_devices() -> Vec<BluetoothDevice> {
+        let batteries = super::setupapi::bluetooth_battery_levels();
+        let mut devices = Vec::new();
+
+        // Remembered devices only, no inquiry: discovery would take seconds
+        let params = BLUETOOTH_DEVICE_SEARCH_PARAMS {
+            dwSize: size_of::<BLUETOOTH_DEVICE_SEARCH_PARAMS>() as u32,
+            fReturnAuthenticated: true.into(),
+            fReturnRemembered: true.into(),
+            fReturnConnected: true.into(),
+            ..Default::default()
+        };
+        let mut info = BLUETOOTH_DEVICE_INFO {
+            dwSize: size_of::<BLUETOOTH_DEVICE_INFO>() as u32,
+            ..Default::default()
+        };
+
+        unsafe {
+            // Fails when there is no radio or nothing is paired
+            let Ok(find) = BluetoothFindFirstDevice(&params, &mut info) else {
+                return devices;
+            };
+
+            loop {
+                let address = format!("{:012X}", info.Address.Anonymous.ullLong & 0xFFFF_FFFF_FFFF);
+                let name_length = info
+                    .szName
+                    .iter()
+                    .position(|c| *c == 0)
+                    .unwrap_or(info.szName.len());
+
+                devices.push(BluetoothDevice {
+                    address: format_address(&address),
+                    name: String::from_utf16_lossy(&info.szName[..name_length]),
+                    kind: kind_from_class_of_device(info.ulClassofDevice),
+                    is_connected: info.fConnected.as_bool(),
+                    battery_percent: batteries.get(&address).copied(),
+                    low_battery: false,
+                });
+
+                info = BLUETOOTH_DEVICE_INFO {
+                    dwSize: size_of::<BLUETOOTH_DEVICE_INFO>() as u32,
+                    ..Default::default()
+                };
+                if BluetoothFindNextDevice(find, &mut info).is_err() {
+                    break;
+                }
+            }
+
+            let _ = BluetoothFindDeviceClose(find);
+        }
+
+        devices
+    }
+
+    /// `E4A7A0A1B2C3` -> `E4:A7:A0:A1:B2:C3`
+    fn format_address(hex: &str) -> String {
+        hex.as_bytes()
+            .chunks(2)
+            .map(|pair| String::from_utf8_lossy(pair).into_owned())
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod bluez {
+    use super::kind_from_icon;
+    use crate::models::bluetooth_device::{BluetoothDevice, BluetoothDeviceKind};
+    use std::process::Command;
+
+    /// Paired devices from `bluetoothctl`; BlueZ reports the battery of
+    /// devices exposing the battery service
+    pub fn paired_devices() -> Vec<BluetoothDevice> {
+        // `paired-devices` was replaced by `devices Paired` in BlueZ 5.65
+        let listing = bluetoothctl(&["devices", "Paired"])
+            .filter(|output| output.contains("Device "))
+            .or_else(|| bluetoothctl(&["paired-devices"]))
+            .unwrap_or_default();
+
+        listing
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.trim().splitn(3, ' ');
+                if parts.next()? != "Device" {
+                    return None;
+                }
+                let address = parts.next()?.to_string();
+                let name = parts.next().unwrap_or_default().to_string();
+                let info = bluetoothctl(&["info", &address]).unwrap_or_default();
+                Some(device_from_info(address, &name, &info))
+            })
+            .collect()
+    }
+
+    fn bluetoothctl(args: &[&str]) -> Option<String> {
+        Command::new("bluetoothctl")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn device_from_info(address: String, listed_name: &str, info: &str) -> BluetoothDevice {
+        let field = |key: &str| {
+            info.lines()
+                .find_map(|line| line.trim().strip_prefix(key))
+                .map(str::trim)
+        };
+
+        BluetoothDevice {
+            name: field("Alias:")
+                .or(field("Name:"))
+                .unwrap_or(listed_name)
+                .to_string(),
+            kind: field("Icon:")
+                .map(kind_from_icon)
+                .unwrap_or(BluetoothDeviceKind::Other),
+            is_connected: field("Connected:") == Some("yes"),
+            // "Battery Percentage: 0x5a (90)"
+            battery_percent: field("Battery Percentage:")
+                .and_then(|value| value.split('(').nth(1))
+                .and_then(|value| value.trim_end_matches(')').parse().ok()),
+            address,
+            low_battery: false,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_device_from_info() {
+            let info = "Device E4:A7:A0:A1:B2:C3 (public)\n\tName: Xbox Wireless Controller\n\tAlias: Xbox Wireless Controller\n\tIcon: input-gaming\n\tPaired: yes\n\tConnected: yes\n\tBattery Percentage: 0x12 (18)\n";
+            let device = device_from_info("E4:A7:A0:A1:B2:C3".to_string(), "", info);
+
+            assert_eq!(device.name, "Xbox Wireless Controller");
+            assert_eq!(device.kind, BluetoothDeviceKind::Controller);
+            assert!(device.is_connected);
+            assert_eq!(device.battery_percent, Some(18));
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sysfs {
+    use super::polling_rate_hz;
+    use crate::models::usb_device::{UsbDevice, UsbSpeed};
+    use std::fs;
+    use std::path::Path;
+
+    const USB_DEVICES_DIR: &str = "/sys/bus/usb/devices";
+    const HUB_CLASS: &str = "09";
+    const HID_CLASS: &str = "03";
+    const MOUSE_PROTOCOL: &str = "02";
+
+    pub fn usb_devices() -> anyhow::Result<Vec<UsbDevice>> {
+        let mut devices = Vec::new();
+        // Missing on machines (and containers) without a USB bus
+        let Ok(entries) = fs::read_dir(USB_DEVICES_DIR) else {
+            return Ok(devices);
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Interfaces are listed next to devices as "<device>:<config>.<interface>"
+            if name.contains(':') {
+                continue;
+            }
+
+            let path = entry.path();
+            if read_attr(&path, "bDeviceClass").as_deref() == Some(HUB_CLASS) {
+                continue;
+            }
+            let (Some(vendor_id), Some(product_id)) =
+                (read_hex(&path, "idVendor"), read_hex(&path, "idProduct"))
+            else {
+                continue;
+            };
+
+            let speed = read_attr(&path, "speed")
+                .map(|mbps| speed_from_mbps(&mbps))
+                .unwrap_or(UsbSpeed::Unknown);
+            let mouse_interval = mouse_interrupt_interval(&path, &name);
+
+            devices.push(UsbDevice {
+                vendor_id: vendor_id as u16,
+                product_id: product_id as u16,
+                manufacturer: read_attr(&path, "manufacturer"),
+                product: read_attr(&path, "product"),
+                speed,
+                is_mouse: mouse_interval.is_some(),
+                polling_rate_hz: mouse_interval
+                    .flatten()
+                    .and_then(|interval| polling_rate_hz(speed, interval)),
+            });
+        }
+
+        Ok(devices)
+    }
+
+    /// `Some` when the device has a HID boot mouse interface, with the
+    /// `bInterval` of its interrupt IN endpoint if readable
+    fn mouse_interrupt_interval(device: &Path, name: &str) -> Option<Option<u8>> {
+        let interfaces = fs::read_dir(device).ok()?.flatten().filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(&format!("{}:", name))
+        });
+
+        for interface in interfaces {
+            let path = interface.path();
+            if read_attr(&path, "bInterfaceClass").as_deref() != Some(HID_CLASS)
+                || read_attr(&path, "bInterfaceProtocol").as_deref() != Some(MOUSE_PROTOCOL)
+            {
+                continue;
+            }
+
+            let interval = fs::read_dir(&path).ok().and_then(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.file_name().to_string_lossy().starts_with("ep_"))
+                    .map(|e| e.path())
+                    .filter(|ep| {
+                        read_attr(ep, "type").as_deref() == Some("Interrupt")
+                            && read_attr(ep, "direction").as_deref() == Some("in")
+                    })
+                    .filter_map(|ep| read_hex(&ep, "bInterval").map(|v| v as u8))
+                    .min()
+            });
+            return Some(interval);
+        }
+
+        None
+    }
+
+    fn speed_from_mbps(mbps: &str) -> UsbSpeed {
+        match mbps.parse::<f64>() {
+            Ok(v) if v <= 1.5 => UsbSpeed::Low,
+            Ok(v) if v <= 12.0 => UsbSpeed::Full,
+            Ok(v) if v <= 480.0 => UsbSpeed::High,
+            Ok(v) if v <= 5000.0 => UsbSpeed::Super,
+            Ok(_) => UsbSpeed::SuperPlus,
+            Err(_) => UsbSpeed::Unknown,
+        }
+    }
+
+    fn read_attr(path: &Path, attr: &str) -> Option<String> {
+        fs::read_to_string(path.join(attr))
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn read_hex(path: &Path, attr: &str) -> Option<u32> {
+        u32::from_str_radix(&read_attr(path, attr)?, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polling_rate_hz() {
+        assert_eq!(polling_rate_hz(UsbSpeed::Full, 1), Some(1000));
+        assert_eq!(polling_rate_hz(UsbSpeed::Full, 8), Some(125));
+        assert_eq!(polling_rate_hz(UsbSpeed::High, 1), Some(8000));
+        assert_eq!(polling_rate_hz(UsbSpeed::High, 4), Some(1000));
+        assert_eq!(polling_rate_hz(UsbSpeed::Low, 0), None);
+        assert_eq!(polling_rate_hz(UsbSpeed::Unknown, 1), None);
+    }
+
+    #[test]
+    fn test_kind_from_class_of_device() {
+        assert_eq!(
+            kind_from_class_of_device(0x002508),
+            BluetoothDeviceKind::Controller
+        );
+        assert_eq!(
+            kind_from_class_of_device(0x240404),
+            BluetoothDeviceKind::Headset
+        );
+        assert_eq!(
+            kind_from_class_of_device(0x002580),
+            BluetoothDeviceKind::Mouse
+        );
+        assert_eq!(
+            kind_from_class_of_device(0x002540),
+            BluetoothDeviceKind::Keyboard
+        );
+        assert_eq!(
+            kind_from_class_of_device(0x5a020c),
+            BluetoothDeviceKind::Other
+        );
+    }
+}
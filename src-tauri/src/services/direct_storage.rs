@@ -0,0 +1,80 @@
+use crate::models::optimization::DirectStorageStatus;
+use crate::services::os_version::{self, WINDOWS_11_BUILD};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+/// DirectStorage readiness of a drive, the system drive when none is given.
+/// Reading BypassIO state needs administrator rights.
+pub fn get_status(drive: Option<String>) -> DirectStorageStatus {
+    let drive = drive
+        .or_else(|| std::env::var("SystemDrive").ok())
+        .unwrap_or_else(|| "C:".to_string());
+    let optimized_os = os_version::windows_build().is_some_and(|build| build >= WINDOWS_11_BUILD);
+
+    #[cfg(target_os = "windows")]
+    let (bypass_io_supported, storage_type) = Command::new("fsutil")
+        .args([
+            "bypassIo",
+            "state",
+            &format!("{}\\", drive.trim_end_matches('\\')),
+        ])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_bypass_io(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or((None, None));
+
+    #[cfg(not(target_os = "windows"))]
+    let (bypass_io_supported, storage_type) = (None, None);
+
+    DirectStorageStatus {
+        drive,
+        optimized_os,
+        bypass_io_supported,
+        storage_type,
+        optimized: optimized_os && bypass_io_supported == Some(true),
+    }
+}
+
+/// Reads `fsutil bypassIo state` output: whether BypassIO is supported on the
+/// volume and the storage type it reports
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_bypass_io(output: &str) -> (Option<bool>, Option<String>) {
+    let supported = if output.contains("is not currently supported") {
+        Some(false)
+    } else if output.contains("is currently supported") {
+        Some(true)
+    } else {
+        None
+    };
+    let storage_type = output.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == "Storage Type").then(|| value.trim().to_string())
+    });
+    (supported, storage_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bypass_io() {
+        let output = "BypassIo on \"C:\\\" is currently supported.\r\n    Storage Type:   NVMe\r\n    Storage Driver: BypassIo compatible\r\n";
+        assert_eq!(
+            parse_bypass_io(output),
+            (Some(true), Some("NVMe".to_string()))
+        );
+
+        let output =
+            "BypassIo on \"D:\\\" is not currently supported.\r\n    Storage Type:   SATA\r\n";
+        assert_eq!(
+            parse_bypass_io(output),
+            (Some(false), Some("SATA".to_string()))
+        );
+    }
+}
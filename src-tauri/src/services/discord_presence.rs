@@ -0,0 +1,276 @@
+// Discord Rich Presence for the tracked game, through the local IPC socket
+// of the Discord client (a named pipe on Windows, a Unix socket elsewhere).
+// Games whose profile hides them are skipped.
+use crate::models::session::TrackedGame;
+use crate::models::system_stats::DiscordConfig;
+use crate::services::{crash_monitor, game_profiles, quiet_hours, stats_line};
+use crate::shared::paths;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const CONFIG_FILE: &str = "discord_presence.json";
+const TICK: Duration = Duration::from_secs(1);
+// Discord drops updates sent faster than once every few seconds
+const MIN_INTERVAL_SECS: u64 = 5;
+const MAX_STATE_LENGTH: usize = 128;
+const IPC_SLOTS: u32 = 10;
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+
+static CONFIG: once_cell::sync::Lazy<Mutex<DiscordConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+static PRESENCE_RUNNING: AtomicBool = AtomicBool::new(false);
+
+trait IpcStream: Read + Write + Send {}
+impl<T: Read + Write + Send> IpcStream for T {}
+
+/// Starts the presence thread, which idles while the integration is
+/// disabled. Called once at startup.
+pub fn start() {
+    if PRESENCE_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(presence_loop);
+}
+
+pub fn get_config() -> DiscordConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: DiscordConfig) -> Result<DiscordConfig> {
+    if config.enabled && config.client_id.is_empty() {
+        return Err(anyhow!("A Discord application id is required"));
+    }
+    if config.enabled && !config.client_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!(
+            "Invalid Discord application id: {}",
+            config.client_id
+        ));
+    }
+    if config.interval_secs < MIN_INTERVAL_SECS {
+        return Err(anyhow!(
+            "Discord presence interval must be at least {} seconds",
+            MIN_INTERVAL_SECS
+        ));
+    }
+
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    Ok(config)
+}
+
+fn load_config() -> DiscordConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+struct Connection {
+    stream: Box<dyn IpcStream>,
+    client_id: String,
+    nonce: u64,
+    showing: bool, // whether a presence is currently set
+}
+
+fn presence_loop() {
+    let mut connection: Option<Connection> = None;
+    let mut elapsed = u64::MAX; // seconds since the last update
+
+    loop {
+        std::thread::sleep(TICK);
+        let config = get_config();
+        if connection
+            .as_ref()
+            .is_some_and(|c| !config.enabled || c.client_id != config.client_id)
+        {
+            // Dropping the connection clears the presence on Discord's side
+            connection = None;
+        }
+        if !config.enabled {
+            continue;
+        }
+
+        elapsed = elapsed.saturating_add(TICK.as_secs());
+        if elapsed < config.interval_secs {
+            continue;
+        }
+        elapsed = 0;
+
+        let game = visible_game();
+        if game.is_none() && connection.as_ref().is_none_or(|c| !c.showing) {
+            continue;
+        }
+        if connection.is_none() {
+            match connect(&config.client_id) {
+                Ok(connected) => connection = Some(connected),
+                // Discord not running is the common case, so stay quiet
+                Err(_) => continue,
+            }
+        }
+
+        if let Some(active) = connection.as_mut() {
+            let stats = game
+                .as_ref()
+                .filter(|_| config.show_stats && !quiet_hours::is_paused())
+                .map(|_| stats_line::stats_line());
+            let activity = game.as_ref().map(|game| activity(game, stats.as_deref()));
+            if let Err(e) = set_activity(active, activity) {
+                eprintln!("Discord presence update failed: {}", e);
+                connection = None;
+            }
+        }
+    }
+}
+
+/// The first tracked game not hidden from Discord by its profile
+fn visible_game() -> Option<TrackedGame> {
+    crash_monitor::get_tracked_games().into_iter().find(|game| {
+        !game_profiles::get_profile(&game.exe_path).is_some_and(|profile| profile.hide_from_discord)
+    })
+}
+
+fn connect(client_id: &str) -> Result<Connection> {
+    let mut stream = open_ipc()?;
+    let handshake = json!({ "v": 1, "client_id": client_id });
+    stream.write_all(&encode_frame(OP_HANDSHAKE, &handshake))?;
+    // The READY dispatch, or an error when the application id is unknown
+    let (_, ready) = read_frame(&mut stream)?;
+    if ready["evt"] != "READY" {
+        return Err(anyhow!(
+            "Discord refused the connection: {}",
+            ready["data"]["message"]
+        ));
+    }
+    Ok(Connection {
+        stream,
+        client_id: client_id.to_string(),
+        nonce: 0,
+        showing: false,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn open_ipc() -> Result<Box<dyn IpcStream>> {
+    (0..IPC_SLOTS)
+        .find_map(|slot| {
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(format!(r"\\.\pipe\discord-ipc-{}", slot))
+                .ok()
+        })
+        .map(|pipe| Box::new(pipe) as Box<dyn IpcStream>)
+        .ok_or_else(|| anyhow!("Discord is not running"))
+}
+
+#[cfg(unix)]
+fn open_ipc() -> Result<Box<dyn IpcStream>> {
+    use std::os::unix::net::UnixStream;
+
+    let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .find_map(std::env::var_os)
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| std::path::PathBuf::from("/tmp"));
+    (0..IPC_SLOTS)
+        .find_map(|slot| UnixStream::connect(dir.join(format!("discord-ipc-{}", slot))).ok())
+        .map(|socket| Box::new(socket) as Box<dyn IpcStream>)
+        .ok_or_else(|| anyhow!("Discord is not running"))
+}
+
+#[cfg(not(any(target_os = "windows", unix)))]
+fn open_ipc() -> Result<Box<dyn IpcStream>> {
+    Err(anyhow!(
+        "Discord presence is not supported on this platform"
+    ))
+}
+
+/// Sets the activity, or clears it with None
+fn set_activity(connection: &mut Connection, activity: Option<Value>) -> Result<()> {
+    connection.nonce += 1;
+    let command = json!({
+        "cmd": "SET_ACTIVITY",
+        "args": { "pid": std::process::id(), "activity": activity },
+        "nonce": connection.nonce.to_string(),
+    });
+    connection
+        .stream
+        .write_all(&encode_frame(OP_FRAME, &command))?;
+    let (_, reply) = read_frame(&mut connection.stream)?;
+    if reply["evt"] == "ERROR" {
+        return Err(anyhow!("{}", reply["data"]["message"]));
+    }
+    connection.showing = activity.is_some();
+    Ok(())
+}
+
+fn activity(game: &TrackedGame, stats: Option<&str>) -> Value {
+    let mut activity = json!({
+        "details": game.name,
+        "timestamps": { "start": game.started_at },
+    });
+    if let Some(stats) = stats.filter(|stats| !stats.is_empty()) {
+        activity["state"] = json!(stats.chars().take(MAX_STATE_LENGTH).collect::<String>());
+    }
+    activity
+}
+
+/// IPC frame: opcode and payload length as little-endian u32, then JSON
+fn encode_frame(opcode: u32, payload: &Value) -> Vec<u8> {
+    let body = payload.to_string();
+    let mut frame = Vec::with_capacity(8 + body.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(body.as_bytes());
+    frame
+}
+
+fn read_frame(stream: &mut dyn Read) -> Result<(u32, Value)> {
+    let mut header = [0u8; 8];
+    stream.read_exact(&mut header)?;
+    let opcode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body)?;
+    Ok((opcode, serde_json::from_slice(&body)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_round_trip() {
+        let payload = json!({ "v": 1, "client_id": "123" });
+        let frame = encode_frame(OP_HANDSHAKE, &payload);
+        assert_eq!(&frame[..4], &[0, 0, 0, 0]);
+        assert_eq!(frame.len(), 8 + payload.to_string().len());
+
+        let (opcode, decoded) = read_frame(&mut frame.as_slice()).unwrap();
+        assert_eq!(opcode, OP_HANDSHAKE);
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_activity() {
+        let game = TrackedGame {
+            pid: 1,
+            name: "Elden Ring".to_string(),
+            exe_path: "eldenring.exe".to_string(),
+            started_at: 1_700_000_000_000,
+            collect_minidumps: false,
+        };
+        let with_stats = activity(&game, Some("CPU 45% | GPU 80%"));
+        assert_eq!(with_stats["details"], "Elden Ring");
+        assert_eq!(with_stats["state"], "CPU 45% | GPU 80%");
+        assert_eq!(with_stats["timestamps"]["start"], 1_700_000_000_000u64);
+        assert!(activity(&game, None).get("state").is_none());
+    }
+}
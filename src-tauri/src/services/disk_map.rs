@@ -0,0 +1,194 @@
+// Which physical disk each mounted volume lives on, so the model and the
+// temperature of a drive are shown next to its own partitions and not next to
+// whatever volume happens to share its index. Disks are identified by their
+// number on Windows (\\.\PhysicalDriveN) and by their block device name on
+// Linux (nvme0n1, sda).
+use std::path::Path;
+
+#[cfg(target_os = "windows")]
+use windows::core::PCWSTR;
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+#[cfg(target_os = "windows")]
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, GetVolumeNameForVolumeMountPointW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_READ,
+    FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Ioctl::{
+    PropertyStandardQuery, StorageDeviceTemperatureProperty, IOCTL_STORAGE_GET_DEVICE_NUMBER,
+    IOCTL_STORAGE_QUERY_PROPERTY, STORAGE_DEVICE_NUMBER, STORAGE_PROPERTY_QUERY,
+    STORAGE_TEMPERATURE_DATA_DESCRIPTOR,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::IO::DeviceIoControl;
+
+/// The physical disk holding the volume mounted at `mount_point`. None for
+/// volumes spanning several disks and for ones no disk backs, like network
+/// shares or RAM disks.
+#[cfg(target_os = "windows")]
+pub fn disk_of_volume(mount_point: &Path, _device: &str) -> Option<String> {
+    // The volume GUID path also covers volumes mounted in a folder
+    let mut mount: Vec<u16> = mount_point.to_string_lossy().encode_utf16().collect();
+    if mount.last() != Some(&(b'\\' as u16)) {
+        mount.push(b'\\' as u16);
+    }
+    mount.push(0);
+    let mut volume = [0u16; 64];
+    unsafe { GetVolumeNameForVolumeMountPointW(PCWSTR(mount.as_ptr()), &mut volume) }.ok()?;
+
+    // Opened without the trailing backslash the path names the volume
+    // device rather than its root directory
+    let length = volume.iter().position(|c| *c == 0)?;
+    let mut volume = volume[..length].to_vec();
+    if volume.last() == Some(&(b'\\' as u16)) {
+        volume.pop();
+    }
+    volume.push(0);
+
+    let handle = open_device(&volume)?;
+    let mut number = STORAGE_DEVICE_NUMBER::default();
+    let result = unsafe {
+        DeviceIoControl(
+            handle,
+            IOCTL_STORAGE_GET_DEVICE_NUMBER,
+            None,
+            0,
+            Some(&mut number as *mut _ as *mut _),
+            std::mem::size_of::<STORAGE_DEVICE_NUMBER>() as u32,
+            None,
+            None,
+        )
+    };
+    let _ = unsafe { CloseHandle(handle) };
+    result.ok()?;
+    Some(number.DeviceNumber.to_string())
+}
+
+/// Current temperature of a disk in Celsius, from the drive's own sensor.
+/// Drives and bridges that don't report one return None.
+#[cfg(target_os = "windows")]
+pub fn disk_temperature(disk: &str) -> Option<f32> {
+    let path: Vec<u16> = format!("\\\\.\\PhysicalDrive{}", disk)
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let handle = open_device(&path)?;
+
+    let query = STORAGE_PROPERTY_QUERY {
+        PropertyId: StorageDeviceTemperatureProperty,
+        QueryType: PropertyStandardQuery,
+        ..Default::default()
+    };
+    let mut data = STORAGE_TEMPERATURE_DATA_DESCRIPTOR::default();
+    let result = unsafe {
+        DeviceIoControl(
+            handle,
+            IOCTL_STORAGE_QUERY_PROPERTY,
+            Some(&query as *const _ as *const _),
+            std::mem::size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+            Some(&mut data as *mut _ as *mut _),
+            std::mem::size_of::<STORAGE_TEMPERATURE_DATA_DESCRIPTOR>() as u32,
+            None,
+            None,
+        )
+    };
+    let _ = unsafe { CloseHandle(handle) };
+    result.ok()?;
+
+    // The first entry is the composite temperature of the drive
+    (data.InfoCount > 0).then(|| data.TemperatureInfo[0].Temperature as f32)
+}
+
+/// Opens a device for queries only, which needs no administrator rights
+#[cfg(target_os = "windows")]
+fn open_device(path: &[u16]) -> Option<HANDLE> {
+    unsafe {
+        CreateFileW(
+            PCWSTR(path.as_ptr()),
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAGS_AND_ATTRIBUTES(0),
+            None,
+        )
+    }
+    .ok()
+}
+
+/// The physical disk holding the volume on `device`, like /dev/nvme0n1p2.
+/// Device mapper volumes (LUKS, LVM) resolve to the disk below them when
+/// there is exactly one.
+#[cfg(not(target_os = "windows"))]
+pub fn disk_of_volume(_mount_point: &Path, device: &str) -> Option<String> {
+    // /dev/mapper names and /dev/disk/by-* paths are links to the real node
+    let device = std::fs::canonicalize(device).ok()?;
+    let mut name = device.file_name()?.to_string_lossy().into_owned();
+
+    for _ in 0..4 {
+        let sys_path = Path::new("/sys/class/block").join(&name);
+        let slaves: Vec<String> = std::fs::read_dir(sys_path.join("slaves"))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        match slaves.as_slice() {
+            [] => {
+                let resolved = std::fs::canonicalize(&sys_path).ok()?;
+                return whole_disk_name(&resolved, sys_path.join("partition").exists());
+            }
+            [slave] => name = slave.clone(),
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Current temperature of a disk in Celsius, from the nvme hwmon driver or
+/// the drivetemp one for SATA drives
+#[cfg(not(target_os = "windows"))]
+pub fn disk_temperature(disk: &str) -> Option<f32> {
+    let device = Path::new("/sys/block").join(disk).join("device");
+    // nvme puts hwmonN in the controller, drivetemp below a hwmon folder
+    [device.clone(), device.join("hwmon")]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("hwmon"))
+        .find_map(|entry| std::fs::read_to_string(entry.path().join("temp1_input")).ok())
+        .and_then(|millidegrees| millidegrees.trim().parse::<f32>().ok())
+        .map(|millidegrees| millidegrees / 1000.0)
+}
+
+/// The disk name from the resolved sysfs path of a block device. Partitions
+/// sit in the folder of their disk.
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn whole_disk_name(resolved: &Path, is_partition: bool) -> Option<String> {
+    let disk = if is_partition {
+        resolved.parent()?
+    } else {
+        resolved
+    };
+    Some(disk.file_name()?.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_disk_name() {
+        let partition =
+            Path::new("/sys/devices/pci0000:00/0000:00:01.1/nvme/nvme0/nvme0n1/nvme0n1p2");
+        assert_eq!(
+            whole_disk_name(partition, true),
+            Some("nvme0n1".to_string())
+        );
+        let disk = Path::new("/sys/devices/pci0000:00/ata1/host0/target0:0:0/0:0:0:0/block/sda");
+        assert_eq!(whole_disk_name(disk, false), Some("sda".to_string()));
+    }
+}
@@ -0,0 +1,685 @@
+// Refresh rate and variable refresh rate of every active display, and named
+// profiles to switch between, like 60 Hz on the desktop and 165 Hz for games.
+//
+// Whether a monitor can run VRR comes from its EDID: a range limits
+// descriptor that is all the timing information there is, or AMD's FreeSync
+// block. This is the check the amdgpu driver makes, and NVIDIA's G-Sync
+// Compatible monitors pass it too. Whether G-Sync or FreeSync is switched on
+// in the vendor's control panel can't be read without their SDKs, so the
+// enabled flag is Windows' own variable refresh rate setting.
+//
+// Windows switches modes through ChangeDisplaySettingsEx, Linux through
+// xrandr, which leaves Wayland sessions without switching.
+use crate::models::gpu_info::{DisplayInfo, RefreshRateProfile};
+use crate::shared::paths;
+use anyhow::{anyhow, Result};
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use crate::shared::registry;
+#[cfg(target_os = "windows")]
+use windows::core::PCWSTR;
+#[cfg(target_os = "windows")]
+use windows::Win32::Graphics::Gdi::{
+    ChangeDisplaySettingsExW, EnumDisplayDevicesW, EnumDisplaySettingsW, CDS_UPDATEREGISTRY,
+    DEVMODEW, DISPLAY_DEVICEW, DISPLAY_DEVICE_ATTACHED_TO_DESKTOP, DISPLAY_DEVICE_PRIMARY_DEVICE,
+    DISP_CHANGE_SUCCESSFUL, DM_DISPLAYFREQUENCY, ENUM_CURRENT_SETTINGS, ENUM_DISPLAY_SETTINGS_MODE,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::WindowsAndMessaging::EDD_GET_DEVICE_INTERFACE_NAME;
+
+#[cfg(not(target_os = "windows"))]
+use std::process::Command;
+
+const PROFILES_FILE: &str = "refresh_profiles.json";
+
+#[cfg(target_os = "windows")]
+const DIRECTX_SETTINGS_KEY: &str = r"HKCU\Software\Microsoft\DirectX\UserGpuPreferences";
+
+static PROFILES: once_cell::sync::Lazy<Mutex<Vec<RefreshRateProfile>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_profiles()));
+
+/// What the EDID tells about a monitor
+#[derive(Debug, Default, PartialEq)]
+struct EdidInfo {
+    name: Option<String>,
+    vrr_range: Option<(u32, u32)>,
+}
+
+pub fn get_displays() -> Result<Vec<DisplayInfo>> {
+    platform::displays()
+}
+
+/// Switches a display, the primary one when None, to the supported rate
+/// closest to `refresh_hz`, or to its highest rate when that is None
+pub fn set_refresh_rate(display: Option<&str>, refresh_hz: Option<u32>) -> Result<DisplayInfo> {
+    let displays = platform::displays()?;
+    let target = match display {
+        Some(id) => displays.iter().find(|d| d.id == id),
+        None => displays.iter().find(|d| d.primary).or(displays.first()),
+    }
+    .ok_or_else(|| anyhow!("Display not found: {}", display.unwrap_or("primary")))?;
+
+    let rate = closest_rate(&target.refresh_rates, refresh_hz)
+        .ok_or_else(|| anyhow!("{} reports no refresh rates", target.name))?;
+    if rate != target.refresh_hz {
+        platform::set_rate(&target.id, rate)?;
+    }
+
+    platform::displays()?
+        .into_iter()
+        .find(|d| d.id == target.id)
+        .ok_or_else(|| anyhow!("Display not found: {}", target.id))
+}
+
+pub fn get_profiles() -> Vec<RefreshRateProfile> {
+    PROFILES
+        .lock()
+        .map(|profiles| profiles.clone())
+        .unwrap_or_else(|_| default_profiles())
+}
+
+pub fn set_profiles(profiles: Vec<RefreshRateProfile>) -> Result<Vec<RefreshRateProfile>> {
+    let profiles: Vec<RefreshRateProfile> = profiles
+        .into_iter()
+        .map(|profile| RefreshRateProfile {
+            name: profile.name.trim().to_string(),
+            display: profile
+                .display
+                .map(|display| display.trim().to_string())
+                .filter(|display| !display.is_empty()),
+            ..profile
+        })
+        .collect();
+    for (i, profile) in profiles.iter().enumerate() {
+        if profile.name.is_empty() {
+            return Err(anyhow!("Every refresh rate profile needs a name"));
+        }
+        if profiles[..i]
+            .iter()
+            .any(|other| other.name.eq_ignore_ascii_case(&profile.name))
+        {
+            return Err(anyhow!("Duplicate refresh rate profile: {}", profile.name));
+        }
+        if profile.refresh_hz == Some(0) {
+            return Err(anyhow!("Invalid refresh rate for {}", profile.name));
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&profiles)?;
+    std::fs::write(paths::app_data_file(PROFILES_FILE), content)?;
+    *PROFILES.lock().map_err(|e| anyhow!(e.to_string()))? = profiles.clone();
+    Ok(profiles)
+}
+
+pub fn apply_profile(name: &str) -> Result<DisplayInfo> {
+    let profile = get_profiles()
+        .into_iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| anyhow!("Refresh rate profile not found: {}", name))?;
+    set_refresh_rate(profile.display.as_deref(), profile.refresh_hz)
+}
+
+fn default_profiles() -> Vec<RefreshRateProfile> {
+    vec![
+        RefreshRateProfile {
+            name: "Desktop".to_string(),
+            display: None,
+            refresh_hz: Some(60),
+        },
+        RefreshRateProfile {
+            name: "Gaming".to_string(),
+            display: None,
+            refresh_hz: None,
+        },
+    ]
+}
+
+fn load_profiles() -> Vec<RefreshRateProfile> {
+    std::fs::read_to_string(paths::app_data_file(PROFILES_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_else(default_profiles)
+}
+
+/// The rate nearest to the wanted one, the higher of two equally near
+fn closest_rate(rates: &[u32], wanted: Option<u32>) -> Option<u32> {
+    match wanted {
+        None => rates.iter().max().copied(),
+        Some(hz) => rates
+            .iter()
+            .min_by_key(|&&rate| (rate.abs_diff(hz), std::cmp::Reverse(rate)))
+            .copied(),
+    }
+}
+
+fn parse_edid(edid: &[u8]) -> Option<EdidInfo> {
+    const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+    if edid.len() < 128 || edid[..8] != HEADER {
+        return None;
+    }
+
+    let mut info = EdidInfo::default();
+    // (min Hz, max Hz, no timing formula besides the limits)
+    let mut range = None;
+    for descriptor in edid[54..126].chunks_exact(18) {
+        // Display descriptors start with three zeros, timings don't
+        if descriptor[..3] != [0, 0, 0] {
+            continue;
+        }
+        match descriptor[3] {
+            0xFC => {
+                let text = &descriptor[5..];
+                let end = text.iter().position(|&b| b == 0x0A).unwrap_or(text.len());
+                let name = String::from_utf8_lossy(&text[..end]).trim().to_string();
+                info.name = Some(name).filter(|name| !name.is_empty());
+            }
+            0xFD => {
+                // Rates above 255 Hz store the excess in the offset flags
+                let flags = descriptor[4];
+                let min = descriptor[5] as u32 + if flags & 0x03 == 0x03 { 255 } else { 0 };
+                let max = descriptor[6] as u32 + if flags & 0x02 != 0 { 255 } else { 0 };
+                range = Some((min, max, descriptor[10] == 0x01));
+            }
+            _ => {}
+        }
+    }
+
+    let freesync = edid
+        .chunks_exact(128)
+        .skip(1)
+        .take(edid[126] as usize)
+        .any(has_freesync_block);
+    info.vrr_range = range
+        .filter(|&(min, max, limits_only)| (limits_only || freesync) && max > min + 10)
+        .map(|(min, max, _)| (min, max));
+    Some(info)
+}
+
+/// AMD's vendor specific data block in a CTA-861 extension
+fn has_freesync_block(block: &[u8]) -> bool {
+    if block[0] != 0x02 {
+        return false;
+    }
+    let end = (block[2] as usize).clamp(4, block.len());
+    let mut offset = 4;
+    while offset < end {
+        let tag = block[offset] >> 5;
+        let length = (block[offset] & 0x1F) as usize;
+        let payload = &block[(offset + 1).min(end)..(offset + 1 + length).min(end)];
+        if tag == 3 && payload.starts_with(&[0x1A, 0x00, 0x00]) {
+            return true;
+        }
+        offset += 1 + length;
+    }
+    false
+}
+
+/// The registry key holding the EDID of a monitor, from its device
+/// interface name like \\?\DISPLAY#GSM5BBF#5&2a6b4e7e&0&UID4352#{e6f07b5f-...}
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn edid_key(interface_name: &str) -> Option<String> {
+    let mut parts = interface_name.split('#');
+    parts.next()?;
+    let model = parts.next().filter(|part| !part.is_empty())?;
+    let instance = parts.next().filter(|part| !part.is_empty())?;
+    Some(format!(
+        r"HKLM\SYSTEM\CurrentControlSet\Enum\DISPLAY\{}\{}\Device Parameters",
+        model, instance
+    ))
+}
+
+/// VRROptimizeEnable from the DirectX settings string, like
+/// SwapEffectUpgradeEnable=1;VRROptimizeEnable=0;
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn parse_vrr_setting(settings: &str) -> Option<bool> {
+    settings.split(';').find_map(|entry| {
+        let (name, value) = entry.split_once('=')?;
+        (name.trim() == "VRROptimizeEnable").then(|| value.trim() == "1")
+    })
+}
+
+/// An active output in `xrandr --query --prop`
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+#[derive(Debug, Default)]
+struct XrandrOutput {
+    name: String,
+    primary: bool,
+    width: u32,
+    height: u32,
+    refresh: f64,
+    rates: Vec<f64>, // of the current resolution
+    edid: Vec<u8>,
+    vrr_capable: Option<bool>,
+}
+
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn parse_xrandr(output: &str) -> Vec<XrandrOutput> {
+    let mut outputs = Vec::new();
+    let mut current: Option<XrandrOutput> = None;
+    let mut in_edid = false;
+
+    for line in output.lines() {
+        if !line.starts_with([' ', '\t']) {
+            outputs.extend(current.take().filter(|o| o.width > 0));
+            in_edid = false;
+            let mut words = line.split_whitespace();
+            if let (Some(name), Some("connected")) = (words.next(), words.next()) {
+                current = Some(XrandrOutput {
+                    name: name.to_string(),
+                    primary: line.contains(" primary "),
+                    ..Default::default()
+                });
+            }
+            continue;
+        }
+        let Some(output) = current.as_mut() else {
+            continue;
+        };
+        let trimmed = line.trim();
+
+        if in_edid {
+            if line.starts_with("\t\t") && trimmed.bytes().all(|b| b.is_ascii_hexdigit()) {
+                output.edid.extend(
+                    (0..trimmed.len() / 2)
+                        .filter_map(|i| u8::from_str_radix(&trimmed[i * 2..i * 2 + 2], 16).ok()),
+                );
+                continue;
+            }
+            in_edid = false;
+        }
+        if trimmed.starts_with("EDID:") {
+            in_edid = true;
+        } else if let Some(value) = trimmed.strip_prefix("vrr_capable:") {
+            output.vrr_capable = value.split_whitespace().next().map(|v| v == "1");
+        } else if trimmed.contains('*') {
+            // 2560x1440    165.00*+ 143.97   59.95
+            let mut words = trimmed.split_whitespace();
+            let Some((width, height)) = words.next().and_then(|mode| mode.split_once('x')) else {
+                continue;
+            };
+            let digits = |s: &str| s.trim_end_matches(|c: char| !c.is_ascii_digit()).parse();
+            let (Ok(width), Ok(height)) = (digits(width), digits(height)) else {
+                continue;
+            };
+            output.width = width;
+            output.height = height;
+            for word in words {
+                let Ok(rate) = word.trim_end_matches(['*', '+']).parse::<f64>() else {
+                    continue;
+                };
+                if word.contains('*') {
+                    output.refresh = rate;
+                }
+                output.rates.push(rate);
+            }
+        }
+    }
+    outputs.extend(current.filter(|o| o.width > 0));
+    outputs
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+
+    pub fn displays() -> Result<Vec<DisplayInfo>> {
+        let vrr_enabled = registry::read_string(DIRECTX_SETTINGS_KEY, "DirectXUserGlobalSettings")
+            .and_then(|settings| parse_vrr_setting(&settings));
+
+        let mut displays = Vec::new();
+        for index in 0.. {
+            let mut adapter = DISPLAY_DEVICEW {
+                cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+                ..Default::default()
+            };
+            if !unsafe { EnumDisplayDevicesW(PCWSTR::null(), index, &mut adapter, 0) }.as_bool() {
+                break;
+            }
+            if !adapter
+                .StateFlags
+                .contains(DISPLAY_DEVICE_ATTACHED_TO_DESKTOP)
+            {
+                continue;
+            }
+            let id = wide_to_string(&adapter.DeviceName);
+            let Some(current) = current_mode(&adapter.DeviceName) else {
+                continue;
+            };
+
+            let mut rates = Vec::new();
+            for mode_index in 0.. {
+                let mut mode = new_mode();
+                let device = PCWSTR(adapter.DeviceName.as_ptr());
+                if !unsafe {
+                    EnumDisplaySettingsW(device, ENUM_DISPLAY_SETTINGS_MODE(mode_index), &mut mode)
+                }
+                .as_bool()
+                {
+                    break;
+                }
+                // 0 and 1 mean the hardware default rate
+                if mode.dmPelsWidth == current.dmPelsWidth
+                    && mode.dmPelsHeight == current.dmPelsHeight
+                    && mode.dmBitsPerPel == current.dmBitsPerPel
+                    && mode.dmDisplayFrequency > 1
+                {
+                    rates.push(mode.dmDisplayFrequency);
+                }
+            }
+            rates.sort_unstable();
+            rates.dedup();
+
+            // The monitor attached to the adapter output
+            let mut monitor = DISPLAY_DEVICEW {
+                cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+                ..Default::default()
+            };
+            let has_monitor = unsafe {
+                EnumDisplayDevicesW(
+                    PCWSTR(adapter.DeviceName.as_ptr()),
+                    0,
+                    &mut monitor,
+                    EDD_GET_DEVICE_INTERFACE_NAME,
+                )
+            }
+            .as_bool();
+            let edid = has_monitor
+                .then(|| edid_key(&wide_to_string(&monitor.DeviceID)))
+                .flatten()
+                .and_then(|key| registry::read_binary(&key, "EDID"))
+                .and_then(|edid| parse_edid(&edid));
+
+            let monitor_name = wide_to_string(&monitor.DeviceString);
+            let vrr_range = edid.as_ref().and_then(|edid| edid.vrr_range);
+            displays.push(DisplayInfo {
+                name: edid
+                    .as_ref()
+                    .and_then(|edid| edid.name.clone())
+                    .or(Some(monitor_name).filter(|name| has_monitor && !name.is_empty()))
+                    .unwrap_or_else(|| id.clone()),
+                id,
+                primary: adapter.StateFlags.contains(DISPLAY_DEVICE_PRIMARY_DEVICE),
+                width: current.dmPelsWidth,
+                height: current.dmPelsHeight,
+                refresh_hz: current.dmDisplayFrequency,
+                refresh_rates: rates,
+                vrr_capable: edid.as_ref().map(|_| vrr_range.is_some()),
+                vrr_min_hz: vrr_range.map(|(min, _)| min),
+                vrr_max_hz: vrr_range.map(|(_, max)| max),
+                vrr_enabled,
+            });
+        }
+        Ok(displays)
+    }
+
+    pub fn set_rate(id: &str, refresh_hz: u32) -> Result<()> {
+        let device: Vec<u16> = id.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut mode =
+            current_mode(&device).ok_or_else(|| anyhow!("Failed to read the mode of {}", id))?;
+        mode.dmDisplayFrequency = refresh_hz;
+        mode.dmFields = DM_DISPLAYFREQUENCY;
+
+        let result = unsafe {
+            ChangeDisplaySettingsExW(
+                PCWSTR(device.as_ptr()),
+                Some(&mode as *const DEVMODEW),
+                None,
+                CDS_UPDATEREGISTRY,
+                None,
+            )
+        };
+        if result != DISP_CHANGE_SUCCESSFUL {
+            return Err(anyhow!(
+                "Failed to switch {} to {} Hz (code {})",
+                id,
+                refresh_hz,
+                result.0
+            ));
+        }
+        Ok(())
+    }
+
+    fn current_mode(device: &[u16]) -> Option<DEVMODEW> {
+        let mut mode = new_mode();
+        unsafe { EnumDisplaySettingsW(PCWSTR(device.as_ptr()), ENUM_CURRENT_SETTINGS, &mut mode) }
+            .as_bool()
+            .then_some(mode)
+    }
+
+    fn new_mode() -> DEVMODEW {
+        DEVMODEW {
+            dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+            ..Default::default()
+        }
+    }
+
+    fn wide_to_string(wide: &[u16]) -> String {
+        let end = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+        String::from_utf16_lossy(&wide[..end])
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::*;
+
+    pub fn displays() -> Result<Vec<DisplayInfo>> {
+        Ok(query()?
+            .into_iter()
+            .map(|output| {
+                let edid = parse_edid(&output.edid);
+                let vrr_range = edid.as_ref().and_then(|edid| edid.vrr_range);
+                let mut rates: Vec<u32> = output.rates.iter().map(|r| r.round() as u32).collect();
+                rates.sort_unstable();
+                rates.dedup();
+                DisplayInfo {
+                    name: edid
+                        .as_ref()
+                        .and_then(|edid| edid.name.clone())
+                        .unwrap_or_else(|| output.name.clone()),
+                    id: output.name,
+                    primary: output.primary,
+                    width: output.width,
+                    height: output.height,
+                    refresh_hz: output.refresh.round() as u32,
+                    refresh_rates: rates,
+                    // The driver's own answer when it gives one
+                    vrr_capable: output
+                        .vrr_capable
+                        .or(edid.as_ref().map(|_| vrr_range.is_some())),
+                    vrr_min_hz: vrr_range.map(|(min, _)| min),
+                    vrr_max_hz: vrr_range.map(|(_, max)| max),
+                    vrr_enabled: None,
+                }
+            })
+            .collect())
+    }
+
+    pub fn set_rate(id: &str, refresh_hz: u32) -> Result<()> {
+        let output = query()?
+            .into_iter()
+            .find(|output| output.name == id)
+            .ok_or_else(|| anyhow!("Display not found: {}", id))?;
+        // xrandr wants the exact rate, like 143.97 for 144 Hz
+        let rate = output
+            .rates
+            .iter()
+            .find(|rate| rate.round() as u32 == refresh_hz)
+            .ok_or_else(|| anyhow!("{} doesn't run at {} Hz", id, refresh_hz))?;
+
+        let result = Command::new("xrandr")
+            .args([
+                "--output",
+                id,
+                "--mode",
+                &format!("{}x{}", output.width, output.height),
+                "--rate",
+                &format!("{:.2}", rate),
+            ])
+            .output()?;
+        if !result.status.success() {
+            return Err(anyhow!(
+                "Failed to switch {} to {} Hz: {}",
+                id,
+                refresh_hz,
+                String::from_utf8_lossy(&result.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn query() -> Result<Vec<XrandrOutput>> {
+        let output = Command::new("xrandr")
+            .args(["--query", "--prop"])
+            .output()
+            .map_err(|e| anyhow!("xrandr is not available: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "xrandr failed, displays can only be read in an X11 session: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(parse_xrandr(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 128 byte EDID with a name and a range limits descriptor
+    fn edid(name: &str, min: u8, max: u8, timing_support: u8) -> Vec<u8> {
+        let mut edid = vec![0u8; 128];
+        edid[..8].copy_from_slice(&[0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00]);
+        // A detailed timing first, as in real EDIDs
+        edid[54] = 0x02;
+        edid[55] = 0x3A;
+
+        let name_descriptor = &mut edid[72..90];
+        name_descriptor[3] = 0xFC;
+        let mut text = name.as_bytes().to_vec();
+        text.push(0x0A);
+        text.resize(13, 0x20);
+        name_descriptor[5..].copy_from_slice(&text);
+
+        let range_descriptor = &mut edid[90..108];
+        range_descriptor[3] = 0xFD;
+        range_descriptor[5] = min;
+        range_descriptor[6] = max;
+        range_descriptor[10] = timing_support;
+        edid
+    }
+
+    #[test]
+    fn test_parse_edid() {
+        let adaptive = parse_edid(&edid("LG ULTRAGEAR", 48, 165, 0x01)).unwrap();
+        assert_eq!(adaptive.name.as_deref(), Some("LG ULTRAGEAR"));
+        assert_eq!(adaptive.vrr_range, Some((48, 165)));
+
+        // A fixed 60 Hz panel declares a range too, but with GTF timings
+        let fixed = parse_edid(&edid("DELL P2419H", 56, 76, 0x00)).unwrap();
+        assert_eq!(fixed.vrr_range, None);
+
+        // Rates above 255 Hz
+        let mut fast = edid("OMEN", 48, 5, 0x01);
+        fast[94] = 0x02;
+        assert_eq!(parse_edid(&fast).unwrap().vrr_range, Some((48, 260)));
+
+        assert_eq!(parse_edid(&[0u8; 128]), None);
+        assert_eq!(parse_edid(&edid("x", 48, 165, 0x01)[..100]), None);
+    }
+
+    #[test]
+    fn test_parse_edid_freesync_block() {
+        let mut edid = edid("VG27AQ", 48, 144, 0x00);
+        edid[126] = 1;
+        let mut extension = vec![0u8; 128];
+        extension[0] = 0x02;
+        extension[1] = 0x03;
+        extension[2] = 12;
+        // An audio block, then AMD's vendor block
+        extension[4] = (1 << 5) | 2;
+        extension[7] = (3 << 5) | 4;
+        extension[8..11].copy_from_slice(&[0x1A, 0x00, 0x00]);
+        edid.extend(extension);
+        assert_eq!(parse_edid(&edid).unwrap().vrr_range, Some((48, 144)));
+
+        // HDMI's own vendor block is not FreeSync
+        edid[136] = 0x03;
+        edid[137] = 0x0C;
+        assert_eq!(parse_edid(&edid).unwrap().vrr_range, None);
+    }
+
+    #[test]
+    fn test_closest_rate() {
+        let rates = [60, 120, 144, 165];
+        assert_eq!(closest_rate(&rates, Some(60)), Some(60));
+        assert_eq!(closest_rate(&rates, Some(75)), Some(60));
+        assert_eq!(closest_rate(&rates, Some(132)), Some(144));
+        assert_eq!(closest_rate(&rates, Some(240)), Some(165));
+        assert_eq!(closest_rate(&rates, None), Some(165));
+        assert_eq!(closest_rate(&[], None), None);
+    }
+
+    #[test]
+    fn test_edid_key() {
+        assert_eq!(
+            edid_key(
+                r"\\?\DISPLAY#GSM5BBF#5&2a6b4e7e&0&UID4352#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}"
+            )
+            .as_deref(),
+            Some(
+                r"HKLM\SYSTEM\CurrentControlSet\Enum\DISPLAY\GSM5BBF\5&2a6b4e7e&0&UID4352\Device Parameters"
+            )
+        );
+        assert_eq!(edid_key(""), None);
+        assert_eq!(edid_key(r"\\?\DISPLAY#Default_Monitor"), None);
+    }
+
+    #[test]
+    fn test_parse_vrr_setting() {
+        assert_eq!(
+            parse_vrr_setting("SwapEffectUpgradeEnable=1;VRROptimizeEnable=1;"),
+            Some(true)
+        );
+        assert_eq!(parse_vrr_setting("VRROptimizeEnable=0;"), Some(false));
+        assert_eq!(parse_vrr_setting("SwapEffectUpgradeEnable=1;"), None);
+    }
+
+    #[test]
+    fn test_parse_xrandr() {
+        let output = "Screen 0: minimum 320 x 200, current 4480 x 1440, maximum 16384 x 16384
+DisplayPort-0 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm
+\tEDID:
+\t\t00ffffffffffff001e6d
+\t\t1a77
+\tvrr_capable: 1
+\t\trange: (0, 1)
+\tmax bpc: 8
+   2560x1440    165.00*+ 143.97   120.00    59.95
+   1920x1080     60.00    50.00
+HDMI-A-0 connected 1920x1080+2560+0 (normal left inverted right x axis y axis) 527mm x 296mm
+   1920x1080     60.00*+  50.00    59.94
+DisplayPort-1 disconnected (normal left inverted right x axis y axis)
+   1280x720      60.00 +
+";
+        let outputs = parse_xrandr(output);
+        assert_eq!(outputs.len(), 2);
+
+        let gaming = &outputs[0];
+        assert_eq!(gaming.name, "DisplayPort-0");
+        assert!(gaming.primary);
+        assert_eq!((gaming.width, gaming.height), (2560, 1440));
+        assert_eq!(gaming.refresh, 165.0);
+        assert_eq!(gaming.rates, vec![165.0, 143.97, 120.0, 59.95]);
+        assert_eq!(gaming.edid.len(), 12);
+        assert_eq!(gaming.vrr_capable, Some(true));
+
+        let second = &outputs[1];
+        assert!(!second.primary);
+        assert_eq!(second.refresh, 60.0);
+        assert_eq!(second.vrr_capable, None);
+        assert!(second.edid.is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::models::system_stats::DisplaySettings;
+use crate::shared::{i18n, paths};
+use crate::utils::{bytes, locale, temperature};
+use anyhow::{anyhow, Result};
+use std::sync::Mutex;
+
+const SETTINGS_FILE: &str = "display_settings.json";
+
+static SETTINGS: once_cell::sync::Lazy<Mutex<DisplaySettings>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_settings()));
+
+/// Applies the saved settings to the formatting helpers. Called once at startup.
+pub fn start() {
+    apply(&get_settings());
+}
+
+pub fn get_settings() -> DisplaySettings {
+    SETTINGS
+        .lock()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Saves the settings and applies them to every value formatted from now on
+pub fn set_settings(settings: DisplaySettings) -> Result<DisplaySettings> {
+    if !valid_locale(&settings.locale) {
+        return Err(anyhow!("Invalid locale: {}", settings.locale));
+    }
+    if !i18n::is_supported(&settings.language) {
+        return Err(anyhow!(i18n::text_with(
+            "error.unsupported_language",
+            "Unsupported language: {language}",
+            &[("language", &settings.language)],
+        )));
+    }
+
+    let content = serde_json::to_string_pretty(&settings)?;
+    std::fs::write(paths::app_data_file(SETTINGS_FILE), content)?;
+
+    apply(&settings);
+    *SETTINGS.lock().map_err(|e| anyhow!(e.to_string()))? = settings.clone();
+    Ok(settings)
+}
+
+fn load_settings() -> DisplaySettings {
+    std::fs::read_to_string(paths::app_data_file(SETTINGS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn apply(settings: &DisplaySettings) {
+    locale::set_locale(&settings.locale);
+    i18n::set_language(&settings.language);
+    bytes::set_byte_units(settings.byte_units);
+    temperature::set_temperature_unit(settings.temperature_unit);
+}
+
+/// Loose check of a BCP 47 tag, unknown languages fall back to English anyway
+fn valid_locale(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.len() <= 35
+        && tag
+            .split(['-', '_'])
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_locale() {
+        assert!(valid_locale("en-US"));
+        assert!(valid_locale("it"));
+        assert!(valid_locale("zh_Hant_TW"));
+        assert!(!valid_locale(""));
+        assert!(!valid_locale("en-"));
+        assert!(!valid_locale("../../x"));
+    }
+}
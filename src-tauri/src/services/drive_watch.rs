@@ -0,0 +1,268 @@
+// Keeps an eye on the free space of every drive and especially the one games
+// are installed on. Used space is recorded hourly and kept for a month, so the
+// growth rate of each drive tells how soon it fills up; the games drive raises
+// an alert once when its free space falls below the threshold, and again only
+// after it went back above it.
+use crate::models::system_stats::{DriveSpaceAlert, DriveSpaceStatus, DriveWatchConfig};
+use crate::services::quiet_hours;
+use crate::shared::{events, paths};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::Disks;
+
+const CONFIG_FILE: &str = "drive_watch.json";
+const HISTORY_FILE: &str = "drive_history.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const HISTORY_INTERVAL_SECS: u64 = 60 * 60;
+const HISTORY_KEEP_SECS: u64 = 30 * 24 * 60 * 60;
+// The growth rate follows the last week, older habits don't matter
+const GROWTH_WINDOW_SECS: u64 = 7 * 24 * 60 * 60;
+// Less than this apart says more about one download than about the drive
+const MIN_GROWTH_SPAN_SECS: u64 = 6 * 60 * 60;
+const SECS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+// Used bytes of each drive by mount point, (unix seconds, used bytes)
+type History = HashMap<String, Vec<(u64, u64)>>;
+
+static CONFIG: once_cell::sync::Lazy<Mutex<DriveWatchConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+static HISTORY: once_cell::sync::Lazy<Mutex<History>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_history()));
+
+// Mount point of the drive the current alert was raised for
+static ALERTED: Mutex<Option<String>> = Mutex::new(None);
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts checking the drives once. Subsequent calls do nothing.
+pub fn start() {
+    if WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| loop {
+        if !quiet_hours::is_paused() {
+            check();
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+pub fn get_config() -> DriveWatchConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: DriveWatchConfig) -> Result<DriveWatchConfig> {
+    let config = DriveWatchConfig {
+        games_drive: config
+            .games_drive
+            .map(|drive| drive.trim().to_string())
+            .filter(|drive| !drive.is_empty()),
+        ..config
+    };
+    if let Some(drive) = &config.games_drive {
+        let disks = Disks::new_with_refreshed_list();
+        if !disks
+            .iter()
+            .any(|disk| disk.mount_point().to_string_lossy() == drive.as_str())
+        {
+            return Err(anyhow!("No drive is mounted at {}", drive));
+        }
+    }
+
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    // A new drive or threshold is checked from scratch
+    if let Ok(mut alerted) = ALERTED.lock() {
+        *alerted = None;
+    }
+    check();
+    Ok(config)
+}
+
+/// Free space and growth rate of every drive
+pub fn get_status() -> Vec<DriveSpaceStatus> {
+    let config = get_config();
+    let history = HISTORY
+        .lock()
+        .map(|history| history.clone())
+        .unwrap_or_default();
+    let now = now_secs();
+
+    Disks::new_with_refreshed_list()
+        .iter()
+        .filter(|disk| disk.total_space() > 0)
+        .map(|disk| {
+            let mount_point = disk.mount_point().to_string_lossy().into_owned();
+            let free_bytes = disk.available_space();
+            let is_games_drive = config.games_drive.as_deref() == Some(mount_point.as_str());
+            let growth_bytes_per_day = history
+                .get(&mount_point)
+                .and_then(|samples| growth_per_day(samples, now));
+            DriveSpaceStatus {
+                name: disk.name().to_string_lossy().into_owned(),
+                total_bytes: disk.total_space(),
+                free_bytes,
+                is_games_drive,
+                low_space: is_games_drive && free_bytes < config.min_free_bytes,
+                growth_bytes_per_day,
+                days_until_full: growth_bytes_per_day
+                    .filter(|growth| *growth > 0.0)
+                    .map(|growth| free_bytes as f64 / growth),
+                mount_point,
+            }
+        })
+        .collect()
+}
+
+/// Records the used space when an hour has passed and alerts about the
+/// games drive
+fn check() {
+    let config = get_config();
+    let disks = Disks::new_with_refreshed_list();
+    let now = now_secs();
+
+    if let Ok(mut history) = HISTORY.lock() {
+        let mut changed = false;
+        for disk in disks.iter().filter(|disk| disk.total_space() > 0) {
+            let used = disk.total_space().saturating_sub(disk.available_space());
+            let samples = history
+                .entry(disk.mount_point().to_string_lossy().into_owned())
+                .or_default();
+            if samples
+                .last()
+                .is_none_or(|(at, _)| now.saturating_sub(*at) >= HISTORY_INTERVAL_SECS)
+            {
+                samples.push((now, used));
+                samples.retain(|(at, _)| now.saturating_sub(*at) <= HISTORY_KEEP_SECS);
+                changed = true;
+            }
+        }
+        if changed {
+            save_history(&history);
+        }
+    }
+
+    let Some(games_drive) = config.games_drive else {
+        return;
+    };
+    let Some(disk) = disks
+        .iter()
+        .find(|disk| disk.mount_point().to_string_lossy() == games_drive.as_str())
+    else {
+        return;
+    };
+    let free_bytes = disk.available_space();
+    let Ok(mut alerted) = ALERTED.lock() else {
+        return;
+    };
+    if free_bytes >= config.min_free_bytes {
+        *alerted = None;
+    } else if alerted.as_deref() != Some(games_drive.as_str()) {
+        *alerted = Some(games_drive.clone());
+        events::emit(
+            events::DRIVE_SPACE_LOW,
+            DriveSpaceAlert {
+                mount_point: games_drive,
+                free_bytes,
+                min_free_bytes: config.min_free_bytes,
+                timestamp: events::now_millis(),
+            },
+        );
+    }
+}
+
+/// Least squares slope of the used space over the growth window, in bytes
+/// per day. None until the samples cover enough time.
+fn growth_per_day(samples: &[(u64, u64)], now: u64) -> Option<f64> {
+    let recent: Vec<(f64, f64)> = samples
+        .iter()
+        .filter(|(at, _)| now.saturating_sub(*at) <= GROWTH_WINDOW_SECS)
+        .map(|(at, used)| (*at as f64, *used as f64))
+        .collect();
+    let (first, last) = (recent.first()?, recent.last()?);
+    if last.0 - first.0 < MIN_GROWTH_SPAN_SECS as f64 {
+        return None;
+    }
+
+    let count = recent.len() as f64;
+    let mean_at = recent.iter().map(|(at, _)| at).sum::<f64>() / count;
+    let mean_used = recent.iter().map(|(_, used)| used).sum::<f64>() / count;
+    let (covariance, variance) =
+        recent
+            .iter()
+            .fold((0.0, 0.0), |(covariance, variance), (at, used)| {
+                (
+                    covariance + (at - mean_at) * (used - mean_used),
+                    variance + (at - mean_at).powi(2),
+                )
+            });
+    (variance > 0.0).then(|| covariance / variance * SECS_PER_DAY)
+}
+
+fn now_secs() -> u64 {
+    events::now_millis() / 1000
+}
+
+fn load_config() -> DriveWatchConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+fn load_history() -> History {
+    paths::load_json(HISTORY_FILE)
+}
+
+fn save_history(history: &History) {
+    match serde_json::to_string(history) {
+        Ok(content) => {
+            if let Err(e) = std::fs::write(paths::app_data_file(HISTORY_FILE), content) {
+                eprintln!("Failed to save the drive history: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to serialize the drive history: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 60 * 60;
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn test_growth_per_day() {
+        let now = 100 * 24 * HOUR;
+        // 1 GB more every 12 hours over two days
+        let samples: Vec<(u64, u64)> = (0..=4)
+            .map(|i| (now - 48 * HOUR + i * 12 * HOUR, 100 * GB + i * GB))
+            .collect();
+        let growth = growth_per_day(&samples, now).unwrap();
+        assert!((growth - 2.0 * GB as f64).abs() < 1.0);
+
+        // A freed drive shrinks
+        let shrinking: Vec<(u64, u64)> = samples
+            .iter()
+            .map(|(at, used)| (*at, 200 * GB - used))
+            .collect();
+        assert!(growth_per_day(&shrinking, now).unwrap() < 0.0);
+    }
+
+    #[test]
+    fn test_growth_needs_enough_time() {
+        let now = 100 * 24 * HOUR;
+        assert_eq!(growth_per_day(&[], now), None);
+        assert_eq!(
+            growth_per_day(&[(now - 2 * HOUR, GB), (now, 2 * GB)], now),
+            None
+        );
+        // Samples older than the window are left out
+        let old = [(now - 20 * 24 * HOUR, GB), (now - 10 * 24 * HOUR, 5 * GB)];
+        assert_eq!(growth_per_day(&old, now), None);
+    }
+}
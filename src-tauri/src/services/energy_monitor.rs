@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[cfg(target_os = "windows")]
+use ntapi::ntexapi::PROCESS_ENERGY_VALUES;
+#[cfg(target_os = "windows")]
+use ntapi::ntpsapi::{NtQueryInformationProcess, ProcessEnergyValues};
+#[cfg(target_os = "windows")]
+use ntapi::winapi::ctypes::c_void;
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::CloseHandle;
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+// Weights of the estimate. CPU dominates the power draw of a laptop, disk and
+// radio activity matter mostly because they keep the devices out of idle states.
+const CPU_POINTS_PER_PERCENT: f64 = 1.0;
+const DISK_POINTS_PER_MB: f64 = 0.5;
+const NETWORK_POINTS_PER_MB: f64 = 2.0;
+const MAX_ENERGY_IMPACT: f64 = 100.0;
+
+// Previous network byte counter per PID from the E3 energy values (bytes, timestamp)
+static NETWORK_SAMPLES: once_cell::sync::Lazy<Mutex<HashMap<u32, (u64, SystemTime)>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Estimates the energy impact of a process as a 0-100 score, from its CPU
+/// share, disk throughput and, on Windows, the network traffic accounted by
+/// the energy estimation engine (E3).
+pub fn estimate_energy_impact(
+    pid: u32,
+    cpu_usage_percent: f64,
+    io_read_rate: u64,
+    io_write_rate: u64,
+) -> f64 {
+    let network_rate = network_rate(pid);
+    energy_score(
+        cpu_usage_percent,
+        io_read_rate.saturating_add(io_write_rate),
+        network_rate,
+    )
+}
+
+/// Task Manager style label for an energy impact score
+pub fn energy_impact_level(score: f64) -> &'static str {
+    match score {
+        s if s < 0.5 => "Very low",
+        s if s < 2.0 => "Low",
+        s if s < 8.0 => "Moderate",
+        s if s < 20.0 => "High",
+        _ => "Very high",
+    }
+}
+
+/// Drops the samples of processes that no longer exist
+pub fn prune_samples(live_pids: &HashSet<u32>) {
+    if let Ok(mut samples) = NETWORK_SAMPLES.lock() {
+        samples.retain(|pid, _| live_pids.contains(pid));
+    }
+}
+
+fn energy_score(cpu_usage_percent: f64, disk_rate: u64, network_rate: u64) -> f64 {
+    const MB: f64 = 1024.0 * 1024.0;
+
+    let score = cpu_usage_percent.max(0.0) * CPU_POINTS_PER_PERCENT
+        + disk_rate as f64 / MB * DISK_POINTS_PER_MB
+        + network_rate as f64 / MB * NETWORK_POINTS_PER_MB;
+
+    score.min(MAX_ENERGY_IMPACT)
+}
+
+/// Network bytes/sec since the previous sample, 0 on the first one
+fn network_rate(pid: u32) -> u64 {
+    let Some(total_bytes) = query_network_bytes(pid) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+
+    let mut samples = match NETWORK_SAMPLES.lock() {
+        Ok(samples) => samples,
+        Err(_) => return 0,
+    };
+
+    let rate = match samples.get(&pid) {
+        Some((last_bytes, last_time)) => {
+            let elapsed = now
+                .duration_since(*last_time)
+                .unwrap_or_default()
+                .as_secs_f64();
+            if elapsed > 0.0 {
+                (total_bytes.saturating_sub(*last_bytes) as f64 / elapsed) as u64
+            } else {
+                0
+            }
+        }
+        None => 0,
+    };
+
+    samples.insert(pid, (total_bytes, now));
+    rate
+}
+
+/// Cumulative network traffic (Wi-Fi/Ethernet and mobile broadband) of a
+/// process, as accounted by `ProcessEnergyValues`
+#[cfg(target_os = "windows")]
+fn query_network_bytes(pid: u32) -> Option<u64> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+        let mut values: PROCESS_ENERGY_VALUES = std::mem::zeroed();
+        let status = NtQueryInformationProcess(
+            handle.0 as *mut c_void,
+            ProcessEnergyValues,
+            &mut values as *mut _ as *mut _,
+            std::mem::size_of::<PROCESS_ENERGY_VALUES>() as u32,
+            std::ptr::null_mut(),
+        );
+        let _ = CloseHandle(handle);
+
+        if status != 0 {
+            return None;
+        }
+        Some(values.NetworkTxRxBytes.saturating_add(values.MBBTxRxBytes))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn query_network_bytes(_pid: u32) -> Option<u64> {
+    // Per-process network accounting is not available without elevated tracing
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_score_and_level() {
+        assert_eq!(energy_score(0.0, 0, 0), 0.0);
+        assert_eq!(energy_impact_level(energy_score(0.0, 0, 0)), "Very low");
+
+        // 1% CPU plus 2 MB/s of disk I/O
+        let score = energy_score(1.0, 2 * 1024 * 1024, 0);
+        assert!((score - 2.0).abs() < f64::EPSILON);
+        assert_eq!(energy_impact_level(score), "Moderate");
+
+        assert_eq!(energy_impact_level(energy_score(25.0, 0, 0)), "Very high");
+        assert_eq!(energy_score(400.0, u64::MAX, u64::MAX), MAX_ENERGY_IMPACT);
+    }
+}
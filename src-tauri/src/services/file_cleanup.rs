@@ -0,0 +1,360 @@
+// Finds disk space that can probably be reclaimed: large files stored twice
+// in the user folders, installers left in Downloads, extracted driver
+// packages and the redistributable runtimes games ship in _CommonRedist.
+// Nothing is deleted by the scan; delete() only accepts paths the last scan
+// returned, and never removes the kept copy of a duplicate.
+use crate::models::system_stats::{
+    CleanupCandidate, CleanupCategory, DeleteSafety, FileCleanupResult,
+};
+use crate::services::game_library;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Smaller duplicates are not worth the time to hash them
+const MIN_DUPLICATE_SIZE: u64 = 100 * 1024 * 1024;
+const MIN_INSTALLER_SIZE: u64 = 10 * 1024 * 1024;
+const STALE_INSTALLER_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const INSTALLER_EXTENSIONS: &[&str] = &["exe", "msi", "msix", "zip", "7z", "rar", "iso"];
+// Deep enough for any user folder, bounded against junction loops
+const MAX_DEPTH: usize = 12;
+// Read from the start and the end of each file before hashing all of it
+const SAMPLE_SIZE: usize = 64 * 1024;
+
+static LAST_SCAN: once_cell::sync::Lazy<Mutex<Vec<CleanupCandidate>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+struct FoundFile {
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Walks the user folders and the known cache locations. Takes a while on
+/// large folders, the files are hashed only when their sizes match.
+pub fn scan() -> Vec<CleanupCandidate> {
+    let mut candidates = Vec::new();
+    candidates.extend(find_duplicates(&user_folders(), MIN_DUPLICATE_SIZE));
+    if let Some(downloads) = user_folders()
+        .into_iter()
+        .find(|folder| folder.ends_with("Downloads"))
+    {
+        candidates.extend(find_stale_installers(&downloads, SystemTime::now()));
+    }
+    candidates.extend(find_driver_packages());
+    candidates.extend(find_redistributables());
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.size_bytes));
+
+    if let Ok(mut last) = LAST_SCAN.lock() {
+        *last = candidates.clone();
+    }
+    candidates
+}
+
+/// Deletes the given candidates of the last scan
+pub fn delete(paths: &[String]) -> FileCleanupResult {
+    let scanned = LAST_SCAN
+        .lock()
+        .map(|last| last.clone())
+        .unwrap_or_default();
+    let mut result = FileCleanupResult::default();
+
+    for path in paths {
+        let Some(candidate) = scanned.iter().find(|candidate| &candidate.path == path) else {
+            result.skipped.push(path.clone());
+            continue;
+        };
+        // The other copy may have been deleted since the scan
+        if candidate
+            .duplicate_of
+            .as_ref()
+            .is_some_and(|kept| !Path::new(kept).exists())
+        {
+            result.skipped.push(path.clone());
+            continue;
+        }
+
+        let target = Path::new(path);
+        let removed = if target.is_dir() {
+            std::fs::remove_dir_all(target)
+        } else {
+            std::fs::remove_file(target)
+        };
+        match removed {
+            Ok(()) => {
+                result.deleted.push(path.clone());
+                result.freed_bytes += candidate.size_bytes;
+            }
+            Err(e) => result.failed.push(format!("{}: {}", path, e)),
+        }
+    }
+
+    if let Ok(mut last) = LAST_SCAN.lock() {
+        last.retain(|candidate| !result.deleted.contains(&candidate.path));
+    }
+    result
+}
+
+fn user_folders() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let home = std::env::var_os("USERPROFILE");
+    #[cfg(not(target_os = "windows"))]
+    let home = std::env::var_os("HOME");
+
+    let Some(home) = home.map(PathBuf::from) else {
+        return Vec::new();
+    };
+    ["Downloads", "Desktop", "Documents", "Videos"]
+        .iter()
+        .map(|folder| home.join(folder))
+        .filter(|folder| folder.is_dir())
+        .collect()
+}
+
+/// Files of at least `min_size` stored more than once. The oldest copy is
+/// kept, the others are candidates.
+fn find_duplicates(roots: &[PathBuf], min_size: u64) -> Vec<CleanupCandidate> {
+    let mut by_size: HashMap<u64, Vec<FoundFile>> = HashMap::new();
+    for root in roots {
+        collect_files(root, min_size, 0, &mut |file| {
+            by_size.entry(file.size).or_default().push(file)
+        });
+    }
+
+    let mut candidates = Vec::new();
+    for files in by_size.into_values().filter(|files| files.len() > 1) {
+        // A sample of each file first, then the whole content of the ones
+        // whose samples match
+        for group in group_by(files, |file| sample_hash(&file.path)) {
+            for mut copies in group_by(group, |file| full_hash(&file.path)) {
+                copies.sort_by_key(|file| file.modified.unwrap_or(SystemTime::UNIX_EPOCH));
+                let kept = copies.remove(0);
+                candidates.extend(copies.into_iter().map(|copy| CleanupCandidate {
+                    path: copy.path.to_string_lossy().into_owned(),
+                    category: CleanupCategory::Duplicate,
+                    size_bytes: copy.size,
+                    modified: unix_secs(copy.modified),
+                    safety: DeleteSafety::Review,
+                    reason: "Same content as another file".to_string(),
+                    duplicate_of: Some(kept.path.to_string_lossy().into_owned()),
+                }));
+            }
+        }
+    }
+    candidates
+}
+
+/// Groups of at least two files with the same key; unreadable files are left out
+fn group_by(files: Vec<FoundFile>, key: impl Fn(&FoundFile) -> Option<u64>) -> Vec<Vec<FoundFile>> {
+    let mut groups: HashMap<u64, Vec<FoundFile>> = HashMap::new();
+    for file in files {
+        if let Some(key) = key(&file) {
+            groups.entry(key).or_default().push(file);
+        }
+    }
+    groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+fn collect_files(folder: &Path, min_size: u64, depth: usize, found: &mut impl FnMut(FoundFile)) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        // Links and junctions would count their target twice
+        let Ok(metadata) = std::fs::symlink_metadata(entry.path()) else {
+            continue;
+        };
+        if metadata.is_symlink() {
+            continue;
+        }
+        if metadata.is_dir() {
+            collect_files(&entry.path(), min_size, depth + 1, found);
+        } else if metadata.len() >= min_size {
+            found(FoundFile {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
+            });
+        }
+    }
+}
+
+fn sample_hash(path: &Path) -> Option<u64> {
+    let mut file = File::open(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0u8; SAMPLE_SIZE];
+    let read = file.read(&mut buffer).ok()?;
+    hasher.write(&buffer[..read]);
+    if file.seek(SeekFrom::End(-(SAMPLE_SIZE as i64))).is_ok() {
+        let read = file.read(&mut buffer).ok()?;
+        hasher.write(&buffer[..read]);
+    }
+    Some(hasher.finish())
+}
+
+fn full_hash(path: &Path) -> Option<u64> {
+    let mut file = File::open(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            return Some(hasher.finish());
+        }
+        hasher.write(&buffer[..read]);
+    }
+}
+
+/// Setup files and archives in Downloads nobody touched for a month
+fn find_stale_installers(downloads: &Path, now: SystemTime) -> Vec<CleanupCandidate> {
+    let Ok(entries) = std::fs::read_dir(downloads) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            let age = now.duration_since(modified).unwrap_or_default();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            (metadata.is_file()
+                && metadata.len() >= MIN_INSTALLER_SIZE
+                && age >= STALE_INSTALLER_AGE
+                && is_installer_name(&name))
+            .then(|| CleanupCandidate {
+                path: entry.path().to_string_lossy().into_owned(),
+                category: CleanupCategory::Installer,
+                size_bytes: metadata.len(),
+                modified: unix_secs(Some(modified)),
+                safety: DeleteSafety::Review,
+                reason: format!("Not used for {} days", age.as_secs() / (24 * 60 * 60)),
+                duplicate_of: None,
+            })
+        })
+        .collect()
+}
+
+fn is_installer_name(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| INSTALLER_EXTENSIONS.contains(&extension.as_str()))
+}
+
+/// Folders the NVIDIA and AMD installers extract the driver to and leave
+/// behind after installing it
+fn find_driver_packages() -> Vec<CleanupCandidate> {
+    #[cfg(target_os = "windows")]
+    let folders = [
+        ("C:\\NVIDIA", "Extracted NVIDIA driver package"),
+        (
+            "C:\\ProgramData\\NVIDIA Corporation\\Downloader",
+            "Driver updates downloaded by the NVIDIA app",
+        ),
+        ("C:\\AMD", "Extracted AMD driver package"),
+    ];
+    #[cfg(not(target_os = "windows"))]
+    let folders: [(&str, &str); 0] = [];
+
+    folders
+        .iter()
+        .filter_map(|(folder, reason)| {
+            folder_candidate(Path::new(folder), CleanupCategory::DriverInstaller, reason)
+        })
+        .collect()
+}
+
+/// _CommonRedist folders of installed games: DirectX, Visual C++ and .NET
+/// installers already run on the first launch
+fn find_redistributables() -> Vec<CleanupCandidate> {
+    game_library::get_game_directories()
+        .iter()
+        .filter_map(|library| std::fs::read_dir(library).ok())
+        .flat_map(|games| games.flatten())
+        .filter_map(|game| {
+            folder_candidate(
+                &game.path().join("_CommonRedist"),
+                CleanupCategory::Redistributable,
+                "Runtime installers, verifying the game files downloads them again",
+            )
+        })
+        .collect()
+}
+
+fn folder_candidate(
+    folder: &Path,
+    category: CleanupCategory,
+    reason: &str,
+) -> Option<CleanupCandidate> {
+    let metadata = std::fs::symlink_metadata(folder).ok()?;
+    if !metadata.is_dir() {
+        return None;
+    }
+    let mut size_bytes = 0;
+    collect_files(folder, 0, 0, &mut |file| size_bytes += file.size);
+    (size_bytes > 0).then(|| CleanupCandidate {
+        path: folder.to_string_lossy().into_owned(),
+        category,
+        size_bytes,
+        modified: unix_secs(metadata.modified().ok()),
+        safety: DeleteSafety::Safe,
+        reason: reason.to_string(),
+        duplicate_of: None,
+    })
+}
+
+fn unix_secs(time: Option<SystemTime>) -> Option<u64> {
+    time?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|age| age.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_installer_name() {
+        assert!(is_installer_name("NVIDIA_app_v11.0.msi"));
+        assert!(is_installer_name("GameSetup.EXE"));
+        assert!(!is_installer_name("notes.txt"));
+        assert!(!is_installer_name("exe"));
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let root = std::env::temp_dir().join(format!("aura-duplicates-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("copies")).unwrap();
+        std::fs::write(root.join("movie.mkv"), vec![1u8; 4096]).unwrap();
+        std::fs::write(root.join("copies").join("movie (1).mkv"), vec![1u8; 4096]).unwrap();
+        // Same size, different content
+        std::fs::write(root.join("other.mkv"), vec![2u8; 4096]).unwrap();
+        // Too small to be considered
+        std::fs::write(root.join("a.txt"), b"same").unwrap();
+        std::fs::write(root.join("b.txt"), b"same").unwrap();
+
+        let duplicates = find_duplicates(std::slice::from_ref(&root), 1024);
+        assert_eq!(duplicates.len(), 1);
+        let duplicate = &duplicates[0];
+        assert_eq!(duplicate.size_bytes, 4096);
+        assert!(duplicate.path.ends_with(".mkv") && !duplicate.path.contains("other"));
+        assert!(duplicate
+            .duplicate_of
+            .as_ref()
+            .is_some_and(|kept| *kept != duplicate.path));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
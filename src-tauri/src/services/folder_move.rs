@@ -0,0 +1,353 @@
+// Moves a folder, typically a game, to another drive and leaves a junction
+// in its place, so launchers keep finding the game at the old path. The copy
+// is verified before anything is touched at the source; the source is then
+// renamed out of the way, replaced by the junction and only deleted once the
+// junction exists, so a failure at any step leaves one complete copy behind.
+// Progress is pushed with the folder-move-progress event.
+use crate::models::system_stats::{FolderMoveStage, FolderMoveStatus};
+use crate::shared::events;
+use crate::shared::system::get_system;
+use anyhow::{anyhow, Result};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::{Disks, ProcessRefreshKind, ProcessesToUpdate};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const BUFFER_SIZE: usize = 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+// The source sits here while the junction replaces it
+const OLD_SUFFIX: &str = ".aura-old";
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static CANCEL: AtomicBool = AtomicBool::new(false);
+static STATUS: once_cell::sync::Lazy<Mutex<FolderMoveStatus>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(FolderMoveStatus::default()));
+
+/// Starts moving `source` into `destination_dir` in the background; the
+/// folder keeps its name. Fails when a move is already running.
+pub fn start(source: &str, destination_dir: &str) -> Result<FolderMoveStatus> {
+    let source = PathBuf::from(source.trim());
+    let destination = destination_for(&source, Path::new(destination_dir.trim()))?;
+    if running_from(&source) {
+        return Err(anyhow!(
+            "A program is running from {}, close it first",
+            source.display()
+        ));
+    }
+    let (total_bytes, total_files) = tree_size(&source)?;
+    if let Some(free) = free_space(&destination) {
+        if free < total_bytes {
+            return Err(anyhow!("Not enough free space on the destination drive"));
+        }
+    }
+
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("A folder move is already running"));
+    }
+    CANCEL.store(false, Ordering::SeqCst);
+    let status = FolderMoveStatus {
+        running: true,
+        source: source.to_string_lossy().into_owned(),
+        destination: destination.to_string_lossy().into_owned(),
+        stage: FolderMoveStage::Copying,
+        total_bytes,
+        total_files,
+        ..Default::default()
+    };
+    update(|current| *current = status.clone());
+
+    std::thread::spawn(move || {
+        let (stage, error) = match run(&source, &destination) {
+            Ok(()) => (FolderMoveStage::Done, None),
+            Err(_) if CANCEL.load(Ordering::SeqCst) => (FolderMoveStage::Cancelled, None),
+            Err(e) => (FolderMoveStage::Failed, Some(e.to_string())),
+        };
+        update(|status| {
+            status.running = false;
+            status.stage = stage;
+            status.error = error;
+        });
+        RUNNING.store(false, Ordering::SeqCst);
+    });
+    Ok(status)
+}
+
+/// Stops a move while it is still copying; the partial copy is deleted and
+/// the source stays as it was. Once the copy is verified the move finishes.
+pub fn cancel() -> FolderMoveStatus {
+    let status = get_status();
+    if status.running && status.stage == FolderMoveStage::Copying {
+        CANCEL.store(true, Ordering::SeqCst);
+    }
+    status
+}
+
+pub fn get_status() -> FolderMoveStatus {
+    STATUS
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_default()
+}
+
+fn update(change: impl FnOnce(&mut FolderMoveStatus)) {
+    if let Ok(mut status) = STATUS.lock() {
+        change(&mut status);
+        events::emit(events::FOLDER_MOVE_PROGRESS, status.clone());
+    }
+}
+
+fn run(source: &Path, destination: &Path) -> Result<()> {
+    let copied = copy_tree(source, destination).and_then(|_| {
+        update(|status| status.stage = FolderMoveStage::Verifying);
+        verify_tree(source, destination)
+    });
+    if let Err(e) = copied {
+        let _ = std::fs::remove_dir_all(destination);
+        return Err(e);
+    }
+
+    update(|status| status.stage = FolderMoveStage::Linking);
+    let mut old = source.as_os_str().to_owned();
+    old.push(OLD_SUFFIX);
+    let old = PathBuf::from(old);
+    std::fs::rename(source, &old)
+        .map_err(|e| anyhow!("Failed to rename {}: {}", source.display(), e))?;
+    if let Err(e) = create_junction(source, destination) {
+        // Back to the start, the copy is the only thing to undo
+        let _ = std::fs::rename(&old, source);
+        let _ = std::fs::remove_dir_all(destination);
+        return Err(e);
+    }
+
+    update(|status| status.stage = FolderMoveStage::Removing);
+    std::fs::remove_dir_all(&old).map_err(|e| {
+        anyhow!(
+            "The folder was moved but {} could not be deleted: {}",
+            old.display(),
+            e
+        )
+    })
+}
+
+/// Where the folder ends up, after checking the move makes sense
+fn destination_for(source: &Path, destination_dir: &Path) -> Result<PathBuf> {
+    let metadata = std::fs::symlink_metadata(source)
+        .map_err(|e| anyhow!("Cannot read {}: {}", source.display(), e))?;
+    if !metadata.is_dir() || metadata.is_symlink() || is_junction(source) {
+        return Err(anyhow!("{} is not a regular folder", source.display()));
+    }
+    if !source.is_absolute() || !destination_dir.is_absolute() {
+        return Err(anyhow!("Both folders must be absolute paths"));
+    }
+    if !destination_dir.is_dir() {
+        return Err(anyhow!("{} is not a folder", destination_dir.display()));
+    }
+    // Compared resolved, the paths given are kept: on Windows the resolved
+    // ones are \\?\ paths that launchers and mklink don't expect
+    if std::fs::canonicalize(destination_dir)?.starts_with(std::fs::canonicalize(source)?) {
+        return Err(anyhow!("The destination is inside the folder to move"));
+    }
+    let name = source
+        .file_name()
+        .ok_or_else(|| anyhow!("A drive root cannot be moved"))?;
+    let destination = destination_dir.join(name);
+    if destination.exists() {
+        return Err(anyhow!("{} already exists", destination.display()));
+    }
+    Ok(destination)
+}
+
+#[cfg(target_os = "windows")]
+fn is_junction(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    // FILE_ATTRIBUTE_REPARSE_POINT
+    std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_attributes() & 0x400 != 0)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_junction(_path: &Path) -> bool {
+    false
+}
+
+/// Whether a running program was started from inside the folder
+fn running_from(folder: &Path) -> bool {
+    let Ok(mut system) = get_system().lock() else {
+        return false;
+    };
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(sysinfo::UpdateKind::OnlyIfNotSet),
+    );
+    system
+        .processes()
+        .values()
+        .filter_map(|process| process.exe())
+        .any(|exe| exe.starts_with(folder))
+}
+
+/// Free space of the drive that will hold `path`
+fn free_space(path: &Path) -> Option<u64> {
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Bytes and files below a folder
+fn tree_size(folder: &Path) -> Result<(u64, u64)> {
+    let mut totals = (0, 0);
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let (bytes, files) = tree_size(&entry.path())?;
+            totals.0 += bytes;
+            totals.1 += files;
+        } else {
+            totals.0 += entry.metadata()?.len();
+            totals.1 += 1;
+        }
+    }
+    Ok(totals)
+}
+
+fn copy_tree(source: &Path, destination: &Path) -> Result<()> {
+    std::fs::create_dir(destination)
+        .map_err(|e| anyhow!("Failed to create {}: {}", destination.display(), e))?;
+    let mut last_progress = Instant::now();
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            copy_file(&entry.path(), &target, &mut last_progress)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_file(source: &Path, destination: &Path, last_progress: &mut Instant) -> Result<()> {
+    let mut reader =
+        File::open(source).map_err(|e| anyhow!("Failed to read {}: {}", source.display(), e))?;
+    let mut writer = File::create(destination)
+        .map_err(|e| anyhow!("Failed to write {}: {}", destination.display(), e))?;
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    loop {
+        if CANCEL.load(Ordering::SeqCst) {
+            return Err(anyhow!("Cancelled"));
+        }
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        if let Ok(mut status) = STATUS.lock() {
+            status.copied_bytes += read as u64;
+        }
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            *last_progress = Instant::now();
+            update(|_| {});
+        }
+    }
+    writer.sync_all()?;
+    update(|status| status.copied_files += 1);
+    Ok(())
+}
+
+/// Every file of the source exists in the copy with the same size
+fn verify_tree(source: &Path, destination: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            verify_tree(&entry.path(), &target)?;
+        } else {
+            let expected = entry.metadata()?.len();
+            let copied = std::fs::metadata(&target)
+                .map(|metadata| metadata.len())
+                .ok();
+            if copied != Some(expected) {
+                return Err(anyhow!("{} was not copied correctly", target.display()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A junction needs no administrator rights, unlike a directory symlink
+#[cfg(target_os = "windows")]
+fn create_junction(link: &Path, target: &Path) -> Result<()> {
+    let output = std::process::Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(link)
+        .arg(target)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to create the junction: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn create_junction(link: &Path, target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link)
+        .map_err(|e| anyhow!("Failed to create the link: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_and_verify_tree() {
+        let root = std::env::temp_dir().join(format!("aura-folder-move-{}", std::process::id()));
+        let source = root.join("Game");
+        std::fs::create_dir_all(source.join("data")).unwrap();
+        std::fs::write(source.join("game.exe"), vec![7u8; 3000]).unwrap();
+        std::fs::write(source.join("data").join("pak0.pak"), b"pak").unwrap();
+
+        assert_eq!(tree_size(&source).unwrap(), (3003, 2));
+        let destination = root.join("Moved");
+        copy_tree(&source, &destination).unwrap();
+        assert!(verify_tree(&source, &destination).is_ok());
+
+        std::fs::write(destination.join("data").join("pak0.pak"), b"pa").unwrap();
+        assert!(verify_tree(&source, &destination).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_destination_for() {
+        let root = std::env::temp_dir().join(format!("aura-destination-{}", std::process::id()));
+        let source = root.join("Game");
+        std::fs::create_dir_all(source.join("sub")).unwrap();
+        std::fs::create_dir_all(root.join("Other")).unwrap();
+
+        let destination = destination_for(&source, &root.join("Other")).unwrap();
+        assert!(destination.ends_with("Other/Game"));
+        // Into itself
+        assert!(destination_for(&source, &source.join("sub")).is_err());
+        // Already there
+        std::fs::create_dir_all(root.join("Other").join("Game")).unwrap();
+        assert!(destination_for(&source, &root.join("Other")).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
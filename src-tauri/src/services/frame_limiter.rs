@@ -0,0 +1,361 @@
+// Caps a game's frame rate while it runs, so a power-limited laptop doesn't
+// spend its budget on frames the screen can't show. The cap goes into the
+// per-game settings of a frame limiter the user already has: the profile
+// RivaTuner Statistics Server keeps for the executable on Windows, the
+// per-application MangoHud config on Linux. Both read it when they hook the
+// game, which happens after the process watcher reports the start. NVIDIA's
+// own Max Frame Rate lives in the driver profiles behind NVAPI, which has no
+// Rust binding, so it isn't offered. What the file held before is put back
+// when the game exits, and saved to disk so a crash doesn't leave the cap.
+use crate::models::game_profile::{FrameLimit, FrameLimiter};
+use crate::services::maintenance;
+use crate::shared::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+const ORIGINAL_FILE: &str = "frame_limit_original.json";
+
+pub const MIN_FPS: u32 = 10;
+pub const MAX_FPS: u32 = 1000;
+
+#[derive(Error, Debug)]
+pub enum FrameLimitError {
+    #[error("No frame limiter is installed")]
+    NoLimiter,
+
+    #[error("{0:?} is not installed")]
+    NotInstalled(FrameLimiter),
+
+    #[error("The frame rate cap must be between {MIN_FPS} and {MAX_FPS} fps, got {0}")]
+    InvalidFps(u32),
+
+    #[error("Failed to write the frame limiter settings: {0}")]
+    ApplyError(String),
+}
+
+type Result<T> = std::result::Result<T, FrameLimitError>;
+
+trait LimiterBackend {
+    fn kind(&self) -> FrameLimiter;
+
+    /// Settings file of the game, None when the tool isn't installed
+    fn profile_path(&self, exe_path: &str) -> Option<PathBuf>;
+
+    /// `content` of the settings file with the cap set
+    fn with_limit(&self, content: &str, fps: u32) -> String;
+}
+
+struct Rtss;
+
+impl LimiterBackend for Rtss {
+    fn kind(&self) -> FrameLimiter {
+        FrameLimiter::Rtss
+    }
+
+    fn profile_path(&self, exe_path: &str) -> Option<PathBuf> {
+        if !cfg!(target_os = "windows") {
+            return None;
+        }
+        let program_files = std::env::var_os("ProgramFiles(x86)")?;
+        let profiles = PathBuf::from(program_files)
+            .join("RivaTuner Statistics Server")
+            .join("Profiles");
+        if !profiles.is_dir() {
+            return None;
+        }
+        // Named after the executable, e.g. game.exe.cfg
+        let file = Path::new(exe_path).file_name()?.to_str()?;
+        Some(profiles.join(format!("{}.cfg", file)))
+    }
+
+    fn with_limit(&self, content: &str, fps: u32) -> String {
+        set_ini_value(content, Some("Framerate"), "Limit", &fps.to_string())
+    }
+}
+
+struct MangoHud;
+
+impl LimiterBackend for MangoHud {
+    fn kind(&self) -> FrameLimiter {
+        FrameLimiter::MangoHud
+    }
+
+    fn profile_path(&self, exe_path: &str) -> Option<PathBuf> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        let installed = ["/usr/bin/mangohud", "/usr/local/bin/mangohud"]
+            .iter()
+            .any(|path| Path::new(path).exists());
+        if !installed {
+            return None;
+        }
+        Some(
+            config
+                .join("MangoHud")
+                .join(mangohud_config_name(exe_path)?),
+        )
+    }
+
+    fn with_limit(&self, content: &str, fps: u32) -> String {
+        set_ini_value(content, None, "fps_limit", &fps.to_string())
+    }
+}
+
+fn backends() -> Vec<Box<dyn LimiterBackend>> {
+    vec![Box::new(Rtss), Box::new(MangoHud)]
+}
+
+/// A settings file as it was before a cap was written into it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Written {
+    path: PathBuf,
+    original: Option<String>, // None when the file didn't exist
+}
+
+// By game pid
+static WRITTEN: once_cell::sync::Lazy<Mutex<HashMap<u32, Written>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Limiters installed on this system
+pub fn available() -> Vec<FrameLimiter> {
+    backends()
+        .iter()
+        // Any name will do, only the install is checked
+        .filter(|backend| backend.profile_path("game.exe").is_some())
+        .map(|backend| backend.kind())
+        .collect()
+}
+
+pub fn validate(limit: &FrameLimit) -> Result<()> {
+    if !(MIN_FPS..=MAX_FPS).contains(&limit.fps) {
+        return Err(FrameLimitError::InvalidFps(limit.fps));
+    }
+    match limit.limiter {
+        Some(limiter) if !available().contains(&limiter) => {
+            Err(FrameLimitError::NotInstalled(limiter))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Writes the cap for a starting game until `release_game`. Returns the fps
+/// applied, None when the cap is only for battery and the PC is plugged in.
+pub fn apply_for_game(game_pid: u32, exe_path: &str, limit: &FrameLimit) -> Result<Option<u32>> {
+    validate(limit)?;
+    if limit.battery_only && maintenance::on_ac_power() != Some(false) {
+        return Ok(None);
+    }
+
+    let (backend, path) = backends()
+        .into_iter()
+        .filter(|backend| {
+            limit
+                .limiter
+                .is_none_or(|limiter| backend.kind() == limiter)
+        })
+        .find_map(|backend| {
+            let path = backend.profile_path(exe_path)?;
+            Some((backend, path))
+        })
+        .ok_or(match limit.limiter {
+            Some(limiter) => FrameLimitError::NotInstalled(limiter),
+            None => FrameLimitError::NoLimiter,
+        })?;
+
+    let mut written = WRITTEN
+        .lock()
+        .map_err(|e| FrameLimitError::ApplyError(e.to_string()))?;
+    let content = std::fs::read_to_string(&path).ok();
+    // A second instance of the game finds the cap of the first in the file
+    let original = written
+        .values()
+        .find(|entry| entry.path == path)
+        .map(|entry| entry.original.clone())
+        .unwrap_or_else(|| content.clone());
+    written.insert(
+        game_pid,
+        Written {
+            path: path.clone(),
+            original,
+        },
+    );
+    save_originals(&written);
+
+    let updated = backend.with_limit(content.as_deref().unwrap_or_default(), limit.fps);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    std::fs::write(&path, updated).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => {
+            FrameLimitError::ApplyError(format!("{} needs administrator rights", path.display()))
+        }
+        _ => FrameLimitError::ApplyError(e.to_string()),
+    })?;
+    Ok(Some(limit.fps))
+}
+
+/// Puts the settings file back once no running instance of the game needs it
+pub fn release_game(game_pid: u32) {
+    let Ok(mut written) = WRITTEN.lock() else {
+        return;
+    };
+    let Some(entry) = written.remove(&game_pid) else {
+        return;
+    };
+    if !written.values().any(|other| other.path == entry.path) {
+        restore(&entry);
+    }
+    save_originals(&written);
+}
+
+/// Puts every settings file back. Called on exit, hence the try_lock.
+pub fn release_all() {
+    let Ok(mut written) = WRITTEN.try_lock() else {
+        return;
+    };
+    let mut restored: Vec<PathBuf> = Vec::new();
+    for (_, entry) in written.drain() {
+        if !restored.contains(&entry.path) {
+            restore(&entry);
+            restored.push(entry.path);
+        }
+    }
+    let _ = std::fs::remove_file(paths::app_data_file(ORIGINAL_FILE));
+}
+
+/// Puts back the settings files a crashed run left capped
+pub fn restore_leftover() {
+    let path = paths::app_data_file(ORIGINAL_FILE);
+    let Some(leftover) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Vec<Written>>(&content).ok())
+    else {
+        return;
+    };
+    if WRITTEN.lock().is_ok_and(|written| written.is_empty()) {
+        for entry in &leftover {
+            restore(entry);
+        }
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn restore(entry: &Written) {
+    let result = match &entry.original {
+        Some(original) => std::fs::write(&entry.path, original),
+        None => std::fs::remove_file(&entry.path),
+    };
+    if let Err(e) = result {
+        eprintln!(
+            "Failed to restore the frame limiter settings {}: {}",
+            entry.path.display(),
+            e
+        );
+    }
+}
+
+fn save_originals(written: &HashMap<u32, Written>) {
+    let path = paths::app_data_file(ORIGINAL_FILE);
+    if written.is_empty() {
+        let _ = std::fs::remove_file(path);
+        return;
+    }
+    let entries: Vec<&Written> = written.values().collect();
+    if let Ok(content) = serde_json::to_string(&entries) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+/// MangoHud looks for `<name>.conf`, and `wine-<name>.conf` for Windows
+/// games run through Wine or Proton
+fn mangohud_config_name(exe_path: &str) -> Option<String> {
+    let file = exe_path.rsplit(['/', '\\']).next()?;
+    match file.strip_suffix(".exe") {
+        Some(stem) => Some(format!("wine-{}.conf", stem)),
+        None if !file.is_empty() => Some(format!("{}.conf", file)),
+        None => None,
+    }
+}
+
+/// `content` of an INI file with `key` set to `value`, in `section` or before
+/// any section when None. The rest of the file is kept as it is.
+fn set_ini_value(content: &str, section: Option<&str>, key: &str, value: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let is_section = |line: &str| line.trim_start().starts_with('[');
+    let header = section.map(|section| format!("[{}]", section));
+
+    // Lines of the section, up to the next header
+    let start = match &header {
+        Some(header) => lines
+            .iter()
+            .position(|line| line.trim().eq_ignore_ascii_case(header))
+            .map(|index| index + 1),
+        None => Some(0),
+    };
+    let entry = format!("{}={}", key, value);
+    match start {
+        Some(start) => {
+            let end = lines[start..]
+                .iter()
+                .position(|line| is_section(line))
+                .map_or(lines.len(), |offset| start + offset);
+            let existing = lines[start..end].iter().position(|line| {
+                line.split_once('=')
+                    .is_some_and(|(name, _)| name.trim().eq_ignore_ascii_case(key))
+            });
+            match existing {
+                Some(offset) => lines[start + offset] = entry,
+                None => lines.insert(end, entry),
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.extend(header);
+            lines.push(entry);
+        }
+    }
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_ini_value() {
+        let profile = "[OSD]\nEnableOSD=1\n[Framerate]\nLimit=0\nLimitDenominator=1\n";
+        assert_eq!(
+            set_ini_value(profile, Some("Framerate"), "Limit", "60"),
+            "[OSD]\nEnableOSD=1\n[Framerate]\nLimit=60\nLimitDenominator=1\n"
+        );
+        assert_eq!(
+            set_ini_value("[OSD]\nEnableOSD=1\n", Some("Framerate"), "Limit", "45"),
+            "[OSD]\nEnableOSD=1\n\n[Framerate]\nLimit=45\n"
+        );
+        assert_eq!(set_ini_value("", None, "fps_limit", "40"), "fps_limit=40\n");
+        assert_eq!(
+            set_ini_value("fps_limit=60\ncpu_temp\n", None, "fps_limit", "40"),
+            "fps_limit=40\ncpu_temp\n"
+        );
+    }
+
+    #[test]
+    fn test_mangohud_config_name() {
+        let proton = mangohud_config_name("C:\\Games\\Elden Ring\\eldenring.exe");
+        assert_eq!(proton.as_deref(), Some("wine-eldenring.conf"));
+        let native = mangohud_config_name("/usr/games/supertuxkart");
+        assert_eq!(native.as_deref(), Some("supertuxkart.conf"));
+        assert_eq!(mangohud_config_name("/usr/games/"), None);
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// Returns the directories where launchers install games (Steam libraries,
+/// Epic, GOG, Xbox, Ubisoft, EA). Only existing directories are returned.
+pub fn get_game_directories() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    for steam_root in get_steam_roots() {
+        candidates.push(steam_root.join("steamapps").join("common"));
+
+        // Additional Steam libraries on other drives
+        let library_file = steam_root.join("steamapps").join("libraryfolders.vdf");
+        if let Ok(content) = std::fs::read_to_string(&library_file) {
+            for library in parse_library_folders(&content) {
+                candidates.push(PathBuf::from(library).join("steamapps").join("common"));
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        candidates.extend(
+            [
+                "C:\\Program Files\\Epic Games",
+                "C:\\Program Files (x86)\\GOG Galaxy\\Games",
+                "C:\\GOG Games",
+                "C:\\XboxGames",
+                "C:\\Program Files\\ModifiableWindowsApps",
+                "C:\\Program Files (x86)\\Ubisoft\\Ubisoft Game Launcher\\games",
+                "C:\\Program Files\\EA Games",
+                "C:\\Program Files (x86)\\Origin Games",
+            ]
+            .iter()
+            .map(PathBuf::from),
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+            candidates.push(home.join("Games"));
+            candidates.push(home.join(".local/share/lutris/games"));
+        }
+    }
+
+    let mut directories: Vec<PathBuf> = Vec::new();
+    for candidate in candidates {
+        if candidate.is_dir() && !directories.iter().any(|d| same_path(d, &candidate)) {
+            directories.push(candidate);
+        }
+    }
+
+    directories
+}
+
+/// Checks whether an executable lives under one of the given game directories
+pub fn is_in_game_directory(exe_path: &Path, game_directories: &[PathBuf]) -> bool {
+    let exe = normalize_path(exe_path);
+    game_directories
+        .iter()
+        .any(|dir| exe.starts_with(&format!("{}/", normalize_path(dir))))
+}
+
+#[cfg(target_os = "windows")]
+fn get_steam_roots() -> Vec<PathBuf> {
+    use std::process::Command;
+
+    let mut roots = Vec::new();
+
+    // Steam stores its install path in the user hive
+    let output = Command::new("reg")
+        .args(["query", "HKCU\\Software\\Valve\\Steam", "/v", "SteamPath"])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output();
+
+    if let Ok(output) = output {
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        for line in output_str.lines() {
+            if let Some((_, value)) = line.split_once("REG_SZ") {
+                let value = value.trim().replace('/', "\\");
+                if !value.is_empty() {
+                    roots.push(PathBuf::from(value));
+                }
+            }
+        }
+    }
+
+    if roots.is_empty() {
+        roots.push(PathBuf::from("C:\\Program Files (x86)\\Steam"));
+    }
+
+    roots
+}
+
+#[cfg(not(target_os = "windows"))]
+fn get_steam_roots() -> Vec<PathBuf> {
+    match std::env::var_os("HOME").map(PathBuf::from) {
+        Some(home) => vec![
+            home.join(".steam/steam"),
+            home.join(".local/share/Steam"),
+            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+        ],
+        None => Vec::new(),
+    }
+}
+
+/// Extracts the library paths from Steam's libraryfolders.vdf
+fn parse_library_folders(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('"').collect();
+            // Expected form: "path"		"D:\\SteamLibrary"
+            if parts.len() >= 4 && parts[1] == "path" {
+                Some(parts[3].replace("\\\\", "\\"))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn normalize_path(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = path.trim_end_matches('/');
+    if cfg!(target_os = "windows") {
+        path.to_lowercase()
+    } else {
+        path.to_string()
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    normalize_path(a) == normalize_path(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_library_folders() {
+        let content = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+		"label"		""
+	}
+	"1"
+	{
+		"path"		"D:\\SteamLibrary"
+	}
+}
+"#;
+        let libraries = parse_library_folders(content);
+        assert_eq!(
+            libraries,
+            vec!["C:\\Program Files (x86)\\Steam", "D:\\SteamLibrary"]
+        );
+    }
+
+    #[test]
+    fn test_is_in_game_directory() {
+        let dirs = vec![PathBuf::from("/games/steamapps/common")];
+        assert!(is_in_game_directory(
+            Path::new("/games/steamapps/common/Game/game.exe"),
+            &dirs
+        ));
+        assert!(!is_in_game_directory(
+            Path::new("/games/steamapps/commonother/game.exe"),
+            &dirs
+        ));
+        assert!(!is_in_game_directory(Path::new("/usr/bin/bash"), &dirs));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::models::game_profile::GameProfile;
+use crate::services::{cooling, frame_limiter, job_groups, power_plan, qos};
+use crate::shared::paths;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use thiserror::Error;
+
+const PROFILES_FILE: &str = "game_profiles.json";
+
+#[derive(Error, Debug)]
+pub enum GameProfileError {
+    #[error("Failed to access game profiles: {0}")]
+    StorageError(String),
+
+    #[error("No profile for {0}")]
+    NotFound(String),
+
+    #[error("Invalid profile: {0}")]
+    InvalidProfile(String),
+
+    #[error("Failed to launch game: {0}")]
+    LaunchError(String),
+}
+
+type Result<T> = std::result::Result<T, GameProfileError>;
+
+static PROFILES: once_cell::sync::Lazy<Mutex<HashMap<String, GameProfile>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_profiles()));
+
+fn load_profiles() -> HashMap<String, GameProfile> {
+    std::fs::read_to_string(paths::app_data_file(PROFILES_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Vec<GameProfile>>(&content).ok())
+        .map(|profiles| {
+            profiles
+                .into_iter()
+                .map(|p| (profile_key(&p.exe_path), p))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn save_profiles(profiles: &HashMap<String, GameProfile>) -> Result<()> {
+    let mut list: Vec<&GameProfile> = profiles.values().collect();
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let content = serde_json::to_string_pretty(&list)
+        .map_err(|e| GameProfileError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(PROFILES_FILE), content)
+        .map_err(|e| GameProfileError::StorageError(e.to_string()))
+}
+
+/// Paths are case-insensitive on Windows
+fn profile_key(exe_path: &str) -> String {
+    if cfg!(target_os = "windows") {
+        exe_path.replace('/', "\\").to_lowercase()
+    } else {
+        exe_path.to_string()
+    }
+}
+
+pub fn get_profiles() -> Vec<GameProfile> {
+    PROFILES
+        .lock()
+        .map(|profiles| profiles.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub fn get_profile(exe_path: &str) -> Option<GameProfile> {
+    PROFILES
+        .lock()
+        .ok()
+        .and_then(|profiles| profiles.get(&profile_key(exe_path)).cloned())
+}
+
+/// Creates or replaces the profile of a game
+pub fn save_profile(profile: GameProfile) -> Result<()> {
+    if profile.exe_path.trim().is_empty() {
+        return Err(GameProfileError::InvalidProfile(
+            "Executable path is required".to_string(),
+        ));
+    }
+    if let Some(key) = profile
+        .env_vars
+        .keys()
+        .find(|k| k.is_empty() || k.contains('=') || k.contains('\0'))
+    {
+        return Err(GameProfileError::InvalidProfile(format!(
+            "Invalid environment variable name: {:?}",
+            key
+        )));
+    }
+
+    if profile
+        .affinity
+        .as_ref()
+        .is_some_and(|cores| cores.is_empty())
+    {
+        return Err(GameProfileError::InvalidProfile(
+            "Affinity needs at least one core".to_string(),
+        ));
+    }
+
+    job_groups::validate_limits(&profile.job_limits)
+        .map_err(|e| GameProfileError::InvalidProfile(e.to_string()))?;
+
+    if let Some(cooling_profile) = profile.cooling_profile {
+        if !cooling::available().contains(&cooling_profile) {
+            return Err(GameProfileError::InvalidProfile(
+                cooling::CoolingError::UnavailableProfile(cooling_profile).to_string(),
+            ));
+        }
+    }
+
+    if profile.high_performance_power_plan && !power_plan::supported() {
+        return Err(GameProfileError::InvalidProfile(
+            power_plan::PowerPlanError::Unsupported.to_string(),
+        ));
+    }
+
+    if let Some(dscp) = profile.dscp.filter(|dscp| *dscp > qos::MAX_DSCP) {
+        return Err(GameProfileError::InvalidProfile(
+            qos::QosError::InvalidDscp(dscp).to_string(),
+        ));
+    }
+
+    if let Some(limit) = &profile.frame_limit {
+        frame_limiter::validate(limit)
+            .map_err(|e| GameProfileError::InvalidProfile(e.to_string()))?;
+    }
+
+    let mut profiles = PROFILES
+        .lock()
+        .map_err(|e| GameProfileError::StorageError(e.to_string()))?;
+    profiles.insert(profile_key(&profile.exe_path), profile);
+    save_profiles(&profiles)
+}
+
+pub fn delete_profile(exe_path: &str) -> Result<()> {
+    let mut profiles = PROFILES
+        .lock()
+        .map_err(|e| GameProfileError::StorageError(e.to_string()))?;
+
+    if profiles.remove(&profile_key(exe_path)).is_none() {
+        return Err(GameProfileError::NotFound(exe_path.to_string()));
+    }
+    save_profiles(&profiles)
+}
+
+/// Launches a game with the environment variables and arguments of its
+/// profile (if any) in a job group with the profile's limits, and returns
+/// the new PID
+pub fn launch_game(exe_path: &str) -> Result<u32> {
+    let path = Path::new(exe_path);
+    if !path.is_file() {
+        return Err(GameProfileError::LaunchError(format!(
+            "{} does not exist",
+            exe_path
+        )));
+    }
+
+    let profile = get_profile(exe_path).unwrap_or_default();
+
+    let mut command = std::process::Command::new(path);
+    command.args(&profile.launch_args).envs(&profile.env_vars);
+    if let Some(dir) = path.parent() {
+        // Many games expect to be started from their install directory
+        command.current_dir(dir);
+    }
+
+    let child = command
+        .spawn()
+        .map_err(|e| GameProfileError::LaunchError(e.to_string()))?;
+
+    // The game keeps running without its group if the job can't be set up
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = if profile.name.is_empty() {
+        name
+    } else {
+        profile.name
+    };
+    if let Err(e) = job_groups::create(child.id(), &name, exe_path, profile.job_limits) {
+        eprintln!("Job group not created for {}: {}", exe_path, e);
+    }
+
+    Ok(child.id())
+}
@@ -0,0 +1,273 @@
+use crate::models::readiness::{GamingServicesStatus, ReadinessItem, ServiceState, ServiceStatus};
+use anyhow::Result;
+
+#[cfg(target_os = "windows")]
+use crate::shared::registry;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+pub const GAME_CONFIG_STORE_KEY: &str = "HKCU\\System\\GameConfigStore";
+#[cfg(target_os = "windows")]
+pub const GAME_DVR_KEY: &str = "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\GameDVR";
+#[cfg(target_os = "windows")]
+pub const GAME_DVR_POLICY_KEY: &str = "HKLM\\SOFTWARE\\Policies\\Microsoft\\Windows\\GameDVR";
+#[cfg(target_os = "windows")]
+const GAME_BAR_KEY: &str = "HKCU\\Software\\Microsoft\\GameBar";
+
+// Services Game Pass titles need for sign-in, cloud saves, multiplayer and
+// Xbox accessories
+#[cfg(target_os = "windows")]
+const XBOX_SERVICES: &[(&str, &str)] = &[
+    ("XblAuthManager", "Xbox Live Auth Manager"),
+    ("XblGameSave", "Xbox Live Game Save"),
+    ("XboxNetApiSvc", "Xbox Live Networking Service"),
+    ("XboxGipSvc", "Xbox Accessory Management Service"),
+];
+#[cfg(target_os = "windows")]
+const GAME_INPUT_SERVICE: (&str, &str) = ("GameInputSvc", "GameInput Service");
+
+// sc.exe codes, printed before the (localized) state name
+#[cfg(target_os = "windows")]
+const SERVICE_RUNNING: u32 = 4;
+#[cfg(target_os = "windows")]
+const SERVICE_DISABLED: u32 = 4;
+#[cfg(target_os = "windows")]
+const ERROR_SERVICE_DOES_NOT_EXIST: i32 = 1060;
+
+/// Audits Game Bar, Game DVR, Game Mode and the Xbox/GameInput services.
+/// Read-only: nothing is changed.
+pub fn get_status() -> Result<GamingServicesStatus> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut status = GamingServicesStatus {
+            game_bar_enabled: registry::flag(GAME_BAR_KEY, "UseNexusForGameBarEnabled", true),
+            game_dvr_enabled: game_dvr_enabled(),
+            background_recording_enabled: registry::flag(
+                GAME_DVR_KEY,
+                "HistoricalCaptureEnabled",
+                false,
+            ),
+            game_mode_enabled: game_mode_enabled(),
+            xbox_services: XBOX_SERVICES
+                .iter()
+                .map(|(name, display_name)| service_status(name, display_name))
+                .collect(),
+            game_input_service: service_status(GAME_INPUT_SERVICE.0, GAME_INPUT_SERVICE.1),
+            checklist: Vec::new(),
+        };
+        status.checklist = readiness_items(&status);
+        Ok(status)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        anyhow::bail!("Game Bar and Xbox services are Windows-only")
+    }
+}
+
+/// Game DVR counts as enabled when either the capture store or app capture is
+/// on, unless the machine policy forbids it
+#[cfg(target_os = "windows")]
+pub fn game_dvr_enabled() -> Option<bool> {
+    if registry::flag(GAME_DVR_POLICY_KEY, "AllowGameDVR", true) == Some(false) {
+        return Some(false);
+    }
+    let store = registry::flag(GAME_CONFIG_STORE_KEY, "GameDVR_Enabled", true)?;
+    let app_capture = registry::flag(GAME_DVR_KEY, "AppCaptureEnabled", true)?;
+    Some(store || app_capture)
+}
+
+/// Whether every Game DVR switch is off: capture store, app capture and the
+/// AllowGameDVR machine policy
+#[cfg(target_os = "windows")]
+pub fn game_dvr_fully_disabled() -> bool {
+    registry::flag(GAME_CONFIG_STORE_KEY, "GameDVR_Enabled", true) == Some(false)
+        && registry::flag(GAME_DVR_KEY, "AppCaptureEnabled", true) == Some(false)
+        && registry::flag(GAME_DVR_POLICY_KEY, "AllowGameDVR", true) == Some(false)
+}
+
+/// Turns Game DVR on or off. Turning it back on removes the policy instead of
+/// allowing it explicitly, so the machine goes back to its default. The policy
+/// lives under HKLM and needs administrator rights.
+#[cfg(target_os = "windows")]
+pub fn set_game_dvr(enabled: bool) -> Result<()> {
+    let flag = u32::from(enabled);
+    registry::set_dword(GAME_CONFIG_STORE_KEY, "GameDVR_Enabled", flag)?;
+    registry::set_dword(GAME_DVR_KEY, "AppCaptureEnabled", flag)?;
+
+    if enabled {
+        registry::delete_value(GAME_DVR_POLICY_KEY, "AllowGameDVR")
+    } else {
+        registry::set_dword(GAME_DVR_POLICY_KEY, "AllowGameDVR", 0)
+    }
+}
+
+/// Game Mode is on by default since Windows 10 1903, so a missing value means enabled
+#[cfg(target_os = "windows")]
+pub fn game_mode_enabled() -> Option<bool> {
+    registry::flag(GAME_BAR_KEY, "AutoGameModeEnabled", true)
+}
+
+/// Checklist entries derived from the audit, with the optimization fixing each
+pub fn readiness_items(status: &GamingServicesStatus) -> Vec<ReadinessItem> {
+    let mut items = Vec::new();
+
+    if let Some(enabled) = status.game_dvr_enabled {
+        let recording = status.background_recording_enabled.unwrap_or(false);
+        items.push(ReadinessItem {
+            id: "game_dvr".to_string(),
+            label: "Game DVR disabled".to_string(),
+            passed: !enabled,
+            detail: match (enabled, recording) {
+                (false, _) => "Game capture is off".to_string(),
+                (true, true) => "Background recording captures every game session".to_string(),
+                (true, false) => "Game capture hooks into every game".to_string(),
+            },
+            fix_id: Some("disable_game_dvr".to_string()),
+        });
+    }
+
+    if let Some(enabled) = status.game_mode_enabled {
+        items.push(ReadinessItem {
+            id: "game_mode".to_string(),
+            label: "Game Mode enabled".to_string(),
+            passed: enabled,
+            detail: if enabled {
+                "Windows prioritizes the game in the foreground".to_string()
+            } else {
+                "Background work is not held back while gaming".to_string()
+            },
+            fix_id: Some("enable_game_mode".to_string()),
+        });
+    }
+
+    let disabled: Vec<&str> = status
+        .xbox_services
+        .iter()
+        .filter(|s| s.state == ServiceState::Disabled)
+        .map(|s| s.display_name.as_str())
+        .collect();
+    items.push(ReadinessItem {
+        id: "xbox_services".to_string(),
+        label: "Xbox services available".to_string(),
+        passed: disabled.is_empty(),
+        detail: if disabled.is_empty() {
+            "Xbox sign-in and cloud saves can start on demand".to_string()
+        } else {
+            format!(
+                "Disabled: {}. Game Pass titles may fail to sign in",
+                disabled.join(", ")
+            )
+        },
+        fix_id: None,
+    });
+
+    let game_input = &status.game_input_service;
+    items.push(ReadinessItem {
+        id: "game_input".to_string(),
+        label: "GameInput service available".to_string(),
+        passed: game_input.state != ServiceState::Disabled,
+        detail: match game_input.state {
+            ServiceState::Disabled => "Controllers may not work in GameInput titles".to_string(),
+            ServiceState::NotInstalled => "Installed by the first game that needs it".to_string(),
+            _ => "Controller input is available".to_string(),
+        },
+        fix_id: None,
+    });
+
+    items
+}
+
+/// State of a Windows service as reported by sc.exe
+#[cfg(target_os = "windows")]
+pub fn service_status(name: &str, display_name: &str) -> ServiceStatus {
+    let state = match sc(&["query", name]) {
+        Err(code) if code == ERROR_SERVICE_DOES_NOT_EXIST => ServiceState::NotInstalled,
+        Ok(output) if parse_sc_code(&output, "STATE") == Some(SERVICE_RUNNING) => {
+            ServiceState::Running
+        }
+        _ => match sc(&["qc", name]) {
+            Ok(output) if parse_sc_code(&output, "START_TYPE") == Some(SERVICE_DISABLED) => {
+                ServiceState::Disabled
+            }
+            _ => ServiceState::Stopped,
+        },
+    };
+
+    ServiceStatus {
+        name: name.to_string(),
+        display_name: display_name.to_string(),
+        state,
+    }
+}
+
+/// Runs sc.exe, returning its output or exit code
+#[cfg(target_os = "windows")]
+fn sc(args: &[&str]) -> std::result::Result<String, i32> {
+    let output = Command::new("sc")
+        .args(args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .map_err(|_| -1)?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(output.status.code().unwrap_or(-1))
+    }
+}
+
+/// Parses the numeric code of a field like `STATE              : 4  RUNNING`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_sc_code(output: &str, field: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if name.trim() != field {
+            return None;
+        }
+        value.split_whitespace().next()?.parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sc_code() {
+        let query = "SERVICE_NAME: XblAuthManager\r\n        TYPE               : 20  WIN32_SHARE_PROCESS\r\n        STATE              : 4  RUNNING\r\n";
+        assert_eq!(parse_sc_code(query, "STATE"), Some(4));
+        let config = "        START_TYPE         : 4   DISABLED\r\n";
+        assert_eq!(parse_sc_code(config, "START_TYPE"), Some(4));
+        assert_eq!(parse_sc_code(config, "STATE"), None);
+    }
+
+    #[test]
+    fn test_readiness_items() {
+        let service = |state| ServiceStatus {
+            name: "XblAuthManager".to_string(),
+            display_name: "Xbox Live Auth Manager".to_string(),
+            state,
+        };
+        let status = GamingServicesStatus {
+            game_bar_enabled: Some(true),
+            game_dvr_enabled: Some(true),
+            background_recording_enabled: Some(false),
+            game_mode_enabled: None,
+            xbox_services: vec![service(ServiceState::Disabled)],
+            game_input_service: service(ServiceState::NotInstalled),
+            checklist: Vec::new(),
+        };
+
+        let items = readiness_items(&status);
+        let ids: Vec<&str> = items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["game_dvr", "xbox_services", "game_input"]);
+        assert!(!items[0].passed);
+        assert_eq!(items[0].fix_id.as_deref(), Some("disable_game_dvr"));
+        assert!(!items[1].passed);
+        assert!(items[2].passed);
+    }
+}
@@ -0,0 +1,427 @@
+// AMD cards. On Windows they are read through ADLX, the library Adrenalin
+// installs with the driver, loaded at runtime so Aura starts without it. On
+// Linux the amdgpu driver's sysfs files are read.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use super::GpuBackend;
+#[cfg(target_os = "linux")]
+use super::{pcie_gen, pcie_link};
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use crate::models::gpu_info::GpuInfo;
+#[cfg(target_os = "linux")]
+use crate::models::gpu_info::PcieLink;
+#[cfg(target_os = "linux")]
+use crate::services::gpu_tuning;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use anyhow::Result;
+
+#[cfg(target_os = "windows")]
+pub(super) struct Adlx;
+
+#[cfg(target_os = "windows")]
+impl GpuBackend for Adlx {
+    fn read(&self) -> Result<Vec<GpuInfo>> {
+        adlx::read()
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(super) struct Amdgpu;
+
+#[cfg(target_os = "linux")]
+impl GpuBackend for Amdgpu {
+    fn read(&self) -> Result<Vec<GpuInfo>> {
+        Ok(read_amdgpu())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_amdgpu() -> Vec<GpuInfo> {
+    use std::fs;
+    let mut gpus = Vec::new();
+
+    // Check for AMD GPUs in /sys/class/drm/
+    if let Ok(entries) = fs::read_dir("/sys/class/drm/") {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if name.starts_with("card") && !name.contains("-") {
+                    // Try to read GPU info from sysfs
+                    let vendor_path = path.join("device/vendor");
+                    let device_path = path.join("device/device");
+
+                    if let (Ok(vendor), Ok(device)) = (
+                        fs::read_to_string(&vendor_path),
+                        fs::read_to_string(&device_path),
+                    ) {
+                        let vendor_id = vendor.trim();
+                        let device_id = device.trim();
+
+                        if vendor_id == "0x1002" {
+                            // AMD vendor ID
+                            let device = path.join("device");
+                            let temps = hwmon_temperatures(&device);
+                            let fans = fs::read_dir(device.join("hwmon"))
+                                .ok()
+                                .and_then(|mut entries| entries.next())
+                                .and_then(|entry| entry.ok())
+                                .map(|hwmon| gpu_tuning::amdgpu_fans(&hwmon.path()))
+                                .unwrap_or_default();
+                            let fans_stopped = gpu_tuning::fans_stopped(&fans);
+                            gpus.push(GpuInfo {
+                                name: format!("AMD GPU ({})", device_id),
+                                vendor: "AMD".to_string(),
+                                temperature: temps.edge,
+                                memory_temperature: temps.mem,
+                                hotspot_temperature: temps.junction,
+                                zero_rpm_enabled: gpu_tuning::amdgpu_zero_rpm(&device)
+                                    .or(fans_stopped.then_some(true)),
+                                fans,
+                                fans_stopped,
+                                pcie_link: sysfs_pcie_link(&device),
+                                is_amd: true,
+                                ..Default::default()
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    gpus
+}
+
+/// ADLX through its C interface: every object starts with a pointer to its
+/// table of methods, called with the object as first argument
+#[cfg(target_os = "windows")]
+mod adlx {
+    use super::super::monitor::{self, LIBRARY_RETRY};
+    use crate::models::gpu_info::{GpuFanInfo, GpuInfo};
+    use crate::services::gpu_tuning;
+    use anyhow::{anyhow, Result};
+    use libloading::Library;
+    use std::ffi::{c_char, c_void, CStr};
+    use std::ptr::null_mut;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    const ADLX_OK: i32 = 0;
+
+    // Method slots, in the order of the ADLX C headers. Every interface but
+    // the system starts with Acquire, Release and QueryInterface.
+    const RELEASE: usize = 1;
+    const SYSTEM_GET_GPUS: usize = 1;
+    const SYSTEM_GET_PERFORMANCE_MONITORING: usize = 9;
+    const LIST_SIZE: usize = 3;
+    const GPU_LIST_AT: usize = 11;
+    const GPU_NAME: usize = 7;
+    const GPU_TOTAL_VRAM: usize = 11; // MB
+    const MONITORING_CURRENT_GPU_METRICS: usize = 18;
+    const METRICS_USAGE: usize = 4; // %
+    const METRICS_CLOCK: usize = 5; // MHz
+    const METRICS_VRAM_CLOCK: usize = 6; // MHz
+    const METRICS_TEMPERATURE: usize = 7; // °C
+    const METRICS_HOTSPOT_TEMPERATURE: usize = 8; // °C
+    const METRICS_POWER: usize = 9; // W, the chip alone
+    const METRICS_BOARD_POWER: usize = 10; // W, the whole card
+    const METRICS_FAN_SPEED: usize = 11; // RPM
+    const METRICS_VRAM: usize = 12; // MB in use
+
+    type QueryFullVersionFn = unsafe extern "C" fn(*mut u64) -> i32;
+    type InitializeFn = unsafe extern "C" fn(u64, *mut *mut Object) -> i32;
+    type TerminateFn = unsafe extern "C" fn() -> i32;
+    type ReleaseFn = unsafe extern "system" fn(*mut Object) -> i32;
+    type GetFn = unsafe extern "system" fn(*mut Object, *mut *mut Object) -> i32;
+    type SizeFn = unsafe extern "system" fn(*mut Object) -> u32;
+    type AtFn = unsafe extern "system" fn(*mut Object, u32, *mut *mut Object) -> i32;
+    type MetricsFn = unsafe extern "system" fn(*mut Object, *mut Object, *mut *mut Object) -> i32;
+    type ValueFn<T> = unsafe extern "system" fn(*mut Object, *mut T) -> i32;
+
+    #[repr(C)]
+    struct Object {
+        methods: *const *const c_void,
+    }
+
+    /// The method in `slot` of the object's table
+    unsafe fn method<F: Copy>(object: *mut Object, slot: usize) -> F {
+        let method = *(*object).methods.add(slot);
+        std::mem::transmute_copy(&method)
+    }
+
+    /// An object Aura holds a reference to, released when dropped
+    struct Owned(*mut Object);
+
+    impl Owned {
+        unsafe fn value<T: Default>(&self, slot: usize) -> Option<T> {
+            let mut value = T::default();
+            (method::<ValueFn<T>>(self.0, slot)(self.0, &mut value) == ADLX_OK).then_some(value)
+        }
+    }
+
+    impl Drop for Owned {
+        fn drop(&mut self) {
+            // Safety: the pointer came from ADLX and is released once
+            unsafe {
+                method::<ReleaseFn>(self.0, RELEASE)(self.0);
+            }
+        }
+    }
+
+    struct Context {
+        // The system object lives until ADLX is terminated, it isn't released
+        system: *mut Object,
+        terminate: TerminateFn,
+        _library: Library,
+    }
+
+    // Safety: the system object is only used under the ADLX mutex
+    unsafe impl Send for Context {}
+
+    impl Drop for Context {
+        fn drop(&mut self) {
+            // Safety: nothing ADLX handed out outlives the context
+            unsafe {
+                (self.terminate)();
+            }
+        }
+    }
+
+    impl Context {
+        fn load() -> Result<Self> {
+            let library = monitor::driver_library("amdadlx64.dll")?;
+            unsafe {
+                let query_version =
+                    *library.get::<QueryFullVersionFn>(b"ADLXQueryFullVersion\0")?;
+                let initialize = *library.get::<InitializeFn>(b"ADLXInitialize\0")?;
+                let terminate = *library.get::<TerminateFn>(b"ADLXTerminate\0")?;
+
+                // Asking for the driver's own version; the methods used here
+                // are in every one of them
+                let mut version = 0;
+                if query_version(&mut version) != ADLX_OK {
+                    return Err(anyhow!("ADLX version unavailable"));
+                }
+                let mut system = null_mut();
+                if initialize(version, &mut system) != ADLX_OK || system.is_null() {
+                    return Err(anyhow!("ADLX failed to initialize"));
+                }
+                Ok(Self {
+                    system,
+                    terminate,
+                    _library: library,
+                })
+            }
+        }
+
+        unsafe fn gpus(&self) -> Result<Vec<GpuInfo>> {
+            let system = self.system;
+            let mut list = null_mut();
+            if method::<GetFn>(system, SYSTEM_GET_GPUS)(system, &mut list) != ADLX_OK
+                || list.is_null()
+            {
+                return Err(anyhow!("ADLX listed no GPUs"));
+            }
+            let list = Owned(list);
+            // Without it the cards are still listed, only without live readings
+            let mut monitoring = null_mut();
+            let monitoring = (method::<GetFn>(system, SYSTEM_GET_PERFORMANCE_MONITORING)(
+                system,
+                &mut monitoring,
+            ) == ADLX_OK
+                && !monitoring.is_null())
+            .then_some(Owned(monitoring));
+
+            let count = method::<SizeFn>(list.0, LIST_SIZE)(list.0);
+            let mut gpus = Vec::new();
+            for index in 0..count {
+                let mut gpu = null_mut();
+                if method::<AtFn>(list.0, GPU_LIST_AT)(list.0, index, &mut gpu) != ADLX_OK
+                    || gpu.is_null()
+                {
+                    continue;
+                }
+                gpus.push(read_gpu(&Owned(gpu), monitoring.as_ref()));
+            }
+            Ok(gpus)
+        }
+    }
+
+    unsafe fn read_gpu(gpu: &Owned, monitoring: Option<&Owned>) -> GpuInfo {
+        let mut name: *const c_char = std::ptr::null();
+        let read = method::<ValueFn<*const c_char>>(gpu.0, GPU_NAME)(gpu.0, &mut name);
+        let name = if read == ADLX_OK && !name.is_null() {
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        } else {
+            "AMD GPU".to_string()
+        };
+        let memory_total = gpu.value::<u32>(GPU_TOTAL_VRAM).unwrap_or(0) as u64 * 1024 * 1024;
+
+        let metrics = monitoring.and_then(|monitoring| {
+            let mut metrics = null_mut();
+            let result = method::<MetricsFn>(monitoring.0, MONITORING_CURRENT_GPU_METRICS)(
+                monitoring.0,
+                gpu.0,
+                &mut metrics,
+            );
+            (result == ADLX_OK && !metrics.is_null()).then_some(Owned(metrics))
+        });
+        let float = |slot| {
+            metrics
+                .as_ref()
+                .and_then(|metrics| metrics.value::<f64>(slot))
+                .map(|value| value as f32)
+        };
+        let int = |slot| {
+            metrics
+                .as_ref()
+                .and_then(|metrics| metrics.value::<i32>(slot))
+                .filter(|value| *value >= 0)
+                .map(|value| value as u32)
+        };
+
+        let utilization = float(METRICS_USAGE).unwrap_or(0.0);
+        let memory_used = (int(METRICS_VRAM).unwrap_or(0) as u64 * 1024 * 1024).min(memory_total);
+        let fans: Vec<GpuFanInfo> = int(METRICS_FAN_SPEED)
+            .map(|rpm| GpuFanInfo {
+                index: 0,
+                speed_percent: None,
+                rpm: Some(rpm),
+                mode: None,
+            })
+            .into_iter()
+            .collect();
+        let fans_stopped = gpu_tuning::fans_stopped(&fans);
+        GpuInfo {
+            name,
+            vendor: "AMD".to_string(),
+            utilization,
+            memory_used,
+            memory_total,
+            memory_usage_percentage: if memory_total > 0 {
+                (memory_used as f32 / memory_total as f32) * 100.0
+            } else {
+                0.0
+            },
+            temperature: float(METRICS_TEMPERATURE),
+            hotspot_temperature: float(METRICS_HOTSPOT_TEMPERATURE),
+            zero_rpm_enabled: fans_stopped.then_some(true),
+            fans,
+            fans_stopped,
+            power_usage: float(METRICS_BOARD_POWER).or_else(|| float(METRICS_POWER)),
+            clock_speed: int(METRICS_CLOCK),
+            memory_clock: int(METRICS_VRAM_CLOCK),
+            is_amd: true,
+            ..Default::default()
+        }
+    }
+
+    struct AdlxState {
+        context: Option<Context>,
+        tried_at: Option<Instant>,
+    }
+
+    static ADLX: Mutex<AdlxState> = Mutex::new(AdlxState {
+        context: None,
+        tried_at: None,
+    });
+
+    /// The AMD cards ADLX sees, none without an AMD driver
+    pub(super) fn read() -> Result<Vec<GpuInfo>> {
+        let mut state = ADLX.lock().map_err(|e| anyhow!(e.to_string()))?;
+        let retry = state
+            .tried_at
+            .is_none_or(|tried_at| tried_at.elapsed() >= LIBRARY_RETRY);
+        if state.context.is_none() && retry {
+            state.context = Context::load().ok();
+            state.tried_at = Some(Instant::now());
+        }
+        match state.context.as_ref() {
+            // Safety: ADLX is initialized and only used under the lock
+            Some(context) => unsafe { context.gpus() },
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// The amdgpu driver labels its sensors edge, junction (the hotspot) and
+/// mem, in millidegrees
+#[cfg(target_os = "linux")]
+fn hwmon_temperatures(device: &std::path::Path) -> AmdTemperatures {
+    use std::fs;
+    let mut temps = AmdTemperatures::default();
+    let Ok(hwmons) = fs::read_dir(device.join("hwmon")) else {
+        return temps;
+    };
+    for hwmon in hwmons.flatten() {
+        let dir = hwmon.path();
+        for index in 1..=8 {
+            let label_path = dir.join(format!("temp{}_label", index));
+            let Ok(label) = fs::read_to_string(label_path) else {
+                continue;
+            };
+            let celsius = fs::read_to_string(dir.join(format!("temp{}_input", index)))
+                .ok()
+                .and_then(|value| value.trim().parse::<f32>().ok())
+                .map(|millidegrees| millidegrees / 1000.0);
+            temps.set(label.trim(), celsius);
+        }
+    }
+    temps
+}
+
+/// The link as the PCI core reports it for the device
+#[cfg(target_os = "linux")]
+fn sysfs_pcie_link(device: &std::path::Path) -> Option<PcieLink> {
+    let read = |name: &str| std::fs::read_to_string(device.join(name)).ok();
+    let width = |name: &str| read(name).and_then(|value| value.trim().parse().ok());
+    let gen = |name: &str| read(name).and_then(|value| pcie_gen(&value));
+    pcie_link(
+        gen("current_link_speed"),
+        width("current_link_width"),
+        gen("max_link_speed"),
+        width("max_link_width"),
+        0.0,
+    )
+}
+
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(all(not(target_os = "linux"), not(test)), allow(dead_code))]
+struct AmdTemperatures {
+    edge: Option<f32>,
+    junction: Option<f32>,
+    mem: Option<f32>,
+}
+
+#[cfg_attr(all(not(target_os = "linux"), not(test)), allow(dead_code))]
+impl AmdTemperatures {
+    fn set(&mut self, label: &str, celsius: Option<f32>) {
+        match label {
+            "edge" => self.edge = celsius,
+            "junction" => self.junction = celsius,
+            "mem" => self.mem = celsius,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amd_sensor_labels() {
+        let mut temps = AmdTemperatures::default();
+        temps.set("edge", Some(61.0));
+        temps.set("junction", Some(78.0));
+        temps.set("mem", Some(84.0));
+        temps.set("PPT", Some(120.0));
+        assert_eq!(
+            temps,
+            AmdTemperatures {
+                edge: Some(61.0),
+                junction: Some(78.0),
+                mem: Some(84.0),
+            }
+        );
+    }
+}
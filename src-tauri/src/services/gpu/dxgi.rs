@@ -0,0 +1,157 @@
+// Every adapter Windows lists, the fallback for cards no vendor library
+// covered. DXGI only knows the name and the memory sizes; the memory in use
+// comes from the "GPU Adapter Memory" performance counters.
+use super::GpuBackend;
+use crate::models::gpu_info::GpuInfo;
+use anyhow::Result;
+
+pub(super) struct Dxgi;
+
+impl GpuBackend for Dxgi {
+    #[cfg(target_os = "windows")]
+    fn read(&self) -> Result<Vec<GpuInfo>> {
+        use crate::services::gpu_processes;
+
+        let adapters = super::monitor::dxgi_adapters().map_err(anyhow::Error::msg)?;
+        let memory_usage = gpu_processes::adapter_memory_usage().unwrap_or_default();
+        let mut gpus = Vec::new();
+        for adapter in adapters {
+            let name = adapter.name;
+            let name_lower = name.to_lowercase();
+
+            // Only skip pure software/virtual adapters that are clearly not hardware
+            // Be very conservative - only exclude obvious software-only adapters
+            if name_lower.contains("microsoft basic render driver")
+                || name_lower.contains("microsoft basic display adapter")
+                || name_lower.contains("remote desktop")
+                || name_lower.contains("teamviewer")
+                || name_lower.contains("vnc")
+                || name_lower.contains("parsec")
+                || name_lower.contains("citrix")
+                || (name_lower.contains("virtual")
+                    && !name_lower.contains("amd")
+                    && !name_lower.contains("nvidia")
+                    && !name_lower.contains("intel"))
+                || name_lower == "software"
+            {
+                continue;
+            }
+
+            let vendor =
+                vendor_from_id(adapter.vendor_id).unwrap_or_else(|| determine_vendor(&name));
+
+            let dedicated_memory = adapter.dedicated_memory;
+            let shared_memory = adapter.shared_memory;
+
+            // Calculate total available memory for all types of GPUs
+            let memory_total = if dedicated_memory > 0 {
+                // Discrete GPU: use dedicated memory
+                dedicated_memory
+            } else if shared_memory > 0 {
+                // Integrated GPU: calculate realistic usable VRAM
+                // Most integrated GPUs can use 512MB to 2GB+ depending on system
+                let base_memory = 512 * 1024 * 1024; // 512MB base
+                let additional_memory = shared_memory / 16; // Small fraction of shared memory
+                let estimated_vram = base_memory + additional_memory;
+                std::cmp::min(estimated_vram, 8 * 1024 * 1024 * 1024) // Cap at 8GB
+            } else {
+                // Fallback for any edge cases
+                512 * 1024 * 1024 // 512MB fallback
+            };
+            // Include ALL GPU hardware - discrete and integrated
+            // Only exclude if it's clearly a software-only adapter
+            let is_known_vendor = vendor != "Unknown";
+            let has_graphics_in_name = name_lower.contains("graphics")
+                || name_lower.contains("display")
+                || name_lower.contains("video")
+                || name_lower.contains("gpu");
+            if !is_known_vendor && !has_graphics_in_name && memory_total < 128 * 1024 * 1024 {
+                continue;
+            }
+
+            let memory_used = memory_usage
+                .get(&adapter.luid)
+                .copied()
+                .unwrap_or(0)
+                .min(memory_total);
+            let memory_usage_percentage = if memory_total > 0 {
+                (memory_used as f32 / memory_total as f32) * 100.0
+            } else {
+                0.0
+            };
+
+            gpus.push(GpuInfo {
+                name,
+                vendor: vendor.to_string(),
+                memory_used,
+                memory_total,
+                memory_usage_percentage,
+                is_nvidia: vendor == "NVIDIA",
+                is_amd: vendor == "AMD",
+                ..Default::default()
+            });
+        }
+        Ok(gpus)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn read(&self) -> Result<Vec<GpuInfo>> {
+        Ok(Vec::new())
+    }
+}
+
+/// PCI vendor IDs of the three GPU makers
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn vendor_from_id(vendor_id: u32) -> Option<&'static str> {
+    match vendor_id {
+        0x10de => Some("NVIDIA"),
+        0x1002 => Some("AMD"),
+        0x8086 => Some("Intel"),
+        _ => None,
+    }
+}
+
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn determine_vendor(gpu_name: &str) -> &'static str {
+    let name_lower = gpu_name.to_lowercase();
+
+    if name_lower.contains("nvidia")
+        || name_lower.contains("geforce")
+        || name_lower.contains("rtx")
+        || name_lower.contains("gtx")
+        || name_lower.contains("quadro")
+        || name_lower.contains("tesla")
+    {
+        "NVIDIA"
+    } else if name_lower.contains("amd")
+        || name_lower.contains("radeon")
+        || name_lower.contains("rx ")
+        || name_lower.contains("vega")
+        || name_lower.contains("navi")
+        || name_lower.contains("rdna")
+    {
+        "AMD"
+    } else if name_lower.contains("intel")
+        || name_lower.contains("iris")
+        || name_lower.contains("uhd")
+        || name_lower.contains("hd graphics")
+    {
+        "Intel"
+    } else {
+        "Unknown"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor() {
+        assert_eq!(vendor_from_id(0x10de), Some("NVIDIA"));
+        assert_eq!(vendor_from_id(0x1414), None); // Microsoft Basic Render Driver
+        assert_eq!(determine_vendor("AMD Radeon RX 7800 XT"), "AMD");
+        assert_eq!(determine_vendor("Intel(R) UHD Graphics 770"), "Intel");
+        assert_eq!(determine_vendor("Virtual Display"), "Unknown");
+    }
+}
@@ -0,0 +1,44 @@
+// Last resort when no backend found a card: the adapters wgpu can open, with
+// their names only. When even that fails a placeholder keeps the panel filled.
+use crate::models::gpu_info::GpuInfo;
+use wgpu::{Backends, DeviceType, Instance, InstanceDescriptor};
+
+pub(super) fn read() -> Vec<GpuInfo> {
+    let instance = Instance::new(&InstanceDescriptor {
+        backends: Backends::all(),
+        ..Default::default()
+    });
+
+    let mut gpus: Vec<GpuInfo> = Vec::new();
+    for adapter in instance.enumerate_adapters(Backends::all()) {
+        let info = adapter.get_info();
+        // Software rasterizers, and the same card seen through several APIs
+        if info.device_type == DeviceType::Cpu || gpus.iter().any(|gpu| gpu.name == info.name) {
+            continue;
+        }
+        let vendor = match info.vendor {
+            0x10de => "NVIDIA",
+            0x1002 => "AMD",
+            0x8086 => "Intel",
+            _ => "Unknown",
+        };
+        gpus.push(GpuInfo {
+            name: info.name,
+            vendor: vendor.to_string(),
+            driver_version: Some(info.driver_info).filter(|driver| !driver.is_empty()),
+            is_nvidia: vendor == "NVIDIA",
+            is_amd: vendor == "AMD",
+            ..Default::default()
+        });
+    }
+
+    if gpus.is_empty() {
+        gpus.push(GpuInfo {
+            name: "Unknown Display Adapter".to_string(),
+            memory_total: 2048 * 1024 * 1024, // 2GB fallback
+            driver_version: Some("Unknown".to_string()),
+            ..Default::default()
+        });
+    }
+    gpus
+}
@@ -0,0 +1,445 @@
+// Intel cards through IGCL, the control library Intel's driver installs on
+// Windows. Like ADLX it is loaded at runtime and kept for the whole run.
+// IGCL reports the engine busy time and the energy used as counters, so
+// utilization and power come from the change between two reads and are
+// missing on the first one.
+use super::monitor::{self, LIBRARY_RETRY};
+use super::GpuBackend;
+use crate::models::gpu_info::GpuInfo;
+use anyhow::{anyhow, Result};
+use libloading::Library;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr::null_mut;
+use std::sync::Mutex;
+use std::time::Instant;
+
+pub(super) struct Igcl;
+
+impl GpuBackend for Igcl {
+    fn read(&self) -> Result<Vec<GpuInfo>> {
+        let mut state = IGCL.lock().map_err(|e| anyhow!(e.to_string()))?;
+        let state = &mut *state;
+        let retry = state
+            .tried_at
+            .is_none_or(|tried_at| tried_at.elapsed() >= LIBRARY_RETRY);
+        if state.context.is_none() && retry {
+            state.context = Context::load().ok();
+            state.tried_at = Some(Instant::now());
+        }
+        match state.context.as_ref() {
+            // Safety: IGCL is initialized and only used under the lock
+            Some(context) => unsafe { context.gpus(&mut state.counters) },
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+const CTL_RESULT_SUCCESS: u32 = 0;
+// CTL_MAKE_VERSION(1, 1), the interface the structures below follow
+const CTL_IMPL_VERSION: u32 = (1 << 16) | 1;
+const CTL_INIT_FLAG_USE_LEVEL_ZERO: u32 = 1;
+const CTL_DEVICE_TYPE_GRAPHICS: u32 = 1;
+const CTL_TEMP_SENSORS_GPU: u32 = 1;
+const CTL_TEMP_SENSORS_MEMORY: u32 = 2;
+const CTL_FREQ_DOMAIN_GPU: u32 = 0;
+const CTL_FREQ_DOMAIN_MEMORY: u32 = 1;
+// Every engine of the card together
+const CTL_ENGINE_GROUP_GT: u32 = 0;
+
+type Handle = *mut c_void;
+type InitFn = unsafe extern "C" fn(*mut InitArgs, *mut Handle) -> u32;
+type CloseFn = unsafe extern "C" fn(Handle) -> u32;
+// Lists the handles of a kind: asked once for the count, then to fill them
+type EnumFn = unsafe extern "C" fn(Handle, *mut u32, *mut Handle) -> u32;
+type GetFn<T> = unsafe extern "C" fn(Handle, *mut T) -> u32;
+
+// The structures of igcl_api.h. Each starts with its own size and version,
+// which IGCL checks before filling it.
+#[repr(C)]
+struct InitArgs {
+    size: u32,
+    version: u8,
+    app_version: u32,
+    flags: u32,
+    supported_version: u32,
+    application_uid: [u32; 4],
+}
+
+#[repr(C)]
+struct AdapterProperties {
+    size: u32,
+    version: u8,
+    device_id: *mut c_void, // the LUID on Windows
+    device_id_size: u32,
+    device_type: u32,
+    supported_subfunction_flags: u32,
+    driver_version: u64,
+    firmware_version: [u64; 3],
+    pci_vendor_id: u32,
+    pci_device_id: u32,
+    rev_id: u32,
+    num_eus_per_sub_slice: u32,
+    num_sub_slices_per_slice: u32,
+    num_slices: u32,
+    name: [c_char; 100],
+    graphics_adapter_properties: u32,
+    frequency: u32,
+    pci_subsys_id: u16,
+    pci_subsys_vendor_id: u16,
+    adapter_bdf: [u8; 3],
+    reserved: [c_char; 112],
+}
+
+#[repr(C)]
+struct MemoryState {
+    size: u32,
+    version: u8,
+    free: u64,  // bytes
+    total: u64, // bytes
+}
+
+#[repr(C)]
+struct TemperatureProperties {
+    size: u32,
+    version: u8,
+    sensor: u32,
+    max_temperature: f64,
+}
+
+#[repr(C)]
+struct FrequencyProperties {
+    size: u32,
+    version: u8,
+    domain: u32,
+    can_control: bool,
+    min: f64,
+    max: f64,
+}
+
+#[repr(C)]
+struct FrequencyState {
+    size: u32,
+    version: u8,
+    current_voltage: f64,
+    request: f64,
+    tdp: f64,
+    efficient: f64,
+    actual: f64, // MHz
+    throttle_reasons: u32,
+}
+
+#[repr(C)]
+struct EngineProperties {
+    size: u32,
+    version: u8,
+    group: u32,
+}
+
+/// Both counters IGCL reports, the busy time of an engine and the energy of
+/// a power domain, come with a timestamp in µs
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Counter {
+    size: u32,
+    version: u8,
+    value: u64, // µs busy or µJ used
+    timestamp: u64,
+}
+
+/// A structure with only its size filled, as IGCL expects it
+fn sized<T>() -> T {
+    // Safety: only used for the plain C structures above, all zero is valid
+    let mut value: T = unsafe { std::mem::zeroed() };
+    unsafe { *(&mut value as *mut T as *mut u32) = std::mem::size_of::<T>() as u32 };
+    value
+}
+
+struct Functions {
+    enumerate_devices: EnumFn,
+    device_properties: GetFn<AdapterProperties>,
+    enum_memory_modules: EnumFn,
+    memory_state: GetFn<MemoryState>,
+    enum_temperature_sensors: EnumFn,
+    temperature_properties: GetFn<TemperatureProperties>,
+    temperature_state: GetFn<f64>,
+    enum_frequency_domains: EnumFn,
+    frequency_properties: GetFn<FrequencyProperties>,
+    frequency_state: GetFn<FrequencyState>,
+    enum_engine_groups: EnumFn,
+    engine_properties: GetFn<EngineProperties>,
+    engine_activity: GetFn<Counter>,
+    enum_power_domains: EnumFn,
+    energy_counter: GetFn<Counter>,
+}
+
+struct Context {
+    api: Handle,
+    close: CloseFn,
+    functions: Functions,
+    _library: Library,
+}
+
+// Safety: the API handle is only used under the IGCL mutex
+unsafe impl Send for Context {}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        // Safety: no device handle outlives the API handle
+        unsafe {
+            (self.close)(self.api);
+        }
+    }
+}
+
+impl Context {
+    fn load() -> Result<Self> {
+        let library = monitor::driver_library("ControlLib.dll")?;
+        unsafe {
+            let init = *library.get::<InitFn>(b"ctlInit\0")?;
+            let close = *library.get::<CloseFn>(b"ctlClose\0")?;
+            let functions = Functions {
+                enumerate_devices: *library.get(b"ctlEnumerateDevices\0")?,
+                device_properties: *library.get(b"ctlGetDeviceProperties\0")?,
+                enum_memory_modules: *library.get(b"ctlEnumMemoryModules\0")?,
+                memory_state: *library.get(b"ctlMemoryGetState\0")?,
+                enum_temperature_sensors: *library.get(b"ctlEnumTemperatureSensors\0")?,
+                temperature_properties: *library.get(b"ctlTemperatureGetProperties\0")?,
+                temperature_state: *library.get(b"ctlTemperatureGetState\0")?,
+                enum_frequency_domains: *library.get(b"ctlEnumFrequencyDomains\0")?,
+                frequency_properties: *library.get(b"ctlFrequencyGetProperties\0")?,
+                frequency_state: *library.get(b"ctlFrequencyGetState\0")?,
+                enum_engine_groups: *library.get(b"ctlEnumEngineGroups\0")?,
+                engine_properties: *library.get(b"ctlEngineGetProperties\0")?,
+                engine_activity: *library.get(b"ctlEngineGetActivity\0")?,
+                enum_power_domains: *library.get(b"ctlEnumPowerDomains\0")?,
+                energy_counter: *library.get(b"ctlPowerGetEnergyCounter\0")?,
+            };
+
+            // Level Zero is what the telemetry calls go through
+            let mut args: InitArgs = sized();
+            args.app_version = CTL_IMPL_VERSION;
+            args.flags = CTL_INIT_FLAG_USE_LEVEL_ZERO;
+            let mut api = null_mut();
+            if init(&mut args, &mut api) != CTL_RESULT_SUCCESS || api.is_null() {
+                return Err(anyhow!("IGCL failed to initialize"));
+            }
+            Ok(Self {
+                api,
+                close,
+                functions,
+                _library: library,
+            })
+        }
+    }
+
+    unsafe fn gpus(&self, counters: &mut Counters) -> Result<Vec<GpuInfo>> {
+        let f = &self.functions;
+        let mut gpus = Vec::new();
+        for device in handles(f.enumerate_devices, self.api) {
+            let mut luid = 0u64;
+            let mut properties: AdapterProperties = sized();
+            properties.device_id = &mut luid as *mut u64 as *mut c_void;
+            properties.device_id_size = std::mem::size_of::<u64>() as u32;
+            if (f.device_properties)(device, &mut properties) != CTL_RESULT_SUCCESS
+                || properties.device_type != CTL_DEVICE_TYPE_GRAPHICS
+            {
+                continue;
+            }
+            gpus.push(self.read_gpu(device, luid, &properties, counters));
+        }
+        Ok(gpus)
+    }
+
+    unsafe fn read_gpu(
+        &self,
+        device: Handle,
+        luid: u64,
+        properties: &AdapterProperties,
+        counters: &mut Counters,
+    ) -> GpuInfo {
+        let f = &self.functions;
+        let name = CStr::from_ptr(properties.name.as_ptr())
+            .to_string_lossy()
+            .trim()
+            .to_string();
+
+        let (mut memory_used, mut memory_total) = (0, 0);
+        for module in handles(f.enum_memory_modules, device) {
+            let mut state: MemoryState = sized();
+            if (f.memory_state)(module, &mut state) == CTL_RESULT_SUCCESS {
+                memory_total += state.total;
+                memory_used += state.total.saturating_sub(state.free);
+            }
+        }
+
+        let (mut temperature, mut memory_temperature) = (None, None);
+        for sensor in handles(f.enum_temperature_sensors, device) {
+            let mut sensor_properties: TemperatureProperties = sized();
+            let mut celsius = 0.0;
+            if (f.temperature_properties)(sensor, &mut sensor_properties) != CTL_RESULT_SUCCESS
+                || (f.temperature_state)(sensor, &mut celsius) != CTL_RESULT_SUCCESS
+            {
+                continue;
+            }
+            match sensor_properties.sensor {
+                CTL_TEMP_SENSORS_GPU => temperature = Some(celsius as f32),
+                CTL_TEMP_SENSORS_MEMORY => memory_temperature = Some(celsius as f32),
+                _ => {}
+            }
+        }
+
+        let (mut clock_speed, mut memory_clock) = (None, None);
+        for domain in handles(f.enum_frequency_domains, device) {
+            let mut domain_properties: FrequencyProperties = sized();
+            let mut state: FrequencyState = sized();
+            if (f.frequency_properties)(domain, &mut domain_properties) != CTL_RESULT_SUCCESS
+                || (f.frequency_state)(domain, &mut state) != CTL_RESULT_SUCCESS
+                || state.actual <= 0.0
+            {
+                continue;
+            }
+            match domain_properties.domain {
+                CTL_FREQ_DOMAIN_GPU => clock_speed = Some(state.actual as u32),
+                CTL_FREQ_DOMAIN_MEMORY => memory_clock = Some(state.actual as u32),
+                _ => {}
+            }
+        }
+
+        let busy = handles(f.enum_engine_groups, device)
+            .into_iter()
+            .find(|engine| {
+                let mut engine_properties: EngineProperties = sized();
+                (f.engine_properties)(*engine, &mut engine_properties) == CTL_RESULT_SUCCESS
+                    && engine_properties.group == CTL_ENGINE_GROUP_GT
+            })
+            .and_then(|engine| read_counter(f.engine_activity, engine));
+        // µs busy per µs elapsed
+        let utilization = busy
+            .and_then(|busy| counters.rate(luid, CounterKind::Busy, busy))
+            .map(|share| (share * 100.0).clamp(0.0, 100.0) as f32)
+            .unwrap_or(0.0);
+
+        // The first domain is the whole card
+        let energy = handles(f.enum_power_domains, device)
+            .first()
+            .and_then(|domain| read_counter(f.energy_counter, *domain));
+        // µJ per µs is W
+        let power_usage = energy
+            .and_then(|energy| counters.rate(luid, CounterKind::Energy, energy))
+            .map(|watts| watts as f32);
+
+        GpuInfo {
+            name,
+            vendor: "Intel".to_string(),
+            utilization,
+            memory_used,
+            memory_total,
+            memory_usage_percentage: if memory_total > 0 {
+                (memory_used as f32 / memory_total as f32) * 100.0
+            } else {
+                0.0
+            },
+            temperature,
+            memory_temperature,
+            power_usage,
+            clock_speed,
+            memory_clock,
+            driver_version: Some(driver_version(properties.driver_version)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Every handle an enumeration function lists under `parent`
+unsafe fn handles(enumerate: EnumFn, parent: Handle) -> Vec<Handle> {
+    let mut count = 0;
+    if enumerate(parent, &mut count, null_mut()) != CTL_RESULT_SUCCESS || count == 0 {
+        return Vec::new();
+    }
+    let mut handles = vec![null_mut(); count as usize];
+    if enumerate(parent, &mut count, handles.as_mut_ptr()) != CTL_RESULT_SUCCESS {
+        return Vec::new();
+    }
+    handles.truncate(count as usize);
+    handles
+}
+
+unsafe fn read_counter(get: GetFn<Counter>, handle: Handle) -> Option<Counter> {
+    let mut counter: Counter = sized();
+    (get(handle, &mut counter) == CTL_RESULT_SUCCESS).then_some(counter)
+}
+
+/// The driver version packed as four 16-bit parts, e.g. 32.0.101.6129
+fn driver_version(packed: u64) -> String {
+    [48, 32, 16, 0]
+        .map(|shift| ((packed >> shift) & 0xffff).to_string())
+        .join(".")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum CounterKind {
+    Busy,
+    Energy,
+}
+
+/// The last reading of each counter, per card
+#[derive(Default)]
+struct Counters(HashMap<(u64, CounterKind), (u64, u64)>);
+
+impl Counters {
+    /// How fast the counter grew since the last read, per µs. None on the
+    /// first read or when the counter went back (a driver reset).
+    fn rate(&mut self, luid: u64, kind: CounterKind, counter: Counter) -> Option<f64> {
+        let now = (counter.value, counter.timestamp);
+        let (value, timestamp) = self.0.insert((luid, kind), now)?;
+        let elapsed = now
+            .1
+            .checked_sub(timestamp)
+            .filter(|elapsed| *elapsed > 0)?;
+        let grown = now.0.checked_sub(value)?;
+        Some(grown as f64 / elapsed as f64)
+    }
+}
+
+struct IgclState {
+    context: Option<Context>,
+    tried_at: Option<Instant>,
+    counters: Counters,
+}
+
+static IGCL: once_cell::sync::Lazy<Mutex<IgclState>> = once_cell::sync::Lazy::new(|| {
+    Mutex::new(IgclState {
+        context: None,
+        tried_at: None,
+        counters: Counters::default(),
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_rate() {
+        let counter = |value, timestamp| Counter {
+            size: 0,
+            version: 0,
+            value,
+            timestamp,
+        };
+        let mut counters = Counters::default();
+        assert_eq!(counters.rate(1, CounterKind::Busy, counter(0, 1_000)), None);
+        assert_eq!(
+            counters.rate(1, CounterKind::Busy, counter(500, 2_000)),
+            Some(0.5)
+        );
+        // Another card, and a counter that was reset
+        assert_eq!(counters.rate(2, CounterKind::Busy, counter(0, 1_000)), None);
+        assert_eq!(
+            counters.rate(1, CounterKind::Busy, counter(10, 3_000)),
+            None
+        );
+        assert_eq!(driver_version(0x0020_0000_0065_17f1), "32.0.101.6129");
+    }
+}
@@ -0,0 +1,179 @@
+//! Every GPU Aura can read, behind one `GpuBackend` trait. Backends are
+//! probed in order of how much they know: NVML for NVIDIA cards, ADLX for AMD
+//! (the amdgpu driver on Linux), IGCL for Intel, then DXGI for the adapters
+//! none of them covered, and a plain wgpu listing when nothing else answers.
+//! The GPU panel, the REST API, the temperature sampler and the session
+//! report all read the same stats.
+
+mod amd;
+mod dxgi;
+mod generic;
+#[cfg(target_os = "windows")]
+mod intel;
+mod monitor;
+mod nvidia;
+
+use crate::models::gpu_info::{GpuInfo, GpuStats, PcieLink};
+use crate::shared::i18n;
+use crate::utils::bytes::format_bytes_in;
+use crate::utils::temperature::{convert_temperature, temperature_unit};
+use anyhow::Result;
+
+trait GpuBackend {
+    /// The cards this backend sees, with temperatures in °C. A backend
+    /// that isn't available returns none.
+    fn read(&self) -> Result<Vec<GpuInfo>>;
+}
+
+fn backends() -> Vec<Box<dyn GpuBackend>> {
+    vec![
+        Box::new(nvidia::Nvml),
+        #[cfg(target_os = "windows")]
+        Box::new(amd::Adlx),
+        #[cfg(target_os = "linux")]
+        Box::new(amd::Amdgpu),
+        #[cfg(target_os = "windows")]
+        Box::new(intel::Igcl),
+        Box::new(dxgi::Dxgi),
+    ]
+}
+
+/// Stats of every GPU, shared by all callers for a moment (see monitor)
+pub fn get_gpu_stats() -> std::result::Result<GpuStats, String> {
+    monitor::cached_stats(query)
+}
+
+fn query() -> std::result::Result<GpuStats, String> {
+    let mut gpus: Vec<GpuInfo> = Vec::new();
+    for backend in backends() {
+        // A failing backend is skipped, the next ones may still see the card
+        let Ok(found) = backend.read() else {
+            continue;
+        };
+        // Vendors a better backend already read are left to it
+        let covered: Vec<String> = gpus.iter().map(|gpu| gpu.vendor.clone()).collect();
+        gpus.extend(
+            found
+                .into_iter()
+                .filter(|gpu| !covered.contains(&gpu.vendor)),
+        );
+    }
+    if gpus.is_empty() {
+        gpus = generic::read();
+    }
+
+    let total_vram = gpus.iter().map(|g| g.memory_total).sum();
+    let total_vram_used = gpus.iter().map(|g| g.memory_used).sum();
+    let average_utilization = if !gpus.is_empty() {
+        gpus.iter().map(|g| g.utilization).sum::<f32>() / gpus.len() as f32
+    } else {
+        0.0
+    };
+    for gpu in &mut gpus {
+        gpu.temperature = gpu.temperature.map(convert_temperature);
+        gpu.memory_temperature = gpu.memory_temperature.map(convert_temperature);
+        gpu.hotspot_temperature = gpu.hotspot_temperature.map(convert_temperature);
+    }
+    Ok(GpuStats {
+        gpus,
+        total_vram_used,
+        total_vram,
+        average_utilization,
+        vram_display: format!(
+            "{} / {}",
+            format_bytes_in(total_vram_used, 3, 1),
+            format_bytes_in(total_vram, 3, 1)
+        ),
+        temperature_unit: temperature_unit(),
+    })
+}
+
+fn pcie_link(
+    current_gen: Option<u32>,
+    current_width: Option<u32>,
+    max_gen: Option<u32>,
+    max_width: Option<u32>,
+    utilization: f32,
+) -> Option<PcieLink> {
+    if current_gen.is_none() && current_width.is_none() {
+        return None;
+    }
+    let mut link = PcieLink {
+        current_gen,
+        current_width,
+        max_gen,
+        max_width,
+        warning: None,
+    };
+    link.warning = pcie_warning(&link, utilization);
+    Some(link)
+}
+
+/// A GPU on four lanes or less sits in a chipset slot or isn't seated
+/// properly. Gen1 is normal while idle, under load it points at a BIOS
+/// setting or a bad riser.
+fn pcie_warning(link: &PcieLink, utilization: f32) -> Option<String> {
+    match (link.current_width, link.max_width) {
+        (Some(width), Some(max_width)) if width <= 4 && max_width > width => {
+            return Some(i18n::text_with(
+                "gpu.pcie_narrow_link",
+                "Running at x{width} instead of x{max_width}: check that the card sits \
+                 in the main slot and is fully seated",
+                &[
+                    ("width", &width.to_string()),
+                    ("max_width", &max_width.to_string()),
+                ],
+            ));
+        }
+        _ => {}
+    }
+    match (link.current_gen, link.max_gen) {
+        (Some(1), Some(max_gen)) if max_gen > 1 && utilization >= 50.0 => Some(i18n::text_with(
+            "gpu.pcie_gen1_under_load",
+            "Running at PCIe Gen1 under load instead of Gen{max_gen}: check the \
+             slot's PCIe setting in the BIOS and any riser cable",
+            &[("max_gen", &max_gen.to_string())],
+        )),
+        _ => None,
+    }
+}
+
+/// Generation of a link speed as sysfs writes it, e.g. "16.0 GT/s PCIe"
+#[cfg_attr(all(not(target_os = "linux"), not(test)), allow(dead_code))]
+fn pcie_gen(speed: &str) -> Option<u32> {
+    let gts: f32 = speed.split_whitespace().next()?.parse().ok()?;
+    match gts {
+        gts if gts < 3.0 => Some(1),
+        gts if gts < 6.0 => Some(2),
+        gts if gts < 10.0 => Some(3),
+        gts if gts < 20.0 => Some(4),
+        gts if gts < 40.0 => Some(5),
+        _ => Some(6),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcie_link() {
+        assert_eq!(pcie_gen("16.0 GT/s PCIe"), Some(4));
+        assert_eq!(pcie_gen("2.5 GT/s PCIe"), Some(1));
+        assert_eq!(pcie_gen("Unknown"), None);
+
+        let link = |current_gen, current_width, utilization| {
+            let (max_gen, max_width) = (Some(4), Some(16));
+            pcie_link(current_gen, current_width, max_gen, max_width, utilization).unwrap()
+        };
+        assert_eq!(link(Some(4), Some(16), 90.0).warning, None);
+        assert_eq!(link(Some(4), Some(8), 90.0).warning, None);
+        let narrow = link(Some(4), Some(4), 0.0).warning.unwrap();
+        assert!(narrow.contains("x4 instead of x16"));
+        // Power saving while idle
+        assert_eq!(link(Some(1), Some(16), 5.0).warning, None);
+        let slow = link(Some(1), Some(16), 90.0).warning.unwrap();
+        assert!(slow.contains("Gen1"));
+        assert_eq!(pcie_link(None, None, Some(4), Some(16), 0.0), None);
+    }
+}
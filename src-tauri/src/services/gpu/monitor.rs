@@ -0,0 +1,149 @@
+// Driver contexts shared by every GPU query. NVML is loaded once and kept for
+// the whole run instead of on every call; when it isn't there (no NVIDIA
+// driver) loading is retried only now and then. ADLX and IGCL are kept the
+// same way by their backends. The DXGI factory and the
+// adapters it lists are kept too, and enumerated again only once the factory
+// reports that the set of adapters changed (a driver update, an eGPU) or the
+// list got old. The full stats are cached for a moment, so the panel, the
+// REST API and the remote monitor polling together cost a single query.
+use crate::models::gpu_info::GpuStats;
+use nvml_wrapper::Nvml;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// How long a failed driver library load is remembered
+pub(super) const LIBRARY_RETRY: Duration = Duration::from_secs(5 * 60);
+// Refresh of the adapter list, on top of the factory's own staleness check
+#[cfg(target_os = "windows")]
+const ADAPTERS_MAX_AGE: Duration = Duration::from_secs(60);
+// Callers asking within this window share one query
+const STATS_TTL: Duration = Duration::from_secs(1);
+
+/// What DXGI tells about an adapter, read once per enumeration
+#[cfg(target_os = "windows")]
+#[derive(Debug, Clone)]
+pub struct DxgiAdapter {
+    pub name: String,
+    pub vendor_id: u32,
+    pub luid: (u32, u32), // high and low part, as in the performance counter names
+    pub dedicated_memory: u64, // bytes
+    pub shared_memory: u64, // bytes
+}
+
+struct NvmlState {
+    nvml: Option<Nvml>,
+    tried_at: Option<Instant>,
+}
+
+static NVML: Mutex<NvmlState> = Mutex::new(NvmlState {
+    nvml: None,
+    tried_at: None,
+});
+
+static STATS: Mutex<Option<(Instant, GpuStats)>> = Mutex::new(None);
+
+/// Runs `f` with the shared NVML context. None when NVML can't be loaded.
+pub fn with_nvml<R>(f: impl FnOnce(&Nvml) -> R) -> Option<R> {
+    let mut state = NVML.lock().ok()?;
+    let retry = state
+        .tried_at
+        .is_none_or(|tried_at| tried_at.elapsed() >= LIBRARY_RETRY);
+    if state.nvml.is_none() && retry {
+        state.nvml = Nvml::init().ok();
+        state.tried_at = Some(Instant::now());
+    }
+    state.nvml.as_ref().map(f)
+}
+
+/// The last stats when they are recent enough, otherwise the ones `query`
+/// returns. Failures are not cached.
+pub fn cached_stats(query: impl FnOnce() -> Result<GpuStats, String>) -> Result<GpuStats, String> {
+    if let Ok(cached) = STATS.lock() {
+        if let Some((at, stats)) = cached.as_ref() {
+            if at.elapsed() < STATS_TTL {
+                return Ok(stats.clone());
+            }
+        }
+    }
+    let stats = query()?;
+    if let Ok(mut cached) = STATS.lock() {
+        *cached = Some((Instant::now(), stats.clone()));
+    }
+    Ok(stats)
+}
+
+/// Loads a library the graphics driver installs, from System32 only so a
+/// copy dropped next to Aura is never picked up
+#[cfg(target_os = "windows")]
+pub(super) fn driver_library(name: &str) -> Result<libloading::Library, libloading::Error> {
+    use libloading::os::windows::{Library, LOAD_LIBRARY_SEARCH_SYSTEM32};
+
+    // Safety: the driver's own libraries only run their initializers here
+    unsafe { Library::load_with_flags(name, LOAD_LIBRARY_SEARCH_SYSTEM32) }.map(Into::into)
+}
+
+#[cfg(target_os = "windows")]
+pub fn dxgi_adapters() -> Result<Vec<DxgiAdapter>, String> {
+    platform::dxgi_adapters()
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{DxgiAdapter, ADAPTERS_MAX_AGE};
+    use std::sync::Mutex;
+    use std::time::Instant;
+    use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIFactory1};
+
+    struct DxgiState {
+        factory: IDXGIFactory1,
+        adapters: Vec<DxgiAdapter>,
+        listed_at: Instant,
+    }
+
+    static DXGI: Mutex<Option<DxgiState>> = Mutex::new(None);
+
+    pub fn dxgi_adapters() -> Result<Vec<DxgiAdapter>, String> {
+        let mut state = DXGI.lock().map_err(|e| e.to_string())?;
+        let fresh = state.as_ref().is_some_and(|state| unsafe {
+            state.factory.IsCurrent().as_bool() && state.listed_at.elapsed() < ADAPTERS_MAX_AGE
+        });
+        if !fresh {
+            // A factory that is no longer current keeps listing the old
+            // adapters, a new one is needed
+            let factory: IDXGIFactory1 = unsafe { CreateDXGIFactory1() }
+                .map_err(|e| format!("Failed to create DXGI factory: {:?}", e))?;
+            let adapters = enumerate(&factory);
+            *state = Some(DxgiState {
+                factory,
+                adapters,
+                listed_at: Instant::now(),
+            });
+        }
+        Ok(state
+            .as_ref()
+            .map(|state| state.adapters.clone())
+            .unwrap_or_default())
+    }
+
+    fn enumerate(factory: &IDXGIFactory1) -> Vec<DxgiAdapter> {
+        let mut adapters = Vec::new();
+        let mut index = 0;
+        // Fails with DXGI_ERROR_NOT_FOUND past the last adapter
+        while let Ok(adapter) = unsafe { factory.EnumAdapters1(index) } {
+            index += 1;
+            let Ok(desc) = (unsafe { adapter.GetDesc1() }) else {
+                continue;
+            };
+            adapters.push(DxgiAdapter {
+                name: String::from_utf16_lossy(&desc.Description)
+                    .trim_end_matches('\0')
+                    .to_string(),
+                vendor_id: desc.VendorId,
+                luid: (desc.AdapterLuid.HighPart as u32, desc.AdapterLuid.LowPart),
+                dedicated_memory: desc.DedicatedVideoMemory as u64,
+                shared_memory: desc.SharedSystemMemory as u64,
+            });
+        }
+        adapters
+    }
+}
@@ -0,0 +1,227 @@
+// NVIDIA cards through NVML, which comes with the driver on Windows and
+// Linux. When the library can't be loaded nvidia-smi is asked instead, which
+// knows less but still more than DXGI.
+use super::monitor;
+use super::{pcie_link, GpuBackend};
+use crate::models::gpu_info::GpuInfo;
+use crate::services::gpu_tuning;
+use anyhow::{anyhow, Result};
+use nvml_wrapper::enum_wrappers::device::{Clock, TemperatureSensor};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+pub(super) struct Nvml;
+
+impl GpuBackend for Nvml {
+    fn read(&self) -> Result<Vec<GpuInfo>> {
+        match monitor::with_nvml(read_devices) {
+            Some(gpus) => gpus,
+            // Without nvidia-smi either there is no NVIDIA driver at all
+            None => Ok(read_nvidia_smi().unwrap_or_default()),
+        }
+    }
+}
+
+fn read_devices(nvml: &nvml_wrapper::Nvml) -> Result<Vec<GpuInfo>> {
+    let device_count = nvml.device_count()?;
+    let mut gpus = Vec::new();
+
+    for i in 0..device_count {
+        let device = nvml.device_by_index(i)?;
+        let name = device.name()?;
+        let memory_info = device.memory_info()?;
+        let utilization = device.utilization_rates()?.gpu;
+        let temperature = device.temperature(TemperatureSensor::Gpu).ok();
+        // NVML has no hotspot sensor, and the memory one is only filled
+        // on cards that expose it
+        let memory_temperature = memory_temperature(&device);
+        let fans = gpu_tuning::nvidia_fans(&device);
+        let fans_stopped = gpu_tuning::fans_stopped(&fans);
+        let power_usage = device.power_usage().ok().map(|p| p as f32 / 1000.0);
+        let clock_speed = device.clock_info(Clock::Graphics).ok();
+        let memory_clock = device.clock_info(Clock::Memory).ok();
+        let driver_version = nvml.sys_driver_version().ok();
+        let pcie_link = pcie_link(
+            device.current_pcie_link_gen().ok(),
+            device.current_pcie_link_width().ok(),
+            device.max_pcie_link_gen().ok(),
+            device.max_pcie_link_width().ok(),
+            utilization as f32,
+        );
+
+        gpus.push(GpuInfo {
+            name,
+            vendor: "NVIDIA".to_string(),
+            utilization: utilization as f32,
+            memory_used: memory_info.used,
+            memory_total: memory_info.total,
+            memory_usage_percentage: (memory_info.used as f32 / memory_info.total as f32) * 100.0,
+            temperature: temperature.map(|t| t as f32),
+            memory_temperature,
+            hotspot_temperature: None,
+            // NVML can't tell whether the card stops its fans, only
+            // that they stand still right now
+            zero_rpm_enabled: fans_stopped.then_some(true),
+            fans,
+            fans_stopped,
+            pcie_link,
+            power_usage,
+            clock_speed,
+            memory_clock,
+            driver_version,
+            is_nvidia: true,
+            is_amd: false,
+        });
+    }
+
+    Ok(gpus)
+}
+
+fn memory_temperature(device: &nvml_wrapper::Device) -> Option<f32> {
+    use nvml_wrapper::enums::device::SampleValue;
+    use nvml_wrapper::structs::device::FieldId;
+    use nvml_wrapper::sys_exports::field_id::NVML_FI_DEV_MEMORY_TEMP;
+
+    let sample = device
+        .field_values_for(&[FieldId(NVML_FI_DEV_MEMORY_TEMP)])
+        .ok()?
+        .into_iter()
+        .next()?
+        .ok()?;
+    match sample.value.ok()? {
+        SampleValue::U32(t) if t > 0 => Some(t as f32),
+        SampleValue::U64(t) if t > 0 => Some(t as f32),
+        SampleValue::I64(t) if t > 0 => Some(t as f32),
+        SampleValue::F64(t) if t > 0.0 => Some(t as f32),
+        _ => None,
+    }
+}
+
+fn read_nvidia_smi() -> Result<Vec<GpuInfo>> {
+    let stdout = run_nvidia_smi(&[
+        &format!("--query-gpu={}", NVIDIA_SMI_FIELDS),
+        "--format=csv,noheader,nounits",
+    ])?;
+    Ok(parse_nvidia_smi(&stdout))
+}
+
+// In the order parse_nvidia_smi reads them
+const NVIDIA_SMI_FIELDS: &str = "name,memory.total,memory.used,temperature.gpu,utilization.gpu,\
+    power.draw,clocks.gr,clocks.mem,driver_version";
+// A driver stuck in a reset can leave nvidia-smi hanging
+const NVIDIA_SMI_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs nvidia-smi from the PATH or where the driver installs it, killing it
+/// when it doesn't answer in time
+fn run_nvidia_smi(args: &[&str]) -> Result<String> {
+    #[cfg(target_os = "windows")]
+    const CANDIDATES: &[&str] = &[
+        "nvidia-smi",
+        r"C:\Windows\System32\nvidia-smi.exe",
+        r"C:\Program Files\NVIDIA Corporation\NVSMI\nvidia-smi.exe",
+    ];
+    #[cfg(not(target_os = "windows"))]
+    const CANDIDATES: &[&str] = &[
+        "nvidia-smi",
+        "/usr/bin/nvidia-smi",
+        "/usr/lib/wsl/lib/nvidia-smi", // WSL
+    ];
+
+    let mut child = CANDIDATES
+        .iter()
+        .find_map(|program| {
+            let mut command = std::process::Command::new(program);
+            command
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null());
+            #[cfg(target_os = "windows")]
+            command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+            command.spawn().ok()
+        })
+        .ok_or_else(|| anyhow!("nvidia-smi not found"))?;
+
+    let deadline = Instant::now() + NVIDIA_SMI_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(50)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(anyhow!("nvidia-smi timed out"));
+            }
+            Err(e) => return Err(anyhow!("Failed to wait for nvidia-smi: {}", e)),
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| anyhow!("Failed to read nvidia-smi output: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow!("nvidia-smi command failed"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// One GPU per line of NVIDIA_SMI_FIELDS. Fields the card doesn't support
+/// read "[N/A]" or "[Not Supported]" and are left empty.
+fn parse_nvidia_smi(stdout: &str) -> Vec<GpuInfo> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+            if parts.len() < 9 {
+                return None;
+            }
+            let field = |index: usize| Some(parts[index]).filter(|value| !value.starts_with('['));
+            let float = |index: usize| field(index).and_then(|value| value.parse::<f32>().ok());
+            let memory_total_mb = float(1).unwrap_or(0.0) as u64;
+            let memory_used_mb = float(2).unwrap_or(0.0) as u64;
+
+            Some(GpuInfo {
+                name: parts[0].to_string(),
+                vendor: "NVIDIA".to_string(),
+                utilization: float(4).unwrap_or(0.0),
+                memory_used: memory_used_mb * 1024 * 1024,
+                memory_total: memory_total_mb * 1024 * 1024,
+                memory_usage_percentage: if memory_total_mb > 0 {
+                    (memory_used_mb as f32 / memory_total_mb as f32) * 100.0
+                } else {
+                    0.0
+                },
+                temperature: float(3),
+                power_usage: float(5),
+                clock_speed: float(6).map(|mhz| mhz as u32),
+                memory_clock: float(7).map(|mhz| mhz as u32),
+                driver_version: field(8).map(|version| version.to_string()),
+                is_nvidia: true,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let stdout = "NVIDIA GeForce RTX 4070, 12282, 1024, 48, 7, 21.35, 210, 405, 560.94\n\
+            NVIDIA GeForce GTX 1060 6GB, 6144, 512, 40, [N/A], [N/A], 139, 405, 560.94\n";
+        let gpus = parse_nvidia_smi(stdout);
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].name, "NVIDIA GeForce RTX 4070");
+        assert_eq!(gpus[0].memory_total, 12282 * 1024 * 1024);
+        assert_eq!(gpus[0].power_usage, Some(21.35));
+        assert_eq!(gpus[0].clock_speed, Some(210));
+        assert_eq!(gpus[0].memory_clock, Some(405));
+        assert_eq!(gpus[0].driver_version.as_deref(), Some("560.94"));
+        assert_eq!(gpus[1].utilization, 0.0);
+        assert_eq!(gpus[1].power_usage, None);
+    }
+}
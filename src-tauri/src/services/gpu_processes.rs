@@ -0,0 +1,473 @@
+use crate::models::game_profile::ProcessPriority;
+use crate::models::gpu_info::{EvictionAction, EvictionResult, GpuPriorityResult, GpuProcessUsage};
+use crate::services::process_control;
+use crate::shared::system::get_system;
+use anyhow::Result;
+use std::collections::HashMap;
+use sysinfo::Pid;
+
+#[cfg(target_os = "windows")]
+use windows::core::w;
+#[cfg(target_os = "windows")]
+use windows::Wdk::Graphics::Direct3D::{
+    D3DKMTGetProcessSchedulingPriorityClass, D3DKMTSetProcessSchedulingPriorityClass,
+    D3DKMT_SCHEDULINGPRIORITYCLASS, D3DKMT_SCHEDULINGPRIORITYCLASS_ABOVE_NORMAL,
+    D3DKMT_SCHEDULINGPRIORITYCLASS_BELOW_NORMAL, D3DKMT_SCHEDULINGPRIORITYCLASS_HIGH,
+    D3DKMT_SCHEDULINGPRIORITYCLASS_IDLE, D3DKMT_SCHEDULINGPRIORITYCLASS_NORMAL,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::CloseHandle;
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Performance::{
+    PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW,
+    PdhOpenQueryW, PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_LARGE, PDH_HCOUNTER, PDH_HQUERY,
+    PDH_MORE_DATA,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::ProcessStatus::EmptyWorkingSet;
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{
+    OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION, PROCESS_SET_QUOTA,
+};
+
+// Processes the desktop depends on, never offered for eviction
+const PROTECTED_PROCESSES: &[&str] = &[
+    "system",
+    "dwm.exe",
+    "csrss.exe",
+    "winlogon.exe",
+    "explorer.exe",
+    "svchost.exe",
+    "xorg",
+    "gnome-shell",
+    "kwin_x11",
+    "kwin_wayland",
+];
+
+// Recorders, streaming tools and overlays that render alongside the game and
+// compete with it for the GPU
+const CAPTURE_AND_OVERLAY_PROCESSES: &[&str] = &[
+    "obs64.exe",
+    "obs32.exe",
+    "streamlabs obs.exe",
+    "xsplit.core.exe",
+    "medal.exe",
+    "overwolf.exe",
+    "nvidia share.exe",
+    "gamebar.exe",
+    "gamebarftserver.exe",
+    "discord.exe",
+    "steamwebhelper.exe",
+];
+
+/// Per-process GPU memory usage, largest VRAM consumers first.
+///
+/// On Windows the "GPU Process Memory" performance counters are used since
+/// NVML cannot report per-process memory under WDDM; elsewhere NVML is queried.
+pub fn get_gpu_process_usage() -> Result<Vec<GpuProcessUsage>> {
+    #[cfg(target_os = "windows")]
+    let usage = query_pdh_usage().or_else(|_| query_nvml_usage())?;
+
+    #[cfg(not(target_os = "windows"))]
+    let usage = query_nvml_usage()?;
+
+    let mut system = get_system()
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+    let mut processes: Vec<GpuProcessUsage> = usage
+        .into_iter()
+        .filter_map(|(pid, (dedicated_memory, shared_memory))| {
+            // Counter instances can outlive their process for a moment
+            let process = system.process(Pid::from(pid as usize))?;
+            Some(GpuProcessUsage {
+                pid,
+                name: process.name().to_string_lossy().into_owned(),
+                exe_path: process
+                    .exe()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "N/A".to_string()),
+                dedicated_memory,
+                shared_memory,
+            })
+        })
+        .collect();
+
+    processes.sort_by_key(|process| std::cmp::Reverse(process.dedicated_memory));
+    Ok(processes)
+}
+
+/// Processes holding at least `min_dedicated_memory` bytes of VRAM that can be
+/// safely trimmed or killed before launching a game
+pub fn find_vram_heavy_processes(min_dedicated_memory: u64) -> Result<Vec<GpuProcessUsage>> {
+    let own_pid = std::process::id();
+
+    Ok(get_gpu_process_usage()?
+        .into_iter()
+        .filter(|p| p.dedicated_memory >= min_dedicated_memory)
+        .filter(|p| p.pid != own_pid && !is_protected(&p.name))
+        .collect())
+}
+
+/// Trims or kills the selected processes to free VRAM. Protected processes
+/// and PIDs that no longer hold GPU memory are skipped.
+pub fn evict_processes(pids: &[u32], action: EvictionAction) -> Result<EvictionResult> {
+    // Re-sample so a recycled PID is never touched
+    let candidates: HashMap<u32, GpuProcessUsage> = find_vram_heavy_processes(0)?
+        .into_iter()
+        .map(|p| (p.pid, p))
+        .collect();
+    let mut result = EvictionResult::default();
+
+    for pid in pids {
+        let Some(process) = candidates.get(pid) else {
+            result.skipped.push(*pid);
+            continue;
+        };
+
+        let outcome = match action {
+            EvictionAction::Trim => trim_working_set(*pid),
+            EvictionAction::Kill => process_control::kill_process(*pid).map_err(|e| e.to_string()),
+        };
+
+        match outcome {
+            Ok(()) => {
+                if action == EvictionAction::Kill {
+                    result.freed_dedicated_memory += process.dedicated_memory;
+                }
+                result.evicted.push(*pid);
+            }
+            Err(e) => result
+                .failed
+                .push(format!("{} ({}): {}", process.name, pid, e)),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Raises the GPU scheduling priority of a game to high and lowers the
+/// capture and overlay apps that are running to below normal. Recordings may
+/// drop frames while the game keeps the GPU busy.
+pub fn prioritize_game_gpu(pid: u32) -> Result<GpuPriorityResult> {
+    let mut result = GpuPriorityResult::default();
+    match set_gpu_priority(pid, ProcessPriority::High) {
+        Ok(()) => result.raised.push(pid),
+        Err(e) => result.failed.push(format!("{}: {}", pid, e)),
+    }
+
+    let others: Vec<(u32, String)> = {
+        let mut system = get_system()
+            .lock()
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        system
+            .processes()
+            .iter()
+            .map(|(other, process)| {
+                (
+                    other.as_u32(),
+                    process.name().to_string_lossy().into_owned(),
+                )
+            })
+            .filter(|(other, name)| *other != pid && is_capture_or_overlay(name))
+            .collect()
+    };
+    for (other, name) in others {
+        match set_gpu_priority(other, ProcessPriority::BelowNormal) {
+            Ok(()) => result.lowered.push(other),
+            Err(e) => result.failed.push(format!("{} ({}): {}", name, other, e)),
+        }
+    }
+    Ok(result)
+}
+
+/// Sets the priority class the GPU scheduler gives the work a process
+/// submits. High needs administrator rights; realtime is never set, it can
+/// starve the desktop compositor.
+#[cfg(target_os = "windows")]
+pub fn set_gpu_priority(pid: u32, priority: ProcessPriority) -> Result<()> {
+    let class = match priority {
+        ProcessPriority::Idle => D3DKMT_SCHEDULINGPRIORITYCLASS_IDLE,
+        ProcessPriority::BelowNormal => D3DKMT_SCHEDULINGPRIORITYCLASS_BELOW_NORMAL,
+        ProcessPriority::Normal => D3DKMT_SCHEDULINGPRIORITYCLASS_NORMAL,
+        ProcessPriority::AboveNormal => D3DKMT_SCHEDULINGPRIORITYCLASS_ABOVE_NORMAL,
+        ProcessPriority::High => D3DKMT_SCHEDULINGPRIORITYCLASS_HIGH,
+    };
+    unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)?;
+        let status = D3DKMTSetProcessSchedulingPriorityClass(handle, class);
+        let _ = CloseHandle(handle);
+        status.ok()?;
+    }
+    Ok(())
+}
+
+/// GPU scheduling priority class of a process, realtime reads as high
+#[cfg(target_os = "windows")]
+pub fn get_gpu_priority(pid: u32) -> Result<ProcessPriority> {
+    let mut class = D3DKMT_SCHEDULINGPRIORITYCLASS::default();
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)?;
+        let status = D3DKMTGetProcessSchedulingPriorityClass(handle, &mut class);
+        let _ = CloseHandle(handle);
+        status.ok()?;
+    }
+    Ok(match class {
+        D3DKMT_SCHEDULINGPRIORITYCLASS_IDLE => ProcessPriority::Idle,
+        D3DKMT_SCHEDULINGPRIORITYCLASS_BELOW_NORMAL => ProcessPriority::BelowNormal,
+        D3DKMT_SCHEDULINGPRIORITYCLASS_NORMAL => ProcessPriority::Normal,
+        D3DKMT_SCHEDULINGPRIORITYCLASS_ABOVE_NORMAL => ProcessPriority::AboveNormal,
+        _ => ProcessPriority::High,
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_gpu_priority(_pid: u32, _priority: ProcessPriority) -> Result<()> {
+    anyhow::bail!("GPU scheduling priority is only supported on Windows")
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_gpu_priority(_pid: u32) -> Result<ProcessPriority> {
+    anyhow::bail!("GPU scheduling priority is only supported on Windows")
+}
+
+fn is_capture_or_overlay(name: &str) -> bool {
+    let name = name.to_lowercase();
+    CAPTURE_AND_OVERLAY_PROCESSES.contains(&name.as_str())
+}
+
+fn is_protected(name: &str) -> bool {
+    let name = name.to_lowercase();
+    PROTECTED_PROCESSES.contains(&name.as_str())
+}
+
+#[cfg(target_os = "windows")]
+fn trim_working_set(pid: u32) -> std::result::Result<(), String> {
+    unsafe {
+        let handle = OpenProcess(
+            PROCESS_SET_QUOTA | PROCESS_QUERY_LIMITED_INFORMATION,
+            false,
+            pid,
+        )
+        .map_err(|e| e.to_string())?;
+        let result = EmptyWorkingSet(handle).map_err(|e| e.to_string());
+        let _ = CloseHandle(handle);
+        result
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn trim_working_set(_pid: u32) -> std::result::Result<(), String> {
+    Err("Trimming a process working set is only supported on Windows".to_string())
+}
+
+/// Dedicated and shared GPU memory per PID from the "GPU Process Memory" counters
+#[cfg(target_os = "windows")]
+fn query_pdh_usage() -> Result<HashMap<u32, (u64, u64)>> {
+    unsafe {
+        let mut query = PDH_HQUERY::default();
+        let status = PdhOpenQueryW(None, 0, &mut query);
+        if status != 0 {
+            anyhow::bail!("PdhOpenQuery failed: {:#x}", status);
+        }
+
+        let result = (|| {
+            let mut dedicated = PDH_HCOUNTER::default();
+            let mut shared = PDH_HCOUNTER::default();
+            let status = PdhAddEnglishCounterW(
+                query,
+                w!("\\GPU Process Memory(*)\\Dedicated Usage"),
+                0,
+                &mut dedicated,
+            );
+            if status != 0 {
+                anyhow::bail!("GPU process counters are not available: {:#x}", status);
+            }
+            let _ = PdhAddEnglishCounterW(
+                query,
+                w!("\\GPU Process Memory(*)\\Shared Usage"),
+                0,
+                &mut shared,
+            );
+
+            let status = PdhCollectQueryData(query);
+            if status != 0 {
+                anyhow::bail!("PdhCollectQueryData failed: {:#x}", status);
+            }
+
+            let mut usage: HashMap<u32, (u64, u64)> = HashMap::new();
+            for (instance, bytes) in read_counter_array(dedicated) {
+                if let Some(pid) = parse_instance_pid(&instance) {
+                    usage.entry(pid).or_default().0 += bytes;
+                }
+            }
+            for (instance, bytes) in read_counter_array(shared) {
+                if let Some(pid) = parse_instance_pid(&instance) {
+                    usage.entry(pid).or_default().1 += bytes;
+                }
+            }
+            Ok(usage)
+        })();
+
+        let _ = PdhCloseQuery(query);
+        result
+    }
+}
+
+/// Dedicated memory in use on each adapter, keyed by its LUID, from the
+/// "GPU Adapter Memory" counters
+#[cfg(target_os = "windows")]
+pub fn adapter_memory_usage() -> Result<HashMap<(u32, u32), u64>> {
+    unsafe {
+        let mut query = PDH_HQUERY::default();
+        let status = PdhOpenQueryW(None, 0, &mut query);
+        if status != 0 {
+            anyhow::bail!("PdhOpenQuery failed: {:#x}", status);
+        }
+
+        let result = (|| {
+            let mut dedicated = PDH_HCOUNTER::default();
+            let status = PdhAddEnglishCounterW(
+                query,
+                w!("\\GPU Adapter Memory(*)\\Dedicated Usage"),
+                0,
+                &mut dedicated,
+            );
+            if status != 0 {
+                anyhow::bail!("GPU adapter counters are not available: {:#x}", status);
+            }
+            let status = PdhCollectQueryData(query);
+            if status != 0 {
+                anyhow::bail!("PdhCollectQueryData failed: {:#x}", status);
+            }
+
+            let mut usage: HashMap<(u32, u32), u64> = HashMap::new();
+            for (instance, bytes) in read_counter_array(dedicated) {
+                if let Some(luid) = parse_instance_luid(&instance) {
+                    *usage.entry(luid).or_default() += bytes;
+                }
+            }
+            Ok(usage)
+        })();
+
+        let _ = PdhCloseQuery(query);
+        result
+    }
+}
+
+/// Reads every instance of a counter as (instance name, bytes)
+#[cfg(target_os = "windows")]
+unsafe fn read_counter_array(counter: PDH_HCOUNTER) -> Vec<(String, u64)> {
+    let mut buffer_size: u32 = 0;
+    let mut item_count: u32 = 0;
+    let status = PdhGetFormattedCounterArrayW(
+        counter,
+        PDH_FMT_LARGE,
+        &mut buffer_size,
+        &mut item_count,
+        None,
+    );
+    if status != PDH_MORE_DATA || buffer_size == 0 {
+        return Vec::new();
+    }
+
+    // The item array is followed by the instance name strings, keep it 8-byte aligned
+    let mut buffer = vec![0u64; (buffer_size as usize).div_ceil(8)];
+    let items = buffer.as_mut_ptr() as *mut PDH_FMT_COUNTERVALUE_ITEM_W;
+    let status = PdhGetFormattedCounterArrayW(
+        counter,
+        PDH_FMT_LARGE,
+        &mut buffer_size,
+        &mut item_count,
+        Some(items),
+    );
+    if status != 0 {
+        return Vec::new();
+    }
+
+    std::slice::from_raw_parts(items, item_count as usize)
+        .iter()
+        .filter(|item| item.FmtValue.CStatus == 0)
+        .filter_map(|item| {
+            let name = item.szName.to_string().ok()?;
+            Some((name, item.FmtValue.Anonymous.largeValue.max(0) as u64))
+        })
+        .collect()
+}
+
+/// Extracts the PID from an instance name like `pid_1234_luid_0x0_0x0000D1E4_phys_0`
+#[cfg(target_os = "windows")]
+fn parse_instance_pid(instance: &str) -> Option<u32> {
+    instance
+        .strip_prefix("pid_")?
+        .split('_')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Extracts the adapter LUID (high, low) from an instance name like
+/// `luid_0x00000000_0x0000D1E4_phys_0`, with or without a `pid_` prefix
+#[cfg(target_os = "windows")]
+fn parse_instance_luid(instance: &str) -> Option<(u32, u32)> {
+    let (_, rest) = instance.split_once("luid_0x")?;
+    let mut parts = rest.split('_');
+    let high = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let low = u32::from_str_radix(parts.next()?.strip_prefix("0x")?, 16).ok()?;
+    Some((high, low))
+}
+
+/// Per-process VRAM from NVML; the value is unavailable under WDDM
+fn query_nvml_usage() -> Result<HashMap<u32, (u64, u64)>> {
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    use nvml_wrapper::Nvml;
+
+    let nvml = Nvml::init()?;
+    let mut usage: HashMap<u32, (u64, u64)> = HashMap::new();
+
+    for index in 0..nvml.device_count()? {
+        let device = nvml.device_by_index(index)?;
+        let graphics = device.running_graphics_processes().unwrap_or_default();
+        let compute = device.running_compute_processes().unwrap_or_default();
+
+        for process in graphics.into_iter().chain(compute) {
+            if let UsedGpuMemory::Used(bytes) = process.used_gpu_memory {
+                let entry = usage.entry(process.pid).or_default();
+                // A process in both lists reports the same allocation twice
+                entry.0 = entry.0.max(bytes);
+            }
+        }
+    }
+
+    Ok(usage)
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instance_pid() {
+        assert_eq!(
+            parse_instance_pid("pid_1234_luid_0x00000000_0x0000D1E4_phys_0"),
+            Some(1234)
+        );
+        assert_eq!(parse_instance_pid("_Total"), None);
+        assert_eq!(parse_instance_pid("pid_abc_luid"), None);
+    }
+
+    #[test]
+    fn test_parse_instance_luid() {
+        assert_eq!(
+            parse_instance_luid("luid_0x00000000_0x0000D1E4_phys_0"),
+            Some((0, 0xD1E4))
+        );
+        assert_eq!(
+            parse_instance_luid("pid_1234_luid_0x00000001_0x0000d1e4_phys_0"),
+            Some((1, 0xD1E4))
+        );
+        assert_eq!(parse_instance_luid("_Total"), None);
+    }
+}
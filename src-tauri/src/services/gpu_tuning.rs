@@ -0,0 +1,395 @@
+// Read-only view of how a GPU is tuned right now: power limit, clock offsets
+// and fans, so users can check that their Afterburner or Adrenalin profile
+// survived a driver update or a reboot. NVIDIA cards are read through NVML,
+// AMD cards on Linux through the amdgpu sysfs files. AMD on Windows needs
+// ADLX, which has no Rust binding yet, so those cards are not reported.
+//
+// Every interface is a TuningBackend. Writing is meant to arrive as further
+// methods of the trait behind a `gpu-tuning-write` feature, leaving the
+// read path and the commands as they are.
+use crate::models::gpu_info::{FanControlMode, GpuFanInfo, GpuTuning, GpuTuningReport};
+use crate::utils::temperature::temperature_unit;
+use thiserror::Error;
+
+#[cfg(target_os = "linux")]
+use crate::models::gpu_info::FanCurvePoint;
+
+#[derive(Error, Debug)]
+pub enum GpuTuningError {
+    /// The interface's driver or library is not installed, not worth reporting
+    #[error("Not present")]
+    NotPresent,
+
+    #[error("{0}")]
+    ReadError(String),
+}
+
+type Result<T> = std::result::Result<T, GpuTuningError>;
+
+trait TuningBackend {
+    fn source(&self) -> &'static str;
+    fn read(&self) -> Result<Vec<GpuTuning>>;
+}
+
+pub fn get_report() -> GpuTuningReport {
+    let mut gpus = Vec::new();
+    let mut errors = Vec::new();
+    for backend in backends() {
+        match backend.read() {
+            Ok(found) => gpus.extend(found),
+            Err(GpuTuningError::NotPresent) => {}
+            Err(e) => errors.push(format!("{}: {}", backend.source(), e)),
+        }
+    }
+    GpuTuningReport {
+        gpus,
+        temperature_unit: temperature_unit(),
+        errors,
+    }
+}
+
+fn backends() -> Vec<Box<dyn TuningBackend>> {
+    vec![
+        Box::new(Nvml),
+        #[cfg(target_os = "linux")]
+        Box::new(Amdgpu),
+    ]
+}
+
+fn empty_tuning(index: u32, name: String, vendor: &str, source: &str) -> GpuTuning {
+    GpuTuning {
+        index,
+        name,
+        vendor: vendor.to_string(),
+        source: source.to_string(),
+        power_limit_w: None,
+        default_power_limit_w: None,
+        min_power_limit_w: None,
+        max_power_limit_w: None,
+        core_clock_offset_mhz: None,
+        memory_clock_offset_mhz: None,
+        max_core_clock_mhz: None,
+        max_memory_clock_mhz: None,
+        fans: Vec::new(),
+        fan_curve: Vec::new(),
+    }
+}
+
+struct Nvml;
+
+impl TuningBackend for Nvml {
+    fn source(&self) -> &'static str {
+        "NVML"
+    }
+
+    fn read(&self) -> Result<Vec<GpuTuning>> {
+        use nvml_wrapper::enum_wrappers::device::{Clock, PerformanceState};
+        use nvml_wrapper::error::NvmlError;
+
+        let nvml = nvml_wrapper::Nvml::init().map_err(|e| match e {
+            NvmlError::LibloadingError(_) | NvmlError::DriverNotLoaded => {
+                GpuTuningError::NotPresent
+            }
+            e => GpuTuningError::ReadError(e.to_string()),
+        })?;
+        let count = nvml
+            .device_count()
+            .map_err(|e| GpuTuningError::ReadError(e.to_string()))?;
+
+        let watts = |milliwatts: u32| milliwatts as f32 / 1000.0;
+        let mut gpus = Vec::new();
+        for index in 0..count {
+            let Ok(device) = nvml.device_by_index(index) else {
+                continue;
+            };
+            let name = device.name().unwrap_or_else(|_| "NVIDIA GPU".to_string());
+            let mut tuning = empty_tuning(index, name, "NVIDIA", self.source());
+
+            // The enforced limit includes caps from other tools, it is what
+            // the card actually runs at
+            tuning.power_limit_w = device
+                .enforced_power_limit()
+                .or_else(|_| device.power_management_limit())
+                .ok()
+                .map(watts);
+            tuning.default_power_limit_w = device.power_management_limit_default().ok().map(watts);
+            if let Ok(constraints) = device.power_management_limit_constraints() {
+                tuning.min_power_limit_w = Some(watts(constraints.min_limit));
+                tuning.max_power_limit_w = Some(watts(constraints.max_limit));
+            }
+
+            // Offsets are what Afterburner's core and memory clock sliders set
+            tuning.core_clock_offset_mhz = device
+                .clock_offset(Clock::Graphics, PerformanceState::Zero)
+                .ok()
+                .map(|offset| offset.clock_offset_mhz);
+            tuning.memory_clock_offset_mhz = device
+                .clock_offset(Clock::Memory, PerformanceState::Zero)
+                .ok()
+                .map(|offset| offset.clock_offset_mhz);
+            tuning.max_core_clock_mhz = device.max_clock_info(Clock::Graphics).ok();
+            tuning.max_memory_clock_mhz = device.max_clock_info(Clock::Memory).ok();
+
+            tuning.fans = nvidia_fans(&device);
+            gpus.push(tuning);
+        }
+        Ok(gpus)
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct Amdgpu;
+
+#[cfg(target_os = "linux")]
+impl TuningBackend for Amdgpu {
+    fn source(&self) -> &'static str {
+        "amdgpu"
+    }
+
+    fn read(&self) -> Result<Vec<GpuTuning>> {
+        use crate::utils::temperature::convert_temperature;
+        use std::fs;
+        use std::path::Path;
+
+        let read = |path: &Path| fs::read_to_string(path).ok();
+        let number = |path: &Path| read(path).and_then(|value| value.trim().parse::<u64>().ok());
+        let watts = |path: &Path| number(path).map(|microwatts| microwatts as f32 / 1_000_000.0);
+
+        let mut cards: Vec<(u32, std::path::PathBuf)> = fs::read_dir("/sys/class/drm/")
+            .map_err(|_| GpuTuningError::NotPresent)?
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let index = name.strip_prefix("card")?.parse().ok()?;
+                let device = entry.path().join("device");
+                (read(&device.join("vendor"))?.trim() == "0x1002").then_some((index, device))
+            })
+            .collect();
+        cards.sort();
+
+        let mut gpus = Vec::new();
+        for (index, device) in cards {
+            let name = read(&device.join("product_name"))
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .or_else(|| {
+                    read(&device.join("device")).map(|id| format!("AMD GPU ({})", id.trim()))
+                })
+                .unwrap_or_else(|| "AMD GPU".to_string());
+            let mut tuning = empty_tuning(index, name, "AMD", self.source());
+
+            if let Some(table) = read(&device.join("pp_od_clk_voltage")) {
+                let clocks = parse_od_clocks(&table);
+                tuning.core_clock_offset_mhz = clocks.core_offset;
+                tuning.max_core_clock_mhz = clocks.max_core;
+                tuning.max_memory_clock_mhz = clocks.max_memory;
+            }
+            if let Some(curve) = read(&device.join("gpu_od/fan_ctrl/fan_curve")) {
+                tuning.fan_curve = parse_fan_curve(&curve)
+                    .into_iter()
+                    .map(|(celsius, speed_percent)| FanCurvePoint {
+                        temperature: convert_temperature(celsius),
+                        speed_percent,
+                    })
+                    .collect();
+            }
+
+            let hwmon = fs::read_dir(device.join("hwmon"))
+                .ok()
+                .and_then(|mut entries| entries.next())
+                .and_then(|entry| entry.ok())
+                .map(|entry| entry.path());
+            if let Some(hwmon) = hwmon {
+                tuning.power_limit_w = watts(&hwmon.join("power1_cap"));
+                tuning.default_power_limit_w = watts(&hwmon.join("power1_cap_default"));
+                tuning.min_power_limit_w = watts(&hwmon.join("power1_cap_min"));
+                tuning.max_power_limit_w = watts(&hwmon.join("power1_cap_max"));
+                tuning.fans = amdgpu_fans(&hwmon);
+            }
+            gpus.push(tuning);
+        }
+        Ok(gpus)
+    }
+}
+
+/// Fans of an NVIDIA card. NVML does not expose the curve itself, a manual
+/// policy shows that software is driving the fans.
+pub(crate) fn nvidia_fans(device: &nvml_wrapper::Device) -> Vec<GpuFanInfo> {
+    use nvml_wrapper::enums::device::FanControlPolicy;
+
+    (0..device.num_fans().unwrap_or(0))
+        .map(|fan| GpuFanInfo {
+            index: fan,
+            speed_percent: device.fan_speed(fan).ok(),
+            rpm: device.fan_speed_rpm(fan).ok(),
+            mode: device
+                .fan_control_policy(fan)
+                .ok()
+                .map(|policy| match policy {
+                    FanControlPolicy::TemperatureContinousSw => FanControlMode::Auto,
+                    FanControlPolicy::Manual => FanControlMode::Manual,
+                }),
+        })
+        .collect()
+}
+
+/// The fan of an amdgpu card, from its hwmon directory
+#[cfg(target_os = "linux")]
+pub(crate) fn amdgpu_fans(hwmon: &std::path::Path) -> Vec<GpuFanInfo> {
+    let number = |name: &str| {
+        std::fs::read_to_string(hwmon.join(name))
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    let pwm = number("pwm1");
+    let rpm = number("fan1_input");
+    if pwm.is_none() && rpm.is_none() {
+        return Vec::new();
+    }
+    vec![GpuFanInfo {
+        index: 0,
+        speed_percent: pwm.map(|pwm| (pwm * 100 / 255) as u32),
+        rpm: rpm.map(|rpm| rpm as u32),
+        // 2 is automatic, 1 manual and 0 full speed
+        mode: number("pwm1_enable").map(|mode| match mode {
+            2 => FanControlMode::Auto,
+            _ => FanControlMode::Manual,
+        }),
+    }]
+}
+
+/// Whether amdgpu's zero RPM mode is on, from gpu_od/fan_ctrl (RDNA3 and
+/// later, kernel 6.13+)
+#[cfg(target_os = "linux")]
+pub(crate) fn amdgpu_zero_rpm(device: &std::path::Path) -> Option<bool> {
+    std::fs::read_to_string(device.join("gpu_od/fan_ctrl/fan_zero_rpm_enable"))
+        .ok()
+        .and_then(|content| parse_zero_rpm(&content))
+}
+
+/// Whether every fan stands still. Cards with zero RPM mode stop them while
+/// the GPU is cool.
+pub(crate) fn fans_stopped(fans: &[GpuFanInfo]) -> bool {
+    !fans.is_empty()
+        && fans
+            .iter()
+            .all(|fan| fan.rpm.or(fan.speed_percent) == Some(0))
+}
+
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+#[derive(Debug, Default, PartialEq)]
+struct OdClocks {
+    core_offset: Option<i32>,
+    max_core: Option<u32>,
+    max_memory: Option<u32>,
+}
+
+/// Reads the clocks of amdgpu's pp_od_clk_voltage: the highest core and
+/// memory states and, on RDNA3, the core clock offset
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_od_clocks(table: &str) -> OdClocks {
+    let mhz = |value: &str| -> Option<i64> {
+        let value = value.trim().to_lowercase();
+        value.strip_suffix("mhz")?.trim().parse().ok()
+    };
+    let mut clocks = OdClocks::default();
+    let mut section = "";
+    for line in table.lines().map(str::trim) {
+        if line.ends_with(':') && !line.contains(' ') {
+            section = line.trim_end_matches(':');
+            continue;
+        }
+        // State lines are "1: 2615Mhz", the offset is a bare "-50Mhz"
+        let value = line.split_once(':').map_or(line, |(_, value)| value);
+        match (section, mhz(value)) {
+            ("OD_SCLK", Some(value)) => clocks.max_core = Some(value as u32),
+            ("OD_MCLK", Some(value)) => clocks.max_memory = Some(value as u32),
+            ("OD_SCLK_OFFSET", Some(value)) => clocks.core_offset = Some(value as i32),
+            _ => {}
+        }
+    }
+    clocks
+}
+
+/// Points of amdgpu's fan_curve as (°C, percent). Unset points read as 0C 0%
+/// and are left out.
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_fan_curve(curve: &str) -> Vec<(f32, u32)> {
+    curve
+        .lines()
+        .map(str::trim)
+        .take_while(|line| *line != "OD_RANGE:")
+        .filter_map(|line| {
+            let (_, point) = line.split_once(':')?;
+            let mut fields = point.split_whitespace();
+            let celsius = fields.next()?.strip_suffix('C')?.parse::<f32>().ok()?;
+            let speed = fields.next()?.strip_suffix('%')?.parse::<u32>().ok()?;
+            Some((celsius, speed))
+        })
+        .filter(|(celsius, speed)| *celsius > 0.0 || *speed > 0)
+        .collect()
+}
+
+/// The value of amdgpu's fan_zero_rpm_enable, the line under its header
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_zero_rpm(content: &str) -> Option<bool> {
+    let mut lines = content.lines().map(str::trim);
+    lines.find(|line| *line == "FAN_ZERO_RPM_ENABLE:")?;
+    match lines.next()? {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_od_clocks() {
+        let rdna2 = "OD_SCLK:\n0: 500Mhz\n1: 2615Mhz\nOD_MCLK:\n0: 97Mhz\n1: 1000MHz\n\
+            OD_VDDGFX_OFFSET:\n0mV\nOD_RANGE:\nSCLK:     500Mhz       4000Mhz\n";
+        assert_eq!(
+            parse_od_clocks(rdna2),
+            OdClocks {
+                core_offset: None,
+                max_core: Some(2615),
+                max_memory: Some(1000),
+            }
+        );
+
+        let rdna3 = "OD_SCLK_OFFSET:\n-50Mhz\nOD_MCLK:\n0: 97Mhz\n1: 1249MHz\n";
+        let clocks = parse_od_clocks(rdna3);
+        assert_eq!(clocks.core_offset, Some(-50));
+        assert_eq!(clocks.max_memory, Some(1249));
+    }
+
+    #[test]
+    fn test_parse_fan_curve() {
+        let curve = "OD_FAN_CURVE:\n0: 35C 25%\n1: 60C 45%\n2: 85C 90%\n3: 0C 0%\n\
+            OD_RANGE:\nFAN_CURVE(hotspot temp): 25C 100C\n";
+        assert_eq!(parse_fan_curve(curve), [(35.0, 25), (60.0, 45), (85.0, 90)]);
+    }
+
+    #[test]
+    fn test_zero_rpm() {
+        let content = "FAN_ZERO_RPM_ENABLE:\n1\nOD_RANGE:\nZERO_RPM_ENABLE: 0 1\n";
+        assert_eq!(parse_zero_rpm(content), Some(true));
+        assert_eq!(parse_zero_rpm("FAN_ZERO_RPM_ENABLE:\n0\n"), Some(false));
+        assert_eq!(parse_zero_rpm(""), None);
+
+        let fan = |rpm, speed_percent| GpuFanInfo {
+            index: 0,
+            speed_percent,
+            rpm,
+            mode: Some(FanControlMode::Auto),
+        };
+        assert!(fans_stopped(&[fan(Some(0), Some(30)), fan(None, Some(0))]));
+        assert!(!fans_stopped(&[
+            fan(Some(0), None),
+            fan(Some(900), Some(30))
+        ]));
+        assert!(!fans_stopped(&[]));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::services::process_control::ProcessInfo;
+use crate::services::{quiet_hours, user_idle};
+use crate::shared::events;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Minimum spacing between two samples of the same process
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+// Number of samples that must all be growing before raising an alert (~1 minute)
+const HISTORY_LEN: usize = 12;
+// A process must hold at least this many handles to be considered leaking
+const HANDLE_COUNT_THRESHOLD: u32 = 10_000;
+// ...and must have gained at least this many over the tracked window
+const MIN_GROWTH: u32 = 500;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HandleLeakAlert {
+    pub pid: u32,
+    pub name: String,
+    pub handle_count: u32,
+    pub growth: u32,
+    pub window_secs: u64,
+    pub timestamp: u64,
+}
+
+struct HandleHistory {
+    name: String,
+    samples: VecDeque<u32>,
+    last_sample: Option<Instant>,
+    alerted: bool,
+}
+
+static HANDLE_HISTORY: once_cell::sync::Lazy<Mutex<HashMap<u32, HandleHistory>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+static ACTIVE_ALERTS: once_cell::sync::Lazy<Mutex<HashMap<u32, HandleLeakAlert>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Records the handle counts of a process snapshot and raises an alert for
+/// every process whose handle count keeps growing past the threshold.
+/// Processes missing from the snapshot are forgotten.
+pub fn record_snapshot(processes: &[ProcessInfo]) {
+    if quiet_hours::is_paused() || user_idle::is_idle() {
+        return;
+    }
+    let mut history = match HANDLE_HISTORY.lock() {
+        Ok(history) => history,
+        Err(_) => return,
+    };
+    let now = Instant::now();
+
+    let live_pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+    history.retain(|pid, _| live_pids.contains(pid));
+    if let Ok(mut alerts) = ACTIVE_ALERTS.lock() {
+        alerts.retain(|pid, _| history.contains_key(pid));
+    }
+
+    for process in processes.iter().filter(|p| p.handle_count > 0) {
+        let entry = history.entry(process.pid).or_insert_with(|| HandleHistory {
+            name: process.name.clone(),
+            samples: VecDeque::with_capacity(HISTORY_LEN),
+            last_sample: None,
+            alerted: false,
+        });
+
+        if entry
+            .last_sample
+            .is_some_and(|last| now.duration_since(last) < SAMPLE_INTERVAL)
+        {
+            continue;
+        }
+        entry.last_sample = Some(now);
+
+        if entry.samples.len() == HISTORY_LEN {
+            entry.samples.pop_front();
+        }
+        entry.samples.push_back(process.handle_count);
+
+        let leaking = entry.samples.len() == HISTORY_LEN && is_leaking(&entry.samples);
+
+        if leaking && !entry.alerted {
+            entry.alerted = true;
+            let alert = HandleLeakAlert {
+                pid: process.pid,
+                name: entry.name.clone(),
+                handle_count: process.handle_count,
+                growth: process.handle_count - entry.samples[0],
+                window_secs: SAMPLE_INTERVAL.as_secs() * (HISTORY_LEN as u64 - 1),
+                timestamp: events::now_millis(),
+            };
+
+            if let Ok(mut alerts) = ACTIVE_ALERTS.lock() {
+                alerts.insert(process.pid, alert.clone());
+            }
+            events::emit(events::HANDLE_LEAK_DETECTED, alert);
+        } else if !leaking && entry.alerted && !is_growing(&entry.samples) {
+            // The count went down, the process released its handles
+            entry.alerted = false;
+            if let Ok(mut alerts) = ACTIVE_ALERTS.lock() {
+                alerts.remove(&process.pid);
+            }
+        }
+    }
+}
+
+/// Returns the processes currently flagged for a handle leak
+pub fn get_active_alerts() -> Vec<HandleLeakAlert> {
+    ACTIVE_ALERTS
+        .lock()
+        .map(|alerts| alerts.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn is_growing(samples: &VecDeque<u32>) -> bool {
+    samples
+        .iter()
+        .zip(samples.iter().skip(1))
+        .all(|(prev, next)| next >= prev)
+}
+
+/// Monotonic (never decreasing) growth of at least MIN_GROWTH that ends above
+/// HANDLE_COUNT_THRESHOLD
+fn is_leaking(samples: &VecDeque<u32>) -> bool {
+    let (Some(first), Some(last)) = (samples.front(), samples.back()) else {
+        return false;
+    };
+
+    *last >= HANDLE_COUNT_THRESHOLD && last - first >= MIN_GROWTH && is_growing(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_leaking() {
+        let growing: VecDeque<u32> = (0..HISTORY_LEN as u32).map(|i| 10_000 + i * 100).collect();
+        assert!(is_leaking(&growing));
+
+        // Below the threshold
+        let small: VecDeque<u32> = (0..HISTORY_LEN as u32).map(|i| 1_000 + i * 100).collect();
+        assert!(!is_leaking(&small));
+
+        // Not monotonic
+        let mut dipping = growing.clone();
+        dipping[5] = 9_000;
+        assert!(!is_leaking(&dipping));
+
+        // Stable high count is not a leak
+        let flat: VecDeque<u32> = std::iter::repeat_n(20_000, HISTORY_LEN).collect();
+        assert!(!is_leaking(&flat));
+    }
+}
@@ -0,0 +1,352 @@
+// Notices hardware that changes under a running session: GPU driver resets
+// (a TDR on Windows, a ring timeout or Xid in the kernel log on Linux), USB
+// devices and monitors plugged in or out, drives mounted or removed and the
+// installed memory. Devices are compared with the previous poll, resets are
+// read from the system log. Each change is emitted and kept in the session
+// report, where a reset right before a crash tells the driver gave up first.
+use crate::models::session::{HardwareChange, HardwareChangeKind};
+use crate::services::{device_inventory, display_modes, quiet_hours, session_report};
+use crate::shared::{events, i18n};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use sysinfo::{Disks, MemoryRefreshKind, RefreshKind, System};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::process::Command;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+// The log is read a bit further back than the poll so no reset falls between
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+const RESET_LOOKBACK_SECS: u64 = 30;
+// Resets already reported, by their log timestamp
+const SEEN_RESETS_KEPT: usize = 50;
+
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// What the devices looked like at one poll
+#[derive(Debug, Default)]
+struct Snapshot {
+    usb: Vec<String>,
+    displays: Vec<String>,
+    disks: Vec<String>,
+    memory_bytes: u64,
+}
+
+/// Starts watching the hardware once. Subsequent calls do nothing.
+pub fn start() {
+    if WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let mut previous: Option<Snapshot> = None;
+        let mut seen_resets: VecDeque<String> = VecDeque::new();
+        loop {
+            if quiet_hours::is_paused() {
+                // Whatever changed meanwhile isn't reported as one burst
+                previous = None;
+            } else {
+                let current = snapshot();
+                let mut changes = match &previous {
+                    Some(previous) => compare(previous, &current),
+                    None => Vec::new(),
+                };
+                for (key, description) in gpu_resets() {
+                    if seen_resets.contains(&key) {
+                        continue;
+                    }
+                    seen_resets.push_back(key);
+                    if seen_resets.len() > SEEN_RESETS_KEPT {
+                        seen_resets.pop_front();
+                    }
+                    // The first poll only learns the resets already in the log
+                    if previous.is_some() {
+                        changes.push((HardwareChangeKind::GpuReset, description));
+                    }
+                }
+                previous = Some(current);
+
+                let at = events::now_millis();
+                for (kind, description) in changes {
+                    let change = HardwareChange {
+                        at,
+                        kind,
+                        description,
+                    };
+                    events::emit(events::HARDWARE_CHANGED, change.clone());
+                    session_report::record_hardware_change(change);
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+fn snapshot() -> Snapshot {
+    let usb = device_inventory::get_usb_devices()
+        .map(|devices| {
+            devices
+                .iter()
+                .map(|device| {
+                    let name = [device.manufacturer.as_deref(), device.product.as_deref()]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    format!(
+                        "{} ({:04x}:{:04x})",
+                        if name.is_empty() { "USB device" } else { &name },
+                        device.vendor_id,
+                        device.product_id
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let displays = display_modes::get_displays()
+        .map(|displays| {
+            displays
+                .iter()
+                .map(|display| format!("{} ({})", display.name, display.id))
+                .collect()
+        })
+        .unwrap_or_default();
+    let disks = Disks::new_with_refreshed_list()
+        .iter()
+        .map(|disk| {
+            let mount = disk.mount_point().to_string_lossy().to_string();
+            let name = disk.name().to_string_lossy();
+            if name.is_empty() {
+                mount
+            } else {
+                format!("{} ({})", name, mount)
+            }
+        })
+        .collect();
+    let system = System::new_with_specifics(
+        RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()),
+    );
+
+    Snapshot {
+        usb,
+        displays,
+        disks,
+        memory_bytes: system.total_memory(),
+    }
+}
+
+/// Changes from one poll to the next
+fn compare(before: &Snapshot, after: &Snapshot) -> Vec<(HardwareChangeKind, String)> {
+    let mut changes = Vec::new();
+    let lists = [
+        (
+            &before.usb,
+            &after.usb,
+            HardwareChangeKind::DeviceAdded,
+            HardwareChangeKind::DeviceRemoved,
+        ),
+        (
+            &before.displays,
+            &after.displays,
+            HardwareChangeKind::DisplayAdded,
+            HardwareChangeKind::DisplayRemoved,
+        ),
+        (
+            &before.disks,
+            &after.disks,
+            HardwareChangeKind::DiskAdded,
+            HardwareChangeKind::DiskRemoved,
+        ),
+    ];
+    for (before, after, added_kind, removed_kind) in lists {
+        let (added, removed) = difference(before, after);
+        changes.extend(removed.into_iter().map(|name| (removed_kind, name)));
+        changes.extend(added.into_iter().map(|name| (added_kind, name)));
+    }
+    // Zero when the read failed, which says nothing about the modules
+    if before.memory_bytes != after.memory_bytes
+        && before.memory_bytes > 0
+        && after.memory_bytes > 0
+    {
+        changes.push((
+            HardwareChangeKind::MemoryChanged,
+            i18n::text_with(
+                "hardware.memory_changed",
+                "Installed memory went from {before} to {after} MB",
+                &[
+                    ("before", &(before.memory_bytes / (1024 * 1024)).to_string()),
+                    ("after", &(after.memory_bytes / (1024 * 1024)).to_string()),
+                ],
+            ),
+        ));
+    }
+    changes
+}
+
+/// Entries only in `after` and only in `before`. Two of the same device are
+/// counted as two, so unplugging one of them is still noticed.
+fn difference(before: &[String], after: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut removed: Vec<String> = before.to_vec();
+    let mut added = Vec::new();
+    for entry in after {
+        match removed.iter().position(|old| old == entry) {
+            Some(index) => {
+                removed.remove(index);
+            }
+            None => added.push(entry.clone()),
+        }
+    }
+    (added, removed)
+}
+
+/// GPU resets in the system log of the last seconds, as (key, description)
+#[cfg(target_os = "windows")]
+fn gpu_resets() -> Vec<(String, String)> {
+    // Event 4101 of the Display source: "Display driver stopped responding
+    // and has successfully recovered", whatever the GPU vendor
+    let query = format!(
+        "*[System[Provider[@Name='Display'] and (EventID=4101) and \
+         TimeCreated[timediff(@SystemTime) <= {}]]]",
+        RESET_LOOKBACK_SECS * 1000
+    );
+    let Ok(output) = Command::new("wevtutil")
+        .args([
+            "qe",
+            "System",
+            &format!("/q:{}", query),
+            "/f:text",
+            "/rd:true",
+        ])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+    else {
+        return Vec::new();
+    };
+    parse_tdr_events(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "linux")]
+fn gpu_resets() -> Vec<(String, String)> {
+    let Ok(output) = Command::new("journalctl")
+        .args(["-k", "-q", "--no-pager", "-o", "short-unix"])
+        .arg(format!("--since=-{}s", RESET_LOOKBACK_SECS))
+        .output()
+    else {
+        return Vec::new();
+    };
+    parse_kernel_gpu_resets(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn gpu_resets() -> Vec<(String, String)> {
+    Vec::new()
+}
+
+/// Events printed by `wevtutil qe /f:text`, keyed by their date
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn parse_tdr_events(output: &str) -> Vec<(String, String)> {
+    let mut resets = Vec::new();
+    let mut date: Option<String> = None;
+    let mut lines = output.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line.starts_with("Event[") {
+            date = None;
+        } else if let Some(value) = line.strip_prefix("Date:") {
+            date = Some(value.trim().to_string());
+        } else if line.starts_with("Description:") {
+            // The text itself is on the next line
+            let description = lines
+                .next()
+                .filter(|text| !text.is_empty())
+                .unwrap_or("Display driver stopped responding and recovered");
+            if let Some(date) = date.take() {
+                resets.push((date, description.to_string()));
+            }
+        }
+    }
+    resets
+}
+
+/// Kernel log lines of the GPU drivers resetting the card, keyed by their
+/// timestamp
+#[cfg_attr(all(not(target_os = "linux"), not(test)), allow(dead_code))]
+fn parse_kernel_gpu_resets(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (timestamp, message) = line.split_once(' ')?;
+            let message = message.split_once(": ").map_or(message, |(_, text)| text);
+            let lower = message.to_lowercase();
+            let reset = lower.contains("gpu reset")
+                || (lower.contains("ring") && lower.contains("timeout"))
+                || lower.contains("gpu hang")
+                || message.contains("NVRM: Xid");
+            // "GPU reset succeeded" and the like follow the reset itself
+            let follow_up = lower.contains("succeeded") || lower.contains("begin!");
+            (reset && !follow_up).then(|| (timestamp.to_string(), message.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difference() {
+        let mouse = "Logitech G Pro (046d:c08b)".to_string();
+        let pad = "Xbox Controller (045e:02ea)".to_string();
+        let before = vec![mouse.clone(), pad.clone(), pad.clone()];
+        let after = vec![pad.clone(), "Webcam (046d:085e)".to_string()];
+        let (added, removed) = difference(&before, &after);
+        assert_eq!(added, vec!["Webcam (046d:085e)".to_string()]);
+        assert_eq!(removed, vec![mouse, pad]);
+    }
+
+    #[test]
+    fn test_compare_memory() {
+        let before = Snapshot {
+            memory_bytes: 32 * 1024 * 1024 * 1024,
+            ..Default::default()
+        };
+        let after = Snapshot {
+            memory_bytes: 16 * 1024 * 1024 * 1024,
+            ..Default::default()
+        };
+        let changes = compare(&before, &after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0, HardwareChangeKind::MemoryChanged);
+        assert!(compare(&before, &Snapshot::default()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_tdr_events() {
+        let output = "Event[0]:\r\n  Log Name: System\r\n  Source: Display\r\n  \
+                      Date: 2026-10-16T21:04:11.5120000Z\r\n  Event ID: 4101\r\n  \
+                      Description: \r\nDisplay driver nvlddmkm stopped responding and has \
+                      successfully recovered.\r\n\r\n";
+        let resets = parse_tdr_events(output);
+        assert_eq!(resets.len(), 1);
+        assert_eq!(resets[0].0, "2026-10-16T21:04:11.5120000Z");
+        assert!(resets[0].1.starts_with("Display driver nvlddmkm"));
+        assert!(parse_tdr_events("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_kernel_gpu_resets() {
+        let output = "1760648651.512345 host kernel: amdgpu 0000:03:00.0: amdgpu: \
+                      ring gfx_0.0.0 timeout, signaled seq=1, emitted seq=3\n\
+                      1760648651.600000 host kernel: amdgpu: GPU reset begin!\n\
+                      1760648653.100000 host kernel: amdgpu: GPU reset succeeded\n\
+                      1760648700.000000 host kernel: NVRM: Xid (PCI:0000:01:00): 79, GPU has \
+                      fallen off the bus.\n\
+                      1760648800.000000 host kernel: usb 1-2: new full-speed USB device\n";
+        let resets = parse_kernel_gpu_resets(output);
+        assert_eq!(resets.len(), 2);
+        assert_eq!(resets[0].0, "1760648651.512345");
+        assert!(resets[0].1.contains("ring gfx_0.0.0 timeout"));
+        assert!(resets[1].1.starts_with("NVRM: Xid"));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::models::optimization::HostsBlocklistStatus;
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+// Everything between the markers is Aura's and nothing outside is touched
+const BEGIN_MARKER: &str = "# BEGIN Aura telemetry blocklist";
+const END_MARKER: &str = "# END Aura telemetry blocklist";
+const SINKHOLE: &str = "0.0.0.0";
+
+/// Analytics and telemetry endpoints of launchers, overlays and engines.
+/// Only hosts that collect data are listed, never ones needed to log in,
+/// download or play.
+pub const BLOCKLIST: &[&str] = &[
+    // Epic Games Launcher and Unreal Engine analytics
+    "datarouter.ol.epicgames.com",
+    "tracking.epicgames.com",
+    // EA app
+    "pin-river.data.ea.com",
+    // NVIDIA overlay
+    "telemetry.gfe.nvidia.com",
+    "events.gfe.nvidia.com",
+    // Unity engine analytics
+    "cdp.cloud.unity3d.com",
+    "perf-events.cloud.unity3d.com",
+    // Xbox app and Windows diagnostics
+    "vortex.data.microsoft.com",
+    "v10.events.data.microsoft.com",
+];
+
+pub fn get_status() -> Result<HostsBlocklistStatus> {
+    let content = std::fs::read_to_string(hosts_path())?;
+    let managed = managed_domains(&content);
+    Ok(HostsBlocklistStatus {
+        applied: !managed.is_empty(),
+        other_blocked: blocked_domains(&content)
+            .into_iter()
+            .filter(|domain| !managed.contains(domain))
+            .collect(),
+        managed,
+    })
+}
+
+/// Adds the blocklist to the hosts file, replacing an older copy of it
+pub fn apply() -> Result<HostsBlocklistStatus> {
+    let path = hosts_path();
+    let content = std::fs::read_to_string(&path)?;
+    write_hosts(&path, &with_block(&content, BLOCKLIST))?;
+    get_status()
+}
+
+/// Removes the blocklist, leaving the rest of the file as it was
+pub fn revert() -> Result<HostsBlocklistStatus> {
+    let path = hosts_path();
+    let content = std::fs::read_to_string(&path)?;
+    write_hosts(&path, &without_block(&content))?;
+    get_status()
+}
+
+pub fn is_applied() -> bool {
+    std::fs::read_to_string(hosts_path()).is_ok_and(|content| content.contains(BEGIN_MARKER))
+}
+
+fn hosts_path() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+        PathBuf::from(root).join("System32\\drivers\\etc\\hosts")
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        PathBuf::from("/etc/hosts")
+    }
+}
+
+fn write_hosts(path: &Path, content: &str) -> Result<()> {
+    std::fs::write(path, content).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => {
+            anyhow!("Editing the hosts file requires administrator rights")
+        }
+        _ => anyhow!("Failed to write the hosts file: {}", e),
+    })?;
+    flush_dns_cache();
+    Ok(())
+}
+
+/// Makes the change apply to lookups cached before it
+fn flush_dns_cache() {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = Command::new("ipconfig")
+            .args(["/flushdns"])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output();
+    }
+}
+
+/// The file with exactly one copy of the block at the end, in the file's own
+/// line endings
+fn with_block(content: &str, domains: &[&str]) -> String {
+    let newline = newline(content);
+    let mut result = without_block(content);
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push_str(newline);
+    }
+    result.push_str(BEGIN_MARKER);
+    result.push_str(newline);
+    for domain in domains {
+        result.push_str(&format!("{} {}{}", SINKHOLE, domain, newline));
+    }
+    result.push_str(END_MARKER);
+    result.push_str(newline);
+    result
+}
+
+fn without_block(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut inside = false;
+    for line in content.split_inclusive('\n') {
+        match line.trim() {
+            BEGIN_MARKER => inside = true,
+            END_MARKER if inside => inside = false,
+            _ if !inside => result.push_str(line),
+            _ => {}
+        }
+    }
+    result
+}
+
+fn newline(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn managed_domains(content: &str) -> Vec<String> {
+    let start = content.find(BEGIN_MARKER);
+    let end = content.find(END_MARKER);
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => blocked_domains(&content[start..end]),
+        _ => Vec::new(),
+    }
+}
+
+/// Domains pointed at an unroutable or loopback address, localhost aside
+fn blocked_domains(content: &str) -> Vec<String> {
+    let mut domains: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let Some(address) = fields.next() else {
+            continue;
+        };
+        if !matches!(address, "0.0.0.0" | "127.0.0.1" | "::" | "::1") {
+            continue;
+        }
+        for domain in fields {
+            let domain = domain.to_lowercase();
+            let local = matches!(
+                domain.as_str(),
+                "localhost" | "localhost.localdomain" | "local" | "broadcasthost" | "ip6-localhost"
+            );
+            if !local && !domains.contains(&domain) {
+                domains.push(domain);
+            }
+        }
+    }
+    domains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTS: &str = "# Copyright (c) Microsoft Corp.\r\n127.0.0.1 localhost\r\n0.0.0.0 ads.example.com # mine\r\n";
+
+    #[test]
+    fn test_block_round_trip() {
+        let applied = with_block(HOSTS, &["a.example.com", "b.example.com"]);
+        assert!(applied.starts_with(HOSTS));
+        assert!(applied.contains("\r\n0.0.0.0 a.example.com\r\n"));
+        assert_eq!(
+            managed_domains(&applied),
+            ["a.example.com", "b.example.com"]
+        );
+        assert_eq!(without_block(&applied), HOSTS);
+
+        // Applying twice keeps a single block
+        let twice = with_block(&applied, &["c.example.com"]);
+        assert_eq!(twice.matches(BEGIN_MARKER).count(), 1);
+        assert_eq!(managed_domains(&twice), ["c.example.com"]);
+    }
+
+    #[test]
+    fn test_missing_final_newline() {
+        let applied = with_block("127.0.0.1 localhost", &["a.example.com"]);
+        assert!(applied.starts_with("127.0.0.1 localhost\n# BEGIN"));
+        assert_eq!(without_block(&applied), "127.0.0.1 localhost\n");
+    }
+
+    #[test]
+    fn test_blocked_domains() {
+        assert_eq!(blocked_domains(HOSTS), ["ads.example.com"]);
+        assert!(blocked_domains("192.168.1.2 nas.lan\n# 0.0.0.0 old.example.com").is_empty());
+    }
+}
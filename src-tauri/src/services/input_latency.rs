@@ -0,0 +1,466 @@
+// Input latency sampler. While the game is in the foreground a one pixel
+// mouse movement is injected, and the time until the game's next Present
+// call, read from the DXGI ETW provider, makes one sample. The display's own
+// scanout and response time are left out, but the number moves with what Aura
+// tunes (scheduling, timer resolution, power plan, Game Mode), so runs before
+// and after an optimization can be compared. Only games presenting through
+// DXGI (Direct3D 10-12) are seen, and the trace session needs administrator
+// rights.
+//
+// The game takes the moves for the player's own, so the camera twitches by a
+// pixel each sample. Moves alternate left and right and the view ends where
+// it started; the notice emitted at the start tells the player to keep still.
+use crate::models::benchmark::InputLatencyResult;
+use crate::shared::{events, i18n, paths};
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const RESULTS_FILE: &str = "input_latency.json";
+// Results kept for comparison
+const MAX_RESULTS: usize = 20;
+const DEFAULT_SAMPLES: u32 = 30;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static RESULTS: once_cell::sync::Lazy<Mutex<Vec<InputLatencyResult>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_results()));
+
+/// Samples the input latency of the game with `pid`. Leaves a few seconds to
+/// bring the game to the foreground first; samples taken while it is not in
+/// front count as missed.
+pub fn measure(
+    pid: u32,
+    samples: Option<u32>,
+    label: Option<String>,
+) -> Result<InputLatencyResult> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("A latency measurement is already running"));
+    }
+    let count = samples.unwrap_or(DEFAULT_SAMPLES).clamp(5, 200);
+    let measured = sampler::run(pid, count);
+    RUNNING.store(false, Ordering::SeqCst);
+    let (latencies, missed) = measured?;
+
+    let process_name = crate::services::process_control::get_process_detailed_info(pid)
+        .map(|info| info.name)
+        .unwrap_or_default();
+    let result = summarize(pid, process_name, label, latencies, missed).ok_or_else(|| {
+        anyhow!("No frame of the game was seen, keep it in the foreground while measuring")
+    })?;
+
+    if let Ok(mut results) = RESULTS.lock() {
+        results.push(result.clone());
+        let excess = results.len().saturating_sub(MAX_RESULTS);
+        results.drain(..excess);
+        save_results(&results);
+    }
+    Ok(result)
+}
+
+/// Warning shown while the game is brought to the foreground
+pub fn notice() -> String {
+    i18n::text(
+        "input_latency.notice",
+        "Aura moves the mouse one pixel left and right for each sample, so the \
+         camera in the game twitches slightly. Keep still and stay out of a match \
+         until the measurement ends.",
+    )
+}
+
+/// Earlier measurements, oldest first
+pub fn get_results() -> Vec<InputLatencyResult> {
+    RESULTS
+        .lock()
+        .map(|results| results.clone())
+        .unwrap_or_default()
+}
+
+pub fn clear_results() {
+    if let Ok(mut results) = RESULTS.lock() {
+        results.clear();
+        save_results(&results);
+    }
+}
+
+fn summarize(
+    pid: u32,
+    process_name: String,
+    label: Option<String>,
+    samples_ms: Vec<f64>,
+    missed: u32,
+) -> Option<InputLatencyResult> {
+    let mut sorted = samples_ms.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    Some(InputLatencyResult {
+        pid,
+        process_name,
+        label: label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty()),
+        timestamp: events::now_millis(),
+        average_ms: sorted.iter().sum::<f64>() / sorted.len().max(1) as f64,
+        median_ms: percentile(&sorted, 50.0)?,
+        p95_ms: percentile(&sorted, 95.0)?,
+        min_ms: *sorted.first()?,
+        max_ms: *sorted.last()?,
+        samples_ms,
+        missed,
+    })
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], percent: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+}
+
+fn load_results() -> Vec<InputLatencyResult> {
+    std::fs::read_to_string(paths::app_data_file(RESULTS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_results(results: &[InputLatencyResult]) {
+    match serde_json::to_string(results) {
+        Ok(content) => {
+            if let Err(e) = std::fs::write(paths::app_data_file(RESULTS_FILE), content) {
+                eprintln!("Failed to save the input latency results: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to serialize the input latency results: {}", e),
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod sampler {
+    use crate::shared::events;
+    use anyhow::{anyhow, Result};
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use std::thread::JoinHandle;
+    use std::time::{Duration, Instant};
+    use windows::core::{w, GUID, PCWSTR, PWSTR};
+    use windows::Win32::System::Diagnostics::Etw::{
+        CloseTrace, ControlTraceW, EnableTraceEx2, OpenTraceW, ProcessTrace, StartTraceW,
+        CONTROLTRACE_HANDLE, EVENT_CONTROL_CODE_ENABLE_PROVIDER, EVENT_RECORD,
+        EVENT_TRACE_CONTROL_STOP, EVENT_TRACE_LOGFILEW, EVENT_TRACE_PROPERTIES,
+        EVENT_TRACE_REAL_TIME_MODE, PROCESSTRACE_HANDLE, PROCESS_TRACE_MODE_EVENT_RECORD,
+        PROCESS_TRACE_MODE_RAW_TIMESTAMP, PROCESS_TRACE_MODE_REAL_TIME, TRACE_LEVEL_INFORMATION,
+        WNODE_FLAG_TRACED_GUID,
+    };
+    use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEEVENTF_MOVE, MOUSEINPUT,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    const SESSION_NAME: PCWSTR = w!("Aura Input Latency");
+    // Microsoft-Windows-DXGI and its Present::Start event
+    const DXGI_PROVIDER: GUID = GUID::from_u128(0xca11c036_0102_4a2d_a6ad_f03cfed5d3c9);
+    const PRESENT_START: u16 = 42;
+    // Not in the bindings: FlushTimer counts milliseconds instead of seconds
+    const EVENT_TRACE_USE_MS_FLUSH_TIMER: u32 = 0x10;
+    const FLUSH_MS: u32 = 10;
+    const ERROR_ALREADY_EXISTS: u32 = 183;
+    // Time to switch from Aura to the game
+    const START_DELAY: Duration = Duration::from_secs(3);
+    const SAMPLE_INTERVAL: Duration = Duration::from_millis(150);
+    // A frame later than this after the input belongs to a stalled game
+    const MAX_LATENCY: Duration = Duration::from_millis(250);
+    // How long events may take to come through the session buffers
+    const DELIVERY_WAIT: Duration = Duration::from_millis(500);
+    const PRESENTS_KEPT: usize = 256;
+
+    struct PresentWatch {
+        pid: u32,
+        presents: VecDeque<i64>, // QPC ticks
+    }
+
+    static WATCH: Mutex<PresentWatch> = Mutex::new(PresentWatch {
+        pid: 0,
+        presents: VecDeque::new(),
+    });
+
+    /// Latencies in milliseconds and the number of missed samples
+    pub fn run(pid: u32, count: u32) -> Result<(Vec<f64>, u32)> {
+        let mut frequency = 0i64;
+        unsafe { QueryPerformanceFrequency(&mut frequency) }?;
+        let max_ticks = MAX_LATENCY.as_secs_f64() * frequency as f64;
+
+        if let Ok(mut watch) = WATCH.lock() {
+            watch.pid = pid;
+            watch.presents.clear();
+        }
+        let trace = PresentTrace::start()?;
+        events::emit(events::INPUT_LATENCY_STARTED, super::notice());
+        std::thread::sleep(START_DELAY);
+
+        let mut latencies = Vec::new();
+        let mut missed = 0;
+        // Back and forth, so the view of the game ends where it started
+        let mut step = 1;
+        let mut offset = 0;
+        for _ in 0..count {
+            if foreground_pid() != Some(pid) {
+                missed += 1;
+                std::thread::sleep(SAMPLE_INTERVAL);
+                continue;
+            }
+            let injected = qpc_now();
+            inject_mouse_move(step);
+            offset += step;
+            step = -step;
+
+            match wait_for_present(injected, max_ticks as i64) {
+                Some(presented) => {
+                    latencies.push((presented - injected) as f64 * 1000.0 / frequency as f64)
+                }
+                None => missed += 1,
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+        drop(trace);
+        // An odd number of moves leaves the view one pixel off
+        if offset != 0 && foreground_pid() == Some(pid) {
+            inject_mouse_move(-offset);
+        }
+        Ok((latencies, missed))
+    }
+
+    /// First present of the game after `injected`, if it came in time
+    fn wait_for_present(injected: i64, max_ticks: i64) -> Option<i64> {
+        let started = Instant::now();
+        while started.elapsed() < DELIVERY_WAIT {
+            if let Ok(watch) = WATCH.lock() {
+                if let Some(presented) = watch.presents.iter().find(|at| **at > injected) {
+                    return (*presented - injected <= max_ticks).then_some(*presented);
+                }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        None
+    }
+
+    fn qpc_now() -> i64 {
+        let mut now = 0i64;
+        let _ = unsafe { QueryPerformanceCounter(&mut now) };
+        now
+    }
+
+    fn foreground_pid() -> Option<u32> {
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(GetForegroundWindow(), Some(&mut pid)) };
+        (pid != 0).then_some(pid)
+    }
+
+    fn inject_mouse_move(dx: i32) {
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dx,
+                    dy: 0,
+                    dwFlags: MOUSEEVENTF_MOVE,
+                    ..Default::default()
+                },
+            },
+        };
+        unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+    }
+
+    unsafe extern "system" fn on_event(record: *mut EVENT_RECORD) {
+        let Some(record) = (unsafe { record.as_ref() }) else {
+            return;
+        };
+        let header = &record.EventHeader;
+        if header.EventDescriptor.Id != PRESENT_START || header.ProviderId != DXGI_PROVIDER {
+            return;
+        }
+        if let Ok(mut watch) = WATCH.lock() {
+            if header.ProcessId == watch.pid {
+                watch.presents.push_back(header.TimeStamp);
+                if watch.presents.len() > PRESENTS_KEPT {
+                    watch.presents.pop_front();
+                }
+            }
+        }
+    }
+
+    /// A real-time session with the DXGI provider enabled, stopped on drop
+    struct PresentTrace {
+        handle: CONTROLTRACE_HANDLE,
+        consumer: Option<JoinHandle<()>>,
+    }
+
+    impl PresentTrace {
+        fn start() -> Result<Self> {
+            let mut properties = Properties::new();
+            let mut handle = CONTROLTRACE_HANDLE::default();
+            let mut status =
+                unsafe { StartTraceW(&mut handle, SESSION_NAME, properties.as_mut_ptr()) };
+            if status.0 == ERROR_ALREADY_EXISTS {
+                // Left behind by a run that didn't finish
+                let mut stale = Properties::new();
+                let _ = unsafe {
+                    ControlTraceW(
+                        CONTROLTRACE_HANDLE::default(),
+                        SESSION_NAME,
+                        stale.as_mut_ptr(),
+                        EVENT_TRACE_CONTROL_STOP,
+                    )
+                };
+                properties = Properties::new();
+                status = unsafe { StartTraceW(&mut handle, SESSION_NAME, properties.as_mut_ptr()) };
+            }
+            if status.0 != 0 {
+                return Err(anyhow!(
+                    "Failed to start the trace session (error {}), administrator rights are needed",
+                    status.0
+                ));
+            }
+            let mut trace = Self {
+                handle,
+                consumer: None,
+            };
+
+            let status = unsafe {
+                EnableTraceEx2(
+                    handle,
+                    &DXGI_PROVIDER,
+                    EVENT_CONTROL_CODE_ENABLE_PROVIDER.0,
+                    TRACE_LEVEL_INFORMATION as u8,
+                    u64::MAX,
+                    0,
+                    0,
+                    None,
+                )
+            };
+            if status.0 != 0 {
+                return Err(anyhow!(
+                    "Failed to enable the DXGI provider (error {})",
+                    status.0
+                ));
+            }
+
+            let mut name: Vec<u16> = unsafe { SESSION_NAME.as_wide() }
+                .iter()
+                .copied()
+                .chain(std::iter::once(0))
+                .collect();
+            let mut logfile = EVENT_TRACE_LOGFILEW {
+                LoggerName: PWSTR(name.as_mut_ptr()),
+                ..Default::default()
+            };
+            logfile.Anonymous1.ProcessTraceMode = PROCESS_TRACE_MODE_REAL_TIME
+                | PROCESS_TRACE_MODE_EVENT_RECORD
+                | PROCESS_TRACE_MODE_RAW_TIMESTAMP;
+            logfile.Anonymous2.EventRecordCallback = Some(on_event);
+            let consumer = unsafe { OpenTraceW(&mut logfile) };
+            if consumer.Value == u64::MAX {
+                return Err(anyhow!("Failed to open the trace session"));
+            }
+            trace.consumer = Some(std::thread::spawn(move || unsafe {
+                // Returns once the session is stopped
+                let _ = ProcessTrace(&[consumer], None, None);
+                let _ = CloseTrace(PROCESSTRACE_HANDLE {
+                    Value: consumer.Value,
+                });
+            }));
+            Ok(trace)
+        }
+    }
+
+    impl Drop for PresentTrace {
+        fn drop(&mut self) {
+            let mut properties = Properties::new();
+            let _ = unsafe {
+                ControlTraceW(
+                    self.handle,
+                    PCWSTR::null(),
+                    properties.as_mut_ptr(),
+                    EVENT_TRACE_CONTROL_STOP,
+                )
+            };
+            if let Some(consumer) = self.consumer.take() {
+                let _ = consumer.join();
+            }
+        }
+    }
+
+    /// EVENT_TRACE_PROPERTIES followed by room for the session name, in u64
+    /// so the structure is aligned
+    struct Properties(Vec<u64>);
+
+    impl Properties {
+        fn new() -> Self {
+            let header = std::mem::size_of::<EVENT_TRACE_PROPERTIES>();
+            let size = header + 256 * 2;
+            let mut buffer = Properties(vec![0u64; size.div_ceil(8)]);
+            let properties = unsafe { &mut *buffer.as_mut_ptr() };
+            properties.Wnode.BufferSize = size as u32;
+            properties.Wnode.Flags = WNODE_FLAG_TRACED_GUID;
+            // Timestamps in QPC ticks, the clock the input is stamped with
+            properties.Wnode.ClientContext = 1;
+            properties.LogFileMode = EVENT_TRACE_REAL_TIME_MODE | EVENT_TRACE_USE_MS_FLUSH_TIMER;
+            properties.FlushTimer = FLUSH_MS;
+            properties.LoggerNameOffset = header as u32;
+            buffer
+        }
+
+        fn as_mut_ptr(&mut self) -> *mut EVENT_TRACE_PROPERTIES {
+            self.0.as_mut_ptr() as *mut EVENT_TRACE_PROPERTIES
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod sampler {
+    use anyhow::{anyhow, Result};
+
+    pub fn run(_pid: u32, _count: u32) -> Result<(Vec<f64>, u32)> {
+        Err(anyhow!(
+            "Input latency is measured from the DXGI present events of Windows"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 50.0), Some(10.0));
+        assert_eq!(percentile(&sorted, 95.0), Some(19.0));
+        assert_eq!(percentile(&sorted, 100.0), Some(20.0));
+        assert_eq!(percentile(&[7.0], 95.0), Some(7.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let result = summarize(
+            42,
+            "game.exe".to_string(),
+            Some("  before  ".to_string()),
+            vec![12.0, 8.0, 10.0, 30.0],
+            2,
+        )
+        .unwrap();
+        assert_eq!(result.label.as_deref(), Some("before"));
+        assert_eq!(result.average_ms, 15.0);
+        assert_eq!(result.median_ms, 10.0);
+        assert_eq!(result.p95_ms, 30.0);
+        assert_eq!((result.min_ms, result.max_ms), (8.0, 30.0));
+        // Samples stay in the order they were taken
+        assert_eq!(result.samples_ms, vec![12.0, 8.0, 10.0, 30.0]);
+        assert_eq!(result.missed, 2);
+
+        assert!(summarize(42, String::new(), Some(" ".to_string()), Vec::new(), 5).is_none());
+    }
+}
@@ -0,0 +1,378 @@
+// Games launched from Aura run in a job object, so the processes they start
+// (launcher stubs, shader compilers, crash handlers) belong to the same group:
+// priority, a CPU rate cap and a memory limit apply to all of them at once,
+// usage is accounted for the whole group and terminating the group leaves
+// nothing behind. Only available on Windows.
+use crate::models::game_profile::{JobGroupInfo, JobLimits};
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use std::collections::HashMap;
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use std::time::Instant;
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JobObjectBasicAndIoAccountingInformation,
+    JobObjectBasicProcessIdList, JobObjectCpuRateControlInformation,
+    JobObjectExtendedLimitInformation, QueryInformationJobObject, SetInformationJobObject,
+    TerminateJobObject, JOBOBJECTINFOCLASS, JOBOBJECT_BASIC_AND_IO_ACCOUNTING_INFORMATION,
+    JOBOBJECT_BASIC_PROCESS_ID_LIST, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+    JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+    JOB_OBJECT_CPU_RATE_CONTROL, JOB_OBJECT_CPU_RATE_CONTROL_ENABLE,
+    JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP, JOB_OBJECT_LIMIT_BREAKAWAY_OK,
+    JOB_OBJECT_LIMIT_JOB_MEMORY, JOB_OBJECT_LIMIT_PRIORITY_CLASS,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE};
+
+const MIN_MEMORY_LIMIT_MB: u64 = 256;
+#[cfg(target_os = "windows")]
+const MAX_LISTED_PIDS: usize = 1024;
+
+#[derive(Error, Debug)]
+pub enum JobGroupError {
+    #[error("Invalid job limits: {0}")]
+    InvalidLimits(String),
+
+    #[error("No job group for process {0}")]
+    NotFound(u32),
+
+    #[cfg(target_os = "windows")]
+    #[error("Job object error: {0}")]
+    JobError(String),
+
+    #[cfg(not(target_os = "windows"))]
+    #[error("Job groups are only available on Windows")]
+    UnsupportedPlatform,
+}
+
+type Result<T> = std::result::Result<T, JobGroupError>;
+
+#[cfg(target_os = "windows")]
+struct JobGroup {
+    handle: isize,
+    name: String,
+    exe_path: String,
+    limits: JobLimits,
+    // CPU time in 100 ns units at the previous query, for the usage
+    last_cpu: Option<(u64, Instant)>,
+}
+
+// By the pid of the game
+#[cfg(target_os = "windows")]
+static GROUPS: once_cell::sync::Lazy<Mutex<HashMap<u32, JobGroup>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn validate_limits(limits: &JobLimits) -> Result<()> {
+    if let Some(rate) = limits.cpu_rate_percent {
+        if !(1..=100).contains(&rate) {
+            return Err(JobGroupError::InvalidLimits(
+                "CPU rate must be between 1 and 100 percent".to_string(),
+            ));
+        }
+    }
+    if let Some(memory) = limits.memory_limit_mb {
+        if memory < MIN_MEMORY_LIMIT_MB {
+            return Err(JobGroupError::InvalidLimits(format!(
+                "Memory limit must be at least {} MB",
+                MIN_MEMORY_LIMIT_MB
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Puts a just launched game in a new job group. Processes it started before
+/// this call stay outside the group.
+pub fn create(pid: u32, name: &str, exe_path: &str, limits: JobLimits) -> Result<()> {
+    validate_limits(&limits)?;
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        let job = CreateJobObjectW(None, windows::core::PCWSTR::null())
+            .map_err(|e| JobGroupError::JobError(e.to_string()))?;
+        let assigned = apply_limits(job, &limits).and_then(|()| {
+            let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, false, pid)
+                .map_err(|e| JobGroupError::JobError(e.to_string()))?;
+            let result = AssignProcessToJobObject(job, process)
+                .map_err(|e| JobGroupError::JobError(e.to_string()));
+            let _ = CloseHandle(process);
+            result
+        });
+        if let Err(e) = assigned {
+            let _ = CloseHandle(job);
+            return Err(e);
+        }
+
+        let mut groups = GROUPS
+            .lock()
+            .map_err(|e| JobGroupError::JobError(e.to_string()))?;
+        groups.insert(
+            pid,
+            JobGroup {
+                handle: job.0 as isize,
+                name: name.to_string(),
+                exe_path: exe_path.to_string(),
+                limits,
+                last_cpu: None,
+            },
+        );
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (pid, name, exe_path);
+        Err(JobGroupError::UnsupportedPlatform)
+    }
+}
+
+/// Changes the limits of a running group
+pub fn set_limits(pid: u32, limits: JobLimits) -> Result<()> {
+    validate_limits(&limits)?;
+
+    #[cfg(target_os = "windows")]
+    {
+        let mut groups = GROUPS
+            .lock()
+            .map_err(|e| JobGroupError::JobError(e.to_string()))?;
+        let group = groups.get_mut(&pid).ok_or(JobGroupError::NotFound(pid))?;
+        unsafe { apply_limits(handle(group.handle), &limits)? };
+        group.limits = limits;
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = pid;
+        Err(JobGroupError::UnsupportedPlatform)
+    }
+}
+
+/// Ends the game and every process of its group
+pub fn terminate(pid: u32) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        let group = GROUPS
+            .lock()
+            .map_err(|e| JobGroupError::JobError(e.to_string()))?
+            .remove(&pid)
+            .ok_or(JobGroupError::NotFound(pid))?;
+        unsafe {
+            let result = TerminateJobObject(handle(group.handle), 1)
+                .map_err(|e| JobGroupError::JobError(e.to_string()));
+            let _ = CloseHandle(handle(group.handle));
+            result
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(JobGroupError::NotFound(pid))
+    }
+}
+
+/// Groups with at least one running process, with their combined usage.
+/// Groups whose processes have all exited are dropped.
+pub fn list() -> Vec<JobGroupInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        let Ok(mut groups) = GROUPS.lock() else {
+            return Vec::new();
+        };
+        let mut found = Vec::new();
+        groups.retain(|pid, group| match unsafe { query(*pid, group) } {
+            Some(info) if info.active_processes > 0 => {
+                found.push(info);
+                true
+            }
+            _ => {
+                unsafe {
+                    let _ = CloseHandle(handle(group.handle));
+                }
+                false
+            }
+        });
+        found.sort_by_key(|info| info.pid);
+        found
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Vec::new()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn handle(raw: isize) -> HANDLE {
+    HANDLE(raw as *mut std::ffi::c_void)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn apply_limits(job: HANDLE, limits: &JobLimits) -> Result<()> {
+    let set = |class: JOBOBJECTINFOCLASS, info: *const std::ffi::c_void, size: usize| {
+        SetInformationJobObject(job, class, info, size as u32)
+            .map_err(|e| JobGroupError::JobError(e.to_string()))
+    };
+
+    // Breakaway stays allowed, some launchers start the game that way
+    let mut extended = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+    extended.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_BREAKAWAY_OK;
+    if let Some(priority) = limits.priority {
+        extended.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+        extended.BasicLimitInformation.PriorityClass =
+            crate::services::process_control::priority_class(priority).0;
+    }
+    if let Some(memory_mb) = limits.memory_limit_mb {
+        extended.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+        extended.JobMemoryLimit = (memory_mb * 1024 * 1024) as usize;
+    }
+    set(
+        JobObjectExtendedLimitInformation,
+        &extended as *const _ as *const std::ffi::c_void,
+        std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>(),
+    )?;
+
+    // The rate is in hundredths of a percent; no flags removes the cap
+    let cpu_rate = match limits.cpu_rate_percent {
+        Some(percent) => JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+            ControlFlags: JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+            Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 {
+                CpuRate: percent * 100,
+            },
+        },
+        None => JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+            ControlFlags: JOB_OBJECT_CPU_RATE_CONTROL(0),
+            Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 { CpuRate: 0 },
+        },
+    };
+    set(
+        JobObjectCpuRateControlInformation,
+        &cpu_rate as *const _ as *const std::ffi::c_void,
+        std::mem::size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>(),
+    )
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn query(pid: u32, group: &mut JobGroup) -> Option<JobGroupInfo> {
+    let job = handle(group.handle);
+
+    let mut accounting = JOBOBJECT_BASIC_AND_IO_ACCOUNTING_INFORMATION::default();
+    QueryInformationJobObject(
+        Some(job),
+        JobObjectBasicAndIoAccountingInformation,
+        &mut accounting as *mut _ as *mut std::ffi::c_void,
+        std::mem::size_of::<JOBOBJECT_BASIC_AND_IO_ACCOUNTING_INFORMATION>() as u32,
+        None,
+    )
+    .ok()?;
+    let mut extended = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+    let _ = QueryInformationJobObject(
+        Some(job),
+        JobObjectExtendedLimitInformation,
+        &mut extended as *mut _ as *mut std::ffi::c_void,
+        std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        None,
+    );
+
+    // Header of two u32 counts followed by the ids, as usize each
+    let mut buffer = vec![0usize; MAX_LISTED_PIDS + 2];
+    let pids = match QueryInformationJobObject(
+        Some(job),
+        JobObjectBasicProcessIdList,
+        buffer.as_mut_ptr() as *mut std::ffi::c_void,
+        (buffer.len() * std::mem::size_of::<usize>()) as u32,
+        None,
+    ) {
+        Ok(()) => {
+            let list = buffer.as_ptr() as *const JOBOBJECT_BASIC_PROCESS_ID_LIST;
+            let count = ((*list).NumberOfProcessIdsInList as usize).min(MAX_LISTED_PIDS);
+            std::slice::from_raw_parts(
+                std::ptr::addr_of!((*list).ProcessIdList) as *const usize,
+                count,
+            )
+            .iter()
+            .map(|pid| *pid as u32)
+            .collect()
+        }
+        Err(_) => Vec::new(),
+    };
+
+    let basic = accounting.BasicInfo;
+    let cpu_time = (basic.TotalUserTime + basic.TotalKernelTime) as u64;
+    let now = Instant::now();
+    let cpu_usage = group.last_cpu.map(|(previous, at)| {
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1) as f64;
+        let elapsed = now.duration_since(at).as_nanos() as f64 / 100.0;
+        (cpu_time.saturating_sub(previous) as f64 / (elapsed * cores) * 100.0).min(100.0) as f32
+    });
+    group.last_cpu = Some((cpu_time, now));
+
+    Some(JobGroupInfo {
+        pid,
+        name: group.name.clone(),
+        exe_path: group.exe_path.clone(),
+        limits: group.limits.clone(),
+        memory_bytes: memory_of(&pids),
+        pids,
+        active_processes: basic.ActiveProcesses,
+        total_processes: basic.TotalProcesses,
+        cpu_time_ms: cpu_time / 10_000,
+        cpu_usage,
+        peak_memory_bytes: extended.PeakJobMemoryUsed as u64,
+        read_bytes: accounting.IoInfo.ReadTransferCount,
+        write_bytes: accounting.IoInfo.WriteTransferCount,
+    })
+}
+
+/// Current memory of the group, the job object only keeps the peak
+#[cfg(target_os = "windows")]
+fn memory_of(pids: &[u32]) -> u64 {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate};
+
+    let Ok(mut system) = crate::shared::system::get_system().lock() else {
+        return 0;
+    };
+    let pids: Vec<Pid> = pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    pids.iter()
+        .filter_map(|pid| system.process(*pid))
+        .map(|process| process.memory())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_limits() {
+        assert!(validate_limits(&JobLimits::default()).is_ok());
+        assert!(validate_limits(&JobLimits {
+            cpu_rate_percent: Some(50),
+            memory_limit_mb: Some(8192),
+            ..Default::default()
+        })
+        .is_ok());
+        assert!(validate_limits(&JobLimits {
+            cpu_rate_percent: Some(0),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(validate_limits(&JobLimits {
+            memory_limit_mb: Some(64),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
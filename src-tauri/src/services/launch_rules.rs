@@ -0,0 +1,541 @@
+// Applies the per-game rules of the profiles when the process watcher
+// reports a game starting, and undoes them when it exits. Priority, affinity,
+// the suspended processes and the frame cap are in place within milliseconds
+// of the launch. The rules that wait on the router, the firewall or powercfg
+// (port forwarding, QoS, network blocks, cooling and power plan) run on a
+// thread of their own, so they never hold up the next process event.
+// Processes started by a game or a boosted process inherit its priority and
+// affinity.
+use crate::models::game_profile::{AppliedGameRules, GameProfile, ProcessPriority};
+use crate::services::process_watcher::{self, ProcessEvent, ProcessEventKind};
+use crate::services::{
+    anti_cheat, cooling, frame_limiter, game_profiles, network_block, port_forwarding, power_plan,
+    process_control, qos,
+};
+use crate::shared::{events, system};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+static RULES_RUNNING: AtomicBool = AtomicBool::new(false);
+
+struct RunningGame {
+    profile: GameProfile,
+    suspended: Vec<u32>, // pids, resumed when the game exits
+}
+
+// By game pid
+static GAMES: once_cell::sync::Lazy<Mutex<HashMap<u32, RunningGame>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Descendant pid -> game pid, for the games with child patterns
+static FAMILY: once_cell::sync::Lazy<Mutex<HashMap<u32, u32>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone)]
+struct InheritedRules {
+    root: u32, // the process that was boosted
+    affinity: Vec<u32>,
+    priority: ProcessPriority,
+}
+
+// Followed pid -> rules its future children get, descendants included
+static FOLLOWED: once_cell::sync::Lazy<Mutex<HashMap<u32, InheritedRules>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The slow part of a game's rules, handled in order so a game that exits
+/// right away is never released before its rules were applied
+enum SessionJob {
+    Apply(GameProfile, AppliedGameRules),
+    Release(u32, GameProfile),
+}
+
+/// Starts listening for game launches once. Subsequent calls do nothing.
+pub fn start() {
+    if RULES_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    process_watcher::start();
+    let process_events = process_watcher::subscribe();
+    let sessions = start_sessions();
+
+    std::thread::spawn(move || {
+        let mut system = System::new();
+        for event in process_events {
+            match event.kind {
+                ProcessEventKind::Created => {
+                    let applied = match event.parent_pid.and_then(game_of) {
+                        Some(game_pid) => apply_child_rules(&mut system, &event, game_pid),
+                        None => match event.parent_pid.and_then(followed_by) {
+                            Some(rules) => Some(apply_inherited(&event, rules)),
+                            None => {
+                                // Reported once the session rules are in place too
+                                if let Some((profile, applied)) = apply_rules(&mut system, &event) {
+                                    let _ = sessions.send(SessionJob::Apply(profile, applied));
+                                }
+                                None
+                            }
+                        },
+                    };
+                    if let Some(applied) = applied {
+                        events::emit(events::GAME_RULES_APPLIED, applied);
+                    }
+                }
+                ProcessEventKind::Exited => {
+                    if let Some(profile) = forget(event.pid) {
+                        let _ = sessions.send(SessionJob::Release(event.pid, profile));
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// The thread applying and releasing the session rules of the games
+fn start_sessions() -> Sender<SessionJob> {
+    let (sender, jobs) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut system = System::new();
+        for job in jobs {
+            match job {
+                SessionJob::Apply(profile, mut applied) => {
+                    apply_session_rules(&mut system, &profile, &mut applied);
+                    events::emit(events::GAME_RULES_APPLIED, applied);
+                }
+                SessionJob::Release(pid, profile) => release_session_rules(pid, &profile),
+            }
+        }
+    });
+    sender
+}
+
+/// Makes the processes a boosted process starts from now on inherit its
+/// affinity and priority, until it exits
+pub fn follow_children(pid: u32, affinity: Vec<u32>, priority: ProcessPriority) {
+    if let Ok(mut followed) = FOLLOWED.lock() {
+        followed.insert(
+            pid,
+            InheritedRules {
+                root: pid,
+                affinity,
+                priority,
+            },
+        );
+    }
+}
+
+fn followed_by(pid: u32) -> Option<InheritedRules> {
+    FOLLOWED
+        .lock()
+        .ok()
+        .and_then(|followed| followed.get(&pid).cloned())
+}
+
+fn apply_inherited(event: &ProcessEvent, rules: InheritedRules) -> AppliedGameRules {
+    let mut errors = Vec::new();
+    if let Err(e) = process_control::pin_process_to_cores(event.pid, &rules.affinity) {
+        errors.push(format!("Affinity: {}", e));
+    }
+    if let Err(e) = process_control::set_process_priority(event.pid, rules.priority) {
+        errors.push(format!("Priority: {}", e));
+    }
+
+    let applied = AppliedGameRules {
+        pid: event.pid,
+        game_pid: rules.root,
+        name: event.name.clone().unwrap_or_default(),
+        exe_path: event.exe_path.clone().unwrap_or_default(),
+        priority: Some(rules.priority),
+        affinity: Some(rules.affinity.clone()),
+        suspended: Vec::new(),
+        network_blocked: Vec::new(),
+        cooling_profile: None,
+        frame_limit: None,
+        errors,
+        latency_ms: events::now_millis().saturating_sub(event.timestamp),
+    };
+    // Passed further down, a launcher may start a stub that starts the game
+    if let Ok(mut followed) = FOLLOWED.lock() {
+        followed.insert(event.pid, rules);
+    }
+    applied
+}
+
+fn has_rules(profile: &GameProfile) -> bool {
+    profile.priority.is_some()
+        || profile.affinity.is_some()
+        || !profile.suspend_while_running.is_empty()
+        || !profile.block_network_while_running.is_empty()
+        || profile.cooling_profile.is_some()
+        || profile.high_performance_power_plan
+        || !profile.port_mappings.is_empty()
+        || profile.dscp.is_some()
+        || profile.frame_limit.is_some()
+}
+
+fn find_profile(system: &mut System, event: &ProcessEvent) -> Option<GameProfile> {
+    if let Some(exe_path) = &event.exe_path {
+        return game_profiles::get_profile(exe_path).filter(has_rules);
+    }
+
+    // The path is only looked up when the name matches one of the games,
+    // every other process start stays cheap
+    let name = event.name.as_deref()?;
+    let candidate = game_profiles::get_profiles().into_iter().any(|profile| {
+        has_rules(&profile)
+            && Path::new(&profile.exe_path)
+                .file_name()
+                .and_then(|file| file.to_str())
+                .is_some_and(|file| system::same_process_name(name, file))
+    });
+    if !candidate {
+        return None;
+    }
+
+    let pid = Pid::from_u32(event.pid);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+    );
+    let exe_path = system.process(pid)?.exe()?.to_string_lossy().into_owned();
+    game_profiles::get_profile(&exe_path).filter(has_rules)
+}
+
+/// The game a process belongs to, when it is one or descends from one
+fn game_of(pid: u32) -> Option<u32> {
+    if GAMES.lock().is_ok_and(|games| games.contains_key(&pid)) {
+        return Some(pid);
+    }
+    FAMILY
+        .lock()
+        .ok()
+        .and_then(|family| family.get(&pid).copied())
+}
+
+/// Applies the rules that have to be in place before the game gets going and
+/// registers it. The session rules are left to `apply_session_rules`.
+fn apply_rules(
+    system: &mut System,
+    event: &ProcessEvent,
+) -> Option<(GameProfile, AppliedGameRules)> {
+    let profile = find_profile(system, event)?;
+    let mut errors = apply_to_process(event.pid, &profile);
+    let suspended = suspend_for_game(system, event.pid, &profile, &mut errors);
+    let mut frame_limit = None;
+    if let Some(limit) = &profile.frame_limit {
+        match frame_limiter::apply_for_game(event.pid, &profile.exe_path, limit) {
+            Ok(fps) => frame_limit = fps,
+            Err(e) => errors.push(format!("Frame limit: {}", e)),
+        }
+    }
+
+    if let Ok(mut games) = GAMES.lock() {
+        games.insert(
+            event.pid,
+            RunningGame {
+                profile: profile.clone(),
+                suspended: suspended.clone(),
+            },
+        );
+    }
+
+    let applied = AppliedGameRules {
+        pid: event.pid,
+        game_pid: event.pid,
+        name: profile.name.clone(),
+        exe_path: profile.exe_path.clone(),
+        priority: profile.priority,
+        affinity: profile.affinity.clone(),
+        suspended,
+        network_blocked: Vec::new(),
+        cooling_profile: None,
+        frame_limit,
+        errors,
+        latency_ms: events::now_millis().saturating_sub(event.timestamp),
+    };
+    Some((profile, applied))
+}
+
+/// Network blocks, cooling, power plan, port forwarding and QoS, which may
+/// take seconds on a slow router or firewall
+fn apply_session_rules(system: &mut System, profile: &GameProfile, applied: &mut AppliedGameRules) {
+    let pid = applied.pid;
+    let errors = &mut applied.errors;
+    applied.network_blocked = block_network_for_game(system, pid, profile, errors);
+    if let Some(cooling_profile) = profile.cooling_profile {
+        match cooling::apply_for_game(pid, cooling_profile) {
+            Ok(()) => applied.cooling_profile = Some(cooling_profile),
+            Err(e) => errors.push(format!("Cooling: {}", e)),
+        }
+    }
+    if profile.high_performance_power_plan {
+        if let Err(e) = power_plan::apply_for_game(pid) {
+            errors.push(format!("Power plan: {}", e));
+        }
+    }
+    port_forwarding::open_for_game(pid, &profile.name, &profile.port_mappings);
+    if let Some(dscp) = profile.dscp {
+        if let Err(e) = qos::apply_for_game(pid, &profile.exe_path, dscp) {
+            errors.push(format!("QoS: {}", e));
+        }
+    }
+}
+
+fn release_session_rules(pid: u32, profile: &GameProfile) {
+    if profile.cooling_profile.is_some() {
+        cooling::release_game(pid);
+    }
+    if profile.high_performance_power_plan {
+        power_plan::release_game(pid);
+    }
+    if !profile.port_mappings.is_empty() {
+        port_forwarding::close_for_game(pid);
+    }
+    if !profile.block_network_while_running.is_empty() {
+        network_block::release_game(pid);
+    }
+    if profile.dscp.is_some() {
+        qos::release_game(pid);
+    }
+}
+
+/// Gives a process started under a game the game's priority and affinity
+/// when its name matches one of the child patterns
+fn apply_child_rules(
+    system: &mut System,
+    event: &ProcessEvent,
+    game_pid: u32,
+) -> Option<AppliedGameRules> {
+    let profile = GAMES
+        .lock()
+        .ok()?
+        .get(&game_pid)
+        .map(|game| game.profile.clone())?;
+    if profile.child_processes.is_empty() {
+        return None;
+    }
+    // Children of children are followed too, a launcher stub often sits
+    // between the game and the process doing the work
+    if let Ok(mut family) = FAMILY.lock() {
+        family.insert(event.pid, game_pid);
+    }
+
+    let name = match &event.name {
+        Some(name) => name.clone(),
+        None => {
+            let pid = Pid::from_u32(event.pid);
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                true,
+                ProcessRefreshKind::nothing(),
+            );
+            system.process(pid)?.name().to_string_lossy().into_owned()
+        }
+    };
+    if !profile
+        .child_processes
+        .iter()
+        .any(|pattern| system::matches_process_pattern(&name, pattern))
+    {
+        return None;
+    }
+
+    let errors = apply_to_process(event.pid, &profile);
+    Some(AppliedGameRules {
+        pid: event.pid,
+        game_pid,
+        name,
+        exe_path: event.exe_path.clone().unwrap_or_default(),
+        priority: profile.priority,
+        affinity: profile.affinity,
+        suspended: Vec::new(),
+        network_blocked: Vec::new(),
+        cooling_profile: None,
+        frame_limit: None,
+        errors,
+        latency_ms: events::now_millis().saturating_sub(event.timestamp),
+    })
+}
+
+/// Priority and affinity, the rules that apply to every process of a game
+fn apply_to_process(pid: u32, profile: &GameProfile) -> Vec<String> {
+    let mut errors = Vec::new();
+    if let Some(cores) = &profile.affinity {
+        if let Err(e) = process_control::pin_process_to_cores(pid, cores) {
+            errors.push(format!("Affinity: {}", e));
+        }
+    }
+    if let Some(priority) = profile.priority {
+        if let Err(e) = process_control::set_process_priority(pid, priority) {
+            errors.push(format!("Priority: {}", e));
+        }
+    }
+    errors
+}
+
+/// Pids of the processes under a running game that match its profile's
+/// child patterns, so a manual boost reaches them as well
+pub fn game_child_processes(game_pid: u32) -> Vec<u32> {
+    let Ok(mut system) = system::get_system().lock() else {
+        return Vec::new();
+    };
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+    let Some(profile) = system
+        .process(Pid::from_u32(game_pid))
+        .and_then(|process| process.exe())
+        .and_then(|exe| game_profiles::get_profile(&exe.to_string_lossy()))
+    else {
+        return Vec::new();
+    };
+
+    let mut found = Vec::new();
+    let mut parents = vec![game_pid];
+    while let Some(parent) = parents.pop() {
+        for (pid, process) in system.processes() {
+            if process.parent().map(|p| p.as_u32()) != Some(parent) {
+                continue;
+            }
+            parents.push(pid.as_u32());
+            let name = process.name().to_string_lossy();
+            if profile
+                .child_processes
+                .iter()
+                .any(|pattern| system::matches_process_pattern(&name, pattern))
+            {
+                found.push(pid.as_u32());
+            }
+        }
+    }
+    found
+}
+
+/// Suspends the running processes named in the profile, leaving alone the
+/// game itself, Aura, anti-cheats and anything already suspended for another
+/// game
+fn suspend_for_game(
+    system: &mut System,
+    game_pid: u32,
+    profile: &GameProfile,
+    errors: &mut Vec<String>,
+) -> Vec<u32> {
+    if profile.suspend_while_running.is_empty() {
+        return Vec::new();
+    }
+    let already: Vec<u32> = match GAMES.lock() {
+        Ok(games) => games
+            .values()
+            .flat_map(|game| game.suspended.iter().copied())
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    let own_pid = std::process::id();
+    let targets: Vec<(u32, String)> = system
+        .processes()
+        .iter()
+        .map(|(pid, process)| (pid.as_u32(), process.name().to_string_lossy().into_owned()))
+        .filter(|(pid, _)| *pid != game_pid && *pid != own_pid && !already.contains(pid))
+        .filter(|(_, name)| {
+            profile
+                .suspend_while_running
+                .iter()
+                .any(|wanted| system::same_process_name(name, wanted))
+        })
+        .collect();
+
+    let mut done = Vec::new();
+    for (pid, name) in targets {
+        if anti_cheat::is_anti_cheat_process(&name) {
+            errors.push(format!(
+                "Suspend {}: anti-cheat processes are never suspended",
+                name
+            ));
+            continue;
+        }
+        match process_control::suspend_process(pid) {
+            Ok(()) => done.push(pid),
+            Err(e) => errors.push(format!("Suspend {}: {}", name, e)),
+        }
+    }
+    done
+}
+
+/// Cuts the profile's apps off the network until the game exits. Entries are
+/// full executable paths or names of running processes.
+fn block_network_for_game(
+    system: &mut System,
+    game_pid: u32,
+    profile: &GameProfile,
+    errors: &mut Vec<String>,
+) -> Vec<String> {
+    let (mut paths, names): (Vec<String>, Vec<String>) = profile
+        .block_network_while_running
+        .iter()
+        .cloned()
+        .partition(|entry| Path::new(entry).is_absolute());
+    if !names.is_empty() {
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+        );
+        for process in system.processes().values() {
+            let name = process.name().to_string_lossy();
+            if !names
+                .iter()
+                .any(|wanted| system::same_process_name(&name, wanted))
+            {
+                continue;
+            }
+            if let Some(exe) = process.exe() {
+                let exe = exe.to_string_lossy().into_owned();
+                if !paths.contains(&exe) {
+                    paths.push(exe);
+                }
+            }
+        }
+    }
+
+    let mut done = Vec::new();
+    for path in paths {
+        match network_block::block(&path, Some(game_pid)) {
+            Ok(app) => done.push(app.exe_path),
+            Err(e) => errors.push(format!("Block network {}: {}", path, e)),
+        }
+    }
+    done
+}
+
+/// Drops an exited process, resuming what its game suspended when it is one.
+/// Returns the profile of a game, whose session rules are still to release.
+fn forget(pid: u32) -> Option<GameProfile> {
+    if let Ok(mut followed) = FOLLOWED.lock() {
+        followed.remove(&pid);
+    }
+    let game = GAMES.lock().ok().and_then(|mut games| games.remove(&pid));
+    if let Ok(mut family) = FAMILY.lock() {
+        family.remove(&pid);
+        if game.is_some() {
+            family.retain(|_, game_pid| *game_pid != pid);
+        }
+    }
+    let game = game?;
+    if game.profile.frame_limit.is_some() {
+        frame_limiter::release_game(pid);
+    }
+    for pid in game.suspended {
+        // The process may have exited in the meantime
+        let _ = process_control::resume_process(pid);
+    }
+    Some(game.profile)
+}
@@ -0,0 +1,538 @@
+// Night-time maintenance: cleans old temporary files, retrims SSDs, purges the
+// standby list and rotates Aura's logs once a day at the configured time. The
+// run waits until nobody has used the PC for a while and a laptop is on the
+// charger, and gives up for the day when that doesn't happen within a few
+// hours. Every run, scheduled or started by hand, goes to the results log.
+use crate::models::system_stats::{
+    MaintenanceConfig, MaintenanceRun, MaintenanceTask, MaintenanceTaskResult,
+};
+use crate::services::{memory_breakdown, quiet_hours, user_idle};
+use crate::shared::{dates, events, paths};
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+#[cfg(target_os = "windows")]
+use ntapi::ntexapi::{
+    MemoryPurgeStandbyList, NtSetSystemInformation, SystemMemoryListInformation,
+    SYSTEM_MEMORY_LIST_COMMAND,
+};
+#[cfg(target_os = "windows")]
+use ntapi::ntrtl::RtlAdjustPrivilege;
+#[cfg(target_os = "windows")]
+use ntapi::ntseapi::SE_PROF_SINGLE_PROCESS_PRIVILEGE;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::process::Command;
+
+const CONFIG_FILE: &str = "maintenance.json";
+const LOG_FILE: &str = "maintenance_log.json";
+const MAX_LOGGED_RUNS: usize = 30;
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// How long after the configured time the run may still start
+const RUN_WINDOW_MINUTES: u32 = 180;
+const IDLE_BEFORE_RUN: Duration = Duration::from_secs(15 * 60);
+// Temporary files untouched for this long are no longer in use
+const TEMP_MIN_AGE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+const MAX_DEPTH: usize = 12;
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+const ROTATED_LOGS_KEPT: u32 = 3;
+
+static CONFIG: once_cell::sync::Lazy<Mutex<MaintenanceConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+static LOG: once_cell::sync::Lazy<Mutex<Vec<MaintenanceRun>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_log()));
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static SCHEDULER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Default, PartialEq)]
+struct Removal {
+    freed: u64,
+    deleted: u32,
+    skipped: u32, // in use or not ours to delete
+}
+
+/// Starts the daily schedule once. Subsequent calls do nothing.
+pub fn start() {
+    if SCHEDULER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| loop {
+        std::thread::sleep(CHECK_INTERVAL);
+        let config = get_config();
+        if !config.enabled || config.tasks.is_empty() || RUNNING.load(Ordering::SeqCst) {
+            continue;
+        }
+        let Some(start) = quiet_hours::parse_time(&config.time) else {
+            continue;
+        };
+        let last_age =
+            last_scheduled_run().map(|started| events::now_millis().saturating_sub(started));
+        if !is_due(dates::local_minutes_of_day(), start, last_age) {
+            continue;
+        }
+        if config.require_idle && !user_away() {
+            continue;
+        }
+        if config.require_ac_power && on_ac_power() == Some(false) {
+            continue;
+        }
+        if let Err(e) = run(&config.tasks, true) {
+            eprintln!("Scheduled maintenance failed: {}", e);
+        }
+    });
+}
+
+pub fn get_config() -> MaintenanceConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: MaintenanceConfig) -> Result<MaintenanceConfig> {
+    if quiet_hours::parse_time(&config.time).is_none() {
+        return Err(anyhow!("Invalid time, expected HH:MM: {}", config.time));
+    }
+    let config = MaintenanceConfig {
+        time: config.time.trim().to_string(),
+        tasks: unique(&config.tasks),
+        ..config
+    };
+
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    Ok(config)
+}
+
+/// Runs the given tasks now, the configured ones when None
+pub fn run_now(tasks: Option<Vec<MaintenanceTask>>) -> Result<MaintenanceRun> {
+    let tasks = tasks.unwrap_or_else(|| get_config().tasks);
+    if tasks.is_empty() {
+        return Err(anyhow!("No maintenance task selected"));
+    }
+    run(&unique(&tasks), false)
+}
+
+/// Past runs, oldest first
+pub fn get_log() -> Vec<MaintenanceRun> {
+    LOG.lock().map(|log| log.clone()).unwrap_or_default()
+}
+
+fn run(tasks: &[MaintenanceTask], scheduled: bool) -> Result<MaintenanceRun> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("Maintenance is already running"));
+    }
+    let started = events::now_millis();
+    let results = tasks.iter().map(|&task| run_task(task)).collect();
+    RUNNING.store(false, Ordering::SeqCst);
+
+    let run = MaintenanceRun {
+        started,
+        finished: events::now_millis(),
+        scheduled,
+        results,
+    };
+    if let Ok(mut log) = LOG.lock() {
+        log.push(run.clone());
+        let excess = log.len().saturating_sub(MAX_LOGGED_RUNS);
+        log.drain(..excess);
+        save_log(&log);
+    }
+    events::emit(events::MAINTENANCE_COMPLETED, run.clone());
+    Ok(run)
+}
+
+fn run_task(task: MaintenanceTask) -> MaintenanceTaskResult {
+    let outcome = match task {
+        MaintenanceTask::TempCleanup => clean_temp(),
+        MaintenanceTask::Retrim => retrim().map(|message| (message, None)),
+        MaintenanceTask::StandbyPurge => purge_standby(),
+        MaintenanceTask::LogRotation => rotate_logs(),
+    };
+    match outcome {
+        Ok((message, freed_bytes)) => MaintenanceTaskResult {
+            task,
+            success: true,
+            message,
+            freed_bytes,
+        },
+        Err(e) => MaintenanceTaskResult {
+            task,
+            success: false,
+            message: e.to_string(),
+            freed_bytes: None,
+        },
+    }
+}
+
+fn clean_temp() -> Result<(String, Option<u64>)> {
+    let cutoff = SystemTime::now() - TEMP_MIN_AGE;
+    let mut removal = Removal::default();
+    for dir in temp_dirs() {
+        clean_dir(&dir, cutoff, 0, &mut removal);
+    }
+    Ok((
+        format!(
+            "Deleted {} files, {} in use or protected",
+            removal.deleted, removal.skipped
+        ),
+        Some(removal.freed),
+    ))
+}
+
+fn temp_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![std::env::temp_dir()];
+    // The system's own, writable when Aura runs as administrator
+    #[cfg(target_os = "windows")]
+    if let Some(root) = std::env::var_os("SystemRoot") {
+        let system_temp = PathBuf::from(root).join("Temp");
+        if !dirs.contains(&system_temp) {
+            dirs.push(system_temp);
+        }
+    }
+    dirs
+}
+
+/// Deletes the regular files below `dir` last modified before `cutoff`, and
+/// the folders that leaves empty. Links are never followed. Returns whether
+/// `dir` is empty afterwards.
+fn clean_dir(dir: &Path, cutoff: SystemTime, depth: usize, removal: &mut Removal) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    let mut empty = true;
+    for entry in entries.flatten() {
+        // Sockets and lock files of running sessions live next to the files
+        // in /tmp, hidden by convention
+        if cfg!(not(target_os = "windows")) && entry.file_name().to_string_lossy().starts_with('.')
+        {
+            empty = false;
+            continue;
+        }
+        let Ok(metadata) = entry.path().symlink_metadata() else {
+            empty = false;
+            continue;
+        };
+        let old = metadata.modified().is_ok_and(|modified| modified < cutoff);
+        let path = entry.path();
+
+        if metadata.is_dir() {
+            let emptied = depth < MAX_DEPTH && clean_dir(&path, cutoff, depth + 1, removal);
+            if !(emptied && old && std::fs::remove_dir(&path).is_ok()) {
+                empty = false;
+            }
+        } else if metadata.is_file() && old {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    removal.freed += metadata.len();
+                    removal.deleted += 1;
+                }
+                Err(_) => {
+                    removal.skipped += 1;
+                    empty = false;
+                }
+            }
+        } else {
+            empty = false;
+        }
+    }
+    empty
+}
+
+#[cfg(target_os = "windows")]
+fn retrim() -> Result<String> {
+    // Every volume, SSDs get a retrim and the others are left alone
+    let output = Command::new("defrag")
+        .args(["/C", "/L"])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let reason = stdout.lines().rev().find(|line| !line.trim().is_empty());
+        return Err(anyhow!(
+            "defrag failed, it needs administrator rights: {}",
+            reason.unwrap_or_default().trim()
+        ));
+    }
+    Ok("Retrimmed the SSD volumes".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn retrim() -> Result<String> {
+    let output = Command::new("fstrim")
+        .args(["--all", "--verbose"])
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "fstrim failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    // One line per trimmed mount, like "/: 12 GiB (...) trimmed on /dev/nvme0n1p2"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn retrim() -> Result<String> {
+    Err(anyhow!("Retrim is not supported on this platform"))
+}
+
+#[cfg(target_os = "windows")]
+fn purge_standby() -> Result<(String, Option<u64>)> {
+    let before = memory_breakdown::get_breakdown().standby;
+    let mut was_enabled = 0u8;
+    let status = unsafe {
+        RtlAdjustPrivilege(
+            SE_PROF_SINGLE_PROCESS_PRIVILEGE as u32,
+            1,
+            0,
+            &mut was_enabled,
+        )
+    };
+    if status < 0 {
+        return Err(anyhow!(
+            "Purging the standby list needs administrator rights"
+        ));
+    }
+    let mut command: SYSTEM_MEMORY_LIST_COMMAND = MemoryPurgeStandbyList;
+    let status = unsafe {
+        NtSetSystemInformation(
+            SystemMemoryListInformation,
+            (&mut command as *mut SYSTEM_MEMORY_LIST_COMMAND).cast(),
+            std::mem::size_of::<SYSTEM_MEMORY_LIST_COMMAND>() as u32,
+        )
+    };
+    if status < 0 {
+        return Err(anyhow!(
+            "Failed to purge the standby list (status {:#x})",
+            status
+        ));
+    }
+    Ok(standby_freed(before))
+}
+
+#[cfg(target_os = "linux")]
+fn purge_standby() -> Result<(String, Option<u64>)> {
+    let before = memory_breakdown::get_breakdown().standby;
+    // Dirty pages can't be dropped, write them out first
+    unsafe { libc::sync() };
+    std::fs::write("/proc/sys/vm/drop_caches", "1")
+        .map_err(|e| anyhow!("Failed to drop the page cache, it needs root: {}", e))?;
+    Ok(standby_freed(before))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn purge_standby() -> Result<(String, Option<u64>)> {
+    Err(anyhow!(
+        "Purging cached memory is not supported on this platform"
+    ))
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn standby_freed(before: Option<u64>) -> (String, Option<u64>) {
+    let after = memory_breakdown::get_breakdown().standby;
+    let freed = before
+        .zip(after)
+        .map(|(before, after)| before.saturating_sub(after));
+    ("Purged the cached memory".to_string(), freed)
+}
+
+fn rotate_logs() -> Result<(String, Option<u64>)> {
+    let data_dir = paths::app_data_dir();
+    let (mut rotated, mut freed) = (0, 0);
+    for dir in [data_dir.join("logs"), data_dir] {
+        let (dir_rotated, dir_freed) = rotate_dir(&dir, MAX_LOG_SIZE, ROTATED_LOGS_KEPT);
+        rotated += dir_rotated;
+        freed += dir_freed;
+    }
+    Ok((format!("Rotated {} log files", rotated), Some(freed)))
+}
+
+/// Renames every .log file above `max_size` to .log.1, shifting the older
+/// copies up to `keep` and deleting the one beyond. Returns the files
+/// rotated and the bytes deleted.
+fn rotate_dir(dir: &Path, max_size: u64, keep: u32) -> (u32, u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    let (mut rotated, mut freed) = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "log")
+            || !entry
+                .metadata()
+                .is_ok_and(|metadata| metadata.is_file() && metadata.len() > max_size)
+        {
+            continue;
+        }
+        let copy = |n: u32| PathBuf::from(format!("{}.{}", path.display(), n));
+
+        if let Ok(oldest) = copy(keep).metadata() {
+            if std::fs::remove_file(copy(keep)).is_ok() {
+                freed += oldest.len();
+            }
+        }
+        for n in (1..keep).rev() {
+            let _ = std::fs::rename(copy(n), copy(n + 1));
+        }
+        if std::fs::rename(&path, copy(1)).is_ok() {
+            rotated += 1;
+        }
+    }
+    (rotated, freed)
+}
+
+/// Whether the daily run should start now: within RUN_WINDOW_MINUTES of the
+/// configured time, and not already run in this window
+fn is_due(now: u32, start: u32, last_run_age_ms: Option<u64>) -> bool {
+    let since_start = (now + 24 * 60 - start) % (24 * 60);
+    since_start < RUN_WINDOW_MINUTES
+        && last_run_age_ms.is_none_or(|age| age > RUN_WINDOW_MINUTES as u64 * 60_000)
+}
+
+fn last_scheduled_run() -> Option<u64> {
+    get_log()
+        .iter()
+        .rev()
+        .find(|run| run.scheduled)
+        .map(|run| run.started)
+}
+
+/// Nobody has touched keyboard or mouse for a while. Where the last input
+/// can't be read the user is taken to be away, as at night.
+fn user_away() -> bool {
+    let status = user_idle::get_status();
+    !status.supported || status.idle_ms >= IDLE_BEFORE_RUN.as_millis() as u64
+}
+
+/// Whether the PC runs on mains power, None for desktops and when unknown
+#[cfg(target_os = "windows")]
+pub(crate) fn on_ac_power() -> Option<bool> {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    match status.ACLineStatus {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+/// Whether the PC runs on mains power, None for desktops and when unknown
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn on_ac_power() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let online: Vec<bool> = entries
+        .flatten()
+        .filter(|entry| {
+            std::fs::read_to_string(entry.path().join("type")).is_ok_and(|t| t.trim() == "Mains")
+        })
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("online")).ok())
+        .map(|online| online.trim() == "1")
+        .collect();
+    (!online.is_empty()).then(|| online.contains(&true))
+}
+
+fn unique(tasks: &[MaintenanceTask]) -> Vec<MaintenanceTask> {
+    let mut unique = Vec::new();
+    for &task in tasks {
+        if !unique.contains(&task) {
+            unique.push(task);
+        }
+    }
+    unique
+}
+
+fn load_config() -> MaintenanceConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+fn load_log() -> Vec<MaintenanceRun> {
+    paths::load_json(LOG_FILE)
+}
+
+fn save_log(log: &[MaintenanceRun]) {
+    if let Err(e) = paths::save_json(LOG_FILE, log) {
+        eprintln!("Failed to save the maintenance log: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_due() {
+        let three_am = 3 * 60;
+        assert!(is_due(three_am, three_am, None));
+        assert!(is_due(5 * 60, three_am, Some(24 * 3_600_000)));
+        assert!(!is_due(2 * 60 + 59, three_am, None));
+        assert!(!is_due(6 * 60, three_am, None));
+        // Already ran tonight
+        assert!(!is_due(4 * 60, three_am, Some(3_600_000)));
+        // Across midnight
+        assert!(is_due(30, 23 * 60, None));
+    }
+
+    #[test]
+    fn test_clean_dir() {
+        let root = std::env::temp_dir().join(format!("aura-maintenance-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("installer")).unwrap();
+        std::fs::write(root.join("old.tmp"), vec![0u8; 100]).unwrap();
+        std::fs::write(root.join("installer").join("setup.cab"), vec![0u8; 50]).unwrap();
+
+        // Nothing is old enough yet
+        let mut removal = Removal::default();
+        assert!(!clean_dir(&root, SystemTime::UNIX_EPOCH, 0, &mut removal));
+        assert_eq!(removal, Removal::default());
+        assert!(root.join("old.tmp").exists());
+
+        let future = SystemTime::now() + Duration::from_secs(60);
+        assert!(clean_dir(&root, future, 0, &mut removal));
+        assert_eq!(removal.deleted, 2);
+        assert_eq!(removal.freed, 150);
+        assert!(!root.join("installer").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_dir() {
+        let root = std::env::temp_dir().join(format!("aura-log-rotation-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let log = root.join("aura.log");
+        std::fs::write(&log, vec![b'a'; 20]).unwrap();
+        std::fs::write(root.join("aura.log.1"), vec![b'b'; 20]).unwrap();
+        std::fs::write(root.join("aura.log.2"), vec![b'c'; 30]).unwrap();
+        std::fs::write(root.join("small.log"), b"ok").unwrap();
+
+        assert_eq!(rotate_dir(&root, 10, 2), (1, 30));
+        assert!(!log.exists());
+        assert_eq!(
+            std::fs::read(root.join("aura.log.1")).unwrap(),
+            vec![b'a'; 20]
+        );
+        assert_eq!(
+            std::fs::read(root.join("aura.log.2")).unwrap(),
+            vec![b'b'; 20]
+        );
+        assert!(!root.join("aura.log.3").exists());
+        assert!(root.join("small.log").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+// Memory micro-benchmark: the four STREAM kernels for bandwidth and a
+// pointer chase through a random cycle for latency, both on buffers far
+// larger than any cache. The configured speed SMBIOS reports tells whether
+// XMP/EXPO is on, and the theoretical bandwidth it implies shows whether the
+// measured one is in line with it.
+use crate::models::benchmark::{MemoryBenchmarkResult, MemoryModuleSpeed};
+use crate::services::{smbios, stress_test};
+use anyhow::{anyhow, Result};
+use rand::Rng;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const STREAM_LEN: usize = 1 << 24; // doubles per array, 128 MiB
+const STREAM_RUNS: usize = 5;
+const CHASE_LEN: usize = 1 << 24; // slots, 128 MiB
+const CHASE_STEPS: usize = 20_000_000;
+const SCALAR: f64 = 3.0;
+// Bus width of a DDR channel in bytes
+const CHANNEL_BYTES: f64 = 8.0;
+
+/// Runs for a few seconds with every core busy, so it refuses to overlap a
+/// stress test
+pub fn run() -> Result<MemoryBenchmarkResult> {
+    if stress_test::is_running() {
+        return Err(anyhow!("Stop the stress test before benchmarking memory"));
+    }
+    let needed = (3 * STREAM_LEN * std::mem::size_of::<f64>()) as u64;
+    if available_memory() < needed * 2 {
+        return Err(anyhow!(
+            "Not enough free memory for the benchmark, {} MB needed",
+            needed * 2 / 1024 / 1024
+        ));
+    }
+
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let [copy_gbps, scale_gbps, add_gbps, triad_gbps] = stream(threads);
+    let latency_ns = latency_ns();
+
+    let (modules, smbios_error) = match smbios::memory_modules() {
+        Ok(modules) => (modules, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    let configured_mts = modules.iter().filter_map(|m| m.configured_mts).min();
+    let theoretical_gbps = configured_mts.map(|mts| theoretical_gbps(mts, modules.len()));
+    let best = [copy_gbps, scale_gbps, add_gbps, triad_gbps]
+        .into_iter()
+        .fold(0.0, f64::max);
+
+    Ok(MemoryBenchmarkResult {
+        threads,
+        copy_gbps,
+        scale_gbps,
+        add_gbps,
+        triad_gbps,
+        latency_ns,
+        xmp_expo_active: xmp_expo_active(&modules),
+        smbios_error,
+        configured_mts,
+        theoretical_gbps,
+        efficiency: theoretical_gbps.map(|theoretical| best / theoretical),
+        modules,
+    })
+}
+
+fn available_memory() -> u64 {
+    crate::shared::system::SYSTEM
+        .lock()
+        .map(|mut system| {
+            system.refresh_memory();
+            system.available_memory()
+        })
+        .unwrap_or(0)
+}
+
+/// Best GB/s of Copy, Scale, Add and Triad, counting bytes the way STREAM does
+fn stream(threads: usize) -> [f64; 4] {
+    let mut a = vec![1.0f64; STREAM_LEN];
+    let mut b = vec![2.0f64; STREAM_LEN];
+    let mut c = vec![0.0f64; STREAM_LEN];
+    let mut best = [Duration::MAX; 4];
+
+    for _ in 0..STREAM_RUNS {
+        let times = [
+            parallel(threads, &mut c, &a, &a, |c, a, _| c.copy_from_slice(a)),
+            parallel(threads, &mut b, &c, &c, |b, c, _| {
+                b.iter_mut().zip(c).for_each(|(b, c)| *b = SCALAR * c)
+            }),
+            parallel(threads, &mut c, &a, &b, |c, a, b| {
+                c.iter_mut()
+                    .zip(a.iter().zip(b))
+                    .for_each(|(c, (a, b))| *c = a + b)
+            }),
+            parallel(threads, &mut a, &b, &c, |a, b, c| {
+                a.iter_mut()
+                    .zip(b.iter().zip(c))
+                    .for_each(|(a, (b, c))| *a = b + SCALAR * c)
+            }),
+        ];
+        for (best, time) in best.iter_mut().zip(times) {
+            *best = (*best).min(time);
+        }
+    }
+    black_box((&a, &b, &c));
+
+    let array_bytes = (STREAM_LEN * std::mem::size_of::<f64>()) as f64;
+    let arrays_touched = [2.0, 2.0, 3.0, 3.0];
+    let mut gbps = [0.0; 4];
+    for (i, time) in best.iter().enumerate() {
+        gbps[i] = arrays_touched[i] * array_bytes / time.as_secs_f64() / 1e9;
+    }
+    gbps
+}
+
+/// Splits the arrays across threads and times one pass of the kernel
+fn parallel<F>(threads: usize, dst: &mut [f64], x: &[f64], y: &[f64], kernel: F) -> Duration
+where
+    F: Fn(&mut [f64], &[f64], &[f64]) + Sync,
+{
+    let chunk = dst.len().div_ceil(threads);
+    let kernel = &kernel;
+    let started = Instant::now();
+    std::thread::scope(|scope| {
+        for (i, out) in dst.chunks_mut(chunk).enumerate() {
+            let range = i * chunk..i * chunk + out.len();
+            let (x, y) = (&x[range.clone()], &y[range]);
+            scope.spawn(move || kernel(out, x, y));
+        }
+    });
+    started.elapsed()
+}
+
+/// Average time of a dependent load that misses every cache
+fn latency_ns() -> f64 {
+    // Sattolo's shuffle: one cycle through every slot, so the chase never
+    // settles in a short loop the caches could hold
+    let mut next: Vec<usize> = (0..CHASE_LEN).collect();
+    let mut rng = rand::rng();
+    for i in (1..CHASE_LEN).rev() {
+        next.swap(i, rng.random_range(0..i));
+    }
+
+    let mut index = 0;
+    let started = Instant::now();
+    for _ in 0..CHASE_STEPS {
+        index = next[index];
+    }
+    let elapsed = started.elapsed();
+    black_box(index);
+    elapsed.as_nanos() as f64 / CHASE_STEPS as f64
+}
+
+/// One channel per module, up to the two of desktop platforms
+fn theoretical_gbps(configured_mts: u32, module_count: usize) -> f64 {
+    let channels = module_count.clamp(1, 2) as f64;
+    configured_mts as f64 * CHANNEL_BYTES * channels / 1000.0
+}
+
+/// Highest speed each memory type reaches without an overclocking profile
+fn jedec_ceiling(memory_type: &str) -> Option<u32> {
+    match memory_type {
+        "DDR3" => Some(2133),
+        "DDR4" => Some(3200),
+        "DDR5" => Some(6400),
+        _ => None,
+    }
+}
+
+fn xmp_expo_active(modules: &[MemoryModuleSpeed]) -> Option<bool> {
+    let votes: Vec<bool> = modules
+        .iter()
+        .filter_map(|module| {
+            let configured = module.configured_mts?;
+            let above_ceiling = module
+                .memory_type
+                .as_deref()
+                .and_then(jedec_ceiling)
+                .is_some_and(|ceiling| configured > ceiling);
+            match module.rated_mts {
+                _ if above_ceiling => Some(true),
+                // SMBIOS reports the JEDEC rating, a profile runs above it
+                Some(rated) if configured > rated => Some(true),
+                Some(rated) if configured < rated => Some(false),
+                _ => None,
+            }
+        })
+        .collect();
+    match votes.first() {
+        Some(first) if votes.iter().all(|vote| vote == first) => Some(*first),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(memory_type: &str, rated: u32, configured: u32) -> MemoryModuleSpeed {
+        MemoryModuleSpeed {
+            locator: "DIMM".to_string(),
+            memory_type: Some(memory_type.to_string()),
+            rated_mts: Some(rated),
+            configured_mts: Some(configured),
+        }
+    }
+
+    #[test]
+    fn test_xmp_expo_active() {
+        assert_eq!(xmp_expo_active(&[module("DDR5", 4800, 6000)]), Some(true));
+        assert_eq!(xmp_expo_active(&[module("DDR4", 3600, 3600)]), Some(true));
+        assert_eq!(xmp_expo_active(&[module("DDR4", 3200, 2133)]), Some(false));
+        assert_eq!(xmp_expo_active(&[module("DDR4", 2133, 2133)]), None);
+        assert_eq!(
+            xmp_expo_active(&[module("DDR5", 4800, 6000), module("DDR5", 5600, 4800)]),
+            None
+        );
+        assert_eq!(xmp_expo_active(&[]), None);
+    }
+
+    #[test]
+    fn test_theoretical_gbps() {
+        assert_eq!(theoretical_gbps(6000, 2), 96.0);
+        assert_eq!(theoretical_gbps(3200, 4), 51.2);
+        assert_eq!(theoretical_gbps(3200, 1), 25.6);
+    }
+
+    #[test]
+    fn test_parallel_covers_every_element() {
+        let mut dst = vec![0.0; 1001];
+        let src: Vec<f64> = (0..1001).map(|i| i as f64).collect();
+        parallel(3, &mut dst, &src, &src, |d, x, y| {
+            d.iter_mut()
+                .zip(x.iter().zip(y))
+                .for_each(|(d, (x, y))| *d = x + y)
+        });
+        assert!(dst.iter().enumerate().all(|(i, v)| *v == 2.0 * i as f64));
+    }
+}
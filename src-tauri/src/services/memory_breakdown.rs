@@ -0,0 +1,261 @@
+// Commit charge, caches, kernel pools and compressed memory, the figures
+// Task Manager and RAMMap show next to used and free. On Windows they come
+// from GetPerformanceInfo and the Memory performance counters, on Linux from
+// /proc/meminfo, zswap and zram. Page and hard fault rates come from the
+// Memory counters on Windows and /proc/vmstat on Linux.
+use crate::models::system_stats::MemoryBreakdown;
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[cfg(target_os = "windows")]
+use windows::core::{w, PCWSTR};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Performance::{
+    PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterValue,
+    PdhGetRawCounterValue, PdhOpenQueryW, PDH_FMT_COUNTERVALUE, PDH_FMT_LARGE, PDH_HCOUNTER,
+    PDH_HQUERY, PDH_RAW_COUNTER,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::ProcessStatus::{GetPerformanceInfo, PERFORMANCE_INFORMATION};
+
+// Fault totals of the previous call, rates are the difference to it
+static LAST_FAULTS: once_cell::sync::Lazy<Mutex<Option<(u64, u64, Instant)>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+#[cfg(target_os = "windows")]
+pub fn get_breakdown() -> MemoryBreakdown {
+    let mut breakdown = MemoryBreakdown::default();
+
+    let mut info = PERFORMANCE_INFORMATION {
+        cb: std::mem::size_of::<PERFORMANCE_INFORMATION>() as u32,
+        ..Default::default()
+    };
+    if unsafe { GetPerformanceInfo(&mut info, info.cb) }.is_ok() {
+        let bytes = |pages: usize| (pages * info.PageSize) as u64;
+        breakdown.committed = Some(bytes(info.CommitTotal));
+        breakdown.commit_limit = Some(bytes(info.CommitLimit));
+        breakdown.commit_peak = Some(bytes(info.CommitPeak));
+        breakdown.cached = Some(bytes(info.SystemCache));
+        breakdown.paged_pool = Some(bytes(info.KernelPaged));
+        breakdown.nonpaged_pool = Some(bytes(info.KernelNonpaged));
+    }
+
+    // The standby list is split by priority, Task Manager adds the three up
+    let values = query_counters(&[
+        w!("\\Memory\\Standby Cache Core Bytes"),
+        w!("\\Memory\\Standby Cache Normal Priority Bytes"),
+        w!("\\Memory\\Standby Cache Reserve Bytes"),
+        w!("\\Memory\\Modified Page List Bytes"),
+        w!("\\Process(MemCompression)\\Working Set"),
+    ]);
+    if let [Some(core), Some(normal), Some(reserve), modified, compressed] = values[..] {
+        breakdown.standby = Some(core + normal + reserve);
+        breakdown.modified = modified;
+        // What Task Manager calls cached
+        breakdown.cached = Some(core + normal + reserve + modified.unwrap_or(0));
+        breakdown.compressed = compressed;
+    }
+
+    // Rate counters need two samples a query would have to wait between, the
+    // raw totals are compared with the previous call instead
+    let totals = query_raw_counters(&[w!("\\Memory\\Page Faults/sec"), w!("\\Memory\\Pages/sec")]);
+    if let [Some(page_faults), Some(hard_faults)] = totals[..] {
+        (breakdown.page_faults_per_sec, breakdown.hard_faults_per_sec) =
+            fault_rates(page_faults, hard_faults);
+    }
+    breakdown
+}
+
+/// Current value of each counter, None for the ones that can't be read
+#[cfg(target_os = "windows")]
+fn query_counters(paths: &[PCWSTR]) -> Vec<Option<u64>> {
+    query_with(paths, |counter| unsafe {
+        let mut value = PDH_FMT_COUNTERVALUE::default();
+        let status = PdhGetFormattedCounterValue(counter, PDH_FMT_LARGE, None, &mut value);
+        (status == 0 && value.CStatus == 0).then(|| value.Anonymous.largeValue.max(0) as u64)
+    })
+}
+
+/// Running total behind each counter, for the ones that count events
+#[cfg(target_os = "windows")]
+pub(crate) fn query_raw_counters(paths: &[PCWSTR]) -> Vec<Option<u64>> {
+    query_with(paths, |counter| unsafe {
+        let mut value = PDH_RAW_COUNTER::default();
+        let status = PdhGetRawCounterValue(counter, None, &mut value);
+        (status == 0 && value.CStatus == 0).then(|| value.FirstValue.max(0) as u64)
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn query_with(paths: &[PCWSTR], read: impl Fn(PDH_HCOUNTER) -> Option<u64>) -> Vec<Option<u64>> {
+    unsafe {
+        let mut query = PDH_HQUERY::default();
+        if PdhOpenQueryW(None, 0, &mut query) != 0 {
+            return vec![None; paths.len()];
+        }
+
+        let counters: Vec<Option<PDH_HCOUNTER>> = paths
+            .iter()
+            .map(|path| {
+                let mut counter = PDH_HCOUNTER::default();
+                (PdhAddEnglishCounterW(query, *path, 0, &mut counter) == 0).then_some(counter)
+            })
+            .collect();
+
+        let values = if PdhCollectQueryData(query) == 0 {
+            counters.iter().map(|counter| read((*counter)?)).collect()
+        } else {
+            vec![None; paths.len()]
+        };
+
+        let _ = PdhCloseQuery(query);
+        values
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_breakdown() -> MemoryBreakdown {
+    let mut breakdown = std::fs::read_to_string("/proc/meminfo")
+        .map(|meminfo| parse_meminfo(&meminfo))
+        .unwrap_or_default();
+    if let Some(zram) = zram_used() {
+        breakdown.compressed = Some(breakdown.compressed.unwrap_or(0) + zram);
+    }
+    if let Some((page_faults, hard_faults)) = std::fs::read_to_string("/proc/vmstat")
+        .ok()
+        .and_then(|vmstat| parse_vmstat_faults(&vmstat))
+    {
+        (breakdown.page_faults_per_sec, breakdown.hard_faults_per_sec) =
+            fault_rates(page_faults, hard_faults);
+    }
+    breakdown
+}
+
+/// Total page faults and major faults since boot
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn parse_vmstat_faults(vmstat: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| -> Option<u64> {
+        vmstat.lines().find_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            if key != name {
+                return None;
+            }
+            value.trim().parse().ok()
+        })
+    };
+    Some((field("pgfault")?, field("pgmajfault")?))
+}
+
+/// RAM taken by every zram device, compressed data and allocator overhead
+#[cfg(not(target_os = "windows"))]
+fn zram_used() -> Option<u64> {
+    let devices = std::fs::read_dir("/sys/block").ok()?;
+    let used: Vec<u64> = devices
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("zram"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("mm_stat")).ok())
+        // orig_data_size compr_data_size mem_used_total ...
+        .filter_map(|stat| stat.split_whitespace().nth(2)?.parse().ok())
+        .collect();
+    (!used.is_empty()).then(|| used.iter().sum())
+}
+
+/// The closest /proc/meminfo equivalents. Linux has no kernel pools in the
+/// Windows sense, those stay None.
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn parse_meminfo(meminfo: &str) -> MemoryBreakdown {
+    let field = |name: &str| -> Option<u64> {
+        meminfo.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            if key.trim() != name {
+                return None;
+            }
+            let kilobytes: u64 = value.split_whitespace().next()?.parse().ok()?;
+            Some(kilobytes * 1024)
+        })
+    };
+    let sum = |names: &[&str]| -> Option<u64> {
+        let values: Vec<u64> = names.iter().filter_map(|name| field(name)).collect();
+        (!values.is_empty()).then(|| values.iter().sum())
+    };
+
+    MemoryBreakdown {
+        committed: field("Committed_AS"),
+        commit_limit: field("CommitLimit"),
+        commit_peak: None,
+        cached: sum(&["Cached", "Buffers"]),
+        // Clean file pages the kernel drops first, like the standby list
+        standby: field("Inactive(file)"),
+        modified: sum(&["Dirty", "Writeback"]),
+        paged_pool: None,
+        nonpaged_pool: None,
+        compressed: field("Zswap"),
+        page_faults_per_sec: None,
+        hard_faults_per_sec: None,
+    }
+}
+
+/// Page and hard faults per second since the previous call, None on the first
+fn fault_rates(page_faults: u64, hard_faults: u64) -> (Option<u64>, Option<u64>) {
+    let now = Instant::now();
+    let Ok(mut last) = LAST_FAULTS.lock() else {
+        return (None, None);
+    };
+    let rates = match *last {
+        Some((last_page_faults, last_hard_faults, at)) => {
+            let elapsed = now.duration_since(at).as_secs_f64();
+            (
+                per_second(last_page_faults, page_faults, elapsed),
+                per_second(last_hard_faults, hard_faults, elapsed),
+            )
+        }
+        None => (None, None),
+    };
+    *last = Some((page_faults, hard_faults, now));
+    rates
+}
+
+fn per_second(previous: u64, current: u64, elapsed_secs: f64) -> Option<u64> {
+    (elapsed_secs > 0.0).then(|| (current.saturating_sub(previous) as f64 / elapsed_secs) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       32768000 kB\n\
+            Buffers:          100 kB\n\
+            Cached:           900 kB\n\
+            Inactive(file):   400 kB\n\
+            Dirty:             20 kB\n\
+            Writeback:          4 kB\n\
+            CommitLimit:     2000 kB\n\
+            Committed_AS:    1500 kB\n";
+        let breakdown = parse_meminfo(meminfo);
+        assert_eq!(breakdown.cached, Some(1000 * 1024));
+        assert_eq!(breakdown.standby, Some(400 * 1024));
+        assert_eq!(breakdown.modified, Some(24 * 1024));
+        assert_eq!(breakdown.committed, Some(1500 * 1024));
+        assert_eq!(breakdown.commit_limit, Some(2000 * 1024));
+        // No zswap line on kernels without it
+        assert_eq!(breakdown.compressed, None);
+        assert_eq!(breakdown.paged_pool, None);
+    }
+
+    #[test]
+    fn test_parse_vmstat_faults() {
+        let vmstat = "pgfree 812345\npgfault 5531002\npgmajfault 4120\npgrefill 0\n";
+        assert_eq!(parse_vmstat_faults(vmstat), Some((5_531_002, 4120)));
+        assert_eq!(parse_vmstat_faults("pgfault 12\n"), None);
+    }
+
+    #[test]
+    fn test_per_second() {
+        assert_eq!(per_second(1000, 3000, 2.0), Some(1000));
+        // Counters reset by a reboot of the counter provider
+        assert_eq!(per_second(3000, 1000, 2.0), Some(0));
+        assert_eq!(per_second(1000, 3000, 0.0), None);
+    }
+}
@@ -1,8 +1,77 @@
-pub mod gpu_service;
-pub mod optimization_service;
+pub mod anti_cheat;
+pub mod audio_service;
+pub mod cooling;
+pub mod cpu_heatmap;
+pub mod cpu_overhead;
+pub mod cpu_tuning;
+pub mod crash_monitor;
+pub mod data_usage;
+pub mod defender;
+pub mod device_guard;
+pub mod device_inventory;
+pub mod direct_storage;
+pub mod discord_presence;
+pub mod disk_map;
+pub mod display_modes;
+pub mod display_settings;
+pub mod drive_watch;
+pub mod energy_monitor;
+pub mod file_cleanup;
+pub mod folder_move;
+pub mod frame_limiter;
+pub mod game_library;
+pub mod game_profiles;
+pub mod gaming_services;
+pub mod gpu;
+pub mod gpu_processes;
+pub mod gpu_tuning;
+pub mod handle_monitor;
+pub mod hardware_watch;
+pub mod hosts_blocklist;
+pub mod input_latency;
+pub mod job_groups;
+pub mod launch_rules;
+pub mod maintenance;
+pub mod memory_benchmark;
+pub mod memory_breakdown;
+pub mod monitor_history;
+pub mod mqtt;
+pub mod network_block;
+pub mod network_tuning;
+pub mod obs;
+pub mod optimization_engine;
+pub mod os_version;
+pub mod platform_features;
+pub mod plugin_host;
+pub mod port_forwarding;
+pub mod power_plan;
+pub mod process_cleanup;
 pub mod process_control;
+pub mod process_history;
 pub mod process_info;
+pub mod process_report;
+pub mod process_security;
 pub mod process_service;
+pub mod process_watcher;
+pub mod qos;
+pub mod quiet_hours;
+pub mod readiness;
+pub mod recovery;
+pub mod recycle_bin;
+pub mod remote_monitor;
+pub mod rest_api;
+pub mod service_host;
+pub mod session_report;
+pub mod smbios;
+pub mod stats_line;
+pub mod stress_test;
+pub mod system_health;
+pub mod temperature_history;
+pub mod timer_resolution;
+pub mod updater;
+pub mod user_idle;
+pub mod window_control;
+pub mod windows_update;
 
 // Re-export delle funzioni più utilizzate
 pub use process_control::{kill_process, resume_process, set_process_affinity, suspend_process};
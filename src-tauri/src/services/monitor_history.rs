@@ -0,0 +1,153 @@
+use crate::shared::{events::now_millis, paths};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const HISTORY_FILE: &str = "monitor_health.json";
+// Failing in this many separate launches makes a subsystem a chronic issue
+const CHRONIC_SESSIONS: u32 = 3;
+
+/// Failures of a monitored subsystem over every launch of the app
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SubsystemErrorHistory {
+    pub total_errors: u64,
+    pub sessions_with_errors: u32,
+    pub last_failure: Option<String>,
+    pub last_failure_at: Option<u64>, // Unix millis
+    // Launch that last counted towards sessions_with_errors
+    last_session: u64,
+}
+
+/// A subsystem that keeps failing launch after launch
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChronicIssue {
+    pub subsystem: String,
+    pub sessions_with_errors: u32,
+    pub total_errors: u64,
+    pub last_failure: Option<String>,
+    pub remediation: String,
+}
+
+// Identifies the current launch
+static SESSION_STARTED: once_cell::sync::Lazy<u64> = once_cell::sync::Lazy::new(now_millis);
+
+static HISTORY: once_cell::sync::Lazy<Mutex<HashMap<String, SubsystemErrorHistory>>> =
+    once_cell::sync::Lazy::new(|| {
+        let history = std::fs::read_to_string(paths::app_data_file(HISTORY_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Mutex::new(history)
+    });
+
+/// Records a failed fetch of a subsystem and persists it
+pub fn record_failure(subsystem: &str, reason: &str) {
+    if let Ok(mut history) = HISTORY.lock() {
+        let entry = history.entry(subsystem.to_string()).or_default();
+        add_failure(entry, reason, now_millis(), *SESSION_STARTED);
+        save(&history);
+    }
+}
+
+pub fn get_error_history() -> HashMap<String, SubsystemErrorHistory> {
+    HISTORY
+        .lock()
+        .map(|history| history.clone())
+        .unwrap_or_default()
+}
+
+/// Subsystems that failed in enough launches to be worth fixing, with what
+/// the user can do about it
+pub fn get_chronic_issues() -> Vec<ChronicIssue> {
+    HISTORY
+        .lock()
+        .map(|history| chronic_issues(&history))
+        .unwrap_or_default()
+}
+
+/// Forgets every recorded failure, e.g. after the user fixed the cause
+pub fn clear() {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.clear();
+        save(&history);
+    }
+}
+
+fn save(history: &HashMap<String, SubsystemErrorHistory>) {
+    if let Ok(content) = serde_json::to_string_pretty(history) {
+        let _ = std::fs::write(paths::app_data_file(HISTORY_FILE), content);
+    }
+}
+
+fn add_failure(entry: &mut SubsystemErrorHistory, reason: &str, now: u64, session: u64) {
+    entry.total_errors += 1;
+    if entry.last_session != session {
+        entry.sessions_with_errors += 1;
+        entry.last_session = session;
+    }
+    entry.last_failure = Some(reason.to_string());
+    entry.last_failure_at = Some(now);
+}
+
+fn chronic_issues(history: &HashMap<String, SubsystemErrorHistory>) -> Vec<ChronicIssue> {
+    let mut issues: Vec<ChronicIssue> = history
+        .iter()
+        .filter(|(_, entry)| entry.sessions_with_errors >= CHRONIC_SESSIONS)
+        .map(|(subsystem, entry)| ChronicIssue {
+            subsystem: subsystem.clone(),
+            sessions_with_errors: entry.sessions_with_errors,
+            total_errors: entry.total_errors,
+            last_failure: entry.last_failure.clone(),
+            remediation: remediation(subsystem).to_string(),
+        })
+        .collect();
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.sessions_with_errors));
+    issues
+}
+
+fn remediation(subsystem: &str) -> &'static str {
+    match subsystem {
+        "cpu" => "Update the chipset drivers. Temperatures need the app to run as administrator",
+        "memory" => "Check that no security software blocks Aura from querying system memory",
+        "storage" => "Check the disks in the system's disk manager, a failing or disconnected drive can stall the query",
+        "network" => "Update or reinstall the network adapter drivers and disable virtual adapters that are not in use",
+        "gpu" => "Update the graphics driver. On laptops, make sure the dedicated GPU is not disabled",
+        "system" => "Run the app as administrator, some system information requires elevated rights",
+        _ => "Restart the app. If the problem persists, report it with the last failure reason",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_counted_once_per_launch() {
+        let mut entry = SubsystemErrorHistory::default();
+        add_failure(&mut entry, "timeout", 10, 1);
+        add_failure(&mut entry, "driver error", 20, 1);
+        add_failure(&mut entry, "driver error", 30, 2);
+        assert_eq!(entry.total_errors, 3);
+        assert_eq!(entry.sessions_with_errors, 2);
+        assert_eq!(entry.last_failure.as_deref(), Some("driver error"));
+        assert_eq!(entry.last_failure_at, Some(30));
+    }
+
+    #[test]
+    fn test_chronic_issues() {
+        let mut history = HashMap::new();
+        let mut gpu = SubsystemErrorHistory::default();
+        for session in 0..CHRONIC_SESSIONS as u64 {
+            add_failure(&mut gpu, "no backend", session, session + 1);
+        }
+        history.insert("gpu".to_string(), gpu);
+        let mut cpu = SubsystemErrorHistory::default();
+        add_failure(&mut cpu, "timeout", 0, 1);
+        history.insert("cpu".to_string(), cpu);
+
+        let issues = chronic_issues(&history);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].subsystem, "gpu");
+        assert_eq!(issues[0].remediation, remediation("gpu"));
+    }
+}
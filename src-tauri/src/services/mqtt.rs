@@ -0,0 +1,383 @@
+// Publishes stats and game session events to an MQTT broker, announcing the
+// sensors with Home Assistant's discovery format. Speaks just enough MQTT
+// 3.1.1 for that: CONNECT, QoS 0 PUBLISH and PINGREQ.
+use crate::commands::{cpu, memory};
+use crate::models::system_stats::MqttConfig;
+use crate::services::{crash_monitor, quiet_hours};
+use crate::shared::paths;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const CONFIG_FILE: &str = "mqtt.json";
+const TICK: Duration = Duration::from_secs(1);
+const KEEP_ALIVE_SECS: u16 = 60;
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Sensors announced to Home Assistant: key in the state payload, name, unit
+const SENSORS: [(&str, &str, Option<&str>); 3] = [
+    ("cpu", "CPU usage", Some("%")),
+    ("memory", "Memory usage", Some("%")),
+    ("games_running", "Games running", None),
+];
+
+static CONFIG: once_cell::sync::Lazy<Mutex<MqttConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+// Events waiting for the publisher thread, as (event, JSON payload)
+static PENDING_EVENTS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+static PUBLISHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts the publisher thread. It idles while MQTT is disabled, so settings
+/// changes apply without a restart.
+pub fn start() {
+    if PUBLISHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(publisher_loop);
+}
+
+pub fn get_config() -> MqttConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: MqttConfig) -> Result<MqttConfig> {
+    if config.enabled && config.host.trim().is_empty() {
+        return Err(anyhow!("MQTT broker host is required"));
+    }
+    if config.interval_secs == 0 {
+        return Err(anyhow!("MQTT interval must be at least one second"));
+    }
+    if config.base_topic.is_empty() || config.base_topic.contains(['+', '#']) {
+        return Err(anyhow!("Invalid MQTT topic: {}", config.base_topic));
+    }
+
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    Ok(config)
+}
+
+/// Queues an event, e.g. a game starting, for <base_topic>/<device>/event/<name>.
+/// Does nothing while MQTT is disabled.
+pub fn publish_event<T: Serialize>(name: &str, payload: &T) {
+    if !get_config().enabled {
+        return;
+    }
+    if let (Ok(payload), Ok(mut pending)) = (serde_json::to_string(payload), PENDING_EVENTS.lock())
+    {
+        pending.push((name.to_string(), payload));
+    }
+}
+
+fn load_config() -> MqttConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+struct Connection {
+    stream: TcpStream,
+    config: MqttConfig,
+    last_sent: Instant,
+}
+
+fn publisher_loop() {
+    let device = device_id();
+    let mut connection: Option<Connection> = None;
+    let mut last_stats: Option<Instant> = None;
+
+    loop {
+        let config = get_config();
+        // Reconnect when the settings changed under an open connection
+        if connection
+            .as_ref()
+            .is_some_and(|c| !same_broker(&c.config, &config))
+        {
+            connection = None;
+        }
+
+        if !config.enabled {
+            connection = None;
+            if let Ok(mut pending) = PENDING_EVENTS.lock() {
+                pending.clear();
+            }
+            std::thread::sleep(TICK);
+            continue;
+        }
+
+        if connection.is_none() {
+            match connect(&config, &device) {
+                Ok(connected) => {
+                    connection = Some(connected);
+                    last_stats = None;
+                }
+                Err(e) => {
+                    eprintln!("MQTT connection to {} failed: {}", config.host, e);
+                    std::thread::sleep(Duration::from_secs(config.interval_secs));
+                    continue;
+                }
+            }
+        }
+
+        if let Some(active) = connection.as_mut() {
+            // Game events still go out during quiet hours, only sampling stops
+            let stats_due = !quiet_hours::is_paused()
+                && last_stats
+                    .is_none_or(|last| last.elapsed() >= Duration::from_secs(config.interval_secs));
+            if stats_due {
+                last_stats = Some(Instant::now());
+            }
+            if let Err(e) = publish_pending(active, &device, stats_due) {
+                eprintln!("MQTT publish failed: {}", e);
+                connection = None;
+            }
+        }
+        std::thread::sleep(TICK);
+    }
+}
+
+fn same_broker(a: &MqttConfig, b: &MqttConfig) -> bool {
+    a.host == b.host
+        && a.port == b.port
+        && a.username == b.username
+        && a.password == b.password
+        && a.base_topic == b.base_topic
+        && a.discovery_prefix == b.discovery_prefix
+}
+
+fn connect(config: &MqttConfig, device: &str) -> Result<Connection> {
+    let mut stream = TcpStream::connect((config.host.as_str(), config.port))?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    stream.write_all(&connect_packet(
+        &format!("aura-{}", device),
+        config.username.as_deref(),
+        config.password.as_deref(),
+    ))?;
+    let mut connack = [0u8; 4];
+    stream.read_exact(&mut connack)?;
+    if connack[0] != 0x20 || connack[3] != 0 {
+        return Err(anyhow!(
+            "Broker refused the connection (code {})",
+            connack[3]
+        ));
+    }
+
+    let mut connection = Connection {
+        stream,
+        config: config.clone(),
+        last_sent: Instant::now(),
+    };
+    // Retained, so Home Assistant picks the sensors up whenever it starts
+    for (topic, payload) in discovery_messages(config, device) {
+        send(
+            &mut connection,
+            &publish_packet(&topic, payload.as_bytes(), true),
+        )?;
+    }
+    Ok(connection)
+}
+
+fn publish_pending(connection: &mut Connection, device: &str, stats_due: bool) -> Result<()> {
+    let base = format!("{}/{}", connection.config.base_topic, device);
+
+    let events = PENDING_EVENTS
+        .lock()
+        .map(|mut pending| std::mem::take(&mut *pending))
+        .unwrap_or_default();
+    for (name, payload) in events {
+        let topic = format!("{}/event/{}", base, name);
+        send(
+            connection,
+            &publish_packet(&topic, payload.as_bytes(), false),
+        )?;
+    }
+
+    if stats_due {
+        let state = serde_json::to_string(&collect_state())?;
+        let topic = format!("{}/state", base);
+        send(connection, &publish_packet(&topic, state.as_bytes(), false))?;
+    }
+
+    if connection.last_sent.elapsed() >= Duration::from_secs(KEEP_ALIVE_SECS as u64 / 2) {
+        send(connection, &[0xC0, 0x00])?;
+    }
+    drain(&mut connection.stream);
+    Ok(())
+}
+
+fn send(connection: &mut Connection, packet: &[u8]) -> Result<()> {
+    connection.stream.write_all(packet)?;
+    connection.last_sent = Instant::now();
+    Ok(())
+}
+
+// Discards what the broker sends (ping responses), so its writes never block
+fn drain(stream: &mut TcpStream) {
+    if stream.set_nonblocking(true).is_ok() {
+        let mut buffer = [0u8; 256];
+        while matches!(stream.read(&mut buffer), Ok(n) if n > 0) {}
+        let _ = stream.set_nonblocking(false);
+    }
+}
+
+fn collect_state() -> serde_json::Value {
+    serde_json::json!({
+        "cpu": cpu::get_cpu_stats().ok().and_then(|stats| stats.percentage),
+        "memory": memory::get_memory_stats().percentage,
+        "games_running": crash_monitor::get_tracked_games().len(),
+    })
+}
+
+/// Host name usable in topics and Home Assistant ids
+fn device_id() -> String {
+    let name = sysinfo::System::host_name().unwrap_or_else(|| "aura".to_string());
+    sanitize(&name)
+}
+
+fn sanitize(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Home Assistant discovery topics and configs, one per sensor
+fn discovery_messages(config: &MqttConfig, device: &str) -> Vec<(String, String)> {
+    let state_topic = format!("{}/{}/state", config.base_topic, device);
+    SENSORS
+        .iter()
+        .map(|(key, name, unit)| {
+            let unique_id = format!("aura_{}_{}", device, key);
+            let mut sensor = serde_json::json!({
+                "name": name,
+                "unique_id": unique_id,
+                "state_topic": state_topic,
+                "value_template": format!("{{{{ value_json.{} }}}}", key),
+                "device": {
+                    "identifiers": [format!("aura_{}", device)],
+                    "name": format!("Aura ({})", device),
+                    "manufacturer": "Aura",
+                },
+            });
+            if let Some(unit) = unit {
+                sensor["unit_of_measurement"] = serde_json::json!(unit);
+                sensor["state_class"] = serde_json::json!("measurement");
+            }
+            (
+                format!("{}/sensor/{}/config", config.discovery_prefix, unique_id),
+                sensor.to_string(),
+            )
+        })
+        .collect()
+}
+
+fn connect_packet(client_id: &str, username: Option<&str>, password: Option<&str>) -> Vec<u8> {
+    let mut flags = 0x02; // clean session
+    let mut body = Vec::new();
+    write_string(&mut body, "MQTT");
+    body.push(4); // protocol level 3.1.1
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if username.is_some() && password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+
+    write_string(&mut body, client_id);
+    if let Some(username) = username {
+        write_string(&mut body, username);
+        if let Some(password) = password {
+            write_string(&mut body, password);
+        }
+    }
+    packet(0x10, body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    write_string(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(0x30 | retain as u8, body)
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet = vec![header];
+    packet.extend(remaining_length(body.len()));
+    packet.extend(body);
+    packet
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+/// Variable length encoding of the packet size: 7 bits per byte, high bit set
+/// while more bytes follow
+fn remaining_length(mut length: usize) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        encoded.push(byte);
+        if length == 0 {
+            return encoded;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_length() {
+        assert_eq!(remaining_length(0), vec![0x00]);
+        assert_eq!(remaining_length(127), vec![0x7F]);
+        assert_eq!(remaining_length(128), vec![0x80, 0x01]);
+        assert_eq!(remaining_length(16_383), vec![0xFF, 0x7F]);
+        assert_eq!(remaining_length(16_384), vec![0x80, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn test_packets() {
+        assert_eq!(
+            publish_packet("a/b", b"1", true),
+            vec![0x31, 0x06, 0x00, 0x03, b'a', b'/', b'b', b'1']
+        );
+
+        let connect = connect_packet("id", Some("user"), Some("pw"));
+        assert_eq!(connect[0], 0x10);
+        assert_eq!(connect[1] as usize, connect.len() - 2);
+        assert_eq!(&connect[2..8], &[0x00, 0x04, b'M', b'Q', b'T', b'T']);
+        assert_eq!(connect[9], 0xC2);
+
+        assert_eq!(connect_packet("id", None, Some("pw"))[9], 0x02);
+    }
+
+    #[test]
+    fn test_discovery_messages() {
+        let messages = discovery_messages(&MqttConfig::default(), "gaming_rig");
+        assert_eq!(messages.len(), SENSORS.len());
+        let (topic, payload) = &messages[0];
+        assert_eq!(topic, "homeassistant/sensor/aura_gaming_rig_cpu/config");
+        let payload: serde_json::Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(payload["state_topic"], "aura/gaming_rig/state");
+        assert_eq!(payload["value_template"], "{{ value_json.cpu }}");
+        assert_eq!(payload["unit_of_measurement"], "%");
+        assert_eq!(sanitize("Gaming-Rig.local"), "gaming_rig_local");
+    }
+}
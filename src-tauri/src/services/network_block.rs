@@ -0,0 +1,448 @@
+// Cuts single applications off the network, e.g. a launcher that starts
+// downloading an update in the middle of a match. On Windows this is a Windows
+// Filtering Platform block filter on the executable's app id, added through a
+// dynamic session so the system drops every filter when Aura exits or
+// crashes. On Linux the processes are moved into a dedicated cgroup that an
+// nftables table drops the traffic of; children started afterwards inherit
+// the cgroup. Both need administrator or root rights.
+use crate::models::game_profile::BlockedApp;
+use crate::services::anti_cheat;
+use crate::shared::events;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum NetworkBlockError {
+    #[error("Process {0} not found")]
+    NotFound(u32),
+
+    #[error("{0} is an anti-cheat process and is never blocked")]
+    AntiCheat(String),
+
+    #[error("Failed to change the network block: {0}")]
+    PlatformError(String),
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    #[error("Network blocking is not supported on this platform")]
+    UnsupportedPlatform,
+}
+
+type Result<T> = std::result::Result<T, NetworkBlockError>;
+
+struct Block {
+    app: BlockedApp,
+    handle: platform::Handle,
+}
+
+// By lowercased executable path
+static BLOCKS: once_cell::sync::Lazy<Mutex<HashMap<String, Block>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Blocks the executable a running process was started from
+pub fn block_pid(pid: u32) -> Result<BlockedApp> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+    );
+    let exe_path = system
+        .process(pid)
+        .and_then(|process| process.exe())
+        .ok_or(NetworkBlockError::NotFound(pid.as_u32()))?
+        .to_string_lossy()
+        .into_owned();
+    block(&exe_path, None)
+}
+
+/// Blocks an executable. With a game pid the block is lifted when that game
+/// exits, see `release_game`. Blocking it again only updates the owner.
+pub fn block(exe_path: &str, game_pid: Option<u32>) -> Result<BlockedApp> {
+    let name = std::path::Path::new(exe_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| exe_path.to_string());
+    if anti_cheat::is_anti_cheat_process(&name) {
+        return Err(NetworkBlockError::AntiCheat(name));
+    }
+
+    let mut blocks = BLOCKS
+        .lock()
+        .map_err(|e| NetworkBlockError::PlatformError(e.to_string()))?;
+    let key = exe_path.to_lowercase();
+    if let Some(existing) = blocks.get_mut(&key) {
+        // A manual block outlives the game
+        if existing.app.game_pid.is_some() {
+            existing.app.game_pid = game_pid;
+        }
+        return Ok(existing.app.clone());
+    }
+
+    let handle = platform::block(exe_path)?;
+    let app = BlockedApp {
+        exe_path: exe_path.to_string(),
+        name,
+        game_pid,
+        since: events::now_millis(),
+    };
+    blocks.insert(
+        key,
+        Block {
+            app: app.clone(),
+            handle,
+        },
+    );
+    Ok(app)
+}
+
+pub fn unblock(exe_path: &str) -> Result<()> {
+    let block = BLOCKS
+        .lock()
+        .map_err(|e| NetworkBlockError::PlatformError(e.to_string()))?
+        .remove(&exe_path.to_lowercase());
+    match block {
+        Some(block) => platform::unblock(block.handle),
+        None => Ok(()),
+    }
+}
+
+/// Lifts the blocks a game asked for
+pub fn release_game(game_pid: u32) {
+    let released: Vec<Block> = match BLOCKS.lock() {
+        Ok(mut blocks) => {
+            let keys: Vec<String> = blocks
+                .iter()
+                .filter(|(_, block)| block.app.game_pid == Some(game_pid))
+                .map(|(key, _)| key.clone())
+                .collect();
+            keys.iter().filter_map(|key| blocks.remove(key)).collect()
+        }
+        Err(_) => return,
+    };
+    for block in released {
+        if let Err(e) = platform::unblock(block.handle) {
+            eprintln!("Failed to unblock {}: {}", block.app.exe_path, e);
+        }
+    }
+}
+
+/// Lifts every block. Called on exit, hence the try_lock.
+pub fn unblock_all() {
+    let Ok(mut blocks) = BLOCKS.try_lock() else {
+        return;
+    };
+    for (_, block) in blocks.drain() {
+        let _ = platform::unblock(block.handle);
+    }
+}
+
+/// Removes what a crashed run left in place. Windows drops the filters of a
+/// dynamic session by itself.
+pub fn restore_leftover() {
+    platform::restore_leftover();
+}
+
+pub fn get_blocked() -> Vec<BlockedApp> {
+    let mut apps: Vec<BlockedApp> = BLOCKS
+        .lock()
+        .map(|blocks| blocks.values().map(|block| block.app.clone()).collect())
+        .unwrap_or_default();
+    apps.sort_by_key(|app| app.since);
+    apps
+}
+
+/// The cgroup v2 path of a process from /proc/<pid>/cgroup
+#[cfg_attr(all(not(target_os = "linux"), not(test)), allow(dead_code))]
+fn parse_cgroup(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.trim().to_string())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{NetworkBlockError, Result};
+    use std::sync::Mutex;
+    use windows::core::{GUID, HSTRING, PCWSTR, PWSTR};
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::NetworkManagement::WindowsFilteringPlatform::{
+        FwpmEngineOpen0, FwpmFilterAdd0, FwpmFilterDeleteById0, FwpmFreeMemory0,
+        FwpmGetAppIdFromFileName0, FWPM_ACTION0, FWPM_CONDITION_ALE_APP_ID, FWPM_DISPLAY_DATA0,
+        FWPM_FILTER0, FWPM_FILTER_CONDITION0, FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+        FWPM_LAYER_ALE_AUTH_CONNECT_V6, FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4,
+        FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6, FWPM_SESSION0, FWPM_SESSION_FLAG_DYNAMIC,
+        FWP_ACTION_BLOCK, FWP_BYTE_BLOB, FWP_BYTE_BLOB_TYPE, FWP_CONDITION_VALUE0,
+        FWP_CONDITION_VALUE0_0, FWP_MATCH_EQUAL,
+    };
+    use windows::Win32::System::Rpc::RPC_C_AUTHN_WINNT;
+
+    // Ids of the filters added for one executable
+    pub type Handle = Vec<u64>;
+
+    // Outgoing connections and incoming ones, over IPv4 and IPv6
+    const LAYERS: [GUID; 4] = [
+        FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+        FWPM_LAYER_ALE_AUTH_CONNECT_V6,
+        FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4,
+        FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6,
+    ];
+
+    // Opened once and kept for the whole run, closing it removes the filters
+    static ENGINE: Mutex<Option<usize>> = Mutex::new(None);
+
+    fn engine() -> Result<HANDLE> {
+        let mut engine = ENGINE
+            .lock()
+            .map_err(|e| NetworkBlockError::PlatformError(e.to_string()))?;
+        if let Some(handle) = *engine {
+            return Ok(HANDLE(handle as *mut _));
+        }
+        let session = FWPM_SESSION0 {
+            flags: FWPM_SESSION_FLAG_DYNAMIC,
+            ..Default::default()
+        };
+        let mut handle = HANDLE::default();
+        let status = unsafe {
+            FwpmEngineOpen0(
+                PCWSTR::null(),
+                RPC_C_AUTHN_WINNT,
+                None,
+                Some(&session),
+                &mut handle,
+            )
+        };
+        if status != 0 {
+            return Err(error("open the filtering engine", status));
+        }
+        *engine = Some(handle.0 as usize);
+        Ok(handle)
+    }
+
+    pub fn block(exe_path: &str) -> Result<Handle> {
+        let engine = engine()?;
+        let mut app_id: *mut FWP_BYTE_BLOB = std::ptr::null_mut();
+        let status = unsafe { FwpmGetAppIdFromFileName0(&HSTRING::from(exe_path), &mut app_id) };
+        if status != 0 {
+            return Err(error(&format!("resolve {}", exe_path), status));
+        }
+
+        let mut name: Vec<u16> = "Aura network block\0".encode_utf16().collect();
+        let mut condition = FWPM_FILTER_CONDITION0 {
+            fieldKey: FWPM_CONDITION_ALE_APP_ID,
+            matchType: FWP_MATCH_EQUAL,
+            conditionValue: FWP_CONDITION_VALUE0 {
+                r#type: FWP_BYTE_BLOB_TYPE,
+                Anonymous: FWP_CONDITION_VALUE0_0 { byteBlob: app_id },
+            },
+        };
+        let mut ids = Vec::new();
+        let mut result = Ok(());
+        for layer in LAYERS {
+            // The weight is left empty for the engine to pick one
+            let filter = FWPM_FILTER0 {
+                displayData: FWPM_DISPLAY_DATA0 {
+                    name: PWSTR(name.as_mut_ptr()),
+                    description: PWSTR::null(),
+                },
+                layerKey: layer,
+                numFilterConditions: 1,
+                filterCondition: &mut condition,
+                action: FWPM_ACTION0 {
+                    r#type: FWP_ACTION_BLOCK,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut id = 0u64;
+            let status = unsafe { FwpmFilterAdd0(engine, &filter, None, Some(&mut id)) };
+            if status != 0 {
+                result = Err(error("add the block filter", status));
+                break;
+            }
+            ids.push(id);
+        }
+        unsafe { FwpmFreeMemory0(&mut app_id as *mut _ as *mut *mut core::ffi::c_void) };
+
+        match result {
+            Ok(()) => Ok(ids),
+            Err(e) => {
+                let _ = unblock(ids);
+                Err(e)
+            }
+        }
+    }
+
+    pub fn unblock(handle: Handle) -> Result<()> {
+        let engine = engine()?;
+        for id in handle {
+            let status = unsafe { FwpmFilterDeleteById0(engine, id) };
+            if status != 0 {
+                return Err(error("remove the block filter", status));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn restore_leftover() {}
+
+    fn error(action: &str, status: u32) -> NetworkBlockError {
+        NetworkBlockError::PlatformError(format!(
+            "Failed to {} (error {:#010x}), Aura must run as administrator",
+            action, status
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{NetworkBlockError, Result};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    const CGROUP_NAME: &str = "aura-netblock";
+    const TABLE: &str = "aura_netblock";
+
+    // The processes moved into the blocked cgroup and where they came from
+    pub type Handle = Vec<(u32, String)>;
+
+    pub fn block(exe_path: &str) -> Result<Handle> {
+        let cgroup = format!("{}/{}", CGROUP_ROOT, CGROUP_NAME);
+        std::fs::create_dir_all(&cgroup).map_err(|e| {
+            NetworkBlockError::PlatformError(format!("Failed to create {}: {}", cgroup, e))
+        })?;
+        ensure_table()?;
+
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+        );
+        let mut moved = Vec::new();
+        for (pid, process) in system.processes() {
+            if process.exe().and_then(|exe| exe.to_str()) != Some(exe_path) {
+                continue;
+            }
+            let pid = pid.as_u32();
+            let Some(original) = std::fs::read_to_string(format!("/proc/{}/cgroup", pid))
+                .ok()
+                .and_then(|content| super::parse_cgroup(&content))
+            else {
+                continue;
+            };
+            if move_to(pid, &format!("/{}", CGROUP_NAME)).is_ok() {
+                moved.push((pid, original));
+            }
+        }
+        if moved.is_empty() {
+            return Err(NetworkBlockError::PlatformError(format!(
+                "No running process of {} could be moved",
+                exe_path
+            )));
+        }
+        Ok(moved)
+    }
+
+    pub fn unblock(handle: Handle) -> Result<()> {
+        for (pid, original) in handle {
+            // Exited processes leave the cgroup by themselves
+            let _ = move_to(pid, &original);
+        }
+        Ok(())
+    }
+
+    /// Drops the table a crashed run left, the processes in the cgroup are
+    /// unblocked with it
+    pub fn restore_leftover() {
+        let _ = Command::new("nft")
+            .args(["delete", "table", "inet", TABLE])
+            .stderr(Stdio::null())
+            .status();
+    }
+
+    fn ensure_table() -> Result<()> {
+        let exists = Command::new("nft")
+            .args(["list", "table", "inet", TABLE])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if exists {
+            return Ok(());
+        }
+
+        let rule = format!("socket cgroupv2 level 1 \"{}\" drop", CGROUP_NAME);
+        let script = format!(
+            "table inet {table} {{\n\
+             \tchain output {{ type filter hook output priority 0; policy accept; {rule} }}\n\
+             \tchain input {{ type filter hook input priority 0; policy accept; {rule} }}\n\
+             }}\n",
+            table = TABLE,
+            rule = rule
+        );
+        let mut child = Command::new("nft")
+            .args(["-f", "-"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| NetworkBlockError::PlatformError(format!("nft: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(script.as_bytes());
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| NetworkBlockError::PlatformError(format!("nft: {}", e)))?;
+        if !output.status.success() {
+            return Err(NetworkBlockError::PlatformError(format!(
+                "nft: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    fn move_to(pid: u32, cgroup: &str) -> std::io::Result<()> {
+        std::fs::write(
+            format!("{}{}/cgroup.procs", CGROUP_ROOT, cgroup),
+            pid.to_string(),
+        )
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::{NetworkBlockError, Result};
+
+    pub type Handle = ();
+
+    pub fn block(_exe_path: &str) -> Result<Handle> {
+        Err(NetworkBlockError::UnsupportedPlatform)
+    }
+
+    pub fn unblock(_handle: Handle) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn restore_leftover() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cgroup() {
+        let content = "12:cpuset:/\n0::/user.slice/user-1000.slice/session-2.scope\n";
+        assert_eq!(
+            parse_cgroup(content).as_deref(),
+            Some("/user.slice/user-1000.slice/session-2.scope")
+        );
+        assert_eq!(parse_cgroup("1:name=systemd:/init.scope\n"), None);
+    }
+}
@@ -0,0 +1,369 @@
+use crate::models::optimization::{AdapterKind, NetworkAdapterTuning};
+use crate::shared::paths;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+// Values adapters had before Aura changed them, so a revert restores exactly
+// those
+const ORIGINALS_FILE: &str = "network_tuning.json";
+
+// Windows picks the route with the lowest metric
+pub const ETHERNET_METRIC: u32 = 10;
+pub const WIFI_METRIC: u32 = 50;
+// Smallest MTU IPv4 guarantees, largest jumbo frame commonly supported
+const MIN_MTU: u32 = 576;
+const MAX_MTU: u32 = 9000;
+
+#[derive(Error, Debug)]
+pub enum NetworkTuningError {
+    #[cfg(target_os = "windows")]
+    #[error("Network command failed: {0}")]
+    CommandError(String),
+
+    #[error("MTU must be between {MIN_MTU} and {MAX_MTU}, got {0}")]
+    InvalidMtu(u32),
+
+    #[error("No network adapter with index {0}")]
+    UnknownAdapter(u32),
+
+    #[error("Failed to access saved network settings: {0}")]
+    StorageError(String),
+
+    #[cfg(not(target_os = "windows"))]
+    #[error("Network adapter tuning is only available on Windows")]
+    UnsupportedPlatform,
+}
+
+type Result<T> = std::result::Result<T, NetworkTuningError>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OriginalMetric {
+    metric: u32,
+    automatic: bool,
+}
+
+/// What an adapter had before Aura's first change, per setting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AdapterOriginal {
+    index: u32,
+    name: String,
+    metric: Option<OriginalMetric>,
+    mtu: Option<u32>,
+}
+
+static ORIGINALS: once_cell::sync::Lazy<Mutex<Vec<AdapterOriginal>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_originals()));
+
+fn load_originals() -> Vec<AdapterOriginal> {
+    std::fs::read_to_string(paths::app_data_file(ORIGINALS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_originals(originals: &[AdapterOriginal]) -> Result<()> {
+    let content = serde_json::to_string_pretty(originals)
+        .map_err(|e| NetworkTuningError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(ORIGINALS_FILE), content)
+        .map_err(|e| NetworkTuningError::StorageError(e.to_string()))
+}
+
+fn with_originals<T>(f: impl FnOnce(&mut Vec<AdapterOriginal>) -> Result<T>) -> Result<T> {
+    let mut originals = ORIGINALS
+        .lock()
+        .map_err(|e| NetworkTuningError::StorageError(e.to_string()))?;
+    f(&mut originals)
+}
+
+/// Physical adapters with their current IPv4 metric and MTU
+pub fn get_adapters() -> Result<Vec<NetworkAdapterTuning>> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = powershell(
+            "$a = @(Get-NetAdapter -Physical | Select-Object ifIndex, Name, PhysicalMediaType, Status); \
+             $i = @(Get-NetIPInterface -AddressFamily IPv4 | Select-Object InterfaceIndex, InterfaceMetric, AutomaticMetric, NlMtu); \
+             @{ adapters = $a; interfaces = $i } | ConvertTo-Json -Depth 3 -Compress",
+        )?;
+        let mut adapters = parse_adapters(&output);
+        with_originals(|originals| {
+            for adapter in adapters.iter_mut() {
+                adapter.modified = originals.iter().any(|o| o.index == adapter.index);
+            }
+            Ok(())
+        })?;
+        Ok(adapters)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(NetworkTuningError::UnsupportedPlatform)
+    }
+}
+
+/// Gives Ethernet a lower metric than Wi-Fi so traffic takes the cable when
+/// both are connected
+pub fn prefer_ethernet() -> Result<Vec<NetworkAdapterTuning>> {
+    let adapters = get_adapters()?;
+    for adapter in &adapters {
+        let metric = match adapter.kind {
+            AdapterKind::Ethernet => ETHERNET_METRIC,
+            AdapterKind::WiFi => WIFI_METRIC,
+            AdapterKind::Other => continue,
+        };
+        if !adapter.automatic_metric && adapter.metric == metric {
+            continue;
+        }
+        record_original(adapter, |original| {
+            original.metric.get_or_insert(OriginalMetric {
+                metric: adapter.metric,
+                automatic: adapter.automatic_metric,
+            });
+        })?;
+        set_interface(adapter.index, &format!("-InterfaceMetric {}", metric))?;
+    }
+    get_adapters()
+}
+
+/// Restores the metrics prefer_ethernet changed
+pub fn restore_metrics() -> Result<Vec<NetworkAdapterTuning>> {
+    with_originals(|originals| {
+        let mut outcome = Ok(());
+        for original in originals.iter_mut() {
+            if let Some(metric) = &original.metric {
+                // Whatever was restored before a failure stays restored
+                if let Err(e) = set_interface(original.index, &metric_argument(metric)) {
+                    outcome = Err(e);
+                    break;
+                }
+                original.metric = None;
+            }
+        }
+        originals.retain(|o| o.metric.is_some() || o.mtu.is_some());
+        save_originals(originals)?;
+        outcome
+    })?;
+    get_adapters()
+}
+
+/// Whether Aura has changed any adapter metric
+pub fn metrics_changed() -> bool {
+    with_originals(|originals| Ok(originals.iter().any(|o| o.metric.is_some()))).unwrap_or(false)
+}
+
+pub fn set_mtu(index: u32, mtu: u32) -> Result<Vec<NetworkAdapterTuning>> {
+    if !(MIN_MTU..=MAX_MTU).contains(&mtu) {
+        return Err(NetworkTuningError::InvalidMtu(mtu));
+    }
+    let adapters = get_adapters()?;
+    let adapter = adapters
+        .iter()
+        .find(|adapter| adapter.index == index)
+        .ok_or(NetworkTuningError::UnknownAdapter(index))?;
+
+    record_original(adapter, |original| {
+        original.mtu.get_or_insert(adapter.mtu);
+    })?;
+    set_interface(index, &format!("-NlMtuBytes {}", mtu))?;
+    get_adapters()
+}
+
+/// Puts back the MTU an adapter had before Aura changed it
+pub fn restore_mtu(index: u32) -> Result<Vec<NetworkAdapterTuning>> {
+    with_originals(|originals| {
+        if let Some(original) = originals.iter_mut().find(|o| o.index == index) {
+            if let Some(mtu) = original.mtu {
+                set_interface(index, &format!("-NlMtuBytes {}", mtu))?;
+                original.mtu = None;
+            }
+        }
+        originals.retain(|o| o.metric.is_some() || o.mtu.is_some());
+        save_originals(originals)
+    })?;
+    get_adapters()
+}
+
+/// Metric and MTU per adapter, for the optimization history
+pub fn settings_snapshot() -> Vec<(String, Option<String>)> {
+    get_adapters()
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|adapter| {
+            let metric = if adapter.automatic_metric {
+                format!("{} (automatic)", adapter.metric)
+            } else {
+                adapter.metric.to_string()
+            };
+            [
+                (format!("{} metric", adapter.name), Some(metric)),
+                (
+                    format!("{} MTU", adapter.name),
+                    Some(adapter.mtu.to_string()),
+                ),
+            ]
+        })
+        .collect()
+}
+
+/// Runs `update` on the adapter's saved originals, creating them first
+fn record_original(
+    adapter: &NetworkAdapterTuning,
+    update: impl FnOnce(&mut AdapterOriginal),
+) -> Result<()> {
+    with_originals(|originals| {
+        let position = match originals.iter().position(|o| o.index == adapter.index) {
+            Some(position) => position,
+            None => {
+                originals.push(AdapterOriginal {
+                    index: adapter.index,
+                    name: adapter.name.clone(),
+                    metric: None,
+                    mtu: None,
+                });
+                originals.len() - 1
+            }
+        };
+        update(&mut originals[position]);
+        save_originals(originals)
+    })
+}
+
+fn metric_argument(metric: &OriginalMetric) -> String {
+    if metric.automatic {
+        "-AutomaticMetric Enabled".to_string()
+    } else {
+        format!("-InterfaceMetric {}", metric.metric)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_interface(index: u32, arguments: &str) -> Result<()> {
+    powershell(&format!(
+        "Set-NetIPInterface -InterfaceIndex {} -AddressFamily IPv4 {}",
+        index, arguments
+    ))
+    .map(|_| ())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn set_interface(_index: u32, _arguments: &str) -> Result<()> {
+    Err(NetworkTuningError::UnsupportedPlatform)
+}
+
+#[cfg(target_os = "windows")]
+fn powershell(script: &str) -> Result<String> {
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .map_err(|e| NetworkTuningError::CommandError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(NetworkTuningError::CommandError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Joins Get-NetAdapter and Get-NetIPInterface output on the interface index.
+/// Adapters without IPv4 are left out.
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn parse_adapters(json: &str) -> Vec<NetworkAdapterTuning> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return Vec::new();
+    };
+    let as_list = |value: &serde_json::Value| match value {
+        serde_json::Value::Array(items) => items.clone(),
+        serde_json::Value::Null => Vec::new(),
+        single => vec![single.clone()],
+    };
+    let interfaces = as_list(&value["interfaces"]);
+
+    as_list(&value["adapters"])
+        .iter()
+        .filter_map(|adapter| {
+            let index = adapter["ifIndex"].as_u64()? as u32;
+            let interface = interfaces
+                .iter()
+                .find(|i| i["InterfaceIndex"].as_u64() == Some(index as u64))?;
+            let media = adapter["PhysicalMediaType"].as_str().unwrap_or_default();
+            Some(NetworkAdapterTuning {
+                index,
+                name: adapter["Name"].as_str().unwrap_or_default().to_string(),
+                kind: if media.contains("802.11") {
+                    AdapterKind::WiFi
+                } else if media.contains("802.3") {
+                    AdapterKind::Ethernet
+                } else {
+                    AdapterKind::Other
+                },
+                connected: adapter["Status"] == "Up",
+                metric: interface["InterfaceMetric"].as_u64().unwrap_or_default() as u32,
+                // ConvertTo-Json writes the enum as a number, 1 is Enabled
+                automatic_metric: interface["AutomaticMetric"].as_u64() == Some(1),
+                mtu: interface["NlMtu"].as_u64().unwrap_or_default() as u32,
+                modified: false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_adapters() {
+        let json = r#"{"adapters":[
+            {"ifIndex":12,"Name":"Ethernet","PhysicalMediaType":"802.3","Status":"Up"},
+            {"ifIndex":7,"Name":"Wi-Fi","PhysicalMediaType":"Native 802.11","Status":"Disconnected"},
+            {"ifIndex":3,"Name":"Bluetooth","PhysicalMediaType":"BlueTooth","Status":"Up"}],
+            "interfaces":[
+            {"InterfaceIndex":12,"InterfaceMetric":25,"AutomaticMetric":1,"NlMtu":1500},
+            {"InterfaceIndex":7,"InterfaceMetric":50,"AutomaticMetric":0,"NlMtu":1400}]}"#;
+        let adapters = parse_adapters(json);
+        assert_eq!(adapters.len(), 2);
+
+        assert_eq!(adapters[0].kind, AdapterKind::Ethernet);
+        assert!(adapters[0].connected);
+        assert!(adapters[0].automatic_metric);
+        assert_eq!(adapters[0].metric, 25);
+        assert_eq!(adapters[0].mtu, 1500);
+
+        assert_eq!(adapters[1].kind, AdapterKind::WiFi);
+        assert!(!adapters[1].connected);
+        assert!(!adapters[1].automatic_metric);
+        assert_eq!(adapters[1].mtu, 1400);
+    }
+
+    #[test]
+    fn test_parse_single_adapter() {
+        // ConvertTo-Json unwraps one-element arrays in nested objects
+        let json = r#"{"adapters":{"ifIndex":4,"Name":"Ethernet 2","PhysicalMediaType":"802.3","Status":"Up"},
+            "interfaces":{"InterfaceIndex":4,"InterfaceMetric":5,"AutomaticMetric":0,"NlMtu":9000}}"#;
+        let adapters = parse_adapters(json);
+        assert_eq!(adapters.len(), 1);
+        assert_eq!(adapters[0].mtu, 9000);
+        assert!(parse_adapters("not json").is_empty());
+    }
+
+    #[test]
+    fn test_metric_argument() {
+        let automatic = OriginalMetric {
+            metric: 25,
+            automatic: true,
+        };
+        assert_eq!(metric_argument(&automatic), "-AutomaticMetric Enabled");
+        let manual = OriginalMetric {
+            metric: 40,
+            automatic: false,
+        };
+        assert_eq!(metric_argument(&manual), "-InterfaceMetric 40");
+    }
+}
@@ -0,0 +1,241 @@
+// OBS Studio integration through obs-websocket v5: keeps a text source
+// updated with the stats line and switches scenes when a tracked game starts
+// or exits. The connection is optional and only exists in builds with the
+// obs feature.
+use crate::models::system_stats::ObsConfig;
+use crate::shared::paths;
+use anyhow::{anyhow, Result};
+use std::sync::Mutex;
+
+#[cfg(feature = "obs")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "obs")]
+use std::time::{Duration, Instant};
+
+const CONFIG_FILE: &str = "obs.json";
+#[cfg(feature = "obs")]
+const TICK: Duration = Duration::from_millis(500);
+#[cfg(feature = "obs")]
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+#[cfg(feature = "obs")]
+const RPC_VERSION: u32 = 1;
+
+static CONFIG: once_cell::sync::Lazy<Mutex<ObsConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+// Scene to switch to as soon as the client is connected
+static PENDING_SCENE: Mutex<Option<String>> = Mutex::new(None);
+
+#[cfg(feature = "obs")]
+static CLIENT_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts the client thread, which idles while OBS is disabled. Called once
+/// at startup.
+pub fn start() {
+    #[cfg(feature = "obs")]
+    if !CLIENT_RUNNING.swap(true, Ordering::SeqCst) {
+        std::thread::spawn(client_loop);
+    }
+}
+
+pub fn get_config() -> ObsConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: ObsConfig) -> Result<ObsConfig> {
+    if config.enabled && !cfg!(feature = "obs") {
+        return Err(anyhow!(
+            "This build of Aura does not include the OBS integration"
+        ));
+    }
+    if config.interval_secs == 0 {
+        return Err(anyhow!("OBS update interval must be at least one second"));
+    }
+
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    Ok(config)
+}
+
+/// Switches to the game scene, if one is set
+pub fn on_game_started() {
+    queue_scene(get_config().game_scene);
+}
+
+/// Switches to the idle scene, if one is set
+pub fn on_game_exited() {
+    queue_scene(get_config().idle_scene);
+}
+
+fn queue_scene(scene: Option<String>) {
+    if !get_config().enabled {
+        return;
+    }
+    if let (Some(scene), Ok(mut pending)) = (scene, PENDING_SCENE.lock()) {
+        *pending = Some(scene);
+    }
+}
+
+fn load_config() -> ObsConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+#[cfg(feature = "obs")]
+fn client_loop() {
+    loop {
+        let config = get_config();
+        if !config.enabled {
+            std::thread::sleep(TICK);
+            continue;
+        }
+        if let Err(e) = run_session(&config) {
+            eprintln!(
+                "OBS connection to {}:{} lost: {}",
+                config.host, config.port, e
+            );
+            std::thread::sleep(RECONNECT_DELAY);
+        }
+    }
+}
+
+/// Connects and pushes updates until the settings change or the link drops
+#[cfg(feature = "obs")]
+fn run_session(config: &ObsConfig) -> Result<()> {
+    use crate::services::{quiet_hours, stats_line};
+    use serde_json::json;
+    use tungstenite::Message;
+
+    let (mut socket, _) = tungstenite::connect(format!("ws://{}:{}", config.host, config.port))?;
+
+    // Hello carries the authentication challenge when OBS has a password
+    let hello = read_json(&mut socket)?;
+    let authentication = hello["d"]["authentication"].as_object().map(|auth| {
+        let password = config.password.as_deref().unwrap_or_default();
+        let salt = auth
+            .get("salt")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let challenge = auth
+            .get("challenge")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        authentication_string(password, salt, challenge)
+    });
+    let mut identify =
+        json!({ "op": 1, "d": { "rpcVersion": RPC_VERSION, "eventSubscriptions": 0 } });
+    if let Some(authentication) = authentication {
+        identify["d"]["authentication"] = json!(authentication);
+    }
+    socket.send(Message::Text(identify.to_string().into()))?;
+    if read_json(&mut socket)?["op"] != 2 {
+        return Err(anyhow!("OBS rejected the connection, check the password"));
+    }
+
+    if let tungstenite::stream::MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream.set_read_timeout(Some(TICK))?;
+    }
+
+    let mut last_update: Option<Instant> = None;
+    let mut request_id: u64 = 0;
+
+    while get_config() == *config {
+        let scene = PENDING_SCENE
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.take());
+        if let Some(scene) = scene {
+            send_request(
+                &mut socket,
+                &mut request_id,
+                "SetCurrentProgramScene",
+                json!({ "sceneName": scene }),
+            )?;
+        }
+
+        let update_due = !quiet_hours::is_paused()
+            && last_update
+                .is_none_or(|last| last.elapsed() >= Duration::from_secs(config.interval_secs));
+        if let (Some(source), true) = (&config.text_source, update_due) {
+            last_update = Some(Instant::now());
+            send_request(
+                &mut socket,
+                &mut request_id,
+                "SetInputSettings",
+                json!({ "inputName": source, "inputSettings": { "text": stats_line::stats_line() } }),
+            )?;
+        }
+
+        // Responses only matter for keeping the connection alive
+        match socket.read() {
+            Ok(Message::Close(_)) => return Err(anyhow!("OBS closed the connection")),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let _ = socket.close(None);
+    Ok(())
+}
+
+#[cfg(feature = "obs")]
+fn send_request<S: std::io::Read + std::io::Write>(
+    socket: &mut tungstenite::WebSocket<S>,
+    request_id: &mut u64,
+    kind: &str,
+    data: serde_json::Value,
+) -> Result<()> {
+    *request_id += 1;
+    let message = serde_json::json!({
+        "op": 6,
+        "d": { "requestType": kind, "requestId": request_id.to_string(), "requestData": data },
+    });
+    socket.send(tungstenite::Message::Text(message.to_string().into()))?;
+    Ok(())
+}
+
+#[cfg(feature = "obs")]
+fn read_json<S: std::io::Read + std::io::Write>(
+    socket: &mut tungstenite::WebSocket<S>,
+) -> Result<serde_json::Value> {
+    loop {
+        if let tungstenite::Message::Text(text) = socket.read()? {
+            return Ok(serde_json::from_str(text.as_str())?);
+        }
+    }
+}
+
+/// obs-websocket v5 authentication:
+/// base64(sha256(base64(sha256(password + salt)) + challenge))
+#[cfg(feature = "obs")]
+fn authentication_string(password: &str, salt: &str, challenge: &str) -> String {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
+    let secret = STANDARD.encode(Sha256::digest(format!("{}{}", password, salt)));
+    STANDARD.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+}
+
+#[cfg(all(test, feature = "obs"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authentication_string() {
+        assert_eq!(
+            authentication_string(
+                "supersecretpassword",
+                "lM1GncleQOaCu9lT1yeUZhFYnqhsLLP1G5lAGo3ixaI=",
+                "+IxH4CnCiqpX1rM9scsNynZzbOe4KhDeYcTNS3PDaeY="
+            ),
+            "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4="
+        );
+    }
+}
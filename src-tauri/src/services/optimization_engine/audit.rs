@@ -0,0 +1,145 @@
+use crate::models::optimization::{
+    AuditAction, OptimizationAuditEntry, OptimizationResult, SettingChange,
+};
+use crate::shared::{events::now_millis, paths};
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use crate::shared::registry;
+
+const HISTORY_FILE: &str = "optimization_history.json";
+// Oldest entries are dropped past this, a few years of normal use
+const MAX_ENTRIES: usize = 2000;
+
+/// Values of the settings an optimization touches, by setting name
+pub type Snapshot = Vec<(String, Option<String>)>;
+
+static HISTORY: once_cell::sync::Lazy<Mutex<Vec<OptimizationAuditEntry>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_history()));
+
+fn load_history() -> Vec<OptimizationAuditEntry> {
+    std::fs::read_to_string(paths::app_data_file(HISTORY_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Every recorded apply and revert, newest first
+pub fn get_history() -> Vec<OptimizationAuditEntry> {
+    HISTORY
+        .lock()
+        .map(|history| history.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Appends an entry and writes the history back. The action already ran, so
+/// a failed write is not reported to the caller.
+pub(super) fn record(
+    action: AuditAction,
+    optimization_id: &str,
+    optimization_name: &str,
+    before: &Snapshot,
+    after: &Snapshot,
+    outcome: &anyhow::Result<OptimizationResult>,
+) {
+    let (success, message) = match outcome {
+        Ok(result) => (result.success, result.message.clone()),
+        Err(e) => (false, e.to_string()),
+    };
+    let entry = OptimizationAuditEntry {
+        timestamp: now_millis(),
+        action,
+        optimization_id: optimization_id.to_string(),
+        optimization_name: optimization_name.to_string(),
+        user: current_user(),
+        changes: diff(before, after),
+        success,
+        message,
+    };
+
+    if let Ok(mut history) = HISTORY.lock() {
+        history.push(entry);
+        if history.len() > MAX_ENTRIES {
+            let excess = history.len() - MAX_ENTRIES;
+            history.drain(..excess);
+        }
+        if let Ok(content) = serde_json::to_string_pretty(&*history) {
+            let _ = std::fs::write(paths::app_data_file(HISTORY_FILE), content);
+        }
+    }
+}
+
+/// Snapshot of registry DWORDs, named `key\\value`
+#[cfg(target_os = "windows")]
+pub(super) fn registry_snapshot<'a>(
+    values: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Snapshot {
+    values
+        .into_iter()
+        .map(|(key, value)| {
+            (
+                format!("{}\\{}", key, value),
+                registry::read_dword(key, value).map(|data| data.to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Settings whose value changed, including ones that appeared or went away
+fn diff(before: &Snapshot, after: &Snapshot) -> Vec<SettingChange> {
+    let value = |snapshot: &Snapshot, setting: &str| {
+        snapshot
+            .iter()
+            .find(|(name, _)| name == setting)
+            .and_then(|(_, value)| value.clone())
+    };
+
+    let mut changes: Vec<SettingChange> = Vec::new();
+    for (setting, _) in before.iter().chain(after) {
+        if changes.iter().any(|c| &c.setting == setting) {
+            continue;
+        }
+        let (old_value, new_value) = (value(before, setting), value(after, setting));
+        if old_value != new_value {
+            changes.push(SettingChange {
+                setting: setting.clone(),
+                old_value,
+                new_value,
+            });
+        }
+    }
+    changes
+}
+
+fn current_user() -> String {
+    std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(values: &[(&str, Option<&str>)]) -> Snapshot {
+        values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.map(str::to_string)))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = snapshot(&[("GameDVR_Enabled", Some("1")), ("AllowGameDVR", None)]);
+        let after = snapshot(&[("GameDVR_Enabled", Some("0")), ("AllowGameDVR", Some("0"))]);
+        let changes = diff(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].old_value.as_deref(), Some("1"));
+        assert_eq!(changes[1].old_value, None);
+
+        assert!(diff(&before, &before).is_empty());
+        let removed = diff(&after, &snapshot(&[("GameDVR_Enabled", Some("0"))]));
+        assert_eq!(removed[0].setting, "AllowGameDVR");
+        assert_eq!(removed[0].new_value, None);
+    }
+}
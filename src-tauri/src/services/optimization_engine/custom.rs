@@ -0,0 +1,563 @@
+use super::{failure, success, Optimization, Snapshot};
+use crate::models::optimization::{
+    OptimizationItem, OptimizationRecipe, OptimizationResult, Platform, RecipeStep,
+};
+use crate::shared::paths;
+use std::process::Command;
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use super::audit::registry_snapshot;
+#[cfg(target_os = "windows")]
+use crate::shared::registry;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const RECIPES_FILE: &str = "custom_optimizations.json";
+const MAX_ID_LENGTH: usize = 64;
+
+// Registry roots recipes may touch: per-user settings, software settings and
+// the current control set. Security hives (SAM, SECURITY) are never reachable.
+const ALLOWED_REGISTRY_ROOTS: &[&str] = &[
+    "HKCU\\",
+    "HKEY_CURRENT_USER\\",
+    "HKLM\\SOFTWARE\\",
+    "HKEY_LOCAL_MACHINE\\SOFTWARE\\",
+    "HKLM\\SYSTEM\\CURRENTCONTROLSET\\",
+    "HKEY_LOCAL_MACHINE\\SYSTEM\\CURRENTCONTROLSET\\",
+];
+
+// Keys that decide what starts with Windows, which services run or how
+// programs load, so a recipe cannot be used to plant something that runs
+// outside Aura
+const BLOCKED_REGISTRY_KEYS: &[&str] = &[
+    "\\CURRENTVERSION\\RUN",
+    "\\CURRENTVERSION\\POLICIES\\EXPLORER\\RUN",
+    "\\WINLOGON",
+    "\\IMAGE FILE EXECUTION OPTIONS",
+    "\\WINDOWS NT\\CURRENTVERSION\\WINDOWS",
+    "\\CONTROL\\LSA",
+    "\\CONTROL\\SESSION MANAGER\\KNOWNDLLS",
+    "\\SERVICES\\",
+    "\\WINDOWS DEFENDER",
+];
+
+// Programs recipes may run. No shells or interpreters, every argument is
+// passed as-is, never through a shell, and `allowed_arguments` only lets
+// through subcommands that change settings: nothing that installs a service,
+// loads a helper DLL or points at a file to run.
+const WINDOWS_PROGRAMS: &[&str] = &["powercfg", "sc", "netsh", "ipconfig"];
+const LINUX_PROGRAMS: &[&str] = &[
+    "sysctl",
+    "systemctl",
+    "cpupower",
+    "powerprofilesctl",
+    "gsettings",
+];
+const MACOS_PROGRAMS: &[&str] = &["pmset", "mdutil", "defaults"];
+
+// Kernel settings sysctl may change. core_pattern, modprobe and the like name
+// a program the kernel runs as root, so only these families are reachable.
+const SYSCTL_PREFIXES: &[&str] = &[
+    "vm.",
+    "net.",
+    "kernel.sched",
+    "kernel.nmi_watchdog",
+    "kernel.numa_balancing",
+    "kernel.split_lock_mitigate",
+    "fs.inotify.",
+    "fs.file-max",
+];
+
+#[derive(Error, Debug)]
+pub enum RecipeError {
+    #[error("Invalid recipe: {0}")]
+    Invalid(String),
+
+    #[error("An optimization with id {0} already exists")]
+    DuplicateId(String),
+
+    #[error("No custom optimization with id {0}")]
+    NotFound(String),
+
+    #[error("Failed to access custom optimizations: {0}")]
+    StorageError(String),
+}
+
+type Result<T> = std::result::Result<T, RecipeError>;
+
+/// Saved recipes that still pass validation; a hand-edited file with a bad
+/// recipe only loses that recipe
+pub(super) fn load_recipes() -> Vec<OptimizationRecipe> {
+    std::fs::read_to_string(paths::app_data_file(RECIPES_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Vec<OptimizationRecipe>>(&content).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|recipe| validate(recipe).is_ok())
+        .collect()
+}
+
+pub(super) fn save_recipes(recipes: &[OptimizationRecipe]) -> Result<()> {
+    let content = serde_json::to_string_pretty(recipes)
+        .map_err(|e| RecipeError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(RECIPES_FILE), content)
+        .map_err(|e| RecipeError::StorageError(e.to_string()))
+}
+
+/// Checks a recipe against the sandbox: known step types only, registry
+/// writes under the allowed roots, and programs from the platform allowlist
+pub(super) fn validate(recipe: &OptimizationRecipe) -> Result<()> {
+    let invalid = |message: String| Err(RecipeError::Invalid(message));
+
+    if recipe.id.is_empty()
+        || recipe.id.len() > MAX_ID_LENGTH
+        || !recipe
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return invalid(format!(
+            "id must be 1-{} lowercase letters, digits or underscores",
+            MAX_ID_LENGTH
+        ));
+    }
+    if recipe.name.trim().is_empty() {
+        return invalid("name is required".to_string());
+    }
+    if recipe.platform == Platform::All {
+        return invalid("recipes must target a single platform".to_string());
+    }
+    if recipe.apply.is_empty() {
+        return invalid("at least one apply step is required".to_string());
+    }
+
+    for step in recipe.apply.iter().chain(&recipe.revert) {
+        validate_step(step, &recipe.platform)?;
+    }
+    if let Some(check) = &recipe.detect {
+        if recipe.platform != Platform::Windows {
+            return invalid("registry detection is Windows-only".to_string());
+        }
+        validate_registry_key(&check.key)?;
+    }
+    if recipe.depends_on.contains(&recipe.id) || recipe.conflicts_with.contains(&recipe.id) {
+        return invalid("a recipe cannot depend on or conflict with itself".to_string());
+    }
+    Ok(())
+}
+
+fn validate_step(step: &RecipeStep, platform: &Platform) -> Result<()> {
+    match step {
+        RecipeStep::SetRegistryDword { key, value, .. }
+        | RecipeStep::DeleteRegistryValue { key, value } => {
+            if *platform != Platform::Windows {
+                return Err(RecipeError::Invalid(
+                    "registry steps are Windows-only".to_string(),
+                ));
+            }
+            if value.trim().is_empty() {
+                return Err(RecipeError::Invalid(format!(
+                    "registry value name is required for {}",
+                    key
+                )));
+            }
+            validate_registry_key(key)
+        }
+        RecipeStep::RunCommand { program, args } => {
+            let allowed = match platform {
+                Platform::Windows => WINDOWS_PROGRAMS,
+                Platform::Linux => LINUX_PROGRAMS,
+                Platform::MacOS => MACOS_PROGRAMS,
+                Platform::All => &[],
+            };
+            let name = program.to_lowercase();
+            let name = name.strip_suffix(".exe").unwrap_or(&name);
+            if !allowed.contains(&name) {
+                return Err(RecipeError::Invalid(format!(
+                    "{} is not an allowed program, use one of: {}",
+                    program,
+                    allowed.join(", ")
+                )));
+            }
+            if args.iter().any(|arg| arg.contains('\0')) {
+                return Err(RecipeError::Invalid(format!(
+                    "arguments of {} contain a NUL character",
+                    program
+                )));
+            }
+            if !allowed_arguments(name, args) {
+                return Err(RecipeError::Invalid(format!(
+                    "{} {} is not an allowed use of {}",
+                    program,
+                    args.join(" "),
+                    program
+                )));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Whether `args` are one of the fixed subcommands allowed for `program`
+fn allowed_arguments(program: &str, args: &[String]) -> bool {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_lowercase()).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    // A service or unit name, never a path, a remote machine or an option
+    let is_name = |arg: &str| {
+        !arg.is_empty() && !arg.starts_with(['-', '\\']) && !arg.contains(['/', '\\', '='])
+    };
+
+    match program {
+        "powercfg" => args.first().is_some_and(|first| {
+            [
+                "setactive",
+                "change",
+                "setacvalueindex",
+                "setdcvalueindex",
+                "hibernate",
+            ]
+            .contains(&first.trim_start_matches(['-', '/']))
+        }),
+        "ipconfig" => matches!(
+            args.as_slice(),
+            ["/flushdns" | "/registerdns" | "/release" | "/renew"]
+        ),
+        "netsh" => matches!(
+            args.as_slice(),
+            ["interface" | "int", "ipv4" | "ipv6" | "tcp", "set", ..]
+        ),
+        "sc" => match args.as_slice() {
+            ["config", service, "start=", "auto" | "demand" | "disabled" | "delayed-auto"] => {
+                is_name(service)
+            }
+            ["start" | "stop", service] => is_name(service),
+            _ => false,
+        },
+        "sysctl" => {
+            let settings: Vec<&str> = args
+                .iter()
+                .copied()
+                .filter(|arg| !matches!(*arg, "-w" | "-q"))
+                .collect();
+            !settings.is_empty()
+                && settings.iter().all(|setting| {
+                    // Dotted names only: a path could climb out of the family
+                    setting.split_once('=').is_some_and(|(key, _)| {
+                        let key = key.trim();
+                        key.chars()
+                            .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+                            && !key.contains("..")
+                            && SYSCTL_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+                    })
+                })
+        }
+        "systemctl" => match args.split_first() {
+            Some((verb, units)) => {
+                [
+                    "start", "stop", "restart", "enable", "disable", "mask", "unmask",
+                ]
+                .contains(verb)
+                    && !units.is_empty()
+                    && units.iter().all(|unit| is_name(unit))
+            }
+            None => false,
+        },
+        "cpupower" => matches!(args.first(), Some(&("frequency-set" | "idle-set" | "set"))),
+        "powerprofilesctl" => matches!(args.as_slice(), ["set", _]),
+        "gsettings" => matches!(args.first(), Some(&("set" | "reset"))),
+        "pmset" => !args.is_empty(),
+        "mdutil" => matches!(args.first(), Some(&("-i" | "-e" | "-d" | "-a"))),
+        // Login hooks and launch services run a program of the recipe's choice
+        "defaults" => match args.as_slice() {
+            ["write" | "delete", domain, ..] => {
+                is_name(domain)
+                    && !domain.contains("loginwindow")
+                    && !domain.contains("launchservices")
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn validate_registry_key(key: &str) -> Result<()> {
+    // With a trailing separator, so a key is matched as well as its subkeys
+    let normalized = format!("{}\\", key.trim().trim_end_matches('\\').to_uppercase());
+    if !ALLOWED_REGISTRY_ROOTS
+        .iter()
+        .any(|root| normalized.starts_with(root))
+    {
+        return Err(RecipeError::Invalid(format!(
+            "{} is outside the allowed registry roots",
+            key
+        )));
+    }
+    if BLOCKED_REGISTRY_KEYS
+        .iter()
+        .any(|blocked| normalized.contains(blocked))
+    {
+        return Err(RecipeError::Invalid(format!(
+            "{} controls what programs start and cannot be changed by a recipe",
+            key
+        )));
+    }
+    Ok(())
+}
+
+pub(super) struct RecipeOptimization(pub(super) OptimizationRecipe);
+
+impl Optimization for RecipeOptimization {
+    fn item(&self) -> OptimizationItem {
+        let recipe = &self.0;
+        OptimizationItem {
+            id: recipe.id.clone(),
+            name: recipe.name.clone(),
+            description: recipe.description.clone(),
+            category: recipe.category.clone(),
+            is_applied: false,
+            is_reversible: !recipe.revert.is_empty(),
+            requires_admin: recipe.requires_admin,
+            risk_level: recipe.risk_level.clone(),
+            platform: recipe.platform.clone(),
+            depends_on: recipe.depends_on.clone(),
+            conflicts_with: recipe.conflicts_with.clone(),
+            min_build: recipe.min_build,
+        }
+    }
+
+    fn detect(&self) -> bool {
+        #[cfg(target_os = "windows")]
+        {
+            self.0.detect.as_ref().is_some_and(|check| {
+                registry::read_dword(&check.key, &check.value) == Some(check.equals)
+            })
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            false
+        }
+    }
+
+    /// Every registry value the recipe writes or deletes, or the detected
+    /// state for command-only recipes
+    #[cfg(target_os = "windows")]
+    fn snapshot(&self) -> Snapshot {
+        let values: Vec<(&str, &str)> = self
+            .0
+            .apply
+            .iter()
+            .chain(&self.0.revert)
+            .filter_map(|step| match step {
+                RecipeStep::SetRegistryDword { key, value, .. }
+                | RecipeStep::DeleteRegistryValue { key, value } => {
+                    Some((key.as_str(), value.as_str()))
+                }
+                RecipeStep::RunCommand { .. } => None,
+            })
+            .collect();
+
+        if values.is_empty() {
+            vec![("Applied".to_string(), Some(self.detect().to_string()))]
+        } else {
+            registry_snapshot(values)
+        }
+    }
+
+    fn apply(&self) -> anyhow::Result<OptimizationResult> {
+        Ok(run_steps(
+            &self.0.apply,
+            &format!("{} applied", self.0.name),
+        ))
+    }
+
+    fn revert(&self) -> anyhow::Result<OptimizationResult> {
+        Ok(run_steps(
+            &self.0.revert,
+            &format!("{} reverted", self.0.name),
+        ))
+    }
+}
+
+/// Runs steps in order and stops at the first failure. Steps already run are
+/// not rolled back.
+fn run_steps(steps: &[RecipeStep], done: &str) -> OptimizationResult {
+    for (index, step) in steps.iter().enumerate() {
+        if let Err(e) = run_step(step) {
+            return failure(format!("Step {} failed: {}", index + 1, e));
+        }
+    }
+    success(done)
+}
+
+fn run_step(step: &RecipeStep) -> anyhow::Result<()> {
+    match step {
+        #[cfg(target_os = "windows")]
+        RecipeStep::SetRegistryDword { key, value, data } => registry::set_dword(key, value, *data),
+        #[cfg(target_os = "windows")]
+        RecipeStep::DeleteRegistryValue { key, value } => registry::delete_value(key, value),
+        #[cfg(not(target_os = "windows"))]
+        RecipeStep::SetRegistryDword { .. } | RecipeStep::DeleteRegistryValue { .. } => {
+            anyhow::bail!("Registry steps are Windows-only")
+        }
+        RecipeStep::RunCommand { program, args } => {
+            let mut command = Command::new(program);
+            command.args(args);
+            #[cfg(target_os = "windows")]
+            command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+            let output = command.output()?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "{} exited with {}: {}",
+                    program,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::optimization::{RegistryCheck, RiskLevel};
+
+    fn recipe(apply: Vec<RecipeStep>) -> OptimizationRecipe {
+        OptimizationRecipe {
+            id: "disable_mouse_acceleration".to_string(),
+            name: "Disable Mouse Acceleration".to_string(),
+            description: String::new(),
+            category: "Custom".to_string(),
+            platform: Platform::Windows,
+            risk_level: RiskLevel::Low,
+            requires_admin: false,
+            apply,
+            revert: Vec::new(),
+            detect: None,
+            depends_on: Vec::new(),
+            conflicts_with: Vec::new(),
+            min_build: None,
+        }
+    }
+
+    fn set_dword(key: &str) -> RecipeStep {
+        RecipeStep::SetRegistryDword {
+            key: key.to_string(),
+            value: "MouseSpeed".to_string(),
+            data: 0,
+        }
+    }
+
+    #[test]
+    fn test_validate_registry_sandbox() {
+        assert!(validate(&recipe(vec![set_dword("HKCU\\Control Panel\\Mouse")])).is_ok());
+        assert!(validate(&recipe(vec![set_dword("HKLM\\SAM\\SAM")])).is_err());
+        assert!(validate(&recipe(vec![set_dword(
+            "HKLM\\SYSTEM\\CurrentControlSet\\Services\\Spooler"
+        )]))
+        .is_err());
+        assert!(validate(&recipe(vec![set_dword(
+            "HKLM\\SYSTEM\\CurrentControlSet\\Services"
+        )]))
+        .is_err());
+        assert!(validate(&recipe(vec![set_dword(
+            "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\RunOnce"
+        )]))
+        .is_err());
+
+        let mut with_check = recipe(vec![set_dword("HKCU\\Control Panel\\Mouse")]);
+        with_check.detect = Some(RegistryCheck {
+            key: "HKLM\\SECURITY\\Policy".to_string(),
+            value: "MouseSpeed".to_string(),
+            equals: 0,
+        });
+        assert!(validate(&with_check).is_err());
+    }
+
+    #[test]
+    fn test_validate_commands() {
+        let run = |program: &str| RecipeStep::RunCommand {
+            program: program.to_string(),
+            args: vec!["/setactive".to_string(), "scheme_min".to_string()],
+        };
+        assert!(validate(&recipe(vec![run("powercfg.exe")])).is_ok());
+        assert!(validate(&recipe(vec![run("powershell")])).is_err());
+        assert!(validate(&recipe(vec![run("C:\\Windows\\System32\\powercfg.exe")])).is_err());
+
+        let mut linux = recipe(vec![run("sysctl")]);
+        linux.platform = Platform::Linux;
+        linux.apply = vec![command("sysctl", &["-w", "vm.swappiness=10"])];
+        assert!(validate(&linux).is_ok());
+        linux.apply.push(set_dword("HKCU\\Control Panel\\Mouse"));
+        assert!(validate(&linux).is_err());
+    }
+
+    fn command(program: &str, args: &[&str]) -> RecipeStep {
+        RecipeStep::RunCommand {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_command_arguments() {
+        let windows =
+            |program: &str, args: &[&str]| validate(&recipe(vec![command(program, args)])).is_ok();
+        assert!(windows("sc", &["config", "SysMain", "start=", "disabled"]));
+        assert!(windows("sc", &["stop", "SysMain"]));
+        assert!(!windows("sc", &["create", "x", "binPath=", "C:\\x.exe"]));
+        assert!(!windows(
+            "sc",
+            &["config", "SysMain", "binPath=", "C:\\x.exe"]
+        ));
+        assert!(!windows("sc", &["\\\\server", "stop", "SysMain"]));
+        assert!(!windows("netsh", &["add", "helper", "C:\\x.dll"]));
+        assert!(!windows("powercfg", &["/import", "C:\\plan.pow"]));
+        assert!(windows("ipconfig", &["/flushdns"]));
+
+        let linux = |program: &str, args: &[&str]| {
+            let mut linux = recipe(vec![command(program, args)]);
+            linux.platform = Platform::Linux;
+            validate(&linux).is_ok()
+        };
+        assert!(linux("systemctl", &["disable", "bluetooth.service"]));
+        assert!(!linux("systemctl", &["enable", "/home/user/evil.service"]));
+        assert!(!linux(
+            "systemctl",
+            &["set-environment", "LD_PRELOAD=/tmp/x.so"]
+        ));
+        assert!(!linux("sysctl", &["-w", "kernel.core_pattern=|/tmp/x"]));
+        assert!(!linux("sysctl", &["kernel/modprobe=/tmp/x"]));
+        assert!(!linux("sysctl", &["vm/../kernel/core_pattern=|/tmp/x"]));
+        assert!(linux("powerprofilesctl", &["set", "performance"]));
+        assert!(!linux("powerprofilesctl", &["launch", "/tmp/x"]));
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        let mut bad_id = recipe(vec![set_dword("HKCU\\Control Panel\\Mouse")]);
+        bad_id.id = "Mouse Tweak".to_string();
+        assert!(validate(&bad_id).is_err());
+        assert!(validate(&recipe(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn test_recipe_json() {
+        let json = r#"{
+            "id": "prefer_ethernet",
+            "name": "Prefer Ethernet",
+            "platform": "Windows",
+            "risk_level": "Low",
+            "apply": [{ "type": "run_command", "program": "netsh", "args": ["interface", "ipv4", "set", "interface", "Ethernet", "metric=5"] }]
+        }"#;
+        let recipe: OptimizationRecipe = serde_json::from_str(json).unwrap();
+        assert_eq!(recipe.category, "Custom");
+        assert!(recipe.revert.is_empty());
+        assert!(validate(&recipe).is_ok());
+        assert!(!RecipeOptimization(recipe).item().is_reversible);
+    }
+}
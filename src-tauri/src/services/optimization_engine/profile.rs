@@ -0,0 +1,186 @@
+use crate::models::optimization::OptimizationItem;
+
+/// Apply order for a set of optimizations, and the ones that cannot be applied
+#[derive(Debug, Default)]
+pub(super) struct ProfilePlan {
+    pub order: Vec<String>,
+    pub rejected: Vec<(String, String)>, // id, reason
+}
+
+/// Orders the requested optimizations so dependencies run first, and rejects
+/// any that conflict with each other or with something already applied, or
+/// whose dependencies are neither requested nor applied
+pub(super) fn plan(
+    requested: &[OptimizationItem],
+    is_applied: &dyn Fn(&str) -> bool,
+) -> ProfilePlan {
+    let conflicts = |a: &OptimizationItem, b: &str| {
+        a.conflicts_with.iter().any(|id| id == b)
+            || requested
+                .iter()
+                .any(|other| other.id == b && other.conflicts_with.contains(&a.id))
+    };
+
+    let mut plan = ProfilePlan::default();
+
+    for item in requested {
+        if let Some(other) = requested
+            .iter()
+            .find(|other| other.id != item.id && conflicts(item, &other.id))
+        {
+            reject(
+                &mut plan,
+                &item.id,
+                format!("Conflicts with {} in the same profile", other.name),
+            );
+        } else if let Some(applied) = item
+            .conflicts_with
+            .iter()
+            .find(|id| is_applied(id.as_str()))
+        {
+            reject(
+                &mut plan,
+                &item.id,
+                format!(
+                    "Conflicts with {}, which is applied; revert it first",
+                    applied
+                ),
+            );
+        } else if let Some(missing) = item
+            .depends_on
+            .iter()
+            .find(|id| !requested.iter().any(|other| &other.id == *id) && !is_applied(id.as_str()))
+        {
+            reject(
+                &mut plan,
+                &item.id,
+                format!(
+                    "Requires {}; add it to the profile or apply it first",
+                    missing
+                ),
+            );
+        }
+    }
+
+    // Kahn's algorithm in request order; whatever never becomes ready either
+    // waits on a rejected optimization or sits in a dependency cycle
+    let mut pending: Vec<&OptimizationItem> = requested
+        .iter()
+        .filter(|item| !plan.rejected.iter().any(|(id, _)| *id == item.id))
+        .collect();
+    loop {
+        let ready = pending.iter().position(|item| {
+            item.depends_on.iter().all(|dependency| {
+                plan.order.contains(dependency)
+                    || (!requested.iter().any(|other| &other.id == dependency)
+                        && is_applied(dependency))
+            })
+        });
+        match ready {
+            Some(index) => plan.order.push(pending.remove(index).id.clone()),
+            None => break,
+        }
+    }
+    for item in pending {
+        let blocker = item
+            .depends_on
+            .iter()
+            .find(|dependency| plan.rejected.iter().any(|(id, _)| id == *dependency));
+        let reason = match blocker {
+            Some(dependency) => format!("Requires {}, which cannot be applied", dependency),
+            None => "Circular dependency between optimizations".to_string(),
+        };
+        reject(&mut plan, &item.id, reason);
+    }
+
+    plan
+}
+
+fn reject(plan: &mut ProfilePlan, id: &str, reason: String) {
+    if !plan.rejected.iter().any(|(rejected, _)| rejected == id) {
+        plan.rejected.push((id.to_string(), reason));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::optimization::{Platform, RiskLevel};
+
+    fn item(id: &str, depends_on: &[&str], conflicts_with: &[&str]) -> OptimizationItem {
+        OptimizationItem {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            category: "Custom".to_string(),
+            is_applied: false,
+            is_reversible: true,
+            requires_admin: false,
+            risk_level: RiskLevel::Low,
+            platform: Platform::All,
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            conflicts_with: conflicts_with.iter().map(|s| s.to_string()).collect(),
+            min_build: None,
+        }
+    }
+
+    fn rejected(plan: &ProfilePlan) -> Vec<&str> {
+        plan.rejected.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_dependencies_run_first() {
+        let requested = [
+            item("b", &["a"], &[]),
+            item("a", &[], &[]),
+            item("c", &[], &[]),
+        ];
+        let plan = plan(&requested, &|_| false);
+        assert_eq!(plan.order, ["a", "b", "c"]);
+        assert!(plan.rejected.is_empty());
+
+        // Already applied dependencies do not need to be requested again
+        let plan = super::plan(&[item("b", &["a"], &[])], &|id| id == "a");
+        assert_eq!(plan.order, ["b"]);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let requested = [
+            item("high_performance", &[], &["balanced"]),
+            item("balanced", &[], &[]),
+            item("other", &[], &[]),
+        ];
+        let plan = plan(&requested, &|_| false);
+        assert_eq!(rejected(&plan), ["high_performance", "balanced"]);
+        assert_eq!(plan.order, ["other"]);
+
+        let plan = super::plan(&[item("high_performance", &[], &["balanced"])], &|id| {
+            id == "balanced"
+        });
+        assert!(plan.order.is_empty());
+        assert!(plan.rejected[0].1.contains("revert it first"));
+    }
+
+    #[test]
+    fn test_missing_and_failed_dependencies() {
+        let plan = plan(&[item("b", &["a"], &[])], &|_| false);
+        assert_eq!(rejected(&plan), ["b"]);
+
+        let requested = [
+            item("a", &[], &["x"]),
+            item("b", &["a"], &[]),
+            item("x", &[], &[]),
+        ];
+        let plan = super::plan(&requested, &|_| false);
+        assert_eq!(rejected(&plan), ["a", "x", "b"]);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let requested = [item("a", &["b"], &[]), item("b", &["a"], &[])];
+        let plan = plan(&requested, &|_| false);
+        assert!(plan.order.is_empty());
+        assert_eq!(rejected(&plan), ["a", "b"]);
+    }
+}
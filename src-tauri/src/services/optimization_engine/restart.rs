@@ -0,0 +1,242 @@
+use crate::models::optimization::{AuditAction, OptimizationResult, PendingRestartItem};
+use crate::shared::{events::now_millis, paths};
+use anyhow::Result;
+use std::process::Command;
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const PENDING_FILE: &str = "pending_restart.json";
+// shutdown on Windows accepts up to ten years, a day is plenty here
+const MAX_DELAY_MINUTES: u32 = 24 * 60;
+
+static PENDING: once_cell::sync::Lazy<Mutex<Vec<PendingRestartItem>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_pending()));
+
+/// Entries recorded before the last boot already took effect
+fn load_pending() -> Vec<PendingRestartItem> {
+    let pending: Vec<PendingRestartItem> =
+        std::fs::read_to_string(paths::app_data_file(PENDING_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+    since_boot(pending, sysinfo::System::boot_time() * 1000)
+}
+
+/// Applies and reverts still waiting for a restart, oldest first
+pub fn get_pending_restart_items() -> Vec<PendingRestartItem> {
+    PENDING
+        .lock()
+        .map(|pending| pending.clone())
+        .unwrap_or_default()
+}
+
+/// Updates the pending list after an apply or revert. Only the latest
+/// successful action on an optimization counts, so reverting a change before
+/// restarting clears it when the revert itself needs no restart.
+pub(super) fn track(
+    action: AuditAction,
+    optimization_id: &str,
+    optimization_name: &str,
+    outcome: &Result<OptimizationResult>,
+) {
+    let Ok(result) = outcome else {
+        return;
+    };
+    if let Ok(mut pending) = PENDING.lock() {
+        if update(
+            &mut pending,
+            action,
+            optimization_id,
+            optimization_name,
+            result,
+        ) {
+            if let Ok(content) = serde_json::to_string_pretty(&*pending) {
+                let _ = std::fs::write(paths::app_data_file(PENDING_FILE), content);
+            }
+        }
+    }
+}
+
+/// Returns whether the list changed
+fn update(
+    pending: &mut Vec<PendingRestartItem>,
+    action: AuditAction,
+    optimization_id: &str,
+    optimization_name: &str,
+    result: &OptimizationResult,
+) -> bool {
+    if !result.success {
+        return false;
+    }
+
+    let count = pending.len();
+    pending.retain(|item| item.optimization_id != optimization_id);
+    if result.needs_restart {
+        pending.push(PendingRestartItem {
+            optimization_id: optimization_id.to_string(),
+            optimization_name: optimization_name.to_string(),
+            action,
+            requested_at: now_millis(),
+        });
+        return true;
+    }
+    pending.len() != count
+}
+
+fn since_boot(pending: Vec<PendingRestartItem>, boot_millis: u64) -> Vec<PendingRestartItem> {
+    pending
+        .into_iter()
+        .filter(|item| item.requested_at >= boot_millis)
+        .collect()
+}
+
+/// Restarts the machine after `minutes`, replacing any restart already
+/// scheduled through the system shutdown command
+pub fn schedule_restart(minutes: u32) -> Result<()> {
+    if minutes > MAX_DELAY_MINUTES {
+        anyhow::bail!(
+            "Restart delay must be at most {} minutes",
+            MAX_DELAY_MINUTES
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // A second /r fails while one is pending, so drop the old one first
+        let _ = shutdown(&["/a"]);
+        shutdown(&[
+            "/r",
+            "/t",
+            &(minutes * 60).to_string(),
+            "/c",
+            "Aura is restarting to finish applying optimizations",
+        ])
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let when = if minutes == 0 {
+            "now".to_string()
+        } else {
+            format!("+{}", minutes)
+        };
+        shutdown(&["-r", &when])
+    }
+}
+
+pub fn cancel_scheduled_restart() -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        shutdown(&["/a"])
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        shutdown(&["-c"])
+    }
+
+    // macOS shutdown has no cancel flag; the pending shutdown process is
+    // what carries out the restart
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("killall").arg("shutdown").output()?;
+        if !output.status.success() {
+            anyhow::bail!("No scheduled restart to cancel");
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        anyhow::bail!("Scheduling restarts is not supported on this platform")
+    }
+}
+
+fn shutdown(args: &[&str]) -> Result<()> {
+    let mut command = Command::new("shutdown");
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = command.output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout
+        } else {
+            stderr
+        };
+        anyhow::bail!("shutdown failed: {}", detail.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(success: bool, needs_restart: bool) -> OptimizationResult {
+        OptimizationResult {
+            success,
+            message: String::new(),
+            needs_restart,
+        }
+    }
+
+    #[test]
+    fn test_update_pending() {
+        let mut pending = Vec::new();
+        assert!(update(
+            &mut pending,
+            AuditAction::Apply,
+            "irq8_priority",
+            "IRQ8",
+            &result(true, true)
+        ));
+        assert_eq!(pending.len(), 1);
+
+        // A failed revert leaves the change pending
+        assert!(!update(
+            &mut pending,
+            AuditAction::Revert,
+            "irq8_priority",
+            "IRQ8",
+            &result(false, false)
+        ));
+        assert_eq!(pending.len(), 1);
+
+        // Reverting to the running state needs no restart
+        assert!(update(
+            &mut pending,
+            AuditAction::Revert,
+            "irq8_priority",
+            "IRQ8",
+            &result(true, false)
+        ));
+        assert!(pending.is_empty());
+
+        assert!(!update(
+            &mut pending,
+            AuditAction::Apply,
+            "enable_game_mode",
+            "Game Mode",
+            &result(true, false)
+        ));
+    }
+
+    #[test]
+    fn test_since_boot() {
+        let item = |requested_at| PendingRestartItem {
+            optimization_id: "irq8_priority".to_string(),
+            optimization_name: "IRQ8".to_string(),
+            action: AuditAction::Apply,
+            requested_at,
+        };
+        let pending = since_boot(vec![item(1_000), item(5_000)], 2_000);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].requested_at, 5_000);
+    }
+}
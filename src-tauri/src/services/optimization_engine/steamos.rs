@@ -0,0 +1,426 @@
+//! Optimizations tailored to SteamOS on the Steam Deck. The system image is
+//! read-only, so everything here lives in /etc or the user's home directory.
+
+use super::{failure, item, success, Optimization, Snapshot};
+use crate::models::optimization::{OptimizationItem, OptimizationResult, Platform, RiskLevel};
+use crate::services::os_version;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+
+const SWAPPINESS_FILE: &str = "/etc/sysctl.d/99-aura-swappiness.conf";
+const SWAPPINESS_PATH: &str = "/proc/sys/vm/swappiness";
+// What CryoUtilities settled on for the Deck's 16 GB of shared memory
+const STEAMOS_SWAPPINESS: u32 = 1;
+const ZRAM_CONFIG: &str = "/etc/systemd/zram-generator.conf";
+const ZRAM_BACKUP: &str = "/etc/systemd/zram-generator.conf.aura-backup";
+const AURA_MARKER: &str = "# Written by Aura";
+const GAMESCOPE_ENV_FILE: &str = ".config/environment.d/aura-gamescope.conf";
+
+// STAPM and slow limits before Aura raised them. The APU goes back to its
+// firmware limits on every boot, so this only needs to outlive the session.
+static ORIGINAL_TDP: Mutex<Option<(f32, f32)>> = Mutex::new(None);
+
+pub(super) fn optimizations() -> Vec<Box<dyn Optimization>> {
+    vec![
+        Box::new(SteamOsSwappiness),
+        Box::new(SteamOsZram),
+        Box::new(GamescopeTearing),
+        Box::new(SustainedTdp),
+    ]
+}
+
+fn steamos_item(
+    id: &str,
+    name: &str,
+    description: &str,
+    requires_admin: bool,
+    risk_level: RiskLevel,
+) -> OptimizationItem {
+    item(
+        id,
+        name,
+        description,
+        "Steam Deck",
+        Platform::Linux,
+        requires_admin,
+        risk_level,
+    )
+}
+
+struct SteamOsSwappiness;
+
+impl Optimization for SteamOsSwappiness {
+    fn item(&self) -> OptimizationItem {
+        steamos_item(
+            "steamos_swappiness",
+            "Lower Swappiness",
+            "Keeps game memory out of swap until RAM is actually full, avoiding stutter on the Deck",
+            true,
+            RiskLevel::Low,
+        )
+    }
+
+    fn supported(&self) -> bool {
+        os_version::is_steamos()
+    }
+
+    fn detect(&self) -> bool {
+        PathBuf::from(SWAPPINESS_FILE).exists() && current_swappiness() == Some(STEAMOS_SWAPPINESS)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        vec![(
+            "vm.swappiness".to_string(),
+            current_swappiness().map(|value| value.to_string()),
+        )]
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        // The previous value goes in the file so revert can restore it
+        let previous = current_swappiness().unwrap_or(100);
+        let content = format!(
+            "{}, previously {}\nvm.swappiness = {}\n",
+            AURA_MARKER, previous, STEAMOS_SWAPPINESS
+        );
+        if let Err(e) = std::fs::write(SWAPPINESS_FILE, content) {
+            return Ok(failure(format!(
+                "Failed to write {}: {}",
+                SWAPPINESS_FILE, e
+            )));
+        }
+
+        let result = match run(
+            "sysctl",
+            &[&format!("vm.swappiness={}", STEAMOS_SWAPPINESS)],
+        ) {
+            Ok(_) => success("Swappiness lowered"),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        let previous = std::fs::read_to_string(SWAPPINESS_FILE)
+            .ok()
+            .and_then(|content| previous_swappiness(&content))
+            .unwrap_or(100);
+        if let Err(e) = remove_if_exists(SWAPPINESS_FILE) {
+            return Ok(failure(format!(
+                "Failed to remove {}: {}",
+                SWAPPINESS_FILE, e
+            )));
+        }
+
+        let result = match run("sysctl", &[&format!("vm.swappiness={}", previous)]) {
+            Ok(_) => success("Swappiness restored"),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+}
+
+fn current_swappiness() -> Option<u32> {
+    std::fs::read_to_string(SWAPPINESS_PATH)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Reads the value recorded in the `# Written by Aura, previously 100` line
+fn previous_swappiness(content: &str) -> Option<u32> {
+    content.lines().find_map(|line| {
+        line.strip_prefix(AURA_MARKER)?
+            .trim_start_matches(", previously ")
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
+struct SteamOsZram;
+
+impl Optimization for SteamOsZram {
+    fn item(&self) -> OptimizationItem {
+        steamos_item(
+            "steamos_zram",
+            "Compressed RAM Swap",
+            "Swaps to zstd-compressed RAM before the slower swap file, sized to half the memory. Takes effect after a restart",
+            true,
+            RiskLevel::Low,
+        )
+    }
+
+    fn supported(&self) -> bool {
+        os_version::is_steamos()
+    }
+
+    fn detect(&self) -> bool {
+        std::fs::read_to_string(ZRAM_CONFIG).is_ok_and(|content| content.starts_with(AURA_MARKER))
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        // Keep a config Valve or the user wrote, unless it is already ours
+        if !self.detect() && PathBuf::from(ZRAM_CONFIG).exists() {
+            if let Err(e) = std::fs::copy(ZRAM_CONFIG, ZRAM_BACKUP) {
+                return Ok(failure(format!("Failed to back up {}: {}", ZRAM_CONFIG, e)));
+            }
+        }
+
+        let content = format!(
+            "{}\n[zram0]\nzram-size = ram / 2\ncompression-algorithm = zstd\nswap-priority = 100\n",
+            AURA_MARKER
+        );
+        let result = match std::fs::write(ZRAM_CONFIG, content) {
+            Ok(()) => OptimizationResult {
+                needs_restart: true,
+                ..success("Compressed RAM swap configured, restart to apply")
+            },
+            Err(e) => failure(format!("Failed to write {}: {}", ZRAM_CONFIG, e)),
+        };
+        Ok(result)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        let outcome = if PathBuf::from(ZRAM_BACKUP).exists() {
+            std::fs::rename(ZRAM_BACKUP, ZRAM_CONFIG)
+        } else {
+            remove_if_exists(ZRAM_CONFIG)
+        };
+        let result = match outcome {
+            Ok(()) => OptimizationResult {
+                needs_restart: true,
+                ..success("Swap configuration restored, restart to apply")
+            },
+            Err(e) => failure(format!("Failed to restore {}: {}", ZRAM_CONFIG, e)),
+        };
+        Ok(result)
+    }
+}
+
+struct GamescopeTearing;
+
+impl Optimization for GamescopeTearing {
+    fn item(&self) -> OptimizationItem {
+        steamos_item(
+            "gamescope_allow_tearing",
+            "Allow Tearing in Game Mode",
+            "Adds the tearing option to the Game Mode performance overlay, trading tearing for lower input latency. Takes effect when Game Mode restarts",
+            false,
+            RiskLevel::Low,
+        )
+    }
+
+    fn supported(&self) -> bool {
+        os_version::is_steamos()
+    }
+
+    fn detect(&self) -> bool {
+        gamescope_env_file().is_some_and(|path| path.exists())
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        let Some(path) = gamescope_env_file() else {
+            return Ok(failure("Home directory not found"));
+        };
+        let content = format!("{}\nSTEAM_GAMESCOPE_TEARING_SUPPORTED=1\n", AURA_MARKER);
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, content));
+
+        let result = match written {
+            Ok(()) => success("Tearing option enabled, restart Game Mode to apply"),
+            Err(e) => failure(format!("Failed to write {}: {}", path.display(), e)),
+        };
+        Ok(result)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        let Some(path) = gamescope_env_file() else {
+            return Ok(failure("Home directory not found"));
+        };
+        let result = match remove_if_exists(&path) {
+            Ok(()) => success("Tearing option removed, restart Game Mode to apply"),
+            Err(e) => failure(format!("Failed to remove {}: {}", path.display(), e)),
+        };
+        Ok(result)
+    }
+}
+
+/// systemd user environment file, read by the Game Mode session at startup
+fn gamescope_env_file() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(GAMESCOPE_ENV_FILE))
+}
+
+struct SustainedTdp;
+
+impl Optimization for SustainedTdp {
+    fn item(&self) -> OptimizationItem {
+        steamos_item(
+            "steamos_sustained_tdp",
+            "Sustained Boost Power",
+            "Raises the APU's sustained power limits to its boost limit with ryzenadj, for steadier frame rates at the cost of battery life and heat. Resets on restart",
+            true,
+            RiskLevel::Medium,
+        )
+    }
+
+    // ryzenadj is not part of SteamOS, only offered where the user installed it
+    fn supported(&self) -> bool {
+        os_version::is_steamos() && ryzenadj_installed()
+    }
+
+    fn detect(&self) -> bool {
+        read_tdp().is_some_and(|limits| limits.stapm >= limits.fast && limits.slow >= limits.fast)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let limits = read_tdp();
+        let watts = |value: Option<f32>| value.map(|watts| format!("{:.1} W", watts));
+        vec![
+            ("STAPM limit".to_string(), watts(limits.map(|l| l.stapm))),
+            ("Slow limit".to_string(), watts(limits.map(|l| l.slow))),
+            ("Fast limit".to_string(), watts(limits.map(|l| l.fast))),
+        ]
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        let Some(limits) = read_tdp() else {
+            return Ok(failure(
+                "Could not read the power limits, ryzenadj needs root",
+            ));
+        };
+        if let Ok(mut original) = ORIGINAL_TDP.lock() {
+            original.get_or_insert((limits.stapm, limits.slow));
+        }
+
+        let result = match set_tdp(limits.fast, limits.fast) {
+            Ok(()) => success(format!(
+                "Sustained power raised to {:.1} W until the next restart",
+                limits.fast
+            )),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        let original = ORIGINAL_TDP
+            .lock()
+            .ok()
+            .and_then(|mut original| original.take());
+        let Some((stapm, slow)) = original else {
+            return Ok(failure(
+                "The original power limits are unknown, restart to go back to the defaults",
+            ));
+        };
+
+        let result = match set_tdp(stapm, slow) {
+            Ok(()) => success("Power limits restored"),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+}
+
+/// APU power limits in watts
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TdpLimits {
+    stapm: f32,
+    fast: f32,
+    slow: f32,
+}
+
+fn ryzenadj_installed() -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join("ryzenadj").is_file()))
+}
+
+fn read_tdp() -> Option<TdpLimits> {
+    run("ryzenadj", &["-i"])
+        .ok()
+        .and_then(|output| parse_ryzenadj_info(&output))
+}
+
+fn set_tdp(stapm: f32, slow: f32) -> Result<()> {
+    // ryzenadj takes milliwatts
+    let milliwatts = |watts: f32| (watts * 1000.0).round() as u32;
+    run(
+        "ryzenadj",
+        &[
+            &format!("--stapm-limit={}", milliwatts(stapm)),
+            &format!("--slow-limit={}", milliwatts(slow)),
+        ],
+    )
+    .map(|_| ())
+}
+
+/// Reads the limits from the table `ryzenadj -i` prints:
+/// `| STAPM LIMIT         |    15.000 | stapm-limit        |`
+fn parse_ryzenadj_info(output: &str) -> Option<TdpLimits> {
+    let value = |name: &str| {
+        output.lines().find_map(|line| {
+            let mut columns = line.split('|').map(str::trim).skip(1);
+            if columns.next()? != name {
+                return None;
+            }
+            columns.next()?.parse::<f32>().ok()
+        })
+    };
+
+    Some(TdpLimits {
+        stapm: value("STAPM LIMIT")?,
+        fast: value("PPT LIMIT FAST")?,
+        slow: value("PPT LIMIT SLOW")?,
+    })
+}
+
+/// Runs a command, returning its output or failing with its stderr
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program).args(args).output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn remove_if_exists(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ryzenadj_info() {
+        let output = "CPU Family: Van Gogh\n\
+            |        Name         |   Value   |     Parameter      |\n\
+            |---------------------|-----------|--------------------|\n\
+            | STAPM LIMIT         |    15.000 | stapm-limit        |\n\
+            | STAPM VALUE         |     3.427 |                    |\n\
+            | PPT LIMIT FAST      |    15.000 | fast-limit         |\n\
+            | PPT LIMIT SLOW      |    12.000 | slow-limit         |\n";
+        let limits = parse_ryzenadj_info(output).unwrap();
+        assert_eq!(limits.stapm, 15.0);
+        assert_eq!(limits.fast, 15.0);
+        assert_eq!(limits.slow, 12.0);
+
+        assert!(parse_ryzenadj_info("CPU Family: Van Gogh\n").is_none());
+    }
+
+    #[test]
+    fn test_previous_swappiness() {
+        let content = "# Written by Aura, previously 100\nvm.swappiness = 1\n";
+        assert_eq!(previous_swappiness(content), Some(100));
+        assert_eq!(previous_swappiness("vm.swappiness = 1\n"), None);
+    }
+}
@@ -0,0 +1,77 @@
+use super::{failure, item, success, Optimization, Snapshot};
+use crate::models::optimization::{OptimizationItem, OptimizationResult, Platform, RiskLevel};
+use crate::services::hosts_blocklist;
+use anyhow::Result;
+
+pub(super) fn optimizations() -> Vec<Box<dyn Optimization>> {
+    vec![Box::new(HighPriorityMode), Box::new(BlockLauncherTelemetry)]
+}
+
+struct HighPriorityMode;
+
+impl Optimization for HighPriorityMode {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "set_high_priority",
+            "High Priority Mode",
+            "Runs the application with high priority for better performance",
+            "Process Management",
+            Platform::All,
+            false,
+            RiskLevel::Low,
+        )
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        Ok(success("High priority mode enabled"))
+    }
+}
+
+struct BlockLauncherTelemetry;
+
+impl Optimization for BlockLauncherTelemetry {
+    fn item(&self) -> OptimizationItem {
+        item(
+            "block_launcher_telemetry",
+            "Block Launcher Telemetry",
+            format!(
+                "Points {} analytics and telemetry hosts of game launchers, overlays and engines to 0.0.0.0 in the hosts file. Reverting removes exactly those lines",
+                hosts_blocklist::BLOCKLIST.len()
+            ),
+            "Privacy & Telemetry",
+            Platform::All,
+            true,
+            RiskLevel::Low,
+        )
+    }
+
+    fn detect(&self) -> bool {
+        hosts_blocklist::is_applied()
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let blocked = hosts_blocklist::get_status()
+            .ok()
+            .map(|status| status.managed.len().to_string());
+        vec![("Domains blocked by Aura".to_string(), blocked)]
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        let result = match hosts_blocklist::apply() {
+            Ok(status) => success(format!(
+                "Blocked {} telemetry domains",
+                status.managed.len()
+            )),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        let result = match hosts_blocklist::revert() {
+            Ok(_) => success("Telemetry blocklist removed from the hosts file"),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+}
@@ -0,0 +1,333 @@
+//! Optimizations for features that only exist on Windows 11. The engine
+//! hides them on older builds through `min_build`.
+
+use super::{failure, item, success, Optimization};
+use crate::models::optimization::{OptimizationItem, OptimizationResult, Platform, RiskLevel};
+use crate::services::os_version::WINDOWS_11_BUILD;
+use anyhow::Result;
+
+#[cfg(target_os = "windows")]
+use super::audit::registry_snapshot;
+#[cfg(target_os = "windows")]
+use super::Snapshot;
+#[cfg(target_os = "windows")]
+use crate::shared::registry;
+
+// Copilot shipped with 23H2, whose servicing builds share 22621 with 22H2
+const COPILOT_BUILD: u32 = 22621;
+
+#[cfg(target_os = "windows")]
+const WIDGETS_POLICY_KEY: &str = "HKLM\\SOFTWARE\\Policies\\Microsoft\\Dsh";
+#[cfg(target_os = "windows")]
+const COPILOT_POLICY_KEY: &str = "HKCU\\Software\\Policies\\Microsoft\\Windows\\WindowsCopilot";
+#[cfg(target_os = "windows")]
+const COPILOT_MACHINE_POLICY_KEY: &str =
+    "HKLM\\SOFTWARE\\Policies\\Microsoft\\Windows\\WindowsCopilot";
+// An empty in-process server for the new menu's COM class makes Explorer fall
+// back to the classic context menu
+#[cfg(target_os = "windows")]
+const CONTEXT_MENU_CLASS_KEY: &str =
+    "HKCU\\Software\\Classes\\CLSID\\{86ca1aa0-34aa-4e8b-a509-50c905bae2a2}";
+#[cfg(target_os = "windows")]
+const CONTEXT_MENU_SERVER_KEY: &str =
+    "HKCU\\Software\\Classes\\CLSID\\{86ca1aa0-34aa-4e8b-a509-50c905bae2a2}\\InprocServer32";
+#[cfg(target_os = "windows")]
+const DEVICE_GUARD_KEY: &str = "HKLM\\SYSTEM\\CurrentControlSet\\Control\\DeviceGuard";
+
+pub(super) fn optimizations() -> Vec<Box<dyn Optimization>> {
+    vec![
+        Box::new(DisableWidgets),
+        Box::new(DisableCopilot),
+        Box::new(ClassicContextMenu),
+        Box::new(DisableVbs),
+    ]
+}
+
+fn windows11_item(
+    id: &str,
+    name: &str,
+    description: &str,
+    min_build: u32,
+    requires_admin: bool,
+    risk_level: RiskLevel,
+) -> OptimizationItem {
+    OptimizationItem {
+        min_build: Some(min_build),
+        ..item(
+            id,
+            name,
+            description,
+            "Windows 11",
+            Platform::Windows,
+            requires_admin,
+            risk_level,
+        )
+    }
+}
+
+struct DisableWidgets;
+
+impl Optimization for DisableWidgets {
+    fn item(&self) -> OptimizationItem {
+        windows11_item(
+            "disable_widgets",
+            "Disable Widgets",
+            "Turns off the Widgets board so its news feed stops refreshing in the background",
+            WINDOWS_11_BUILD,
+            true,
+            RiskLevel::Low,
+        )
+    }
+
+    fn detect(&self) -> bool {
+        #[cfg(target_os = "windows")]
+        {
+            registry::read_dword(WIDGETS_POLICY_KEY, "AllowNewsAndInterests") == Some(0)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            false
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn snapshot(&self) -> Snapshot {
+        registry_snapshot([(WIDGETS_POLICY_KEY, "AllowNewsAndInterests")])
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        set_widgets(false)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        set_widgets(true)
+    }
+}
+
+fn set_widgets(enabled: bool) -> Result<OptimizationResult> {
+    #[cfg(target_os = "windows")]
+    {
+        let outcome = if enabled {
+            registry::delete_value(WIDGETS_POLICY_KEY, "AllowNewsAndInterests")
+        } else {
+            registry::set_dword(WIDGETS_POLICY_KEY, "AllowNewsAndInterests", 0)
+        };
+        let result = match outcome {
+            Ok(()) if enabled => success("Widgets enabled"),
+            Ok(()) => success("Widgets disabled"),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = enabled;
+        Ok(failure("Widgets are Windows-only"))
+    }
+}
+
+struct DisableCopilot;
+
+impl Optimization for DisableCopilot {
+    fn item(&self) -> OptimizationItem {
+        windows11_item(
+            "disable_copilot",
+            "Disable Copilot",
+            "Turns off Windows Copilot and its sidebar for the current user. Takes effect after signing out",
+            COPILOT_BUILD,
+            false,
+            RiskLevel::Low,
+        )
+    }
+
+    fn detect(&self) -> bool {
+        // Also applied when an administrator turned it off for every user
+        #[cfg(target_os = "windows")]
+        {
+            [COPILOT_POLICY_KEY, COPILOT_MACHINE_POLICY_KEY]
+                .iter()
+                .any(|key| registry::read_dword(key, "TurnOffWindowsCopilot") == Some(1))
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            false
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn snapshot(&self) -> Snapshot {
+        registry_snapshot([(COPILOT_POLICY_KEY, "TurnOffWindowsCopilot")])
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        set_copilot(false)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        set_copilot(true)
+    }
+}
+
+fn set_copilot(enabled: bool) -> Result<OptimizationResult> {
+    #[cfg(target_os = "windows")]
+    {
+        let outcome = if enabled {
+            registry::delete_value(COPILOT_POLICY_KEY, "TurnOffWindowsCopilot")
+        } else {
+            registry::set_dword(COPILOT_POLICY_KEY, "TurnOffWindowsCopilot", 1)
+        };
+        let result = match outcome {
+            Ok(()) if enabled => success("Copilot enabled, sign out to apply"),
+            Ok(()) => success("Copilot disabled, sign out to apply"),
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = enabled;
+        Ok(failure("Copilot is Windows-only"))
+    }
+}
+
+struct ClassicContextMenu;
+
+impl Optimization for ClassicContextMenu {
+    fn item(&self) -> OptimizationItem {
+        windows11_item(
+            "classic_context_menu",
+            "Classic Context Menu",
+            "Restores the full right-click menu without the \"Show more options\" step. Takes effect after Explorer restarts",
+            WINDOWS_11_BUILD,
+            false,
+            RiskLevel::Low,
+        )
+    }
+
+    fn detect(&self) -> bool {
+        #[cfg(target_os = "windows")]
+        {
+            registry::key_exists(CONTEXT_MENU_SERVER_KEY)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            false
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn snapshot(&self) -> Snapshot {
+        let present = registry::key_exists(CONTEXT_MENU_SERVER_KEY);
+        vec![(
+            CONTEXT_MENU_SERVER_KEY.to_string(),
+            present.then(|| "present".to_string()),
+        )]
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        #[cfg(target_os = "windows")]
+        {
+            let result = match registry::create_key(CONTEXT_MENU_SERVER_KEY) {
+                Ok(()) => success("Classic context menu restored, restart Explorer to apply"),
+                Err(e) => failure(e.to_string()),
+            };
+            Ok(result)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Ok(failure("The classic context menu is Windows-only"))
+        }
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        #[cfg(target_os = "windows")]
+        {
+            let result = match registry::delete_key(CONTEXT_MENU_CLASS_KEY) {
+                Ok(()) => success("Windows 11 context menu restored, restart Explorer to apply"),
+                Err(e) => failure(e.to_string()),
+            };
+            Ok(result)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Ok(failure("The classic context menu is Windows-only"))
+        }
+    }
+}
+
+struct DisableVbs;
+
+impl Optimization for DisableVbs {
+    fn item(&self) -> OptimizationItem {
+        windows11_item(
+            "disable_vbs",
+            "Disable Virtualization-Based Security",
+            "WARNING: removes the isolation that protects credentials and the kernel. Windows 11 turns VBS on by default, which costs some CPU performance in games. Has no effect when VBS is locked on in UEFI. Takes effect after a restart",
+            WINDOWS_11_BUILD,
+            true,
+            RiskLevel::High,
+        )
+    }
+
+    fn detect(&self) -> bool {
+        #[cfg(target_os = "windows")]
+        {
+            registry::read_dword(DEVICE_GUARD_KEY, "EnableVirtualizationBasedSecurity") == Some(0)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            false
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn snapshot(&self) -> Snapshot {
+        registry_snapshot([(DEVICE_GUARD_KEY, "EnableVirtualizationBasedSecurity")])
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        set_vbs(false)
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        set_vbs(true)
+    }
+}
+
+fn set_vbs(enabled: bool) -> Result<OptimizationResult> {
+    #[cfg(target_os = "windows")]
+    {
+        // Reverting hands the decision back to Windows instead of forcing VBS on
+        let outcome = if enabled {
+            registry::delete_value(DEVICE_GUARD_KEY, "EnableVirtualizationBasedSecurity")
+        } else {
+            registry::set_dword(DEVICE_GUARD_KEY, "EnableVirtualizationBasedSecurity", 0)
+        };
+        let result = match outcome {
+            Ok(()) => OptimizationResult {
+                needs_restart: true,
+                ..success(if enabled {
+                    "Virtualization-based security restored to the Windows default, restart to apply"
+                } else {
+                    "Virtualization-based security disabled, restart to apply"
+                })
+            },
+            Err(e) => failure(e.to_string()),
+        };
+        Ok(result)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = enabled;
+        Ok(failure("Virtualization-based security is Windows-only"))
+    }
+}
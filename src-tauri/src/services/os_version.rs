@@ -0,0 +1,181 @@
+use crate::models::system_stats::PlatformInfo;
+use sysinfo::System;
+
+#[cfg(target_os = "windows")]
+use crate::shared::registry;
+
+#[cfg(target_os = "windows")]
+const CURRENT_VERSION_KEY: &str = r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+/// Windows 11 still reports itself as 10.0, only the build tells them apart
+pub const WINDOWS_11_BUILD: u32 = 22000;
+
+// The OS cannot change while the app runs
+static PLATFORM: once_cell::sync::Lazy<PlatformInfo> = once_cell::sync::Lazy::new(detect);
+
+pub fn get_platform_info() -> PlatformInfo {
+    PLATFORM.clone()
+}
+
+/// Windows build number, None on other platforms
+pub fn windows_build() -> Option<u32> {
+    PLATFORM.build
+}
+
+/// Valve's SteamOS, as shipped on the Steam Deck
+pub fn is_steamos() -> bool {
+    PLATFORM.distro_id.as_deref() == Some("steamos")
+}
+
+/// Whether the system image is read-only, so packages cannot be installed
+/// the usual way
+pub fn is_immutable() -> bool {
+    PLATFORM.immutable
+}
+
+fn detect() -> PlatformInfo {
+    let arch = if cfg!(target_arch = "x86_64") {
+        "x64"
+    } else if cfg!(target_arch = "x86") {
+        "x86"
+    } else if cfg!(target_arch = "aarch64") {
+        "ARM64"
+    } else {
+        "Unknown"
+    };
+
+    let mut info = PlatformInfo {
+        os: "Unknown".to_string(),
+        version: "Unknown".to_string(),
+        arch: arch.to_string(),
+        build: None,
+        build_revision: None,
+        display_version: None,
+        edition: None,
+        distro_id: None,
+        distro_name: None,
+        immutable: false,
+        kernel_version: System::kernel_version(),
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        info.os = "Windows".to_string();
+        if let Some((major, minor, build)) = rtl_version() {
+            info.version = if build >= WINDOWS_11_BUILD {
+                "11".to_string()
+            } else if major == 10 {
+                "10".to_string()
+            } else {
+                format!("{}.{}", major, minor)
+            };
+            info.kernel_version = Some(format!("{}.{}.{}", major, minor, build));
+            info.build = Some(build);
+        }
+        info.build_revision = registry::read_dword(CURRENT_VERSION_KEY, "UBR");
+        info.display_version = registry::read_string(CURRENT_VERSION_KEY, "DisplayVersion");
+        info.edition = registry::read_string(CURRENT_VERSION_KEY, "EditionID");
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        info.os = "Linux".to_string();
+        if let Ok(content) = std::fs::read_to_string("/etc/os-release") {
+            let release = parse_os_release(&content);
+            let field = |name: &str| {
+                release
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.clone())
+            };
+            info.version = field("VERSION_ID").unwrap_or_else(|| "Unknown".to_string());
+            info.distro_id = field("ID");
+            info.distro_name = field("PRETTY_NAME").or_else(|| field("NAME"));
+        }
+        // rpm-ostree systems (Silverblue, Kinoite, Bazzite) flag themselves,
+        // SteamOS and others mount the system read-only
+        info.immutable = std::path::Path::new("/run/ostree-booted").exists()
+            || std::fs::read_to_string("/proc/mounts")
+                .is_ok_and(|mounts| read_only_system(&mounts));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        info.os = "macOS".to_string();
+        info.version = System::os_version().unwrap_or_else(|| "Unknown".to_string());
+    }
+
+    info
+}
+
+/// Major, minor and build straight from the kernel. GetVersionEx lies to
+/// processes without a compatibility manifest, RtlGetVersion does not.
+#[cfg(target_os = "windows")]
+fn rtl_version() -> Option<(u32, u32, u32)> {
+    use windows::Wdk::System::SystemServices::RtlGetVersion;
+    use windows::Win32::System::SystemInformation::OSVERSIONINFOW;
+
+    let mut version = OSVERSIONINFOW {
+        dwOSVersionInfoSize: std::mem::size_of::<OSVERSIONINFOW>() as u32,
+        ..Default::default()
+    };
+    let status = unsafe { RtlGetVersion(&mut version) };
+    if status.is_err() {
+        return None;
+    }
+    Some((
+        version.dwMajorVersion,
+        version.dwMinorVersion,
+        version.dwBuildNumber,
+    ))
+}
+
+/// `KEY=value` pairs of /etc/os-release, with quotes removed
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_os_release(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().trim_matches('"').trim_matches('\'');
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Whether `/` or `/usr` is mounted read-only, from /proc/mounts
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn read_only_system(mounts: &str) -> bool {
+    mounts.lines().any(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, mount_point, _, options, ..] => {
+                (*mount_point == "/" || *mount_point == "/usr")
+                    && options.split(',').any(|option| option == "ro")
+            }
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_os_release() {
+        let content = "NAME=\"SteamOS\"\n# comment\nID=steamos\nVERSION_ID=3.6\nPRETTY_NAME=\"SteamOS 3.6\"\n";
+        let release = parse_os_release(content);
+        assert_eq!(release.len(), 4);
+        assert!(release.contains(&("ID".to_string(), "steamos".to_string())));
+        assert!(release.contains(&("PRETTY_NAME".to_string(), "SteamOS 3.6".to_string())));
+    }
+
+    #[test]
+    fn test_read_only_system() {
+        let steamos = "/dev/nvme0n1p4 / btrfs ro,relatime,ssd 0 0\n/dev/nvme0n1p8 /home ext4 rw,relatime 0 0\n";
+        assert!(read_only_system(steamos));
+        let regular = "/dev/sda2 / ext4 rw,relatime 0 0\n/dev/sr0 /media/cdrom iso9660 ro 0 0\n";
+        assert!(!read_only_system(regular));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::models::system_stats::PlatformFeatures;
+use sysinfo::System;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+// EFI global variable GUID, owner of the SecureBoot variable
+#[cfg(target_os = "linux")]
+const SECURE_BOOT_VARIABLE: &str =
+    "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// CPUID feature bits of interest
+#[derive(Debug, Default, Clone, Copy)]
+struct CpuidFeatures {
+    vmx: bool,
+    svm: bool,
+    hypervisor: bool,
+}
+
+/// Reports virtualization, SMT and Secure Boot state
+pub fn get_platform_features(system: &System) -> PlatformFeatures {
+    let cpuid = read_cpuid();
+    let logical_cores = system.cpus().len();
+    let physical_cores = System::physical_core_count().unwrap_or(logical_cores);
+    let virtualization_supported = cpuid.vmx || cpuid.svm;
+
+    PlatformFeatures {
+        virtualization_supported,
+        virtualization_enabled: virtualization_enabled(cpuid),
+        hypervisor_present: cpuid.hypervisor,
+        smt_enabled: smt_enabled(logical_cores, physical_cores),
+        physical_cores,
+        logical_cores,
+        secure_boot_enabled: secure_boot_enabled(),
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn read_cpuid() -> CpuidFeatures {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::__cpuid;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::__cpuid;
+
+    let basic = __cpuid(1);
+    let extended_max = __cpuid(0x8000_0000).eax;
+    let svm = extended_max >= 0x8000_0001 && __cpuid(0x8000_0001).ecx & (1 << 2) != 0;
+
+    CpuidFeatures {
+        vmx: basic.ecx & (1 << 5) != 0,
+        svm,
+        hypervisor: basic.ecx & (1 << 31) != 0,
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn read_cpuid() -> CpuidFeatures {
+    CpuidFeatures::default()
+}
+
+/// Whether the firmware left virtualization switched on. A running hypervisor
+/// (Hyper-V, VBS) hides VT-x from CPUID but implies it is enabled.
+fn virtualization_enabled(cpuid: CpuidFeatures) -> Option<bool> {
+    if cpuid.hypervisor {
+        return Some(true);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::System::Threading::{
+            IsProcessorFeaturePresent, PF_VIRT_FIRMWARE_ENABLED,
+        };
+        Some(unsafe { IsProcessorFeaturePresent(PF_VIRT_FIRMWARE_ENABLED) }.as_bool())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if !(cpuid.vmx || cpuid.svm) {
+            return Some(false);
+        }
+        // KVM only creates its device when the firmware allows VMX/SVM
+        std::path::Path::new("/dev/kvm").exists().then_some(true)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+fn smt_enabled(logical_cores: usize, physical_cores: usize) -> bool {
+    #[cfg(target_os = "linux")]
+    if let Ok(active) = std::fs::read_to_string("/sys/devices/system/cpu/smt/active") {
+        return active.trim() == "1";
+    }
+
+    logical_cores > physical_cores
+}
+
+#[cfg(target_os = "windows")]
+fn secure_boot_enabled() -> Option<bool> {
+    use std::process::Command;
+
+    // Mirror of the SecureBoot firmware variable, which needs the system
+    // environment privilege to read directly
+    let output = Command::new("reg")
+        .args([
+            "query",
+            "HKLM\\SYSTEM\\CurrentControlSet\\Control\\SecureBoot\\State",
+            "/v",
+            "UEFISecureBootEnabled",
+        ])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        // The key is missing on legacy BIOS installs
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let (_, value) = line.split_once("REG_DWORD")?;
+            u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+        })
+        .map(|value| value != 0)
+}
+
+#[cfg(target_os = "linux")]
+fn secure_boot_enabled() -> Option<bool> {
+    let variable = std::fs::read(SECURE_BOOT_VARIABLE).ok()?;
+    parse_efi_bool(&variable)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn secure_boot_enabled() -> Option<bool> {
+    None
+}
+
+/// efivarfs files start with the 4-byte attribute mask, then the value
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_efi_bool(variable: &[u8]) -> Option<bool> {
+    variable.get(4).map(|value| *value == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_efi_bool() {
+        assert_eq!(parse_efi_bool(&[0x06, 0, 0, 0, 1]), Some(true));
+        assert_eq!(parse_efi_bool(&[0x06, 0, 0, 0, 0]), Some(false));
+        assert_eq!(parse_efi_bool(&[0x06, 0, 0, 0]), None);
+    }
+}
@@ -0,0 +1,578 @@
+// Host for third-party plugins: dynamic libraries installed in Aura's
+// plugins folder that add stat providers (sensors, RGB controllers, ...) and
+// optimizations without changing the crate.
+//
+// The interface is a C ABI exchanging JSON, so plugins can be written in any
+// language and do not depend on the Rust layout of Aura's types. A plugin
+// exports:
+//
+//   uint32_t aura_plugin_abi_version(void);            // PLUGIN_ABI_VERSION
+//   const char *aura_plugin_manifest(void);            // PluginManifest JSON
+//   char *aura_plugin_call(const char *request);       // PluginRequest JSON
+//   void aura_plugin_free(char *reply);                // frees call replies
+//
+// Replies are {"ok": <value>} or {"error": "<message>"}: SystemStats for
+// collect_stats, a bool for detect and an OptimizationResult for apply and
+// revert.
+//
+// Plugins run inside Aura with its administrator rights, so a library is
+// loaded only when all of these hold: it sits in a folder only administrators
+// can write (under Program Files on Windows, /usr/lib/aura on Linux), the user
+// enabled it by name, and it is trusted by the system: a valid Authenticode
+// signature on Windows, owned by root and writable by nobody else elsewhere.
+use crate::models::optimization::{OptimizationItem, OptimizationResult};
+use crate::models::system_stats::SystemStats;
+use crate::services::optimization_engine::Optimization;
+use crate::shared::{i18n, paths};
+use anyhow::{anyhow, Result};
+use libloading::Library;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::ffi::{c_char, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Version of the plugin interface, bumped on every incompatible change
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+// Plugins the user enabled, by file name
+const CONFIG_FILE: &str = "plugins.json";
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type ManifestFn = unsafe extern "C" fn() -> *const c_char;
+type CallFn = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+/// A source of stats shown next to the built-in monitors
+pub trait StatProvider: Send + Sync {
+    fn id(&self) -> String;
+    fn title(&self) -> String;
+    fn collect(&self) -> Result<SystemStats>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatProviderInfo {
+    pub id: String,
+    pub title: String,
+}
+
+/// What a plugin declares about itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub stat_providers: Vec<StatProviderInfo>,
+    #[serde(default)]
+    pub optimizations: Vec<OptimizationItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    #[serde(flatten)]
+    pub manifest: PluginManifest,
+    pub path: String,
+}
+
+/// A library in the plugins folder that could not be loaded
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginLoadError {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginList {
+    pub directory: String,
+    pub plugins: Vec<PluginInfo>,
+    pub disabled: Vec<String>, // libraries in the folder the user didn't enable
+    pub errors: Vec<PluginLoadError>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PluginConfig {
+    enabled: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum PluginRequest<'a> {
+    CollectStats { provider: &'a str },
+    Detect { optimization: &'a str },
+    Apply { optimization: &'a str },
+    Revert { optimization: &'a str },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PluginResponse<T> {
+    Ok(T),
+    Error(String),
+}
+
+struct Plugin {
+    manifest: PluginManifest,
+    path: PathBuf,
+    call: CallFn,
+    free: FreeFn,
+    // Keeps the function pointers above valid, so it is dropped last
+    _library: Library,
+}
+
+impl Plugin {
+    fn load(path: &Path) -> Result<Self> {
+        check_trusted(path)?;
+        // Safety: loading runs the library's initializers; only enabled files
+        // of the protected plugins folder that passed the checks above get here
+        let library = unsafe { Library::new(path)? };
+        unsafe {
+            let abi_version = *library.get::<AbiVersionFn>(b"aura_plugin_abi_version\0")?;
+            let version = abi_version();
+            if version != PLUGIN_ABI_VERSION {
+                return Err(anyhow!(
+                    "Plugin interface version {} is not supported (expected {})",
+                    version,
+                    PLUGIN_ABI_VERSION
+                ));
+            }
+
+            let manifest_fn = *library.get::<ManifestFn>(b"aura_plugin_manifest\0")?;
+            let call = *library.get::<CallFn>(b"aura_plugin_call\0")?;
+            let free = *library.get::<FreeFn>(b"aura_plugin_free\0")?;
+
+            let manifest = manifest_fn();
+            if manifest.is_null() {
+                return Err(anyhow!("Plugin returned no manifest"));
+            }
+            let manifest = parse_manifest(&CStr::from_ptr(manifest).to_string_lossy())?;
+
+            Ok(Self {
+                manifest,
+                path: path.to_path_buf(),
+                call,
+                free,
+                _library: library,
+            })
+        }
+    }
+
+    fn request<T: DeserializeOwned>(&self, request: &PluginRequest) -> Result<T> {
+        let request = CString::new(serde_json::to_string(request)?)?;
+        // Safety: the library is alive as long as `self`, and the reply is
+        // handed back to the plugin's own allocator
+        let reply = unsafe {
+            let reply = (self.call)(request.as_ptr());
+            if reply.is_null() {
+                return Err(anyhow!("Plugin {} returned no reply", self.manifest.id));
+            }
+            let text = CStr::from_ptr(reply).to_string_lossy().into_owned();
+            (self.free)(reply);
+            text
+        };
+        parse_response(&reply).map_err(|e| anyhow!("Plugin {}: {}", self.manifest.id, e))
+    }
+}
+
+#[derive(Default)]
+struct PluginHost {
+    plugins: Vec<Arc<Plugin>>,
+    disabled: Vec<String>,
+    errors: Vec<PluginLoadError>,
+}
+
+// Plugins are loaded once, the first time anything asks for them
+static HOST: once_cell::sync::Lazy<Mutex<PluginHost>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_plugins(&plugins_dir(), &load_config().enabled)));
+
+/// Folder plugins are installed in. Only administrators can write it, which
+/// keeps an unelevated program from getting its code run by Aura.
+pub fn plugins_dir() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    let dir = std::env::var_os("ProgramFiles")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\Program Files"))
+        .join("Aura")
+        .join("plugins");
+
+    #[cfg(target_os = "macos")]
+    let dir = PathBuf::from("/Library/Application Support/Aura/plugins");
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let dir = PathBuf::from("/usr/lib/aura/plugins");
+
+    dir
+}
+
+/// Enables or disables a plugin by its file name. Takes effect the next time
+/// Aura starts, as loaded libraries stay loaded.
+pub fn set_plugin_enabled(file_name: &str, enabled: bool) -> Result<()> {
+    if file_name.contains(['/', '\\']) || !plugins_dir().join(file_name).is_file() {
+        return Err(anyhow!(i18n::text_with(
+            "error.plugin_not_installed",
+            "No plugin named {name} is installed",
+            &[("name", file_name)]
+        )));
+    }
+    let mut config = load_config();
+    config.enabled.retain(|name| name != file_name);
+    if enabled {
+        config.enabled.push(file_name.to_string());
+    }
+    paths::save_json(CONFIG_FILE, &config)?;
+    Ok(())
+}
+
+fn load_config() -> PluginConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+pub fn get_plugins() -> PluginList {
+    let host = HOST.lock().unwrap_or_else(|e| e.into_inner());
+    PluginList {
+        directory: plugins_dir().to_string_lossy().into_owned(),
+        plugins: host
+            .plugins
+            .iter()
+            .map(|plugin| PluginInfo {
+                manifest: plugin.manifest.clone(),
+                path: plugin.path.to_string_lossy().into_owned(),
+            })
+            .collect(),
+        disabled: host.disabled.clone(),
+        errors: host.errors.clone(),
+    }
+}
+
+/// Stat providers of every loaded plugin
+pub fn stat_providers() -> Vec<Box<dyn StatProvider>> {
+    plugins()
+        .into_iter()
+        .flat_map(|plugin| {
+            plugin
+                .manifest
+                .stat_providers
+                .clone()
+                .into_iter()
+                .map(move |info| {
+                    Box::new(PluginStatProvider {
+                        plugin: plugin.clone(),
+                        info,
+                    }) as Box<dyn StatProvider>
+                })
+        })
+        .collect()
+}
+
+/// Collects the stats of one provider, titled as in its manifest when the
+/// plugin leaves the title empty
+pub fn collect_stats(provider_id: &str) -> Result<SystemStats> {
+    let provider = stat_providers()
+        .into_iter()
+        .find(|provider| provider.id() == provider_id)
+        .ok_or_else(|| anyhow!("Unknown stat provider: {}", provider_id))?;
+    let mut stats = provider.collect()?;
+    if stats.title.is_empty() {
+        stats.title = provider.title();
+    }
+    Ok(stats)
+}
+
+/// Optimizations of every loaded plugin, registered by the optimization engine
+pub fn optimizations() -> Vec<Box<dyn Optimization>> {
+    plugins()
+        .into_iter()
+        .flat_map(|plugin| {
+            plugin
+                .manifest
+                .optimizations
+                .clone()
+                .into_iter()
+                .map(move |item| {
+                    Box::new(PluginOptimization {
+                        plugin: plugin.clone(),
+                        item,
+                    }) as Box<dyn Optimization>
+                })
+        })
+        .collect()
+}
+
+fn plugins() -> Vec<Arc<Plugin>> {
+    HOST.lock()
+        .map(|host| host.plugins.clone())
+        .unwrap_or_default()
+}
+
+fn load_plugins(dir: &Path, enabled: &[String]) -> PluginHost {
+    let mut host = PluginHost::default();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return host;
+    };
+
+    let mut libraries: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    libraries.sort();
+
+    for path in libraries {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if !enabled.contains(&file_name) {
+            host.disabled.push(file_name);
+            continue;
+        }
+        let result = Plugin::load(&path).and_then(|plugin| {
+            if host
+                .plugins
+                .iter()
+                .any(|p| p.manifest.id == plugin.manifest.id)
+            {
+                return Err(anyhow!(
+                    "A plugin with id {} is already loaded",
+                    plugin.manifest.id
+                ));
+            }
+            Ok(plugin)
+        });
+        match result {
+            Ok(plugin) => host.plugins.push(Arc::new(plugin)),
+            Err(e) => host.errors.push(PluginLoadError {
+                path: path.to_string_lossy().into_owned(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+    host
+}
+
+/// Refuses a library anyone but an administrator could have written
+#[cfg(target_os = "windows")]
+fn check_trusted(path: &Path) -> Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{HANDLE, HWND};
+    use windows::Win32::Security::WinTrust::{
+        WinVerifyTrust, WINTRUST_ACTION_GENERIC_VERIFY_V2, WINTRUST_DATA, WINTRUST_DATA_0,
+        WINTRUST_FILE_INFO, WTD_CHOICE_FILE, WTD_REVOKE_WHOLECHAIN, WTD_STATEACTION_CLOSE,
+        WTD_STATEACTION_VERIFY, WTD_UI_NONE,
+    };
+
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut file = WINTRUST_FILE_INFO {
+        cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+        pcwszFilePath: PCWSTR(wide.as_ptr()),
+        ..Default::default()
+    };
+    let mut data = WINTRUST_DATA {
+        cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+        dwUIChoice: WTD_UI_NONE,
+        fdwRevocationChecks: WTD_REVOKE_WHOLECHAIN,
+        dwUnionChoice: WTD_CHOICE_FILE,
+        Anonymous: WINTRUST_DATA_0 { pFile: &mut file },
+        dwStateAction: WTD_STATEACTION_VERIFY,
+        hWVTStateData: HANDLE::default(),
+        ..Default::default()
+    };
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+    // Safety: `data` points to `file` and `wide`, which outlive both calls;
+    // the second call frees the state the first one allocated
+    let status = unsafe {
+        let status = WinVerifyTrust(
+            HWND(-1isize as *mut std::ffi::c_void), // INVALID_HANDLE_VALUE: no UI
+            &mut action,
+            &mut data as *mut WINTRUST_DATA as *mut std::ffi::c_void,
+        );
+        data.dwStateAction = WTD_STATEACTION_CLOSE;
+        WinVerifyTrust(
+            HWND(-1isize as *mut std::ffi::c_void),
+            &mut action,
+            &mut data as *mut WINTRUST_DATA as *mut std::ffi::c_void,
+        );
+        status
+    };
+    if status != 0 {
+        return Err(anyhow!(i18n::text_with(
+            "error.plugin_unsigned",
+            "The plugin has no valid Authenticode signature ({status})",
+            &[("status", &format!("0x{:08X}", status as u32))]
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn check_trusted(path: &Path) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    // The folder too, or the file could be swapped for another
+    for checked in [Some(path), path.parent()].into_iter().flatten() {
+        let metadata = std::fs::metadata(checked)?;
+        if metadata.uid() != 0 || metadata.mode() & 0o022 != 0 {
+            return Err(anyhow!(i18n::text_with(
+                "error.plugin_untrusted_owner",
+                "{path} must be owned by root and writable by nobody else",
+                &[("path", &checked.display().to_string())]
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", unix)))]
+fn check_trusted(_path: &Path) -> Result<()> {
+    Err(anyhow!(i18n::text(
+        "error.plugins_unsupported",
+        "Plugins are not supported on this platform"
+    )))
+}
+
+fn parse_manifest(content: &str) -> Result<PluginManifest> {
+    let manifest: PluginManifest =
+        serde_json::from_str(content).map_err(|e| anyhow!("Invalid manifest: {}", e))?;
+    if manifest.id.trim().is_empty() {
+        return Err(anyhow!("Invalid manifest: empty id"));
+    }
+    Ok(manifest)
+}
+
+fn parse_response<T: DeserializeOwned>(content: &str) -> Result<T> {
+    match serde_json::from_str(content).map_err(|e| anyhow!("Invalid reply: {}", e))? {
+        PluginResponse::Ok(value) => Ok(value),
+        PluginResponse::Error(message) => Err(anyhow!(message)),
+    }
+}
+
+struct PluginStatProvider {
+    plugin: Arc<Plugin>,
+    info: StatProviderInfo,
+}
+
+impl StatProvider for PluginStatProvider {
+    fn id(&self) -> String {
+        self.info.id.clone()
+    }
+
+    fn title(&self) -> String {
+        self.info.title.clone()
+    }
+
+    fn collect(&self) -> Result<SystemStats> {
+        self.plugin.request(&PluginRequest::CollectStats {
+            provider: &self.info.id,
+        })
+    }
+}
+
+struct PluginOptimization {
+    plugin: Arc<Plugin>,
+    item: OptimizationItem,
+}
+
+impl Optimization for PluginOptimization {
+    fn item(&self) -> OptimizationItem {
+        OptimizationItem {
+            is_applied: false,
+            ..self.item.clone()
+        }
+    }
+
+    fn detect(&self) -> bool {
+        self.plugin
+            .request(&PluginRequest::Detect {
+                optimization: &self.item.id,
+            })
+            .unwrap_or(false)
+    }
+
+    fn apply(&self) -> Result<OptimizationResult> {
+        self.plugin.request(&PluginRequest::Apply {
+            optimization: &self.item.id,
+        })
+    }
+
+    fn revert(&self) -> Result<OptimizationResult> {
+        self.plugin.request(&PluginRequest::Revert {
+            optimization: &self.item.id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = parse_manifest(
+            r#"{"id": "rgb", "name": "RGB", "version": "1.0.0",
+                "stat_providers": [{"id": "rgb_fans", "title": "Fans"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.stat_providers[0].id, "rgb_fans");
+        assert!(manifest.optimizations.is_empty());
+
+        assert!(parse_manifest(r#"{"id": " ", "name": "x", "version": "1"}"#).is_err());
+        assert!(parse_manifest("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let stats: SystemStats =
+            parse_response(r#"{"ok": {"title": "Fans", "percentage": 40.0}}"#).unwrap();
+        assert_eq!(stats.title, "Fans");
+        assert!(!stats.is_stale);
+
+        let applied: bool = parse_response(r#"{"ok": true}"#).unwrap();
+        assert!(applied);
+
+        let error = parse_response::<bool>(r#"{"error": "device busy"}"#).unwrap_err();
+        assert_eq!(error.to_string(), "device busy");
+    }
+
+    #[test]
+    fn test_requests_are_tagged_by_method() {
+        let request = serde_json::to_string(&PluginRequest::Apply { optimization: "x" }).unwrap();
+        assert_eq!(request, r#"{"method":"apply","optimization":"x"}"#);
+    }
+
+    #[test]
+    fn test_missing_directory_loads_nothing() {
+        let host = load_plugins(Path::new("/nonexistent/aura/plugins"), &[]);
+        assert!(host.plugins.is_empty());
+        assert!(host.errors.is_empty());
+    }
+
+    #[test]
+    fn test_libraries_not_enabled_are_not_loaded() {
+        let dir = std::env::temp_dir().join(format!("aura-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_name = format!("planted.{}", std::env::consts::DLL_EXTENSION);
+        std::fs::write(dir.join(&file_name), b"not a library").unwrap();
+
+        let host = load_plugins(&dir, &[]);
+        assert!(host.plugins.is_empty());
+        assert!(host.errors.is_empty());
+        assert_eq!(host.disabled, vec![file_name.clone()]);
+
+        // Enabled, but neither trusted nor a library: reported, not loaded
+        let host = load_plugins(&dir, &[file_name]);
+        assert!(host.plugins.is_empty());
+        assert_eq!(host.errors.len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
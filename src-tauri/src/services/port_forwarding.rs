@@ -0,0 +1,874 @@
+// Forwards the ports a game profile lists on the router while the game runs.
+// UPnP IGD is tried first and NAT-PMP second; both are small enough to speak
+// over std sockets, so no HTTP or XML library is needed. Mappings are leased
+// for an hour and renewed while the game runs, which lets the router drop
+// them by itself when Aura crashes. Opt-in: nothing is sent to the router
+// until enabled.
+use crate::models::game_profile::{
+    ActivePortMapping, NatMethod, PortForwardingConfig, PortForwardingStatus, PortMapping,
+    PortProtocol,
+};
+use crate::shared::{events, paths};
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+const CONFIG_FILE: &str = "port_forwarding.json";
+const LEASE_SECS: u32 = 60 * 60;
+const RENEW_INTERVAL: Duration = Duration::from_secs(20 * 60);
+const SSDP_ADDR: &str = "239.255.255.250:1900";
+const SSDP_TIMEOUT: Duration = Duration::from_secs(2);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+// Device descriptions and SOAP replies are a few KB, anything past this is
+// not a router answering
+const MAX_RESPONSE_BYTES: u64 = 1024 * 1024;
+const NAT_PMP_PORT: u16 = 5351;
+// RFC 6886 starts at 250 ms and doubles on every retry
+const NAT_PMP_TRIES: u32 = 4;
+const DESCRIPTION: &str = "Aura";
+
+#[derive(Error, Debug)]
+pub enum PortForwardingError {
+    #[error("No UPnP or NAT-PMP gateway found")]
+    NoGateway,
+
+    #[error("The router refused the mapping: {0}")]
+    Refused(String),
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    #[error("Failed to access port forwarding settings: {0}")]
+    StorageError(String),
+}
+
+type Result<T> = std::result::Result<T, PortForwardingError>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct HttpUrl {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+#[derive(Debug, Clone)]
+enum Gateway {
+    Upnp {
+        control_url: HttpUrl,
+        service_type: String,
+        local_ip: Ipv4Addr,
+    },
+    NatPmp {
+        address: Ipv4Addr,
+        local_ip: Ipv4Addr,
+    },
+}
+
+#[derive(Default)]
+struct State {
+    gateway: Option<Gateway>,
+    external_ip: Option<String>,
+    mappings: Vec<ActivePortMapping>,
+    last_error: Option<String>,
+}
+
+static CONFIG: once_cell::sync::Lazy<Mutex<PortForwardingConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+static STATE: once_cell::sync::Lazy<Mutex<State>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(State::default()));
+
+// Router calls take seconds, they run one at a time off the STATE lock
+static ROUTER: Mutex<()> = Mutex::new(());
+
+static RENEW_RUNNING: AtomicBool = AtomicBool::new(false);
+
+pub fn get_config() -> PortForwardingConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+/// Disabling removes every mapping Aura holds
+pub fn set_config(config: PortForwardingConfig) -> Result<()> {
+    paths::save_json(CONFIG_FILE, &config)
+        .map_err(|e| PortForwardingError::StorageError(e.to_string()))?;
+    let enabled = config.enabled;
+    if let Ok(mut current) = CONFIG.lock() {
+        *current = config;
+    }
+    if !enabled {
+        std::thread::spawn(close_all);
+    }
+    Ok(())
+}
+
+pub fn get_status() -> PortForwardingStatus {
+    let enabled = get_config().enabled;
+    let Ok(state) = STATE.lock() else {
+        return PortForwardingStatus {
+            enabled,
+            method: None,
+            gateway: None,
+            local_ip: None,
+            external_ip: None,
+            mappings: Vec::new(),
+            last_error: None,
+        };
+    };
+    let (method, gateway, local_ip) = match &state.gateway {
+        Some(Gateway::Upnp {
+            control_url,
+            local_ip,
+            ..
+        }) => (
+            Some(NatMethod::Upnp),
+            Some(control_url.host.clone()),
+            Some(local_ip.to_string()),
+        ),
+        Some(Gateway::NatPmp { address, local_ip }) => (
+            Some(NatMethod::NatPmp),
+            Some(address.to_string()),
+            Some(local_ip.to_string()),
+        ),
+        None => (None, None, None),
+    };
+    PortForwardingStatus {
+        enabled,
+        method,
+        gateway,
+        local_ip,
+        external_ip: state.external_ip.clone(),
+        mappings: state.mappings.clone(),
+        last_error: state.last_error.clone(),
+    }
+}
+
+/// Requests the game's mappings in the background, failures end up in the
+/// status
+pub fn open_for_game(game_pid: u32, game: &str, mappings: &[PortMapping]) {
+    if !get_config().enabled || mappings.is_empty() {
+        return;
+    }
+    let game = game.to_string();
+    let mappings = mappings.to_vec();
+    std::thread::spawn(move || {
+        let _router = ROUTER.lock();
+        let result = open_mappings(game_pid, &game, &mappings);
+        set_last_error(result.err());
+    });
+    ensure_renew_thread_running();
+}
+
+/// Removes the game's mappings in the background
+pub fn close_for_game(game_pid: u32) {
+    std::thread::spawn(move || {
+        let _router = ROUTER.lock();
+        let closing = take_mappings(|mapping| mapping.game_pid == game_pid);
+        set_last_error(delete_mappings(&closing).err());
+    });
+}
+
+/// Removes every mapping right away. Called on exit.
+pub fn close_all() {
+    let _router = ROUTER.lock();
+    let closing = take_mappings(|_| true);
+    let _ = delete_mappings(&closing);
+}
+
+fn open_mappings(game_pid: u32, game: &str, mappings: &[PortMapping]) -> Result<()> {
+    let gateway = gateway()?;
+    let mut first_error = None;
+    for mapping in mappings {
+        match add_mapping(&gateway, mapping.protocol, mapping.port, mapping.port) {
+            Ok(external_port) => {
+                let active = ActivePortMapping {
+                    game_pid,
+                    game: game.to_string(),
+                    protocol: mapping.protocol,
+                    internal_port: mapping.port,
+                    external_port,
+                    method: method_of(&gateway),
+                    renewed_at: now_secs(),
+                };
+                if let Ok(mut state) = STATE.lock() {
+                    state.mappings.push(active);
+                }
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+fn delete_mappings(mappings: &[ActivePortMapping]) -> Result<()> {
+    if mappings.is_empty() {
+        return Ok(());
+    }
+    let gateway = gateway()?;
+    let mut first_error = None;
+    for mapping in mappings {
+        if let Err(e) = delete_mapping(&gateway, mapping) {
+            first_error.get_or_insert(e);
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+fn take_mappings(matches: impl Fn(&ActivePortMapping) -> bool) -> Vec<ActivePortMapping> {
+    let Ok(mut state) = STATE.lock() else {
+        return Vec::new();
+    };
+    let (taken, kept) = std::mem::take(&mut state.mappings)
+        .into_iter()
+        .partition(|mapping| matches(mapping));
+    state.mappings = kept;
+    taken
+}
+
+fn set_last_error(error: Option<PortForwardingError>) {
+    let Ok(mut state) = STATE.lock() else {
+        return;
+    };
+    if let Some(error) = &error {
+        // Rediscovered on the next request, the router may have changed
+        if matches!(error, PortForwardingError::Network(_)) {
+            state.gateway = None;
+        }
+        eprintln!("Port forwarding failed: {}", error);
+    }
+    state.last_error = error.map(|e| e.to_string());
+}
+
+fn ensure_renew_thread_running() {
+    if RENEW_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(|| loop {
+        std::thread::sleep(RENEW_INTERVAL);
+        let _router = ROUTER.lock();
+        let mappings = STATE
+            .lock()
+            .map(|state| state.mappings.clone())
+            .unwrap_or_default();
+        if mappings.is_empty() {
+            continue;
+        }
+        let result = gateway().and_then(|gateway| {
+            for mapping in &mappings {
+                add_mapping(
+                    &gateway,
+                    mapping.protocol,
+                    mapping.internal_port,
+                    mapping.external_port,
+                )?;
+            }
+            Ok(())
+        });
+        if result.is_ok() {
+            if let Ok(mut state) = STATE.lock() {
+                let now = now_secs();
+                for mapping in state.mappings.iter_mut() {
+                    mapping.renewed_at = now;
+                }
+            }
+        }
+        set_last_error(result.err());
+    });
+}
+
+/// The gateway found earlier, or a new UPnP and then NAT-PMP discovery
+fn gateway() -> Result<Gateway> {
+    if let Some(gateway) = STATE.lock().ok().and_then(|state| state.gateway.clone()) {
+        return Ok(gateway);
+    }
+    let gateway = discover_upnp().or_else(|_| discover_nat_pmp())?;
+    let external_ip = external_ip(&gateway).ok();
+    if let Ok(mut state) = STATE.lock() {
+        state.gateway = Some(gateway.clone());
+        state.external_ip = external_ip;
+    }
+    Ok(gateway)
+}
+
+fn method_of(gateway: &Gateway) -> NatMethod {
+    match gateway {
+        Gateway::Upnp { .. } => NatMethod::Upnp,
+        Gateway::NatPmp { .. } => NatMethod::NatPmp,
+    }
+}
+
+/// Maps the port and returns the external one the router assigned
+fn add_mapping(
+    gateway: &Gateway,
+    protocol: PortProtocol,
+    internal_port: u16,
+    external_port: u16,
+) -> Result<u16> {
+    match gateway {
+        Gateway::Upnp {
+            control_url,
+            service_type,
+            local_ip,
+        } => {
+            let args = |lease: u32| {
+                vec![
+                    ("NewRemoteHost", String::new()),
+                    ("NewExternalPort", external_port.to_string()),
+                    ("NewProtocol", protocol_name(protocol).to_string()),
+                    ("NewInternalPort", internal_port.to_string()),
+                    ("NewInternalClient", local_ip.to_string()),
+                    ("NewEnabled", "1".to_string()),
+                    ("NewPortMappingDescription", DESCRIPTION.to_string()),
+                    ("NewLeaseDuration", lease.to_string()),
+                ]
+            };
+            match soap_call(
+                control_url,
+                service_type,
+                "AddPortMapping",
+                &args(LEASE_SECS),
+            ) {
+                // OnlyPermanentLeasesSupported, such routers keep the mapping
+                // until it is deleted
+                Err(PortForwardingError::Refused(e)) if e.starts_with("725") => {
+                    soap_call(control_url, service_type, "AddPortMapping", &args(0))?;
+                }
+                result => {
+                    result?;
+                }
+            }
+            Ok(external_port)
+        }
+        Gateway::NatPmp { address, .. } => {
+            let request = nat_pmp_map_request(protocol, internal_port, external_port, LEASE_SECS);
+            let response = nat_pmp_request(*address, &request, 16)?;
+            parse_nat_pmp_mapping(&response).ok_or_else(|| {
+                PortForwardingError::Network("Malformed NAT-PMP response".to_string())
+            })
+        }
+    }
+}
+
+fn delete_mapping(gateway: &Gateway, mapping: &ActivePortMapping) -> Result<()> {
+    match gateway {
+        Gateway::Upnp {
+            control_url,
+            service_type,
+            ..
+        } => {
+            let args = [
+                ("NewRemoteHost", String::new()),
+                ("NewExternalPort", mapping.external_port.to_string()),
+                ("NewProtocol", protocol_name(mapping.protocol).to_string()),
+            ];
+            soap_call(control_url, service_type, "DeletePortMapping", &args).map(|_| ())
+        }
+        Gateway::NatPmp { address, .. } => {
+            // A zero lifetime and external port deletes the mapping
+            let request = nat_pmp_map_request(mapping.protocol, mapping.internal_port, 0, 0);
+            nat_pmp_request(*address, &request, 16).map(|_| ())
+        }
+    }
+}
+
+fn external_ip(gateway: &Gateway) -> Result<String> {
+    match gateway {
+        Gateway::Upnp {
+            control_url,
+            service_type,
+            ..
+        } => {
+            let response = soap_call(control_url, service_type, "GetExternalIPAddress", &[])?;
+            tag_text(&response, "NewExternalIPAddress")
+                .map(str::to_string)
+                .ok_or_else(|| PortForwardingError::Network("No external address".to_string()))
+        }
+        Gateway::NatPmp { address, .. } => {
+            let response = nat_pmp_request(*address, &[0, 0], 12)?;
+            Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11]).to_string())
+        }
+    }
+}
+
+fn protocol_name(protocol: PortProtocol) -> &'static str {
+    match protocol {
+        PortProtocol::Tcp => "TCP",
+        PortProtocol::Udp => "UDP",
+    }
+}
+
+fn network(e: std::io::Error) -> PortForwardingError {
+    PortForwardingError::Network(e.to_string())
+}
+
+// --- UPnP ---
+
+fn discover_upnp() -> Result<Gateway> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(network)?;
+    socket
+        .set_read_timeout(Some(SSDP_TIMEOUT))
+        .map_err(network)?;
+    let search = "M-SEARCH * HTTP/1.1\r\n\
+                  HOST: 239.255.255.250:1900\r\n\
+                  MAN: \"ssdp:discover\"\r\n\
+                  MX: 2\r\n\
+                  ST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\r\n";
+    socket
+        .send_to(search.as_bytes(), SSDP_ADDR)
+        .map_err(network)?;
+
+    let deadline = Instant::now() + SSDP_TIMEOUT;
+    let mut buffer = [0u8; 2048];
+    while Instant::now() < deadline {
+        let Ok((len, _)) = socket.recv_from(&mut buffer) else {
+            break;
+        };
+        let response = String::from_utf8_lossy(&buffer[..len]);
+        let Some(location) = header_value(&response, "location").and_then(parse_url) else {
+            continue;
+        };
+        let Ok(description) = http_request(&location, None) else {
+            continue;
+        };
+        let Some((service_type, control_path)) = find_wan_service(&description) else {
+            continue;
+        };
+        let local_ip = local_ip_towards(&location.host, location.port)?;
+        return Ok(Gateway::Upnp {
+            control_url: resolve_url(&location, &control_path),
+            service_type,
+            local_ip,
+        });
+    }
+    Err(PortForwardingError::NoGateway)
+}
+
+fn soap_call(
+    control_url: &HttpUrl,
+    service_type: &str,
+    action: &str,
+    args: &[(&str, String)],
+) -> Result<String> {
+    let body = soap_envelope(service_type, action, args);
+    let soap_action = format!("\"{}#{}\"", service_type, action);
+    let response = http_request(control_url, Some((&soap_action, &body)))?;
+    match soap_error(&response) {
+        Some(error) => Err(PortForwardingError::Refused(error)),
+        None => Ok(response),
+    }
+}
+
+/// GET without a body, a SOAP POST with one. Returns the response body.
+fn http_request(url: &HttpUrl, soap: Option<(&str, &str)>) -> Result<String> {
+    let address = (url.host.as_str(), url.port)
+        .to_socket_addrs()
+        .map_err(network)?
+        .next()
+        .ok_or(PortForwardingError::NoGateway)?;
+    let mut stream = TcpStream::connect_timeout(&address, HTTP_TIMEOUT).map_err(network)?;
+    stream
+        .set_read_timeout(Some(HTTP_TIMEOUT))
+        .map_err(network)?;
+    stream
+        .set_write_timeout(Some(HTTP_TIMEOUT))
+        .map_err(network)?;
+
+    let request = match soap {
+        Some((action, body)) => format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: text/xml; charset=\"utf-8\"\r\n\
+             SOAPAction: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            url.path,
+            url.host,
+            url.port,
+            action,
+            body.len(),
+            body
+        ),
+        None => format!(
+            "GET {} HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\n\r\n",
+            url.path, url.host, url.port
+        ),
+    };
+    stream.write_all(request.as_bytes()).map_err(network)?;
+    let mut response = Vec::new();
+    stream
+        .take(MAX_RESPONSE_BYTES)
+        .read_to_end(&mut response)
+        .map_err(network)?;
+
+    // Chunk sizes count bytes, so the body is only decoded as text afterwards
+    let head_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| PortForwardingError::Network("Malformed HTTP response".to_string()))?;
+    let head = String::from_utf8_lossy(&response[..head_end]);
+    let body = &response[head_end + 4..];
+    let body = match header_value(&head, "transfer-encoding") {
+        Some(encoding) if encoding.eq_ignore_ascii_case("chunked") => {
+            String::from_utf8_lossy(&decode_chunked(body)).into_owned()
+        }
+        _ => String::from_utf8_lossy(body).into_owned(),
+    };
+    // SOAP faults come with a 500 and are read from the body
+    let status = head.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" && soap_error(&body).is_none() {
+        return Err(PortForwardingError::Network(format!(
+            "HTTP {} from {}",
+            status, url.host
+        )));
+    }
+    Ok(body)
+}
+
+/// The local address the OS routes towards the gateway from
+fn local_ip_towards(host: &str, port: u16) -> Result<Ipv4Addr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(network)?;
+    socket.connect((host, port)).map_err(network)?;
+    match socket.local_addr().map_err(network)? {
+        SocketAddr::V4(address) => Ok(*address.ip()),
+        SocketAddr::V6(_) => Err(PortForwardingError::NoGateway),
+    }
+}
+
+/// Case-insensitive header lookup in an HTTP or SSDP message
+fn header_value<'a>(message: &'a str, name: &str) -> Option<&'a str> {
+    message.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Only plain http, which is what gateways announce
+fn parse_url(url: &str) -> Option<HttpUrl> {
+    let rest = url.trim().strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().ok()?),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some(HttpUrl {
+        host: host.to_string(),
+        port,
+        path: path.to_string(),
+    })
+}
+
+/// The control URL is usually a path on the description's host
+fn resolve_url(base: &HttpUrl, url: &str) -> HttpUrl {
+    if let Some(absolute) = parse_url(url) {
+        return absolute;
+    }
+    let path = if url.starts_with('/') {
+        url.to_string()
+    } else {
+        let directory = &base.path[..base.path.rfind('/').map_or(0, |index| index + 1)];
+        format!("{}{}", directory, url)
+    };
+    HttpUrl {
+        host: base.host.clone(),
+        port: base.port,
+        path: if path.starts_with('/') {
+            path
+        } else {
+            format!("/{}", path)
+        },
+    }
+}
+
+/// Service type and control URL of the WAN connection in a device description
+fn find_wan_service(description: &str) -> Option<(String, String)> {
+    description.split("<service>").skip(1).find_map(|service| {
+        let service_type = tag_text(service, "serviceType")?;
+        if !service_type.contains(":WANIPConnection:")
+            && !service_type.contains(":WANPPPConnection:")
+        {
+            return None;
+        }
+        let control_url = tag_text(service, "controlURL")?;
+        Some((service_type.to_string(), control_url.to_string()))
+    })
+}
+
+fn tag_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(xml[start..end].trim())
+}
+
+fn soap_envelope(service_type: &str, action: &str, args: &[(&str, String)]) -> String {
+    let args: String = args
+        .iter()
+        .map(|(name, value)| format!("<{0}>{1}</{0}>", name, xml_escape(value)))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\"?>\
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+         s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+         <s:Body><u:{action} xmlns:u=\"{service_type}\">{args}</u:{action}></s:Body>\
+         </s:Envelope>"
+    )
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// "code description" of a UPnP fault, if the response is one
+fn soap_error(body: &str) -> Option<String> {
+    let code = tag_text(body, "errorCode")?;
+    Some(match tag_text(body, "errorDescription") {
+        Some(description) => format!("{} {}", code, description),
+        None => code.to_string(),
+    })
+}
+
+/// The body of a chunked response. Stops at the last chunk, or at the first
+/// one that is malformed or cut short.
+fn decode_chunked(body: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    let mut rest = body;
+    while let Some(line_end) = rest.windows(2).position(|window| window == b"\r\n") {
+        let line = String::from_utf8_lossy(&rest[..line_end]);
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let Ok(size) = usize::from_str_radix(size, 16) else {
+            break;
+        };
+        let after = &rest[line_end + 2..];
+        let Some(chunk) = after.get(..size).filter(|_| size > 0) else {
+            break;
+        };
+        decoded.extend_from_slice(chunk);
+        let after = &after[size..];
+        rest = after.strip_prefix(b"\r\n").unwrap_or(after);
+    }
+    decoded
+}
+
+// --- NAT-PMP ---
+
+fn discover_nat_pmp() -> Result<Gateway> {
+    let address = default_gateway().ok_or(PortForwardingError::NoGateway)?;
+    // Asking for the external address tells whether the router speaks it
+    nat_pmp_request(address, &[0, 0], 12).map_err(|_| PortForwardingError::NoGateway)?;
+    let local_ip = local_ip_towards(&address.to_string(), NAT_PMP_PORT)?;
+    Ok(Gateway::NatPmp { address, local_ip })
+}
+
+fn nat_pmp_request(gateway: Ipv4Addr, request: &[u8], response_len: usize) -> Result<Vec<u8>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(network)?;
+    socket.connect((gateway, NAT_PMP_PORT)).map_err(network)?;
+    let mut buffer = [0u8; 16];
+    let mut timeout = Duration::from_millis(250);
+    for _ in 0..NAT_PMP_TRIES {
+        socket.send(request).map_err(network)?;
+        socket.set_read_timeout(Some(timeout)).map_err(network)?;
+        if let Ok(len) = socket.recv(&mut buffer) {
+            let response = &buffer[..len];
+            if len < response_len || response[0] != 0 || response[1] != request[1] + 128 {
+                continue;
+            }
+            return match u16::from_be_bytes([response[2], response[3]]) {
+                0 => Ok(response.to_vec()),
+                code => Err(PortForwardingError::Refused(
+                    nat_pmp_error(code).to_string(),
+                )),
+            };
+        }
+        timeout *= 2;
+    }
+    Err(PortForwardingError::Network(format!(
+        "No NAT-PMP answer from {}",
+        gateway
+    )))
+}
+
+fn nat_pmp_map_request(
+    protocol: PortProtocol,
+    internal_port: u16,
+    external_port: u16,
+    lifetime: u32,
+) -> [u8; 12] {
+    let opcode = match protocol {
+        PortProtocol::Udp => 1,
+        PortProtocol::Tcp => 2,
+    };
+    let mut request = [0u8; 12];
+    request[1] = opcode;
+    request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    request[6..8].copy_from_slice(&external_port.to_be_bytes());
+    request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+    request
+}
+
+/// The external port of a successful mapping response
+fn parse_nat_pmp_mapping(response: &[u8]) -> Option<u16> {
+    let external = response.get(10..12)?;
+    Some(u16::from_be_bytes([external[0], external[1]]))
+}
+
+fn nat_pmp_error(code: u16) -> &'static str {
+    match code {
+        1 => "unsupported version",
+        2 => "not authorized",
+        3 => "network failure",
+        4 => "out of resources",
+        5 => "unsupported opcode",
+        _ => "unknown error",
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn default_gateway() -> Option<Ipv4Addr> {
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "(Get-NetRoute -DestinationPrefix 0.0.0.0/0 | Sort-Object RouteMetric | \
+             Select-Object -First 1).NextHop",
+        ])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<Ipv4Addr> {
+    parse_proc_route(&std::fs::read_to_string("/proc/net/route").ok()?)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn default_gateway() -> Option<Ipv4Addr> {
+    None
+}
+
+/// Gateway of the default route in /proc/net/route, stored as little-endian hex
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_proc_route(content: &str) -> Option<Ipv4Addr> {
+    content.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(1) != Some(&"00000000") {
+            return None;
+        }
+        let gateway = u32::from_str_radix(fields.get(2)?, 16).ok()?;
+        (gateway != 0).then(|| Ipv4Addr::from(gateway.swap_bytes()))
+    })
+}
+
+fn now_secs() -> u64 {
+    events::now_millis() / 1000
+}
+
+fn load_config() -> PortForwardingConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upnp_description() {
+        let description = "<root><device><serviceList>\
+            <service><serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType>\
+            <controlURL>/ctl/L3F</controlURL></service>\
+            <service><serviceType>urn:schemas-upnp-org:service:WANIPConnection:2</serviceType>\
+            <controlURL>ctl/IPConn</controlURL></service>\
+            </serviceList></device></root>";
+        let (service_type, control) = find_wan_service(description).unwrap();
+        assert_eq!(
+            service_type,
+            "urn:schemas-upnp-org:service:WANIPConnection:2"
+        );
+
+        let location = parse_url("http://192.168.1.1:5000/rootDesc.xml").unwrap();
+        assert_eq!(location.port, 5000);
+        assert_eq!(resolve_url(&location, &control).path, "/ctl/IPConn");
+        assert_eq!(
+            resolve_url(&location, "/upnp/control").path,
+            "/upnp/control"
+        );
+        assert_eq!(parse_url("http://10.0.0.1").unwrap().path, "/");
+        assert!(parse_url("https://10.0.0.1/").is_none());
+
+        let ssdp = "HTTP/1.1 200 OK\r\nLOCATION: http://192.168.1.1:5000/rootDesc.xml\r\n\r\n";
+        assert_eq!(
+            header_value(ssdp, "location"),
+            Some("http://192.168.1.1:5000/rootDesc.xml")
+        );
+    }
+
+    #[test]
+    fn test_soap_messages() {
+        let envelope = soap_envelope(
+            "urn:schemas-upnp-org:service:WANIPConnection:1",
+            "DeletePortMapping",
+            &[("NewProtocol", "UDP".to_string())],
+        );
+        assert!(envelope.contains(
+            "<u:DeletePortMapping xmlns:u=\"urn:schemas-upnp-org:service:WANIPConnection:1\">\
+             <NewProtocol>UDP</NewProtocol></u:DeletePortMapping>"
+        ));
+
+        let fault = "<s:Fault><detail><UPnPError><errorCode>718</errorCode>\
+                     <errorDescription>ConflictInMappingEntry</errorDescription>\
+                     </UPnPError></detail></s:Fault>";
+        assert_eq!(soap_error(fault).unwrap(), "718 ConflictInMappingEntry");
+        assert!(soap_error("<NewExternalIPAddress>1.2.3.4</NewExternalIPAddress>").is_none());
+
+        assert_eq!(
+            decode_chunked(b"5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n"),
+            b"hello world"
+        );
+        // A chunk ending inside a character, and one longer than the body
+        assert_eq!(
+            decode_chunked(b"1\r\n\xc3\r\n1\r\n\xa8\r\n0\r\n\r\n"),
+            "\u{e8}".as_bytes()
+        );
+        assert_eq!(decode_chunked(b"ffffffff\r\nshort"), b"");
+    }
+
+    #[test]
+    fn test_nat_pmp() {
+        let request = nat_pmp_map_request(PortProtocol::Tcp, 27015, 27015, 3600);
+        assert_eq!(
+            request,
+            [0, 2, 0, 0, 0x69, 0x87, 0x69, 0x87, 0, 0, 0x0e, 0x10]
+        );
+
+        let response = [
+            0, 130, 0, 0, 0, 0, 0, 1, 0x69, 0x87, 0x69, 0x88, 0, 0, 0x0e, 0x10,
+        ];
+        assert_eq!(parse_nat_pmp_mapping(&response), Some(27016));
+
+        let route = "Iface\tDestination\tGateway\tFlags\n\
+                     eth0\t0000A8C0\t00000000\t0001\n\
+                     eth0\t00000000\t0101A8C0\t0003\n";
+        assert_eq!(parse_proc_route(route), Some(Ipv4Addr::new(192, 168, 1, 1)));
+    }
+}
@@ -0,0 +1,185 @@
+// Switches Windows to the High performance power plan while a game runs and
+// puts the previous plan back when the last such game exits. The plan found
+// before the switch is saved to disk, so a crash of Aura in the middle of a
+// game does not leave the machine on High performance.
+use crate::shared::paths;
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+const ORIGINAL_FILE: &str = "power_plan_original.json";
+const HIGH_PERFORMANCE: &str = "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c";
+
+#[derive(Error, Debug)]
+pub enum PowerPlanError {
+    #[error("Power plans can only be switched on Windows")]
+    Unsupported,
+
+    #[error("Failed to switch the power plan: {0}")]
+    ApplyError(String),
+
+    #[error("Failed to save the previous power plan: {0}")]
+    StorageError(String),
+}
+
+type Result<T> = std::result::Result<T, PowerPlanError>;
+
+struct Held {
+    original: String, // GUID of the plan
+    games: Vec<u32>,
+}
+
+static HELD: once_cell::sync::Lazy<Mutex<Option<Held>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+/// Whether this system can switch plans for a game
+pub fn supported() -> bool {
+    cfg!(target_os = "windows")
+}
+
+/// Switches to High performance for a game. The plan active before the first
+/// game is kept until the last one is released.
+pub fn apply_for_game(game_pid: u32) -> Result<()> {
+    let mut held = HELD
+        .lock()
+        .map_err(|e| PowerPlanError::ApplyError(e.to_string()))?;
+    if held.is_none() {
+        let original = active_plan()?;
+        save_original(&original)?;
+        *held = Some(Held {
+            original,
+            games: Vec::new(),
+        });
+    }
+    let result = set_active(HIGH_PERFORMANCE);
+    if let Some(held) = held.as_mut() {
+        if !held.games.contains(&game_pid) {
+            held.games.push(game_pid);
+        }
+    }
+    result
+}
+
+/// Restores the previous plan once no running game needs High performance
+pub fn release_game(game_pid: u32) {
+    let Ok(mut held) = HELD.lock() else {
+        return;
+    };
+    let Some(current) = held.as_mut() else {
+        return;
+    };
+    current.games.retain(|pid| *pid != game_pid);
+    if current.games.is_empty() {
+        if let Err(e) = set_active(&current.original) {
+            eprintln!("Failed to restore the power plan: {}", e);
+        }
+        let _ = std::fs::remove_file(paths::app_data_file(ORIGINAL_FILE));
+        *held = None;
+    }
+}
+
+/// Restores the previous plan right away, whatever games still run. Used on
+/// exit and from the panic hook, hence the try_lock.
+pub fn release_all() {
+    let Ok(mut held) = HELD.try_lock() else {
+        return;
+    };
+    if let Some(current) = held.take() {
+        // The saved original is replayed on the next start if this fails
+        if set_active(&current.original).is_ok() {
+            let _ = std::fs::remove_file(paths::app_data_file(ORIGINAL_FILE));
+        }
+    }
+}
+
+/// Puts back the plan left behind when Aura stopped during a game
+pub fn restore_leftover() {
+    let path = paths::app_data_file(ORIGINAL_FILE);
+    let Some(original) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<String>(&content).ok())
+    else {
+        return;
+    };
+    if HELD.lock().is_ok_and(|held| held.is_none()) && set_active(&original).is_ok() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn save_original(original: &str) -> Result<()> {
+    let content =
+        serde_json::to_string(original).map_err(|e| PowerPlanError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(ORIGINAL_FILE), content)
+        .map_err(|e| PowerPlanError::StorageError(e.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn active_plan() -> Result<String> {
+    let output = powercfg(&["/getactivescheme"])?;
+    parse_scheme_guid(&output)
+        .ok_or_else(|| PowerPlanError::ApplyError("no active power plan".to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn set_active(guid: &str) -> Result<()> {
+    powercfg(&["/setactive", guid]).map(|_| ())
+}
+
+#[cfg(target_os = "windows")]
+fn powercfg(args: &[&str]) -> Result<String> {
+    let output = Command::new("powercfg")
+        .args(args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .map_err(|e| PowerPlanError::ApplyError(e.to_string()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        // powercfg prints its errors, like a plan that doesn't exist, to stdout
+        return Err(PowerPlanError::ApplyError(stdout.trim().to_string()));
+    }
+    Ok(stdout)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn active_plan() -> Result<String> {
+    Err(PowerPlanError::Unsupported)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn set_active(_guid: &str) -> Result<()> {
+    Err(PowerPlanError::Unsupported)
+}
+
+/// The plan GUID of a `powercfg /getactivescheme`, whatever its language
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn parse_scheme_guid(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .find(|token| {
+            token.len() == 36
+                && token.chars().enumerate().all(|(index, c)| match index {
+                    8 | 13 | 18 | 23 => c == '-',
+                    _ => c.is_ascii_hexdigit(),
+                })
+        })
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scheme_guid() {
+        let output = "Power Scheme GUID: 381b4222-f694-41f0-9685-ff5bb260df2e  (Balanced)\r\n";
+        assert_eq!(
+            parse_scheme_guid(output).as_deref(),
+            Some("381b4222-f694-41f0-9685-ff5bb260df2e")
+        );
+        assert_eq!(parse_scheme_guid("Invalid Parameters"), None);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::services::game_library;
+use crate::services::process_control::{self, ProcessControlError};
+use crate::shared::system::get_system;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use sysinfo::{Pid, Process, System};
+
+type Result<T> = std::result::Result<T, ProcessControlError>;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrphanProcess {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub exe_path: String,
+    pub memory_bytes: u64,
+    pub run_time_secs: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CleanupResult {
+    pub terminated: Vec<u32>,
+    pub skipped: Vec<u32>, // no longer orphaned or already gone
+    pub failed: Vec<String>,
+}
+
+/// Finds processes left behind by a crashed game: their parent is gone and
+/// their executable lives under a known game directory.
+pub fn find_orphaned_game_processes() -> Result<Vec<OrphanProcess>> {
+    let game_directories = game_library::get_game_directories();
+    if game_directories.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut system = get_system()
+        .lock()
+        .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+    system.refresh_all();
+
+    let orphans = system
+        .processes()
+        .iter()
+        .filter(|(_, process)| is_orphaned_game_process(&system, process, &game_directories))
+        .map(|(pid, process)| OrphanProcess {
+            pid: pid.as_u32(),
+            parent_pid: process.parent().map(|p| p.as_u32()),
+            name: process.name().to_string_lossy().into_owned(),
+            exe_path: process
+                .exe()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            memory_bytes: process.memory(),
+            run_time_secs: process.run_time(),
+        })
+        .collect();
+
+    Ok(orphans)
+}
+
+/// Terminates the given orphaned processes. Each PID is checked again right
+/// before the kill so a PID reused by an unrelated process is never touched.
+pub fn terminate_orphaned_processes(pids: &[u32]) -> Result<CleanupResult> {
+    let game_directories = game_library::get_game_directories();
+    let mut result = CleanupResult::default();
+
+    let still_orphaned: Vec<u32> = {
+        let mut system = get_system()
+            .lock()
+            .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+        system.refresh_all();
+
+        pids.iter()
+            .copied()
+            .filter(|pid| {
+                let orphaned = system
+                    .process(Pid::from(*pid as usize))
+                    .is_some_and(|p| is_orphaned_game_process(&system, p, &game_directories));
+                if !orphaned {
+                    result.skipped.push(*pid);
+                }
+                orphaned
+            })
+            .collect()
+    };
+
+    // kill_process takes the system lock itself
+    for pid in still_orphaned {
+        match process_control::kill_process(pid) {
+            Ok(()) => result.terminated.push(pid),
+            Err(e) => result.failed.push(format!("{}: {}", pid, e)),
+        }
+    }
+
+    Ok(result)
+}
+
+fn is_orphaned_game_process(
+    system: &System,
+    process: &Process,
+    game_directories: &[PathBuf],
+) -> bool {
+    let in_game_directory = process
+        .exe()
+        .is_some_and(|exe| game_library::is_in_game_directory(exe, game_directories));
+
+    in_game_directory && !has_live_parent(system, process)
+}
+
+fn has_live_parent(system: &System, process: &Process) -> bool {
+    let Some(parent_pid) = process.parent() else {
+        return false;
+    };
+
+    // On Linux orphans are re-parented to init
+    #[cfg(target_os = "linux")]
+    if parent_pid.as_u32() == 1 {
+        return false;
+    }
+
+    match system.process(parent_pid) {
+        // Windows reuses PIDs: a "parent" started after the child is a different process
+        Some(parent) => parent.start_time() <= process.start_time(),
+        None => false,
+    }
+}
@@ -1,5 +1,7 @@
+use crate::models::game_profile::ProcessPriority;
+use crate::services::recovery::{self, Action};
 use crate::shared::system::get_system;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 use sysinfo::Pid;
@@ -18,9 +20,20 @@ use windows::Win32::System::Threading::{
     OpenThread, ResumeThread, SuspendThread, THREAD_SUSPEND_RESUME,
 };
 
+// Two cumulative counters of each PID and when they were read
+type SampleCache = once_cell::sync::Lazy<Arc<Mutex<HashMap<u32, (u64, u64, SystemTime)>>>>;
+
 // Static cache for CPU usage calculation
 #[cfg(target_os = "windows")]
-static CPU_USAGE_CACHE: once_cell::sync::Lazy<Arc<Mutex<HashMap<u32, (u64, u64, SystemTime)>>>> =
+static CPU_USAGE_CACHE: SampleCache =
+    once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Static cache for disk I/O rate calculation (total read bytes, total write bytes, timestamp)
+static IO_RATE_CACHE: SampleCache =
+    once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+// Static cache for fault rate calculation (total page faults, hard faults, timestamp)
+static FAULT_RATE_CACHE: SampleCache =
     once_cell::sync::Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
 
 // External Windows API declarations
@@ -50,7 +63,10 @@ const THREAD_WAIT_REASON_SUSPENDED: u32 = 5;
 struct SystemProcessInformation {
     next_entry_offset: u32,
     number_of_threads: u32,
-    reserved1: [u64; 3],
+    working_set_private_size: i64,
+    hard_fault_count: u32,
+    number_of_threads_high_watermark: u32,
+    cycle_time: u64,
     create_time: i64,
     user_time: i64,
     kernel_time: i64,
@@ -125,6 +141,9 @@ pub enum ProcessControlError {
 
     #[error("Process not found: {0}")]
     NotFound(u32),
+
+    #[error("Window operation failed: {0}")]
+    WindowError(String),
 }
 
 type Result<T> = std::result::Result<T, ProcessControlError>;
@@ -319,6 +338,173 @@ pub fn get_process_affinity(pid: u32) -> Result<Vec<u32>> {
     }
 }
 
+/// Restricts a process to the given logical cores without touching its
+/// priority, unlike `set_process_affinity_cores`
+pub fn pin_process_to_cores(pid: u32, cores: &[u32]) -> Result<()> {
+    if cores.is_empty() {
+        return Err(ProcessControlError::AffinityError(
+            "At least one core must be specified".to_string(),
+        ));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let mask = cores
+            .iter()
+            .filter(|core| **core < usize::BITS)
+            .fold(0usize, |mask, core| mask | (1 << core));
+        unsafe {
+            let process_handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)
+                .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+            let result = SetProcessAffinityMask(process_handle, mask)
+                .map_err(|e| ProcessControlError::AffinityError(e.to_string()));
+            let _ = CloseHandle(process_handle);
+            result
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Affinity is per thread on Linux, so every thread gets the mask
+        let tasks = std::fs::read_dir(format!("/proc/{}/task", pid))
+            .map_err(|_| ProcessControlError::NotFound(pid))?;
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for core in cores {
+                libc::CPU_SET(*core as usize, &mut set);
+            }
+            for task in tasks.flatten() {
+                let Some(tid) = task.file_name().to_str().and_then(|t| t.parse().ok()) else {
+                    continue;
+                };
+                if libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                    return Err(ProcessControlError::AffinityError(
+                        std::io::Error::last_os_error().to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
+/// Sets the priority class of a process, or its nice value on Linux
+pub fn set_process_priority(pid: u32, priority: ProcessPriority) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        unsafe {
+            let process_handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)
+                .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+            let result = SetPriorityClass(process_handle, priority_class(priority))
+                .map_err(|e| ProcessControlError::OpenError(e.to_string()));
+            let _ = CloseHandle(process_handle);
+            result
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let nice = match priority {
+            ProcessPriority::Idle => 19,
+            ProcessPriority::BelowNormal => 10,
+            ProcessPriority::Normal => 0,
+            ProcessPriority::AboveNormal => -5,
+            ProcessPriority::High => -10,
+        };
+        // Negative values need CAP_SYS_NICE
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid, nice) } != 0 {
+            return Err(ProcessControlError::OpenError(
+                std::io::Error::last_os_error().to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = priority;
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn priority_class(
+    priority: ProcessPriority,
+) -> windows::Win32::System::Threading::PROCESS_CREATION_FLAGS {
+    use windows::Win32::System::Threading::{
+        ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
+        NORMAL_PRIORITY_CLASS,
+    };
+
+    match priority {
+        ProcessPriority::Idle => IDLE_PRIORITY_CLASS,
+        ProcessPriority::BelowNormal => BELOW_NORMAL_PRIORITY_CLASS,
+        ProcessPriority::Normal => NORMAL_PRIORITY_CLASS,
+        ProcessPriority::AboveNormal => ABOVE_NORMAL_PRIORITY_CLASS,
+        ProcessPriority::High => HIGH_PRIORITY_CLASS,
+    }
+}
+
+/// Turns the dynamic priority boost of a process on or off. Windows briefly
+/// raises the priority of threads that wake up or own the foreground window;
+/// background processes that wake often keep winning time slices from the
+/// game that way.
+pub fn set_priority_boost(pid: u32, enabled: bool) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::System::Threading::SetProcessPriorityBoost;
+
+        unsafe {
+            let process_handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid)
+                .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+            // The API takes whether boosting is disabled
+            let result = SetProcessPriorityBoost(process_handle, !enabled)
+                .map_err(|e| ProcessControlError::OpenError(e.to_string()));
+            let _ = CloseHandle(process_handle);
+            result
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        // The Linux scheduler has no per-process boost to turn off
+        let _ = (pid, enabled);
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
+/// Whether the dynamic priority boost of a process is on, the default
+pub fn get_priority_boost(pid: u32) -> Result<bool> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::System::Threading::{
+            GetProcessPriorityBoost, PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+
+        unsafe {
+            let process_handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+                .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+            let mut disabled = windows::core::BOOL::default();
+            let result = GetProcessPriorityBoost(process_handle, &mut disabled)
+                .map(|_| !disabled.as_bool())
+                .map_err(|e| ProcessControlError::OpenError(e.to_string()));
+            let _ = CloseHandle(process_handle);
+            result
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = pid;
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
 pub fn kill_process(pid: u32) -> Result<()> {
     let mut system = get_system()
         .lock()
@@ -374,7 +560,21 @@ pub fn kill_process(pid: u32) -> Result<()> {
     }
 }
 
+/// Suspends a process. It is journaled until resumed, so a crash of Aura
+/// doesn't leave it frozen.
 pub fn suspend_process(pid: u32) -> Result<()> {
+    suspend_process_inner(pid)?;
+    recovery::track(Action::Resume, pid);
+    Ok(())
+}
+
+pub fn resume_process(pid: u32) -> Result<()> {
+    let result = resume_process_inner(pid);
+    recovery::untrack(Action::Resume, pid);
+    result
+}
+
+fn suspend_process_inner(pid: u32) -> Result<()> {
     #[cfg(target_os = "windows")]
     {
         suspend_process_threads(pid)
@@ -409,7 +609,7 @@ pub fn suspend_process(pid: u32) -> Result<()> {
     }
 }
 
-pub fn resume_process(pid: u32) -> Result<()> {
+fn resume_process_inner(pid: u32) -> Result<()> {
     #[cfg(target_os = "windows")]
     {
         resume_process_threads(pid)
@@ -626,8 +826,10 @@ fn resume_process_threads(pid: u32) -> Result<()> {
 
 
 
+/// Whether each running process has a suspended thread, keyed by PID, from a
+/// single system snapshot, so checking every process costs one scan
 #[cfg(target_os = "windows")]
-pub fn is_process_suspended(pid: u32) -> Result<bool> {
+pub fn get_suspension_states() -> Result<HashMap<u32, bool>> {
     unsafe {
         // First try to get the required buffer size
         let mut buffer_size: u32 = 0;
@@ -662,7 +864,7 @@ pub fn is_process_suspended(pid: u32) -> Result<bool> {
             )));
         }
 
-        // Parse the buffer to find our process
+        let mut states = HashMap::new();
         let mut offset = 0usize;
         loop {
             if offset >= buffer.len() {
@@ -671,9 +873,8 @@ pub fn is_process_suspended(pid: u32) -> Result<bool> {
 
             let process_info = &*(buffer.as_ptr().add(offset) as *const SystemProcessInformation);
 
-            // Check if this is our target process
-            if process_info.unique_process_id == pid as usize {
-                // Found our process, now check its threads
+            // Skip system idle process (PID 0)
+            if process_info.unique_process_id != 0 {
                 let threads_start = offset + std::mem::size_of::<SystemProcessInformation>();
                 let mut suspended_threads = 0;
                 let total_threads = process_info.number_of_threads;
@@ -697,7 +898,7 @@ pub fn is_process_suspended(pid: u32) -> Result<bool> {
                 }
 
                 // Process is considered suspended if any of its threads are suspended
-                return Ok(suspended_threads > 0);
+                states.insert(process_info.unique_process_id as u32, suspended_threads > 0);
             }
 
             // Move to next process entry
@@ -707,8 +908,7 @@ pub fn is_process_suspended(pid: u32) -> Result<bool> {
             offset += process_info.next_entry_offset as usize;
         }
 
-        // Process not found
-        Err(ProcessControlError::NotFound(pid))
+        Ok(states)
     }
 }
 
@@ -740,6 +940,24 @@ pub fn is_process_suspended(_pid: u32) -> Result<bool> {
     Ok(false) // Always return false for unsupported platforms
 }
 
+/// Whether each running process is stopped, keyed by PID
+#[cfg(target_os = "linux")]
+pub fn get_suspension_states() -> Result<HashMap<u32, bool>> {
+    let entries =
+        std::fs::read_dir("/proc").map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+
+    let states = entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| Some((pid, is_process_suspended(pid).ok()?)))
+        .collect();
+    Ok(states)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn get_suspension_states() -> Result<HashMap<u32, bool>> {
+    Ok(HashMap::new())
+}
+
 // Complete process information structure
 #[derive(Debug, Clone)]
 pub struct ProcessInfo {
@@ -749,7 +967,8 @@ pub struct ProcessInfo {
     pub exe_path: String,
     pub cpu_time_user: u64,
     pub cpu_time_kernel: u64,
-    pub cpu_usage_percent: f64,
+    pub cpu_usage_percent: f64,     // share of total CPU capacity (0-100)
+    pub cpu_usage_single_core: f64, // share of one logical core (may exceed 100)
     pub memory_working_set: u64,
     pub memory_private: u64,
     pub memory_virtual: u64,
@@ -757,16 +976,47 @@ pub struct ProcessInfo {
     pub handle_count: u32,
     pub thread_count: u32,
     pub is_suspended: bool,
+    pub is_hung: bool, // owns a window that is not responding
     pub create_time: i64,
     pub session_id: u32,
     pub io_read_bytes: u64,
     pub io_write_bytes: u64,
     pub io_read_operations: u64,
     pub io_write_operations: u64,
+    pub io_read_rate: u64,  // bytes/sec
+    pub io_write_rate: u64, // bytes/sec
+    pub energy_impact: f64, // estimated, 0-100
+    pub page_faults: u64,
+    pub hard_faults: u64,     // faults that had to read from disk
+    pub page_fault_rate: u64, // faults/sec
+    pub hard_fault_rate: u64, // faults/sec
+}
+
+/// Per-process values that cost a system call each, so callers that don't
+/// show them can skip them
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessQuery {
+    pub exe_path: bool,
+    pub energy_impact: bool,
+    pub is_hung: bool,
+}
+
+impl ProcessQuery {
+    pub const ALL: Self = Self {
+        exe_path: true,
+        energy_impact: true,
+        is_hung: true,
+    };
 }
 
-#[cfg(target_os = "windows")]
 pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
+    get_processes_info(ProcessQuery::ALL)
+}
+
+/// Every process with the values `query` asks for; skipped paths are "N/A",
+/// skipped numbers and flags are zero and false
+#[cfg(target_os = "windows")]
+pub fn get_processes_info(query: ProcessQuery) -> Result<Vec<ProcessInfo>> {
     unsafe {
         // First try to get the required buffer size
         let mut buffer_size: u32 = 0;
@@ -805,6 +1055,18 @@ pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
         let mut processes = Vec::new();
         let mut offset = 0usize;
 
+        // Logical processor count used to normalize CPU usage
+        let mut system_info = SYSTEM_INFO::default();
+        GetSystemInfo(&mut system_info);
+        let logical_processors = system_info.dwNumberOfProcessors.max(1);
+
+        // Processes with a window that stopped responding
+        let hung_pids = if query.is_hung {
+            crate::services::window_control::get_hung_process_ids()
+        } else {
+            Default::default()
+        };
+
         loop {
             if offset >= buffer.len() {
                 break;
@@ -830,14 +1092,18 @@ pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
                 };
 
                 // Get executable path
-                let exe_path =
-                    get_process_executable_path(pid).unwrap_or_else(|| "N/A".to_string());
+                let exe_path = query
+                    .exe_path
+                    .then(|| get_process_executable_path(pid))
+                    .flatten()
+                    .unwrap_or_else(|| "N/A".to_string());
 
                 // Calculate CPU usage
-                let cpu_usage = calculate_cpu_usage(
+                let (cpu_usage_single_core, cpu_usage) = calculate_cpu_usage(
                     pid,
                     process_info.user_time as u64,
                     process_info.kernel_time as u64,
+                    logical_processors,
                 );
 
                 // Check if any thread is suspended
@@ -863,6 +1129,28 @@ pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
                     }
                 }
 
+                // Calculate disk I/O rates from the cumulative transfer counters
+                let (io_read_rate, io_write_rate) = calculate_io_rates(
+                    pid,
+                    process_info.read_transfer_count as u64,
+                    process_info.write_transfer_count as u64,
+                );
+                let (page_fault_rate, hard_fault_rate) = calculate_fault_rates(
+                    pid,
+                    process_info.page_fault_count as u64,
+                    process_info.hard_fault_count as u64,
+                );
+                let energy_impact = if query.energy_impact {
+                    crate::services::energy_monitor::estimate_energy_impact(
+                        pid,
+                        cpu_usage,
+                        io_read_rate,
+                        io_write_rate,
+                    )
+                } else {
+                    0.0
+                };
+
                 let proc_info = ProcessInfo {
                     pid,
                     parent_pid: process_info.inherited_from_unique_process_id as u32,
@@ -871,6 +1159,7 @@ pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
                     cpu_time_user: process_info.user_time as u64,
                     cpu_time_kernel: process_info.kernel_time as u64,
                     cpu_usage_percent: cpu_usage,
+                    cpu_usage_single_core,
                     memory_working_set: process_info.working_set_size as u64,
                     memory_private: process_info.private_page_count as u64,
                     memory_virtual: process_info.virtual_size as u64,
@@ -878,14 +1167,27 @@ pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
                     handle_count: process_info.handle_count,
                     thread_count: process_info.number_of_threads,
                     is_suspended: suspended_threads > 0,
+                    is_hung: hung_pids.contains(&pid),
                     create_time: process_info.create_time,
                     session_id: process_info.session_id,
                     io_read_bytes: process_info.read_transfer_count as u64,
                     io_write_bytes: process_info.write_transfer_count as u64,
                     io_read_operations: process_info.read_operation_count as u64,
                     io_write_operations: process_info.write_operation_count as u64,
+                    io_read_rate,
+                    io_write_rate,
+                    energy_impact,
+                    page_faults: process_info.page_fault_count as u64,
+                    hard_faults: process_info.hard_fault_count as u64,
+                    page_fault_rate,
+                    hard_fault_rate,
                 };
 
+                crate::services::process_history::record(
+                    pid,
+                    cpu_usage,
+                    proc_info.memory_working_set,
+                );
                 processes.push(proc_info);
             }
 
@@ -896,6 +1198,13 @@ pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
             offset += process_info.next_entry_offset as usize;
         }
 
+        // Drop cached samples of processes that no longer exist
+        let live_pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+        prune_stale_samples(&live_pids);
+
+        // Feed the handle leak detector with the fresh handle counts
+        crate::services::handle_monitor::record_snapshot(&processes);
+
         Ok(processes)
     }
 }
@@ -986,41 +1295,150 @@ fn convert_device_path_to_drive_path(device_path: &str) -> Option<String> {
     None
 }
 
+/// Calculates CPU usage from the kernel+user time delta since the previous sample.
+/// Returns (percent of one logical core, percent of total CPU capacity).
 #[cfg(target_os = "windows")]
-fn calculate_cpu_usage(pid: u32, user_time: u64, kernel_time: u64) -> f64 {
+fn calculate_cpu_usage(
+    pid: u32,
+    user_time: u64,
+    kernel_time: u64,
+    logical_processors: u32,
+) -> (f64, f64) {
     let current_time = SystemTime::now();
     let current_total_time = user_time + kernel_time;
 
-    let mut cache = CPU_USAGE_CACHE.lock().unwrap();
+    let mut cache = match CPU_USAGE_CACHE.lock() {
+        Ok(cache) => cache,
+        Err(_) => return (0.0, 0.0),
+    };
+
+    let single_core_percentage =
+        if let Some((last_total_time, _last_user_time, last_timestamp)) = cache.get(&pid) {
+            let time_delta = current_time
+                .duration_since(*last_timestamp)
+                .unwrap_or_default()
+                .as_secs_f64();
+            let cpu_time_delta = current_total_time.saturating_sub(*last_total_time);
+
+            // Convert FILETIME (100ns units) to seconds and calculate percentage
+            let cpu_seconds = (cpu_time_delta as f64) / 10_000_000.0;
+            if time_delta > 0.0 {
+                (cpu_seconds / time_delta) * 100.0
+            } else {
+                0.0
+            }
+        } else {
+            // First time seeing this process, store data and return 0
+            0.0
+        };
+
+    // Update cache
+    cache.insert(pid, (current_total_time, user_time, current_time));
+
+    normalize_cpu_usage(single_core_percentage, logical_processors)
+}
+
+/// Clamps a "percent of one core" value to the machine capacity and derives the
+/// matching "percent of total CPU" value.
+pub fn normalize_cpu_usage(single_core_percentage: f64, logical_processors: u32) -> (f64, f64) {
+    let logical_processors = logical_processors.max(1) as f64;
+    let single_core = single_core_percentage.clamp(0.0, 100.0 * logical_processors);
+    let total = (single_core / logical_processors).min(100.0);
+    (single_core, total)
+}
+
+/// Removes cached CPU and I/O samples for PIDs that are no longer running, so
+/// the caches don't grow forever and a recycled PID doesn't inherit old data.
+pub fn prune_stale_samples(live_pids: &HashSet<u32>) {
+    #[cfg(target_os = "windows")]
+    if let Ok(mut cache) = CPU_USAGE_CACHE.lock() {
+        cache.retain(|pid, _| live_pids.contains(pid));
+    }
+
+    if let Ok(mut cache) = IO_RATE_CACHE.lock() {
+        cache.retain(|pid, _| live_pids.contains(pid));
+    }
+
+    if let Ok(mut cache) = FAULT_RATE_CACHE.lock() {
+        cache.retain(|pid, _| live_pids.contains(pid));
+    }
+
+    crate::services::energy_monitor::prune_samples(live_pids);
+    crate::services::process_history::prune_samples(live_pids);
+}
 
-    if let Some((last_total_time, _last_user_time, last_timestamp)) = cache.get(&pid) {
+/// Converts cumulative read/write byte counters into bytes/sec rates using the
+/// previous sample stored for the same PID. Returns (0, 0) on the first sample.
+pub fn calculate_io_rates(pid: u32, total_read: u64, total_write: u64) -> (u64, u64) {
+    counter_rates(&IO_RATE_CACHE, pid, total_read, total_write)
+}
+
+/// Converts cumulative page fault and hard fault counters into faults/sec the
+/// same way. Hard faults read from disk, a burst of them is a classic cause of
+/// stutter when RAM runs short.
+pub fn calculate_fault_rates(pid: u32, page_faults: u64, hard_faults: u64) -> (u64, u64) {
+    counter_rates(&FAULT_RATE_CACHE, pid, page_faults, hard_faults)
+}
+
+fn counter_rates(
+    cache: &Mutex<HashMap<u32, (u64, u64, SystemTime)>>,
+    pid: u32,
+    total_first: u64,
+    total_second: u64,
+) -> (u64, u64) {
+    let current_time = SystemTime::now();
+
+    let mut cache = match cache.lock() {
+        Ok(cache) => cache,
+        Err(_) => return (0, 0),
+    };
+
+    let rates = if let Some((last_first, last_second, last_timestamp)) = cache.get(&pid) {
         let time_delta = current_time
             .duration_since(*last_timestamp)
             .unwrap_or_default()
             .as_secs_f64();
-        let cpu_time_delta = current_total_time.saturating_sub(*last_total_time);
 
-        // Convert FILETIME (100ns units) to seconds and calculate percentage
-        let cpu_seconds = (cpu_time_delta as f64) / 10_000_000.0;
-        let cpu_percentage = if time_delta > 0.0 {
-            (cpu_seconds / time_delta) * 100.0
+        if time_delta > 0.0 {
+            let first_rate = (total_first.saturating_sub(*last_first) as f64 / time_delta) as u64;
+            let second_rate =
+                (total_second.saturating_sub(*last_second) as f64 / time_delta) as u64;
+            (first_rate, second_rate)
         } else {
-            0.0
-        };
+            (0, 0)
+        }
+    } else {
+        // First time seeing this process, no rate available yet
+        (0, 0)
+    };
 
-        // Update cache
-        cache.insert(pid, (current_total_time, user_time, current_time));
+    cache.insert(pid, (total_first, total_second, current_time));
+    rates
+}
 
-        cpu_percentage.min(100.0) // Cap at 100%
-    } else {
-        // First time seeing this process, store data and return 0
-        cache.insert(pid, (current_total_time, user_time, current_time));
-        0.0
-    }
+/// Minor and major fault counts of a process from /proc/<pid>/stat
+#[cfg(not(target_os = "windows"))]
+fn proc_fault_counts(pid: u32) -> (u64, u64) {
+    std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .ok()
+        .and_then(|stat| parse_stat_faults(&stat))
+        .unwrap_or_default()
+}
+
+/// (all faults, major faults) from a /proc/<pid>/stat line. The name in
+/// parentheses may hold spaces, fields are counted after its closing one.
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn parse_stat_faults(stat: &str) -> Option<(u64, u64)> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // state ppid pgrp session tty_nr tpgid flags minflt cminflt majflt
+    let minor: u64 = fields.get(7)?.parse().ok()?;
+    let major: u64 = fields.get(9)?.parse().ok()?;
+    Some((minor + major, major))
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn get_all_processes_info() -> Result<Vec<ProcessInfo>> {
+pub fn get_processes_info(_query: ProcessQuery) -> Result<Vec<ProcessInfo>> {
     Err(ProcessControlError::UnsupportedPlatform)
 }
 
@@ -1056,6 +1474,47 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_io_rates() {
+        // Use a PID that cannot exist so the cache entry is isolated
+        let pid = u32::MAX;
+
+        assert_eq!(calculate_io_rates(pid, 1000, 1000), (0, 0));
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let (read_rate, write_rate) = calculate_io_rates(pid, 1_000_000, 1000);
+        assert!(read_rate > 0);
+        assert_eq!(write_rate, 0);
+    }
+
+    #[test]
+    fn test_fault_rates() {
+        let pid = u32::MAX;
+
+        assert_eq!(calculate_fault_rates(pid, 500, 10), (0, 0));
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let (page_fault_rate, hard_fault_rate) = calculate_fault_rates(pid, 500, 5000);
+        assert_eq!(page_fault_rate, 0);
+        assert!(hard_fault_rate > 0);
+    }
+
+    #[test]
+    fn test_parse_stat_faults() {
+        let stat = "1234 (Web Content) S 1 1234 1234 0 -1 4194560 8800 0 120 0 35 12 0 0 20 0";
+        assert_eq!(parse_stat_faults(stat), Some((8920, 120)));
+        assert_eq!(parse_stat_faults("1234 (truncated"), None);
+    }
+
+    #[test]
+    fn test_normalize_cpu_usage() {
+        assert_eq!(normalize_cpu_usage(50.0, 1), (50.0, 50.0));
+        assert_eq!(normalize_cpu_usage(400.0, 8), (400.0, 50.0));
+        // Values above machine capacity are clamped
+        assert_eq!(normalize_cpu_usage(1000.0, 4), (400.0, 100.0));
+        assert_eq!(normalize_cpu_usage(-5.0, 4), (0.0, 0.0));
+    }
+
     #[test]
     fn test_invalid_process() {
         let rt = Runtime::new().unwrap();
@@ -1092,6 +1551,17 @@ pub fn get_process_detailed_info(pid: u32) -> Result<ProcessInfo> {
         .ok_or(ProcessControlError::NotFound(pid))?;
 
     let is_suspended = is_process_suspended(pid).unwrap_or(false);
+    // sysinfo reports usage relative to a single core
+    let (cpu_usage_single_core, cpu_usage_percent) =
+        normalize_cpu_usage(process.cpu_usage() as f64, system.cpus().len() as u32);
+    let disk_usage = process.disk_usage();
+    let (io_read_rate, io_write_rate) = calculate_io_rates(
+        pid,
+        disk_usage.total_read_bytes,
+        disk_usage.total_written_bytes,
+    );
+    let (page_faults, hard_faults) = proc_fault_counts(pid);
+    let (page_fault_rate, hard_fault_rate) = calculate_fault_rates(pid, page_faults, hard_faults);
 
     Ok(ProcessInfo {
         pid,
@@ -1103,7 +1573,8 @@ pub fn get_process_detailed_info(pid: u32) -> Result<ProcessInfo> {
             .unwrap_or_else(|| "N/A".to_string()),
         cpu_time_user: 0,   // Not available through sysinfo
         cpu_time_kernel: 0, // Not available through sysinfo
-        cpu_usage_percent: process.cpu_usage() as f64,
+        cpu_usage_percent,
+        cpu_usage_single_core,
         memory_working_set: process.memory(),
         memory_private: 0, // Not available through sysinfo
         memory_virtual: process.virtual_memory(),
@@ -1111,12 +1582,26 @@ pub fn get_process_detailed_info(pid: u32) -> Result<ProcessInfo> {
         handle_count: 0,    // Not available through sysinfo
         thread_count: 0,    // Not available through sysinfo
         is_suspended,
-        create_time: 0,         // Not easily available through sysinfo
-        session_id: 0,          // Not available through sysinfo
-        io_read_bytes: 0,       // Not available through sysinfo
-        io_write_bytes: 0,      // Not available through sysinfo
+        is_hung: false, // Window responsiveness is only tracked on Windows
+        // sysinfo gives the start time as Unix seconds, store it as FILETIME like the native path
+        create_time: (process.start_time() as i64 + 11644473600) * 10_000_000,
+        session_id: 0, // Not available through sysinfo
+        io_read_bytes: disk_usage.total_read_bytes,
+        io_write_bytes: disk_usage.total_written_bytes,
         io_read_operations: 0,  // Not available through sysinfo
         io_write_operations: 0, // Not available through sysinfo
+        io_read_rate,
+        io_write_rate,
+        energy_impact: crate::services::energy_monitor::estimate_energy_impact(
+            pid,
+            cpu_usage_percent,
+            io_read_rate,
+            io_write_rate,
+        ),
+        page_faults,
+        hard_faults,
+        page_fault_rate,
+        hard_fault_rate,
     })
 }
 
@@ -1148,6 +1633,17 @@ pub fn get_child_processes(parent_pid: u32) -> Result<Vec<ProcessInfo>> {
                 if process_parent_pid.as_u32() == parent_pid {
                     let child_pid = pid.as_u32();
                     let is_suspended = is_process_suspended(child_pid).unwrap_or(false);
+                    let (cpu_usage_single_core, cpu_usage_percent) =
+                        normalize_cpu_usage(process.cpu_usage() as f64, system.cpus().len() as u32);
+                    let disk_usage = process.disk_usage();
+                    let (io_read_rate, io_write_rate) = calculate_io_rates(
+                        child_pid,
+                        disk_usage.total_read_bytes,
+                        disk_usage.total_written_bytes,
+                    );
+                    let (page_faults, hard_faults) = proc_fault_counts(child_pid);
+                    let (page_fault_rate, hard_fault_rate) =
+                        calculate_fault_rates(child_pid, page_faults, hard_faults);
 
                     let child_info = ProcessInfo {
                         pid: child_pid,
@@ -1159,7 +1655,8 @@ pub fn get_child_processes(parent_pid: u32) -> Result<Vec<ProcessInfo>> {
                             .unwrap_or_else(|| "N/A".to_string()),
                         cpu_time_user: 0,
                         cpu_time_kernel: 0,
-                        cpu_usage_percent: process.cpu_usage() as f64,
+                        cpu_usage_percent,
+                        cpu_usage_single_core,
                         memory_working_set: process.memory(),
                         memory_private: 0,
                         memory_virtual: process.virtual_memory(),
@@ -1167,12 +1664,25 @@ pub fn get_child_processes(parent_pid: u32) -> Result<Vec<ProcessInfo>> {
                         handle_count: 0,
                         thread_count: 0,
                         is_suspended,
+                        is_hung: false,
                         create_time: 0,
                         session_id: 0,
-                        io_read_bytes: 0,
-                        io_write_bytes: 0,
+                        io_read_bytes: disk_usage.total_read_bytes,
+                        io_write_bytes: disk_usage.total_written_bytes,
                         io_read_operations: 0,
                         io_write_operations: 0,
+                        io_read_rate,
+                        io_write_rate,
+                        energy_impact: crate::services::energy_monitor::estimate_energy_impact(
+                            child_pid,
+                            cpu_usage_percent,
+                            io_read_rate,
+                            io_write_rate,
+                        ),
+                        page_faults,
+                        hard_faults,
+                        page_fault_rate,
+                        hard_fault_rate,
                     };
 
                     children.push(child_info);
@@ -0,0 +1,127 @@
+use crate::shared::events;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Minimum spacing between two samples of the same process
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+// Samples kept per process, a minute at the sampling interval
+const HISTORY_LEN: usize = 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProcessSample {
+    pub timestamp: u64, // Unix millis
+    pub cpu_usage: f64, // percent of total CPU capacity
+    pub memory_bytes: u64,
+}
+
+/// Recent CPU and memory samples of a process, oldest first
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessHistory {
+    pub pid: u32,
+    pub interval_ms: u64,
+    pub samples: Vec<ProcessSample>,
+}
+
+struct SampleBuffer {
+    samples: VecDeque<ProcessSample>,
+    last_sample: Instant,
+}
+
+static HISTORY: once_cell::sync::Lazy<Mutex<HashMap<u32, SampleBuffer>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Adds a sample for a process, unless the previous one is too recent.
+/// Called by every process listing, so history builds up while the process
+/// list is open.
+pub fn record(pid: u32, cpu_usage: f64, memory_bytes: u64) {
+    let Ok(mut history) = HISTORY.lock() else {
+        return;
+    };
+    let now = Instant::now();
+
+    let sample = ProcessSample {
+        timestamp: events::now_millis(),
+        cpu_usage,
+        memory_bytes,
+    };
+    match history.get_mut(&pid) {
+        Some(buffer) if now.duration_since(buffer.last_sample) < SAMPLE_INTERVAL => {}
+        Some(buffer) => {
+            push_sample(&mut buffer.samples, sample);
+            buffer.last_sample = now;
+        }
+        None => {
+            let mut samples = VecDeque::with_capacity(HISTORY_LEN);
+            samples.push_back(sample);
+            history.insert(
+                pid,
+                SampleBuffer {
+                    samples,
+                    last_sample: now,
+                },
+            );
+        }
+    }
+}
+
+/// Samples recorded for a process, empty when it was never listed
+pub fn get_history(pid: u32) -> ProcessHistory {
+    let samples = HISTORY
+        .lock()
+        .ok()
+        .and_then(|history| {
+            history
+                .get(&pid)
+                .map(|buffer| buffer.samples.iter().cloned().collect())
+        })
+        .unwrap_or_default();
+
+    ProcessHistory {
+        pid,
+        interval_ms: SAMPLE_INTERVAL.as_millis() as u64,
+        samples,
+    }
+}
+
+/// Drops the history of processes that no longer exist, so a recycled PID
+/// starts from scratch
+pub fn prune_samples(live_pids: &HashSet<u32>) {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.retain(|pid, _| live_pids.contains(pid));
+    }
+}
+
+fn push_sample(samples: &mut VecDeque<ProcessSample>, sample: ProcessSample) {
+    if samples.len() == HISTORY_LEN {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_sample_keeps_latest() {
+        let mut samples = VecDeque::new();
+        for i in 0..HISTORY_LEN as u64 + 5 {
+            push_sample(
+                &mut samples,
+                ProcessSample {
+                    timestamp: i,
+                    cpu_usage: 0.0,
+                    memory_bytes: 0,
+                },
+            );
+        }
+        assert_eq!(samples.len(), HISTORY_LEN);
+        assert_eq!(samples.front().map(|s| s.timestamp), Some(5));
+        assert_eq!(
+            samples.back().map(|s| s.timestamp),
+            Some(HISTORY_LEN as u64 + 4)
+        );
+    }
+}
@@ -1,13 +1,33 @@
+use crate::shared::i18n;
+#[cfg(target_os = "windows")]
+use ntapi::ntpebteb::PEB;
+#[cfg(target_os = "windows")]
 use ntapi::ntpsapi::{
     NtQueryInformationProcess, ProcessBasicInformation, PROCESS_BASIC_INFORMATION,
 };
+#[cfg(target_os = "windows")]
+use ntapi::ntrtl::RTL_USER_PROCESS_PARAMETERS;
+#[cfg(target_os = "windows")]
 use ntapi::winapi::ctypes::c_void;
 use std::collections::HashMap;
 use std::io::{self, Error, ErrorKind};
 use sysinfo::Pid;
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+#[cfg(target_os = "windows")]
 use windows::Win32::System::Diagnostics::Debug::ReadProcessMemory;
+#[cfg(target_os = "windows")]
 use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
 
+// Upper bound for the environment block, guards against corrupted sizes
+#[cfg(target_os = "windows")]
+const MAX_ENVIRONMENT_SIZE: usize = 1024 * 1024;
+
+/// Reads the environment of another process.
+///
+/// The environment is not stored in the PEB itself: the PEB points to the
+/// RTL_USER_PROCESS_PARAMETERS block, whose `Environment` field points to a
+/// UTF-16 block of `NAME=value\0` entries terminated by an empty entry.
 #[cfg(target_os = "windows")]
 pub fn get_env_vars(pid: Pid) -> io::Result<HashMap<String, String>> {
     unsafe {
@@ -17,75 +37,140 @@ pub fn get_env_vars(pid: Pid) -> io::Result<HashMap<String, String>> {
             pid.as_u32(),
         )?;
 
-        let handle_ptr = handle.0 as *mut c_void;
-
-        let mut pbi: PROCESS_BASIC_INFORMATION = std::mem::zeroed();
-        let status = NtQueryInformationProcess(
-            handle_ptr,
-            ProcessBasicInformation,
-            &mut pbi as *mut _ as *mut _,
-            std::mem::size_of::<PROCESS_BASIC_INFORMATION>() as u32,
-            std::ptr::null_mut(),
-        );
-
-        if status != 0 {
-            return Err(Error::new(
-                ErrorKind::Other,
-                "Impossibile leggere le informazioni del processo",
-            ));
+        let result = read_environment_block(handle);
+        let _ = CloseHandle(handle);
+
+        Ok(parse_environment_block(&result?))
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn read_environment_block(handle: HANDLE) -> io::Result<Vec<u16>> {
+    let mut pbi: PROCESS_BASIC_INFORMATION = std::mem::zeroed();
+    let status = NtQueryInformationProcess(
+        handle.0 as *mut c_void,
+        ProcessBasicInformation,
+        &mut pbi as *mut _ as *mut _,
+        std::mem::size_of::<PROCESS_BASIC_INFORMATION>() as u32,
+        std::ptr::null_mut(),
+    );
+
+    if status != 0 || pbi.PebBaseAddress.is_null() {
+        return Err(Error::other(i18n::text(
+            "error.process_info_unavailable",
+            "Unable to read the process information",
+        )));
+    }
+
+    let peb: PEB = read_struct(handle, pbi.PebBaseAddress as *const _)?;
+    if peb.ProcessParameters.is_null() {
+        return Err(Error::other(i18n::text(
+            "error.process_no_parameters",
+            "The process has no user parameters",
+        )));
+    }
+
+    let params: RTL_USER_PROCESS_PARAMETERS =
+        read_struct(handle, peb.ProcessParameters as *const _)?;
+    if params.Environment.is_null() {
+        return Ok(Vec::new());
+    }
+
+    let size = (params.EnvironmentSize as usize).min(MAX_ENVIRONMENT_SIZE);
+    let mut buffer = vec![0u16; size / 2];
+    let mut bytes_read: usize = 0;
+
+    ReadProcessMemory(
+        handle,
+        params.Environment as *const _,
+        buffer.as_mut_ptr() as *mut _,
+        buffer.len() * 2,
+        Some(&mut bytes_read),
+    )
+    .map_err(|_| {
+        Error::other(i18n::text(
+            "error.process_memory_unreadable",
+            "Unable to read the process memory",
+        ))
+    })?;
+
+    buffer.truncate(bytes_read / 2);
+    Ok(buffer)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn read_struct<T>(handle: HANDLE, address: *const std::ffi::c_void) -> io::Result<T> {
+    let mut value: T = std::mem::zeroed();
+    let mut bytes_read: usize = 0;
+
+    ReadProcessMemory(
+        handle,
+        address,
+        &mut value as *mut T as *mut _,
+        std::mem::size_of::<T>(),
+        Some(&mut bytes_read),
+    )
+    .map_err(|_| {
+        Error::other(i18n::text(
+            "error.process_memory_unreadable",
+            "Unable to read the process memory",
+        ))
+    })?;
+
+    if bytes_read != std::mem::size_of::<T>() {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            i18n::text("error.partial_read", "Incomplete read"),
+        ));
+    }
+
+    Ok(value)
+}
+
+/// Parses a UTF-16 environment block (`NAME=value\0...\0\0`)
+#[cfg(target_os = "windows")]
+fn parse_environment_block(block: &[u16]) -> HashMap<String, String> {
+    let mut env_vars = HashMap::new();
+
+    for entry in block.split(|c| *c == 0) {
+        // An empty entry marks the end of the block
+        if entry.is_empty() {
+            break;
         }
 
-        let mut env_vars = HashMap::new();
-        let mut buffer = vec![0u16; 4096];
-        let mut bytes_read: usize = 0;
-
-        let result = ReadProcessMemory(
-            handle,
-            pbi.PebBaseAddress as *const _,
-            buffer.as_mut_ptr() as *mut _,
-            buffer.len(),
-            Some(&mut bytes_read as *mut usize),
-        );
-
-        if result.is_ok() {
-            // Parsing delle variabili d'ambiente dalla memoria
-            let env_block = String::from_utf16_lossy(&buffer[..bytes_read / 2]);
-            for line in env_block.split('\0') {
-                if let Some((key, value)) = line.split_once('=') {
-                    env_vars.insert(key.to_string(), value.to_string());
-                }
-            }
-            Ok(env_vars)
-        } else {
-            Err(Error::new(
-                ErrorKind::Other,
-                "Impossibile leggere la memoria del processo",
-            ))
+        let entry = String::from_utf16_lossy(entry);
+        // Per-drive cwd entries like "=C:=C:\dir" start with '=', so the
+        // separator is searched from the second character
+        if let Some((index, _)) = entry.char_indices().skip(1).find(|(_, c)| *c == '=') {
+            env_vars.insert(entry[..index].to_string(), entry[index + 1..].to_string());
         }
     }
+
+    env_vars
 }
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 pub fn get_env_vars(pid: Pid) -> io::Result<HashMap<String, String>> {
-    let output = if cfg!(target_os = "linux") {
-        Command::new("cat")
-            .arg(format!("/proc/{}/environ", pid.as_u32()))
-            .output()?
+    use std::process::Command;
+
+    let contents = if cfg!(target_os = "linux") {
+        std::fs::read(format!("/proc/{}/environ", pid.as_u32()))?
     } else {
-        Command::new("ps")
+        let output = Command::new("ps")
             .arg("eww")
             .arg(pid.as_u32().to_string())
-            .output()?
-    };
+            .output()?;
 
-    if !output.status.success() {
-        return Err(Error::new(
-            ErrorKind::Other,
-            "Impossibile ottenere le variabili di ambiente",
-        ));
-    }
+        if !output.status.success() {
+            return Err(Error::other(i18n::text(
+                "error.env_vars_unavailable",
+                "Unable to get the environment variables",
+            )));
+        }
+        output.stdout
+    };
 
-    let contents = String::from_utf8_lossy(&output.stdout);
+    let contents = String::from_utf8_lossy(&contents);
     let env_vars = contents
         .split('\0')
         .filter(|s| !s.is_empty())
@@ -101,3 +186,20 @@ pub fn get_env_vars(pid: Pid) -> io::Result<HashMap<String, String>> {
 
     Ok(env_vars)
 }
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_environment_block() {
+        let raw = "=C:=C:\\Games\0PATH=C:\\Windows\0DXVK_HUD=fps\0\0garbage";
+        let block: Vec<u16> = raw.encode_utf16().collect();
+        let env_vars = parse_environment_block(&block);
+
+        assert_eq!(env_vars.len(), 3);
+        assert_eq!(env_vars.get("=C:").map(String::as_str), Some("C:\\Games"));
+        assert_eq!(env_vars.get("DXVK_HUD").map(String::as_str), Some("fps"));
+        assert!(!env_vars.contains_key("garbage"));
+    }
+}
@@ -0,0 +1,756 @@
+// One-click diagnostics for a process: details, recent CPU and memory use,
+// network connections, loaded modules and environment bundled into a report
+// users can paste into a support thread, as markdown for forums and chats or
+// as JSON for issue trackers. Values of variables that look like credentials
+// are left out of the environment.
+use crate::services::process_history::{self, ProcessSample};
+use crate::services::{os_version, process_control, process_security, process_service};
+use crate::shared::events;
+use crate::utils::{format_bytes, format_run_time};
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use std::sync::Arc;
+
+#[cfg(target_os = "windows")]
+use std::ffi::c_void;
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::ERROR_INSUFFICIENT_BUFFER;
+#[cfg(target_os = "windows")]
+use windows::Win32::NetworkManagement::IpHelper::{
+    GetExtendedTcpTable, GetExtendedUdpTable, MIB_TCP6ROW_OWNER_PID, MIB_TCPROW_OWNER_PID,
+    MIB_UDP6ROW_OWNER_PID, MIB_UDPROW_OWNER_PID, TCP_TABLE_OWNER_PID_ALL, UDP_TABLE_OWNER_PID,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+#[cfg(not(target_os = "windows"))]
+use std::collections::HashSet;
+
+// Variables whose names contain one of these keep their name only
+const SECRET_MARKERS: [&str; 9] = [
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "KEY",
+    "AUTH",
+    "CREDENTIAL",
+    "COOKIE",
+    "SESSION",
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProcessReport {
+    pub generated_at: u64, // Unix millis
+    pub aura_version: String,
+    pub platform: String,
+    pub process: ProcessSummary,
+    pub history: Option<HistorySummary>, // None before the first samples
+    pub connections: Vec<Connection>,
+    pub modules: Vec<String>,
+    pub environment: Option<EnvSummary>, // None when the process can't be read
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProcessSummary {
+    pub pid: u32,
+    pub parent_pid: u32,
+    pub name: String,
+    pub exe_path: String,
+    pub user: Option<String>,
+    pub run_time_secs: Option<u64>,
+    pub cpu_usage_percent: f64,
+    pub memory_working_set: u64,
+    pub memory_private: u64,
+    pub handle_count: u32,
+    pub thread_count: u32,
+    pub is_suspended: bool,
+    pub is_hung: bool,
+    pub io_read_bytes: u64,
+    pub io_write_bytes: u64,
+    pub page_fault_rate: u64, // faults/sec
+    pub hard_fault_rate: u64, // faults/sec
+}
+
+/// CPU and memory over the samples of the last minute
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct HistorySummary {
+    pub sample_count: usize,
+    pub span_secs: u64,
+    pub cpu_average: f64,
+    pub cpu_peak: f64,
+    pub memory_min_bytes: u64,
+    pub memory_max_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Connection {
+    pub protocol: String, // TCP or UDP
+    pub local: String,
+    pub remote: Option<String>, // None for listening TCP and for UDP
+    pub state: Option<String>,  // TCP only
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct EnvSummary {
+    pub count: usize,
+    pub hidden: usize,          // variables whose value was left out
+    pub variables: Vec<String>, // NAME=value
+}
+
+/// The same report in both formats, ready for the clipboard
+#[derive(Debug, Serialize, Clone)]
+pub struct ProcessReportSnippet {
+    pub markdown: String,
+    pub json: String,
+}
+
+pub fn build(pid: u32) -> Result<ProcessReport> {
+    let info = process_control::get_process_detailed_info(pid)?;
+    let user = process_security::get_process_user(pid, info.create_time);
+    let platform = os_version::get_platform_info();
+    let platform = [
+        Some(platform.os),
+        Some(platform.version),
+        platform.display_version,
+        platform.build.map(|build| format!("(build {})", build)),
+        Some(platform.arch),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    let environment = process_service::env_vars(Arc::new(sysinfo::Pid::from(pid as usize)))
+        .ok()
+        .map(|variables| summarize_env(&variables));
+
+    Ok(ProcessReport {
+        generated_at: events::now_millis(),
+        aura_version: env!("CARGO_PKG_VERSION").to_string(),
+        platform,
+        process: ProcessSummary {
+            pid: info.pid,
+            parent_pid: info.parent_pid,
+            name: info.name,
+            exe_path: info.exe_path,
+            user,
+            run_time_secs: run_time_secs(info.create_time, events::now_millis() / 1000),
+            cpu_usage_percent: info.cpu_usage_percent,
+            memory_working_set: info.memory_working_set,
+            memory_private: info.memory_private,
+            handle_count: info.handle_count,
+            thread_count: info.thread_count,
+            is_suspended: info.is_suspended,
+            is_hung: info.is_hung,
+            io_read_bytes: info.io_read_bytes,
+            io_write_bytes: info.io_write_bytes,
+            page_fault_rate: info.page_fault_rate,
+            hard_fault_rate: info.hard_fault_rate,
+        },
+        history: summarize_history(&process_history::get_history(pid).samples),
+        connections: connections(pid),
+        modules: modules(pid),
+        environment,
+    })
+}
+
+pub fn snippet(report: &ProcessReport) -> Result<ProcessReportSnippet> {
+    Ok(ProcessReportSnippet {
+        markdown: to_markdown(report),
+        json: serde_json::to_string_pretty(report)?,
+    })
+}
+
+/// Seconds since a FILETIME start time, None when it is unknown
+fn run_time_secs(create_time: i64, now_secs: u64) -> Option<u64> {
+    (create_time > 0).then(|| {
+        let started = create_time / 10_000_000 - 11644473600;
+        (now_secs as i64 - started).max(0) as u64
+    })
+}
+
+fn summarize_history(samples: &[ProcessSample]) -> Option<HistorySummary> {
+    let (first, last) = (samples.first()?, samples.last()?);
+    let count = samples.len();
+    Some(HistorySummary {
+        sample_count: count,
+        span_secs: last.timestamp.saturating_sub(first.timestamp) / 1000,
+        cpu_average: samples.iter().map(|s| s.cpu_usage).sum::<f64>() / count as f64,
+        cpu_peak: samples.iter().map(|s| s.cpu_usage).fold(0.0, f64::max),
+        memory_min_bytes: samples.iter().map(|s| s.memory_bytes).min()?,
+        memory_max_bytes: samples.iter().map(|s| s.memory_bytes).max()?,
+    })
+}
+
+fn summarize_env(variables: &[String]) -> EnvSummary {
+    let mut hidden = 0;
+    let variables: Vec<String> = variables
+        .iter()
+        .map(|variable| {
+            let name = variable
+                .split_once('=')
+                .map_or(variable.as_str(), |(name, _)| name);
+            let upper = name.to_uppercase();
+            if SECRET_MARKERS.iter().any(|marker| upper.contains(marker)) {
+                hidden += 1;
+                format!("{}=<hidden>", name)
+            } else {
+                variable.clone()
+            }
+        })
+        .collect();
+    EnvSummary {
+        count: variables.len(),
+        hidden,
+        variables,
+    }
+}
+
+fn to_markdown(report: &ProcessReport) -> String {
+    let process = &report.process;
+    let mut out = String::new();
+    let _ = writeln!(out, "### {} (PID {})", process.name, process.pid);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "- **Path:** `{}`", process.exe_path);
+    let _ = writeln!(out, "- **Parent PID:** {}", process.parent_pid);
+    if let Some(user) = &process.user {
+        let _ = writeln!(out, "- **User:** {}", user);
+    }
+    if let Some(secs) = process.run_time_secs {
+        let _ = writeln!(out, "- **Running for:** {}", format_run_time(secs));
+    }
+    let status = if process.is_suspended {
+        "Suspended"
+    } else if process.is_hung {
+        "Not responding"
+    } else {
+        "Running"
+    };
+    let _ = writeln!(out, "- **Status:** {}", status);
+    let _ = writeln!(out, "- **CPU:** {:.1}%", process.cpu_usage_percent);
+    let _ = writeln!(
+        out,
+        "- **Memory:** {} working set, {} private",
+        format_bytes(process.memory_working_set),
+        format_bytes(process.memory_private)
+    );
+    let _ = writeln!(
+        out,
+        "- **Handles / threads:** {} / {}",
+        process.handle_count, process.thread_count
+    );
+    let _ = writeln!(
+        out,
+        "- **Disk I/O:** {} read, {} written",
+        format_bytes(process.io_read_bytes),
+        format_bytes(process.io_write_bytes)
+    );
+    let _ = writeln!(
+        out,
+        "- **Page faults:** {}/s ({}/s hard)",
+        process.page_fault_rate, process.hard_fault_rate
+    );
+    if let Some(history) = &report.history {
+        let _ = writeln!(
+            out,
+            "- **Last {}s:** CPU {:.1}% average, {:.1}% peak; memory {} to {}",
+            history.span_secs,
+            history.cpu_average,
+            history.cpu_peak,
+            format_bytes(history.memory_min_bytes),
+            format_bytes(history.memory_max_bytes)
+        );
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "**Connections ({})**", report.connections.len());
+    if !report.connections.is_empty() {
+        let lines: Vec<String> = report
+            .connections
+            .iter()
+            .map(|connection| {
+                let mut line = format!("{} {}", connection.protocol, connection.local);
+                if let Some(remote) = &connection.remote {
+                    let _ = write!(line, " -> {}", remote);
+                }
+                if let Some(state) = &connection.state {
+                    let _ = write!(line, " {}", state);
+                }
+                line
+            })
+            .collect();
+        write_block(&mut out, &lines);
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "<details><summary>Modules ({})</summary>",
+        report.modules.len()
+    );
+    let _ = writeln!(out);
+    write_block(&mut out, &report.modules);
+    let _ = writeln!(out, "</details>");
+
+    if let Some(environment) = &report.environment {
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "<details><summary>Environment ({} variables, {} hidden)</summary>",
+            environment.count, environment.hidden
+        );
+        let _ = writeln!(out);
+        write_block(&mut out, &environment.variables);
+        let _ = writeln!(out, "</details>");
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "_Aura {} on {}_", report.aura_version, report.platform);
+    out
+}
+
+fn write_block(out: &mut String, lines: &[String]) {
+    let _ = writeln!(out, "```");
+    for line in lines {
+        let _ = writeln!(out, "{}", line);
+    }
+    let _ = writeln!(out, "```");
+}
+
+#[cfg(target_os = "windows")]
+fn modules(pid: u32) -> Vec<String> {
+    let mut modules = crate::utils::loaded_module::get_loaded_modules(pid);
+    modules.sort();
+    modules.dedup();
+    modules
+}
+
+/// Shared libraries mapped into the process
+#[cfg(not(target_os = "windows"))]
+fn modules(pid: u32) -> Vec<String> {
+    std::fs::read_to_string(format!("/proc/{}/maps", pid))
+        .map(|maps| parse_maps_modules(&maps))
+        .unwrap_or_default()
+}
+
+// Names of the MIB_TCP_STATE values, starting at 1
+#[cfg(target_os = "windows")]
+const TCP_STATES: [&str; 12] = [
+    "CLOSED",
+    "LISTEN",
+    "SYN_SENT",
+    "SYN_RECEIVED",
+    "ESTABLISHED",
+    "FIN_WAIT1",
+    "FIN_WAIT2",
+    "CLOSE_WAIT",
+    "CLOSING",
+    "LAST_ACK",
+    "TIME_WAIT",
+    "DELETE_TCB",
+];
+
+#[cfg(target_os = "windows")]
+fn connections(pid: u32) -> Vec<Connection> {
+    let mut connections = Vec::new();
+    let tcp_state = |state: u32| {
+        TCP_STATES
+            .get((state as usize).wrapping_sub(1))
+            .map(|name| name.to_string())
+    };
+
+    let tcp4 = owner_table(|table, size| unsafe {
+        GetExtendedTcpTable(
+            table,
+            size,
+            true,
+            AF_INET.0 as u32,
+            TCP_TABLE_OWNER_PID_ALL,
+            0,
+        )
+    });
+    for row in table_rows::<MIB_TCPROW_OWNER_PID>(&tcp4) {
+        if row.dwOwningPid == pid {
+            connections.push(Connection {
+                protocol: "TCP".to_string(),
+                local: socket_v4(row.dwLocalAddr, row.dwLocalPort),
+                remote: (row.dwState != 2).then(|| socket_v4(row.dwRemoteAddr, row.dwRemotePort)),
+                state: tcp_state(row.dwState),
+            });
+        }
+    }
+    let tcp6 = owner_table(|table, size| unsafe {
+        GetExtendedTcpTable(
+            table,
+            size,
+            true,
+            AF_INET6.0 as u32,
+            TCP_TABLE_OWNER_PID_ALL,
+            0,
+        )
+    });
+    for row in table_rows::<MIB_TCP6ROW_OWNER_PID>(&tcp6) {
+        if row.dwOwningPid == pid {
+            connections.push(Connection {
+                protocol: "TCP".to_string(),
+                local: socket_v6(row.ucLocalAddr, row.dwLocalPort),
+                remote: (row.dwState != 2).then(|| socket_v6(row.ucRemoteAddr, row.dwRemotePort)),
+                state: tcp_state(row.dwState),
+            });
+        }
+    }
+
+    let udp4 = owner_table(|table, size| unsafe {
+        GetExtendedUdpTable(table, size, true, AF_INET.0 as u32, UDP_TABLE_OWNER_PID, 0)
+    });
+    for row in table_rows::<MIB_UDPROW_OWNER_PID>(&udp4) {
+        if row.dwOwningPid == pid {
+            connections.push(Connection {
+                protocol: "UDP".to_string(),
+                local: socket_v4(row.dwLocalAddr, row.dwLocalPort),
+                remote: None,
+                state: None,
+            });
+        }
+    }
+    let udp6 = owner_table(|table, size| unsafe {
+        GetExtendedUdpTable(table, size, true, AF_INET6.0 as u32, UDP_TABLE_OWNER_PID, 0)
+    });
+    for row in table_rows::<MIB_UDP6ROW_OWNER_PID>(&udp6) {
+        if row.dwOwningPid == pid {
+            connections.push(Connection {
+                protocol: "UDP".to_string(),
+                local: socket_v6(row.ucLocalAddr, row.dwLocalPort),
+                remote: None,
+                state: None,
+            });
+        }
+    }
+    connections
+}
+
+/// Fetches one of the owner PID tables, asking for its size first. The
+/// buffer is made of u32 so the rows are aligned.
+#[cfg(target_os = "windows")]
+fn owner_table(fetch: impl Fn(Option<*mut c_void>, *mut u32) -> u32) -> Vec<u32> {
+    let mut size = 0u32;
+    fetch(None, &mut size);
+    // The table can grow between the two calls
+    for _ in 0..3 {
+        let mut buffer = vec![0u32; (size as usize).div_ceil(4).max(1)];
+        match fetch(Some(buffer.as_mut_ptr() as *mut c_void), &mut size) {
+            0 => return buffer,
+            code if code == ERROR_INSUFFICIENT_BUFFER.0 => continue,
+            _ => break,
+        }
+    }
+    Vec::new()
+}
+
+/// The rows after the dwNumEntries header of a table
+#[cfg(target_os = "windows")]
+fn table_rows<T: Copy>(buffer: &[u32]) -> Vec<T> {
+    let Some(count) = buffer.first().map(|count| *count as usize) else {
+        return Vec::new();
+    };
+    let available = (buffer.len() - 1) * 4 / std::mem::size_of::<T>();
+    let rows = unsafe {
+        std::slice::from_raw_parts(buffer.as_ptr().add(1) as *const T, count.min(available))
+    };
+    rows.to_vec()
+}
+
+/// Addresses and ports in the tables are in network byte order
+#[cfg(target_os = "windows")]
+fn socket_v4(address: u32, port: u32) -> String {
+    SocketAddrV4::new(
+        Ipv4Addr::from(address.to_ne_bytes()),
+        u16::from_be(port as u16),
+    )
+    .to_string()
+}
+
+#[cfg(target_os = "windows")]
+fn socket_v6(address: [u8; 16], port: u32) -> String {
+    SocketAddrV6::new(Ipv6Addr::from(address), u16::from_be(port as u16), 0, 0).to_string()
+}
+
+// Names of the kernel TCP states, starting at 1
+#[cfg(not(target_os = "windows"))]
+const TCP_STATES: [&str; 11] = [
+    "ESTABLISHED",
+    "SYN_SENT",
+    "SYN_RECEIVED",
+    "FIN_WAIT1",
+    "FIN_WAIT2",
+    "TIME_WAIT",
+    "CLOSED",
+    "CLOSE_WAIT",
+    "LAST_ACK",
+    "LISTEN",
+    "CLOSING",
+];
+
+/// The sockets among the open files of the process, looked up in the tables
+/// of its network namespace
+#[cfg(not(target_os = "windows"))]
+fn connections(pid: u32) -> Vec<Connection> {
+    let inodes: HashSet<u64> = std::fs::read_dir(format!("/proc/{}/fd", pid))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+                .filter_map(|link| {
+                    link.to_str()?
+                        .strip_prefix("socket:[")?
+                        .strip_suffix(']')?
+                        .parse()
+                        .ok()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if inodes.is_empty() {
+        return Vec::new();
+    }
+
+    [
+        ("tcp", "TCP"),
+        ("tcp6", "TCP"),
+        ("udp", "UDP"),
+        ("udp6", "UDP"),
+    ]
+    .iter()
+    .filter_map(|(file, protocol)| {
+        let table = std::fs::read_to_string(format!("/proc/{}/net/{}", pid, file)).ok()?;
+        Some(parse_proc_net(&table, protocol, &inodes))
+    })
+    .flatten()
+    .collect()
+}
+
+/// Rows of /proc/net/{tcp,udp}[6] owned by one of `inodes`
+#[cfg(not(target_os = "windows"))]
+fn parse_proc_net(table: &str, protocol: &str, inodes: &HashSet<u64>) -> Vec<Connection> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let inode: u64 = fields.get(9)?.parse().ok()?;
+            if !inodes.contains(&inode) {
+                return None;
+            }
+            let local = parse_proc_socket(fields.get(1)?)?;
+            let state = usize::from_str_radix(fields.get(3)?, 16).ok()?;
+            let is_tcp = protocol == "TCP";
+            // 0A is LISTEN
+            let remote = if is_tcp && state != 0x0A {
+                Some(parse_proc_socket(fields.get(2)?)?)
+            } else {
+                None
+            };
+            Some(Connection {
+                protocol: protocol.to_string(),
+                local,
+                remote,
+                state: is_tcp
+                    .then(|| TCP_STATES.get(state.wrapping_sub(1)))
+                    .flatten()
+                    .map(|name| name.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// An address like 0100007F:1F90, each 32 bit word of the IP is printed in
+/// host byte order and the port as a plain number
+#[cfg(not(target_os = "windows"))]
+fn parse_proc_socket(value: &str) -> Option<String> {
+    let (address, port) = value.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for word in 0..address.len() / 8 {
+        let word = u32::from_str_radix(address.get(word * 8..word * 8 + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    match bytes.len() {
+        4 => Some(
+            SocketAddrV4::new(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]), port)
+                .to_string(),
+        ),
+        16 => {
+            let bytes: [u8; 16] = bytes.try_into().ok()?;
+            Some(SocketAddrV6::new(Ipv6Addr::from(bytes), port, 0, 0).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// File names of the shared libraries in a /proc/<pid>/maps listing
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn parse_maps_modules(maps: &str) -> Vec<String> {
+    let mut modules: Vec<String> = maps
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(5))
+        .filter(|path| path.starts_with('/') && (path.ends_with(".so") || path.contains(".so.")))
+        .filter_map(|path| path.rsplit('/').next())
+        .map(|name| name.to_string())
+        .collect();
+    modules.sort();
+    modules.dedup();
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64, cpu_usage: f64, memory_bytes: u64) -> ProcessSample {
+        ProcessSample {
+            timestamp,
+            cpu_usage,
+            memory_bytes,
+        }
+    }
+
+    #[test]
+    fn test_summarize_history() {
+        assert_eq!(summarize_history(&[]), None);
+        let summary = summarize_history(&[
+            sample(10_000, 2.0, 300),
+            sample(11_000, 10.0, 100),
+            sample(40_000, 6.0, 200),
+        ])
+        .unwrap();
+        assert_eq!(summary.sample_count, 3);
+        assert_eq!(summary.span_secs, 30);
+        assert!((summary.cpu_average - 6.0).abs() < 1e-9);
+        assert_eq!(summary.cpu_peak, 10.0);
+        assert_eq!(
+            (summary.memory_min_bytes, summary.memory_max_bytes),
+            (100, 300)
+        );
+    }
+
+    #[test]
+    fn test_env_secrets_hidden() {
+        let summary = summarize_env(&[
+            "PATH=C:\\Windows".to_string(),
+            "GITHUB_TOKEN=ghp_abc".to_string(),
+            "Steam_Api_Key=123".to_string(),
+            "DXVK_HUD=fps".to_string(),
+        ]);
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.hidden, 2);
+        assert_eq!(
+            summary.variables,
+            vec![
+                "PATH=C:\\Windows",
+                "GITHUB_TOKEN=<hidden>",
+                "Steam_Api_Key=<hidden>",
+                "DXVK_HUD=fps"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_time_secs() {
+        // 2024-01-01 00:00:00 UTC as FILETIME
+        let started = (1_704_067_200 + 11_644_473_600) * 10_000_000;
+        assert_eq!(run_time_secs(started, 1_704_067_200 + 90), Some(90));
+        assert_eq!(run_time_secs(0, 1_704_067_200), None);
+    }
+
+    #[test]
+    fn test_parse_maps_modules() {
+        let maps = "\
+55d0c0000000-55d0c0021000 r--p 00000000 103:02 1311 /usr/bin/game
+7f1c00000000-7f1c00028000 r--p 00000000 103:02 2001 /usr/lib/libc.so.6
+7f1c00028000-7f1c001bd000 r-xp 00028000 103:02 2001 /usr/lib/libc.so.6
+7f1c10000000-7f1c10010000 r-xp 00000000 103:02 2002 /opt/game/libsteam_api.so
+7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0 [stack]
+7f1c20000000-7f1c20001000 rw-p 00000000 00:00 0
+";
+        assert_eq!(
+            parse_maps_modules(maps),
+            vec!["libc.so.6", "libsteam_api.so"]
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        let report = ProcessReport {
+            generated_at: 0,
+            aura_version: "1.0.0".to_string(),
+            platform: "Windows 11 23H2 x64".to_string(),
+            process: ProcessSummary {
+                pid: 4242,
+                parent_pid: 1,
+                name: "game.exe".to_string(),
+                exe_path: "D:\\Games\\game.exe".to_string(),
+                user: None,
+                run_time_secs: Some(3600),
+                cpu_usage_percent: 12.34,
+                memory_working_set: 1024,
+                memory_private: 0,
+                handle_count: 800,
+                thread_count: 40,
+                is_suspended: false,
+                is_hung: true,
+                io_read_bytes: 0,
+                io_write_bytes: 0,
+                page_fault_rate: 10,
+                hard_fault_rate: 1,
+            },
+            history: None,
+            connections: vec![Connection {
+                protocol: "TCP".to_string(),
+                local: "10.0.0.2:50000".to_string(),
+                remote: Some("1.2.3.4:443".to_string()),
+                state: Some("ESTABLISHED".to_string()),
+            }],
+            modules: vec!["kernel32.dll".to_string()],
+            environment: None,
+        };
+        let markdown = to_markdown(&report);
+        assert!(markdown.starts_with("### game.exe (PID 4242)\n"));
+        assert!(markdown.contains("- **Status:** Not responding\n"));
+        assert!(markdown.contains("- **CPU:** 12.3%\n"));
+        assert!(markdown.contains("TCP 10.0.0.2:50000 -> 1.2.3.4:443 ESTABLISHED\n"));
+        assert!(
+            markdown.contains("<details><summary>Modules (1)</summary>\n\n```\nkernel32.dll\n```")
+        );
+        assert!(!markdown.contains("Environment"));
+        assert!(markdown.ends_with("_Aura 1.0.0 on Windows 11 23H2 x64_\n"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_parse_proc_net() {
+        let table = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 111 1 0000000000000000 100 0 0 10 0
+   1: 0200000A:C350 04030201:01BB 01 00000000:00000000 00:00000000 00000000  1000        0 222 1 0000000000000000 20 4 30 10 -1
+   2: 0200000A:C351 04030201:01BB 01 00000000:00000000 00:00000000 00000000  1000        0 333 1 0000000000000000 20 4 30 10 -1
+";
+        let inodes: HashSet<u64> = [111, 222].into_iter().collect();
+        let connections = parse_proc_net(table, "TCP", &inodes);
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[0].local, "127.0.0.1:8080");
+        assert_eq!(connections[0].remote, None);
+        assert_eq!(connections[0].state.as_deref(), Some("LISTEN"));
+        assert_eq!(connections[1].local, "10.0.0.2:50000");
+        assert_eq!(connections[1].remote.as_deref(), Some("1.2.3.4:443"));
+        assert_eq!(connections[1].state.as_deref(), Some("ESTABLISHED"));
+
+        assert_eq!(
+            parse_proc_socket("00000000000000000000000001000000:0050").as_deref(),
+            Some("[::1]:80")
+        );
+    }
+}
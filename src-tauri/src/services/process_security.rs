@@ -0,0 +1,328 @@
+use crate::services::process_control::ProcessControlError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use windows::core::{PCWSTR, PWSTR};
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL, LUID};
+#[cfg(target_os = "windows")]
+use windows::Win32::Security::Authorization::ConvertStringSidToSidW;
+#[cfg(target_os = "windows")]
+use windows::Win32::Security::{
+    GetSidSubAuthority, GetSidSubAuthorityCount, GetTokenInformation, LookupAccountSidW,
+    LookupPrivilegeNameW, TokenElevation, TokenIntegrityLevel, TokenPrivileges, TokenUser, PSID,
+    SE_PRIVILEGE_ENABLED, SID_NAME_USE, TOKEN_ELEVATION, TOKEN_INFORMATION_CLASS,
+    TOKEN_MANDATORY_LABEL, TOKEN_PRIVILEGES, TOKEN_QUERY, TOKEN_USER,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{
+    OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+
+type Result<T> = std::result::Result<T, ProcessControlError>;
+
+// Mandatory integrity level RIDs (winnt.h)
+const SECURITY_MANDATORY_LOW_RID: u32 = 0x1000;
+const SECURITY_MANDATORY_MEDIUM_RID: u32 = 0x2000;
+const SECURITY_MANDATORY_HIGH_RID: u32 = 0x3000;
+const SECURITY_MANDATORY_SYSTEM_RID: u32 = 0x4000;
+const SECURITY_MANDATORY_PROTECTED_PROCESS_RID: u32 = 0x5000;
+
+// Bound for the caches below, they are simply cleared when full
+const MAX_CACHE_ENTRIES: usize = 4096;
+
+// Owner per (pid, create time), so a recycled PID is resolved again
+type ProcessUserCache = HashMap<(u32, i64), Option<String>>;
+
+static PROCESS_USER_CACHE: once_cell::sync::Lazy<Mutex<ProcessUserCache>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Account name per SID / uid string; LookupAccountSid can hit the domain controller
+static ACCOUNT_NAME_CACHE: once_cell::sync::Lazy<Mutex<HashMap<String, String>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrivilegeInfo {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// Security context of a process, taken from its access token
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProcessSecurityInfo {
+    pub user: Option<String>, // DOMAIN\user
+    pub integrity_level: String,
+    pub is_elevated: bool,
+    pub privileges: Vec<PrivilegeInfo>,
+}
+
+/// Maps an integrity level RID to its display name
+pub fn integrity_level_name(rid: u32) -> &'static str {
+    match rid {
+        r if r >= SECURITY_MANDATORY_PROTECTED_PROCESS_RID => "Protected",
+        r if r >= SECURITY_MANDATORY_SYSTEM_RID => "System",
+        r if r >= SECURITY_MANDATORY_HIGH_RID => "High",
+        r if r >= SECURITY_MANDATORY_MEDIUM_RID + 0x100 => "Medium Plus",
+        r if r >= SECURITY_MANDATORY_MEDIUM_RID => "Medium",
+        r if r >= SECURITY_MANDATORY_LOW_RID => "Low",
+        _ => "Untrusted",
+    }
+}
+
+/// Reads the owner, integrity level, elevation and privileges of a process.
+/// Protected and system processes may deny token access.
+pub fn get_process_security_info(pid: u32) -> Result<ProcessSecurityInfo> {
+    #[cfg(target_os = "windows")]
+    unsafe {
+        let token = open_process_token(pid)?;
+
+        let info = ProcessSecurityInfo {
+            user: query_token_user(token),
+            integrity_level: query_integrity_rid(token)
+                .map(integrity_level_name)
+                .unwrap_or("Unknown")
+                .to_string(),
+            is_elevated: query_elevation(token).unwrap_or(false),
+            privileges: query_privileges(token),
+        };
+
+        let _ = CloseHandle(token);
+        Ok(info)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = pid;
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
+/// Owner of a process as `DOMAIN\user`, cached per process instance.
+/// `create_time` only needs to be stable for a given process.
+pub fn get_process_user(pid: u32, create_time: i64) -> Option<String> {
+    if let Some(user) = PROCESS_USER_CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&(pid, create_time)).cloned())
+    {
+        return user;
+    }
+
+    #[cfg(target_os = "windows")]
+    let user = unsafe {
+        open_process_token(pid).ok().and_then(|token| {
+            let user = query_token_user(token);
+            let _ = CloseHandle(token);
+            user
+        })
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let user = {
+        use crate::shared::system::get_system;
+        get_system().lock().ok().and_then(|system| {
+            system
+                .process(sysinfo::Pid::from(pid as usize))
+                .and_then(|p| p.user_id())
+                .map(resolve_user_id)
+        })
+    };
+
+    if let Ok(mut cache) = PROCESS_USER_CACHE.lock() {
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.clear();
+        }
+        cache.insert((pid, create_time), user.clone());
+    }
+
+    user
+}
+
+/// Resolves a sysinfo user id to an account name. On Windows the id is a SID
+/// string and becomes `DOMAIN\user`; falls back to the raw id.
+pub fn resolve_user_id(uid: &sysinfo::Uid) -> String {
+    let raw = uid.to_string();
+
+    if let Some(name) = ACCOUNT_NAME_CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&raw).cloned())
+    {
+        return name;
+    }
+
+    #[cfg(target_os = "windows")]
+    let resolved = unsafe {
+        let wide: Vec<u16> = raw.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut sid = PSID::default();
+        match ConvertStringSidToSidW(PCWSTR(wide.as_ptr()), &mut sid) {
+            Ok(()) => {
+                let name = lookup_account_sid(sid);
+                let _ = LocalFree(Some(HLOCAL(sid.0)));
+                name
+            }
+            Err(_) => None,
+        }
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let resolved = sysinfo::Users::new_with_refreshed_list()
+        .get_user_by_id(uid)
+        .map(|user| user.name().to_string());
+
+    let name = resolved.unwrap_or(raw.clone());
+    if let Ok(mut cache) = ACCOUNT_NAME_CACHE.lock() {
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(raw, name.clone());
+    }
+    name
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn open_process_token(pid: u32) -> Result<HANDLE> {
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+        .map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+
+    let mut token = HANDLE::default();
+    let result = OpenProcessToken(process, TOKEN_QUERY, &mut token);
+    let _ = CloseHandle(process);
+
+    result.map_err(|e| ProcessControlError::OpenError(e.to_string()))?;
+    Ok(token)
+}
+
+/// Reads a variable-size token information class into an 8-byte aligned buffer
+#[cfg(target_os = "windows")]
+unsafe fn query_token(token: HANDLE, class: TOKEN_INFORMATION_CLASS) -> Option<Vec<u64>> {
+    let mut length: u32 = 0;
+    let _ = GetTokenInformation(token, class, None, 0, &mut length);
+    if length == 0 {
+        return None;
+    }
+
+    let mut buffer = vec![0u64; (length as usize).div_ceil(8)];
+    GetTokenInformation(
+        token,
+        class,
+        Some(buffer.as_mut_ptr() as *mut _),
+        length,
+        &mut length,
+    )
+    .ok()?;
+
+    Some(buffer)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn query_token_user(token: HANDLE) -> Option<String> {
+    let buffer = query_token(token, TokenUser)?;
+    let token_user = &*(buffer.as_ptr() as *const TOKEN_USER);
+    lookup_account_sid(token_user.User.Sid)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn query_integrity_rid(token: HANDLE) -> Option<u32> {
+    let buffer = query_token(token, TokenIntegrityLevel)?;
+    let label = &*(buffer.as_ptr() as *const TOKEN_MANDATORY_LABEL);
+    let sid = label.Label.Sid;
+
+    let count = *GetSidSubAuthorityCount(sid);
+    if count == 0 {
+        return None;
+    }
+    Some(*GetSidSubAuthority(sid, (count - 1) as u32))
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn query_elevation(token: HANDLE) -> Option<bool> {
+    let buffer = query_token(token, TokenElevation)?;
+    let elevation = &*(buffer.as_ptr() as *const TOKEN_ELEVATION);
+    Some(elevation.TokenIsElevated != 0)
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn query_privileges(token: HANDLE) -> Vec<PrivilegeInfo> {
+    let Some(buffer) = query_token(token, TokenPrivileges) else {
+        return Vec::new();
+    };
+    let privileges = &*(buffer.as_ptr() as *const TOKEN_PRIVILEGES);
+    // The struct declares a 1-element array, the real length is PrivilegeCount
+    let entries = std::slice::from_raw_parts(
+        privileges.Privileges.as_ptr(),
+        privileges.PrivilegeCount as usize,
+    );
+
+    let mut result: Vec<PrivilegeInfo> = entries
+        .iter()
+        .filter_map(|entry| {
+            lookup_privilege_name(&entry.Luid).map(|name| PrivilegeInfo {
+                name,
+                enabled: entry.Attributes & SE_PRIVILEGE_ENABLED == SE_PRIVILEGE_ENABLED,
+            })
+        })
+        .collect();
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    result
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn lookup_privilege_name(luid: &LUID) -> Option<String> {
+    let mut buffer = [0u16; 128];
+    let mut length = buffer.len() as u32;
+    LookupPrivilegeNameW(
+        PCWSTR::null(),
+        luid,
+        Some(PWSTR(buffer.as_mut_ptr())),
+        &mut length,
+    )
+    .ok()?;
+    Some(String::from_utf16_lossy(&buffer[..length as usize]))
+}
+
+/// Resolves a SID to `DOMAIN\user`. `sid` must point to a valid SID.
+#[cfg(target_os = "windows")]
+unsafe fn lookup_account_sid(sid: PSID) -> Option<String> {
+    let mut name = [0u16; 256];
+    let mut domain = [0u16; 256];
+    let mut name_length = name.len() as u32;
+    let mut domain_length = domain.len() as u32;
+    let mut sid_type = SID_NAME_USE::default();
+
+    LookupAccountSidW(
+        PCWSTR::null(),
+        sid,
+        Some(PWSTR(name.as_mut_ptr())),
+        &mut name_length,
+        Some(PWSTR(domain.as_mut_ptr())),
+        &mut domain_length,
+        &mut sid_type,
+    )
+    .ok()?;
+
+    let name = String::from_utf16_lossy(&name[..name_length as usize]);
+    let domain = String::from_utf16_lossy(&domain[..domain_length as usize]);
+    if domain.is_empty() {
+        Some(name)
+    } else {
+        Some(format!("{}\\{}", domain, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrity_level_name() {
+        assert_eq!(integrity_level_name(0x0000), "Untrusted");
+        assert_eq!(integrity_level_name(0x1000), "Low");
+        assert_eq!(integrity_level_name(0x2000), "Medium");
+        assert_eq!(integrity_level_name(0x2100), "Medium Plus");
+        assert_eq!(integrity_level_name(0x3000), "High");
+        assert_eq!(integrity_level_name(0x4000), "System");
+    }
+}
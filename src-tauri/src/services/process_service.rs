@@ -55,7 +55,7 @@ pub fn user(pid: Arc<Pid>) -> Result<String> {
         .process(*pid)
         .ok_or_else(|| anyhow::anyhow!("Process not found"))?
         .user_id()
-        .map(|uid| uid.to_string())
+        .map(crate::services::process_security::resolve_user_id)
         .unwrap_or_else(|| "Unknown".to_string()))
 }
 
@@ -129,6 +129,17 @@ pub fn disk_io(pid: Arc<Pid>) -> Result<String> {
 }
 
 pub fn env_vars(pid: Arc<Pid>) -> Result<Vec<String>> {
+    // Read the environment block straight from the process parameters
+    #[cfg(target_os = "windows")]
+    if let Ok(vars) = crate::services::process_info::env_vars::get_env_vars(*pid) {
+        let mut env_vars: Vec<String> = vars
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        env_vars.sort();
+        return Ok(env_vars);
+    }
+
     let mut system = get_system()
         .lock()
         .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
@@ -0,0 +1,519 @@
+use crate::shared::events;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Interval of the polling fallback used when no event source is available
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessEventKind {
+    Created,
+    Exited,
+}
+
+/// A process start or exit, pushed as soon as the OS reports it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessEvent {
+    pub kind: ProcessEventKind,
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: Option<String>,
+    pub exe_path: Option<String>,
+    pub exit_code: Option<u32>,
+    pub timestamp: u64,
+}
+
+impl ProcessEvent {
+    fn new(kind: ProcessEventKind, pid: u32) -> Self {
+        Self {
+            kind,
+            pid,
+            parent_pid: None,
+            name: None,
+            exe_path: None,
+            exit_code: None,
+            timestamp: events::now_millis(),
+        }
+    }
+}
+
+static SUBSCRIBERS: once_cell::sync::Lazy<Mutex<Vec<Sender<ProcessEvent>>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Name of the backend delivering events, for diagnostics
+static ACTIVE_BACKEND: Mutex<&'static str> = Mutex::new("stopped");
+
+/// Starts the watcher thread once. Subsequent calls do nothing.
+pub fn start() {
+    if WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(|| {
+        #[cfg(target_os = "windows")]
+        {
+            if let Err(e) = wmi::run() {
+                eprintln!(
+                    "WMI process watcher unavailable, falling back to polling: {}",
+                    e
+                );
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            if let Err(e) = netlink::run() {
+                eprintln!(
+                    "Netlink process connector unavailable, falling back to polling: {}",
+                    e
+                );
+            }
+        }
+
+        run_polling();
+    });
+}
+
+/// Registers a consumer of process events (game detection, watchdogs, ...)
+pub fn subscribe() -> Receiver<ProcessEvent> {
+    let (sender, receiver) = channel();
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.push(sender);
+    }
+    receiver
+}
+
+pub fn active_backend() -> &'static str {
+    ACTIVE_BACKEND.lock().map(|b| *b).unwrap_or("unknown")
+}
+
+fn set_backend(name: &'static str) {
+    if let Ok(mut backend) = ACTIVE_BACKEND.lock() {
+        *backend = name;
+    }
+}
+
+fn publish(event: ProcessEvent) {
+    let name = match event.kind {
+        ProcessEventKind::Created => events::PROCESS_CREATED,
+        ProcessEventKind::Exited => events::PROCESS_EXITED,
+    };
+
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        // Drop the subscribers whose receiver is gone
+        subscribers.retain(|s| s.send(event.clone()).is_ok());
+    }
+
+    events::emit(name, event);
+}
+
+/// Fallback: diff the PID list. Only the PID set is read, not full process
+/// details, so this stays cheap compared to the process table refresh.
+fn run_polling() {
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+    set_backend("polling");
+    // Own instance, so the shared one used for CPU deltas is left untouched
+    let mut system = System::new();
+    let mut known: Option<HashSet<u32>> = None;
+
+    loop {
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_exe(sysinfo::UpdateKind::OnlyIfNotSet),
+        );
+        let pids: HashSet<u32> = system.processes().keys().map(|p| p.as_u32()).collect();
+
+        if let Some(known) = &known {
+            for pid in known.difference(&pids) {
+                publish(ProcessEvent::new(ProcessEventKind::Exited, *pid));
+            }
+
+            for pid in pids.difference(known) {
+                if let Some(process) = system.process(sysinfo::Pid::from(*pid as usize)) {
+                    let mut event = ProcessEvent::new(ProcessEventKind::Created, *pid);
+                    event.parent_pid = process.parent().map(|p| p.as_u32());
+                    event.name = Some(process.name().to_string_lossy().into_owned());
+                    event.exe_path = process.exe().map(|e| e.to_string_lossy().into_owned());
+                    publish(event);
+                }
+            }
+        }
+        known = Some(pids);
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod wmi {
+    use super::{publish, set_backend, ProcessEvent, ProcessEventKind};
+    use windows::core::{Interface, BSTR, PCWSTR, PWSTR};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoSetProxyBlanket, CoTaskMemFree, CLSCTX_INPROC_SERVER,
+        COINIT_MULTITHREADED, EOAC_NONE, RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE,
+    };
+    use windows::Win32::System::Variant::{
+        VariantClear, VariantToStringAlloc, VariantToUInt32, VARIANT, VT_UNKNOWN,
+    };
+    use windows::Win32::System::Wmi::{
+        IEnumWbemClassObject, IWbemClassObject, IWbemLocator, IWbemServices, WbemLocator,
+        WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_RETURN_IMMEDIATELY, WBEM_INFINITE,
+    };
+
+    const RPC_C_AUTHN_WINNT: u32 = 10;
+    const RPC_C_AUTHZ_NONE: u32 = 0;
+
+    // Kernel trace events, delivered instantly but only to elevated clients
+    const START_TRACE_QUERY: &str = "SELECT * FROM Win32_ProcessStartTrace";
+    const STOP_TRACE_QUERY: &str = "SELECT * FROM Win32_ProcessStopTrace";
+    // Intrinsic events, available to everyone, WMI checks every second
+    const CREATION_QUERY: &str =
+        "SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process'";
+    const DELETION_QUERY: &str =
+        "SELECT * FROM __InstanceDeletionEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process'";
+
+    /// Blocks delivering WMI events; returns only if WMI can't be used
+    pub fn run() -> windows::core::Result<()> {
+        unsafe {
+            CoInitializeEx(None, COINIT_MULTITHREADED).ok()?;
+            let services = connect()?;
+
+            let trace = (
+                subscribe(&services, START_TRACE_QUERY),
+                subscribe(&services, STOP_TRACE_QUERY),
+            );
+
+            let (start_events, stop_events, is_trace) = match trace {
+                (Ok(start), Ok(stop)) => (start, stop, true),
+                _ => (
+                    subscribe(&services, CREATION_QUERY)?,
+                    subscribe(&services, DELETION_QUERY)?,
+                    false,
+                ),
+            };
+            set_backend(if is_trace { "wmi-trace" } else { "wmi" });
+
+            // Exit events are read on their own thread, both enumerators block
+            let stop_events = AgileEnum(stop_events);
+            std::thread::spawn(move || {
+                let stop_events = stop_events;
+                let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+                let _ = pump(&stop_events.0, ProcessEventKind::Exited, is_trace);
+            });
+
+            pump(&start_events, ProcessEventKind::Created, is_trace)
+        }
+    }
+
+    // WMI proxies live in the MTA and can be used from any of its threads
+    struct AgileEnum(IEnumWbemClassObject);
+    unsafe impl Send for AgileEnum {}
+
+    unsafe fn connect() -> windows::core::Result<IWbemServices> {
+        let locator: IWbemLocator = CoCreateInstance(&WbemLocator, None, CLSCTX_INPROC_SERVER)?;
+        let services = locator.ConnectServer(
+            &BSTR::from("ROOT\\CIMV2"),
+            &BSTR::new(),
+            &BSTR::new(),
+            &BSTR::new(),
+            0,
+            &BSTR::new(),
+            None,
+        )?;
+
+        CoSetProxyBlanket(
+            &services,
+            RPC_C_AUTHN_WINNT,
+            RPC_C_AUTHZ_NONE,
+            PCWSTR::null(),
+            RPC_C_AUTHN_LEVEL_CALL,
+            RPC_C_IMP_LEVEL_IMPERSONATE,
+            None,
+            EOAC_NONE,
+        )?;
+
+        Ok(services)
+    }
+
+    unsafe fn subscribe(
+        services: &IWbemServices,
+        query: &str,
+    ) -> windows::core::Result<IEnumWbemClassObject> {
+        services.ExecNotificationQuery(
+            &BSTR::from("WQL"),
+            &BSTR::from(query),
+            WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY,
+            None,
+        )
+    }
+
+    unsafe fn pump(
+        events: &IEnumWbemClassObject,
+        kind: ProcessEventKind,
+        is_trace: bool,
+    ) -> windows::core::Result<()> {
+        loop {
+            let mut objects = [None];
+            let mut returned = 0u32;
+            events
+                .Next(WBEM_INFINITE, &mut objects, &mut returned)
+                .ok()?;
+
+            let Some(object) = objects[0].take() else {
+                continue;
+            };
+
+            let event = if is_trace {
+                trace_event(&object, kind)
+            } else {
+                get_object(&object, "TargetInstance")
+                    .and_then(|instance| instance_event(&instance, kind))
+            };
+
+            if let Some(event) = event {
+                publish(event);
+            }
+        }
+    }
+
+    /// Win32_ProcessStartTrace / Win32_ProcessStopTrace
+    unsafe fn trace_event(
+        object: &IWbemClassObject,
+        kind: ProcessEventKind,
+    ) -> Option<ProcessEvent> {
+        let mut event = ProcessEvent::new(kind, get_u32(object, "ProcessID")?);
+        event.parent_pid = get_u32(object, "ParentProcessID");
+        event.name = get_string(object, "ProcessName");
+        if kind == ProcessEventKind::Exited {
+            event.exit_code = get_u32(object, "ExitStatus");
+        }
+        Some(event)
+    }
+
+    /// Win32_Process instance carried by __InstanceCreation/DeletionEvent
+    unsafe fn instance_event(
+        instance: &IWbemClassObject,
+        kind: ProcessEventKind,
+    ) -> Option<ProcessEvent> {
+        let mut event = ProcessEvent::new(kind, get_u32(instance, "ProcessId")?);
+        event.parent_pid = get_u32(instance, "ParentProcessId");
+        event.name = get_string(instance, "Name");
+        event.exe_path = get_string(instance, "ExecutablePath");
+        Some(event)
+    }
+
+    unsafe fn get_property(object: &IWbemClassObject, name: &str) -> Option<VARIANT> {
+        let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut value = VARIANT::default();
+        object
+            .Get(PCWSTR(wide.as_ptr()), 0, &mut value, None, None)
+            .ok()?;
+        Some(value)
+    }
+
+    unsafe fn get_u32(object: &IWbemClassObject, name: &str) -> Option<u32> {
+        let mut value = get_property(object, name)?;
+        let result = VariantToUInt32(&value).ok();
+        let _ = VariantClear(&mut value);
+        result
+    }
+
+    unsafe fn get_string(object: &IWbemClassObject, name: &str) -> Option<String> {
+        let mut value = get_property(object, name)?;
+        let result = VariantToStringAlloc(&value).ok().map(|pwstr: PWSTR| {
+            let text = pwstr.to_string().unwrap_or_default();
+            CoTaskMemFree(Some(pwstr.0 as *const _));
+            text
+        });
+        let _ = VariantClear(&mut value);
+        result.filter(|s| !s.is_empty())
+    }
+
+    unsafe fn get_object(object: &IWbemClassObject, name: &str) -> Option<IWbemClassObject> {
+        let mut value = get_property(object, name)?;
+        let inner = &value.Anonymous.Anonymous;
+        let result = if inner.vt == VT_UNKNOWN {
+            inner
+                .Anonymous
+                .punkVal
+                .as_ref()
+                .and_then(|unknown| unknown.cast::<IWbemClassObject>().ok())
+        } else {
+            None
+        };
+        let _ = VariantClear(&mut value);
+        result
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod netlink {
+    use super::{publish, set_backend, ProcessEvent, ProcessEventKind};
+    use std::io;
+
+    // linux/connector.h and linux/cn_proc.h
+    const NETLINK_CONNECTOR: libc::c_int = 11;
+    const CN_IDX_PROC: u32 = 1;
+    const CN_VAL_PROC: u32 = 1;
+    const PROC_CN_MCAST_LISTEN: u32 = 1;
+    const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+    const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+    const NLMSG_HEADER_LEN: usize = 16;
+    const CN_MSG_LEN: usize = 20;
+    // what, cpu, timestamp_ns
+    const PROC_EVENT_HEADER_LEN: usize = 16;
+
+    /// Blocks delivering proc connector events. Needs CAP_NET_ADMIN, returns an
+    /// error right away otherwise.
+    pub fn run() -> io::Result<()> {
+        unsafe {
+            let socket = libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM, NETLINK_CONNECTOR);
+            if socket < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let result = listen(socket);
+            libc::close(socket);
+            result
+        }
+    }
+
+    unsafe fn listen(socket: libc::c_int) -> io::Result<()> {
+        let mut address: libc::sockaddr_nl = std::mem::zeroed();
+        address.nl_family = libc::AF_NETLINK as u16;
+        address.nl_groups = CN_IDX_PROC;
+        address.nl_pid = libc::getpid() as u32;
+
+        if libc::bind(
+            socket,
+            &address as *const _ as *const libc::sockaddr,
+            std::mem::size_of::<libc::sockaddr_nl>() as u32,
+        ) < 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Subscribe: nlmsghdr + cn_msg + PROC_CN_MCAST_LISTEN
+        let total_len = NLMSG_HEADER_LEN + CN_MSG_LEN + 4;
+        let mut message = Vec::with_capacity(total_len);
+        message.extend_from_slice(&(total_len as u32).to_ne_bytes());
+        message.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+        message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+        message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+        message.extend_from_slice(&(libc::getpid() as u32).to_ne_bytes());
+        message.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+        message.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+        message.extend_from_slice(&0u32.to_ne_bytes()); // ack
+        message.extend_from_slice(&4u16.to_ne_bytes()); // payload len
+        message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+        message.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+
+        if libc::send(socket, message.as_ptr() as *const _, message.len(), 0) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        set_backend("netlink");
+        let mut buffer = vec![0u8; 4096];
+
+        loop {
+            let received = libc::recv(socket, buffer.as_mut_ptr() as *mut _, buffer.len(), 0);
+            if received < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+
+            if let Some(event) = parse_message(&buffer[..received as usize]) {
+                publish(event);
+            }
+        }
+    }
+
+    fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+        data.get(offset..offset + 4)
+            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(super) fn parse_message(data: &[u8]) -> Option<ProcessEvent> {
+        let event_offset = NLMSG_HEADER_LEN + CN_MSG_LEN;
+        let what = read_u32(data, event_offset)?;
+        let payload = event_offset + PROC_EVENT_HEADER_LEN;
+
+        match what {
+            PROC_EVENT_EXEC => {
+                // exec_proc_event { process_pid, process_tgid }
+                let pid = read_u32(data, payload + 4)?;
+                let mut event = ProcessEvent::new(ProcessEventKind::Created, pid);
+                event.name = std::fs::read_to_string(format!("/proc/{}/comm", pid))
+                    .ok()
+                    .map(|name| name.trim_end().to_string());
+                event.exe_path = std::fs::read_link(format!("/proc/{}/exe", pid))
+                    .ok()
+                    .map(|path| path.to_string_lossy().into_owned());
+                event.parent_pid = read_parent_pid(pid);
+                Some(event)
+            }
+            PROC_EVENT_EXIT => {
+                // exit_proc_event { process_pid, process_tgid, exit_code, exit_signal, ... }
+                let thread_id = read_u32(data, payload)?;
+                let pid = read_u32(data, payload + 4)?;
+                // Thread exits are reported too, only keep the main thread
+                if thread_id != pid {
+                    return None;
+                }
+                let mut event = ProcessEvent::new(ProcessEventKind::Exited, pid);
+                // exit_code is a wait status: code in bits 8-15, signal in bits 0-6
+                event.exit_code = read_u32(data, payload + 8).map(|status| {
+                    if status & 0x7f == 0 {
+                        (status >> 8) & 0xff
+                    } else {
+                        128 + (status & 0x7f) // shell convention for signals
+                    }
+                });
+                Some(event)
+            }
+            _ => None,
+        }
+    }
+
+    fn read_parent_pid(pid: u32) -> Option<u32> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // The name in parentheses may contain spaces, fields resume after ')'
+        let fields = &stat[stat.rfind(')')? + 1..];
+        fields.split_whitespace().nth(1)?.parse().ok()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_netlink_exit_event() {
+        let mut data = vec![0u8; 36];
+        data.extend_from_slice(&0x8000_0000u32.to_ne_bytes()); // what
+        data.extend_from_slice(&0u32.to_ne_bytes()); // cpu
+        data.extend_from_slice(&0u64.to_ne_bytes()); // timestamp
+        data.extend_from_slice(&4242u32.to_ne_bytes()); // pid
+        data.extend_from_slice(&4242u32.to_ne_bytes()); // tgid
+        data.extend_from_slice(&(9u32 << 8).to_ne_bytes()); // wait status
+
+        let event = netlink::parse_message(&data).expect("exit event");
+        assert_eq!(event.kind, ProcessEventKind::Exited);
+        assert_eq!(event.pid, 4242);
+        assert_eq!(event.exit_code, Some(9));
+    }
+}
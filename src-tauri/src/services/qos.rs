@@ -0,0 +1,274 @@
+// Tags the traffic of a running game with a DSCP value, so routers that honor
+// it queue the game's packets ahead of downloads and streams. On Windows this
+// is a QoS policy on the executable (the same thing Group Policy QoS creates)
+// in the active store, which never outlives a reboot. On Linux the game's
+// processes are moved into a cgroup that an nftables table rewrites the DSCP
+// field for. Both need administrator or root rights; leftovers of a crashed
+// run are removed on the next start.
+use crate::models::game_profile::QosPolicy;
+use crate::shared::events;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use thiserror::Error;
+
+// DSCP is a 6 bit field
+pub const MAX_DSCP: u8 = 63;
+
+#[derive(Error, Debug)]
+pub enum QosError {
+    #[error("DSCP must be between 0 and {MAX_DSCP}, got {0}")]
+    InvalidDscp(u8),
+
+    #[error("Failed to apply the QoS policy: {0}")]
+    ApplyError(String),
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    #[error("QoS tagging is not supported on this platform")]
+    UnsupportedPlatform,
+}
+
+type Result<T> = std::result::Result<T, QosError>;
+
+// By game pid
+static POLICIES: once_cell::sync::Lazy<Mutex<HashMap<u32, QosPolicy>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Tags the game's traffic until `release_game`
+pub fn apply_for_game(game_pid: u32, exe_path: &str, dscp: u8) -> Result<()> {
+    if dscp > MAX_DSCP {
+        return Err(QosError::InvalidDscp(dscp));
+    }
+    platform::apply(game_pid, exe_path, dscp)?;
+    if let Ok(mut policies) = POLICIES.lock() {
+        policies.insert(
+            game_pid,
+            QosPolicy {
+                game_pid,
+                exe_path: exe_path.to_string(),
+                dscp,
+                since: events::now_millis(),
+            },
+        );
+    }
+    Ok(())
+}
+
+pub fn release_game(game_pid: u32) {
+    let removed = POLICIES
+        .lock()
+        .ok()
+        .and_then(|mut policies| policies.remove(&game_pid));
+    if removed.is_some() {
+        if let Err(e) = platform::remove(game_pid) {
+            eprintln!("Failed to remove the QoS policy: {}", e);
+        }
+    }
+}
+
+/// Removes every policy. Called on exit, hence the try_lock.
+pub fn release_all() {
+    let Ok(mut policies) = POLICIES.try_lock() else {
+        return;
+    };
+    for (game_pid, _) in policies.drain() {
+        let _ = platform::remove(game_pid);
+    }
+}
+
+/// Removes the policies a crashed run left in place
+pub fn restore_leftover() {
+    platform::remove_all();
+}
+
+pub fn get_policies() -> Vec<QosPolicy> {
+    POLICIES
+        .lock()
+        .map(|policies| policies.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{QosError, Result};
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    fn policy_name(game_pid: u32) -> String {
+        format!("Aura QoS {}", game_pid)
+    }
+
+    pub fn apply(game_pid: u32, exe_path: &str, dscp: u8) -> Result<()> {
+        powershell(&format!(
+            "New-NetQosPolicy -Name '{}' -AppPathNameMatchCondition '{}' \
+             -DSCPAction {} -NetworkProfile All -PolicyStore ActiveStore | Out-Null",
+            policy_name(game_pid),
+            exe_path.replace('\'', "''"),
+            dscp
+        ))
+    }
+
+    pub fn remove(game_pid: u32) -> Result<()> {
+        powershell(&format!(
+            "Remove-NetQosPolicy -Name '{}' -PolicyStore ActiveStore -Confirm:$false",
+            policy_name(game_pid)
+        ))
+    }
+
+    pub fn remove_all() {
+        let _ = powershell(
+            "Get-NetQosPolicy -PolicyStore ActiveStore | \
+             Where-Object Name -like 'Aura QoS *' | \
+             Remove-NetQosPolicy -Confirm:$false",
+        );
+    }
+
+    fn powershell(script: &str) -> Result<()> {
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .map_err(|e| QosError::ApplyError(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(QosError::ApplyError(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{QosError, Result};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    const TABLE: &str = "aura_qos";
+
+    // One cgroup per game, the rule matches on it
+    fn cgroup_name(game_pid: u32) -> String {
+        format!("aura-qos-{}", game_pid)
+    }
+
+    pub fn apply(game_pid: u32, _exe_path: &str, dscp: u8) -> Result<()> {
+        let cgroup = format!("{}/{}", CGROUP_ROOT, cgroup_name(game_pid));
+        std::fs::create_dir_all(&cgroup)
+            .map_err(|e| QosError::ApplyError(format!("Failed to create {}: {}", cgroup, e)))?;
+        // Processes the game starts from now on follow it into the cgroup
+        std::fs::write(format!("{}/cgroup.procs", cgroup), game_pid.to_string())
+            .map_err(|e| QosError::ApplyError(format!("Failed to move the game: {}", e)))?;
+
+        let matcher = format!("socket cgroupv2 level 1 \"{}\"", cgroup_name(game_pid));
+        let script = format!(
+            "table inet {table} {{\n\
+             \tchain output {{ type filter hook output priority mangle; policy accept; }}\n\
+             }}\n\
+             add rule inet {table} output {matcher} ip dscp set {dscp} comment \"{game_pid}\"\n\
+             add rule inet {table} output {matcher} ip6 dscp set {dscp} comment \"{game_pid}\"\n",
+            table = TABLE,
+        );
+        nft_script(&script)
+    }
+
+    pub fn remove(game_pid: u32) -> Result<()> {
+        let output = Command::new("nft")
+            .args(["-a", "list", "chain", "inet", TABLE, "output"])
+            .output()
+            .map_err(|e| QosError::ApplyError(format!("nft: {}", e)))?;
+        let listing = String::from_utf8_lossy(&output.stdout);
+        for handle in super::rule_handles(&listing, game_pid) {
+            let _ = Command::new("nft")
+                .args([
+                    "delete",
+                    "rule",
+                    "inet",
+                    TABLE,
+                    "output",
+                    "handle",
+                    &handle.to_string(),
+                ])
+                .status();
+        }
+        // Only empty once the game's processes are gone, which is the case
+        // when this runs after it exited
+        let _ = std::fs::remove_dir(format!("{}/{}", CGROUP_ROOT, cgroup_name(game_pid)));
+        Ok(())
+    }
+
+    pub fn remove_all() {
+        let _ = Command::new("nft")
+            .args(["delete", "table", "inet", TABLE])
+            .stderr(Stdio::null())
+            .status();
+    }
+
+    fn nft_script(script: &str) -> Result<()> {
+        let mut child = Command::new("nft")
+            .args(["-f", "-"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| QosError::ApplyError(format!("nft: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(script.as_bytes());
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| QosError::ApplyError(format!("nft: {}", e)))?;
+        if !output.status.success() {
+            return Err(QosError::ApplyError(format!(
+                "nft: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::{QosError, Result};
+
+    pub fn apply(_game_pid: u32, _exe_path: &str, _dscp: u8) -> Result<()> {
+        Err(QosError::UnsupportedPlatform)
+    }
+
+    pub fn remove(_game_pid: u32) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn remove_all() {}
+}
+
+/// Handles of the rules added for a game in `nft -a list chain` output,
+/// recognized by the game pid in their comment
+#[cfg_attr(all(not(target_os = "linux"), not(test)), allow(dead_code))]
+fn rule_handles(listing: &str, game_pid: u32) -> Vec<u64> {
+    let comment = format!("comment \"{}\"", game_pid);
+    listing
+        .lines()
+        .filter(|line| line.contains(&comment))
+        .filter_map(|line| line.rsplit_once("# handle "))
+        .filter_map(|(_, handle)| handle.trim().parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_handles() {
+        let listing = "table inet aura_qos {\n\
+            \tchain output { # handle 1\n\
+            \t\tsocket cgroupv2 level 1 \"aura-qos-42\" ip dscp set ef comment \"42\" # handle 4\n\
+            \t\tsocket cgroupv2 level 1 \"aura-qos-42\" ip6 dscp set ef comment \"42\" # handle 5\n\
+            \t\tsocket cgroupv2 level 1 \"aura-qos-7\" ip dscp set ef comment \"7\" # handle 6\n\
+            \t}\n\
+            }\n";
+        assert_eq!(rule_handles(listing, 42), vec![4, 5]);
+        assert_eq!(rule_handles(listing, 4), Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,216 @@
+// Quiet hours: pauses background sampling, alerts and watchdogs during a
+// daily window, while one of the listed processes runs, or on demand. The
+// samplers check is_paused() each round, so a paused Aura costs nothing but
+// this thread waking up.
+use crate::models::system_stats::{MonitoringPauseReason, MonitoringPauseStatus, QuietHoursConfig};
+use crate::shared::{dates, events, paths, system};
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+const CONFIG_FILE: &str = "quiet_hours.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const TICK: Duration = Duration::from_secs(1);
+// Shorter would throttle during a pause in reading
+const MIN_IDLE_AFTER_SECS: u32 = 30;
+
+static CONFIG: once_cell::sync::Lazy<Mutex<QuietHoursConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+// Manual pause: Some(None) until resumed, Some(Some(millis)) until then
+static MANUAL_PAUSE: Mutex<Option<Option<u64>>> = Mutex::new(None);
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static REASON: Mutex<Option<MonitoringPauseReason>> = Mutex::new(None);
+static EVALUATOR_RUNNING: AtomicBool = AtomicBool::new(false);
+// Set when the settings change so the evaluator doesn't wait a full interval
+static RECHECK: AtomicBool = AtomicBool::new(false);
+
+/// Whether background monitoring should skip its work right now
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Starts the thread that decides whether monitoring is paused. Called once
+/// at startup.
+pub fn start() {
+    if EVALUATOR_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let mut system = System::new();
+        let mut elapsed = Duration::MAX;
+        loop {
+            if elapsed >= CHECK_INTERVAL || RECHECK.swap(false, Ordering::SeqCst) {
+                elapsed = Duration::ZERO;
+                update(evaluate(&mut system));
+            }
+            std::thread::sleep(TICK);
+            elapsed = elapsed.saturating_add(TICK);
+        }
+    });
+}
+
+pub fn get_config() -> QuietHoursConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: QuietHoursConfig) -> Result<QuietHoursConfig> {
+    for time in [&config.start, &config.end] {
+        if parse_time(time).is_none() {
+            return Err(anyhow!("Invalid time, expected HH:MM: {}", time));
+        }
+    }
+    if config.idle_after_secs < MIN_IDLE_AFTER_SECS {
+        return Err(anyhow!(
+            "Idle time must be at least {} seconds",
+            MIN_IDLE_AFTER_SECS
+        ));
+    }
+    let config = QuietHoursConfig {
+        pause_for_processes: config
+            .pause_for_processes
+            .iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        ..config
+    };
+
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    RECHECK.store(true, Ordering::SeqCst);
+    Ok(config)
+}
+
+/// Pauses monitoring for the given minutes, or until resumed
+pub fn pause(minutes: Option<u32>) -> MonitoringPauseStatus {
+    let until = minutes.map(|minutes| events::now_millis() + minutes as u64 * 60_000);
+    if let Ok(mut manual) = MANUAL_PAUSE.lock() {
+        *manual = Some(until);
+    }
+    update(Some(MonitoringPauseReason::Manual));
+    get_status()
+}
+
+/// Ends a manual pause; quiet hours and processes still apply
+pub fn resume() -> MonitoringPauseStatus {
+    if let Ok(mut manual) = MANUAL_PAUSE.lock() {
+        *manual = None;
+    }
+    update(evaluate(&mut System::new()));
+    get_status()
+}
+
+pub fn get_status() -> MonitoringPauseStatus {
+    MonitoringPauseStatus {
+        paused: is_paused(),
+        reason: REASON.lock().ok().and_then(|reason| reason.clone()),
+        manual_until: MANUAL_PAUSE.lock().ok().and_then(|manual| manual.flatten()),
+    }
+}
+
+fn load_config() -> QuietHoursConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+/// Why monitoring should be paused now, if it should
+fn evaluate(system: &mut System) -> Option<MonitoringPauseReason> {
+    if let Ok(mut manual) = MANUAL_PAUSE.lock() {
+        match *manual {
+            Some(Some(until)) if events::now_millis() >= until => *manual = None,
+            Some(_) => return Some(MonitoringPauseReason::Manual),
+            None => {}
+        }
+    }
+
+    let config = get_config();
+    if !config.enabled {
+        return None;
+    }
+    if let (Some(start), Some(end)) = (parse_time(&config.start), parse_time(&config.end)) {
+        if in_window(dates::local_minutes_of_day(), start, end) {
+            return Some(MonitoringPauseReason::QuietHours);
+        }
+    }
+    if config.pause_for_processes.is_empty() {
+        return None;
+    }
+
+    // Names only, which keeps the check cheap
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    system.processes().values().find_map(|process| {
+        let name = process.name().to_string_lossy();
+        config
+            .pause_for_processes
+            .iter()
+            .find(|wanted| system::same_process_name(&name, wanted))
+            .map(|_| MonitoringPauseReason::Process(name.to_string()))
+    })
+}
+
+fn update(reason: Option<MonitoringPauseReason>) {
+    let changed = match REASON.lock() {
+        Ok(mut current) if *current != reason => {
+            *current = reason.clone();
+            true
+        }
+        _ => false,
+    };
+    PAUSED.store(reason.is_some(), Ordering::SeqCst);
+    if changed {
+        events::emit(events::MONITORING_PAUSE_CHANGED, get_status());
+    }
+}
+
+/// Minutes since midnight from "HH:MM"
+pub fn parse_time(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Whether now falls in [start, end), wrapping past midnight when end is
+/// earlier than start. An empty window never matches.
+fn in_window(now: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("00:00"), Some(0));
+        assert_eq!(parse_time("07:30"), Some(450));
+        assert_eq!(parse_time(" 23:59 "), Some(1439));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("12:60"), None);
+        assert_eq!(parse_time("noon"), None);
+    }
+
+    #[test]
+    fn test_in_window() {
+        // Same day
+        assert!(in_window(600, 540, 1020));
+        assert!(!in_window(1020, 540, 1020));
+        assert!(!in_window(500, 540, 1020));
+        // Across midnight
+        assert!(in_window(1380, 1320, 420));
+        assert!(in_window(60, 1320, 420));
+        assert!(!in_window(720, 1320, 420));
+        // Empty
+        assert!(!in_window(600, 600, 600));
+    }
+}
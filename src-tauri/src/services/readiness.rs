@@ -0,0 +1,423 @@
+use crate::models::readiness::{ReadinessItem, ReadinessReport};
+use crate::services::crash_monitor;
+use crate::services::gpu;
+use crate::services::timer_resolution;
+use crate::shared::dates::days_from_civil;
+use crate::shared::events::now_millis;
+use crate::shared::system::get_system;
+use anyhow::Result;
+use sysinfo::Disks;
+
+#[cfg(target_os = "windows")]
+use crate::services::gaming_services;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+const GB: u64 = 1024 * 1024 * 1024;
+const MIN_FREE_RAM: u64 = 4 * GB;
+const MIN_FREE_VRAM: u64 = GB;
+const MIN_FREE_DISK: u64 = 20 * GB;
+const MAX_DRIVER_AGE_DAYS: i64 = 180;
+const MAX_TIMER_RESOLUTION_MS: f64 = 1.0;
+// Share of the whole CPU a background process may use before it counts as a hog
+const CPU_HOG_THRESHOLD: f32 = 5.0;
+const MAX_LISTED_HOGS: usize = 3;
+
+#[cfg(target_os = "windows")]
+const HIGH_PERFORMANCE_SCHEMES: &[&str] = &[
+    "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c", // High performance
+    "e9a42b02-d5df-448d-aa00-03f14749eb61", // Ultimate Performance
+];
+
+/// Runs every pre-flight check and scores the result. Checks that do not
+/// apply to the platform or hardware are left out of the checklist.
+pub fn run_readiness_check() -> Result<ReadinessReport> {
+    let mut items = Vec::new();
+
+    items.extend(check_power_plan());
+    items.extend(check_timer_resolution());
+    items.push(check_free_ram()?);
+    items.extend(check_free_vram());
+    items.extend(check_disk_space());
+    items.extend(check_driver_age());
+    items.push(check_background_cpu()?);
+    items.extend(check_pending_updates());
+
+    #[cfg(target_os = "windows")]
+    if let Ok(status) = gaming_services::get_status() {
+        items.extend(status.checklist);
+    }
+
+    Ok(ReadinessReport {
+        score: score(&items),
+        items,
+        checked_at: now_millis(),
+    })
+}
+
+/// Weighted share of passed checks, 0-100
+fn score(items: &[ReadinessItem]) -> u8 {
+    let total: u32 = items.iter().map(|i| weight(&i.id)).sum();
+    if total == 0 {
+        return 100;
+    }
+    let passed: u32 = items
+        .iter()
+        .filter(|i| i.passed)
+        .map(|i| weight(&i.id))
+        .sum();
+    (passed as f64 / total as f64 * 100.0).round() as u8
+}
+
+/// How much a check weighs on the score, by how badly it hurts frame times
+fn weight(id: &str) -> u32 {
+    match id {
+        "power_plan" | "free_ram" | "background_cpu" => 15,
+        "timer_resolution" | "free_vram" | "disk_space" | "driver_age" | "pending_updates" => 10,
+        "game_dvr" | "game_mode" => 5,
+        _ => 2,
+    }
+}
+
+fn item(
+    id: &str,
+    label: &str,
+    passed: bool,
+    detail: String,
+    fix_id: Option<&str>,
+) -> ReadinessItem {
+    ReadinessItem {
+        id: id.to_string(),
+        label: label.to_string(),
+        passed,
+        detail,
+        fix_id: fix_id.map(str::to_string),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn check_power_plan() -> Option<ReadinessItem> {
+    let output = Command::new("powercfg")
+        .arg("/getactivescheme")
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    let (guid, name) = parse_active_scheme(&String::from_utf8_lossy(&output.stdout))?;
+
+    Some(item(
+        "power_plan",
+        "High performance power plan",
+        HIGH_PERFORMANCE_SCHEMES.contains(&guid.as_str()),
+        format!("Active plan: {}", name),
+        Some("high_performance_power_plan"),
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn check_power_plan() -> Option<ReadinessItem> {
+    let governor =
+        std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor").ok()?;
+    let governor = governor.trim();
+
+    Some(item(
+        "power_plan",
+        "Performance CPU governor",
+        governor == "performance",
+        format!("Active governor: {}", governor),
+        Some("enable_performance_governor"),
+    ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn check_power_plan() -> Option<ReadinessItem> {
+    None
+}
+
+fn check_timer_resolution() -> Option<ReadinessItem> {
+    // Only Windows has a coarse default timer
+    let resolution = timer_resolution::get_timer_resolution().ok()?;
+
+    Some(item(
+        "timer_resolution",
+        "High timer resolution",
+        resolution.current_ms <= MAX_TIMER_RESOLUTION_MS,
+        format!("Current resolution: {:.2} ms", resolution.current_ms),
+        Some("increase_timer_resolution"),
+    ))
+}
+
+fn check_free_ram() -> Result<ReadinessItem> {
+    let mut system = get_system()
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    system.refresh_memory();
+    let available = system.available_memory();
+
+    Ok(item(
+        "free_ram",
+        "Enough free RAM",
+        available >= MIN_FREE_RAM,
+        format!("{:.1} GB available", available as f64 / GB as f64),
+        Some("clear_memory_cache"),
+    ))
+}
+
+fn check_free_vram() -> Option<ReadinessItem> {
+    let stats = gpu::get_gpu_stats().ok()?;
+    // Integrated or unknown GPUs don't report dedicated memory
+    if stats.total_vram == 0 {
+        return None;
+    }
+    let free = stats.total_vram.saturating_sub(stats.total_vram_used);
+
+    Some(item(
+        "free_vram",
+        "Enough free VRAM",
+        free >= MIN_FREE_VRAM,
+        format!("{:.1} GB free", free as f64 / GB as f64),
+        None,
+    ))
+}
+
+/// Free space on the system drive, where shader caches and the page file live
+fn check_disk_space() -> Option<ReadinessItem> {
+    #[cfg(target_os = "windows")]
+    let system_root = format!(
+        "{}\\",
+        std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string())
+    );
+    #[cfg(not(target_os = "windows"))]
+    let system_root = "/".to_string();
+
+    let disks = Disks::new_with_refreshed_list();
+    let disk = disks.iter().find(|d| {
+        d.mount_point()
+            .to_string_lossy()
+            .eq_ignore_ascii_case(&system_root)
+    })?;
+    let available = disk.available_space();
+
+    Some(item(
+        "disk_space",
+        "Enough free disk space",
+        available >= MIN_FREE_DISK,
+        format!(
+            "{:.1} GB free on {}",
+            available as f64 / GB as f64,
+            system_root
+        ),
+        None,
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn check_driver_age() -> Option<ReadinessItem> {
+    let output = Command::new("wmic")
+        .args([
+            "path",
+            "win32_videocontroller",
+            "get",
+            "DriverDate,Name",
+            "/format:csv",
+        ])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    let today = (now_millis() / 86_400_000) as i64;
+
+    // The oldest driver decides
+    let (name, age) = parse_driver_dates(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .map(|(name, date)| (name, today - date))
+        .max_by_key(|(_, age)| *age)?;
+
+    Some(item(
+        "driver_age",
+        "Recent graphics driver",
+        age <= MAX_DRIVER_AGE_DAYS,
+        format!("{} driver is {} days old", name, age.max(0)),
+        None,
+    ))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn check_driver_age() -> Option<ReadinessItem> {
+    // Drivers ship with the distribution's kernel and Mesa packages
+    None
+}
+
+/// Background processes using a noticeable share of the CPU
+fn check_background_cpu() -> Result<ReadinessItem> {
+    let own_pid = std::process::id();
+    let games: Vec<u32> = crash_monitor::get_tracked_games()
+        .iter()
+        .map(|g| g.pid)
+        .collect();
+
+    let mut system = get_system()
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    // CPU usage is measured between two refreshes
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    let cpu_count = system.cpus().len().max(1) as f32;
+
+    let mut hogs: Vec<(String, f32)> = system
+        .processes()
+        .iter()
+        .filter(|(pid, _)| {
+            let pid = pid.as_u32();
+            pid != own_pid && pid != 0 && !games.contains(&pid)
+        })
+        .map(|(_, p)| {
+            (
+                p.name().to_string_lossy().into_owned(),
+                p.cpu_usage() / cpu_count,
+            )
+        })
+        .filter(|(_, usage)| *usage >= CPU_HOG_THRESHOLD)
+        .collect();
+    hogs.sort_by(|a, b| b.1.total_cmp(&a.1));
+    drop(system);
+
+    let detail = if hogs.is_empty() {
+        "No background process is using much CPU".to_string()
+    } else {
+        hogs.iter()
+            .take(MAX_LISTED_HOGS)
+            .map(|(name, usage)| format!("{} ({:.0}%)", name, usage))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    Ok(item(
+        "background_cpu",
+        "No background CPU hogs",
+        hogs.is_empty(),
+        detail,
+        None,
+    ))
+}
+
+/// Windows Update waiting for a restart tends to start working mid-session
+fn check_pending_updates() -> Option<ReadinessItem> {
+    let pending = updates_pending_restart()?;
+    Some(item(
+        "pending_updates",
+        "No pending Windows updates",
+        !pending,
+        if pending {
+            "Updates are waiting for a restart".to_string()
+        } else {
+            "Windows is up to date".to_string()
+        },
+        None,
+    ))
+}
+
+/// Whether installed updates wait for a restart, None where unknown
+#[cfg(target_os = "windows")]
+pub(crate) fn updates_pending_restart() -> Option<bool> {
+    const REBOOT_KEYS: &[&str] = &[
+        "HKLM\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\WindowsUpdate\\Auto Update\\RebootRequired",
+        "HKLM\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Component Based Servicing\\RebootPending",
+    ];
+
+    let mut pending = false;
+    for key in REBOOT_KEYS {
+        let output = Command::new("reg")
+            .args(["query", key])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .ok()?;
+        // The keys only exist while an update is waiting
+        pending |= output.status.success();
+    }
+    Some(pending)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn updates_pending_restart() -> Option<bool> {
+    None
+}
+
+/// Parses `Power Scheme GUID: 381b4222-...  (Balanced)` into (guid, name)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_active_scheme(output: &str) -> Option<(String, String)> {
+    let (_, rest) = output.split_once(':')?;
+    let mut parts = rest.trim().splitn(2, ' ');
+    let guid = parts.next()?.to_lowercase();
+    let name = parts
+        .next()
+        .map(|n| n.trim().trim_start_matches('(').trim_end_matches(')'))
+        .unwrap_or(&guid)
+        .to_string();
+    Some((guid, name))
+}
+
+/// Parses `wmic ... /format:csv` rows (`Node,DriverDate,Name`) into
+/// (adapter name, driver date as days since the Unix epoch)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_driver_dates(output: &str) -> Vec<(String, i64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(3, ',');
+            let _node = fields.next()?;
+            let date = fields.next()?;
+            let name = fields.next()?.trim();
+
+            // DriverDate is a CIM datetime: yyyymmddHHMMSS.mmmmmm+UUU
+            let year = date.get(0..4)?.parse().ok()?;
+            let month = date.get(4..6)?.parse().ok()?;
+            let day = date.get(6..8)?.parse().ok()?;
+            Some((name.to_string(), days_from_civil(year, month, day)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        let check = |id: &str, passed| item(id, id, passed, String::new(), None);
+
+        assert_eq!(score(&[]), 100);
+        assert_eq!(
+            score(&[check("power_plan", true), check("free_ram", false)]),
+            50
+        );
+        assert_eq!(
+            score(&[check("background_cpu", true), check("game_input", false)]),
+            88
+        );
+    }
+
+    #[test]
+    fn test_parse_active_scheme() {
+        assert_eq!(
+            parse_active_scheme(
+                "Power Scheme GUID: 8C5E7FDA-E8BF-4A96-9A85-A6E23A8C635C  (High performance)\r\n"
+            ),
+            Some((
+                "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c".to_string(),
+                "High performance".to_string()
+            ))
+        );
+        assert_eq!(parse_active_scheme(""), None);
+    }
+
+    #[test]
+    fn test_parse_driver_dates() {
+        let output = "\r\nNode,DriverDate,Name\r\nDESKTOP,20240315000000.000000-000,NVIDIA GeForce RTX 4070\r\n";
+        assert_eq!(
+            parse_driver_dates(output),
+            vec![("NVIDIA GeForce RTX 4070".to_string(), 19797)]
+        );
+    }
+}
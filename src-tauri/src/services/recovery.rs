@@ -0,0 +1,195 @@
+// Undoes temporary system changes Aura leaves behind when it stops
+// mid-session. Suspended processes and focus-mode mutes are journaled in
+// pending_reversions.json while they last, so the next start can put them
+// back after a crash. The power plan, the cooling profile, frame limits, QoS
+// policies and the Linux network block save what they replaced and are
+// restored from it. The timer resolution request and the Windows network
+// block filters die with the process. Everything is also released on exit,
+// and a panic runs the same cleanup before the process goes down.
+use crate::services::{
+    audio_service, cooling, frame_limiter, network_block, power_plan, process_control, qos,
+    timer_resolution,
+};
+use crate::shared::paths;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+const JOURNAL_FILE: &str = "pending_reversions.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Resume,
+    Unmute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Reversion {
+    action: Action,
+    pid: u32,
+    // Guards against the PID having been reused by the time it is replayed
+    start_time: u64,
+}
+
+static JOURNAL: once_cell::sync::Lazy<Mutex<Vec<Reversion>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Vec::new()));
+
+static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Reverts what a previous run left behind and installs the panic hook.
+/// Subsequent calls do nothing.
+pub fn start() {
+    if HOOK_INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    replay();
+    power_plan::restore_leftover();
+    cooling::restore_leftover();
+    frame_limiter::restore_leftover();
+    network_block::restore_leftover();
+    qos::restore_leftover();
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // Release builds abort on any panic, debug ones only go down with the
+        // main thread
+        if cfg!(panic = "abort") || std::thread::current().name() == Some("main") {
+            undo_all();
+        }
+        default_hook(info);
+    }));
+}
+
+/// Remembers a change to undo if Aura stops before reverting it
+pub fn track(action: Action, pid: u32) {
+    let Some(start_time) = start_time_of(pid) else {
+        return;
+    };
+    let Ok(mut journal) = JOURNAL.lock() else {
+        return;
+    };
+    let entry = Reversion {
+        action,
+        pid,
+        start_time,
+    };
+    if !journal.contains(&entry) {
+        journal.push(entry);
+        save(&journal);
+    }
+}
+
+/// Forgets a change once it has been reverted
+pub fn untrack(action: Action, pid: u32) {
+    let Ok(mut journal) = JOURNAL.lock() else {
+        return;
+    };
+    let before = journal.len();
+    journal.retain(|entry| !(entry.action == action && entry.pid == pid));
+    if journal.len() != before {
+        save(&journal);
+    }
+}
+
+/// Reverts everything still pending. Called on exit and from the panic hook,
+/// so nothing here may block on a lock the panicking thread could hold.
+pub fn undo_all() {
+    let pending = match JOURNAL.try_lock() {
+        Ok(mut journal) => std::mem::take(&mut *journal),
+        Err(_) => Vec::new(),
+    };
+    revert(&pending);
+    if let Ok(journal) = JOURNAL.try_lock() {
+        save(&journal);
+    }
+
+    power_plan::release_all();
+    cooling::release_all();
+    frame_limiter::release_all();
+    network_block::unblock_all();
+    qos::release_all();
+    timer_resolution::restore();
+}
+
+/// Reverts the entries a crashed run left in the journal
+fn replay() {
+    let path = paths::app_data_file(JOURNAL_FILE);
+    let pending: Vec<Reversion> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if pending.is_empty() {
+        return;
+    }
+
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    let alive: Vec<Reversion> = pending
+        .into_iter()
+        .filter(|entry| {
+            system
+                .process(Pid::from_u32(entry.pid))
+                .is_some_and(|process| process.start_time() == entry.start_time)
+        })
+        .collect();
+    revert(&alive);
+    let _ = std::fs::remove_file(path);
+}
+
+fn revert(entries: &[Reversion]) {
+    for entry in entries {
+        // The process may be gone already
+        match entry.action {
+            Action::Resume => {
+                let _ = process_control::resume_process(entry.pid);
+            }
+            Action::Unmute => {
+                let _ = audio_service::set_process_mute(entry.pid, false);
+            }
+        }
+    }
+}
+
+fn start_time_of(pid: u32) -> Option<u64> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    system.process(pid).map(|process| process.start_time())
+}
+
+fn save(journal: &[Reversion]) {
+    let path = paths::app_data_file(JOURNAL_FILE);
+    if journal.is_empty() {
+        let _ = std::fs::remove_file(path);
+    } else if let Ok(content) = serde_json::to_string(journal) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_format() {
+        let entry = Reversion {
+            action: Action::Resume,
+            pid: 42,
+            start_time: 1_700_000_000,
+        };
+        let content = serde_json::to_string(std::slice::from_ref(&entry)).unwrap();
+        assert_eq!(
+            content,
+            r#"[{"action":"resume","pid":42,"start_time":1700000000}]"#
+        );
+        let parsed: Vec<Reversion> = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed, vec![entry]);
+    }
+}
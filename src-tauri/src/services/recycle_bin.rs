@@ -0,0 +1,208 @@
+// Size of the Recycle Bin on each drive and emptying it. Deleted files keep
+// their space until the bin is emptied, often gigabytes of used space on a
+// games drive that no folder in Explorer accounts for. On Linux the same is
+// done for the freedesktop trash of the user: the one in the home folder and
+// the .Trash-<uid> folders at the root of other drives.
+use crate::models::system_stats::RecycleBinInfo;
+use sysinfo::Disks;
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use windows::core::PCWSTR;
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::Shell::{
+    SHEmptyRecycleBinW, SHQueryRecycleBinW, SHERB_NOCONFIRMATION, SHERB_NOPROGRESSUI,
+    SHERB_NOSOUND, SHQUERYRBINFO,
+};
+
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+
+#[derive(Error, Debug)]
+pub enum RecycleBinError {
+    #[error("No drive is mounted at {0}")]
+    UnknownDrive(String),
+
+    #[error("Failed to empty the Recycle Bin: {0}")]
+    EmptyError(String),
+}
+
+type Result<T> = std::result::Result<T, RecycleBinError>;
+
+/// The bins that hold something, by drive
+pub fn get_usage() -> Vec<RecycleBinInfo> {
+    mount_points()
+        .into_iter()
+        .filter_map(|drive| query(&drive))
+        .filter(|info| info.item_count > 0)
+        .collect()
+}
+
+/// Empties the bin of one drive, or of every drive without one. Returns the
+/// bytes freed.
+pub fn empty(drive: Option<&str>) -> Result<u64> {
+    let drives = match drive {
+        Some(drive) => {
+            if !mount_points().iter().any(|mount| mount == drive) {
+                return Err(RecycleBinError::UnknownDrive(drive.to_string()));
+            }
+            vec![drive.to_string()]
+        }
+        None => mount_points(),
+    };
+
+    let mut freed = 0;
+    for info in drives.iter().filter_map(|drive| query(drive)) {
+        // Windows reports a failure when asked to empty an empty bin
+        if info.item_count == 0 {
+            continue;
+        }
+        empty_drive(&info.drive)?;
+        freed += info.size_bytes;
+    }
+    Ok(freed)
+}
+
+fn mount_points() -> Vec<String> {
+    Disks::new_with_refreshed_list()
+        .iter()
+        .map(|disk| disk.mount_point().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn query(drive: &str) -> Option<RecycleBinInfo> {
+    let root = wide(drive);
+    let mut info = SHQUERYRBINFO {
+        cbSize: std::mem::size_of::<SHQUERYRBINFO>() as u32,
+        ..Default::default()
+    };
+    unsafe { SHQueryRecycleBinW(PCWSTR(root.as_ptr()), &mut info) }.ok()?;
+    Some(RecycleBinInfo {
+        drive: drive.to_string(),
+        size_bytes: info.i64Size.max(0) as u64,
+        item_count: info.i64NumItems.max(0) as u64,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn empty_drive(drive: &str) -> Result<()> {
+    let root = wide(drive);
+    unsafe {
+        SHEmptyRecycleBinW(
+            None,
+            PCWSTR(root.as_ptr()),
+            SHERB_NOCONFIRMATION | SHERB_NOPROGRESSUI | SHERB_NOSOUND,
+        )
+    }
+    .map_err(|e| RecycleBinError::EmptyError(e.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(target_os = "linux")]
+fn query(drive: &str) -> Option<RecycleBinInfo> {
+    let trashes = trash_dirs(drive);
+    if trashes.is_empty() {
+        return None;
+    }
+    let (size_bytes, item_count) = trashes
+        .iter()
+        .filter_map(|trash| std::fs::read_dir(trash.join("files")).ok())
+        .flat_map(|entries| entries.flatten())
+        .fold((0, 0), |(size, count), entry| {
+            (size + size_on_disk(&entry.path()), count + 1)
+        });
+    Some(RecycleBinInfo {
+        drive: drive.to_string(),
+        size_bytes,
+        item_count,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn empty_drive(drive: &str) -> Result<()> {
+    for trash in trash_dirs(drive) {
+        // The deleted files, their .trashinfo records and the size cache
+        for folder in ["files", "info", "expunged"] {
+            let Ok(entries) = std::fs::read_dir(trash.join(folder)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let removed = if path.is_dir() && !path.is_symlink() {
+                    std::fs::remove_dir_all(&path)
+                } else {
+                    std::fs::remove_file(&path)
+                };
+                removed.map_err(|e| {
+                    RecycleBinError::EmptyError(format!("{}: {}", path.display(), e))
+                })?;
+            }
+        }
+        let _ = std::fs::remove_file(trash.join("directorysizes"));
+    }
+    Ok(())
+}
+
+/// Trash folders of the user that live on the drive mounted at `drive`
+#[cfg(target_os = "linux")]
+fn trash_dirs(drive: &str) -> Vec<PathBuf> {
+    let uid = unsafe { libc::getuid() };
+    let mount = Path::new(drive);
+    let mut trashes = vec![
+        mount.join(".Trash").join(uid.to_string()),
+        mount.join(format!(".Trash-{}", uid)),
+    ];
+
+    // The home trash belongs to the drive with the longest matching mount
+    // point, "/" holds it only when /home is not a separate mount
+    let home_trash = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|data| data.join("Trash"));
+    if let Some(home_trash) = home_trash {
+        let mounts = mount_points();
+        let owner = mounts
+            .iter()
+            .filter(|mount| home_trash.starts_with(mount))
+            .max_by_key(|mount| mount.len());
+        if owner.map(String::as_str) == Some(drive) {
+            trashes.push(home_trash);
+        }
+    }
+    trashes.retain(|trash| trash.is_dir());
+    trashes
+}
+
+/// Total size of a file or folder, links are not followed
+#[cfg(target_os = "linux")]
+fn size_on_disk(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| size_on_disk(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn query(_drive: &str) -> Option<RecycleBinInfo> {
+    None
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn empty_drive(_drive: &str) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,207 @@
+// Paired-agent mode: this Aura watches another one on the LAN. The agent
+// streams snapshots over a WebSocket (/ws/stats) on its pairing port, behind
+// a pairing token that can read stats and nothing else; the viewer keeps the
+// latest snapshot and reconnects on its own when the link drops.
+use crate::models::system_stats::{RemoteAgent, RemoteMonitorStatus, RemoteSnapshot};
+use crate::shared::{events, paths};
+use anyhow::{anyhow, Result};
+use std::sync::Mutex;
+
+#[cfg(feature = "remote-monitor")]
+use crate::commands::{cpu, gpu, memory, network, storage, system};
+#[cfg(feature = "remote-monitor")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "remote-monitor")]
+use std::time::Duration;
+
+const AGENT_FILE: &str = "remote_agent.json";
+#[cfg(feature = "remote-monitor")]
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+#[cfg(feature = "remote-monitor")]
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct RemoteState {
+    agent: Option<RemoteAgent>,
+    connected: bool,
+    last_error: Option<String>,
+    snapshot: Option<RemoteSnapshot>,
+}
+
+static STATE: once_cell::sync::Lazy<Mutex<RemoteState>> = once_cell::sync::Lazy::new(|| {
+    Mutex::new(RemoteState {
+        agent: load_agent(),
+        ..Default::default()
+    })
+});
+
+// Bumped on every pair and unpair; a viewer thread stops once it is stale
+#[cfg(feature = "remote-monitor")]
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Stats of this machine as streamed to paired viewers. Subsystems that fail
+/// are left out.
+#[cfg(feature = "remote-monitor")]
+pub fn snapshot() -> RemoteSnapshot {
+    let stats = [
+        cpu::get_cpu_stats(),
+        Ok(memory::get_memory_stats()),
+        storage::get_storage_stats(),
+        network::get_network_stats(),
+        system::get_system_stats(),
+    ];
+    RemoteSnapshot {
+        host_name: sysinfo::System::host_name().unwrap_or_default(),
+        timestamp: events::now_millis(),
+        stats: stats.into_iter().flatten().collect(),
+        gpu: gpu::get_gpu_stats().ok(),
+    }
+}
+
+/// Reconnects to the agent paired last time. Called once at startup.
+pub fn start() {
+    #[cfg(feature = "remote-monitor")]
+    if let Some(agent) = with_state(|state| state.agent.clone()).flatten() {
+        spawn_viewer(agent);
+    }
+}
+
+pub fn get_status() -> RemoteMonitorStatus {
+    with_state(|state| RemoteMonitorStatus {
+        host: state.agent.as_ref().map(|agent| agent.host.clone()),
+        port: state.agent.as_ref().map(|agent| agent.port),
+        connected: state.connected,
+        last_error: state.last_error.clone(),
+        snapshot: state.snapshot.clone(),
+        available: cfg!(feature = "remote-monitor"),
+    })
+    .unwrap_or(RemoteMonitorStatus {
+        host: None,
+        port: None,
+        connected: false,
+        last_error: None,
+        snapshot: None,
+        available: cfg!(feature = "remote-monitor"),
+    })
+}
+
+/// Pairs with an agent, replacing the previous one, and starts watching it
+pub fn pair(agent: RemoteAgent) -> Result<RemoteMonitorStatus> {
+    if !cfg!(feature = "remote-monitor") {
+        return Err(anyhow!(
+            "This build of Aura does not include remote monitoring"
+        ));
+    }
+    if agent.host.trim().is_empty() || agent.token.is_empty() {
+        return Err(anyhow!("Host and token are required to pair"));
+    }
+
+    let content = serde_json::to_string_pretty(&agent)?;
+    std::fs::write(paths::app_data_file(AGENT_FILE), content)?;
+    with_state(|state| {
+        *state = RemoteState {
+            agent: Some(agent.clone()),
+            ..Default::default()
+        }
+    });
+    #[cfg(feature = "remote-monitor")]
+    spawn_viewer(agent);
+    Ok(get_status())
+}
+
+pub fn unpair() -> Result<RemoteMonitorStatus> {
+    #[cfg(feature = "remote-monitor")]
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let path = paths::app_data_file(AGENT_FILE);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    with_state(|state| *state = RemoteState::default());
+    emit_status();
+    Ok(get_status())
+}
+
+fn load_agent() -> Option<RemoteAgent> {
+    std::fs::read_to_string(paths::app_data_file(AGENT_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+fn with_state<T>(f: impl FnOnce(&mut RemoteState) -> T) -> Option<T> {
+    STATE.lock().ok().map(|mut state| f(&mut state))
+}
+
+#[cfg(feature = "remote-monitor")]
+fn spawn_viewer(agent: RemoteAgent) {
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    std::thread::spawn(move || {
+        while GENERATION.load(Ordering::SeqCst) == generation {
+            let result = watch(&agent, generation);
+            if GENERATION.load(Ordering::SeqCst) != generation {
+                break;
+            }
+            with_state(|state| {
+                state.connected = false;
+                state.last_error = result.err().map(|e| e.to_string());
+            });
+            emit_status();
+            std::thread::sleep(RECONNECT_DELAY);
+        }
+    });
+}
+
+/// Reads snapshots until the connection drops or the agent is unpaired
+#[cfg(feature = "remote-monitor")]
+fn watch(agent: &RemoteAgent, generation: u64) -> Result<()> {
+    use tungstenite::client::IntoClientRequest;
+    use tungstenite::http::{header, HeaderValue};
+    use tungstenite::stream::MaybeTlsStream;
+    use tungstenite::Message;
+
+    let mut request =
+        format!("ws://{}:{}/ws/stats", agent.host, agent.port).into_client_request()?;
+    request.headers_mut().insert(
+        header::AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", agent.token))?,
+    );
+    let (mut socket, _) = tungstenite::connect(request)?;
+    // Wakes up regularly to notice an unpair while the agent is silent
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    }
+    with_state(|state| {
+        state.connected = true;
+        state.last_error = None;
+    });
+    emit_status();
+
+    while GENERATION.load(Ordering::SeqCst) == generation {
+        let message = match socket.read() {
+            Ok(message) => message,
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(e) => return Err(e.into()),
+        };
+        match message {
+            Message::Text(text) => {
+                let snapshot: RemoteSnapshot = serde_json::from_str(text.as_str())?;
+                with_state(|state| state.snapshot = Some(snapshot));
+                emit_status();
+            }
+            Message::Close(_) => return Err(anyhow!("The agent closed the connection")),
+            _ => {}
+        }
+    }
+    let _ = socket.close(None);
+    Ok(())
+}
+
+fn emit_status() {
+    events::emit(events::REMOTE_STATS_UPDATED, get_status());
+}
@@ -0,0 +1,195 @@
+// Opt-in REST API on localhost mirroring the Tauri commands, for tools like
+// Home Assistant or stream decks. Every request needs the bearer token from
+// the settings, and the API only listens on 127.0.0.1. Allowing LAN access
+// opens a second listener, one port up, that serves nothing but the stats
+// stream for pairing another Aura, behind a read-only pairing token: the
+// full token never has to leave the machine.
+use crate::models::system_stats::{RestApiConfig, RestApiStatus};
+use crate::shared::paths;
+use anyhow::{anyhow, Result};
+use rand::Rng;
+use std::sync::Mutex;
+
+#[cfg(feature = "rest-api")]
+mod server;
+
+const CONFIG_FILE: &str = "rest_api.json";
+const TOKEN_BYTES: usize = 32;
+
+static CONFIG: once_cell::sync::Lazy<Mutex<RestApiConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+#[cfg(feature = "rest-api")]
+static SERVER: Mutex<Option<server::Server>> = Mutex::new(None);
+
+/// Starts the server when it was left enabled. Called once at startup.
+pub fn start() {
+    let config = get_config();
+    if config.enabled {
+        if let Err(e) = restart(&config) {
+            eprintln!("REST API not started: {}", e);
+        }
+    }
+}
+
+pub fn get_status() -> RestApiStatus {
+    let config = get_config();
+    RestApiStatus {
+        pairing_port: config.allow_lan.then(|| pairing_port(config.port)),
+        config,
+        running: is_running(),
+        available: cfg!(feature = "rest-api"),
+    }
+}
+
+/// Port of the LAN stats stream, the one above the API's
+fn pairing_port(port: u16) -> u16 {
+    port.saturating_add(1)
+}
+
+/// Enables or disables the API and moves it to another port. The tokens are
+/// generated the first time they are needed.
+pub fn set_config(enabled: bool, port: u16, allow_lan: bool) -> Result<RestApiStatus> {
+    if enabled && !cfg!(feature = "rest-api") {
+        return Err(anyhow!("This build of Aura does not include the REST API"));
+    }
+    if port < 1024 || (allow_lan && port == u16::MAX) {
+        return Err(anyhow!("Invalid port: {}", port));
+    }
+
+    let mut config = get_config();
+    config.enabled = enabled;
+    config.port = port;
+    config.allow_lan = allow_lan;
+    if config.token.is_empty() {
+        config.token = generate_token();
+    }
+    if allow_lan && config.pairing_token.is_empty() {
+        config.pairing_token = generate_token();
+    }
+    save_config(&config)?;
+    restart(&config)?;
+    Ok(get_status())
+}
+
+/// Replaces both tokens; clients and paired viewers using the old ones are
+/// rejected from now on
+pub fn regenerate_token() -> Result<RestApiStatus> {
+    let mut config = get_config();
+    config.token = generate_token();
+    if !config.pairing_token.is_empty() {
+        config.pairing_token = generate_token();
+    }
+    save_config(&config)?;
+    Ok(get_status())
+}
+
+fn get_config() -> RestApiConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+fn load_config() -> RestApiConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+fn save_config(config: &RestApiConfig) -> Result<()> {
+    paths::save_json(CONFIG_FILE, config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    Ok(())
+}
+
+fn generate_token() -> String {
+    let bytes: [u8; TOKEN_BYTES] = rand::rng().random();
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Whether a request's token is the configured one. Compares in constant
+/// time, and an empty token never matches.
+#[cfg(feature = "rest-api")]
+pub(crate) fn token_matches(token: &str) -> bool {
+    tokens_equal(&get_config().token, token)
+}
+
+/// Same as `token_matches` for the read-only pairing token
+#[cfg(feature = "remote-monitor")]
+pub(crate) fn pairing_token_matches(token: &str) -> bool {
+    tokens_equal(&get_config().pairing_token, token)
+}
+
+#[cfg(any(feature = "rest-api", test))]
+fn tokens_equal(expected: &str, token: &str) -> bool {
+    if expected.is_empty() || expected.len() != token.len() {
+        return false;
+    }
+    expected
+        .bytes()
+        .zip(token.bytes())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+#[cfg(feature = "rest-api")]
+fn restart(config: &RestApiConfig) -> Result<()> {
+    let mut running = SERVER.lock().map_err(|e| anyhow!(e.to_string()))?;
+    if let Some(server) = running.take() {
+        server.stop();
+    }
+    if config.enabled {
+        let pairing_port = config.allow_lan.then(|| pairing_port(config.port));
+        *running = Some(server::Server::spawn(config.port, pairing_port)?);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "rest-api"))]
+fn restart(_config: &RestApiConfig) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "rest-api")]
+fn is_running() -> bool {
+    SERVER
+        .lock()
+        .map(|server| server.is_some())
+        .unwrap_or(false)
+}
+
+#[cfg(not(feature = "rest-api"))]
+fn is_running() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_equal() {
+        assert!(tokens_equal("abc123", "abc123"));
+        assert!(!tokens_equal("abc123", "abc124"));
+        assert!(!tokens_equal("abc123", "abc"));
+        assert!(!tokens_equal("", ""));
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token();
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn test_pairing_token_is_not_the_api_token() {
+        let config: RestApiConfig = serde_json::from_str(
+            r#"{"enabled": true, "port": 7878, "token": "abc", "allow_lan": true}"#,
+        )
+        .unwrap();
+        assert!(config.pairing_token.is_empty());
+        assert!(!tokens_equal(&config.pairing_token, ""));
+        assert_eq!(pairing_port(config.port), 7879);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::commands::{cpu, gpu, memory, network, processes, storage, system};
+use crate::services::optimization_engine::ENGINE;
+use anyhow::{anyhow, Result};
+use axum::extract::{Path, Request};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use serde_json::Value;
+use std::net::{Ipv4Addr, TcpListener};
+use tokio::sync::oneshot;
+
+#[cfg(feature = "remote-monitor")]
+const STREAM_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// A running server; dropping the senders is enough to stop it too
+pub struct Server {
+    shutdown: Vec<oneshot::Sender<()>>,
+}
+
+impl Server {
+    /// Binds the ports right away, so a port in use is reported to the caller,
+    /// then serves from background threads with their own runtime. The API
+    /// listens on localhost only; `pairing_port` opens the stats stream, and
+    /// nothing else, to the LAN.
+    pub fn spawn(port: u16, pairing_port: Option<u16>) -> Result<Self> {
+        let mut listeners = vec![(bind(Ipv4Addr::LOCALHOST, port)?, router())];
+        if let Some(pairing_port) = pairing_port {
+            listeners.push((bind(Ipv4Addr::UNSPECIFIED, pairing_port)?, pairing_router()));
+        }
+
+        let mut shutdown = Vec::new();
+        for (listener, router) in listeners {
+            let (sender, signal) = oneshot::channel::<()>();
+            serve(listener, router, signal)?;
+            shutdown.push(sender);
+        }
+        Ok(Self { shutdown })
+    }
+
+    pub fn stop(self) {
+        for shutdown in self.shutdown {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+fn bind(address: Ipv4Addr, port: u16) -> Result<TcpListener> {
+    let listener = TcpListener::bind((address, port))
+        .map_err(|e| anyhow!("Cannot listen on port {}: {}", port, e))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+fn serve(listener: TcpListener, router: Router, signal: oneshot::Receiver<()>) -> Result<()> {
+    std::thread::Builder::new()
+        .name("rest-api".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    eprintln!("REST API runtime failed to start: {}", e);
+                    return;
+                }
+            };
+            runtime.block_on(async move {
+                let listener = match tokio::net::TcpListener::from_std(listener) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        eprintln!("REST API failed to listen: {}", e);
+                        return;
+                    }
+                };
+                let result = axum::serve(listener, router)
+                    .with_graceful_shutdown(async {
+                        let _ = signal.await;
+                    })
+                    .await;
+                if let Err(e) = result {
+                    eprintln!("REST API stopped: {}", e);
+                }
+            });
+        })?;
+    Ok(())
+}
+
+fn router() -> Router {
+    let router = Router::new()
+        .route("/stats/{kind}", get(get_stats))
+        .route("/processes", get(get_processes))
+        .route("/process/{pid}/{action}", post(control_process))
+        .route("/optimizations", get(get_optimizations))
+        .route("/optimizations/{id}/{action}", post(change_optimization));
+    #[cfg(feature = "remote-monitor")]
+    let router = router.route("/ws/stats", get(stream_stats));
+    router.layer(middleware::from_fn(require_token))
+}
+
+/// What the LAN sees: the stats stream, behind the pairing token
+fn pairing_router() -> Router {
+    let router = Router::new();
+    #[cfg(feature = "remote-monitor")]
+    let router = router
+        .route("/ws/stats", get(stream_stats))
+        .layer(middleware::from_fn(require_pairing_token));
+    router
+}
+
+fn bearer_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+fn unauthorized() -> Response {
+    ApiError(
+        StatusCode::UNAUTHORIZED,
+        "Missing or invalid token".to_string(),
+    )
+    .into_response()
+}
+
+#[cfg(feature = "remote-monitor")]
+async fn require_pairing_token(request: Request, next: Next) -> Response {
+    if bearer_token(&request).is_some_and(super::pairing_token_matches) {
+        next.run(request).await
+    } else {
+        unauthorized()
+    }
+}
+
+async fn require_token(request: Request, next: Next) -> Response {
+    if bearer_token(&request).is_some_and(super::token_matches) {
+        next.run(request).await
+    } else {
+        unauthorized()
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult = std::result::Result<Json<Value>, ApiError>;
+
+fn json<T: Serialize>(value: T) -> ApiResult {
+    serde_json::to_value(value)
+        .map(Json)
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+fn failed(error: impl ToString) -> ApiError {
+    ApiError(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+}
+
+fn not_found(what: &str) -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, format!("Unknown {}", what))
+}
+
+async fn get_stats(Path(kind): Path<String>) -> ApiResult {
+    // The cpu and network stats sleep between two samples, the gpu ones go
+    // through the drivers
+    blocking(move || match kind.as_str() {
+        "cpu" => json(cpu::get_cpu_stats().map_err(failed)?),
+        "memory" => json(memory::get_memory_stats()),
+        "storage" => json(storage::get_storage_stats().map_err(failed)?),
+        "network" => json(network::get_network_stats().map_err(failed)?),
+        "system" => json(system::get_system_stats().map_err(failed)?),
+        "gpu" => json(gpu::get_gpu_stats().map_err(failed)?),
+        _ => Err(not_found("stats")),
+    })
+    .await?
+}
+
+async fn get_processes() -> ApiResult {
+    let filter = processes::FrontendProcessFilter::default();
+    json(
+        processes::get_running_processes(filter)
+            .await
+            .map_err(failed)?,
+    )
+}
+
+async fn control_process(Path((pid, action)): Path<(u32, String)>) -> ApiResult {
+    if !["suspend", "resume", "kill", "boost"].contains(&action.as_str()) {
+        return Err(not_found("action"));
+    }
+    let name = action.clone();
+    blocking(move || match name.as_str() {
+        "suspend" => processes::suspend_process(pid),
+        "resume" => processes::resume_process(pid),
+        "kill" => processes::kill_process(pid),
+        _ => processes::boost_process_for_gaming(pid, None),
+    })
+    .await?
+    .map_err(failed)?;
+    json(serde_json::json!({ "pid": pid, "action": action }))
+}
+
+async fn get_optimizations() -> ApiResult {
+    let optimizations = blocking(|| {
+        let engine = ENGINE.lock().map_err(|e| e.to_string())?;
+        engine
+            .get_available_optimizations()
+            .map_err(|e| e.to_string())
+    })
+    .await?
+    .map_err(failed)?;
+    json(optimizations)
+}
+
+async fn change_optimization(Path((id, action)): Path<(String, String)>) -> ApiResult {
+    let apply = match action.as_str() {
+        "apply" => true,
+        "revert" => false,
+        _ => return Err(not_found("action")),
+    };
+    let result = blocking(move || {
+        let engine = ENGINE.lock().map_err(|e| e.to_string())?;
+        let result = if apply {
+            engine.apply(&id)
+        } else {
+            engine.revert(&id)
+        };
+        result.map_err(|e| e.to_string())
+    })
+    .await?
+    .map_err(failed)?;
+    json(result)
+}
+
+/// Runs stats, engine and process work on the blocking pool, so a slow
+/// sample or optimization doesn't hold up the runtime every other client is
+/// served from
+async fn blocking<T, F>(work: F) -> std::result::Result<T, ApiError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work).await.map_err(failed)
+}
+
+/// Streams a snapshot of this machine every few seconds to a paired Aura
+#[cfg(feature = "remote-monitor")]
+async fn stream_stats(upgrade: axum::extract::ws::WebSocketUpgrade) -> Response {
+    use crate::services::remote_monitor;
+    use axum::extract::ws::Message;
+
+    upgrade.on_upgrade(|mut socket| async move {
+        loop {
+            let Ok(snapshot) = tokio::task::spawn_blocking(remote_monitor::snapshot).await else {
+                break;
+            };
+            let Ok(text) = serde_json::to_string(&snapshot) else {
+                break;
+            };
+            if socket.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+            tokio::time::sleep(STREAM_INTERVAL).await;
+        }
+    })
+}
@@ -0,0 +1,202 @@
+// Which processes host system services: on Windows the ones the Service
+// Control Manager started (one svchost.exe often runs several services), on
+// Linux the processes of systemd system units. Killing or suspending one takes
+// its services down with it, so the process list points at the services
+// instead. The list is read at most every few seconds, however many rows ask.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "windows")]
+use windows::core::PCWSTR;
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Services::{
+    CloseServiceHandle, EnumServicesStatusExW, OpenSCManagerW, ENUM_SERVICE_STATUS_PROCESSW,
+    SC_ENUM_PROCESS_INFO, SC_MANAGER_ENUMERATE_SERVICE, SERVICE_ACTIVE, SERVICE_WIN32,
+};
+
+const CACHE_TTL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HostedService {
+    pub name: String, // service or systemd unit name, what the services view is keyed by
+    pub display_name: String,
+}
+
+type ServiceMap = HashMap<u32, Vec<HostedService>>;
+
+static SERVICES: Mutex<Option<(Instant, ServiceMap)>> = Mutex::new(None);
+
+/// Services running in the process, empty for ordinary processes
+pub fn services_of(pid: u32) -> Vec<HostedService> {
+    let Ok(mut cache) = SERVICES.lock() else {
+        return Vec::new();
+    };
+    if cache
+        .as_ref()
+        .is_none_or(|(read_at, _)| read_at.elapsed() >= CACHE_TTL)
+    {
+        *cache = Some((Instant::now(), read_services()));
+    }
+    cache
+        .as_ref()
+        .and_then(|(_, services)| services.get(&pid).cloned())
+        .unwrap_or_default()
+}
+
+/// Session 0 holds services and system processes, apart from the desktop
+/// sessions of the users
+pub fn is_service_process(session_id: Option<u32>, services: &[HostedService]) -> bool {
+    (cfg!(target_os = "windows") && session_id == Some(0)) || !services.is_empty()
+}
+
+#[cfg(target_os = "windows")]
+fn read_services() -> ServiceMap {
+    let mut services = ServiceMap::new();
+    let Ok(manager) =
+        (unsafe { OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE) })
+    else {
+        return services;
+    };
+
+    let mut needed = 0u32;
+    let mut returned = 0u32;
+    let _ = unsafe {
+        EnumServicesStatusExW(
+            manager,
+            SC_ENUM_PROCESS_INFO,
+            SERVICE_WIN32,
+            SERVICE_ACTIVE,
+            None,
+            &mut needed,
+            &mut returned,
+            None,
+            PCWSTR::null(),
+        )
+    };
+    // Services can start between the two calls
+    for _ in 0..3 {
+        // u64 so the entries, which hold pointers, are aligned
+        let mut buffer = vec![0u64; (needed as usize).div_ceil(8).max(1)];
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8)
+        };
+        let result = unsafe {
+            EnumServicesStatusExW(
+                manager,
+                SC_ENUM_PROCESS_INFO,
+                SERVICE_WIN32,
+                SERVICE_ACTIVE,
+                Some(bytes),
+                &mut needed,
+                &mut returned,
+                None,
+                PCWSTR::null(),
+            )
+        };
+        if result.is_err() {
+            continue;
+        }
+
+        let entries = unsafe {
+            std::slice::from_raw_parts(
+                buffer.as_ptr() as *const ENUM_SERVICE_STATUS_PROCESSW,
+                returned as usize,
+            )
+        };
+        for entry in entries {
+            let pid = entry.ServiceStatusProcess.dwProcessId;
+            if pid == 0 {
+                continue;
+            }
+            services.entry(pid).or_default().push(HostedService {
+                name: unsafe { entry.lpServiceName.to_string() }.unwrap_or_default(),
+                display_name: unsafe { entry.lpDisplayName.to_string() }.unwrap_or_default(),
+            });
+        }
+        break;
+    }
+    let _ = unsafe { CloseServiceHandle(manager) };
+    services
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_services() -> ServiceMap {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return ServiceMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let cgroup = std::fs::read_to_string(entry.path().join("cgroup")).ok()?;
+            let unit = system_unit(&cgroup)?;
+            let display_name = unit.trim_end_matches(".service").to_string();
+            Some((
+                pid,
+                vec![HostedService {
+                    name: unit,
+                    display_name,
+                }],
+            ))
+        })
+        .collect()
+}
+
+/// The systemd system service of a /proc/<pid>/cgroup listing, like
+/// sshd.service for 0::/system.slice/sshd.service. Units of the user manager
+/// live below user.slice and are not services of the system.
+#[cfg_attr(all(target_os = "windows", not(test)), allow(dead_code))]
+fn system_unit(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        // hierarchy-id:controllers:path, the systemd one is named or unified
+        let path = line.splitn(3, ':').nth(2)?;
+        let path = path.strip_prefix("/system.slice/")?;
+        path.split('/')
+            .find(|part| part.ends_with(".service"))
+            .map(|unit| unit.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_unit() {
+        assert_eq!(
+            system_unit("0::/system.slice/sshd.service\n").as_deref(),
+            Some("sshd.service")
+        );
+        // Templated units inside a sub-slice
+        assert_eq!(
+            system_unit("0::/system.slice/system-getty.slice/getty@tty1.service").as_deref(),
+            Some("getty@tty1.service")
+        );
+        // cgroup v1 lists the systemd hierarchy among the controllers
+        let v1 = "12:cpu,cpuacct:/\n1:name=systemd:/system.slice/docker.service\n";
+        assert_eq!(system_unit(v1).as_deref(), Some("docker.service"));
+
+        assert_eq!(
+            system_unit("0::/user.slice/user-1000.slice/user@1000.service/app.slice/steam.scope"),
+            None
+        );
+        assert_eq!(system_unit("0::/init.scope"), None);
+    }
+
+    #[test]
+    fn test_is_service_process() {
+        let service = HostedService {
+            name: "Audiosrv".to_string(),
+            display_name: "Windows Audio".to_string(),
+        };
+        assert!(is_service_process(Some(1), &[service]));
+        assert!(!is_service_process(Some(1), &[]));
+        assert!(!is_service_process(None, &[]));
+        assert_eq!(
+            is_service_process(Some(0), &[]),
+            cfg!(target_os = "windows")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::models::session::{
+    GameExitRecord, GpuCoolingSample, HardwareChange, HardwareChangeKind, SessionReport,
+};
+use crate::shared::events;
+use std::sync::Mutex;
+
+// How far back the GPU cooling samples go
+const COOLING_KEPT_MS: u64 = 30 * 60 * 1000;
+// A flapping device shouldn't grow the report without end
+const HARDWARE_CHANGES_KEPT: usize = 500;
+// A GPU reset can be noticed a little after the crash it caused
+const LATE_GPU_RESET_MS: u64 = 30 * 1000;
+
+static SESSION_REPORT: once_cell::sync::Lazy<Mutex<SessionReport>> =
+    once_cell::sync::Lazy::new(|| {
+        Mutex::new(SessionReport {
+            started_at: events::now_millis(),
+            ..Default::default()
+        })
+    });
+
+/// Applies a change to the session report
+pub fn update<F: FnOnce(&mut SessionReport)>(f: F) {
+    if let Ok(mut report) = SESSION_REPORT.lock() {
+        f(&mut report);
+    }
+}
+
+pub fn record_game_exit(record: GameExitRecord) {
+    update(|report| {
+        report.tracked_games.retain(|g| g.pid != record.pid);
+        report.game_exits.push(record);
+    });
+}
+
+/// Adds GPU temperatures and fans, dropping the samples that got too old
+pub fn record_gpu_cooling(samples: Vec<GpuCoolingSample>) {
+    let oldest = events::now_millis().saturating_sub(COOLING_KEPT_MS);
+    update(|report| {
+        report.gpu_cooling.extend(samples);
+        report.gpu_cooling.retain(|sample| sample.at >= oldest);
+    });
+}
+
+/// GPU cooling samples taken at or after `since` (Unix millis)
+pub fn gpu_cooling_since(since: u64) -> Vec<GpuCoolingSample> {
+    SESSION_REPORT
+        .lock()
+        .map(|report| {
+            report
+                .gpu_cooling
+                .iter()
+                .filter(|sample| sample.at >= since)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Adds a hardware change. A GPU reset noticed just after a game crashed is
+/// attached to that crash too.
+pub fn record_hardware_change(change: HardwareChange) {
+    update(|report| {
+        if change.kind == HardwareChangeKind::GpuReset {
+            for exit in report.game_exits.iter_mut().filter(|exit| {
+                exit.crashed && change.at.saturating_sub(exit.exited_at) <= LATE_GPU_RESET_MS
+            }) {
+                exit.hardware_changes.push(change.clone());
+            }
+        }
+        report.hardware_changes.push(change);
+        let excess = report
+            .hardware_changes
+            .len()
+            .saturating_sub(HARDWARE_CHANGES_KEPT);
+        report.hardware_changes.drain(..excess);
+    });
+}
+
+/// Hardware changes noticed at or after `since` (Unix millis)
+pub fn hardware_changes_since(since: u64) -> Vec<HardwareChange> {
+    SESSION_REPORT
+        .lock()
+        .map(|report| {
+            report
+                .hardware_changes
+                .iter()
+                .filter(|change| change.at >= since)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Snapshot of the current session report
+pub fn get_report() -> SessionReport {
+    SESSION_REPORT
+        .lock()
+        .map(|report| report.clone())
+        .unwrap_or_default()
+}
+
+/// Starts a fresh report, keeping the games that are still being tracked
+pub fn reset() {
+    update(|report| {
+        *report = SessionReport {
+            started_at: events::now_millis(),
+            tracked_games: std::mem::take(&mut report.tracked_games),
+            ..Default::default()
+        };
+    });
+}
@@ -0,0 +1,171 @@
+// Reads the SMBIOS tables the firmware exposes: GetSystemFirmwareTable on
+// Windows, /sys/firmware/dmi/tables/DMI on Linux (readable by root only).
+// Only the Memory Device structures (type 17) are decoded for now.
+use crate::models::benchmark::MemoryModuleSpeed;
+use anyhow::{anyhow, Result};
+
+const MEMORY_DEVICE: u8 = 17;
+const END_OF_TABLE: u8 = 127;
+
+/// Populated memory slots with their rated and configured speeds
+pub fn memory_modules() -> Result<Vec<MemoryModuleSpeed>> {
+    Ok(parse_memory_modules(&read_table()?))
+}
+
+#[cfg(target_os = "windows")]
+fn read_table() -> Result<Vec<u8>> {
+    use windows::Win32::System::SystemInformation::{
+        GetSystemFirmwareTable, FIRMWARE_TABLE_PROVIDER,
+    };
+
+    // 'RSMB', the raw SMBIOS provider
+    let provider = FIRMWARE_TABLE_PROVIDER(u32::from_be_bytes(*b"RSMB"));
+    let size = unsafe { GetSystemFirmwareTable(provider, 0, None) };
+    if size == 0 {
+        return Err(anyhow!("The firmware does not expose SMBIOS tables"));
+    }
+    let mut buffer = vec![0u8; size as usize];
+    let written = unsafe { GetSystemFirmwareTable(provider, 0, Some(&mut buffer)) };
+    // RawSMBIOSData: 4 bytes of version info and the table length, then the table
+    if written < 8 {
+        return Err(anyhow!("Truncated SMBIOS data"));
+    }
+    let length = u32::from_le_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]) as usize;
+    Ok(buffer
+        .get(8..8 + length)
+        .ok_or_else(|| anyhow!("Truncated SMBIOS data"))?
+        .to_vec())
+}
+
+#[cfg(target_os = "linux")]
+fn read_table() -> Result<Vec<u8>> {
+    std::fs::read("/sys/firmware/dmi/tables/DMI")
+        .map_err(|e| anyhow!("Unable to read the SMBIOS tables: {}", e))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn read_table() -> Result<Vec<u8>> {
+    Err(anyhow!("SMBIOS tables are not available on this platform"))
+}
+
+/// Walks the structures of a raw SMBIOS table
+fn structures(table: &[u8]) -> Vec<(&[u8], Vec<String>)> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while offset + 4 <= table.len() {
+        let kind = table[offset];
+        let length = table[offset + 1] as usize;
+        if length < 4 || offset + length > table.len() {
+            break;
+        }
+        let formatted = &table[offset..offset + length];
+
+        // Strings follow the formatted area and end with a double NUL
+        let mut end = offset + length;
+        while end + 1 < table.len() && !(table[end] == 0 && table[end + 1] == 0) {
+            end += 1;
+        }
+        let strings = table[offset + length..end]
+            .split(|byte| *byte == 0)
+            .filter(|s| !s.is_empty())
+            .map(|s| String::from_utf8_lossy(s).trim().to_string())
+            .collect();
+
+        found.push((formatted, strings));
+        if kind == END_OF_TABLE {
+            break;
+        }
+        offset = end + 2;
+    }
+    found
+}
+
+fn parse_memory_modules(table: &[u8]) -> Vec<MemoryModuleSpeed> {
+    let word = |data: &[u8], at: usize| {
+        data.get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as u32)
+    };
+    let dword = |data: &[u8], at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    // 0 is unknown, 0xFFFF defers to the 32-bit extended field
+    let speed = |data: &[u8], at: usize, extended_at: usize| match word(data, at) {
+        Some(0xFFFF) => dword(data, extended_at).filter(|v| *v != 0),
+        Some(0) | None => None,
+        value => value,
+    };
+
+    structures(table)
+        .into_iter()
+        .filter(|(data, _)| data[0] == MEMORY_DEVICE)
+        // Size 0 is an empty slot
+        .filter(|(data, _)| word(data, 0x0C).is_some_and(|size| size != 0))
+        .map(|(data, strings)| {
+            let string = |at: usize| {
+                data.get(at)
+                    .filter(|index| **index > 0)
+                    .and_then(|index| strings.get(*index as usize - 1).cloned())
+            };
+            MemoryModuleSpeed {
+                locator: string(0x10).unwrap_or_default(),
+                memory_type: data.get(0x12).and_then(|kind| memory_type_name(*kind)),
+                rated_mts: speed(data, 0x15, 0x54),
+                configured_mts: speed(data, 0x20, 0x58),
+            }
+        })
+        .collect()
+}
+
+fn memory_type_name(kind: u8) -> Option<String> {
+    let name = match kind {
+        0x18 => "DDR3",
+        0x1A => "DDR4",
+        0x1B => "LPDDR",
+        0x1C => "LPDDR2",
+        0x1D => "LPDDR3",
+        0x1E => "LPDDR4",
+        0x22 => "DDR5",
+        0x23 => "LPDDR5",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_device(size: u16, kind: u8, speed: u16, configured: u16) -> Vec<u8> {
+        let mut data = vec![0u8; 0x28];
+        data[0] = MEMORY_DEVICE;
+        data[1] = 0x28;
+        data[0x0C..0x0E].copy_from_slice(&size.to_le_bytes());
+        data[0x10] = 1; // device locator, first string
+        data[0x12] = kind;
+        data[0x15..0x17].copy_from_slice(&speed.to_le_bytes());
+        data[0x20..0x22].copy_from_slice(&configured.to_le_bytes());
+        data.extend_from_slice(b"DIMM_A1\0\0");
+        data
+    }
+
+    #[test]
+    fn test_parse_memory_modules() {
+        let mut table = memory_device(16384, 0x22, 4800, 6000);
+        table.extend(memory_device(0, 0x02, 0, 0)); // empty slot
+        table.extend([END_OF_TABLE, 4, 0, 0, 0, 0]);
+
+        let modules = parse_memory_modules(&table);
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].locator, "DIMM_A1");
+        assert_eq!(modules[0].memory_type.as_deref(), Some("DDR5"));
+        assert_eq!(modules[0].rated_mts, Some(4800));
+        assert_eq!(modules[0].configured_mts, Some(6000));
+    }
+
+    #[test]
+    fn test_truncated_table() {
+        let table = memory_device(8192, 0x1A, 2133, 3200);
+        assert!(parse_memory_modules(&table[..10]).is_empty());
+    }
+}
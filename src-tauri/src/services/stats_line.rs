@@ -0,0 +1,84 @@
+// One-line summary of the current load and temperatures, for places with room
+// for a single line of text such as a stream overlay. Aura does not measure
+// frame rates, so the line has none.
+use crate::commands::{cpu, gpu};
+use crate::utils::locale::format_number;
+use crate::utils::temperature::{format_temperature, temperature_unit};
+
+/// E.g. "CPU 45% 62.0°C | GPU 80% 70.0°C", leaving out what is unavailable
+pub fn stats_line() -> String {
+    let cpu_stats = cpu::get_cpu_stats().ok();
+    let cpu_usage = cpu_stats.as_ref().and_then(|stats| stats.percentage);
+    let cpu_temperature = cpu_stats
+        .iter()
+        .flat_map(|stats| stats.generic_data.iter().flatten())
+        .find(|data| data.title == "Temp")
+        .and_then(|data| data.raw_value)
+        .map(|celsius| format_temperature(celsius as f32));
+
+    let gpu_stats = gpu::get_gpu_stats().ok();
+    let gpu_usage = gpu_stats
+        .as_ref()
+        .filter(|stats| !stats.gpus.is_empty())
+        .map(|stats| stats.average_utilization);
+    // Already converted to the preferred scale
+    let gpu_temperature = gpu_stats
+        .iter()
+        .flat_map(|stats| &stats.gpus)
+        .find_map(|gpu| gpu.temperature)
+        .map(|value| {
+            format!(
+                "{}{}",
+                format_number(value as f64, 1),
+                temperature_unit().symbol()
+            )
+        });
+
+    format_line(&[
+        ("CPU", cpu_usage, cpu_temperature),
+        ("GPU", gpu_usage, gpu_temperature),
+    ])
+}
+
+fn format_line(parts: &[(&str, Option<f32>, Option<String>)]) -> String {
+    parts
+        .iter()
+        .filter(|(_, usage, temperature)| usage.is_some() || temperature.is_some())
+        .map(|(name, usage, temperature)| {
+            let mut part = name.to_string();
+            if let Some(usage) = usage {
+                part.push_str(&format!(" {}%", format_number(*usage as f64, 0)));
+            }
+            if let Some(temperature) = temperature {
+                part.push(' ');
+                part.push_str(temperature);
+            }
+            part
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(
+            format_line(&[
+                ("CPU", Some(45.2), Some("62.0°C".to_string())),
+                ("GPU", Some(80.0), None),
+            ]),
+            "CPU 45% 62.0°C | GPU 80%"
+        );
+        assert_eq!(
+            format_line(&[
+                ("CPU", None, None),
+                ("GPU", None, Some("70.0°C".to_string()))
+            ]),
+            "GPU 70.0°C"
+        );
+        assert_eq!(format_line(&[("CPU", None, None)]), "");
+    }
+}
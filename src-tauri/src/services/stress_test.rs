@@ -0,0 +1,298 @@
+// CPU and memory stress test to validate cooling and stability after
+// applying a performance profile. Every worker repeats a deterministic work
+// unit and checks the result against one computed before the load started,
+// so an unstable overclock shows up as a computation error rather than
+// silently. A coordinator thread samples temperature and clock once per
+// second and stops everything above the thermal limit.
+use crate::commands::cpu;
+use crate::models::benchmark::{StressTestConfig, StressTestOutcome, StressTestStatus};
+use crate::shared::events;
+use crate::utils::temperature::convert_temperature;
+use anyhow::{anyhow, Result};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const MIN_DURATION_SECS: u64 = 10;
+const MAX_DURATION_SECS: u64 = 3600;
+const MIN_TEMPERATURE_LIMIT: f32 = 50.0;
+const MAX_TEMPERATURE_LIMIT: f32 = 110.0;
+const MIN_MEMORY_MB: u64 = 64;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const WORK_UNIT_ROUNDS: u32 = 100_000;
+const WORK_UNIT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static STOP: AtomicBool = AtomicBool::new(false);
+static ITERATIONS: AtomicU64 = AtomicU64::new(0);
+static STATUS: once_cell::sync::Lazy<Mutex<StressTestStatus>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(StressTestStatus::default()));
+// First reason to stop wins
+static OUTCOME: Mutex<Option<StressTestOutcome>> = Mutex::new(None);
+
+/// Starts a run in the background; fails when one is already running
+pub fn start(config: StressTestConfig) -> Result<StressTestStatus> {
+    let logical_cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let threads = config.threads.unwrap_or(logical_cores);
+    if threads == 0 || threads > logical_cores {
+        return Err(anyhow!(
+            "Thread count must be between 1 and {}",
+            logical_cores
+        ));
+    }
+    if !(MIN_DURATION_SECS..=MAX_DURATION_SECS).contains(&config.duration_secs) {
+        return Err(anyhow!(
+            "Duration must be between {} and {} seconds",
+            MIN_DURATION_SECS,
+            MAX_DURATION_SECS
+        ));
+    }
+    if !(MIN_TEMPERATURE_LIMIT..=MAX_TEMPERATURE_LIMIT).contains(&config.max_temperature) {
+        return Err(anyhow!(
+            "Temperature limit must be between {}°C and {}°C",
+            MIN_TEMPERATURE_LIMIT,
+            MAX_TEMPERATURE_LIMIT
+        ));
+    }
+    if config.include_memory {
+        // Leaves half of the free memory to the rest of the system
+        let available_mb = available_memory() / 1024 / 1024;
+        if config.memory_mb < MIN_MEMORY_MB || config.memory_mb > available_mb / 2 {
+            return Err(anyhow!(
+                "Memory block must be between {} and {} MB",
+                MIN_MEMORY_MB,
+                available_mb / 2
+            ));
+        }
+    }
+
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("A stress test is already running"));
+    }
+    STOP.store(false, Ordering::SeqCst);
+    ITERATIONS.store(0, Ordering::SeqCst);
+    if let Ok(mut outcome) = OUTCOME.lock() {
+        *outcome = None;
+    }
+    set_status(StressTestStatus {
+        running: true,
+        threads,
+        duration_secs: config.duration_secs,
+        ..Default::default()
+    });
+
+    std::thread::spawn(move || run(config, threads));
+    Ok(get_status())
+}
+
+/// Asks a running test to stop; the final status follows with the progress
+/// event
+pub fn stop() -> StressTestStatus {
+    if RUNNING.load(Ordering::SeqCst) {
+        finish(StressTestOutcome::Stopped);
+    }
+    get_status()
+}
+
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
+
+pub fn get_status() -> StressTestStatus {
+    STATUS
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_default()
+}
+
+fn set_status(status: StressTestStatus) {
+    if let Ok(mut current) = STATUS.lock() {
+        *current = status;
+    }
+}
+
+/// Records why the test ends and tells every thread to stop
+fn finish(outcome: StressTestOutcome) {
+    if let Ok(mut current) = OUTCOME.lock() {
+        current.get_or_insert(outcome);
+    }
+    STOP.store(true, Ordering::SeqCst);
+}
+
+fn available_memory() -> u64 {
+    crate::shared::system::SYSTEM
+        .lock()
+        .map(|mut system| {
+            system.refresh_memory();
+            system.available_memory()
+        })
+        .unwrap_or(0)
+}
+
+/// Coordinator: spawns the workers, samples sensors until the end and
+/// publishes the final status
+fn run(config: StressTestConfig, threads: usize) {
+    // Computed before the load starts, while the CPU is known to be stable
+    let expected = work_unit(WORK_UNIT_SEED);
+    let mut workers: Vec<_> = (0..threads)
+        .map(|thread| std::thread::spawn(move || cpu_worker(thread, expected)))
+        .collect();
+    if config.include_memory {
+        let words = (config.memory_mb * 1024 * 1024 / 8) as usize;
+        workers.push(std::thread::spawn(move || memory_worker(words)));
+    }
+
+    let started = Instant::now();
+    let deadline = Duration::from_secs(config.duration_secs);
+    let mut max_seen: Option<f32> = None;
+
+    while !STOP.load(Ordering::SeqCst) {
+        std::thread::sleep(SAMPLE_INTERVAL);
+        let (usage, celsius, clock_mhz) = sample_sensors();
+        if let Some(celsius) = celsius {
+            max_seen = Some(max_seen.map_or(celsius, |max: f32| max.max(celsius)));
+            if celsius >= config.max_temperature {
+                finish(StressTestOutcome::ThermalLimit {
+                    temperature: convert_temperature(celsius),
+                });
+            }
+        }
+        if started.elapsed() >= deadline {
+            finish(StressTestOutcome::Completed);
+        }
+
+        let status = StressTestStatus {
+            running: true,
+            threads,
+            elapsed_secs: started.elapsed().as_secs(),
+            duration_secs: config.duration_secs,
+            cpu_usage: usage,
+            temperature: celsius.map(convert_temperature),
+            max_temperature_seen: max_seen.map(convert_temperature),
+            clock_mhz,
+            iterations: ITERATIONS.load(Ordering::Relaxed),
+            outcome: None,
+        };
+        set_status(status.clone());
+        events::emit(events::STRESS_TEST_PROGRESS, status);
+    }
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+    let mut status = get_status();
+    status.running = false;
+    status.elapsed_secs = started.elapsed().as_secs();
+    status.iterations = ITERATIONS.load(Ordering::Relaxed);
+    status.outcome = OUTCOME.lock().ok().and_then(|outcome| outcome.clone());
+    set_status(status.clone());
+    RUNNING.store(false, Ordering::SeqCst);
+    events::emit(events::STRESS_TEST_PROGRESS, status);
+}
+
+/// CPU usage, average temperature in °C and highest core clock in MHz
+fn sample_sensors() -> (Option<f32>, Option<f32>, Option<u64>) {
+    let Ok(stats) = cpu::get_cpu_stats() else {
+        return (None, None, None);
+    };
+    let value = |title: &str| {
+        stats
+            .generic_data
+            .iter()
+            .flatten()
+            .find(|data| data.title == title)
+            .and_then(|data| data.raw_value)
+    };
+    (
+        stats.percentage,
+        value("Temp").map(|celsius| celsius as f32),
+        value("Max Clock").map(|hz| (hz / 1_000_000.0) as u64),
+    )
+}
+
+fn cpu_worker(thread: usize, expected: u64) {
+    while !STOP.load(Ordering::Relaxed) {
+        if work_unit(black_box(WORK_UNIT_SEED)) != expected {
+            finish(StressTestOutcome::ComputationError { thread });
+            return;
+        }
+        ITERATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Integer and floating point mix whose result only depends on the seed
+fn work_unit(seed: u64) -> u64 {
+    let mut x = seed;
+    let mut f = 1.0f64;
+    for _ in 0..WORK_UNIT_ROUNDS {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        f = (f * 1.000_000_1 + (x & 0xff) as f64 * 1e-7).sqrt() + 0.5;
+    }
+    x ^ f.to_bits()
+}
+
+/// Writes a pattern over the block and reads it back, pass after pass
+fn memory_worker(words: usize) {
+    let mut block = vec![0u64; words];
+    let mut pass: u64 = 0;
+    while !STOP.load(Ordering::Relaxed) {
+        pass += 1;
+        fill_pattern(&mut block, pass);
+        let errors = verify_pattern(black_box(&block), pass);
+        if errors > 0 {
+            finish(StressTestOutcome::MemoryError { errors });
+            return;
+        }
+    }
+}
+
+fn pattern(index: usize, pass: u64) -> u64 {
+    // Alternates bit polarity between passes
+    let value = (index as u64).wrapping_mul(WORK_UNIT_SEED) ^ pass;
+    if pass.is_multiple_of(2) {
+        !value
+    } else {
+        value
+    }
+}
+
+fn fill_pattern(block: &mut [u64], pass: u64) {
+    for (index, word) in block.iter_mut().enumerate() {
+        *word = pattern(index, pass);
+    }
+}
+
+fn verify_pattern(block: &[u64], pass: u64) -> u64 {
+    block
+        .iter()
+        .enumerate()
+        .filter(|(index, word)| **word != pattern(*index, pass))
+        .count() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_work_unit_is_deterministic() {
+        assert_eq!(work_unit(WORK_UNIT_SEED), work_unit(WORK_UNIT_SEED));
+        assert_ne!(work_unit(WORK_UNIT_SEED), work_unit(WORK_UNIT_SEED + 1));
+    }
+
+    #[test]
+    fn test_memory_pattern() {
+        let mut block = vec![0u64; 1024];
+        fill_pattern(&mut block, 3);
+        assert_eq!(verify_pattern(&block, 3), 0);
+        block[10] ^= 1 << 5;
+        assert_eq!(verify_pattern(&block, 3), 1);
+        // Every word differs from the previous pass
+        assert_eq!(verify_pattern(&block, 4), 1024);
+    }
+}
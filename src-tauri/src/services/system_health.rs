@@ -0,0 +1,201 @@
+// Tells when a restart is worth it. Long uptimes are where slow leaks in
+// drivers and services pile up (on Windows, shutting down with Fast Startup
+// doesn't reset them, only a restart does), optimizations and updates may be
+// waiting for one, and memory can end up in a state only a restart clears:
+// commit charge close to the limit, a kernel pool grown out of proportion,
+// or free memory split into pieces too small for huge pages.
+use crate::models::system_stats::{RebootReason, RebootReasonKind, SystemHealth};
+use crate::services::{memory_breakdown, optimization_engine, readiness};
+use crate::utils::bytes::format_bytes_in;
+use sysinfo::System;
+
+const LONG_UPTIME_SECS: u64 = 7 * 24 * 3600;
+const COMMIT_RATIO: f64 = 0.9;
+// Usually a few hundred MB, a driver leaking into it keeps growing it
+const KERNEL_POOL_LIMIT: u64 = 2 * 1024 * 1024 * 1024;
+// Below this share of free memory in 2 MB blocks, with enough free memory
+// around that it isn't just full
+const HIGH_ORDER_MIN_SHARE: f64 = 0.05;
+const FRAGMENTATION_MIN_FREE: u64 = 1024 * 1024 * 1024;
+// Buddy allocator order of a 2 MB block with 4 KB pages
+const HUGE_PAGE_ORDER: usize = 9;
+const PAGE_SIZE: u64 = 4096;
+
+/// What the advice is based on, gathered once per call
+#[derive(Debug, Default)]
+struct Signals {
+    uptime_secs: u64,
+    pending_optimizations: Vec<String>,
+    updates_pending: bool,
+    committed: Option<u64>,
+    commit_limit: Option<u64>,
+    kernel_pool: Option<u64>,
+    free_memory: Option<(u64, f64)>, // free bytes and the share in huge page sized blocks
+}
+
+pub fn get_health() -> SystemHealth {
+    let breakdown = memory_breakdown::get_breakdown();
+    let signals = Signals {
+        uptime_secs: System::uptime(),
+        pending_optimizations: optimization_engine::get_pending_restart_items()
+            .into_iter()
+            .map(|item| item.optimization_name)
+            .collect(),
+        updates_pending: readiness::updates_pending_restart().unwrap_or(false),
+        committed: breakdown.committed,
+        commit_limit: breakdown.commit_limit,
+        kernel_pool: breakdown.nonpaged_pool,
+        free_memory: std::fs::read_to_string("/proc/buddyinfo")
+            .ok()
+            .and_then(|buddyinfo| parse_buddyinfo(&buddyinfo)),
+    };
+    let reasons = reasons(&signals);
+    SystemHealth {
+        uptime_secs: signals.uptime_secs,
+        reboot_recommended: !reasons.is_empty(),
+        reasons,
+    }
+}
+
+fn reasons(signals: &Signals) -> Vec<RebootReason> {
+    let mut reasons = Vec::new();
+    let mut add = |kind, detail: String| reasons.push(RebootReason { kind, detail });
+
+    if signals.uptime_secs >= LONG_UPTIME_SECS {
+        add(
+            RebootReasonKind::LongUptime,
+            format!(
+                "Running for {} days without a restart",
+                signals.uptime_secs / (24 * 3600)
+            ),
+        );
+    }
+    if !signals.pending_optimizations.is_empty() {
+        add(
+            RebootReasonKind::PendingOptimizations,
+            format!(
+                "Waiting for a restart to take effect: {}",
+                signals.pending_optimizations.join(", ")
+            ),
+        );
+    }
+    if signals.updates_pending {
+        add(
+            RebootReasonKind::PendingUpdates,
+            "Installed updates are waiting for a restart".to_string(),
+        );
+    }
+    if let (Some(committed), Some(limit)) = (signals.committed, signals.commit_limit) {
+        if limit > 0 && committed as f64 >= limit as f64 * COMMIT_RATIO {
+            add(
+                RebootReasonKind::CommitCharge,
+                format!(
+                    "{} of {} committed, programs may fail to allocate memory",
+                    format_bytes_in(committed, 3, 1),
+                    format_bytes_in(limit, 3, 1)
+                ),
+            );
+        }
+    }
+    if let Some(pool) = signals
+        .kernel_pool
+        .filter(|pool| *pool >= KERNEL_POOL_LIMIT)
+    {
+        add(
+            RebootReasonKind::KernelPool,
+            format!(
+                "The nonpaged pool holds {}, a driver may be leaking memory",
+                format_bytes_in(pool, 3, 1)
+            ),
+        );
+    }
+    if let Some((free, share)) = signals.free_memory {
+        if free >= FRAGMENTATION_MIN_FREE && share < HIGH_ORDER_MIN_SHARE {
+            add(
+                RebootReasonKind::MemoryFragmentation,
+                format!(
+                    "Only {:.0}% of the {} free is in blocks large enough for huge pages",
+                    share * 100.0,
+                    format_bytes_in(free, 3, 1)
+                ),
+            );
+        }
+    }
+    reasons
+}
+
+/// Free bytes of every zone in /proc/buddyinfo, and the share of them in
+/// blocks of at least 2 MB
+fn parse_buddyinfo(buddyinfo: &str) -> Option<(u64, f64)> {
+    let mut free_pages: u64 = 0;
+    let mut high_order_pages: u64 = 0;
+    for line in buddyinfo.lines() {
+        // Node 0, zone   Normal   1234   567 ..., one count per order
+        let Some((_, counts)) = line.split_once("zone") else {
+            continue;
+        };
+        let counts = counts
+            .split_whitespace()
+            .skip(1)
+            .filter_map(|count| count.parse::<u64>().ok());
+        for (order, count) in counts.enumerate() {
+            let pages = count << order;
+            free_pages += pages;
+            if order >= HUGE_PAGE_ORDER {
+                high_order_pages += pages;
+            }
+        }
+    }
+    (free_pages > 0).then(|| {
+        (
+            free_pages * PAGE_SIZE,
+            high_order_pages as f64 / free_pages as f64,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reasons() {
+        let healthy = Signals {
+            uptime_secs: 3600,
+            committed: Some(8 << 30),
+            commit_limit: Some(32 << 30),
+            kernel_pool: Some(300 << 20),
+            ..Default::default()
+        };
+        assert!(reasons(&healthy).is_empty());
+
+        let worn = Signals {
+            uptime_secs: 12 * 24 * 3600,
+            pending_optimizations: vec!["Disable HPET".to_string()],
+            committed: Some(31 << 30),
+            kernel_pool: Some(3 << 30),
+            ..healthy
+        };
+        let kinds: Vec<RebootReasonKind> = reasons(&worn).iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                RebootReasonKind::LongUptime,
+                RebootReasonKind::PendingOptimizations,
+                RebootReasonKind::CommitCharge,
+                RebootReasonKind::KernelPool,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_buddyinfo() {
+        // 1000 free 4 KB pages and a single 2 MB block in the Normal zone
+        let buddyinfo = "Node 0, zone      DMA      0      0      0      0      0      0      0      0      0      0      0\n\
+            Node 0, zone   Normal   1000      0      0      0      0      0      0      0      0      1      0\n";
+        let (free, share) = parse_buddyinfo(buddyinfo).unwrap();
+        assert_eq!(free, (1000 + 512) * 4096);
+        assert!((share - 512.0 / 1512.0).abs() < 1e-9);
+        assert_eq!(parse_buddyinfo(""), None);
+    }
+}
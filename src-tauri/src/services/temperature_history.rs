@@ -0,0 +1,388 @@
+// Session minimum, average and maximum of every temperature sensor: CPU
+// cores and package, GPUs and drives. A background thread samples them while
+// Aura runs, so peaks reached during a long game can be checked afterwards.
+// Values are kept in °C and converted to the current unit when read. A GPU
+// memory junction that stays above the configured threshold for long raises
+// an alert once per hot spell.
+use crate::models::gpu_info::{GpuMemoryAlertConfig, GpuMemoryHotAlert};
+use crate::models::session::GpuCoolingSample;
+use crate::models::system_stats::{SensorKind, TemperatureSensorSummary, TemperatureSummary};
+use crate::services::gpu;
+use crate::services::{quiet_hours, session_report, user_idle};
+use crate::shared::{events, paths};
+use crate::utils::temperature::{convert_temperature, temperature_unit};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::Components;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+// GPU queries are heavier, read every few samples
+const GPU_EVERY: u32 = 3;
+// Readings outside this range are sensor glitches
+const MIN_VALID: f32 = -20.0;
+const MAX_VALID: f32 = 150.0;
+const ALERT_CONFIG_FILE: &str = "gpu_memory_alert.json";
+// A spell ends this far below the threshold, so hovering around it doesn't
+// start a new one every sample
+const ALERT_HYSTERESIS: f32 = 5.0;
+
+#[derive(Debug, Clone)]
+struct SensorStats {
+    kind: SensorKind,
+    current: f32,
+    min: f32,
+    max: f32,
+    max_at: u64,
+    sum: f64,
+    samples: u64,
+}
+
+impl SensorStats {
+    fn new(kind: SensorKind, celsius: f32, at: u64) -> Self {
+        Self {
+            kind,
+            current: celsius,
+            min: celsius,
+            max: celsius,
+            max_at: at,
+            sum: celsius as f64,
+            samples: 1,
+        }
+    }
+
+    fn record(&mut self, celsius: f32, at: u64) {
+        self.current = celsius;
+        self.min = self.min.min(celsius);
+        if celsius > self.max {
+            self.max = celsius;
+            self.max_at = at;
+        }
+        self.sum += celsius as f64;
+        self.samples += 1;
+    }
+
+    fn avg(&self) -> f32 {
+        (self.sum / self.samples as f64) as f32
+    }
+}
+
+struct History {
+    since: u64,
+    sensors: HashMap<String, SensorStats>,
+}
+
+static HISTORY: once_cell::sync::Lazy<Mutex<History>> = once_cell::sync::Lazy::new(|| {
+    Mutex::new(History {
+        since: events::now_millis(),
+        sensors: HashMap::new(),
+    })
+});
+
+// Memory junction above the threshold, by GPU
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HotSpell {
+    since: u64,
+    reported: bool,
+}
+
+static ALERT_CONFIG: once_cell::sync::Lazy<Mutex<GpuMemoryAlertConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_alert_config()));
+
+static HOT_SPELLS: once_cell::sync::Lazy<Mutex<HashMap<String, HotSpell>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+static SAMPLER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts the sampler once. Subsequent calls do nothing.
+pub fn start() {
+    if SAMPLER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(|| {
+        let mut components = Components::new_with_refreshed_list();
+        let mut tick: u32 = 0;
+        loop {
+            if !quiet_hours::is_paused() && !user_idle::skips_round(tick as u64) {
+                components.refresh(false);
+                let mut readings: Vec<(String, SensorKind, f32)> = components
+                    .iter()
+                    .filter_map(|component| {
+                        let label = component.label().to_string();
+                        let celsius = component.temperature()?;
+                        Some((label.clone(), classify(&label), celsius))
+                    })
+                    .collect();
+                if tick.is_multiple_of(GPU_EVERY) {
+                    readings.extend(gpu_readings());
+                }
+                record(readings, events::now_millis());
+            }
+            tick = tick.wrapping_add(1);
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+}
+
+pub fn get_summary() -> TemperatureSummary {
+    let (since, mut sensors) = match HISTORY.lock() {
+        Ok(history) => (
+            history.since,
+            history
+                .sensors
+                .iter()
+                .map(|(sensor, stats)| TemperatureSensorSummary {
+                    sensor: sensor.clone(),
+                    kind: stats.kind,
+                    current: convert_temperature(stats.current),
+                    min: convert_temperature(stats.min),
+                    avg: convert_temperature(stats.avg()),
+                    max: convert_temperature(stats.max),
+                    max_at: stats.max_at,
+                    samples: stats.samples,
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Err(_) => (events::now_millis(), Vec::new()),
+    };
+    sensors.sort_by(|a, b| {
+        (a.kind as u8)
+            .cmp(&(b.kind as u8))
+            .then_with(|| a.sensor.cmp(&b.sensor))
+    });
+
+    TemperatureSummary {
+        since,
+        unit: temperature_unit(),
+        sensors,
+    }
+}
+
+/// Starts a new session, e.g. right before a game
+pub fn reset() {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.since = events::now_millis();
+        history.sensors.clear();
+    }
+}
+
+pub fn get_alert_config() -> GpuMemoryAlertConfig {
+    ALERT_CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_alert_config(config: GpuMemoryAlertConfig) -> Result<GpuMemoryAlertConfig> {
+    if !(60.0..=MAX_VALID).contains(&config.threshold_celsius) {
+        return Err(anyhow!(
+            "The threshold must be between 60 and {} °C",
+            MAX_VALID
+        ));
+    }
+    let content = serde_json::to_string_pretty(&config)?;
+    std::fs::write(paths::app_data_file(ALERT_CONFIG_FILE), content)?;
+    *ALERT_CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    if let Ok(mut spells) = HOT_SPELLS.lock() {
+        spells.clear();
+    }
+    Ok(config)
+}
+
+fn record(readings: Vec<(String, SensorKind, f32)>, at: u64) {
+    let Ok(mut history) = HISTORY.lock() else {
+        return;
+    };
+    for (sensor, kind, celsius) in readings {
+        if !(MIN_VALID..=MAX_VALID).contains(&celsius) {
+            continue;
+        }
+        history
+            .sensors
+            .entry(sensor)
+            .and_modify(|stats| stats.record(celsius, at))
+            .or_insert_with(|| SensorStats::new(kind, celsius, at));
+    }
+}
+
+/// Temperatures of every GPU. Their fans go to the session report along with
+/// them, so the cooling before a crash can be looked at afterwards.
+fn gpu_readings() -> Vec<(String, SensorKind, f32)> {
+    let Ok(stats) = gpu::get_gpu_stats() else {
+        return Vec::new();
+    };
+    let at = events::now_millis();
+    let to_celsius = |value: Option<f32>| value.map(|v| stats.temperature_unit.to_celsius(v));
+    let numbered = stats.gpus.len() > 1;
+    let mut readings = Vec::new();
+    let mut cooling = Vec::new();
+    for (index, gpu) in stats.gpus.iter().enumerate() {
+        let name = if numbered {
+            format!("{} #{}", gpu.name, index + 1)
+        } else {
+            gpu.name.clone()
+        };
+        let temperature = to_celsius(gpu.temperature);
+        let memory_temperature = to_celsius(gpu.memory_temperature);
+        let hotspot_temperature = to_celsius(gpu.hotspot_temperature);
+        if let Some(celsius) = memory_temperature {
+            check_memory(&name, celsius);
+        }
+        readings.extend(
+            [
+                (name.clone(), temperature),
+                (format!("{} memory junction", name), memory_temperature),
+                (format!("{} hotspot", name), hotspot_temperature),
+            ]
+            .into_iter()
+            .filter_map(|(sensor, celsius)| Some((sensor, SensorKind::Gpu, celsius?))),
+        );
+        if temperature.is_some() || !gpu.fans.is_empty() {
+            cooling.push(GpuCoolingSample {
+                at,
+                gpu: name,
+                temperature_celsius: temperature,
+                memory_temperature_celsius: memory_temperature,
+                hotspot_temperature_celsius: hotspot_temperature,
+                fans: gpu.fans.clone(),
+                fans_stopped: gpu.fans_stopped,
+            });
+        }
+    }
+    session_report::record_gpu_cooling(cooling);
+    readings
+}
+
+/// Raises the alert once the memory junction of a GPU stayed hot long enough
+fn check_memory(gpu: &str, celsius: f32) {
+    let config = get_alert_config();
+    if !config.enabled || !(MIN_VALID..=MAX_VALID).contains(&celsius) {
+        return;
+    }
+    let now = events::now_millis();
+    let Ok(mut spells) = HOT_SPELLS.lock() else {
+        return;
+    };
+    let mut spell = spells.get(gpu).copied();
+    let report = update_spell(&mut spell, celsius, &config, now);
+    match spell {
+        Some(spell) => spells.insert(gpu.to_string(), spell),
+        None => spells.remove(gpu),
+    };
+    if let (true, Some(spell)) = (report, spell) {
+        events::emit(
+            events::GPU_MEMORY_HOT,
+            GpuMemoryHotAlert {
+                gpu: gpu.to_string(),
+                memory_temperature: convert_temperature(celsius),
+                threshold: convert_temperature(config.threshold_celsius),
+                temperature_unit: temperature_unit(),
+                hot_since: spell.since,
+                timestamp: now,
+            },
+        );
+    }
+}
+
+/// Starts, continues or ends a hot spell. True when it has just lasted long
+/// enough to report.
+fn update_spell(
+    spell: &mut Option<HotSpell>,
+    celsius: f32,
+    config: &GpuMemoryAlertConfig,
+    now: u64,
+) -> bool {
+    if celsius < config.threshold_celsius - ALERT_HYSTERESIS {
+        *spell = None;
+        return false;
+    }
+    let current = match spell {
+        Some(current) => current,
+        None if celsius >= config.threshold_celsius => spell.insert(HotSpell {
+            since: now,
+            reported: false,
+        }),
+        None => return false,
+    };
+    if current.reported || now.saturating_sub(current.since) < config.sustained_secs * 1000 {
+        return false;
+    }
+    current.reported = true;
+    true
+}
+
+fn load_alert_config() -> GpuMemoryAlertConfig {
+    std::fs::read_to_string(paths::app_data_file(ALERT_CONFIG_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Tells sensors apart by the labels the drivers give them
+fn classify(label: &str) -> SensorKind {
+    let label = label.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| label.contains(word));
+    if has(&["nvme", "ssd", "drive", "disk", "sata", "drivetemp"]) {
+        SensorKind::Storage
+    } else if has(&["gpu", "amdgpu", "nouveau", "radeon", "edge", "junction"]) {
+        SensorKind::Gpu
+    } else if has(&[
+        "cpu", "core", "package", "tctl", "tdie", "tccd", "k10temp", "coretemp",
+    ]) {
+        SensorKind::Cpu
+    } else {
+        SensorKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("coretemp Core 3"), SensorKind::Cpu);
+        assert_eq!(classify("k10temp Tctl"), SensorKind::Cpu);
+        assert_eq!(
+            classify("nvme Composite Samsung SSD 980"),
+            SensorKind::Storage
+        );
+        assert_eq!(classify("amdgpu edge"), SensorKind::Gpu);
+        assert_eq!(classify("acpitz temp1"), SensorKind::Other);
+    }
+
+    #[test]
+    fn test_sensor_stats() {
+        let mut stats = SensorStats::new(SensorKind::Cpu, 40.0, 1);
+        stats.record(80.0, 2);
+        stats.record(60.0, 3);
+        assert_eq!(stats.min, 40.0);
+        assert_eq!(stats.max, 80.0);
+        assert_eq!(stats.max_at, 2);
+        assert_eq!(stats.avg(), 60.0);
+        assert_eq!(stats.current, 60.0);
+        assert_eq!(stats.samples, 3);
+    }
+
+    #[test]
+    fn test_memory_hot_spell() {
+        let config = GpuMemoryAlertConfig {
+            enabled: true,
+            threshold_celsius: 100.0,
+            sustained_secs: 60,
+        };
+        let mut spell = None;
+        assert!(!update_spell(&mut spell, 98.0, &config, 0));
+        assert_eq!(spell, None);
+        assert!(!update_spell(&mut spell, 101.0, &config, 1_000));
+        // Dipping a little below doesn't end it
+        assert!(!update_spell(&mut spell, 97.0, &config, 30_000));
+        assert!(update_spell(&mut spell, 102.0, &config, 61_000));
+        assert!(!update_spell(&mut spell, 103.0, &config, 120_000));
+        assert!(!update_spell(&mut spell, 90.0, &config, 130_000));
+        assert_eq!(spell, None);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::models::optimization::{TimerKeeperConfig, TimerResolution};
+use crate::services::crash_monitor;
+use crate::shared::paths;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use thiserror::Error;
+
+#[cfg(target_os = "windows")]
+use ntapi::ntexapi::{NtQueryTimerResolution, NtSetTimerResolution};
+
+const KEEPER_FILE: &str = "timer_resolution.json";
+const KEEPER_INTERVAL: Duration = Duration::from_secs(2);
+// 1 ms in the 100 ns units of the kernel timer
+#[cfg(target_os = "windows")]
+const TARGET_RESOLUTION: u32 = 10_000;
+
+#[derive(Error, Debug)]
+pub enum TimerResolutionError {
+    #[cfg(target_os = "windows")]
+    #[error("Timer resolution call failed with status {0:#x}")]
+    NtStatus(i32),
+
+    #[error("Failed to access timer resolution settings: {0}")]
+    StorageError(String),
+
+    // The timer resolution is a Windows kernel setting
+    #[cfg(not(target_os = "windows"))]
+    #[error("Timer resolution is not supported on this platform")]
+    UnsupportedPlatform,
+}
+
+type Result<T> = std::result::Result<T, TimerResolutionError>;
+
+static KEEPER: once_cell::sync::Lazy<Mutex<TimerKeeperConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_keeper()));
+
+// Whether Aura currently has a resolution request registered with the kernel
+static HELD: AtomicBool = AtomicBool::new(false);
+static KEEPER_THREAD_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Current, finest and coarsest timer resolution. The current value is the
+/// finest one requested by any process, not just Aura.
+pub fn get_timer_resolution() -> Result<TimerResolution> {
+    #[cfg(target_os = "windows")]
+    {
+        let (mut coarsest, mut finest, mut current) = (0u32, 0u32, 0u32);
+        let status = unsafe { NtQueryTimerResolution(&mut coarsest, &mut finest, &mut current) };
+        if status < 0 {
+            return Err(TimerResolutionError::NtStatus(status));
+        }
+
+        Ok(TimerResolution {
+            current_ms: current as f64 / 10_000.0,
+            finest_ms: finest as f64 / 10_000.0,
+            coarsest_ms: coarsest as f64 / 10_000.0,
+            held_by_aura: HELD.load(Ordering::SeqCst),
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(TimerResolutionError::UnsupportedPlatform)
+    }
+}
+
+pub fn get_keeper() -> TimerKeeperConfig {
+    KEEPER
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+/// Saves the keeper settings and applies them right away
+pub fn set_keeper(config: TimerKeeperConfig) -> Result<TimerResolution> {
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| TimerResolutionError::StorageError(e.to_string()))?;
+    std::fs::write(paths::app_data_file(KEEPER_FILE), content)
+        .map_err(|e| TimerResolutionError::StorageError(e.to_string()))?;
+
+    *KEEPER
+        .lock()
+        .map_err(|e| TimerResolutionError::StorageError(e.to_string()))? = config;
+
+    ensure_keeper_thread_running();
+    apply_keeper()?;
+    get_timer_resolution()
+}
+
+/// Resumes the keeper at startup if it was left enabled
+pub fn start_keeper() {
+    if get_keeper().enabled {
+        ensure_keeper_thread_running();
+    }
+}
+
+/// Drops Aura's request so the system falls back to its default resolution.
+/// Called when the app exits.
+pub fn restore() {
+    if HELD.swap(false, Ordering::SeqCst) {
+        let _ = request_resolution(false);
+    }
+}
+
+fn load_keeper() -> TimerKeeperConfig {
+    std::fs::read_to_string(paths::app_data_file(KEEPER_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn ensure_keeper_thread_running() {
+    if KEEPER_THREAD_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(|| loop {
+        let _ = apply_keeper();
+        std::thread::sleep(KEEPER_INTERVAL);
+    });
+}
+
+/// Holds the request while the keeper wants it, releases it otherwise. The
+/// request is renewed on every tick in case a driver or game reset it.
+fn apply_keeper() -> Result<()> {
+    let config = get_keeper();
+    let wanted = config.enabled
+        && (!config.only_while_gaming || !crash_monitor::get_tracked_games().is_empty());
+
+    if wanted {
+        request_resolution(true)?;
+        HELD.store(true, Ordering::SeqCst);
+    } else if HELD.swap(false, Ordering::SeqCst) {
+        request_resolution(false)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn request_resolution(set: bool) -> Result<()> {
+    if set {
+        keep_timer_requests_honored();
+    }
+
+    let mut current = 0u32;
+    let status = unsafe { NtSetTimerResolution(TARGET_RESOLUTION, set as u8, &mut current) };
+    if status < 0 {
+        return Err(TimerResolutionError::NtStatus(status));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn request_resolution(_set: bool) -> Result<()> {
+    Err(TimerResolutionError::UnsupportedPlatform)
+}
+
+/// Windows 11 ignores the timer requests of processes without a visible
+/// window, which Aura is when minimized to the tray, unless opted out
+#[cfg(target_os = "windows")]
+fn keep_timer_requests_honored() {
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, ProcessPowerThrottling, SetProcessInformation,
+        PROCESS_POWER_THROTTLING_CURRENT_VERSION, PROCESS_POWER_THROTTLING_IGNORE_TIMER_RESOLUTION,
+        PROCESS_POWER_THROTTLING_STATE,
+    };
+
+    let state = PROCESS_POWER_THROTTLING_STATE {
+        Version: PROCESS_POWER_THROTTLING_CURRENT_VERSION,
+        ControlMask: PROCESS_POWER_THROTTLING_IGNORE_TIMER_RESOLUTION,
+        StateMask: 0,
+    };
+    // Fails on Windows 10, where the requests are always honored
+    let _ = unsafe {
+        SetProcessInformation(
+            GetCurrentProcess(),
+            ProcessPowerThrottling,
+            &state as *const _ as *const _,
+            std::mem::size_of::<PROCESS_POWER_THROTTLING_STATE>() as u32,
+        )
+    };
+}
@@ -0,0 +1,726 @@
+// Looks for newer Aura releases on GitHub, downloads the installer for this
+// platform and verifies who published it before anything runs. A checksum
+// from the release itself only proves the download is intact, so the
+// publisher is pinned at build time: on Windows the installer must carry a
+// valid Authenticode signature from the certificate whose thumbprint is in
+// AURA_UPDATE_THUMBPRINT, on Linux the AppImage must match its .minisig
+// signature under the minisign public key in AURA_UPDATE_PUBKEY. A build
+// without the key can check for updates but not install them.
+//
+// Requests go through the system's curl (shipped with Windows 10 and later)
+// and signatures through PowerShell or minisign, the same way the other
+// services use the platform tools, so no TLS or crypto stack is linked in.
+use crate::models::system_stats::{UpdateConfig, UpdateInfo, UpdateStage, UpdateStatus};
+use crate::shared::{events, i18n, paths};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::cmp::Ordering as CmpOrdering;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const CONFIG_FILE: &str = "update_config.json";
+const RELEASES_URL: &str = "https://api.github.com/repos/Master-Antonio/Aura/releases?per_page=20";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Let startup settle before the first check
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+const TICK: Duration = Duration::from_secs(10 * 60);
+const CHECK_EVERY_MS: u64 = 12 * 60 * 60 * 1000;
+const CHECKSUM_FILES: &[&str] = &["SHA256SUMS", "SHA256SUMS.txt", "checksums.txt"];
+
+// Set by the release build, see the top of the file
+#[cfg(target_os = "windows")]
+const PUBLISHER_THUMBPRINT: Option<&str> = option_env!("AURA_UPDATE_THUMBPRINT");
+#[cfg(not(target_os = "windows"))]
+const PUBLIC_KEY: Option<&str> = option_env!("AURA_UPDATE_PUBKEY");
+
+static CONFIG: once_cell::sync::Lazy<Mutex<UpdateConfig>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(load_config()));
+
+static STATUS: once_cell::sync::Lazy<Mutex<UpdateStatus>> = once_cell::sync::Lazy::new(|| {
+    Mutex::new(UpdateStatus {
+        current_version: CURRENT_VERSION.to_string(),
+        stage: UpdateStage::UpToDate,
+        update: None,
+        last_checked: None,
+        error: None,
+    })
+});
+
+// The verified installer and its SHA-256, set once the stage is Ready
+static DOWNLOADED: Mutex<Option<(PathBuf, String)>> = Mutex::new(None);
+static DOWNLOADING: AtomicBool = AtomicBool::new(false);
+static CHECKER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts the periodic check once. Subsequent calls do nothing.
+pub fn start() {
+    if CHECKER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        std::thread::sleep(STARTUP_DELAY);
+        loop {
+            let due = get_status().last_checked.is_none_or(|checked| {
+                events::now_millis().saturating_sub(checked) >= CHECK_EVERY_MS
+            });
+            if get_config().check_automatically && due {
+                if let Err(e) = check() {
+                    eprintln!("Update check failed: {}", e);
+                }
+            }
+            std::thread::sleep(TICK);
+        }
+    });
+}
+
+pub fn get_config() -> UpdateConfig {
+    CONFIG
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_config(config: UpdateConfig) -> Result<UpdateConfig> {
+    paths::save_json(CONFIG_FILE, &config)?;
+    *CONFIG.lock().map_err(|e| anyhow!(e.to_string()))? = config.clone();
+    Ok(config)
+}
+
+pub fn get_status() -> UpdateStatus {
+    STATUS
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_else(|e| e.into_inner().clone())
+}
+
+/// Asks GitHub for the newest release. Emits UPDATE_AVAILABLE the first
+/// time a version is found.
+pub fn check() -> Result<UpdateStatus> {
+    let found = latest_update(get_config().include_prereleases);
+    let previous = get_status();
+    let mut status = UpdateStatus {
+        last_checked: Some(events::now_millis()),
+        ..previous.clone()
+    };
+
+    match found {
+        Ok(update) => {
+            // A download of the same version stays usable
+            let same = previous
+                .update
+                .as_ref()
+                .zip(update.as_ref())
+                .is_some_and(|(before, now)| before.version == now.version);
+            if !(same && previous.stage == UpdateStage::Ready) {
+                status.stage = match update {
+                    Some(_) => UpdateStage::Available,
+                    None => UpdateStage::UpToDate,
+                };
+            }
+            let announce = update.is_some() && !same;
+            status.update = update;
+            status.error = None;
+            set_status(status.clone());
+            if announce {
+                events::emit(events::UPDATE_AVAILABLE, status.clone());
+            }
+            Ok(status)
+        }
+        Err(e) => {
+            status.error = Some(e.to_string());
+            set_status(status);
+            Err(e)
+        }
+    }
+}
+
+/// Downloads the installer of the last found release and verifies it
+pub fn download() -> Result<UpdateStatus> {
+    let update = get_status().update.ok_or_else(|| {
+        anyhow!(i18n::text(
+            "error.update_none_found",
+            "No update to download, check for updates first"
+        ))
+    })?;
+    if DOWNLOADING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!(i18n::text(
+            "error.update_downloading",
+            "The update is already downloading"
+        )));
+    }
+    update_stage(UpdateStage::Downloading, None);
+
+    let result = download_verified(&update);
+    DOWNLOADING.store(false, Ordering::SeqCst);
+    match result {
+        Ok(verified) => {
+            if let Ok(mut downloaded) = DOWNLOADED.lock() {
+                *downloaded = Some(verified);
+            }
+            update_stage(UpdateStage::Ready, None);
+            Ok(get_status())
+        }
+        Err(e) => {
+            update_stage(UpdateStage::Failed, Some(e.to_string()));
+            Err(e)
+        }
+    }
+}
+
+/// Starts the verified installer. Aura has to quit or restart afterwards
+/// for the new version to take over.
+pub fn install() -> Result<()> {
+    let (path, hash) = DOWNLOADED
+        .lock()
+        .ok()
+        .and_then(|downloaded| downloaded.clone())
+        .filter(|(path, _)| path.exists())
+        .ok_or_else(|| {
+            anyhow!(i18n::text(
+                "error.update_not_ready",
+                "No verified update is ready to install"
+            ))
+        })?;
+    run_installer(&path, &hash)
+}
+
+fn latest_update(include_prereleases: bool) -> Result<Option<UpdateInfo>> {
+    let body = curl(&[RELEASES_URL])?;
+    let releases: Value = serde_json::from_slice(&body)?;
+    let releases = releases.as_array().ok_or_else(|| {
+        anyhow!(i18n::text(
+            "error.update_unexpected_answer",
+            "Unexpected answer from GitHub"
+        ))
+    })?;
+
+    let newest = releases
+        .iter()
+        .filter(|release| !release["draft"].as_bool().unwrap_or(false))
+        .filter(|release| include_prereleases || !release["prerelease"].as_bool().unwrap_or(false))
+        .filter(|release| {
+            compare_versions(
+                release["tag_name"].as_str().unwrap_or_default(),
+                CURRENT_VERSION,
+            ) == Some(CmpOrdering::Greater)
+        })
+        .max_by(|a, b| {
+            compare_versions(
+                a["tag_name"].as_str().unwrap_or_default(),
+                b["tag_name"].as_str().unwrap_or_default(),
+            )
+            .unwrap_or(CmpOrdering::Equal)
+        });
+    let Some(release) = newest else {
+        return Ok(None);
+    };
+
+    let assets = release["assets"].as_array().cloned().unwrap_or_default();
+    let names: Vec<&str> = assets
+        .iter()
+        .map(|asset| asset["name"].as_str().unwrap_or_default())
+        .collect();
+    let version = release["tag_name"].as_str().unwrap_or_default();
+    let index =
+        pick_asset(&names, std::env::consts::OS, std::env::consts::ARCH).ok_or_else(|| {
+            anyhow!(i18n::text_with(
+                "error.update_no_installer",
+                "Release {version} has no installer for this platform",
+                &[("version", version)]
+            ))
+        })?;
+    let asset_name = names[index];
+    let checksum_url = names
+        .iter()
+        .position(|name| *name == format!("{}.sha256", asset_name) || CHECKSUM_FILES.contains(name))
+        .and_then(|i| assets[i]["browser_download_url"].as_str())
+        .map(str::to_string);
+    let signature_url = names
+        .iter()
+        .position(|name| *name == format!("{}.minisig", asset_name))
+        .and_then(|i| assets[i]["browser_download_url"].as_str())
+        .map(str::to_string);
+
+    Ok(Some(UpdateInfo {
+        version: version.trim_start_matches('v').to_string(),
+        name: release["name"].as_str().unwrap_or(version).to_string(),
+        notes: release["body"].as_str().unwrap_or_default().to_string(),
+        published_at: release["published_at"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        page_url: release["html_url"].as_str().unwrap_or_default().to_string(),
+        asset_name: asset_name.to_string(),
+        asset_url: assets[index]["browser_download_url"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        asset_size: assets[index]["size"].as_u64().unwrap_or_default(),
+        checksum_url,
+        signature_url,
+    }))
+}
+
+/// The path of the verified installer and its SHA-256
+fn download_verified(update: &UpdateInfo) -> Result<(PathBuf, String)> {
+    // The name comes from GitHub, keep it inside the download folder
+    if update.asset_name.contains(['/', '\\']) || update.asset_name.starts_with('.') {
+        return Err(anyhow!(i18n::text_with(
+            "error.update_invalid_name",
+            "Invalid installer name: {name}",
+            &[("name", &update.asset_name)]
+        )));
+    }
+    // Inside the user's own app data, where other accounts can't swap the file
+    let dir = paths::app_data_file("updates");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(&update.asset_name);
+    let _ = std::fs::remove_file(&path);
+
+    let target = path.to_string_lossy().into_owned();
+    curl(&["-o", &target, &update.asset_url])?;
+
+    match verify(update, &path).and_then(|_| sha256_of(&path)) {
+        Ok(hash) => Ok((path, hash)),
+        Err(e) => {
+            let _ = std::fs::remove_file(&path);
+            Err(e)
+        }
+    }
+}
+
+/// The checksum, when the release has one, catches a broken download; the
+/// pinned signature decides whether the installer may run
+fn verify(update: &UpdateInfo, path: &std::path::Path) -> Result<()> {
+    if let Some(url) = &update.checksum_url {
+        let sums = String::from_utf8_lossy(&curl(&[url.as_str()])?).into_owned();
+        let expected = parse_checksum(&sums, &update.asset_name).ok_or_else(|| {
+            anyhow!(i18n::text_with(
+                "error.update_no_checksum",
+                "The release lists no checksum for {name}",
+                &[("name", &update.asset_name)]
+            ))
+        })?;
+        let actual = sha256_of(path)?;
+        if !actual.eq_ignore_ascii_case(&expected) {
+            return Err(anyhow!(i18n::text_with(
+                "error.update_checksum_mismatch",
+                "Checksum mismatch for {name}: expected {expected}, got {actual}",
+                &[
+                    ("name", &update.asset_name),
+                    ("expected", &expected),
+                    ("actual", &actual)
+                ]
+            )));
+        }
+    }
+    check_publisher(update, path)
+}
+
+/// The installer must be signed with Aura's own certificate, not just any
+/// valid one
+#[cfg(target_os = "windows")]
+fn check_publisher(_update: &UpdateInfo, path: &std::path::Path) -> Result<()> {
+    let pinned = PUBLISHER_THUMBPRINT.ok_or_else(unpinned_build)?;
+    let script = format!(
+        "$s = Get-AuthenticodeSignature -LiteralPath '{}'; \
+         \"$($s.Status) $($s.SignerCertificate.Thumbprint)\"",
+        path.to_string_lossy().replace('\'', "''")
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+    signed_by(&String::from_utf8_lossy(&output.stdout), pinned)
+}
+
+/// The AppImage must match its minisign signature under Aura's public key
+#[cfg(not(target_os = "windows"))]
+fn check_publisher(update: &UpdateInfo, path: &std::path::Path) -> Result<()> {
+    let key = PUBLIC_KEY.ok_or_else(unpinned_build)?;
+    let url = update.signature_url.as_deref().ok_or_else(|| {
+        anyhow!(i18n::text_with(
+            "error.update_no_signature",
+            "The release publishes no signature for {name}",
+            &[("name", &update.asset_name)]
+        ))
+    })?;
+    let signature = path.with_file_name(format!("{}.minisig", update.asset_name));
+    curl(&["-o", &signature.to_string_lossy(), url])?;
+
+    let output = Command::new("minisign")
+        .args(["-V", "-q", "-P", key, "-m"])
+        .arg(path)
+        .arg("-x")
+        .arg(&signature)
+        .output();
+    let _ = std::fs::remove_file(&signature);
+    let output = output.map_err(|_| {
+        anyhow!(i18n::text(
+            "error.update_minisign_missing",
+            "minisign is needed to verify the update"
+        ))
+    })?;
+    if !output.status.success() {
+        return Err(anyhow!(i18n::text_with(
+            "error.update_wrong_key",
+            "{name} is not signed with Aura's key",
+            &[("name", &update.asset_name)]
+        )));
+    }
+    Ok(())
+}
+
+fn unpinned_build() -> anyhow::Error {
+    anyhow!(i18n::text(
+        "error.update_unpinned",
+        "This build of Aura cannot verify updates, download them from the release page"
+    ))
+}
+
+/// Checks the "<status> <thumbprint>" line printed for an Authenticode
+/// signature against the pinned thumbprint
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn signed_by(output: &str, pinned: &str) -> Result<()> {
+    let mut parts = output.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("Valid"), Some(thumbprint)) if thumbprint.eq_ignore_ascii_case(pinned.trim()) => {
+            Ok(())
+        }
+        (Some("Valid"), Some(thumbprint)) => Err(anyhow!(i18n::text_with(
+            "error.update_other_publisher",
+            "The installer is signed by another publisher ({thumbprint})",
+            &[("thumbprint", thumbprint)]
+        ))),
+        (Some(status), _) => Err(anyhow!(i18n::text_with(
+            "error.update_invalid_signature",
+            "The installer's signature is not valid: {status}",
+            &[("status", status)]
+        ))),
+        (None, _) => Err(anyhow!(i18n::text(
+            "error.update_unreadable_signature",
+            "The installer's signature could not be read"
+        ))),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn sha256_of(path: &std::path::Path) -> Result<String> {
+    let output = Command::new("certutil")
+        .arg("-hashfile")
+        .arg(path)
+        .arg("SHA256")
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+    parse_certutil_hash(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| hash_failed(path))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn sha256_of(path: &std::path::Path) -> Result<String> {
+    let output = Command::new("sha256sum").arg(path).output()?;
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .filter(|hash| is_sha256(hash))
+        .map(str::to_string)
+        .ok_or_else(|| hash_failed(path))
+}
+
+/// Fails when the file no longer is the one verified at download time
+fn check_unchanged(path: &std::path::Path, verified_hash: &str) -> Result<()> {
+    if !sha256_of(path)?.eq_ignore_ascii_case(verified_hash) {
+        return Err(anyhow!(i18n::text(
+            "error.update_changed",
+            "The update changed after it was verified, download it again"
+        )));
+    }
+    Ok(())
+}
+
+fn hash_failed(path: &std::path::Path) -> anyhow::Error {
+    anyhow!(i18n::text_with(
+        "error.update_hash_failed",
+        "Failed to hash {path}",
+        &[("path", &path.display().to_string())]
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn run_installer(path: &std::path::Path, verified_hash: &str) -> Result<()> {
+    check_unchanged(path, verified_hash)?;
+    // Start-Process goes through the shell, which asks for elevation when
+    // the installer needs it
+    let script = format!(
+        "Start-Process -FilePath '{}'",
+        path.to_string_lossy().replace('\'', "''")
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(i18n::text_with(
+            "error.update_installer_failed",
+            "Failed to start the installer: {error}",
+            &[("error", String::from_utf8_lossy(&output.stderr).trim())]
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn run_installer(path: &std::path::Path, verified_hash: &str) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Only an AppImage can replace itself, packages go through the package manager
+    let current = std::env::var_os("APPIMAGE")
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            anyhow!(i18n::text(
+                "error.update_package_manager",
+                "Aura was installed by a package manager, update it there"
+            ))
+        })?;
+    let staged = current.with_extension("AppImage.new");
+    std::fs::copy(path, &staged)?;
+    // Check the copy itself, so nothing can change between the check and the swap
+    if let Err(e) = check_unchanged(&staged, verified_hash) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e);
+    }
+    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    std::fs::rename(&staged, &current)?;
+    Ok(())
+}
+
+/// Runs curl on HTTPS only and returns what it printed
+fn curl(args: &[&str]) -> Result<Vec<u8>> {
+    let mut command = Command::new("curl");
+    command
+        .args(["-fsSL", "--proto", "=https", "-H", "User-Agent: Aura"])
+        .args(args);
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = command.output().map_err(|e| {
+        anyhow!(i18n::text_with(
+            "error.curl_unavailable",
+            "curl is not available: {error}",
+            &[("error", &e.to_string())]
+        ))
+    })?;
+    if !output.status.success() {
+        return Err(anyhow!(i18n::text_with(
+            "error.request_failed",
+            "Request failed: {error}",
+            &[("error", String::from_utf8_lossy(&output.stderr).trim())]
+        )));
+    }
+    Ok(output.stdout)
+}
+
+fn update_stage(stage: UpdateStage, error: Option<String>) {
+    set_status(UpdateStatus {
+        stage,
+        error,
+        ..get_status()
+    });
+}
+
+fn set_status(status: UpdateStatus) {
+    if let Ok(mut current) = STATUS.lock() {
+        *current = status;
+    }
+}
+
+fn load_config() -> UpdateConfig {
+    paths::load_json(CONFIG_FILE)
+}
+
+/// Orders release tags like v0.3.0 and 0.3.0-beta.2 by semver precedence.
+/// None when either is not a version.
+fn compare_versions(a: &str, b: &str) -> Option<CmpOrdering> {
+    let parse = |tag: &str| -> Option<(Vec<u64>, Option<String>)> {
+        let tag = tag.trim().trim_start_matches('v');
+        let tag = tag.split('+').next()?;
+        let (core, pre) = match tag.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (tag, None),
+        };
+        let numbers: Vec<u64> = core
+            .split('.')
+            .map(|n| n.parse().ok())
+            .collect::<Option<_>>()?;
+        (numbers.len() == 3).then_some((numbers, pre))
+    };
+    let (a_numbers, a_pre) = parse(a)?;
+    let (b_numbers, b_pre) = parse(b)?;
+
+    Some(
+        a_numbers
+            .cmp(&b_numbers)
+            .then_with(|| match (a_pre, b_pre) {
+                (None, None) => CmpOrdering::Equal,
+                // A release ranks above its own prereleases
+                (None, Some(_)) => CmpOrdering::Greater,
+                (Some(_), None) => CmpOrdering::Less,
+                (Some(a), Some(b)) => {
+                    let a: Vec<&str> = a.split('.').collect();
+                    let b: Vec<&str> = b.split('.').collect();
+                    for (x, y) in a.iter().zip(&b) {
+                        let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                            (Ok(x), Ok(y)) => x.cmp(&y),
+                            (Ok(_), Err(_)) => CmpOrdering::Less,
+                            (Err(_), Ok(_)) => CmpOrdering::Greater,
+                            (Err(_), Err(_)) => x.cmp(y),
+                        };
+                        if order != CmpOrdering::Equal {
+                            return order;
+                        }
+                    }
+                    a.len().cmp(&b.len())
+                }
+            }),
+    )
+}
+
+/// The installer among the release assets that fits this OS and CPU: the
+/// NSIS setup or the MSI on Windows, the AppImage on Linux
+fn pick_asset(names: &[&str], os: &str, arch: &str) -> Option<usize> {
+    let suffixes: &[&str] = match os {
+        "windows" => &["-setup.exe", ".msi"],
+        "linux" => &[".AppImage"],
+        _ => &[],
+    };
+    let arch_tags: &[&str] = match arch {
+        "x86_64" => &["x64", "amd64", "x86_64"],
+        "aarch64" => &["arm64", "aarch64"],
+        _ => &[],
+    };
+    suffixes.iter().find_map(|suffix| {
+        let matching: Vec<usize> = (0..names.len())
+            .filter(|&i| names[i].to_lowercase().ends_with(&suffix.to_lowercase()))
+            .collect();
+        matching
+            .iter()
+            .copied()
+            .find(|&i| arch_tags.iter().any(|tag| names[i].contains(tag)))
+            .or(matching.first().copied())
+    })
+}
+
+/// The hash of a file in a sha256sum listing, or the lone hash of a
+/// .sha256 file
+fn parse_checksum(content: &str, file_name: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next().filter(|hash| is_sha256(hash))?;
+        match parts.next() {
+            // "*" marks binary mode in sha256sum output
+            Some(name) if name.trim_start_matches('*') != file_name => None,
+            _ => Some(hash.to_lowercase()),
+        }
+    })
+}
+
+/// The hash line of `certutil -hashfile`, which older Windows versions print
+/// with spaces between the bytes
+#[cfg_attr(all(not(target_os = "windows"), not(test)), allow(dead_code))]
+fn parse_certutil_hash(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let hash: String = line.split_whitespace().collect();
+        is_sha256(&hash).then(|| hash.to_lowercase())
+    })
+}
+
+fn is_sha256(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(
+            compare_versions("v0.3.0", "0.2.0"),
+            Some(CmpOrdering::Greater)
+        );
+        assert_eq!(compare_versions("0.2.0", "0.2.0"), Some(CmpOrdering::Equal));
+        assert_eq!(
+            compare_versions("0.10.0", "0.9.1"),
+            Some(CmpOrdering::Greater)
+        );
+        assert_eq!(
+            compare_versions("0.3.0-beta.2", "0.3.0"),
+            Some(CmpOrdering::Less)
+        );
+        assert_eq!(
+            compare_versions("0.3.0-beta.10", "0.3.0-beta.2"),
+            Some(CmpOrdering::Greater)
+        );
+        assert_eq!(
+            compare_versions("0.3.0-rc.1", "0.3.0-beta.4"),
+            Some(CmpOrdering::Greater)
+        );
+        assert_eq!(compare_versions("nightly", "0.2.0"), None);
+    }
+
+    #[test]
+    fn test_pick_asset() {
+        let names = [
+            "Aura_0.3.0_x64_en-US.msi",
+            "Aura_0.3.0_arm64-setup.exe",
+            "Aura_0.3.0_x64-setup.exe",
+            "Aura_0.3.0_amd64.AppImage",
+            "SHA256SUMS",
+        ];
+        assert_eq!(pick_asset(&names, "windows", "x86_64"), Some(2));
+        assert_eq!(pick_asset(&names, "windows", "aarch64"), Some(1));
+        assert_eq!(pick_asset(&names, "linux", "x86_64"), Some(3));
+        assert_eq!(pick_asset(&names, "macos", "aarch64"), None);
+        assert_eq!(pick_asset(&names[..1], "windows", "aarch64"), Some(0));
+    }
+
+    #[test]
+    fn test_parse_checksums() {
+        let hash = "9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08";
+        let sums = format!(
+            "{}  Aura_0.3.0_amd64.AppImage\n{} *Aura_0.3.0_x64-setup.exe\n",
+            "0".repeat(64),
+            hash
+        );
+        assert_eq!(
+            parse_checksum(&sums, "Aura_0.3.0_x64-setup.exe"),
+            Some(hash.to_lowercase())
+        );
+        assert_eq!(parse_checksum(&sums, "other.msi"), None);
+        assert_eq!(parse_checksum(hash, "anything"), Some(hash.to_lowercase()));
+
+        let certutil = format!(
+            "SHA256 hash of C:\\Temp\\setup.exe:\r\n{}\r\nCertUtil: -hashfile command completed successfully.\r\n",
+            hash.as_bytes()
+                .chunks(2)
+                .map(|pair| String::from_utf8_lossy(pair).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        assert_eq!(parse_certutil_hash(&certutil), Some(hash.to_lowercase()));
+    }
+
+    #[test]
+    fn test_signed_by() {
+        let pinned = "A1B2C3D4E5F60718293A4B5C6D7E8F9012345678";
+        assert!(signed_by(&format!("Valid {}\r\n", pinned.to_lowercase()), pinned).is_ok());
+        assert!(signed_by("Valid 0000000000000000000000000000000000000000", pinned).is_err());
+        assert!(signed_by("NotSigned ", pinned).is_err());
+        assert!(signed_by("HashMismatch A1B2", pinned).is_err());
+        assert!(signed_by("", pinned).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+// Detects when nobody is using the PC from the time of the last keyboard or
+// mouse input, so Aura's own background work can slow down. While idle the
+// samplers only run one round in IDLE_SLOWDOWN and scans of every process are
+// skipped; the next input ends the throttle within a tick. The last input
+// time is only available on Windows, elsewhere the user is never idle.
+use crate::models::system_stats::UserIdleStatus;
+use crate::services::quiet_hours;
+use crate::shared::events;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use windows::Win32::System::SystemInformation::GetTickCount;
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+const TICK: Duration = Duration::from_millis(250);
+// Samplers run one round in this many while idle
+const IDLE_SLOWDOWN: u64 = 5;
+
+static THROTTLED: AtomicBool = AtomicBool::new(false);
+static IDLE_MS: AtomicU64 = AtomicU64::new(0);
+static MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts following user input once. Subsequent calls do nothing.
+pub fn start() {
+    if MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    // Nothing to follow without a last input time
+    if last_input_age().is_none() {
+        return;
+    }
+
+    std::thread::spawn(|| loop {
+        let idle_ms = last_input_age().unwrap_or_default();
+        IDLE_MS.store(idle_ms, Ordering::Relaxed);
+
+        let config = quiet_hours::get_config();
+        let throttled = config.throttle_when_idle && is_idle_for(idle_ms, config.idle_after_secs);
+        if THROTTLED.swap(throttled, Ordering::SeqCst) != throttled {
+            events::emit(events::USER_IDLE_CHANGED, get_status());
+        }
+        std::thread::sleep(TICK);
+    });
+}
+
+/// Whether background work should be throttled right now
+pub fn is_idle() -> bool {
+    THROTTLED.load(Ordering::Relaxed)
+}
+
+/// Whether a sampler should leave out this round of its loop, counting
+/// rounds from zero. Always false while someone uses the PC.
+pub fn skips_round(round: u64) -> bool {
+    is_idle() && !round.is_multiple_of(IDLE_SLOWDOWN)
+}
+
+pub fn get_status() -> UserIdleStatus {
+    let idle_ms = IDLE_MS.load(Ordering::Relaxed);
+    UserIdleStatus {
+        supported: last_input_age().is_some(),
+        idle: is_idle_for(idle_ms, quiet_hours::get_config().idle_after_secs),
+        idle_ms,
+        throttled: is_idle(),
+    }
+}
+
+fn is_idle_for(idle_ms: u64, idle_after_secs: u32) -> bool {
+    idle_ms >= idle_after_secs as u64 * 1000
+}
+
+/// Milliseconds since the last keyboard or mouse input of the session
+#[cfg(target_os = "windows")]
+fn last_input_age() -> Option<u64> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    // Both wrap after 49.7 days, the difference stays right
+    Some(unsafe { GetTickCount() }.wrapping_sub(info.dwTime) as u64)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn last_input_age() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_idle_for() {
+        assert!(!is_idle_for(299_999, 300));
+        assert!(is_idle_for(300_000, 300));
+        assert!(is_idle_for(0, 0));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::services::process_control::ProcessControlError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[cfg(target_os = "windows")]
+use windows::core::BOOL;
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindow, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+    IsHungAppWindow, IsIconic, IsWindowVisible, PostMessageW, SendMessageTimeoutW,
+    SetForegroundWindow, ShowWindow, GW_OWNER, SMTO_ABORTIFHUNG, SW_MINIMIZE, SW_RESTORE, WM_CLOSE,
+    WM_NULL,
+};
+
+// How long a window may take to answer WM_NULL before it is considered hung
+#[cfg(target_os = "windows")]
+const HUNG_PROBE_TIMEOUT_MS: u32 = 250;
+
+type Result<T> = std::result::Result<T, ProcessControlError>;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProcessWindow {
+    pub handle: isize,
+    pub title: String,
+    pub is_minimized: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowAction {
+    Minimize,
+    Restore,
+    BringToFront,
+    Close,
+}
+
+/// Lists the visible top-level windows owned by a process
+pub fn get_process_windows(pid: u32) -> Result<Vec<ProcessWindow>> {
+    #[cfg(target_os = "windows")]
+    {
+        let windows = enum_process_windows(pid)
+            .into_iter()
+            .map(|hwnd| unsafe {
+                ProcessWindow {
+                    handle: hwnd.0 as isize,
+                    title: get_window_title(hwnd),
+                    is_minimized: IsIconic(hwnd).as_bool(),
+                }
+            })
+            .collect();
+        Ok(windows)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = pid;
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
+/// Applies a window action to every top-level window of a process.
+/// Returns the number of windows the action was applied to.
+pub fn apply_window_action(pid: u32, action: WindowAction) -> Result<usize> {
+    #[cfg(target_os = "windows")]
+    {
+        let windows = enum_process_windows(pid);
+        if windows.is_empty() {
+            return Err(ProcessControlError::WindowError(format!(
+                "Process {} has no visible windows",
+                pid
+            )));
+        }
+
+        let mut applied = 0;
+        unsafe {
+            for hwnd in &windows {
+                let ok = match action {
+                    WindowAction::Minimize => {
+                        let _ = ShowWindow(*hwnd, SW_MINIMIZE);
+                        true
+                    }
+                    WindowAction::Restore => {
+                        let _ = ShowWindow(*hwnd, SW_RESTORE);
+                        true
+                    }
+                    WindowAction::BringToFront => {
+                        // A minimized window must be restored before it can take focus
+                        if IsIconic(*hwnd).as_bool() {
+                            let _ = ShowWindow(*hwnd, SW_RESTORE);
+                        }
+                        SetForegroundWindow(*hwnd).as_bool()
+                    }
+                    // WM_CLOSE asks the application to close gracefully, it is not a kill
+                    WindowAction::Close => {
+                        PostMessageW(Some(*hwnd), WM_CLOSE, WPARAM(0), LPARAM(0)).is_ok()
+                    }
+                };
+
+                if ok {
+                    applied += 1;
+                }
+
+                // Only the main window needs to come to the front
+                if action == WindowAction::BringToFront && ok {
+                    break;
+                }
+            }
+        }
+
+        if applied == 0 {
+            return Err(ProcessControlError::WindowError(format!(
+                "Failed to apply {:?} to the windows of process {}",
+                action, pid
+            )));
+        }
+
+        Ok(applied)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (pid, action);
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
+/// Returns the PIDs of all processes that own at least one window Windows
+/// flags as hung. Cheap enough to run on every process list refresh, as
+/// IsHungAppWindow does not send any message to the window.
+pub fn get_hung_process_ids() -> HashSet<u32> {
+    #[cfg(target_os = "windows")]
+    {
+        enum_top_level_windows(None)
+            .into_iter()
+            .filter(|(hwnd, _)| unsafe { IsHungAppWindow(*hwnd).as_bool() })
+            .map(|(_, pid)| pid)
+            .collect()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        HashSet::new()
+    }
+}
+
+/// Checks whether a single process is not responding. Besides IsHungAppWindow
+/// it probes each window with WM_NULL, which also catches windows that stopped
+/// pumping messages less than the 5 seconds IsHungAppWindow waits for.
+pub fn is_process_hung(pid: u32) -> Result<bool> {
+    #[cfg(target_os = "windows")]
+    {
+        let windows = enum_process_windows(pid);
+
+        let hung = windows.iter().any(|hwnd| unsafe {
+            if IsHungAppWindow(*hwnd).as_bool() {
+                return true;
+            }
+
+            let result = SendMessageTimeoutW(
+                *hwnd,
+                WM_NULL,
+                WPARAM(0),
+                LPARAM(0),
+                SMTO_ABORTIFHUNG,
+                HUNG_PROBE_TIMEOUT_MS,
+                None,
+            );
+            result.0 == 0
+        });
+
+        Ok(hung)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = pid;
+        Err(ProcessControlError::UnsupportedPlatform)
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct EnumContext {
+    pid: Option<u32>,
+    windows: Vec<(HWND, u32)>,
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let context = &mut *(lparam.0 as *mut EnumContext);
+
+    let mut window_pid: u32 = 0;
+    GetWindowThreadProcessId(hwnd, Some(&mut window_pid));
+
+    // Keep only visible, unowned top-level windows (skips tooltips, popups, etc.)
+    if context.pid.is_none_or(|pid| pid == window_pid)
+        && IsWindowVisible(hwnd).as_bool()
+        && GetWindow(hwnd, GW_OWNER).is_err()
+    {
+        context.windows.push((hwnd, window_pid));
+    }
+
+    BOOL(1) // Continue enumeration
+}
+
+/// Enumerates visible top-level windows with their owning PID, optionally
+/// restricted to a single process
+#[cfg(target_os = "windows")]
+fn enum_top_level_windows(pid: Option<u32>) -> Vec<(HWND, u32)> {
+    let mut context = EnumContext {
+        pid,
+        windows: Vec::new(),
+    };
+
+    unsafe {
+        let _ = EnumWindows(
+            Some(enum_windows_callback),
+            LPARAM(&mut context as *mut EnumContext as isize),
+        );
+    }
+
+    context.windows
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn enum_process_windows(pid: u32) -> Vec<HWND> {
+    enum_top_level_windows(Some(pid))
+        .into_iter()
+        .map(|(hwnd, _)| hwnd)
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn get_window_title(hwnd: HWND) -> String {
+    let length = GetWindowTextLengthW(hwnd);
+    if length <= 0 {
+        return String::new();
+    }
+
+    let mut buffer = vec![0u16; length as usize + 1];
+    let copied = GetWindowTextW(hwnd, &mut buffer);
+    String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+}
@@ -0,0 +1,190 @@
+use crate::models::optimization::WindowsUpdateState;
+use anyhow::Result;
+
+#[cfg(target_os = "windows")]
+use crate::models::readiness::ServiceState;
+#[cfg(target_os = "windows")]
+use crate::services::gaming_services;
+#[cfg(target_os = "windows")]
+use crate::shared::{dates, events::now_millis};
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+pub const DEFAULT_PAUSE_DAYS: u32 = 7;
+// Windows refuses pauses longer than five weeks
+#[cfg(target_os = "windows")]
+const MAX_PAUSE_DAYS: u32 = 35;
+
+// Where the Settings app stores the pause, read back by the update orchestrator
+#[cfg(target_os = "windows")]
+const UX_SETTINGS_KEY: &str = "HKLM\\SOFTWARE\\Microsoft\\WindowsUpdate\\UX\\Settings";
+#[cfg(target_os = "windows")]
+const PAUSE_START_VALUES: &[&str] = &[
+    "PauseUpdatesStartTime",
+    "PauseFeatureUpdatesStartTime",
+    "PauseQualityUpdatesStartTime",
+];
+#[cfg(target_os = "windows")]
+const PAUSE_END_VALUES: &[&str] = &[
+    "PauseUpdatesExpiryTime",
+    "PauseFeatureUpdatesEndTime",
+    "PauseQualityUpdatesEndTime",
+];
+#[cfg(target_os = "windows")]
+const UPDATE_SERVICES: &[&str] = &["wuauserv", "DoSvc"];
+
+/// Pauses Windows Update for `days` (1-35) and stops the update and
+/// Delivery Optimization services so no download is left running
+pub fn pause(days: u32) -> Result<WindowsUpdateState> {
+    #[cfg(target_os = "windows")]
+    {
+        let start = now_millis();
+        let end = start + u64::from(days.clamp(1, MAX_PAUSE_DAYS)) * 86_400_000;
+
+        for value in PAUSE_START_VALUES {
+            reg(&[
+                "add",
+                UX_SETTINGS_KEY,
+                "/v",
+                value,
+                "/t",
+                "REG_SZ",
+                "/d",
+                &dates::format_iso8601(start),
+                "/f",
+            ])?;
+        }
+        for value in PAUSE_END_VALUES {
+            reg(&[
+                "add",
+                UX_SETTINGS_KEY,
+                "/v",
+                value,
+                "/t",
+                "REG_SZ",
+                "/d",
+                &dates::format_iso8601(end),
+                "/f",
+            ])?;
+        }
+
+        for service in UPDATE_SERVICES {
+            // Both restart on demand, and honour the pause when they do
+            let _ = sc(&["stop", service]);
+        }
+
+        get_state()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = days;
+        anyhow::bail!("Windows Update is Windows-only")
+    }
+}
+
+/// Lifts the pause and lets the update services start again
+pub fn resume() -> Result<WindowsUpdateState> {
+    #[cfg(target_os = "windows")]
+    {
+        for value in PAUSE_START_VALUES.iter().chain(PAUSE_END_VALUES) {
+            // Deleting a value that is not there fails harmlessly
+            let _ = reg(&["delete", UX_SETTINGS_KEY, "/v", value, "/f"]);
+        }
+
+        for service in UPDATE_SERVICES {
+            let _ = sc(&["start", service]);
+        }
+
+        get_state()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        anyhow::bail!("Windows Update is Windows-only")
+    }
+}
+
+/// Reads the pause back from the registry. A pause that has expired, or was
+/// lifted from the Settings app, is reported as not paused.
+pub fn get_state() -> Result<WindowsUpdateState> {
+    #[cfg(target_os = "windows")]
+    {
+        let paused_until = reg(&["query", UX_SETTINGS_KEY, "/v", "PauseUpdatesExpiryTime"])
+            .ok()
+            .and_then(|output| parse_reg_string(&output))
+            .and_then(|value| dates::parse_iso8601(&value));
+        let paused = paused_until.is_some_and(|until| until > now_millis());
+        let delivery_optimization =
+            gaming_services::service_status("DoSvc", "Delivery Optimization");
+
+        Ok(WindowsUpdateState {
+            paused,
+            paused_until: paused_until.filter(|_| paused),
+            delivery_optimization_running: delivery_optimization.state == ServiceState::Running,
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        anyhow::bail!("Windows Update is Windows-only")
+    }
+}
+
+/// Whether a pause is in effect, for the optimization `is_applied` flag
+pub fn is_paused() -> bool {
+    get_state().map(|state| state.paused).unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<String> {
+    let output = Command::new("reg")
+        .args(args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "reg {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "windows")]
+fn sc(args: &[&str]) -> Result<()> {
+    let status = Command::new("sc")
+        .args(args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .status()?;
+    anyhow::ensure!(status.success(), "sc {} failed", args.join(" "));
+    Ok(())
+}
+
+/// Parses `    PauseUpdatesExpiryTime    REG_SZ    2024-03-22T18:30:00Z`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_reg_string(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (_, value) = line.split_once("REG_SZ")?;
+        Some(value.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reg_string() {
+        let output = "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\WindowsUpdate\\UX\\Settings\r\n    PauseUpdatesExpiryTime    REG_SZ    2024-03-22T18:30:00Z\r\n";
+        assert_eq!(
+            parse_reg_string(output).as_deref(),
+            Some("2024-03-22T18:30:00Z")
+        );
+        assert_eq!(parse_reg_string("    Flag    REG_DWORD    0x1"), None);
+    }
+}
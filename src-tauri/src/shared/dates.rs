@@ -0,0 +1,148 @@
+// Calendar helpers for the few places that read or write dates in text form
+
+const MILLIS_PER_DAY: u64 = 86_400_000;
+
+/// Days since 1970-01-01 of a Gregorian date
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Gregorian (year, month, day) of a count of days since 1970-01-01
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats Unix millis as `2024-03-15T18:30:00Z`
+pub fn format_iso8601(millis: u64) -> String {
+    let (year, month, day) = civil_from_days((millis / MILLIS_PER_DAY) as i64);
+    let seconds = (millis % MILLIS_PER_DAY) / 1000;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Parses a UTC timestamp like `2024-03-15T18:30:00Z` into Unix millis.
+/// Fractional seconds are ignored.
+pub fn parse_iso8601(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+
+    let days = days_from_civil(number(0..4)?, number(5..7)?, number(8..10)?);
+    let seconds = number(11..13)? * 3600 + number(14..16)? * 60 + number(17..19)?;
+    u64::try_from(days * 86_400 + seconds)
+        .ok()
+        .map(|s| s * 1000)
+}
+
+/// Minutes since local midnight. Platforms without a time zone lookup here
+/// use UTC.
+pub fn local_minutes_of_day() -> u32 {
+    #[cfg(target_os = "windows")]
+    {
+        let now = unsafe { windows::Win32::System::SystemInformation::GetLocalTime() };
+        now.wHour as u32 * 60 + now.wMinute as u32
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let now = unsafe { libc::time(std::ptr::null_mut()) };
+        let mut local: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&now, &mut local) }.is_null() {
+            return utc_minutes_of_day();
+        }
+        local.tm_hour as u32 * 60 + local.tm_min as u32
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        utc_minutes_of_day()
+    }
+}
+
+/// Today's local (year, month, day), UTC where no time zone lookup is
+/// available
+pub fn local_date() -> (i64, i64, i64) {
+    #[cfg(target_os = "windows")]
+    {
+        let now = unsafe { windows::Win32::System::SystemInformation::GetLocalTime() };
+        (now.wYear as i64, now.wMonth as i64, now.wDay as i64)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let now = unsafe { libc::time(std::ptr::null_mut()) };
+        let mut local: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&now, &mut local) }.is_null() {
+            return utc_date();
+        }
+        (
+            local.tm_year as i64 + 1900,
+            local.tm_mon as i64 + 1,
+            local.tm_mday as i64,
+        )
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        utc_date()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn utc_date() -> (i64, i64, i64) {
+    let millis = crate::shared::events::now_millis();
+    civil_from_days((millis / MILLIS_PER_DAY) as i64)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn utc_minutes_of_day() -> u32 {
+    let millis = crate::shared::events::now_millis();
+    ((millis % MILLIS_PER_DAY) / 60_000) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(19797), (2024, 3, 15));
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_iso8601() {
+        let millis = 1_710_527_400_000; // 2024-03-15 18:30:00 UTC
+        assert_eq!(format_iso8601(millis), "2024-03-15T18:30:00Z");
+        assert_eq!(parse_iso8601("2024-03-15T18:30:00Z"), Some(millis));
+        assert_eq!(parse_iso8601("2024-03-15T18:30:00.123Z"), Some(millis));
+        assert_eq!(parse_iso8601("not a date"), None);
+    }
+}
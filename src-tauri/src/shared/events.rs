@@ -0,0 +1,48 @@
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+// Event names pushed to the frontend
+pub const PROCESS_STATE_CHANGED: &str = "process-state-changed";
+pub const HANDLE_LEAK_DETECTED: &str = "handle-leak-detected";
+pub const GAME_CRASHED: &str = "game-crashed";
+pub const PROCESS_CREATED: &str = "process-created";
+pub const PROCESS_EXITED: &str = "process-exited";
+pub const MONITOR_HEALTH_CHANGED: &str = "monitor-health-changed";
+pub const REMOTE_STATS_UPDATED: &str = "remote-stats-updated";
+pub const MONITORING_PAUSE_CHANGED: &str = "monitoring-pause-changed";
+pub const USER_IDLE_CHANGED: &str = "user-idle-changed";
+pub const STRESS_TEST_PROGRESS: &str = "stress-test-progress";
+pub const GAME_RULES_APPLIED: &str = "game-rules-applied";
+pub const DRIVE_SPACE_LOW: &str = "drive-space-low";
+pub const FOLDER_MOVE_PROGRESS: &str = "folder-move-progress";
+pub const MAINTENANCE_COMPLETED: &str = "maintenance-completed";
+pub const UPDATE_AVAILABLE: &str = "update-available";
+pub const DATA_CAP_WARNING: &str = "data-cap-warning";
+pub const GPU_MEMORY_HOT: &str = "gpu-memory-hot";
+pub const HARDWARE_CHANGED: &str = "hardware-changed";
+pub const INPUT_LATENCY_STARTED: &str = "input-latency-started";
+
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+
+/// Stores the application handle so services can emit events without
+/// having it threaded through every call. Called once from the setup hook.
+pub fn init(app_handle: AppHandle) {
+    let _ = APP_HANDLE.set(app_handle);
+}
+
+/// Emits an event to all windows. Silently does nothing if the app handle
+/// has not been initialized yet (e.g. in tests or headless usage).
+pub fn emit<S: Serialize + Clone>(event: &str, payload: S) {
+    if let Some(app_handle) = APP_HANDLE.get() {
+        let _ = app_handle.emit(event, payload);
+    }
+}
+
+/// Current Unix timestamp in milliseconds, used for event payloads
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
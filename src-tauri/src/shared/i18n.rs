@@ -0,0 +1,154 @@
+// Message catalogs for strings produced by the backend. Code keeps the
+// English text as the default, so a missing key never shows up as a raw key.
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+// Language code and catalog, compiled into the binary
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("../../locales/en.json")),
+    ("it", include_str!("../../locales/it.json")),
+];
+
+static MESSAGES: once_cell::sync::Lazy<HashMap<&'static str, HashMap<String, String>>> =
+    once_cell::sync::Lazy::new(|| {
+        CATALOGS
+            .iter()
+            .map(|(language, content)| {
+                let messages = serde_json::from_str(content).unwrap_or_else(|e| {
+                    eprintln!("Invalid {} message catalog: {}", language, e);
+                    HashMap::new()
+                });
+                (*language, messages)
+            })
+            .collect()
+    });
+
+static LANGUAGE: RwLock<&'static str> = RwLock::new(DEFAULT_LANGUAGE);
+
+/// Languages with a catalog
+pub fn available_languages() -> Vec<String> {
+    CATALOGS
+        .iter()
+        .map(|(language, _)| language.to_string())
+        .collect()
+}
+
+/// Switches every backend message to a language. Accepts full tags like
+/// it-IT; false when there is no catalog for the language.
+pub fn set_language(language: &str) -> bool {
+    let Some(language) = find_language(language) else {
+        return false;
+    };
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = language;
+    }
+    true
+}
+
+pub fn is_supported(language: &str) -> bool {
+    find_language(language).is_some()
+}
+
+pub fn language() -> &'static str {
+    LANGUAGE
+        .read()
+        .map(|language| *language)
+        .unwrap_or(DEFAULT_LANGUAGE)
+}
+
+/// Message for a key in the current language, falling back to English and
+/// then to `default`
+pub fn text(key: &str, default: &str) -> String {
+    lookup(&MESSAGES, language(), key).unwrap_or_else(|| default.to_string())
+}
+
+/// Like `text`, replacing `{name}` placeholders with the given values
+pub fn text_with(key: &str, default: &str, args: &[(&str, &str)]) -> String {
+    fill(text(key, default), args)
+}
+
+/// Key for a free-form name, e.g. a category: "Privacy & Telemetry" becomes
+/// "category.privacy_telemetry"
+pub fn slug_key(prefix: &str, name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    format!("{}.{}", prefix, slug)
+}
+
+fn find_language(language: &str) -> Option<&'static str> {
+    let primary = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    CATALOGS
+        .iter()
+        .map(|(code, _)| *code)
+        .find(|code| *code == primary)
+}
+
+fn lookup(
+    messages: &HashMap<&'static str, HashMap<String, String>>,
+    language: &str,
+    key: &str,
+) -> Option<String> {
+    [language, DEFAULT_LANGUAGE]
+        .iter()
+        .find_map(|language| messages.get(language)?.get(key))
+        .cloned()
+}
+
+fn fill(mut message: String, args: &[(&str, &str)]) -> String {
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs_parse_and_match() {
+        let english = &MESSAGES["en"];
+        assert!(!english.is_empty());
+        for (language, messages) in MESSAGES.iter() {
+            assert!(!messages.is_empty(), "{} catalog is empty", language);
+        }
+        // Every English message has a translation
+        for key in english.keys() {
+            assert!(MESSAGES["it"].contains_key(key), "it is missing {}", key);
+        }
+    }
+
+    #[test]
+    fn test_lookup_falls_back_to_english() {
+        let mut messages = HashMap::new();
+        messages.insert("en", HashMap::from([("a".to_string(), "A".to_string())]));
+        messages.insert("it", HashMap::new());
+        assert_eq!(lookup(&messages, "it", "a"), Some("A".to_string()));
+        assert_eq!(lookup(&messages, "it", "b"), None);
+    }
+
+    #[test]
+    fn test_find_language_and_slug() {
+        assert_eq!(find_language("it-IT"), Some("it"));
+        assert_eq!(find_language("EN"), Some("en"));
+        assert_eq!(find_language("xx"), None);
+        assert_eq!(
+            slug_key("category", "Privacy & Telemetry"),
+            "category.privacy_telemetry"
+        );
+        assert_eq!(
+            fill("{a} and {b}".to_string(), &[("a", "1"), ("b", "2")]),
+            "1 and 2"
+        );
+    }
+}
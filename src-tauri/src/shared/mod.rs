@@ -1 +1,6 @@
+pub mod dates;
+pub mod events;
+pub mod i18n;
+pub mod paths;
+pub mod registry;
 pub mod system;
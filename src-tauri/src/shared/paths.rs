@@ -0,0 +1,49 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+
+// Same identifier as tauri.conf.json, so files end up next to Tauri's own app data
+const APP_IDENTIFIER: &str = "com.aura.app";
+
+/// Directory where Aura persists its own data (profiles, settings, history).
+/// Mirrors Tauri's `app_data_dir` so it can be used without an AppHandle.
+pub fn app_data_dir() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(target_os = "macos")]
+    let base = std::env::var_os("HOME").map(|home| {
+        PathBuf::from(home)
+            .join("Library")
+            .join("Application Support")
+    });
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+
+    let dir = base.unwrap_or_else(std::env::temp_dir).join(APP_IDENTIFIER);
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Path of a file inside the app data directory
+pub fn app_data_file(name: &str) -> PathBuf {
+    app_data_dir().join(name)
+}
+
+/// Reads a JSON file of the app data directory, the default when it is
+/// missing or unreadable
+pub fn load_json<T: DeserializeOwned + Default>(name: &str) -> T {
+    std::fs::read_to_string(app_data_file(name))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Writes a value as pretty JSON into the app data directory
+pub fn save_json<T: Serialize + ?Sized>(name: &str, value: &T) -> std::io::Result<()> {
+    let content = serde_json::to_string_pretty(value)?;
+    std::fs::write(app_data_file(name), content)
+}
@@ -0,0 +1,194 @@
+// Thin wrappers over reg.exe for the services that read and write DWORD flags
+#[cfg(target_os = "windows")]
+use anyhow::Result;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+/// Reads a DWORD flag, `default` when the value is not set. None when the
+/// registry could not be queried at all.
+#[cfg(target_os = "windows")]
+pub fn flag(key: &str, value: &str, default: bool) -> Option<bool> {
+    let output = query(key, value)?;
+    if !output.status.success() {
+        // The value or the whole key does not exist yet
+        return Some(default);
+    }
+    parse_dword(&String::from_utf8_lossy(&output.stdout)).map(|v| v != 0)
+}
+
+/// Reads a DWORD, None when it is not set
+#[cfg(target_os = "windows")]
+pub fn read_dword(key: &str, value: &str) -> Option<u32> {
+    let output = query(key, value)?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_dword(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reads a REG_SZ, None when it is not set
+#[cfg(target_os = "windows")]
+pub fn read_string(key: &str, value: &str) -> Option<String> {
+    let output = query(key, value)?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_string(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reads a REG_BINARY, None when it is not set
+#[cfg(target_os = "windows")]
+pub fn read_binary(key: &str, value: &str) -> Option<Vec<u8>> {
+    let output = query(key, value)?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_binary(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "windows")]
+pub fn value_exists(key: &str, value: &str) -> bool {
+    query(key, value).is_some_and(|output| output.status.success())
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_dword(key: &str, value: &str, data: u32) -> Result<()> {
+    reg(&[
+        "add",
+        key,
+        "/v",
+        value,
+        "/t",
+        "REG_DWORD",
+        "/d",
+        &data.to_string(),
+        "/f",
+    ])
+}
+
+/// Creates a key, with an empty default value when it did not exist
+#[cfg(target_os = "windows")]
+pub fn create_key(key: &str) -> Result<()> {
+    reg(&["add", key, "/ve", "/f"])
+}
+
+#[cfg(target_os = "windows")]
+pub fn key_exists(key: &str) -> bool {
+    Command::new("reg")
+        .args(["query", key])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Deletes a key and everything under it, succeeding when it does not exist
+#[cfg(target_os = "windows")]
+pub fn delete_key(key: &str) -> Result<()> {
+    if !key_exists(key) {
+        return Ok(());
+    }
+    reg(&["delete", key, "/f"])
+}
+
+/// Deletes a value, succeeding when it was never set
+#[cfg(target_os = "windows")]
+pub fn delete_value(key: &str, value: &str) -> Result<()> {
+    if !value_exists(key, value) {
+        return Ok(());
+    }
+    reg(&["delete", key, "/v", value, "/f"])
+}
+
+#[cfg(target_os = "windows")]
+fn query(key: &str, value: &str) -> Option<std::process::Output> {
+    Command::new("reg")
+        .args(["query", key, "/v", value])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()
+}
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<()> {
+    let output = Command::new("reg")
+        .args(args)
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to update {}: {}",
+            args.get(1).unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Parses `    GameDVR_Enabled    REG_DWORD    0x0` from `reg query`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn parse_dword(output: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let (_, value) = line.split_once("REG_DWORD")?;
+        u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+    })
+}
+
+/// Parses `    DisplayVersion    REG_SZ    23H2` from `reg query`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn parse_string(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (_, value) = line.split_once("    REG_SZ    ")?;
+        Some(value.trim().to_string())
+    })
+}
+
+/// Parses `    EDID    REG_BINARY    00FFFFFF` from `reg query`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn parse_binary(output: &str) -> Option<Vec<u8>> {
+    output.lines().find_map(|line| {
+        let (_, value) = line.split_once("    REG_BINARY    ")?;
+        let hex = value.trim();
+        if hex.len() % 2 != 0 {
+            return None;
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dword() {
+        let output = "\r\nHKEY_CURRENT_USER\\System\\GameConfigStore\r\n    GameDVR_Enabled    REG_DWORD    0x1\r\n\r\n";
+        assert_eq!(parse_dword(output), Some(1));
+        assert_eq!(parse_dword("    SteamPath    REG_SZ    c:/steam"), None);
+    }
+
+    #[test]
+    fn test_parse_string() {
+        let output = "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion\r\n    EditionID    REG_SZ    Professional\r\n\r\n";
+        assert_eq!(parse_string(output).as_deref(), Some("Professional"));
+        assert_eq!(parse_string("    UBR    REG_DWORD    0x10dd"), None);
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        let output = "\r\nHKEY_LOCAL_MACHINE\\SYSTEM\\CurrentControlSet\\Enum\\DISPLAY\\GSM5BBF\\5&1&UID4352\\Device Parameters\r\n    EDID    REG_BINARY    00FFFFFFFFFFFF001E6D\r\n\r\n";
+        assert_eq!(
+            parse_binary(output),
+            Some(vec![
+                0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x1E, 0x6D
+            ])
+        );
+        assert_eq!(parse_binary("    EDID    REG_BINARY    0FF"), None);
+        assert_eq!(parse_binary("    UBR    REG_DWORD    0x10dd"), None);
+    }
+}
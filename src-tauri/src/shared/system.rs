@@ -21,3 +21,83 @@ pub fn refresh_and_lock() -> Result<std::sync::MutexGuard<'static, System>, Stri
     system.refresh_all();
     Ok(system)
 }
+
+/// Process names compared case-insensitively, with or without the .exe
+/// extension
+pub fn same_process_name(name: &str, wanted: &str) -> bool {
+    strip_exe(name) == strip_exe(wanted)
+}
+
+/// Like `same_process_name`, with `*` matching any run of characters and
+/// `?` a single one
+pub fn matches_process_pattern(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = strip_exe(name).chars().collect();
+    let pattern: Vec<char> = strip_exe(pattern).chars().collect();
+
+    // Greedy matching, backtracking to the last `*` on a mismatch
+    let (mut n, mut p) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                n += 1;
+                p += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn strip_exe(value: &str) -> String {
+    let lower = value.to_lowercase();
+    lower
+        .strip_suffix(".exe")
+        .map(str::to_string)
+        .unwrap_or(lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_process_name() {
+        assert!(same_process_name(
+            "Ableton Live 12 Suite.exe",
+            "ableton live 12 suite"
+        ));
+        assert!(same_process_name("3DMark.exe", "3dmark.exe"));
+        assert!(!same_process_name("3DMarkCmd.exe", "3dmark"));
+    }
+
+    #[test]
+    fn test_matches_process_pattern() {
+        assert!(matches_process_pattern(
+            "ShaderCompileWorker.exe",
+            "shadercompile*"
+        ));
+        assert!(matches_process_pattern("EasyAntiCheat.exe", "*anticheat"));
+        assert!(matches_process_pattern(
+            "Game-Win64-Shipping.exe",
+            "game-*-shipping.exe"
+        ));
+        assert!(matches_process_pattern("worker1.exe", "worker?"));
+        assert!(matches_process_pattern("Launcher.exe", "launcher"));
+        assert!(matches_process_pattern("anything.exe", "*"));
+        assert!(!matches_process_pattern("worker12.exe", "worker?"));
+        assert!(!matches_process_pattern("GameLauncher.exe", "launcher*"));
+        assert!(!matches_process_pattern("a.exe", ""));
+    }
+}
@@ -1,44 +1,146 @@
-const KB: f64 = 1024.0;
-const MB: f64 = KB * 1024.0;
-const GB: f64 = MB * 1024.0;
-const TB: f64 = GB * 1024.0;
+use super::locale::format_number;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Sistema di unità usato per mostrare le dimensioni
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteUnits {
+    /// Multipli di 1024 con simboli KB/MB/GB, come Esplora file di Windows
+    #[default]
+    Jedec,
+    /// Multipli di 1024 con simboli KiB/MiB/GiB
+    Iec,
+    /// Multipli di 1000 con simboli KB/MB/GB, come i produttori di dischi
+    Si,
+}
+
+impl ByteUnits {
+    fn base(self) -> f64 {
+        match self {
+            ByteUnits::Jedec | ByteUnits::Iec => 1024.0,
+            ByteUnits::Si => 1000.0,
+        }
+    }
+
+    fn symbol(self, power: u32) -> &'static str {
+        const JEDEC: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+        const IEC: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let symbols = match self {
+            ByteUnits::Iec => IEC,
+            ByteUnits::Jedec | ByteUnits::Si => JEDEC,
+        };
+        symbols[(power as usize).min(MAX_POWER as usize)]
+    }
+}
+
+/// Potenza della base corrispondente ai TB
+pub const MAX_POWER: u32 = 4;
+
+static BYTE_UNITS: RwLock<ByteUnits> = RwLock::new(ByteUnits::Jedec);
+
+/// Imposta le unità rispettate da tutte le funzioni di formattazione
+pub fn set_byte_units(units: ByteUnits) {
+    if let Ok(mut current) = BYTE_UNITS.write() {
+        *current = units;
+    }
+}
+
+/// Unità correnti
+pub fn byte_units() -> ByteUnits {
+    BYTE_UNITS.read().map(|units| *units).unwrap_or_default()
+}
+
+/// Cifre decimali di `format_bytes`. I bytes sotto il KB sono sempre interi.
+pub const DEFAULT_PRECISION: usize = 2;
 
 /// Formatta un numero di bytes in una stringa leggibile
+///
+/// # Arguments
+/// * `bytes` - Il numero di bytes
+///
+/// # Returns
+/// Una stringa come "1.46 KB", con `DEFAULT_PRECISION` decimali
 pub fn format_bytes(bytes: u64) -> String {
-    let bytes = bytes as f64;
-    match bytes {
-        b if b >= TB => format!("{:.1} TB", b / TB),
-        b if b >= GB => {
-            let gb_value = b / GB;
-            if gb_value >= 1000.0 {
-                format!("{:.1} TB", gb_value / 1000.0)
-            } else {
-                format!("{:.0} GB", gb_value)
-            }
-        }
-        b if b >= MB => {
-            let mb_value = b / MB;
-            if mb_value >= 1000.0 {
-                format!("{:.0} GB", mb_value / 1000.0)
-            } else {
-                format!("{:.0} MB", mb_value)
-            }
-        }
-        b if b >= KB => {
-            let kb_value = b / KB;
-            if kb_value >= 1000.0 {
-                format!("{:.0} MB", kb_value / 1000.0)
-            } else {
-                format!("{:.0} KB", kb_value)
-            }
-        }
-        b => format!("{:.0} B", b),
+    format_bytes_with_precision(bytes, DEFAULT_PRECISION)
+}
+
+/// Formatta un numero di bytes con un numero di decimali scelto
+///
+/// L'unità è la più grande in cui il valore è almeno 1, fino ai TB. Se
+/// l'arrotondamento porta il valore alla base (1024.00 KB) si passa
+/// all'unità successiva (1.00 MB).
+///
+/// # Arguments
+/// * `bytes` - Il numero di bytes
+/// * `decimals` - Le cifre decimali da mostrare sopra il KB
+pub fn format_bytes_with_precision(bytes: u64, decimals: usize) -> String {
+    format_bytes_with(byte_units(), bytes, decimals)
+}
+
+fn format_bytes_with(units: ByteUnits, bytes: u64, decimals: usize) -> String {
+    let base = units.base();
+    let power = power_for(units, bytes, 0, MAX_POWER);
+    if power == 0 {
+        return format!("{} {}", format_number(bytes as f64, 0), units.symbol(0));
+    }
+
+    let mut power = power;
+    let mut value = bytes as f64 / base.powi(power as i32);
+    let scale = 10f64.powi(decimals as i32);
+    if power < MAX_POWER && (value * scale).round() / scale >= base {
+        power += 1;
+        value /= base;
     }
+    format!("{} {}", format_number(value, decimals), units.symbol(power))
+}
+
+/// Formatta un numero di bytes in un'unità scelta dal chiamante
+///
+/// # Arguments
+/// * `bytes` - Il numero di bytes
+/// * `power` - La potenza della base da usare (1 = KB, 3 = GB)
+/// * `decimals` - Le cifre decimali da mostrare
+///
+/// # Returns
+/// Una stringa come "15.9 GB", nelle unità correnti
+pub fn format_bytes_in(bytes: u64, power: u32, decimals: usize) -> String {
+    let units = byte_units();
+    let power = power.min(MAX_POWER);
+    format!(
+        "{} {}",
+        format_number(bytes as f64 / units.base().powi(power as i32), decimals),
+        units.symbol(power)
+    )
+}
+
+/// Potenza più grande, entro i limiti dati, per cui il valore è almeno 1
+///
+/// # Arguments
+/// * `bytes` - Il numero di bytes
+/// * `min_power` - La potenza minima (ad esempio 1 per non scendere sotto i KB)
+/// * `max_power` - La potenza massima
+pub fn unit_power(bytes: u64, min_power: u32, max_power: u32) -> u32 {
+    power_for(byte_units(), bytes, min_power, max_power)
+}
+
+fn power_for(units: ByteUnits, bytes: u64, min_power: u32, max_power: u32) -> u32 {
+    let base = units.base();
+    let mut power = min_power;
+    while power < max_power.min(MAX_POWER) && bytes as f64 >= base.powi(power as i32 + 1) {
+        power += 1;
+    }
+    power
 }
 
 /// Formatta una velocità in bytes/secondo
 pub fn format_bytes_per_second(bytes: u64) -> String {
-    format!("{}/s", format_bytes(bytes))
+    format_bytes_per_second_with_precision(bytes, DEFAULT_PRECISION)
+}
+
+/// Formatta una velocità in bytes/secondo con un numero di decimali scelto
+pub fn format_bytes_per_second_with_precision(bytes: u64, decimals: usize) -> String {
+    format!("{}/s", format_bytes_with_precision(bytes, decimals))
 }
 
 #[cfg(test)]
@@ -53,6 +155,33 @@ mod tests {
         assert_eq!(format_bytes(1500000000), "1.40 GB");
     }
 
+    #[test]
+    fn test_format_bytes_with_units() {
+        assert_eq!(format_bytes_with(ByteUnits::Iec, 2048, 2), "2.00 KiB");
+        assert_eq!(format_bytes_with(ByteUnits::Si, 2000, 2), "2.00 KB");
+        assert_eq!(format_bytes_with(ByteUnits::Si, 999, 2), "999 B");
+    }
+
+    #[test]
+    fn test_format_bytes_boundaries() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1,023 B");
+        assert_eq!(format_bytes(1024), "1.00 KB");
+        assert_eq!(format_bytes(1024 * 1024 - 1), "1.00 MB");
+        assert_eq!(format_bytes(1024 * 1024 * 1024), "1.00 GB");
+        assert_eq!(format_bytes(1024u64.pow(4)), "1.00 TB");
+        assert_eq!(format_bytes(2048 * 1024u64.pow(4)), "2,048.00 TB");
+    }
+
+    #[test]
+    fn test_format_bytes_with_precision() {
+        assert_eq!(format_bytes_with_precision(1500, 0), "1 KB");
+        assert_eq!(format_bytes_with_precision(1500, 1), "1.5 KB");
+        assert_eq!(format_bytes_with_precision(1500, 3), "1.465 KB");
+        assert_eq!(format_bytes_with_precision(500, 3), "500 B");
+        assert_eq!(format_bytes_per_second_with_precision(1536, 1), "1.5 KB/s");
+    }
+
     #[test]
     fn test_format_bytes_per_second() {
         assert_eq!(format_bytes_per_second(1024), "1.00 KB/s");
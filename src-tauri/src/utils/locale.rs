@@ -0,0 +1,125 @@
+use std::sync::RwLock;
+
+/// Locale usata finché l'utente non ne sceglie un'altra
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Separatori usati per formattare i numeri in una locale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    pub group_separator: char,
+}
+
+const EN_FORMAT: NumberFormat = NumberFormat {
+    decimal_separator: '.',
+    group_separator: ',',
+};
+
+static CURRENT: RwLock<NumberFormat> = RwLock::new(EN_FORMAT);
+
+/// Ricava i separatori da un tag di lingua
+///
+/// # Arguments
+/// * `locale` - Tag BCP 47 come "it-IT", anche con underscore ("de_DE")
+///
+/// # Returns
+/// Il formato della lingua, quello inglese se la lingua non è nota
+pub fn number_format(locale: &str) -> NumberFormat {
+    let language = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match language.as_str() {
+        "de" | "it" | "es" | "pt" | "nl" | "id" | "tr" | "da" | "el" | "ro" => NumberFormat {
+            decimal_separator: ',',
+            group_separator: '.',
+        },
+        "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => {
+            NumberFormat {
+                decimal_separator: ',',
+                group_separator: '\u{a0}',
+            }
+        }
+        _ => EN_FORMAT,
+    }
+}
+
+/// Imposta la locale rispettata da tutte le funzioni di formattazione
+pub fn set_locale(locale: &str) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = number_format(locale);
+    }
+}
+
+/// Formato della locale corrente
+pub fn current_format() -> NumberFormat {
+    CURRENT.read().map(|current| *current).unwrap_or(EN_FORMAT)
+}
+
+/// Formatta un numero con la locale corrente
+///
+/// # Arguments
+/// * `value` - Il numero da formattare
+/// * `decimals` - Le cifre decimali da mostrare
+///
+/// # Returns
+/// Il numero con separatori decimali e delle migliaia della locale
+pub fn format_number(value: f64, decimals: usize) -> String {
+    format_number_with(current_format(), value, decimals)
+}
+
+/// Formatta un numero con un formato esplicito
+pub fn format_number_with(format: NumberFormat, value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value);
+    let (sign, digits) = match formatted.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", formatted.as_str()),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+    let mut grouped = String::with_capacity(integer.len() + integer.len() / 3);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(format.group_separator);
+        }
+        grouped.push(digit);
+    }
+
+    if fraction.is_empty() {
+        format!("{}{}", sign, grouped)
+    } else {
+        format!(
+            "{}{}{}{}",
+            sign, grouped, format.decimal_separator, fraction
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_format() {
+        assert_eq!(number_format("en-US"), EN_FORMAT);
+        assert_eq!(number_format("it_IT").decimal_separator, ',');
+        assert_eq!(number_format("fr").group_separator, '\u{a0}');
+        assert_eq!(number_format(""), EN_FORMAT);
+    }
+
+    #[test]
+    fn test_format_number_with() {
+        assert_eq!(
+            format_number_with(EN_FORMAT, 1234567.891, 2),
+            "1,234,567.89"
+        );
+        assert_eq!(format_number_with(EN_FORMAT, 999.0, 0), "999");
+        assert_eq!(format_number_with(EN_FORMAT, -1500.5, 1), "-1,500.5");
+        assert_eq!(
+            format_number_with(number_format("it-IT"), 1234.5, 2),
+            "1.234,50"
+        );
+    }
+}
@@ -1,6 +1,8 @@
 pub mod bytes;
 pub mod loaded_module;
+pub mod locale;
 pub mod system;
+pub mod temperature;
 pub mod time;
 
 pub use bytes::{format_bytes, format_bytes_per_second};
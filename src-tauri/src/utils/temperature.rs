@@ -0,0 +1,96 @@
+use super::locale::format_number;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Scala usata per mostrare le temperature
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+
+    fn convert_celsius(self, celsius: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    /// Riporta in gradi Celsius una temperatura espressa in questa scala
+    pub fn to_celsius(self, value: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => value,
+            TemperatureUnit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+        }
+    }
+}
+
+static TEMPERATURE_UNIT: RwLock<TemperatureUnit> = RwLock::new(TemperatureUnit::Celsius);
+
+/// Imposta la scala rispettata da tutte le funzioni sulle temperature
+pub fn set_temperature_unit(unit: TemperatureUnit) {
+    if let Ok(mut current) = TEMPERATURE_UNIT.write() {
+        *current = unit;
+    }
+}
+
+/// Scala corrente
+pub fn temperature_unit() -> TemperatureUnit {
+    TEMPERATURE_UNIT
+        .read()
+        .map(|unit| *unit)
+        .unwrap_or_default()
+}
+
+/// Converte una temperatura letta dai sensori nella scala corrente
+///
+/// # Arguments
+/// * `celsius` - La temperatura in gradi Celsius
+pub fn convert_temperature(celsius: f32) -> f32 {
+    temperature_unit().convert_celsius(celsius)
+}
+
+/// Formatta una temperatura nella scala corrente
+///
+/// # Arguments
+/// * `celsius` - La temperatura in gradi Celsius
+///
+/// # Returns
+/// Una stringa come "45.0°C" o "113.0°F"
+pub fn format_temperature(celsius: f32) -> String {
+    let unit = temperature_unit();
+    format!(
+        "{}{}",
+        format_number(unit.convert_celsius(celsius) as f64, 1),
+        unit.symbol()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_celsius() {
+        assert_eq!(TemperatureUnit::Celsius.convert_celsius(45.0), 45.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert_celsius(100.0), 212.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert_celsius(-40.0), -40.0);
+    }
+
+    #[test]
+    fn test_to_celsius() {
+        assert_eq!(TemperatureUnit::Celsius.to_celsius(45.0), 45.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.to_celsius(212.0), 100.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.to_celsius(-40.0), -40.0);
+    }
+}
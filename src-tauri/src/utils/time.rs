@@ -1,3 +1,4 @@
+use super::locale::format_number;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -97,7 +98,7 @@ pub fn format_milliseconds(ms: u64) -> String {
     if ms < 1000 {
         format!("{}ms", ms)
     } else if ms < 60000 {
-        format!("{:.1}s", ms as f64 / 1000.0)
+        format!("{}s", format_number(ms as f64 / 1000.0, 1))
     } else {
         format_duration(Duration::from_millis(ms))
     }